@@ -0,0 +1,59 @@
+use bevy::app::App;
+use bevy::ecs::component::Component;
+use bevy::ecs::event::EventReader;
+use bevy::ecs::query::Changed;
+use bevy::ecs::system::Query;
+use bevy::log::LogPlugin;
+use bevy_dojo::torii::{SystemExecuted, ToriiComponent, ToriiEntity, ToriiPlugin, Url};
+use starknet::core::types::FieldElement;
+
+#[derive(Component, Debug)]
+struct Position {
+    x: u64,
+    y: u64,
+}
+
+impl ToriiComponent for Position {
+    const NAME: &'static str = "Position";
+
+    fn from_values(values: &[FieldElement]) -> Option<Self> {
+        match values {
+            [x, y] => Some(Self { x: (*x).try_into().ok()?, y: (*y).try_into().ok()? }),
+            _ => None,
+        }
+    }
+}
+
+fn main() {
+    let url = Url::parse("http://localhost:8080").unwrap();
+    let world = FieldElement::from_hex_be(
+        "0x26065106fa319c3981618e7e20f9b7d5e4bd1e1b55d4b9a5a6f6e1e4d5fbb68",
+    )
+    .unwrap();
+
+    App::new()
+        .set_runner(runner)
+        .add_plugin(LogPlugin::default())
+        .add_plugin(ToriiPlugin::new(url, world).with_component::<Position>())
+        .add_system(log_positions)
+        .add_system(log_executions)
+        .run();
+}
+
+fn runner(mut app: App) {
+    loop {
+        app.update();
+    }
+}
+
+fn log_positions(query: Query<'_, '_, (&ToriiEntity, &Position), Changed<Position>>) {
+    for (entity, position) in &query {
+        println!("{}: ({}, {})", entity.0, position.x, position.y);
+    }
+}
+
+fn log_executions(mut events: EventReader<'_, '_, SystemExecuted>) {
+    for event in events.iter() {
+        println!("{}: {:?}", event.system, event.result);
+    }
+}
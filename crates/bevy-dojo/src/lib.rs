@@ -1,4 +1,6 @@
 mod plugins;
+#[cfg(feature = "torii")]
+pub mod torii;
 
 pub use plugins::*;
 
@@ -0,0 +1,299 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bevy::app::{App, Plugin};
+use bevy::ecs::component::Component;
+use bevy::ecs::entity::Entity;
+use bevy::ecs::event::EventReader;
+use bevy::ecs::system::{Res, ResMut, Resource};
+use bevy::ecs::world::World;
+use bevy::log;
+use bevy_tokio_tasks::{TokioTasksPlugin, TokioTasksRuntime};
+use starknet::accounts::{Account, Call, SingleOwnerAccount};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet::providers::Provider;
+use starknet::signers::{LocalWallet, SigningKey};
+use torii_client::{Client, EntityStateUpdate};
+pub use url::Url;
+
+/// Delay before subscribing again after the connection to torii is lost.
+const RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// A Bevy component mirroring a component of the world.
+pub trait ToriiComponent: Component + Sized {
+    /// Name of the component in the world.
+    const NAME: &'static str;
+
+    /// Decodes the component from the values indexed by torii, `None` if they're invalid.
+    fn from_values(values: &[FieldElement]) -> Option<Self>;
+}
+
+/// Marks the Bevy entities mirroring the world's, with the id torii indexed the entity under.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct ToriiEntity(pub String);
+
+/// Bevy entities mirroring the world's, by torii id.
+#[derive(Resource, Default)]
+pub struct ToriiEntities {
+    pub entities: HashMap<String, Entity>,
+    /// Id of the last update applied, subscriptions resume from it.
+    pub cursor: Option<String>,
+}
+
+/// Account executing the world's systems on behalf of the player.
+#[derive(Clone)]
+pub struct AccountConfig {
+    pub rpc_url: Url,
+    pub address: FieldElement,
+    pub private_key: FieldElement,
+}
+
+/// Executes a system of the world through the configured account.
+pub struct ExecuteSystem {
+    /// Name of the system, without the `System` suffix.
+    pub system: String,
+    pub calldata: Vec<FieldElement>,
+}
+
+/// Outcome of an [`ExecuteSystem`], the hash of the transaction sent or the reason it couldn't
+/// be.
+pub struct SystemExecuted {
+    pub system: String,
+    pub result: Result<FieldElement, String>,
+}
+
+/// How a component of the world is mirrored on the Bevy entities.
+#[derive(Clone, Copy)]
+struct MirroredComponent {
+    type_id: TypeId,
+    insert: fn(&mut World, Entity, &[FieldElement]),
+    remove: fn(&mut World, Entity),
+}
+
+/// Syncs the world's entities indexed by torii into Bevy, mirroring every registered component
+/// as a Bevy component of the entity, and executes systems sent as [`ExecuteSystem`] events.
+pub struct ToriiPlugin {
+    url: Url,
+    world: FieldElement,
+    account: Option<AccountConfig>,
+    components: HashMap<String, MirroredComponent>,
+}
+
+#[derive(Resource)]
+struct ToriiConfig {
+    url: Url,
+    world: FieldElement,
+    account: Option<AccountConfig>,
+}
+
+#[derive(Resource)]
+struct ToriiComponents(HashMap<String, MirroredComponent>);
+
+#[derive(Resource)]
+struct WorldAccount(Arc<SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>>);
+
+impl ToriiPlugin {
+    /// Syncs the world at address `world` from the torii server at `url`.
+    pub fn new(url: Url, world: FieldElement) -> Self {
+        Self { url, world, account: None, components: HashMap::new() }
+    }
+
+    /// Mirrors the world's component `C` on the synced entities, entities left without any
+    /// mirrored component are despawned.
+    pub fn with_component<C: ToriiComponent>(mut self) -> Self {
+        self.components.insert(
+            C::NAME.to_string(),
+            MirroredComponent {
+                type_id: TypeId::of::<C>(),
+                insert: insert_component::<C>,
+                remove: remove_component::<C>,
+            },
+        );
+        self
+    }
+
+    /// Executes the systems through this account, without it [`ExecuteSystem`] events fail.
+    pub fn with_account(mut self, account: AccountConfig) -> Self {
+        self.account = Some(account);
+        self
+    }
+}
+
+impl Plugin for ToriiPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<TokioTasksPlugin>() {
+            app.add_plugin(TokioTasksPlugin::default());
+        }
+
+        app.insert_resource(ToriiConfig {
+            url: self.url.clone(),
+            world: self.world,
+            account: self.account.clone(),
+        })
+        .insert_resource(ToriiComponents(self.components.clone()))
+        .init_resource::<ToriiEntities>()
+        .add_event::<ExecuteSystem>()
+        .add_event::<SystemExecuted>()
+        .add_startup_system(setup)
+        .add_system(execute_systems);
+    }
+}
+
+fn insert_component<C: ToriiComponent>(world: &mut World, entity: Entity, values: &[FieldElement]) {
+    match C::from_values(values) {
+        Some(component) => {
+            world.entity_mut(entity).insert(component);
+        }
+        None => log::warn!("Invalid values for component {}", C::NAME),
+    }
+}
+
+fn remove_component<C: ToriiComponent>(world: &mut World, entity: Entity) {
+    world.entity_mut(entity).remove::<C>();
+}
+
+fn setup(runtime: ResMut<'_, TokioTasksRuntime>, config: Res<'_, ToriiConfig>) {
+    let client = Client::new(config.url.clone());
+
+    runtime.spawn_background_task(|mut ctx| async move {
+        let mut cursor = None;
+        loop {
+            match client.subscribe_entity_state_updates(cursor.as_deref()).await {
+                Ok(mut subscription) => {
+                    while let Some(update) = subscription.next().await {
+                        let update = match update {
+                            Ok(update) => update,
+                            Err(e) => {
+                                log::error!("Torii subscription failed: {e}");
+                                break;
+                            }
+                        };
+                        cursor = Some(update.id.clone());
+                        ctx.run_on_main_thread(move |ctx| apply_update(ctx.world, update)).await;
+                    }
+                }
+                Err(e) => log::error!("Failed to subscribe to torii: {e}"),
+            }
+
+            // Resume from the last update applied, the updates indexed meanwhile are replayed.
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    });
+
+    if let Some(account) = config.account.clone() {
+        runtime.spawn_background_task(|mut ctx| async move {
+            let provider = JsonRpcClient::new(HttpTransport::new(account.rpc_url));
+            let chain_id = match provider.chain_id().await {
+                Ok(chain_id) => chain_id,
+                Err(e) => {
+                    log::error!("Failed to set up the account: {e}");
+                    return;
+                }
+            };
+            let signer =
+                LocalWallet::from_signing_key(SigningKey::from_secret_scalar(account.private_key));
+            let account = SingleOwnerAccount::new(provider, signer, account.address, chain_id);
+
+            ctx.run_on_main_thread(move |ctx| {
+                ctx.world.insert_resource(WorldAccount(Arc::new(account)));
+            })
+            .await;
+        });
+    }
+}
+
+fn apply_update(world: &mut World, update: EntityStateUpdate) {
+    world.resource_mut::<ToriiEntities>().cursor = Some(update.id.clone());
+
+    let Some(component) =
+        world.resource::<ToriiComponents>().0.get(&update.component_name).copied()
+    else {
+        return;
+    };
+
+    if update.deleted {
+        let entities = &world.resource::<ToriiEntities>().entities;
+        let Some(entity) = entities.get(&update.entity_id).copied() else {
+            return;
+        };
+        (component.remove)(world, entity);
+
+        // The entity is despawned once it has no component left in the world.
+        let entity_ref = world.entity(entity);
+        let mut mirrored = world.resource::<ToriiComponents>().0.values();
+        if !mirrored.any(|c| entity_ref.contains_type_id(c.type_id)) {
+            world.despawn(entity);
+            world.resource_mut::<ToriiEntities>().entities.remove(&update.entity_id);
+        }
+        return;
+    }
+
+    let Some(values) = update.values() else {
+        log::warn!("Invalid values for component {}", update.component_name);
+        return;
+    };
+
+    let entity = match world.resource::<ToriiEntities>().entities.get(&update.entity_id) {
+        Some(entity) => *entity,
+        None => {
+            let entity = world.spawn(ToriiEntity(update.entity_id.clone())).id();
+            world.resource_mut::<ToriiEntities>().entities.insert(update.entity_id, entity);
+            entity
+        }
+    };
+
+    (component.insert)(world, entity, &values);
+}
+
+fn execute_systems(
+    mut events: EventReader<'_, '_, ExecuteSystem>,
+    runtime: ResMut<'_, TokioTasksRuntime>,
+    config: Res<'_, ToriiConfig>,
+    account: Option<Res<'_, WorldAccount>>,
+) {
+    for event in events.iter() {
+        let system = event.system.clone();
+        let world = config.world;
+        let account = account.as_ref().map(|account| account.0.clone());
+        let calldata = event.calldata.clone();
+
+        runtime.spawn_background_task(move |mut ctx| async move {
+            let result = match account {
+                Some(account) => execute(&account, world, &system, calldata).await,
+                None => Err("No account configured".to_string()),
+            };
+            if let Err(e) = &result {
+                log::error!("Failed to execute {system}: {e}");
+            }
+
+            ctx.run_on_main_thread(move |ctx| {
+                ctx.world.send_event(SystemExecuted { system, result });
+            })
+            .await;
+        });
+    }
+}
+
+async fn execute(
+    account: &SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>,
+    world: FieldElement,
+    system: &str,
+    calldata: Vec<FieldElement>,
+) -> Result<FieldElement, String> {
+    let system = cairo_short_string_to_felt(system).map_err(|e| e.to_string())?;
+    let mut execute_calldata = vec![system, FieldElement::from(calldata.len())];
+    execute_calldata.extend(calldata);
+
+    let call = Call {
+        to: world,
+        selector: get_selector_from_name("execute").unwrap(),
+        calldata: execute_calldata,
+    };
+    let result = account.execute(vec![call]).send().await.map_err(|e| e.to_string())?;
+
+    Ok(result.transaction_hash)
+}
@@ -2,6 +2,7 @@ mod auth;
 mod executor;
 mod integer;
 mod interfaces;
+mod packing;
 mod serde;
 mod storage;
 mod string;
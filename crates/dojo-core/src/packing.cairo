@@ -0,0 +1,16 @@
+use option::OptionTrait;
+use traits::{Into, TryInto};
+
+/// Extracts the `mask` wide value stored at `shift` (a power of two) from a packed slot.
+/// Used by the `Serde` implementations generated for `#[component(packed: true)]` components.
+fn unpack(packed: felt252, shift: felt252, mask: u128) -> u128 {
+    let packed: u256 = packed.into();
+    let shift: u256 = shift.into();
+    (packed / shift).low & mask
+}
+
+/// Whether `value` fits in `mask`, i.e. in the width declared with `#[packed_bits(..)]`.
+fn fits(value: felt252, mask: u128) -> bool {
+    let value: u256 = value.into();
+    value.high == 0 & value.low <= mask
+}
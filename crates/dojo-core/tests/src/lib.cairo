@@ -1,5 +1,6 @@
 mod executor;
 mod integer;
+mod packing;
 mod storage;
 mod world;
 mod world_factory;
@@ -0,0 +1,27 @@
+use dojo_core::packing::{fits, unpack};
+
+#[test]
+fn test_unpack() {
+    // flag: 1 bit at 0, level: 8 bits at 1, score: 32 bits at 9
+    let packed = 1 + 42 * 0x2 + 1000 * 0x200;
+
+    assert(unpack(packed, 0x1, 0x1) == 1, 'unpack flag');
+    assert(unpack(packed, 0x2, 0xff) == 42, 'unpack level');
+    assert(unpack(packed, 0x200, 0xffffffff) == 1000, 'unpack score');
+}
+
+#[test]
+fn test_unpack_max_values() {
+    let packed = 0xff + 0xffffffffffffffffffffffffffffffff * 0x100;
+
+    assert(unpack(packed, 0x1, 0xff) == 0xff, 'unpack u8 max');
+    let u128_max = 0xffffffffffffffffffffffffffffffff_u128;
+    assert(unpack(packed, 0x100, u128_max) == u128_max, 'unpack u128 max');
+}
+
+#[test]
+fn test_fits() {
+    assert(fits(0xf, 0xf), 'fits its width');
+    assert(!fits(0x10, 0xf), 'exceeds its width');
+    assert(!fits(0x100000000000000000000000000000000, 0xf), 'exceeds 128 bits');
+}
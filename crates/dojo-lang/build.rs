@@ -0,0 +1,40 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Exposes the version of `cairo-lang-compiler` the workspace resolved as `CAIRO_VERSION`, read
+/// from the closest `Cargo.lock` so that the fingerprints of the builds can't drift from it.
+/// Without a lock file, e.g. when built as a dependency, the version is `unknown`.
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let lock_path =
+        manifest_dir.ancestors().map(|dir| dir.join("Cargo.lock")).find(|path| path.exists());
+
+    let version = match lock_path {
+        Some(lock_path) => {
+            println!("cargo:rerun-if-changed={}", lock_path.display());
+            locked_version(&lock_path, "cairo-lang-compiler")
+        }
+        None => None,
+    };
+    let version = version.unwrap_or_else(|| {
+        println!(
+            "cargo:warning=The version of cairo-lang-compiler isn't locked, builds are \
+             fingerprinted with an unknown compiler version"
+        );
+        "unknown".to_string()
+    });
+    println!("cargo:rustc-env=CAIRO_VERSION={version}");
+}
+
+/// Version of the package `name` in the lock file, if it can be read.
+fn locked_version(lock_path: &Path, name: &str) -> Option<String> {
+    let lock = fs::read_to_string(lock_path).ok()?.parse::<toml::Value>().ok()?;
+    lock.get("package")?
+        .as_array()?
+        .iter()
+        .find(|package| package.get("name").and_then(|value| value.as_str()) == Some(name))?
+        .get("version")?
+        .as_str()
+        .map(str::to_string)
+}
@@ -0,0 +1,122 @@
+//! Cache of the contracts compiled by the previous builds of a target directory, so that the
+//! contracts whose sources didn't change aren't compiled again.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_defs::db::DefsGroup;
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::CrateId;
+use cairo_lang_parser::db::ParserGroup;
+use cairo_lang_starknet::contract::ContractDeclaration;
+use cairo_lang_syntax::node::helpers::QueryAttrs;
+use cairo_lang_syntax::node::{ast, TypedSyntaxNode};
+use camino::Utf8Path;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::starknet_keccak;
+
+/// Directory of the cache in the target directory, apart from the artifacts.
+pub const BUILD_CACHE_DIR: &str = "cache";
+pub const BUILD_CACHE_FILE: &str = "build.json";
+
+/// The contracts compiled in a target directory, by artifact file name.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BuildCache {
+    pub contracts: BTreeMap<String, CachedContract>,
+}
+
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedContract {
+    /// Fingerprint of the sources the artifact was compiled from.
+    #[serde_as(as = "UfeHex")]
+    pub fingerprint: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+}
+
+impl BuildCache {
+    /// The cache of a previous build, empty if there is none or it can't be read, in which case
+    /// every contract is compiled.
+    pub fn load_from_path(path: &Utf8Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Class hash of the artifact if it was compiled from sources with this fingerprint.
+    pub fn class_hash(&self, file_name: &str, fingerprint: FieldElement) -> Option<FieldElement> {
+        self.contracts
+            .get(file_name)
+            .filter(|contract| contract.fingerprint == fingerprint)
+            .map(|contract| contract.class_hash)
+    }
+}
+
+/// Fingerprints of the sources of the contracts, in order. A contract is fingerprinted from the
+/// file of its module, generated by the plugins for systems and components, and from every item
+/// of the crates but the contract modules. Editing a system only changes its own fingerprint,
+/// editing anything else, components included, changes all of them. `salt` covers what isn't in
+/// the sources, such as the compiler version.
+pub fn fingerprint_contracts(
+    db: &RootDatabase,
+    crate_ids: &[CrateId],
+    contracts: &[&ContractDeclaration],
+    salt: &[u8],
+) -> Vec<FieldElement> {
+    let mut shared = salt.to_vec();
+    let mut seen = HashSet::new();
+    for crate_id in crate_ids {
+        for module_id in db.crate_modules(*crate_id).iter() {
+            for file_id in db.module_files(*module_id).unwrap_or_default() {
+                if !seen.insert(file_id) {
+                    continue;
+                }
+                if let Ok(syntax) = db.file_syntax(file_id) {
+                    hash_items(db, syntax.items(db), &mut shared);
+                }
+            }
+        }
+    }
+    let shared = starknet_keccak(&shared);
+
+    contracts
+        .iter()
+        .map(|decl| {
+            let own = db
+                .module_main_file(decl.module_id())
+                .ok()
+                .and_then(|file_id| db.file_content(file_id))
+                .map(|content| starknet_keccak(content.as_bytes()))
+                .unwrap_or_default();
+            starknet_keccak(&[shared.to_bytes_be(), own.to_bytes_be()].concat())
+        })
+        .collect()
+}
+
+/// Appends the hash of every item to `hashes`, skipping the contract and system modules, which
+/// are part of their own fingerprint only.
+fn hash_items(db: &RootDatabase, items: ast::ItemList, hashes: &mut Vec<u8>) {
+    for item in items.elements(db) {
+        if let ast::Item::Module(module) = &item {
+            if module.has_attr(db, "contract") || module.has_attr(db, "system") {
+                continue;
+            }
+            if let ast::MaybeModuleBody::Some(body) = module.body(db) {
+                hashes.extend(starknet_keccak(module.name(db).text(db).as_bytes()).to_bytes_be());
+                hash_items(db, body.items(db), hashes);
+                continue;
+            }
+        }
+        hashes.extend(starknet_keccak(item.as_syntax_node().get_text(db).as_bytes()).to_bytes_be());
+    }
+}
+
+#[cfg(test)]
+#[path = "build_cache_test.rs"]
+mod test;
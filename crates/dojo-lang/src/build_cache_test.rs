@@ -0,0 +1,41 @@
+use std::collections::HashSet;
+
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_starknet::contract::find_contracts;
+use dojo_test_utils::compiler::{build_test_config, build_test_db};
+use scarb::ops;
+use starknet::core::types::FieldElement;
+
+use super::{fingerprint_contracts, BuildCache, CachedContract};
+
+#[test]
+fn test_fingerprint_contracts() {
+    let config = build_test_config("../../examples/ecs/Scarb.toml").unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config).unwrap();
+    let db = build_test_db(&ws).unwrap();
+
+    let crate_ids = db.crates();
+    let contracts = find_contracts(&db, &crate_ids);
+    let contracts = contracts.iter().collect::<Vec<_>>();
+    let fingerprints = fingerprint_contracts(&db, &crate_ids, &contracts, b"1.1.0");
+
+    assert_eq!(fingerprints.len(), contracts.len());
+    assert_eq!(fingerprints, fingerprint_contracts(&db, &crate_ids, &contracts, b"1.1.0"));
+    assert_eq!(fingerprints.iter().collect::<HashSet<_>>().len(), fingerprints.len());
+
+    let other_salt = fingerprint_contracts(&db, &crate_ids, &contracts, b"1.2.0");
+    assert!(fingerprints.iter().zip(&other_salt).all(|(a, b)| a != b));
+}
+
+#[test]
+fn test_class_hash_of_changed_sources() {
+    let mut cache = BuildCache::default();
+    cache.contracts.insert(
+        "ecs_MoveSystem.json".to_string(),
+        CachedContract { fingerprint: FieldElement::ONE, class_hash: FieldElement::TWO },
+    );
+
+    assert_eq!(cache.class_hash("ecs_MoveSystem.json", FieldElement::ONE), Some(FieldElement::TWO));
+    assert_eq!(cache.class_hash("ecs_MoveSystem.json", FieldElement::TWO), None);
+    assert_eq!(cache.class_hash("ecs_SpawnSystem.json", FieldElement::ONE), None);
+}
@@ -12,6 +12,7 @@ use super::{CommandData, CommandTrait, CAIRO_ERR_MSG_LEN};
 pub struct EntitiesCommand {
     query_id: String,
     data: CommandData,
+    pub components: Vec<SmolStr>,
 }
 
 impl CommandTrait for EntitiesCommand {
@@ -23,7 +24,11 @@ impl CommandTrait for EntitiesCommand {
         let mut query_id =
             StringSanitizer::from(let_pattern.unwrap().as_syntax_node().get_text(db));
         query_id.to_snake_case();
-        let mut command = EntitiesCommand { query_id: query_id.get(), data: CommandData::new() };
+        let mut command = EntitiesCommand {
+            query_id: query_id.get(),
+            data: CommandData::new(),
+            components: find_components(db, &command_ast),
+        };
 
         let partition =
             if let Some(partition) = command_ast.arguments(db).args(db).elements(db).first() {
@@ -42,7 +47,8 @@ impl CommandTrait for EntitiesCommand {
         ));
 
         command.data.rewrite_nodes.extend(
-            find_components(db, &command_ast)
+            command
+                .components
                 .iter()
                 .map(|component| {
                     RewriteNode::interpolate_patched(
@@ -79,7 +85,8 @@ impl CommandTrait for EntitiesCommand {
         ));
 
         command.data.rewrite_nodes.extend(
-            find_components(db, &command_ast)
+            command
+                .components
                 .iter()
                 .enumerate()
                 .map(|(idx, component)| {
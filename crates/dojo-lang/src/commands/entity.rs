@@ -16,6 +16,7 @@ pub struct EntityCommand {
     query_id: String,
     query_pattern: String,
     data: CommandData,
+    pub components: Vec<SmolStr>,
 }
 
 impl CommandTrait for EntityCommand {
@@ -32,6 +33,7 @@ impl CommandTrait for EntityCommand {
             query_id: query_id.get(),
             query_pattern: var_name.as_syntax_node().get_text(db),
             data: CommandData::new(),
+            components: vec![],
         };
 
         let elements = command_ast.arguments(db).args(db).elements(db);
@@ -57,6 +59,7 @@ impl CommandTrait for EntityCommand {
             })
             .collect();
 
+        command.components = components.clone();
         if command_name == "entity" {
             command.handle_entity(components, query, part_names);
         } else {
@@ -38,6 +38,8 @@ pub struct Command {
     pub rewrite_nodes: Vec<RewriteNode>,
     pub diagnostics: Vec<PluginDiagnostic>,
     pub component_deps: Vec<SmolStr>,
+    /// Components read by the command.
+    pub component_reads: Vec<SmolStr>,
 }
 
 impl Command {
@@ -46,8 +48,12 @@ impl Command {
         let_pattern: Option<ast::Pattern>,
         command_ast: ast::ExprFunctionCall,
     ) -> Self {
-        let mut command =
-            Command { rewrite_nodes: vec![], diagnostics: vec![], component_deps: vec![] };
+        let mut command = Command {
+            rewrite_nodes: vec![],
+            diagnostics: vec![],
+            component_deps: vec![],
+            component_reads: vec![],
+        };
 
         match command_name(db, command_ast.clone()).as_str() {
             "uuid" => {
@@ -59,11 +65,13 @@ impl Command {
                 let sc = entity::EntityCommand::from_ast(db, let_pattern, command_ast);
                 command.rewrite_nodes.extend(sc.rewrite_nodes());
                 command.diagnostics.extend(sc.diagnostics());
+                command.component_reads.extend(sc.components);
             }
             "try_entity" => {
                 let sc = entity::EntityCommand::from_ast(db, let_pattern, command_ast);
                 command.rewrite_nodes.extend(sc.rewrite_nodes());
                 command.diagnostics.extend(sc.diagnostics());
+                command.component_reads.extend(sc.components);
             }
             "set_entity" => {
                 let sc = set::SetCommand::from_ast(db, let_pattern, command_ast);
@@ -75,6 +83,7 @@ impl Command {
                 let sc = entities::EntitiesCommand::from_ast(db, let_pattern, command_ast);
                 command.rewrite_nodes.extend(sc.rewrite_nodes());
                 command.diagnostics.extend(sc.diagnostics());
+                command.component_reads.extend(sc.components);
             }
             "execute" => {
                 let sc = execute::ExecuteCommand::from_ast(db, let_pattern, command_ast);
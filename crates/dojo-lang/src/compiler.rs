@@ -2,23 +2,35 @@ use std::collections::HashMap;
 use std::iter::zip;
 use std::ops::DerefMut;
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
+use cairo_lang_defs::db::DefsGroup;
 use cairo_lang_filesystem::db::FilesGroup;
 use cairo_lang_filesystem::ids::{CrateId, CrateLongId};
 use cairo_lang_starknet::contract::find_contracts;
 use cairo_lang_starknet::contract_class::{compile_prepared_db, ContractClass};
 use cairo_lang_utils::UpcastMut;
+use dojo_world::config::schema_upgrades_from_workspace;
+use dojo_world::manifest::{BuildInfo, ContractFingerprint};
 use scarb::compiler::helpers::build_compiler_config;
 use scarb::compiler::{CompilationUnit, Compiler};
 use scarb::core::Workspace;
 use smol_str::SmolStr;
 use starknet::core::types::contract::SierraClass;
 use starknet::core::types::FieldElement;
+use starknet::core::utils::starknet_keccak;
 use tracing::{trace, trace_span};
 
+use crate::build_cache::{
+    fingerprint_contracts, BuildCache, CachedContract, BUILD_CACHE_DIR, BUILD_CACHE_FILE,
+};
 use crate::manifest::Manifest;
 
+/// Version of the Cairo compiler, the `cairo-lang-compiler` package resolved by Cargo.
+pub const CAIRO_VERSION: &str = env!("CAIRO_VERSION");
+pub const PLUGIN_VERSION: &str = env!("CARGO_PKG_VERSION");
+
 pub struct DojoCompiler;
 
 impl Compiler for DojoCompiler {
@@ -34,11 +46,12 @@ impl Compiler for DojoCompiler {
     ) -> Result<()> {
         let target_dir = unit.target_dir(ws.config());
         let compiler_config = build_compiler_config(&unit, ws);
-        let main_crate_ids = collect_main_crate_ids(&unit, db);
+        let dojo_crates = collect_dojo_crates(&unit, db, ws);
+        let dojo_crate_ids = dojo_crates.iter().map(|(crate_id, _)| *crate_id).collect::<Vec<_>>();
 
         let contracts = {
             let _ = trace_span!("find_contracts").enter();
-            find_contracts(db, &main_crate_ids)
+            find_contracts(db, &dojo_crate_ids)
         };
 
         trace!(
@@ -50,31 +63,107 @@ impl Compiler for DojoCompiler {
 
         let contracts = contracts.iter().collect::<Vec<_>>();
 
+        // Contracts compiled by a previous build from the same sources keep their artifact.
+        let cache_dir = target_dir.child(BUILD_CACHE_DIR);
+        let cache_path = cache_dir.path_unchecked().join(BUILD_CACHE_FILE);
+        let mut cache = BuildCache::load_from_path(&cache_path);
+        let source_fingerprints = {
+            let _ = trace_span!("fingerprint_contracts").enter();
+            let crate_ids = unit
+                .components
+                .iter()
+                .map(|component| db.intern_crate(CrateLongId(component.cairo_package_name())))
+                .collect::<Vec<_>>();
+            let salt = format!("{CAIRO_VERSION} {PLUGIN_VERSION} {}", compiler_config.replace_ids);
+            fingerprint_contracts(db, &crate_ids, &contracts, salt.as_bytes())
+        };
+        let file_names = contracts
+            .iter()
+            .map(|decl| {
+                let contract_name = decl.submodule_id.name(db.upcast_mut());
+                format!("{}_{contract_name}.json", unit.target().name)
+            })
+            .collect::<Vec<_>>();
+        let cached_class_hashes = zip(&file_names, &source_fingerprints)
+            .map(|(file_name, fingerprint)| {
+                cache
+                    .class_hash(file_name, *fingerprint)
+                    .filter(|_| target_dir.path_unchecked().join(file_name).exists())
+            })
+            .collect::<Vec<_>>();
+        let outdated = zip(&contracts, &cached_class_hashes)
+            .filter(|(_, cached)| cached.is_none())
+            .map(|(decl, _)| *decl)
+            .collect::<Vec<_>>();
+        trace!(cached = contracts.len() - outdated.len(), compiled = outdated.len());
+
         let classes = {
             let _ = trace_span!("compile_starknet").enter();
-            compile_prepared_db(db, &contracts, compiler_config)?
+            compile_prepared_db(db, &outdated, compiler_config)?
         };
+        let mut classes = classes.into_iter();
 
         // (contract name, class hash)
         let mut compiled_classes: HashMap<SmolStr, FieldElement> = HashMap::new();
+        let mut fingerprints = vec![];
 
-        for (decl, class) in zip(contracts, classes) {
-            let target_name = &unit.target().name;
+        for (((decl, file_name), fingerprint), cached_class_hash) in
+            zip(zip(zip(contracts, file_names), source_fingerprints), cached_class_hashes)
+        {
             let contract_name = decl.submodule_id.name(db.upcast_mut());
-            let file_name = format!("{target_name}_{contract_name}.json");
 
-            let mut file = target_dir.open_rw(file_name.clone(), "output file", ws.config())?;
-            serde_json::to_writer_pretty(file.deref_mut(), &class)
-                .with_context(|| format!("failed to serialize contract: {contract_name}"))?;
+            let class_hash = match cached_class_hash {
+                Some(class_hash) => class_hash,
+                None => {
+                    let class = classes.next().expect("every outdated contract is compiled");
+                    let mut file =
+                        target_dir.open_rw(file_name.clone(), "output file", ws.config())?;
+                    serde_json::to_writer_pretty(file.deref_mut(), &class)
+                        .with_context(|| format!("failed to serialize contract: {contract_name}"))?;
+
+                    compute_class_hash_of_contract_class(class).with_context(|| {
+                        format!("problem computing class hash for contract `{contract_name}`")
+                    })?
+                }
+            };
+            cache.contracts.insert(file_name, CachedContract { fingerprint, class_hash });
 
-            let class_hash = compute_class_hash_of_contract_class(class).with_context(|| {
-                format!("problem computing class hash for contract `{contract_name}`")
-            })?;
-            compiled_classes.insert(contract_name, class_hash);
+            let source_hash = db
+                .module_main_file(decl.module_id())
+                .ok()
+                .and_then(|file_id| db.file_content(file_id))
+                .map(|content| starknet_keccak(content.as_bytes()))
+                .unwrap_or_default();
+
+            fingerprints.push(ContractFingerprint {
+                name: contract_name.clone(),
+                class_hash,
+                source_hash,
+            });
+            if compiled_classes.insert(contract_name.clone(), class_hash).is_some() {
+                bail!("contract `{contract_name}` is defined by several packages of the workspace");
+            }
+        }
+
+        let mut file = cache_dir.open_rw(BUILD_CACHE_FILE, "build cache", ws.config())?;
+        serde_json::to_writer_pretty(file.deref_mut(), &cache)
+            .with_context(|| "failed to serialize the build cache")?;
+
+        fingerprints.sort_by(|a, b| a.name.cmp(&b.name));
+        let build_info = BuildInfo {
+            compiler_version: CAIRO_VERSION.to_string(),
+            plugin_version: PLUGIN_VERSION.to_string(),
+            contracts: fingerprints,
+        };
+
+        // The contracts of a library are in the manifests of the packages depending on it.
+        if is_dojo_dependency(&unit, ws) {
+            return Ok(());
         }
 
         let mut file = target_dir.open_rw("manifest.json", "output file", ws.config())?;
-        let manifest = Manifest::new(db, &main_crate_ids, compiled_classes);
+        let manifest = Manifest::new(db, &dojo_crates, compiled_classes, build_info)
+            .with_schema_upgrades(schema_upgrades_from_workspace(ws)?)?;
         serde_json::to_writer_pretty(file.deref_mut(), &manifest)
             .with_context(|| "failed to serialize manifest")?;
 
@@ -82,6 +171,33 @@ impl Compiler for DojoCompiler {
     }
 }
 
+/// Expands the Dojo plugins and type checks the crates without generating any artifact, much
+/// faster than [`DojoCompiler`].
+pub struct DojoCheckCompiler;
+
+impl Compiler for DojoCheckCompiler {
+    fn target_kind(&self) -> &str {
+        "dojo"
+    }
+
+    fn compile(
+        &self,
+        unit: CompilationUnit,
+        db: &mut RootDatabase,
+        _: &Workspace<'_>,
+    ) -> Result<()> {
+        // Registers `dojo_core` so that its diagnostics are reported too.
+        collect_main_crate_ids(&unit, db);
+
+        if DiagnosticsReporter::stderr().check(db) {
+            let package = unit.main_component().cairo_package_name();
+            bail!("could not check `{package}` due to previous errors");
+        }
+
+        Ok(())
+    }
+}
+
 fn compute_class_hash_of_contract_class(class: ContractClass) -> Result<FieldElement> {
     let class_str = serde_json::to_string(&class)?;
     let sierra_class = serde_json::from_str::<SierraClass>(&class_str)
@@ -97,6 +213,39 @@ pub fn collect_main_crate_ids(unit: &CompilationUnit, db: &RootDatabase) -> Vec<
     main_crate_ids
 }
 
+/// Crates whose contracts make up the world of the unit: its package's, the ones of the workspace
+/// members it depends on, such as shared component libraries, and `dojo_core`. Each crate comes
+/// with the name of its package.
+pub fn collect_dojo_crates(
+    unit: &CompilationUnit,
+    db: &RootDatabase,
+    ws: &Workspace<'_>,
+) -> Vec<(CrateId, SmolStr)> {
+    let mut crates = vec![];
+    let packages = unit
+        .components
+        .iter()
+        .filter(|component| ws.members().any(|member| member.id == component.package.id))
+        .map(|component| component.cairo_package_name())
+        .chain(std::iter::once("dojo_core".into()));
+    for package in packages {
+        if crates.iter().all(|(_, name)| *name != package) {
+            crates.push((db.intern_crate(CrateLongId(package.clone())), package));
+        }
+    }
+    crates
+}
+
+/// Whether another dojo package of the workspace depends on the unit's package, in which case it
+/// only provides contracts to that package's world.
+fn is_dojo_dependency(unit: &CompilationUnit, ws: &Workspace<'_>) -> bool {
+    let name = &unit.main_component().package.id.name;
+    ws.members().any(|member| {
+        member.manifest.targets.iter().any(|target| target.kind == "dojo")
+            && member.manifest.summary.dependencies.iter().any(|dep| dep.name == *name)
+    })
+}
+
 #[test]
 fn test_compiler() {
     use dojo_test_utils::compiler::build_test_config;
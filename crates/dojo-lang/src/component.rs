@@ -1,6 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
-use cairo_lang_defs::plugin::{DynGeneratedFileAuxData, PluginGeneratedFile, PluginResult};
+use cairo_lang_defs::plugin::{
+    DynGeneratedFileAuxData, PluginDiagnostic, PluginGeneratedFile, PluginResult,
+};
 use cairo_lang_semantic::patcher::{PatchBuilder, RewriteNode};
 use cairo_lang_semantic::plugin::DynPluginAuxData;
 use cairo_lang_syntax::attribute::structured::{
@@ -10,9 +12,10 @@ use cairo_lang_syntax::node::ast::ItemStruct;
 use cairo_lang_syntax::node::db::SyntaxGroup;
 use cairo_lang_syntax::node::helpers::QueryAttrs;
 use cairo_lang_syntax::node::{ast, Terminal, TypedSyntaxNode};
-use dojo_world::manifest::Member;
+use dojo_world::manifest::{packed_bits, storage_layout, Member};
+use smol_str::SmolStr;
 
-use crate::plugin::{Component, DojoAuxData};
+use crate::plugin::{doc_comment, Component, DojoAuxData};
 
 /// A handler for Dojo code that modifies a component struct.
 /// Parameters:
@@ -21,6 +24,19 @@ use crate::plugin::{Component, DojoAuxData};
 /// Returns:
 /// * A PluginResult containing the generated code.
 pub fn handle_component_struct(db: &dyn SyntaxGroup, struct_ast: ItemStruct) -> PluginResult {
+    let packed = is_packed(db, struct_ast.clone());
+    if packed && derives(db, struct_ast.clone(), "Serde") {
+        return PluginResult {
+            diagnostics: vec![PluginDiagnostic {
+                stable_ptr: struct_ast.name(db).stable_ptr().untyped(),
+                message: "Packed components generate their own Serde implementation, remove it \
+                          from the derive attribute."
+                    .into(),
+            }],
+            ..PluginResult::default()
+        };
+    }
+
     let mut body_nodes = vec![RewriteNode::interpolate_patched(
         "
             #[view]
@@ -67,20 +83,50 @@ pub fn handle_component_struct(db: &dyn SyntaxGroup, struct_ast: ItemStruct) ->
     // Add the is_indexed function to the body
     body_nodes.push(is_indexed_fn);
 
+    let declared_bits = match declared_bits(db, &struct_ast, packed) {
+        Ok(declared_bits) => declared_bits,
+        Err(diagnostics) => return PluginResult { diagnostics, ..PluginResult::default() },
+    };
+
     let members: Vec<_> = struct_ast
         .members(db)
         .elements(db)
         .iter()
-        .map(|member| (member.name(db).text(db), member.type_clause(db).ty(db), 252))
+        .map(|member| {
+            let name = member.name(db).text(db);
+            let ty = member.type_clause(db).ty(db);
+            let bits = if packed {
+                declared_bits
+                    .get(&name)
+                    .copied()
+                    .or_else(|| packed_bits(ty.as_syntax_node().get_text(db).trim()))
+            } else {
+                None
+            };
+            (name, ty, bits)
+        })
         .collect::<_>();
+    let layout = storage_layout(&members.iter().map(|(_, _, bits)| *bits).collect::<Vec<_>>());
+    let docs = struct_ast
+        .members(db)
+        .elements(db)
+        .iter()
+        .map(|member| doc_comment(db, &member.as_syntax_node()))
+        .collect::<Vec<_>>();
 
     let name = struct_ast.name(db).text(db);
+    let serde_impl = if packed {
+        packed_serde_impl(db, &struct_ast, &members, &layout)
+    } else {
+        RewriteNode::Text("".to_string())
+    };
+
     let mut builder = PatchBuilder::new(db);
     builder.add_modified(RewriteNode::interpolate_patched(
         "
             struct $type_name$ {
                 $members$
-            }
+            }$serde_impl$
 
             #[abi]
             trait I$type_name$ {
@@ -110,6 +156,7 @@ pub fn handle_component_struct(db: &dyn SyntaxGroup, struct_ast: ItemStruct) ->
             ),
             ("members".to_string(), RewriteNode::Copied(struct_ast.members(db).as_syntax_node())),
             ("body".to_string(), RewriteNode::new_modified(body_nodes)),
+            ("serde_impl".to_string(), serde_impl),
             (
                 "schema_members".to_string(),
                 RewriteNode::new_modified(
@@ -118,13 +165,17 @@ pub fn handle_component_struct(db: &dyn SyntaxGroup, struct_ast: ItemStruct) ->
                         .map(|item| {
                             RewriteNode::interpolate_patched(
                                 "array::ArrayTrait::append(ref arr, ('$name$', '$type_clause$', \
-                                 252));\n",
+                                 $size$));\n",
                                 HashMap::from([
                                     ("name".to_string(), RewriteNode::Text(item.0.to_string())),
                                     (
                                         "type_clause".to_string(),
                                         RewriteNode::new_trimmed(item.1.as_syntax_node()),
                                     ),
+                                    (
+                                        "size".to_string(),
+                                        RewriteNode::Text(item.2.unwrap_or(252).to_string()),
+                                    ),
                                 ]),
                             )
                         })
@@ -144,14 +195,19 @@ pub fn handle_component_struct(db: &dyn SyntaxGroup, struct_ast: ItemStruct) ->
                     name: name.to_string(),
                     members: members
                         .iter()
-                        .enumerate()
-                        .map(|(slot, (name, ty, _size))| Member {
+                        .zip(&layout)
+                        .zip(docs)
+                        .map(|(((name, ty, _bits), (slot, offset)), doc)| Member {
                             name: name.to_string(),
                             ty: ty.as_syntax_node().get_text(db).trim().to_string(),
-                            slot,
-                            offset: 0,
+                            slot: *slot,
+                            offset: *offset,
+                            bits: declared_bits.get(name).copied(),
+                            variants: None,
+                            doc,
                         })
                         .collect(),
+                    doc: doc_comment(db, &struct_ast.as_syntax_node()),
                 }],
                 systems: vec![],
             })),
@@ -163,6 +219,83 @@ pub fn handle_component_struct(db: &dyn SyntaxGroup, struct_ast: ItemStruct) ->
 
 /// Returns true if the component is indexed #[component(indexed: true)]
 fn is_indexed(db: &dyn SyntaxGroup, struct_ast: ItemStruct) -> bool {
+    has_component_flag(db, struct_ast, "indexed")
+}
+
+/// Returns true if the component's small members are packed #[component(packed: true)]
+fn is_packed(db: &dyn SyntaxGroup, struct_ast: ItemStruct) -> bool {
+    has_component_flag(db, struct_ast, "packed")
+}
+
+/// Bit widths declared by `#[packed_bits(member: bits, ..)]`, narrowing the members of a packed
+/// component below the width of their type.
+fn declared_bits(
+    db: &dyn SyntaxGroup,
+    struct_ast: &ItemStruct,
+    packed: bool,
+) -> Result<HashMap<SmolStr, u8>, Vec<PluginDiagnostic>> {
+    let members = struct_ast
+        .members(db)
+        .elements(db)
+        .iter()
+        .map(|member| {
+            let ty = member.type_clause(db).ty(db);
+            (member.name(db).text(db), ty.as_syntax_node().get_text(db).trim().to_string())
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut declared = HashMap::new();
+    let mut diagnostics = vec![];
+    for attr in struct_ast.attributes(db).query_attr(db, "packed_bits") {
+        if !packed {
+            diagnostics.push(PluginDiagnostic {
+                stable_ptr: attr.stable_ptr().untyped(),
+                message: "Bit widths only apply to packed components, add \
+                          `#[component(packed: true)]`."
+                    .into(),
+            });
+            continue;
+        }
+
+        for arg in attr.structurize(db).args {
+            let AttributeArg {
+                variant: AttributeArgVariant::Named { value, name, .. },
+                arg_stable_ptr,
+                ..
+            } = arg else {
+                diagnostics.push(PluginDiagnostic {
+                    stable_ptr: arg.arg_stable_ptr.untyped(),
+                    message: "Expected `member: bits`.".into(),
+                });
+                continue;
+            };
+
+            let message = match members.get(&name).map(|ty| (ty, packed_bits(ty))) {
+                None => format!("Unknown member `{name}`."),
+                Some((ty, None)) => format!("Member `{name}` of type `{ty}` can't be packed."),
+                Some((ty, Some(max))) => {
+                    let bits = value.as_syntax_node().get_text_without_trivia(db);
+                    match bits.parse::<u8>() {
+                        Ok(bits) if (1..=max).contains(&bits) => {
+                            declared.insert(name, bits);
+                            continue;
+                        }
+                        _ => format!(
+                            "Expected a width between 1 and {max} bits for `{name}`, the width of \
+                             `{ty}`."
+                        ),
+                    }
+                }
+            };
+            diagnostics.push(PluginDiagnostic { stable_ptr: arg_stable_ptr.untyped(), message });
+        }
+    }
+
+    if diagnostics.is_empty() { Ok(declared) } else { Err(diagnostics) }
+}
+
+/// Returns true if the `flag` argument of the component attribute is set to true.
+fn has_component_flag(db: &dyn SyntaxGroup, struct_ast: ItemStruct, flag: &str) -> bool {
     for attr in struct_ast.attributes(db).query_attr(db, "component") {
         let attr = attr.structurize(db);
 
@@ -178,10 +311,138 @@ fn is_indexed(db: &dyn SyntaxGroup, struct_ast: ItemStruct) -> bool {
                 continue;
             };
 
-            if name == "indexed" {
+            if name == flag {
                 return true;
             }
         }
     }
     false
 }
+
+/// Returns true if the struct derives `trait_name`.
+fn derives(db: &dyn SyntaxGroup, struct_ast: ItemStruct, trait_name: &str) -> bool {
+    struct_ast.attributes(db).query_attr(db, "derive").into_iter().any(|attr| {
+        attr.structurize(db).args.into_iter().any(|arg| {
+            matches!(
+                arg.variant,
+                AttributeArgVariant::Unnamed { value: ast::Expr::Path(path), .. }
+                    if path.as_syntax_node().get_text(db).trim() == trait_name
+            )
+        })
+    })
+}
+
+/// Generates the `Serde` implementation of a packed component, serializing its slots in order.
+fn packed_serde_impl(
+    db: &dyn SyntaxGroup,
+    struct_ast: &ItemStruct,
+    members: &[(SmolStr, ast::Expr, Option<u8>)],
+    layout: &[(usize, u8)],
+) -> RewriteNode {
+    let mut slots: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (index, (slot, _)) in layout.iter().enumerate() {
+        slots.entry(*slot).or_default().push(index);
+    }
+
+    let mut serialize = vec![];
+    let mut deserialize = vec![];
+
+    for (slot, indexes) in slots {
+        let (name, ty, bits) = &members[indexes[0]];
+        let ty_text = ty.as_syntax_node().get_text(db).trim().to_string();
+
+        if bits.is_none() {
+            serialize.push(format!("serde::Serde::serialize(self.{name}, ref output);"));
+            deserialize.push(format!(
+                "let {name}: {ty_text} = serde::Serde::deserialize(ref serialized)?;"
+            ));
+            continue;
+        }
+
+        serialize.push(format!("let mut slot_{slot} = 0;"));
+        deserialize
+            .push(format!("let slot_{slot} = *array::SpanTrait::pop_front(ref serialized)?;"));
+
+        for index in indexes {
+            let (name, ty, bits) = &members[index];
+            let ty = ty.as_syntax_node().get_text(db).trim().to_string();
+            let shift = pow2_hex(layout[index].1);
+
+            // Values wider than a declared width would overwrite the next members of the slot.
+            if *bits != packed_bits(&ty) {
+                serialize.push(format!(
+                    "assert(dojo_core::packing::fits({}(*self.{name}), {}), 'packed member \
+                     overflows');",
+                    to_felt252_fn(&ty),
+                    mask_hex(bits.unwrap_or_default())
+                ));
+            }
+            serialize.push(format!(
+                "slot_{slot} = slot_{slot} + {}(*self.{name}) * {shift};",
+                to_felt252_fn(&ty)
+            ));
+
+            let mask = mask_hex(bits.unwrap_or_default());
+            deserialize.push(format!(
+                "let {name} = dojo_core::packing::unpack(slot_{slot}, {shift}, {mask});"
+            ));
+            match ty.as_str() {
+                "bool" => deserialize.push(format!("let {name} = {name} == 1_u128;")),
+                "u128" => {}
+                _ => deserialize.push(format!(
+                    "let {name}: {ty} = \
+                     traits::TryInto::try_into(integer::u128_to_felt252({name}))?;"
+                )),
+            }
+        }
+
+        serialize.push(format!("array::ArrayTrait::append(ref output, slot_{slot});"));
+    }
+
+    let fields = members.iter().map(|(name, _, _)| name.to_string()).collect::<Vec<_>>();
+
+    RewriteNode::interpolate_patched(
+        "
+            impl $type_name$Serde of serde::Serde<$type_name$> {
+                fn serialize(self: @$type_name$, ref output: Array<felt252>) {
+                    $serialize$
+                }
+
+                fn deserialize(ref serialized: Span<felt252>) -> Option<$type_name$> {
+                    $deserialize$
+                    Option::Some($type_name$ { $fields$ })
+                }
+            }
+        ",
+        HashMap::from([
+            (
+                "type_name".to_string(),
+                RewriteNode::new_trimmed(struct_ast.name(db).as_syntax_node()),
+            ),
+            ("serialize".to_string(), RewriteNode::Text(serialize.join("\n"))),
+            ("deserialize".to_string(), RewriteNode::Text(deserialize.join("\n"))),
+            ("fields".to_string(), RewriteNode::Text(fields.join(", "))),
+        ]),
+    )
+}
+
+fn to_felt252_fn(ty: &str) -> String {
+    match ty {
+        "bool" => "bool_to_felt252".to_string(),
+        // usize is an alias of u32
+        "usize" => "integer::u32_to_felt252".to_string(),
+        _ => format!("integer::{ty}_to_felt252"),
+    }
+}
+
+/// `2^exponent` as a hex literal.
+fn pow2_hex(exponent: u8) -> String {
+    let exponent = exponent as usize;
+    format!("0x{}{}", ["1", "2", "4", "8"][exponent % 4], "0".repeat(exponent / 4))
+}
+
+/// A mask of the `bits` lowest bits as a hex literal.
+fn mask_hex(bits: u8) -> String {
+    let bits = bits as usize;
+    format!("0x{}{}", ["", "1", "3", "7"][bits % 4], "f".repeat(bits / 4))
+}
@@ -3,6 +3,7 @@
 //! Dojo is a full stack toolchain for developing onchain games in Cairo.
 //!
 //! Learn more at [dojoengine.gg](http://dojoengine.gg).
+pub mod build_cache;
 mod commands;
 pub mod compiler;
 pub mod component;
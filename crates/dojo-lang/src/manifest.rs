@@ -1,11 +1,12 @@
 use std::collections::HashMap;
 
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use cairo_lang_defs::ids::{ModuleId, ModuleItemId};
 use cairo_lang_filesystem::ids::CrateId;
 use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_semantic::plugin::DynPluginAuxData;
-use dojo_world::manifest::{Input, Output, System};
+use dojo_world::manifest::{BuildInfo, Input, Output, System};
+use dojo_world::migration::schema::SchemaUpgrade;
 use serde::Serialize;
 use smol_str::SmolStr;
 use starknet::core::types::FieldElement;
@@ -18,10 +19,14 @@ pub(crate) struct Manifest(dojo_world::manifest::Manifest);
 impl Manifest {
     pub fn new(
         db: &dyn SemanticGroup,
-        crate_ids: &[CrateId],
+        crates: &[(CrateId, SmolStr)],
         compiled_classes: HashMap<SmolStr, FieldElement>,
+        build_info: BuildInfo,
     ) -> Self {
-        let mut manifest = Manifest(dojo_world::manifest::Manifest::default());
+        let mut manifest = Manifest(dojo_world::manifest::Manifest {
+            build_info: Some(build_info),
+            ..Default::default()
+        });
 
         let world = compiled_classes.get("World").unwrap_or_else(|| {
             panic!("World contract not found. Did you include `dojo_core` as a dependency?");
@@ -33,7 +38,7 @@ impl Manifest {
         manifest.0.world = *world;
         manifest.0.executor = *executor;
 
-        for crate_id in crate_ids {
+        for (crate_id, package) in crates {
             let modules = db.crate_modules(*crate_id);
             for module_id in modules.iter() {
                 let generated_file_infos =
@@ -46,8 +51,10 @@ impl Manifest {
                     let Some(aux_data) = mapper.0.as_any(
                 ).downcast_ref::<DojoAuxData>() else { continue; };
 
-                    manifest.find_components(db, aux_data, *module_id, &compiled_classes);
-                    manifest.find_systems(db, aux_data, *module_id, &compiled_classes).unwrap();
+                    manifest.find_components(db, aux_data, *module_id, package, &compiled_classes);
+                    manifest
+                        .find_systems(db, aux_data, *module_id, package, &compiled_classes)
+                        .unwrap();
                 }
             }
         }
@@ -55,6 +62,21 @@ impl Manifest {
         manifest
     }
 
+    /// Attaches the transforms of the components upgraded from another schema, by component
+    /// name.
+    pub fn with_schema_upgrades(
+        mut self,
+        mut upgrades: HashMap<String, SchemaUpgrade>,
+    ) -> Result<Self> {
+        for component in &mut self.0.components {
+            component.upgrade = upgrades.remove(&component.name);
+        }
+        if let Some(name) = upgrades.keys().next() {
+            bail!("`[tool.dojo.upgrades.{name}]` doesn't match any component");
+        }
+        Ok(self)
+    }
+
     /// Finds the inline modules annotated as components in the given crate_ids and
     /// returns the corresponding Components.
     fn find_components(
@@ -62,6 +84,7 @@ impl Manifest {
         db: &dyn SemanticGroup,
         aux_data: &DojoAuxData,
         module_id: ModuleId,
+        package: &SmolStr,
         compiled_classes: &HashMap<SmolStr, FieldElement>,
     ) {
         for component in &aux_data.components {
@@ -81,6 +104,9 @@ impl Manifest {
                     name: component.name,
                     members: component.members,
                     class_hash: *class_hash,
+                    package: Some(package.clone()),
+                    upgrade: None,
+                    doc: component.doc,
                 });
             }
         }
@@ -91,9 +117,10 @@ impl Manifest {
         db: &dyn SemanticGroup,
         aux_data: &DojoAuxData,
         module_id: ModuleId,
+        package: &SmolStr,
         compiled_classes: &HashMap<SmolStr, FieldElement>,
     ) -> Result<()> {
-        for SystemAuxData { name, dependencies } in &aux_data.systems {
+        for SystemAuxData { name, dependencies, reads, doc } in &aux_data.systems {
             if let Ok(Some(ModuleItemId::Submodule(submodule_id))) =
                 db.module_item_by_name(module_id, name.clone())
             {
@@ -137,6 +164,9 @@ impl Manifest {
                             .iter()
                             .map(|s| s.to_string())
                             .collect::<Vec<_>>(),
+                        reads: reads.iter().map(|s| s.to_string()).collect::<Vec<_>>(),
+                        package: Some(package.clone()),
+                        doc: doc.clone(),
                     });
                 }
             } else {
@@ -15,7 +15,7 @@ use cairo_lang_syntax::attribute::structured::{
 };
 use cairo_lang_syntax::node::db::SyntaxGroup;
 use cairo_lang_syntax::node::helpers::QueryAttrs;
-use cairo_lang_syntax::node::{ast, Terminal};
+use cairo_lang_syntax::node::{ast, SyntaxNode, Terminal};
 use camino::Utf8Path;
 use dojo_world::manifest::Member;
 use scarb::compiler::plugin::builtin::BuiltinSemanticCairoPlugin;
@@ -32,12 +32,31 @@ const SYSTEM_ATTR: &str = "system";
 pub struct Component {
     pub name: String,
     pub members: Vec<Member>,
+    pub doc: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct SystemAuxData {
     pub name: SmolStr,
     pub dependencies: Vec<SmolStr>,
+    /// Components read through the `entity`, `try_entity` and `entities` commands.
+    pub reads: Vec<SmolStr>,
+    pub doc: Option<String>,
+}
+
+/// The `///` comments preceding an item, without their markers, `None` if there are none.
+pub fn doc_comment(db: &dyn SyntaxGroup, node: &SyntaxNode) -> Option<String> {
+    // Comments are part of the leading trivia of the item's first token.
+    let text = node.get_text(db);
+    let lines = text
+        .lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with("//"))
+        .filter_map(|line| line.strip_prefix("///"))
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>();
+
+    if lines.is_empty() { None } else { Some(lines.join("\n")) }
 }
 
 /// Dojo related auxiliary data of the Dojo plugin.
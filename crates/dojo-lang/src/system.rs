@@ -11,17 +11,18 @@ use cairo_lang_syntax::node::db::SyntaxGroup;
 use cairo_lang_syntax::node::{ast, Terminal, TypedSyntaxNode};
 
 use crate::commands::Command;
-use crate::plugin::{DojoAuxData, SystemAuxData};
+use crate::plugin::{doc_comment, DojoAuxData, SystemAuxData};
 
 pub struct System {
     diagnostics: Vec<PluginDiagnostic>,
     dependencies: Vec<smol_str::SmolStr>,
+    reads: Vec<smol_str::SmolStr>,
 }
 
 impl System {
     pub fn from_module(db: &dyn SyntaxGroup, module_ast: ast::ItemModule) -> PluginResult {
         let name = module_ast.name(db).text(db);
-        let mut system = System { diagnostics: vec![], dependencies: vec![] };
+        let mut system = System { diagnostics: vec![], dependencies: vec![], reads: vec![] };
 
         if let MaybeModuleBody::Some(body) = module_ast.body(db) {
             let body_nodes = body
@@ -83,6 +84,8 @@ impl System {
                         systems: vec![SystemAuxData {
                             name: format!("{name}System").into(),
                             dependencies: system.dependencies.clone(),
+                            reads: system.reads.clone(),
+                            doc: doc_comment(db, &module_ast.as_syntax_node()),
                         }],
                     })),
                 }),
@@ -299,6 +302,7 @@ impl System {
                     let command = Command::from_ast(db, var_name, expr_fn);
                     self.diagnostics.extend(command.diagnostics);
                     self.dependencies.extend(command.component_deps);
+                    self.reads.extend(command.component_reads);
                     return Some(command.rewrite_nodes);
                 }
             }
@@ -307,6 +311,7 @@ impl System {
                     let command = Command::from_ast(db, var_name, expr_fn);
                     self.diagnostics.extend(command.diagnostics);
                     self.dependencies.extend(command.component_deps);
+                    self.reads.extend(command.component_reads);
                     return Some(command.rewrite_nodes);
                 }
             }
@@ -0,0 +1,91 @@
+use async_trait::async_trait;
+use starknet::core::crypto::Signature;
+use starknet::core::types::FieldElement;
+use starknet::signers::{Signer, VerifyingKey};
+use thiserror::Error;
+use tokio::process::Command;
+
+#[cfg(test)]
+#[path = "command_test.rs"]
+mod test;
+
+#[derive(Debug, Error)]
+pub enum CommandSignerError {
+    #[error("Failed to run signer command: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Signer command exited with {status}: {stderr}")]
+    Failed { status: std::process::ExitStatus, stderr: String },
+    #[error("Signer command output is not a valid signature: {0}")]
+    InvalidOutput(String),
+    #[error("Signer command does not expose a public key.")]
+    PublicKeyUnavailable,
+}
+
+/// A signer that delegates signing to an external program.
+///
+/// The command is run through the shell with the hex-encoded hash appended as its last argument,
+/// and must print the `r` and `s` components of the signature as whitespace-separated hex
+/// values on stdout.
+#[derive(Debug, Clone)]
+pub struct CommandSigner {
+    command: String,
+    public_key: Option<FieldElement>,
+}
+
+impl CommandSigner {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self { command: command.into(), public_key: None }
+    }
+
+    pub fn with_public_key(mut self, public_key: FieldElement) -> Self {
+        self.public_key = Some(public_key);
+        self
+    }
+
+    async fn run(&self, hash: &FieldElement) -> Result<String, CommandSignerError> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} {hash:#x}", self.command))
+            .output()
+            .await?;
+
+        if !output.status.success() {
+            return Err(CommandSignerError::Failed {
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            });
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[async_trait]
+impl Signer for CommandSigner {
+    type GetPublicKeyError = CommandSignerError;
+    type SignError = CommandSignerError;
+
+    async fn get_public_key(&self) -> Result<VerifyingKey, Self::GetPublicKeyError> {
+        self.public_key
+            .map(VerifyingKey::from_scalar)
+            .ok_or(CommandSignerError::PublicKeyUnavailable)
+    }
+
+    async fn sign_hash(&self, hash: &FieldElement) -> Result<Signature, Self::SignError> {
+        let output = self.run(hash).await?;
+        parse_signature(&output).ok_or(CommandSignerError::InvalidOutput(output))
+    }
+}
+
+fn parse_signature(output: &str) -> Option<Signature> {
+    let mut parts = output.split_whitespace().map(FieldElement::from_hex_be);
+
+    let r = parts.next()?.ok()?;
+    let s = parts.next()?.ok()?;
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(Signature { r, s })
+}
@@ -0,0 +1,24 @@
+use starknet::core::types::FieldElement;
+use starknet::signers::Signer;
+
+use super::{parse_signature, CommandSigner};
+
+#[test]
+fn test_parse_signature() {
+    let signature = parse_signature("0x1 0x2\n").unwrap();
+    assert_eq!(signature.r, FieldElement::ONE);
+    assert_eq!(signature.s, FieldElement::TWO);
+
+    assert!(parse_signature("0x1").is_none());
+    assert!(parse_signature("0x1 0x2 0x3").is_none());
+    assert!(parse_signature("foo bar").is_none());
+}
+
+#[tokio::test]
+async fn test_sign_hash_with_command() {
+    let signer = CommandSigner::new("echo 0x3");
+    let signature = signer.sign_hash(&FieldElement::ONE).await.unwrap();
+
+    assert_eq!(signature.r, FieldElement::THREE);
+    assert_eq!(signature.s, FieldElement::ONE);
+}
@@ -0,0 +1,80 @@
+//! The accounts predeployed by a devnet such as katana, derived from its seed so that tools and
+//! tests can use them without reading them from the devnet's output.
+
+use rand::rngs::SmallRng;
+use rand::{RngCore, SeedableRng};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::get_contract_address;
+use starknet::signers::SigningKey;
+
+#[cfg(test)]
+#[path = "devnet_test.rs"]
+mod test;
+
+/// Seed of the devnet when none is given.
+pub const DEFAULT_SEED: [u8; 32] = [0; 32];
+
+/// Number of accounts predeployed when not configured.
+pub const DEFAULT_TOTAL_ACCOUNTS: u8 = 10;
+
+/// Class hash of the account contract predeployed by default.
+pub const DEFAULT_ACCOUNT_CLASS_HASH: FieldElement = FieldElement::from_mont([
+    6326636397321439257,
+    10506760794544333483,
+    15183387117217141275,
+    182561485776497992,
+]);
+
+/// Salt the accounts are deployed with, their addresses depend on it.
+const ACCOUNT_SALT: u64 = 666;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DevAccount {
+    pub address: FieldElement,
+    pub private_key: FieldElement,
+    pub public_key: FieldElement,
+}
+
+/// Parses the seed given to the devnet as a string: its first 32 bytes, padded with zeros.
+pub fn parse_seed(seed: &str) -> [u8; 32] {
+    let mut parsed = [0u8; 32];
+    let bytes = &seed.as_bytes()[..seed.len().min(32)];
+    parsed[..bytes.len()].copy_from_slice(bytes);
+    parsed
+}
+
+/// Derives the first `total` accounts of a devnet started with `seed`, each deployed from
+/// `class_hash` with its public key as the only constructor argument.
+pub fn dev_accounts(seed: [u8; 32], total: u8, class_hash: FieldElement) -> Vec<DevAccount> {
+    let mut seed = seed;
+    let mut accounts = vec![];
+
+    for _ in 0..total {
+        let mut rng = SmallRng::from_seed(seed);
+        let mut private_key_bytes = [0u8; 32];
+
+        rng.fill_bytes(&mut private_key_bytes);
+        // Keeps the key below the field's modulus.
+        private_key_bytes[0] %= 0x9;
+        seed = private_key_bytes;
+
+        let private_key = FieldElement::from_bytes_be(&private_key_bytes)
+            .expect("private key should be a valid field element");
+        let public_key = SigningKey::from_secret_scalar(private_key).verifying_key().scalar();
+        let address = get_contract_address(
+            FieldElement::from(ACCOUNT_SALT),
+            class_hash,
+            &[public_key],
+            FieldElement::ZERO,
+        );
+
+        accounts.push(DevAccount { address, private_key, public_key });
+    }
+
+    accounts
+}
+
+/// The accounts of a devnet started with the default seed and account class.
+pub fn default_dev_accounts(total: u8) -> Vec<DevAccount> {
+    dev_accounts(DEFAULT_SEED, total, DEFAULT_ACCOUNT_CLASS_HASH)
+}
@@ -0,0 +1,45 @@
+use starknet::core::types::FieldElement;
+
+use super::{default_dev_accounts, parse_seed, DEFAULT_ACCOUNT_CLASS_HASH};
+
+#[test]
+fn test_default_account_class_hash() {
+    assert_eq!(
+        DEFAULT_ACCOUNT_CLASS_HASH,
+        FieldElement::from_hex_be(
+            "0x04d07e40e93398ed3c76981e72dd1fd22557a78ce36c0515f679e27f0bb5bc5f"
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn test_default_dev_accounts() {
+    let accounts = default_dev_accounts(3);
+    assert_eq!(accounts.len(), 3);
+    assert_eq!(
+        accounts[0].address,
+        FieldElement::from_hex_be(
+            "0x6f62894bfd81d2e396ce266b2ad0f21e0668d604e5bb1077337b6d570a54aea"
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        accounts[0].private_key,
+        FieldElement::from_hex_be(
+            "0x7230b49615d175307d580c33d6fda61fc7b9aec91df0f5c1a5ebe3b8cbfee02"
+        )
+        .unwrap()
+    );
+
+    // Each account is derived from the previous one.
+    assert_eq!(default_dev_accounts(1), accounts[..1]);
+    assert_ne!(accounts[1].address, accounts[2].address);
+}
+
+#[test]
+fn test_parse_seed() {
+    assert_eq!(parse_seed(""), [0; 32]);
+    assert_eq!(&parse_seed("dojo")[..5], b"dojo\0");
+    assert_eq!(parse_seed(&"a".repeat(40)), [b'a'; 32]);
+}
@@ -1,7 +1,15 @@
 use std::env;
 
+use async_trait::async_trait;
+use starknet::core::crypto::Signature;
 use starknet::core::types::FieldElement;
-use starknet::signers::{LocalWallet, SigningKey};
+use starknet::signers::{LocalWallet, Signer, SigningKey, VerifyingKey};
+use thiserror::Error;
+
+pub mod command;
+pub mod devnet;
+
+use self::command::{CommandSigner, CommandSignerError};
 
 pub trait FromEnv {
     fn from_env() -> anyhow::Result<Self>
@@ -17,3 +25,62 @@ impl FromEnv for LocalWallet {
         Ok(LocalWallet::from_signing_key(SigningKey::from_secret_scalar(private_key)))
     }
 }
+
+#[derive(Debug, Error)]
+pub enum DojoSignerError {
+    #[error(transparent)]
+    Local(<LocalWallet as Signer>::SignError),
+    #[error(transparent)]
+    Command(CommandSignerError),
+}
+
+#[derive(Debug, Error)]
+pub enum DojoGetPublicKeyError {
+    #[error(transparent)]
+    Local(<LocalWallet as Signer>::GetPublicKeyError),
+    #[error(transparent)]
+    Command(CommandSignerError),
+}
+
+/// Any of the signers that can be configured for a Dojo environment.
+#[derive(Debug, Clone)]
+pub enum DojoSigner {
+    Local(LocalWallet),
+    Command(CommandSigner),
+}
+
+#[async_trait]
+impl Signer for DojoSigner {
+    type GetPublicKeyError = DojoGetPublicKeyError;
+    type SignError = DojoSignerError;
+
+    async fn get_public_key(&self) -> Result<VerifyingKey, Self::GetPublicKeyError> {
+        match self {
+            Self::Local(signer) => {
+                signer.get_public_key().await.map_err(DojoGetPublicKeyError::Local)
+            }
+            Self::Command(signer) => {
+                signer.get_public_key().await.map_err(DojoGetPublicKeyError::Command)
+            }
+        }
+    }
+
+    async fn sign_hash(&self, hash: &FieldElement) -> Result<Signature, Self::SignError> {
+        match self {
+            Self::Local(signer) => signer.sign_hash(hash).await.map_err(DojoSignerError::Local),
+            Self::Command(signer) => signer.sign_hash(hash).await.map_err(DojoSignerError::Command),
+        }
+    }
+}
+
+impl From<LocalWallet> for DojoSigner {
+    fn from(signer: LocalWallet) -> Self {
+        Self::Local(signer)
+    }
+}
+
+impl From<CommandSigner> for DojoSigner {
+    fn from(signer: CommandSigner) -> Self {
+        Self::Command(signer)
+    }
+}
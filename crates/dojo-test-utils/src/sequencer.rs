@@ -1,5 +1,6 @@
 use std::sync::Arc;
 
+use dojo_signers::devnet::default_dev_accounts;
 use jsonrpsee::core::Error;
 use jsonrpsee::server::ServerHandle;
 use katana_core::sequencer::KatanaSequencer;
@@ -10,19 +11,8 @@ use starknet::core::types::FieldElement;
 use tokio::sync::RwLock;
 use url::Url;
 
-const ACCOUNT_ADDRESS: FieldElement = FieldElement::from_mont([
-    17796827018638541424,
-    17823930040347334339,
-    3831215415084348690,
-    569269283564471682,
-]);
-
-const ACCOUNT_PK: FieldElement = FieldElement::from_mont([
-    1113403281716850492,
-    5176054014765279300,
-    6075185975736318605,
-    165462628152687232,
-]);
+/// Number of accounts predeployed on the sequencer.
+const TOTAL_ACCOUNTS: u8 = 2;
 
 pub struct Account {
     pub private_key: FieldElement,
@@ -37,7 +27,7 @@ pub struct Sequencer {
 impl Sequencer {
     pub async fn start() -> Sequencer {
         let sequencer = Arc::new(RwLock::new(KatanaSequencer::new(StarknetConfig {
-            total_accounts: 1,
+            total_accounts: TOTAL_ACCOUNTS,
             allow_zero_max_fee: true,
             ..StarknetConfig::default()
         })));
@@ -54,7 +44,16 @@ impl Sequencer {
     }
 
     pub fn account(&self) -> Account {
-        Account { address: ACCOUNT_ADDRESS, private_key: ACCOUNT_PK }
+        let account = default_dev_accounts(1)[0];
+        Account { address: account.address, private_key: account.private_key }
+    }
+
+    /// The predeployed accounts, the first one being [`Sequencer::account`].
+    pub fn accounts(&self) -> Vec<Account> {
+        default_dev_accounts(TOTAL_ACCOUNTS)
+            .into_iter()
+            .map(|account| Account { address: account.address, private_key: account.private_key })
+            .collect()
     }
 
     pub fn stop(&self) -> Result<(), Error> {
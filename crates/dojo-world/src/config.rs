@@ -1,14 +1,41 @@
-use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use camino::Utf8PathBuf;
+use dojo_signers::command::CommandSigner;
+use dojo_signers::DojoSigner;
 use scarb::core::Workspace;
 use serde::{Deserialize, Serialize};
-use starknet::accounts::SingleOwnerAccount;
+use starknet::accounts::{
+    Account, AccountError, ConnectedAccount, Declaration, Execution, SingleOwnerAccount,
+};
 use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
 use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
 use starknet::providers::Provider;
 use starknet::signers::{LocalWallet, SigningKey};
 use toml::Value;
 use url::Url;
 
+use crate::migration::dispatcher::Dispatcher;
+use crate::migration::fee::{
+    EstimateMultiplier, FeeStrategy, FeeStrategyConfig, FixedFee, HistoricalPercentile,
+    DEFAULT_FEE_ESTIMATE_MULTIPLIER,
+};
+use crate::migration::finality::Finality;
+use crate::migration::retry::{BackoffCurve, RetryPolicy};
+use crate::migration::schema::SchemaUpgrade;
+
+#[cfg(test)]
+#[path = "config_test.rs"]
+mod test;
+
+/// File next to the workspace's Scarb.toml mapping key aliases to accounts.
+pub const KEYS_FILE: &str = "keys.toml";
+
 #[allow(clippy::enum_variant_names)]
 #[derive(thiserror::Error, Debug)]
 pub enum DeserializationError {
@@ -60,6 +87,17 @@ impl WorldConfig {
     }
 }
 
+/// Transforms of the components upgraded from another schema, by component name, from the
+/// `[tool.dojo.upgrades]` table.
+pub fn schema_upgrades_from_workspace(
+    ws: &Workspace<'_>,
+) -> Result<HashMap<String, SchemaUpgrade>> {
+    match dojo_metadata_from_workspace(ws).and_then(|metadata| metadata.get("upgrades").cloned()) {
+        Some(upgrades) => upgrades.try_into().context("Invalid `[tool.dojo.upgrades]`"),
+        None => Ok(HashMap::new()),
+    }
+}
+
 #[derive(Debug, Deserialize, Clone, Default)]
 pub struct EnvironmentConfig {
     pub rpc: Option<Url>,
@@ -67,6 +105,329 @@ pub struct EnvironmentConfig {
     pub account_address: Option<FieldElement>,
     pub keystore_path: Option<String>,
     pub keystore_password: Option<String>,
+    pub signer_command: Option<String>,
+    /// Additional accounts sending transactions in parallel with the main one.
+    #[serde(default)]
+    pub accounts: Vec<AccountConfig>,
+    /// Multiplier applied to the estimated fee of a transaction to get its max fee.
+    pub fee_estimate_multiplier: Option<f64>,
+    /// How the max fee of a transaction is decided, from its estimate times
+    /// `fee_estimate_multiplier` by default.
+    #[serde(skip)]
+    pub fee_strategy: Option<FeeStrategyConfig>,
+    /// Finality awaited between dependent migration steps, defaults to the chain's.
+    pub finality: Option<Finality>,
+    /// Chains on which changes to the world are confirmed before being sent, mainnet when not
+    /// set.
+    pub protected_chains: Option<Vec<FieldElement>>,
+    /// Attempts of an RPC operation failing to reach the node, the first one included.
+    pub rpc_max_attempts: Option<u32>,
+    /// Wait before retrying a failed RPC operation, in milliseconds, growing after every retry
+    /// following `rpc_retry_backoff_curve`.
+    pub rpc_retry_backoff_ms: Option<u64>,
+    /// Cap on the wait between two attempts of an RPC operation, in milliseconds.
+    pub rpc_retry_max_backoff_ms: Option<u64>,
+    /// How the wait between two attempts grows, exponentially by default.
+    pub rpc_retry_backoff_curve: Option<BackoffCurve>,
+    /// Time after which an RPC call without answer fails, to be retried, in milliseconds.
+    pub rpc_timeout_ms: Option<u64>,
+    /// Time after which a sent transaction still unknown to the node is considered dropped, in
+    /// milliseconds.
+    pub receipt_timeout_ms: Option<u64>,
+}
+
+/// Max fee of the transactions sent, decided by a [`FeeStrategy`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FeeConfig {
+    pub strategy: FeeStrategyConfig,
+    /// Runs every transaction through the fee estimation, which executes it without sending it,
+    /// before sending it, so a failing transaction is never sent even with a fixed max fee.
+    pub simulate: bool,
+}
+
+impl FeeConfig {
+    /// Sets the max fee of the execution following the strategy, estimating its fee first if the
+    /// strategy needs it.
+    pub async fn execution<'a, A>(
+        &self,
+        provider: &A::Provider,
+        execution: Execution<'a, A>,
+    ) -> Result<Execution<'a, A>, AccountError<A::SignError, <A::Provider as Provider>::Error>>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        let estimated_fee = if self.strategy.needs_estimate() {
+            execution.estimate_fee().await?.overall_fee
+        } else {
+            0
+        };
+        let max_fee =
+            self.strategy.max_fee(provider, estimated_fee).await.map_err(AccountError::Provider)?;
+        Ok(execution.max_fee(max_fee))
+    }
+
+    /// Same as [`FeeConfig::execution`], for the declaration of a class.
+    pub async fn declaration<'a, A>(
+        &self,
+        provider: &A::Provider,
+        declaration: Declaration<'a, A>,
+    ) -> Result<Declaration<'a, A>, AccountError<A::SignError, <A::Provider as Provider>::Error>>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        let estimated_fee = if self.strategy.needs_estimate() {
+            declaration.estimate_fee().await?.overall_fee
+        } else {
+            0
+        };
+        let max_fee =
+            self.strategy.max_fee(provider, estimated_fee).await.map_err(AccountError::Provider)?;
+        Ok(declaration.max_fee(max_fee))
+    }
+
+    /// Simulates the execution if `simulate` is set, the error holding the revert reason. The
+    /// strategies estimating the fee already simulated it.
+    pub async fn simulate_execution<A>(
+        &self,
+        execution: &Execution<'_, A>,
+    ) -> Result<(), AccountError<A::SignError, <A::Provider as Provider>::Error>>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        if self.simulate && !self.strategy.needs_estimate() {
+            execution.estimate_fee().await?;
+        }
+        Ok(())
+    }
+
+    /// Simulates the declaration if `simulate` is set, the error holding the revert reason.
+    pub async fn simulate_declaration<A>(
+        &self,
+        declaration: &Declaration<'_, A>,
+    ) -> Result<(), AccountError<A::SignError, <A::Provider as Provider>::Error>>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        if self.simulate && !self.strategy.needs_estimate() {
+            declaration.estimate_fee().await?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct AccountConfig {
+    pub account_address: FieldElement,
+    pub private_key: FieldElement,
+}
+
+/// An account referred to by its alias from the environment config's `account`, so that
+/// switching identities doesn't require editing the environment config.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct KeyAlias {
+    pub account_address: FieldElement,
+    pub private_key: Option<FieldElement>,
+    pub keystore_path: Option<String>,
+    pub keystore_password: Option<String>,
+    pub signer_command: Option<String>,
+}
+
+/// Parses a chain id given as hex or as its short string, such as `SN_MAIN`.
+pub fn parse_chain_id(chain_id: &str) -> Result<FieldElement> {
+    if chain_id.starts_with("0x") {
+        FieldElement::from_hex_be(chain_id).map_err(|_| anyhow!("Invalid chain id `{chain_id}`"))
+    } else {
+        cairo_short_string_to_felt(chain_id).map_err(|_| anyhow!("Invalid chain id `{chain_id}`"))
+    }
+}
+
+/// Reads the strategy named `strategy` and its settings from the environment config:
+///
+/// - `estimate`: the estimated fee times `fee_estimate_multiplier`.
+/// - `fixed`: `max_fee` for every transaction.
+/// - `percentile`: the `fee_percentile`th percentile, 75 by default, of the max fees of the
+///   transactions of the last `fee_history_blocks` blocks, 10 by default, or the estimate times
+///   `fee_estimate_multiplier` if it's higher.
+fn fee_strategy_from_env(
+    strategy: &str,
+    env: &Value,
+    multiplier: f64,
+) -> Result<FeeStrategyConfig> {
+    match strategy {
+        "estimate" => Ok(FeeStrategyConfig::Estimate(EstimateMultiplier { multiplier })),
+        "fixed" => {
+            let max_fee = env
+                .get("max_fee")
+                .and_then(|v| {
+                    v.as_str()
+                        .and_then(|s| {
+                            if s.starts_with("0x") {
+                                FieldElement::from_hex_be(s).ok()
+                            } else {
+                                FieldElement::from_dec_str(s).ok()
+                            }
+                        })
+                        .or(v.as_integer().and_then(|i| u64::try_from(i).ok()).map(Into::into))
+                })
+                .ok_or(anyhow!("The `fixed` fee strategy requires a `max_fee`, in wei"))?;
+            Ok(FeeStrategyConfig::Fixed(FixedFee { max_fee }))
+        }
+        "percentile" => {
+            let default = HistoricalPercentile::default();
+            let percentile = match env.get("fee_percentile") {
+                Some(percentile) => percentile
+                    .as_integer()
+                    .filter(|p| (1..=100).contains(p))
+                    .map(|p| p as u8)
+                    .ok_or(anyhow!("`fee_percentile` must be between 1 and 100"))?,
+                None => default.percentile,
+            };
+            let blocks = match env.get("fee_history_blocks") {
+                Some(blocks) => blocks
+                    .as_integer()
+                    .filter(|b| *b > 0)
+                    .map(|b| b as u64)
+                    .ok_or(anyhow!("`fee_history_blocks` must be a positive integer"))?,
+                None => default.blocks,
+            };
+            Ok(FeeStrategyConfig::Percentile(HistoricalPercentile {
+                percentile,
+                blocks,
+                multiplier,
+            }))
+        }
+        _ => Err(anyhow!(
+            "Unknown fee strategy `{strategy}`, expected `estimate`, `fixed` or `percentile`"
+        )),
+    }
+}
+
+/// Loads a keys file, a table of [`KeyAlias`] by alias:
+///
+/// ```toml
+/// [deployer]
+/// account_address = "0x123"
+/// keystore_path = "/home/alice/.starknet/deployer.json"
+/// ```
+///
+/// Keystore paths are used as is, `~` isn't expanded.
+pub fn load_key_aliases<P>(path: P) -> Result<HashMap<String, KeyAlias>>
+where
+    P: AsRef<Path>,
+{
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.as_ref().display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.as_ref().display()))
+}
+
+/// Where the signer of an environment is configured: a table of Scarb.toml, or of the keys file
+/// when the environment uses a key alias.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerLocation {
+    pub path: Utf8PathBuf,
+    /// Dotted name of the table, such as `tool.dojo.env.release`.
+    pub table: String,
+}
+
+impl SignerLocation {
+    /// Mirrors the lookup of [`EnvironmentConfig::from_workspace`].
+    pub fn from_workspace<T: AsRef<str>>(profile: T, ws: &Workspace<'_>) -> Self {
+        let env =
+            dojo_metadata_from_workspace(ws).and_then(|metadata| metadata.get("env").cloned());
+        let profile_env = env.as_ref().and_then(|env| env.get(profile.as_ref())).cloned();
+
+        let alias = profile_env
+            .as_ref()
+            .or(env.as_ref())
+            .and_then(|env| env.get("account").and_then(|v| v.as_str().map(|s| s.to_string())))
+            .or(std::env::var("DOJO_ACCOUNT").ok());
+
+        match (alias, profile_env) {
+            (Some(alias), _) => {
+                Self { path: ws.manifest_path().with_file_name(KEYS_FILE), table: alias }
+            }
+            (None, Some(_)) => Self {
+                path: ws.manifest_path().to_path_buf(),
+                table: format!("tool.dojo.env.{}", profile.as_ref()),
+            },
+            (None, None) => {
+                Self { path: ws.manifest_path().to_path_buf(), table: "tool.dojo.env".into() }
+            }
+        }
+    }
+
+    /// Applies `updates` to the table, replacing the file atomically so that an interrupted
+    /// write can't leave it half updated.
+    pub fn update(&self, updates: &[(&str, KeyUpdate)]) -> Result<()> {
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read {}", self.path))?;
+        let updated = update_toml_table(&content, &self.table, updates)
+            .with_context(|| format!("Failed to update {}", self.path))?;
+
+        let tmp_path = self.path.with_extension("toml.tmp");
+        fs::write(&tmp_path, updated).with_context(|| format!("Failed to write {tmp_path}"))?;
+        fs::rename(&tmp_path, &self.path)
+            .with_context(|| format!("Failed to replace {}", self.path))
+    }
+}
+
+/// Change to a string key of a TOML table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeyUpdate {
+    /// Sets the key, adding it at the end of the table if missing.
+    Set(String),
+    /// Sets the key only if the table already has it.
+    Replace(String),
+    Remove,
+}
+
+/// Applies `updates` to the keys of `[table]`, keeping the rest of the document as written,
+/// comments included. Only tables declared with a header are supported.
+pub fn update_toml_table(
+    content: &str,
+    table: &str,
+    updates: &[(&str, KeyUpdate)],
+) -> Result<String> {
+    let mut lines: Vec<String> = content.lines().map(|line| line.to_string()).collect();
+    let header = format!("[{table}]");
+    let start = lines
+        .iter()
+        .position(|line| line.trim() == header)
+        .ok_or_else(|| anyhow!("Missing `{header}` table"))?
+        + 1;
+
+    for (key, update) in updates {
+        let end = lines[start..]
+            .iter()
+            .position(|line| line.trim_start().starts_with('['))
+            .map_or(lines.len(), |offset| start + offset);
+        let existing = (start..end).find(|&i| {
+            let line = lines[i].trim_start();
+            line.strip_prefix(key).map_or(false, |rest| rest.trim_start().starts_with('='))
+        });
+        let line = |value: &str| {
+            format!("{key} = \"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+        };
+
+        match (update, existing) {
+            (KeyUpdate::Set(value) | KeyUpdate::Replace(value), Some(i)) => lines[i] = line(value),
+            (KeyUpdate::Set(value), None) => {
+                // after the last key, before the blank lines separating the next table
+                let last = (start..end).rev().find(|&i| !lines[i].trim().is_empty());
+                lines.insert(last.map_or(start, |i| i + 1), line(value));
+            }
+            (KeyUpdate::Remove, Some(i)) => {
+                lines.remove(i);
+            }
+            (KeyUpdate::Replace(_) | KeyUpdate::Remove, None) => {}
+        }
+    }
+
+    let mut updated = lines.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    Ok(updated)
 }
 
 impl EnvironmentConfig {
@@ -115,6 +476,14 @@ impl EnvironmentConfig {
                 config.keystore_password = Some(password);
             }
 
+            if let Some(command) = env
+                .get("signer_command")
+                .and_then(|v| v.as_str().map(|s| s.to_string()))
+                .or(std::env::var("DOJO_SIGNER_COMMAND").ok())
+            {
+                config.signer_command = Some(command);
+            }
+
             if let Some(account_address) = env
                 .get("account_address")
                 .and_then(|v| v.as_str().map(|s| s.to_string()))
@@ -124,14 +493,182 @@ impl EnvironmentConfig {
                     .map_err(|_| DeserializationError::ParsingFieldElement)?;
                 config.account_address = Some(address);
             }
+
+            if let Some(accounts) = env.get("accounts").and_then(|v| v.as_array()) {
+                for account in accounts {
+                    let field = |name: &str| {
+                        account
+                            .get(name)
+                            .and_then(|v| v.as_str())
+                            .and_then(|v| FieldElement::from_hex_be(v).ok())
+                            .ok_or(DeserializationError::ParsingFieldElement)
+                    };
+                    config.accounts.push(AccountConfig {
+                        account_address: field("account_address")?,
+                        private_key: field("private_key")?,
+                    });
+                }
+            }
+
+            if let Some(multiplier) = env.get("fee_estimate_multiplier") {
+                let multiplier = multiplier
+                    .as_float()
+                    .or(multiplier.as_integer().map(|m| m as f64))
+                    .filter(|m| *m > 0.0)
+                    .ok_or(anyhow!("`fee_estimate_multiplier` must be a positive number"))?;
+                config.fee_estimate_multiplier = Some(multiplier);
+            }
+
+            if let Some(strategy) = env.get("fee_strategy").and_then(|v| v.as_str()) {
+                let multiplier =
+                    config.fee_estimate_multiplier.unwrap_or(DEFAULT_FEE_ESTIMATE_MULTIPLIER);
+                config.fee_strategy = Some(fee_strategy_from_env(strategy, &env, multiplier)?);
+            }
+
+            if let Some(finality) = env.get("finality").and_then(|v| v.as_str()) {
+                config.finality = Some(finality.parse()?);
+            }
+
+            if let Some(chains) = env.get("protected_chains") {
+                let chains = chains
+                    .as_array()
+                    .ok_or(anyhow!("`protected_chains` must be an array of chain ids"))?;
+                let chains = chains
+                    .iter()
+                    .map(|chain| {
+                        chain
+                            .as_str()
+                            .ok_or(anyhow!("`protected_chains` must be an array of chain ids"))
+                            .and_then(parse_chain_id)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                config.protected_chains = Some(chains);
+            }
+
+            if let Some(attempts) = env.get("rpc_max_attempts") {
+                let attempts = attempts
+                    .as_integer()
+                    .filter(|a| *a > 0)
+                    .and_then(|a| u32::try_from(a).ok())
+                    .ok_or(anyhow!("`rpc_max_attempts` must be a positive integer"))?;
+                config.rpc_max_attempts = Some(attempts);
+            }
+
+            if let Some(backoff) = env.get("rpc_retry_backoff_ms") {
+                let backoff = backoff
+                    .as_integer()
+                    .and_then(|b| u64::try_from(b).ok())
+                    .ok_or(anyhow!("`rpc_retry_backoff_ms` must be a number of milliseconds"))?;
+                config.rpc_retry_backoff_ms = Some(backoff);
+            }
+
+            if let Some(backoff) = env.get("rpc_retry_max_backoff_ms") {
+                let backoff = backoff.as_integer().and_then(|b| u64::try_from(b).ok()).ok_or(
+                    anyhow!("`rpc_retry_max_backoff_ms` must be a number of milliseconds"),
+                )?;
+                config.rpc_retry_max_backoff_ms = Some(backoff);
+            }
+
+            if let Some(curve) = env.get("rpc_retry_backoff_curve").and_then(|v| v.as_str()) {
+                config.rpc_retry_backoff_curve = Some(curve.parse()?);
+            }
+
+            if let Some(timeout) = env.get("rpc_timeout_ms") {
+                let timeout = timeout
+                    .as_integer()
+                    .filter(|t| *t > 0)
+                    .and_then(|t| u64::try_from(t).ok())
+                    .ok_or(anyhow!("`rpc_timeout_ms` must be a positive number of milliseconds"))?;
+                config.rpc_timeout_ms = Some(timeout);
+            }
+
+            if let Some(timeout) = env.get("receipt_timeout_ms") {
+                let timeout = timeout
+                    .as_integer()
+                    .filter(|t| *t > 0)
+                    .and_then(|t| u64::try_from(t).ok())
+                    .ok_or(anyhow!(
+                        "`receipt_timeout_ms` must be a positive number of milliseconds"
+                    ))?;
+                config.receipt_timeout_ms = Some(timeout);
+            }
+
+            if let Some(alias) = env
+                .get("account")
+                .and_then(|v| v.as_str().map(|s| s.to_string()))
+                .or(std::env::var("DOJO_ACCOUNT").ok())
+            {
+                let keys_path = ws.manifest_path().with_file_name(KEYS_FILE);
+                let aliases = load_key_aliases(&keys_path)?;
+                let key = aliases
+                    .get(&alias)
+                    .ok_or_else(|| anyhow!("Key alias `{alias}` isn't defined in {keys_path}"))?;
+                config.use_key_alias(key);
+            }
         }
 
         Ok(config)
     }
 
-    pub fn signer(&self) -> Result<LocalWallet> {
+    /// Uses the account and signer of `key` instead of those of the environment config. The
+    /// keystore password is kept when the alias doesn't set one.
+    pub fn use_key_alias(&mut self, key: &KeyAlias) {
+        self.account_address = Some(key.account_address);
+        self.private_key = key.private_key;
+        self.keystore_path = key.keystore_path.clone();
+        self.signer_command = key.signer_command.clone();
+        if key.keystore_password.is_some() {
+            self.keystore_password = key.keystore_password.clone();
+        }
+    }
+
+    /// Fees of the transactions sent, each one's max fee being `max_fee` when set.
+    pub fn fee_config(&self, max_fee: Option<FieldElement>) -> FeeConfig {
+        let strategy = match (max_fee, self.fee_strategy, self.fee_estimate_multiplier) {
+            (Some(max_fee), ..) => FeeStrategyConfig::Fixed(FixedFee { max_fee }),
+            (None, Some(strategy), _) => strategy,
+            (None, None, Some(multiplier)) => {
+                FeeStrategyConfig::Estimate(EstimateMultiplier { multiplier })
+            }
+            (None, None, None) => FeeStrategyConfig::default(),
+        };
+        FeeConfig { strategy, simulate: false }
+    }
+
+    /// Finality awaited between dependent migration steps on the chain `chain_id`.
+    pub fn finality(&self, chain_id: FieldElement) -> Finality {
+        self.finality.unwrap_or_else(|| Finality::default_for_chain(chain_id))
+    }
+
+    /// Retries of the RPC operations failing to reach the node.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        let default = RetryPolicy::default();
+        RetryPolicy {
+            max_attempts: self.rpc_max_attempts.unwrap_or(default.max_attempts),
+            initial_backoff: self
+                .rpc_retry_backoff_ms
+                .map_or(default.initial_backoff, Duration::from_millis),
+            max_backoff: self
+                .rpc_retry_max_backoff_ms
+                .map_or(default.max_backoff, Duration::from_millis),
+            curve: self.rpc_retry_backoff_curve.unwrap_or(default.curve),
+            receipt_timeout: self
+                .receipt_timeout_ms
+                .map_or(default.receipt_timeout, Duration::from_millis),
+        }
+    }
+
+    /// Whether changes to the world on the chain `chain_id` must be confirmed before being sent.
+    pub fn is_protected(&self, chain_id: FieldElement) -> bool {
+        match &self.protected_chains {
+            Some(chains) => chains.contains(&chain_id),
+            None => cairo_short_string_to_felt("SN_MAIN").ok() == Some(chain_id),
+        }
+    }
+
+    pub fn signer(&self) -> Result<DojoSigner> {
         if let Some(private_key) = &self.private_key {
-            Ok(LocalWallet::from_signing_key(SigningKey::from_secret_scalar(*private_key)))
+            Ok(LocalWallet::from_signing_key(SigningKey::from_secret_scalar(*private_key)).into())
         } else if let Some(keystore_path) = &self.keystore_path {
             let keystore_password = self
                 .keystore_password
@@ -141,9 +678,15 @@ impl EnvironmentConfig {
             Ok(LocalWallet::from_signing_key(SigningKey::from_keystore(
                 keystore_path,
                 keystore_password,
-            )?))
+            )?)
+            .into())
+        } else if let Some(command) = &self.signer_command {
+            Ok(CommandSigner::new(command).into())
         } else {
-            Err(anyhow!("Missing `private_key` or `keystore_path` in the environment config"))
+            Err(anyhow!(
+                "Missing `private_key`, `keystore_path` or `signer_command` in the environment \
+                 config"
+            ))
         }
     }
 
@@ -152,7 +695,17 @@ impl EnvironmentConfig {
             return Err(anyhow!("Missing `rpc_url` in the environment config"))
         };
 
-        Ok(JsonRpcClient::new(HttpTransport::new(url.clone())))
+        let transport = match self.rpc_timeout_ms {
+            Some(timeout) => {
+                let client = reqwest::Client::builder()
+                    .timeout(Duration::from_millis(timeout))
+                    .build()
+                    .context("Failed to build the RPC client")?;
+                HttpTransport::new_with_client(url.clone(), client)
+            }
+            None => HttpTransport::new(url.clone()),
+        };
+        Ok(JsonRpcClient::new(transport))
     }
 
     pub fn account_address(&self) -> Result<FieldElement> {
@@ -161,7 +714,7 @@ impl EnvironmentConfig {
 
     pub async fn migrator(
         &self,
-    ) -> Result<SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>> {
+    ) -> Result<SingleOwnerAccount<JsonRpcClient<HttpTransport>, DojoSigner>> {
         let signer = self.signer()?;
         let account_address = self.account_address()?;
 
@@ -170,4 +723,28 @@ impl EnvironmentConfig {
 
         Ok(SingleOwnerAccount::new(provider, signer, account_address, chain_id))
     }
+
+    /// Dispatcher sending through the main account and the additional `accounts`.
+    pub async fn dispatcher(
+        &self,
+    ) -> Result<Dispatcher<SingleOwnerAccount<JsonRpcClient<HttpTransport>, DojoSigner>>> {
+        let migrator = self.migrator().await?;
+        let chain_id = migrator.chain_id();
+
+        let mut accounts = vec![migrator];
+        for account in &self.accounts {
+            let signer =
+                LocalWallet::from_signing_key(SigningKey::from_secret_scalar(account.private_key));
+            accounts.push(SingleOwnerAccount::new(
+                self.provider()?,
+                signer.into(),
+                account.account_address,
+                chain_id,
+            ));
+        }
+
+        Ok(Dispatcher::new(accounts)
+            .with_fees(self.fee_config(None))
+            .with_retry(self.retry_policy()))
+    }
 }
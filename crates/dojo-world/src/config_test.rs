@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use assert_fs::prelude::*;
+use assert_fs::TempDir;
+use starknet::core::types::FieldElement;
+
+use super::{
+    fee_strategy_from_env, load_key_aliases, parse_chain_id, update_toml_table, EnvironmentConfig,
+    KeyUpdate, KEYS_FILE,
+};
+use crate::migration::fee::{EstimateMultiplier, FeeStrategyConfig, FixedFee, HistoricalPercentile};
+use crate::migration::retry::{BackoffCurve, RetryPolicy};
+
+#[test]
+fn test_key_alias_resolution() {
+    let dir = TempDir::new().unwrap();
+    let keys = dir.child(KEYS_FILE);
+    keys.write_str(
+        r#"
+        [deployer]
+        account_address = "0x123"
+        keystore_path = "deployer.json"
+
+        [ci]
+        account_address = "0x456"
+        private_key = "0x789"
+        "#,
+    )
+    .unwrap();
+
+    let aliases = load_key_aliases(keys.path()).unwrap();
+    assert_eq!(aliases.len(), 2);
+
+    let mut config = EnvironmentConfig {
+        account_address: Some(FieldElement::ONE),
+        private_key: Some(FieldElement::TWO),
+        keystore_password: Some("password".into()),
+        ..EnvironmentConfig::default()
+    };
+
+    config.use_key_alias(&aliases["deployer"]);
+    assert_eq!(config.account_address, Some(FieldElement::from(0x123_u16)));
+    assert_eq!(config.private_key, None);
+    assert_eq!(config.keystore_path.as_deref(), Some("deployer.json"));
+    assert_eq!(config.keystore_password.as_deref(), Some("password"));
+
+    config.use_key_alias(&aliases["ci"]);
+    assert_eq!(config.account_address, Some(FieldElement::from(0x456_u16)));
+    assert_eq!(config.private_key, Some(FieldElement::from(0x789_u16)));
+    assert_eq!(config.keystore_path, None);
+}
+
+#[test]
+fn test_protected_chains() {
+    let mainnet = parse_chain_id("SN_MAIN").unwrap();
+    let goerli = parse_chain_id("SN_GOERLI").unwrap();
+    assert_eq!(parse_chain_id(&format!("{goerli:#x}")).unwrap(), goerli);
+
+    let config = EnvironmentConfig::default();
+    assert!(config.is_protected(mainnet));
+    assert!(!config.is_protected(goerli));
+
+    let config =
+        EnvironmentConfig { protected_chains: Some(vec![goerli]), ..EnvironmentConfig::default() };
+    assert!(!config.is_protected(mainnet));
+    assert!(config.is_protected(goerli));
+}
+
+#[test]
+fn test_retry_policy() {
+    let config = EnvironmentConfig::default();
+    assert_eq!(config.retry_policy(), RetryPolicy::default());
+
+    let config = EnvironmentConfig {
+        rpc_max_attempts: Some(1),
+        rpc_retry_backoff_ms: Some(100),
+        rpc_retry_max_backoff_ms: Some(1000),
+        rpc_retry_backoff_curve: Some(BackoffCurve::Linear),
+        receipt_timeout_ms: Some(60_000),
+        ..EnvironmentConfig::default()
+    };
+    let policy = config.retry_policy();
+    assert_eq!(policy.max_attempts, 1);
+    assert_eq!(policy.initial_backoff, Duration::from_millis(100));
+    assert_eq!(policy.max_backoff, Duration::from_millis(1000));
+    assert_eq!(policy.curve, BackoffCurve::Linear);
+    assert_eq!(policy.receipt_timeout, Duration::from_secs(60));
+}
+
+#[test]
+fn test_fee_strategy() {
+    let config = EnvironmentConfig::default();
+    assert_eq!(config.fee_config(None).strategy, FeeStrategyConfig::default());
+    assert_eq!(
+        config.fee_config(Some(FieldElement::TWO)).strategy,
+        FeeStrategyConfig::Fixed(FixedFee { max_fee: FieldElement::TWO })
+    );
+
+    let env = |content: &str| content.parse::<toml::Value>().unwrap();
+    assert_eq!(
+        fee_strategy_from_env("estimate", &env(""), 2.0).unwrap(),
+        FeeStrategyConfig::Estimate(EstimateMultiplier { multiplier: 2.0 })
+    );
+    assert_eq!(
+        fee_strategy_from_env("fixed", &env("max_fee = \"0x10\""), 2.0).unwrap(),
+        FeeStrategyConfig::Fixed(FixedFee { max_fee: FieldElement::from(16_u8) })
+    );
+    assert!(fee_strategy_from_env("fixed", &env(""), 2.0).is_err());
+    assert_eq!(
+        fee_strategy_from_env("percentile", &env("fee_percentile = 90"), 2.0).unwrap(),
+        FeeStrategyConfig::Percentile(HistoricalPercentile {
+            percentile: 90,
+            blocks: 10,
+            multiplier: 2.0,
+        })
+    );
+    assert!(fee_strategy_from_env("percentile", &env("fee_percentile = 0"), 2.0).is_err());
+    assert!(fee_strategy_from_env("median", &env(""), 2.0).is_err());
+}
+
+#[test]
+fn test_update_toml_table() {
+    let content = r#"[package]
+name = "game"
+
+[tool.dojo.env]
+rpc_url = "http://localhost:5050/"
+# the deployer
+account_address = "0x1"
+private_key = "0x2"
+
+[tool.dojo.env.release]
+private_key = "0x3"
+"#;
+
+    let updated = update_toml_table(
+        content,
+        "tool.dojo.env",
+        &[
+            ("private_key", KeyUpdate::Remove),
+            ("keystore_path", KeyUpdate::Set("keys/deployer.json".into())),
+            ("keystore_password", KeyUpdate::Replace("secret".into())),
+            ("account_address", KeyUpdate::Set("0x4".into())),
+        ],
+    )
+    .unwrap();
+
+    assert_eq!(
+        updated,
+        r#"[package]
+name = "game"
+
+[tool.dojo.env]
+rpc_url = "http://localhost:5050/"
+# the deployer
+account_address = "0x4"
+keystore_path = "keys/deployer.json"
+
+[tool.dojo.env.release]
+private_key = "0x3"
+"#
+    );
+
+    assert!(update_toml_table(content, "tool.dojo.env.dev", &[]).is_err());
+}
@@ -0,0 +1,59 @@
+use anyhow::{anyhow, Result};
+use serde_json::{json, Value};
+use starknet::core::types::FieldElement;
+use url::Url;
+
+/// 1 ETH, in wei.
+pub const DEFAULT_FUND_AMOUNT: u128 = 1_000_000_000_000_000_000;
+
+/// A development network endpoint able to mint fee tokens.
+#[derive(Debug, Clone)]
+pub enum Faucet {
+    /// The `katana_mint` JSON-RPC method exposed by katana on its RPC endpoint.
+    Katana(Url),
+    /// A starknet-devnet style `POST /mint` HTTP endpoint.
+    Devnet(Url),
+}
+
+impl Faucet {
+    pub async fn fund(&self, address: FieldElement, amount: u128) -> Result<()> {
+        let client = reqwest::Client::new();
+
+        match self {
+            Faucet::Katana(url) => {
+                let res: Value = client
+                    .post(url.clone())
+                    .json(&json!({
+                        "jsonrpc": "2.0",
+                        "id": 1,
+                        "method": "katana_mint",
+                        "params": [format!("{address:#x}"), amount],
+                    }))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .json()
+                    .await?;
+
+                if let Some(error) = res.get("error") {
+                    return Err(anyhow!("Katana failed to mint fee tokens: {error}"));
+                }
+            }
+
+            Faucet::Devnet(url) => {
+                client
+                    .post(url.join("mint")?)
+                    .json(&json!({
+                        "address": format!("{address:#x}"),
+                        "amount": amount,
+                        "lock": true,
+                    }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+        }
+
+        Ok(())
+    }
+}
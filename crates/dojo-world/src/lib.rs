@@ -1,3 +1,4 @@
 pub mod config;
+pub mod faucet;
 pub mod manifest;
 pub mod migration;
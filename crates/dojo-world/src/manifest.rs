@@ -11,11 +11,13 @@ use starknet::core::utils::{cairo_short_string_to_felt, CairoShortStringToFeltEr
 use starknet::providers::{Provider, ProviderError};
 use thiserror::Error;
 
+use crate::migration::schema::SchemaUpgrade;
+
 #[cfg(test)]
 #[path = "manifest_test.rs"]
 mod test;
 
-const EXECUTOR_ADDRESS_SLOT: FieldElement = FieldElement::from_mont([
+pub const EXECUTOR_ADDRESS_SLOT: FieldElement = FieldElement::from_mont([
     7467091854009816808,
     5217539096067869628,
     17301706476858600182,
@@ -56,6 +58,72 @@ pub struct Member {
     pub ty: String,
     pub slot: usize,
     pub offset: u8,
+    /// Width declared with `#[packed_bits(..)]`, when narrower than the width of the type.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bits: Option<u8>,
+    /// Variant names, ordered by discriminant, when the member is an enum.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variants: Option<Vec<String>>,
+    /// The `///` comments of the member.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
+}
+
+impl Member {
+    /// Number of bits the member occupies when stored in a packed slot, `None` if the type can't
+    /// be packed.
+    pub fn packed_bits(&self) -> Option<u8> {
+        self.bits.or_else(|| packed_bits(&self.ty))
+    }
+}
+
+/// Number of usable bits of a packed storage slot, a felt252 can't hold all 252 bits.
+pub const PACKED_SLOT_BITS: u16 = 251;
+
+/// Bit width of the types that can be packed together in a single storage slot.
+pub fn packed_bits(ty: &str) -> Option<u8> {
+    match ty {
+        "bool" => Some(1),
+        "u8" => Some(8),
+        "u16" => Some(16),
+        "u32" | "usize" => Some(32),
+        "u64" => Some(64),
+        "u128" => Some(128),
+        _ => None,
+    }
+}
+
+/// Assigns a storage slot and a bit offset to each member. Members with a bit width are appended
+/// in declaration order to the last packed slot, a new one being opened when they don't fit in
+/// what's left of it, and earlier slots are never filled again. The others get a slot of their
+/// own, without closing the packed slot being filled.
+pub fn storage_layout(bits: &[Option<u8>]) -> Vec<(usize, u8)> {
+    let mut layout = vec![];
+    let mut next_slot = 0;
+    // Packed slot being filled and the number of bits used in it.
+    let mut open_slot: Option<(usize, u16)> = None;
+
+    for bits in bits {
+        let Some(bits) = bits.map(u16::from) else {
+            layout.push((next_slot, 0));
+            next_slot += 1;
+            continue;
+        };
+
+        match open_slot {
+            Some((slot, used)) if used + bits <= PACKED_SLOT_BITS => {
+                layout.push((slot, used as u8));
+                open_slot = Some((slot, used + bits));
+            }
+            _ => {
+                layout.push((next_slot, 0));
+                open_slot = Some((next_slot, bits));
+                next_slot += 1;
+            }
+        }
+    }
+
+    layout
 }
 
 /// Represents a declaration of a component.
@@ -66,6 +134,15 @@ pub struct Component {
     pub members: Vec<Member>,
     #[serde_as(as = "UfeHex")]
     pub class_hash: FieldElement,
+    /// Package of the workspace defining the component, unknown for a deployed world.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<SmolStr>,
+    /// Transform of the existing values when the component is upgraded from another schema.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upgrade: Option<SchemaUpgrade>,
+    /// The `///` comments of the component's struct.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 /// System input ABI.
@@ -93,6 +170,15 @@ pub struct System {
     #[serde_as(as = "UfeHex")]
     pub class_hash: FieldElement,
     pub dependencies: Vec<String>,
+    /// Components the system reads, whether it writes them or not.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reads: Vec<String>,
+    /// Package of the workspace defining the system, unknown for a deployed world.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<SmolStr>,
+    /// The `///` comments of the system's module.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub doc: Option<String>,
 }
 
 #[serde_as]
@@ -103,6 +189,26 @@ pub struct Contract {
     pub class_hash: FieldElement,
 }
 
+/// Fingerprint of a compiled contract.
+#[serde_as]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ContractFingerprint {
+    pub name: SmolStr,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    /// Hash of the Cairo source of the contract's module.
+    #[serde_as(as = "UfeHex")]
+    pub source_hash: FieldElement,
+}
+
+/// Toolchain and sources a manifest was built from, used to check that a build is reproducible.
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct BuildInfo {
+    pub compiler_version: String,
+    pub plugin_version: String,
+    pub contracts: Vec<ContractFingerprint>,
+}
+
 #[serde_as]
 #[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Manifest {
@@ -113,6 +219,9 @@ pub struct Manifest {
     pub systems: Vec<System>,
     pub contracts: Vec<Contract>,
     pub components: Vec<Component>,
+    /// Only available on manifests generated by the compiler.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_info: Option<BuildInfo>,
 }
 
 impl Manifest {
@@ -219,6 +328,7 @@ impl Manifest {
             contracts: vec![],
             world: world_class_hash,
             executor: executor_class_hash,
+            build_info: None,
         })
     }
 }
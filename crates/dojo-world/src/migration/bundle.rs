@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::accounts::Call;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::FieldElement;
+
+use crate::migration::plan::{MigrationPlan, PlannedStep};
+
+#[cfg(test)]
+#[path = "bundle_test.rs"]
+mod test;
+
+/// A call of a [`CallBundle`].
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleCall {
+    #[serde_as(as = "UfeHex")]
+    pub to: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub selector: FieldElement,
+    #[serde_as(as = "Vec<UfeHex>")]
+    pub calldata: Vec<FieldElement>,
+    /// Always zero, calls don't transfer value on Starknet. Kept for the tools expecting it.
+    #[serde_as(as = "UfeHex")]
+    pub value: FieldElement,
+}
+
+impl From<&Call> for BundleCall {
+    fn from(call: &Call) -> Self {
+        Self {
+            to: call.to,
+            selector: call.selector,
+            calldata: call.calldata.clone(),
+            value: FieldElement::ZERO,
+        }
+    }
+}
+
+/// A class that must be declared before executing the calls of a [`CallBundle`].
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleDeclaration {
+    pub name: String,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+}
+
+/// The calls a migration would make, meant to be executed by a multisig or a governance process
+/// owning the world instead of the migrator account. Declarations aren't calls, anyone can send
+/// them beforehand.
+#[serde_as]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallBundle {
+    #[serde_as(as = "Option<UfeHex>")]
+    pub world_address: Option<FieldElement>,
+    pub declarations: Vec<BundleDeclaration>,
+    pub calls: Vec<BundleCall>,
+}
+
+impl CallBundle {
+    pub fn from_plan(plan: &MigrationPlan) -> Self {
+        let declarations = plan
+            .steps
+            .iter()
+            .filter_map(|step| match step {
+                PlannedStep::Declare { name, class_hash, .. } => {
+                    Some(BundleDeclaration { name: name.clone(), class_hash: *class_hash })
+                }
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            world_address: plan.world_address,
+            declarations,
+            calls: plan.calls.iter().map(BundleCall::from).collect(),
+        }
+    }
+
+    pub fn write_to_path(&self, path: impl AsRef<Path>) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
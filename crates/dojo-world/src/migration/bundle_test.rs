@@ -0,0 +1,42 @@
+use starknet::accounts::Call;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::get_selector_from_name;
+
+use super::CallBundle;
+use crate::migration::plan::{MigrationPlan, PlannedStep};
+
+#[test]
+fn test_bundle_from_plan() {
+    let world = FieldElement::from(0x1234_u32);
+    let class_hash = FieldElement::from(0x42_u32);
+    let plan = MigrationPlan {
+        world_address: Some(world),
+        steps: vec![
+            PlannedStep::Declare {
+                name: "MoveSystem".to_string(),
+                class_hash,
+                remote_class_hash: None,
+                estimated_fee: None,
+            },
+            PlannedStep::RegisterSystems { class_hashes: vec![class_hash], estimated_fee: None },
+        ],
+        estimated_fee: 0,
+        unestimated_steps: 2,
+        calls: vec![Call {
+            to: world,
+            selector: get_selector_from_name("register_system").unwrap(),
+            calldata: vec![class_hash],
+        }],
+    };
+
+    let bundle = CallBundle::from_plan(&plan);
+    assert_eq!(bundle.declarations.len(), 1);
+    assert_eq!(bundle.declarations[0].name, "MoveSystem");
+    assert_eq!(bundle.calls.len(), 1);
+    assert_eq!(bundle.calls[0].to, world);
+    assert_eq!(bundle.calls[0].value, FieldElement::ZERO);
+
+    let json = serde_json::to_value(&bundle).unwrap();
+    assert_eq!(json["world_address"], "0x1234");
+    assert_eq!(json["calls"][0]["calldata"][0], "0x42");
+}
@@ -0,0 +1,124 @@
+//! Classes known to be declared, shared by the projects and profiles of the machine so that
+//! migrations don't look up the same declarations again.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+#[cfg(test)]
+#[path = "cache_test.rs"]
+mod test;
+
+/// Environment variable overriding the directory of the cache, `~/.dojo/cache` by default.
+pub const CACHE_DIR_ENV: &str = "DOJO_CACHE_DIR";
+
+const DECLARED_CLASSES_FILE: &str = "declared_classes.json";
+
+/// Chains restarted from scratch under the same id, whose declarations can't be cached.
+const DEV_CHAINS: &[&str] = &["KATANA"];
+
+/// A class known to be declared on a chain.
+#[serde_as]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeclaredClass {
+    /// Transaction that declared the class, `None` if it was found already declared.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub transaction_hash: Option<FieldElement>,
+}
+
+/// Declarations are permanent, a class found here doesn't need to be looked up again on its
+/// chain. Development chains are never cached, a restart wipes their declarations.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DeclaredClasses {
+    /// Classes by chain id and class hash, both `0x` prefixed hex.
+    chains: BTreeMap<String, BTreeMap<String, DeclaredClass>>,
+    /// File the cache is saved to.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl DeclaredClasses {
+    /// File of the cache, `None` if there is no home directory to put it in.
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = match std::env::var_os(CACHE_DIR_ENV) {
+            Some(dir) => PathBuf::from(dir),
+            None => {
+                let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+                PathBuf::from(home).join(".dojo").join("cache")
+            }
+        };
+        Some(dir.join(DECLARED_CLASSES_FILE))
+    }
+
+    /// Loads the cache saved to `path`, empty if it doesn't exist yet.
+    pub fn load_from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let mut cache: Self = if path.exists() {
+            serde_json::from_reader(fs::File::open(path)?)
+                .map_err(|e| anyhow!("Failed to load the declared classes cache: {e}"))?
+        } else {
+            Self::default()
+        };
+        cache.path = Some(path.to_path_buf());
+        Ok(cache)
+    }
+
+    pub fn get(&self, chain_id: FieldElement, class_hash: FieldElement) -> Option<&DeclaredClass> {
+        if is_dev_chain(chain_id) {
+            return None;
+        }
+        self.chains.get(&format!("{chain_id:#x}"))?.get(&format!("{class_hash:#x}"))
+    }
+
+    pub fn insert(
+        &mut self,
+        chain_id: FieldElement,
+        class_hash: FieldElement,
+        class: DeclaredClass,
+    ) {
+        if is_dev_chain(chain_id) {
+            return;
+        }
+        self.chains
+            .entry(format!("{chain_id:#x}"))
+            .or_default()
+            .insert(format!("{class_hash:#x}"), class);
+    }
+
+    /// Number of classes cached, across all chains.
+    pub fn len(&self) -> usize {
+        self.chains.values().map(BTreeMap::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Writes the cache back to the file it was loaded from, if any.
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).context("Failed to write the declared classes cache")
+    }
+}
+
+fn is_dev_chain(chain_id: FieldElement) -> bool {
+    DEV_CHAINS.iter().any(|name| cairo_short_string_to_felt(name).ok() == Some(chain_id))
+}
@@ -0,0 +1,40 @@
+use assert_fs::TempDir;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+use super::{DeclaredClass, DeclaredClasses};
+
+#[test]
+fn test_declared_classes() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("cache").join("declared_classes.json");
+    let mainnet = FieldElement::from(1_u64);
+    let testnet = FieldElement::from(2_u64);
+    let class_hash = FieldElement::from(0x420_u64);
+
+    let mut cache = DeclaredClasses::load_from_path(&path).unwrap();
+    assert!(cache.is_empty());
+
+    let declared = DeclaredClass { transaction_hash: Some(FieldElement::from(0x1337_u64)) };
+    cache.insert(mainnet, class_hash, declared);
+    cache.insert(testnet, FieldElement::from(0x421_u64), DeclaredClass::default());
+    cache.save().unwrap();
+
+    let cache = DeclaredClasses::load_from_path(&path).unwrap();
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.get(mainnet, class_hash), Some(&declared));
+    assert_eq!(cache.get(testnet, class_hash), None);
+}
+
+#[test]
+fn test_dev_chains_are_not_cached() {
+    let dir = TempDir::new().unwrap();
+    let katana = cairo_short_string_to_felt("KATANA").unwrap();
+    let class_hash = FieldElement::from(0x420_u64);
+
+    let mut cache =
+        DeclaredClasses::load_from_path(dir.path().join("declared_classes.json")).unwrap();
+    cache.insert(katana, class_hash, DeclaredClass::default());
+    assert!(cache.is_empty());
+    assert_eq!(cache.get(katana, class_hash), None);
+}
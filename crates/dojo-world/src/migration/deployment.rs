@@ -0,0 +1,498 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::{
+    BlockId, BlockTag, EmittedEvent, EventFilter, FieldElement, MaybePendingTransactionReceipt,
+    TransactionReceipt,
+};
+use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
+use starknet::providers::Provider;
+
+use super::object::udc_address;
+use super::strategy::SubmittedTransaction;
+use crate::manifest::{Component, Manifest, Member, System, EXECUTOR_ADDRESS_SLOT};
+
+#[cfg(test)]
+#[path = "deployment_test.rs"]
+mod test;
+
+/// Number of events requested per page when reading the history of a world.
+const EVENTS_CHUNK_SIZE: u64 = 100;
+
+/// A contract deployed through the UDC, its address is derived from the class hash and the salt.
+#[serde_as]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeployedContract {
+    pub name: String,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub salt: FieldElement,
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub address: Option<FieldElement>,
+    /// Transaction that deployed the contract.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub transaction_hash: Option<FieldElement>,
+    #[serde(default)]
+    pub block_number: Option<u64>,
+}
+
+/// A class registered to the world.
+#[serde_as]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct RegisteredClass {
+    pub name: String,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    /// Transaction that registered the class.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub transaction_hash: Option<FieldElement>,
+    #[serde(default)]
+    pub block_number: Option<u64>,
+    /// Members of a component as registered, the previous schema of its next upgrade.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<Member>,
+}
+
+/// The `WorldSpawned` event emitted by the constructor of a world.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorldSpawn {
+    pub name: String,
+    /// Account that deployed the world.
+    #[serde_as(as = "UfeHex")]
+    pub creator: FieldElement,
+    #[serde_as(as = "UfeHex")]
+    pub transaction_hash: FieldElement,
+    pub block_number: u64,
+}
+
+/// Describes a deployed world precisely enough to reproduce it on another chain: the same
+/// classes, the same salts and the same registration order yield the same world. The
+/// transactions and blocks are those of the chain the world was migrated to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DeploymentManifest {
+    pub world: DeployedContract,
+    pub executor: DeployedContract,
+    /// Components in registration order.
+    pub components: Vec<RegisteredClass>,
+    /// Systems in registration order, without the `System` suffix.
+    pub systems: Vec<RegisteredClass>,
+    /// Fees of the migration that wrote the manifest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fees: Option<MigrationFees>,
+}
+
+/// Kind of a transaction sent by a migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionKind {
+    Declare,
+    Deploy,
+    Invoke,
+}
+
+impl TransactionKind {
+    pub fn of(tx: &SubmittedTransaction) -> Self {
+        match (tx.class_hash, tx.contract_address) {
+            (Some(_), Some(_)) => Self::Deploy,
+            (Some(_), None) => Self::Declare,
+            _ => Self::Invoke,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Declare => "declare",
+            Self::Deploy => "deploy",
+            Self::Invoke => "invoke",
+        }
+    }
+}
+
+/// Actual fee paid by a transaction of a migration, in wei.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TransactionFee {
+    pub description: String,
+    #[serde_as(as = "UfeHex")]
+    pub transaction_hash: FieldElement,
+    pub kind: TransactionKind,
+    /// Not known while the transaction is pending or when its receipt can't be fetched.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub actual_fee: Option<FieldElement>,
+}
+
+/// What the transactions of a migration cost, in wei, in total and per kind of transaction.
+#[serde_as]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MigrationFees {
+    pub transactions: Vec<TransactionFee>,
+    #[serde_as(as = "BTreeMap<_, UfeHex>")]
+    pub totals: BTreeMap<TransactionKind, FieldElement>,
+    #[serde_as(as = "UfeHex")]
+    pub total: FieldElement,
+}
+
+impl MigrationFees {
+    pub fn new(transactions: Vec<TransactionFee>) -> Self {
+        let mut totals = BTreeMap::new();
+        let mut total = FieldElement::ZERO;
+        for tx in &transactions {
+            let fee = tx.actual_fee.unwrap_or_default();
+            let kind_total = totals.entry(tx.kind).or_insert(FieldElement::ZERO);
+            *kind_total += fee;
+            total += fee;
+        }
+        Self { transactions, totals, total }
+    }
+
+    /// Looks up the actual fee of the submitted transactions in their receipts.
+    pub async fn from_receipts<P>(provider: &P, submitted: &[SubmittedTransaction]) -> Self
+    where
+        P: Provider + Sync,
+    {
+        let mut transactions = vec![];
+        for tx in submitted {
+            let actual_fee = match provider.get_transaction_receipt(tx.transaction_hash).await {
+                Ok(MaybePendingTransactionReceipt::Receipt(receipt)) => Some(match receipt {
+                    TransactionReceipt::Invoke(r) => r.actual_fee,
+                    TransactionReceipt::L1Handler(r) => r.actual_fee,
+                    TransactionReceipt::Declare(r) => r.actual_fee,
+                    TransactionReceipt::Deploy(r) => r.actual_fee,
+                    TransactionReceipt::DeployAccount(r) => r.actual_fee,
+                }),
+                _ => None,
+            };
+            transactions.push(TransactionFee {
+                description: tx.description.clone(),
+                transaction_hash: tx.transaction_hash,
+                kind: TransactionKind::of(tx),
+                actual_fee,
+            });
+        }
+        Self::new(transactions)
+    }
+}
+
+impl DeploymentManifest {
+    pub fn load_from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        serde_json::from_reader(fs::File::open(path)?)
+            .map_err(|e| anyhow!("Failed to load deployment manifest: {e}"))
+    }
+
+    pub fn write_to_path<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).context("Failed to write deployment manifest")
+    }
+
+    /// Looks up the block of the transactions whose block number isn't known yet. Transactions
+    /// still pending are left without one.
+    pub async fn resolve_block_numbers<P>(&mut self, provider: &P) -> Result<()>
+    where
+        P: Provider + Sync,
+    {
+        let mut blocks: HashMap<FieldElement, Option<u64>> = HashMap::new();
+
+        let contracts = [&mut self.world, &mut self.executor]
+            .into_iter()
+            .map(|c| (c.transaction_hash, &mut c.block_number));
+        let classes = self
+            .components
+            .iter_mut()
+            .chain(self.systems.iter_mut())
+            .map(|c| (c.transaction_hash, &mut c.block_number));
+
+        for (transaction_hash, block_number) in contracts.chain(classes) {
+            let Some(transaction_hash) = transaction_hash else { continue };
+            if block_number.is_some() {
+                continue;
+            }
+
+            // All the classes of a kind are registered by the same transaction.
+            if let Some(block) = blocks.get(&transaction_hash) {
+                *block_number = *block;
+                continue;
+            }
+
+            let receipt =
+                provider.get_transaction_receipt(transaction_hash).await.map_err(|e| {
+                    anyhow!("Failed to fetch the receipt of {transaction_hash:#x}: {e}")
+                })?;
+            let block = match receipt {
+                MaybePendingTransactionReceipt::Receipt(receipt) => Some(match receipt {
+                    TransactionReceipt::Invoke(r) => r.block_number,
+                    TransactionReceipt::L1Handler(r) => r.block_number,
+                    TransactionReceipt::Declare(r) => r.block_number,
+                    TransactionReceipt::Deploy(r) => r.block_number,
+                    TransactionReceipt::DeployAccount(r) => r.block_number,
+                }),
+                MaybePendingTransactionReceipt::PendingReceipt(_) => None,
+            };
+
+            blocks.insert(transaction_hash, block);
+            *block_number = block;
+        }
+
+        Ok(())
+    }
+    /// The deployed world as a manifest holding its class hashes only, with the `System` suffix of
+    /// the system names restored.
+    pub fn to_manifest(&self) -> Manifest {
+        Manifest {
+            world: self.world.class_hash,
+            executor: self.executor.class_hash,
+            components: self
+                .components
+                .iter()
+                .map(|component| Component {
+                    name: component.name.clone(),
+                    class_hash: component.class_hash,
+                    ..Default::default()
+                })
+                .collect(),
+            systems: self
+                .systems
+                .iter()
+                .map(|system| System {
+                    name: format!("{}System", system.name).into(),
+                    class_hash: system.class_hash,
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Reconstructs the manifest of a world deployed by someone else from the chain: the classes
+    /// registered to it in registration order, from its events, and the salts the world and its
+    /// executor were deployed with, from the UDC's events. The history is read from
+    /// `from_block`, the executor is only found if it was deployed after it and before the world.
+    pub async fn from_remote<P>(
+        provider: &P,
+        world_address: FieldElement,
+        from_block: u64,
+    ) -> Result<Self>
+    where
+        P: Provider + Sync,
+    {
+        let pending = BlockId::Tag(BlockTag::Pending);
+        let world_class_hash = provider
+            .get_class_hash_at(pending, world_address)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch the class of the world: {e}"))?;
+        let executor_address = provider
+            .get_storage_at(world_address, EXECUTOR_ADDRESS_SLOT, pending)
+            .await
+            .map_err(|e| anyhow!("Failed to read the executor of the world: {e}"))?;
+        let executor_class_hash = provider
+            .get_class_hash_at(pending, executor_address)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch the class of the executor: {e}"))?;
+
+        let world_spawned = get_selector_from_name("WorldSpawned")?;
+        let filter = EventFilter {
+            from_block: Some(BlockId::Number(from_block)),
+            to_block: Some(pending),
+            address: Some(world_address),
+            keys: Some(vec![vec![
+                world_spawned,
+                get_selector_from_name("ComponentRegistered")?,
+                get_selector_from_name("SystemRegistered")?,
+            ]]),
+        };
+        let events = fetch_events(provider, filter).await?;
+        let (components, systems) = registered_classes(&events)?;
+
+        let world_block = events
+            .iter()
+            .find(|event| event.keys.first() == Some(&world_spawned))
+            .map(|event| event.block_number);
+        let world = match world_block {
+            Some(block) => udc_deployment(provider, world_address, block, BlockId::Number(block))
+                .await?
+                .unwrap_or_default(),
+            None => DeployedContract::default(),
+        };
+        let executor_until = world_block.map_or(pending, BlockId::Number);
+        let executor = udc_deployment(provider, executor_address, from_block, executor_until)
+            .await?
+            .unwrap_or_default();
+
+        Ok(DeploymentManifest {
+            world: DeployedContract {
+                name: "World".into(),
+                class_hash: world_class_hash,
+                address: Some(world_address),
+                ..world
+            },
+            executor: DeployedContract {
+                name: "Executor".into(),
+                class_hash: executor_class_hash,
+                address: Some(executor_address),
+                ..executor
+            },
+            components,
+            systems,
+            fees: None,
+        })
+    }
+}
+
+/// Components and systems registered to the world at `world_address` from `from_block`, read
+/// from its events, in registration order. Unlike [`DeploymentManifest::from_remote`] the
+/// deployments of the world and its executor aren't looked up.
+pub async fn registered_classes_from_remote<P>(
+    provider: &P,
+    world_address: FieldElement,
+    from_block: u64,
+) -> Result<(Vec<RegisteredClass>, Vec<RegisteredClass>)>
+where
+    P: Provider + Sync,
+{
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(from_block)),
+        to_block: Some(BlockId::Tag(BlockTag::Pending)),
+        address: Some(world_address),
+        keys: Some(vec![vec![
+            get_selector_from_name("ComponentRegistered")?,
+            get_selector_from_name("SystemRegistered")?,
+        ]]),
+    };
+    let events = fetch_events(provider, filter).await?;
+    registered_classes(&events)
+}
+
+/// The spawn of the world at `world_address`, if it happened after `from_block`.
+pub async fn world_spawn<P>(
+    provider: &P,
+    world_address: FieldElement,
+    from_block: u64,
+) -> Result<Option<WorldSpawn>>
+where
+    P: Provider + Sync,
+{
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(from_block)),
+        to_block: Some(BlockId::Tag(BlockTag::Pending)),
+        address: Some(world_address),
+        keys: Some(vec![vec![get_selector_from_name("WorldSpawned")?]]),
+    };
+    let Some(event) = fetch_events(provider, filter).await?.into_iter().next() else {
+        return Ok(None);
+    };
+    let [_, creator, name, ..] = event.data[..] else {
+        return Err(anyhow!("Malformed world spawn in {:#x}", event.transaction_hash));
+    };
+
+    Ok(Some(WorldSpawn {
+        name: parse_cairo_short_string(&name)?,
+        creator,
+        transaction_hash: event.transaction_hash,
+        block_number: event.block_number,
+    }))
+}
+
+async fn fetch_events<P>(provider: &P, filter: EventFilter) -> Result<Vec<EmittedEvent>>
+where
+    P: Provider + Sync,
+{
+    let mut events = vec![];
+    let mut continuation_token = None;
+    loop {
+        let page = provider
+            .get_events(filter.clone(), continuation_token, EVENTS_CHUNK_SIZE)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch events: {e}"))?;
+        events.extend(page.events);
+
+        continuation_token = page.continuation_token;
+        if continuation_token.is_none() {
+            return Ok(events);
+        }
+    }
+}
+
+/// Components and systems registered by the `ComponentRegistered` and `SystemRegistered` events,
+/// in the order they were first registered. A class registered again replaces the previous one.
+fn registered_classes(
+    events: &[EmittedEvent],
+) -> Result<(Vec<RegisteredClass>, Vec<RegisteredClass>)> {
+    let component_registered = get_selector_from_name("ComponentRegistered")?;
+    let system_registered = get_selector_from_name("SystemRegistered")?;
+
+    let (mut components, mut systems) = (vec![], vec![]);
+    for event in events {
+        let classes: &mut Vec<RegisteredClass> = match event.keys.first() {
+            Some(key) if *key == component_registered => &mut components,
+            Some(key) if *key == system_registered => &mut systems,
+            _ => continue,
+        };
+        let [name, class_hash, ..] = event.data[..] else {
+            return Err(anyhow!("Malformed registration in {:#x}", event.transaction_hash));
+        };
+
+        let class = RegisteredClass {
+            name: parse_cairo_short_string(&name)?,
+            class_hash,
+            transaction_hash: Some(event.transaction_hash),
+            block_number: Some(event.block_number),
+            members: vec![],
+        };
+        match classes.iter_mut().find(|registered| registered.name == class.name) {
+            Some(registered) => *registered = class,
+            None => classes.push(class),
+        }
+    }
+
+    Ok((components, systems))
+}
+
+/// Looks up the deployment of `address` among the `ContractDeployed` events of the UDC, whose
+/// data is `[address, deployer, unique, class_hash, calldata_len, ...calldata, salt]`. Only the
+/// salt, the transaction and the block of the returned contract are set.
+async fn udc_deployment<P>(
+    provider: &P,
+    address: FieldElement,
+    from_block: u64,
+    to_block: BlockId,
+) -> Result<Option<DeployedContract>>
+where
+    P: Provider + Sync,
+{
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(from_block)),
+        to_block: Some(to_block),
+        address: Some(udc_address()),
+        keys: Some(vec![vec![get_selector_from_name("ContractDeployed")?]]),
+    };
+    let events = fetch_events(provider, filter).await?;
+
+    Ok(events.into_iter().find(|event| event.data.first() == Some(&address)).and_then(|event| {
+        Some(DeployedContract {
+            salt: *event.data.last()?,
+            transaction_hash: Some(event.transaction_hash),
+            block_number: Some(event.block_number),
+            ..Default::default()
+        })
+    }))
+}
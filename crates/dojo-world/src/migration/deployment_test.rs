@@ -0,0 +1,110 @@
+use starknet::core::types::{EmittedEvent, FieldElement};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+
+use super::{
+    registered_classes, DeployedContract, DeploymentManifest, MigrationFees, RegisteredClass,
+    TransactionFee, TransactionKind,
+};
+use crate::migration::strategy::SubmittedTransaction;
+
+fn registration(event: &str, name: &str, class_hash: u64, block_number: u64) -> EmittedEvent {
+    EmittedEvent {
+        from_address: FieldElement::ONE,
+        keys: vec![get_selector_from_name(event).unwrap()],
+        data: vec![cairo_short_string_to_felt(name).unwrap(), FieldElement::from(class_hash)],
+        block_hash: FieldElement::ZERO,
+        block_number,
+        transaction_hash: FieldElement::from(block_number),
+    }
+}
+
+#[test]
+fn test_registered_classes() {
+    let events = vec![
+        registration("ComponentRegistered", "Position", 1, 10),
+        registration("ComponentRegistered", "Moves", 2, 10),
+        registration("SystemRegistered", "spawn", 3, 11),
+        registration("ComponentRegistered", "Position", 4, 12),
+    ];
+
+    let (components, systems) = registered_classes(&events).unwrap();
+
+    // The upgraded component keeps its registration order.
+    let names = components.iter().map(|c| c.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names, ["Position", "Moves"]);
+    assert_eq!(components[0].class_hash, FieldElement::from(4_u64));
+    assert_eq!(components[0].block_number, Some(12));
+    assert_eq!(components[0].transaction_hash, Some(FieldElement::from(12_u64)));
+
+    assert_eq!(systems.len(), 1);
+    assert_eq!(systems[0].name, "spawn");
+    assert_eq!(systems[0].class_hash, FieldElement::from(3_u64));
+}
+
+#[test]
+fn test_malformed_registration() {
+    let mut event = registration("SystemRegistered", "spawn", 3, 11);
+    event.data.truncate(1);
+    assert!(registered_classes(&[event]).is_err());
+}
+
+#[test]
+fn test_migration_fees() {
+    let submitted = |description: &str, class_hash: Option<u64>, contract_address: Option<u64>| {
+        SubmittedTransaction {
+            description: description.to_string(),
+            transaction_hash: FieldElement::ONE,
+            calldata: vec![],
+            class_hash: class_hash.map(FieldElement::from),
+            salt: None,
+            contract_address: contract_address.map(FieldElement::from),
+            sender: None,
+        }
+    };
+    let fee = |tx: &SubmittedTransaction, actual_fee: Option<u64>| TransactionFee {
+        description: tx.description.clone(),
+        transaction_hash: tx.transaction_hash,
+        kind: TransactionKind::of(tx),
+        actual_fee: actual_fee.map(FieldElement::from),
+    };
+
+    let declare = submitted("declare World", Some(1), None);
+    let deploy = submitted("deploy world", Some(1), Some(2));
+    let register = submitted("register components", None, None);
+    let fees = MigrationFees::new(vec![
+        fee(&declare, Some(100)),
+        fee(&deploy, Some(20)),
+        fee(&register, Some(3)),
+        // Still pending.
+        fee(&register, None),
+    ]);
+
+    assert_eq!(fees.totals[&TransactionKind::Declare], FieldElement::from(100_u64));
+    assert_eq!(fees.totals[&TransactionKind::Deploy], FieldElement::from(20_u64));
+    assert_eq!(fees.totals[&TransactionKind::Invoke], FieldElement::from(3_u64));
+    assert_eq!(fees.total, FieldElement::from(123_u64));
+}
+
+#[test]
+fn test_deployment_to_manifest() {
+    let class = |name: &str, class_hash: u64| RegisteredClass {
+        name: name.into(),
+        class_hash: FieldElement::from(class_hash),
+        ..Default::default()
+    };
+    let deployment = DeploymentManifest {
+        world: DeployedContract { class_hash: FieldElement::ONE, ..Default::default() },
+        executor: DeployedContract { class_hash: FieldElement::TWO, ..Default::default() },
+        components: vec![class("Position", 3)],
+        systems: vec![class("spawn", 4)],
+        fees: None,
+    };
+
+    let manifest = deployment.to_manifest();
+    assert_eq!(manifest.world, FieldElement::ONE);
+    assert_eq!(manifest.executor, FieldElement::TWO);
+    assert_eq!(manifest.components[0].name, "Position");
+    assert_eq!(manifest.components[0].class_hash, FieldElement::from(3_u64));
+    assert_eq!(manifest.systems[0].name, "spawnSystem");
+    assert_eq!(manifest.systems[0].class_hash, FieldElement::from(4_u64));
+}
@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use futures::future::join_all;
+use starknet::accounts::{Account, AccountError, ConnectedAccount};
+use starknet::core::types::FieldElement;
+use starknet::providers::Provider;
+
+use super::finality::{wait_for_finality, Finality, FinalityError};
+use super::nonce::{NonceManager, NonceUse};
+use super::object::{Declarable, DeclareOutput, MigrationError};
+use super::retry::RetryPolicy;
+use crate::config::FeeConfig;
+
+pub type DeclareResult<A> = Result<
+    Option<DeclareOutput>,
+    MigrationError<
+        <A as Account>::SignError,
+        <<A as ConnectedAccount>::Provider as Provider>::Error,
+    >,
+>;
+
+/// Distributes declarations across several accounts sending in parallel.
+///
+/// Each account sends its share of the declarations one after the other, its nonces handed out by
+/// a [`NonceManager`], so it doesn't wait for a transaction to be accepted before sending the
+/// next one.
+/// Accounts must not be shared with anything else sending transactions at the same time.
+pub struct Dispatcher<A> {
+    accounts: Vec<A>,
+    fees: FeeConfig,
+    retry: RetryPolicy,
+}
+
+impl<A> Dispatcher<A>
+where
+    A: ConnectedAccount + Sync,
+{
+    pub fn new(accounts: Vec<A>) -> Self {
+        assert!(!accounts.is_empty(), "A dispatcher needs at least one account");
+        Self { accounts, fees: FeeConfig::default(), retry: RetryPolicy::default() }
+    }
+
+    /// Sends the transactions with these fees instead of the estimated ones.
+    pub fn with_fees(mut self, fees: FeeConfig) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Sends the transactions again as long as they fail to reach the node, following `retry`.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn accounts(&self) -> &[A] {
+        &self.accounts
+    }
+
+    /// Declares the classes, returning `None` for the ones already declared. The results are in
+    /// the order of `classes`, along with the address of the account that declared each class.
+    pub async fn declare_all<D>(&self, classes: &[D]) -> Vec<(FieldElement, DeclareResult<A>)>
+    where
+        D: Declarable + Sync,
+    {
+        let next = AtomicUsize::new(0);
+
+        let workers = self.accounts.iter().map(|account| {
+            let next = &next;
+            async move {
+                let mut results = vec![];
+                let nonces = NonceManager::new(account, self.retry);
+
+                while let Some(index) = take_job(next, classes.len()) {
+                    let nonce = match nonces.reserve().await {
+                        Ok(nonce) => nonce,
+                        Err(e) => {
+                            let e = MigrationError::Migrator(AccountError::Provider(e));
+                            results.push((index, (account.address(), Err(e))));
+                            continue;
+                        }
+                    };
+
+                    let declared = classes[index]
+                        .declare_with_nonce(account, Some(nonce), &self.fees, &self.retry)
+                        .await;
+                    let (res, nonce_use) = match declared {
+                        Ok(output) => {
+                            let nonce_use = NonceUse::Used(output.transaction_hash);
+                            (Ok(Some(output)), nonce_use)
+                        }
+                        Err(MigrationError::ClassAlreadyDeclared) => (Ok(None), NonceUse::Unused),
+                        Err(e) => (Err(e), NonceUse::Unknown),
+                    };
+                    nonces.settle(nonce, nonce_use).await;
+                    results.push((index, (account.address(), res)));
+                }
+
+                results
+            }
+        });
+
+        in_order(join_all(workers).await)
+    }
+
+    /// Waits until every transaction reaches `finality`. Transactions sent by different accounts
+    /// may be included in any order, so wait before sending transactions that depend on them.
+    pub async fn wait_for_transactions(
+        &self,
+        transaction_hashes: &[FieldElement],
+        finality: Finality,
+    ) -> Result<(), FinalityError> {
+        let provider = self.accounts[0].provider();
+        for hash in transaction_hashes {
+            wait_for_finality(provider, *hash, finality, &self.retry).await?;
+        }
+        Ok(())
+    }
+}
+
+fn take_job(next: &AtomicUsize, len: usize) -> Option<usize> {
+    let index = next.fetch_add(1, Ordering::Relaxed);
+    (index < len).then_some(index)
+}
+
+fn in_order<T>(results: Vec<Vec<(usize, T)>>) -> Vec<T> {
+    let mut results = results.into_iter().flatten().collect::<Vec<_>>();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, res)| res).collect()
+}
@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use starknet::core::types::{
+    BlockId, FieldElement, InvokeTransaction, MaybePendingBlockWithTxs, Transaction,
+};
+use starknet::providers::{Provider, ProviderError};
+
+#[cfg(test)]
+#[path = "fee_test.rs"]
+mod test;
+
+/// Multiplier applied by starknet-rs to the estimated fee when none is configured.
+pub const DEFAULT_FEE_ESTIMATE_MULTIPLIER: f64 = 1.1;
+
+/// Decides the max fee of a transaction.
+#[async_trait]
+pub trait FeeStrategy: Sync {
+    /// Whether [`FeeStrategy::max_fee`] needs the estimated fee of the transaction, `0` being
+    /// passed otherwise.
+    fn needs_estimate(&self) -> bool {
+        true
+    }
+
+    async fn max_fee<P>(
+        &self,
+        provider: &P,
+        estimated_fee: u64,
+    ) -> Result<FieldElement, ProviderError<P::Error>>
+    where
+        P: Provider + Sync;
+}
+
+/// The estimated fee times a multiplier, which is enough as long as the gas price doesn't rise
+/// more than the multiplier before the transaction is included.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EstimateMultiplier {
+    pub multiplier: f64,
+}
+
+impl Default for EstimateMultiplier {
+    fn default() -> Self {
+        Self { multiplier: DEFAULT_FEE_ESTIMATE_MULTIPLIER }
+    }
+}
+
+impl EstimateMultiplier {
+    fn apply(&self, estimated_fee: u64) -> FieldElement {
+        FieldElement::from((estimated_fee as f64 * self.multiplier) as u64)
+    }
+}
+
+#[async_trait]
+impl FeeStrategy for EstimateMultiplier {
+    async fn max_fee<P>(
+        &self,
+        _provider: &P,
+        estimated_fee: u64,
+    ) -> Result<FieldElement, ProviderError<P::Error>>
+    where
+        P: Provider + Sync,
+    {
+        Ok(self.apply(estimated_fee))
+    }
+}
+
+/// The same max fee for every transaction, which is never estimated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedFee {
+    pub max_fee: FieldElement,
+}
+
+#[async_trait]
+impl FeeStrategy for FixedFee {
+    fn needs_estimate(&self) -> bool {
+        false
+    }
+
+    async fn max_fee<P>(
+        &self,
+        _provider: &P,
+        _estimated_fee: u64,
+    ) -> Result<FieldElement, ProviderError<P::Error>>
+    where
+        P: Provider + Sync,
+    {
+        Ok(self.max_fee)
+    }
+}
+
+/// The `percentile`th percentile of the max fees set by the invoke transactions of the last
+/// `blocks` blocks, so that a transaction bids like the others while the chain is congested, but
+/// never less than its estimate times `multiplier`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoricalPercentile {
+    /// Between 1 and 100.
+    pub percentile: u8,
+    pub blocks: u64,
+    pub multiplier: f64,
+}
+
+impl Default for HistoricalPercentile {
+    fn default() -> Self {
+        Self { percentile: 75, blocks: 10, multiplier: DEFAULT_FEE_ESTIMATE_MULTIPLIER }
+    }
+}
+
+#[async_trait]
+impl FeeStrategy for HistoricalPercentile {
+    async fn max_fee<P>(
+        &self,
+        provider: &P,
+        estimated_fee: u64,
+    ) -> Result<FieldElement, ProviderError<P::Error>>
+    where
+        P: Provider + Sync,
+    {
+        let latest = provider.block_number().await?;
+
+        let mut fees = vec![];
+        for number in latest.saturating_sub(self.blocks.saturating_sub(1))..=latest {
+            let block = provider.get_block_with_txs(BlockId::Number(number)).await?;
+            let MaybePendingBlockWithTxs::Block(block) = block else {
+                continue;
+            };
+            fees.extend(block.transactions.iter().filter_map(|transaction| match transaction {
+                Transaction::Invoke(InvokeTransaction::V0(tx)) => u128::try_from(tx.max_fee).ok(),
+                Transaction::Invoke(InvokeTransaction::V1(tx)) => u128::try_from(tx.max_fee).ok(),
+                _ => None,
+            }));
+        }
+
+        let estimate = EstimateMultiplier { multiplier: self.multiplier }.apply(estimated_fee);
+        Ok(match percentile(&mut fees, self.percentile) {
+            Some(fee) if FieldElement::from(fee) > estimate => FieldElement::from(fee),
+            _ => estimate,
+        })
+    }
+}
+
+/// Strategy selected by the `fee_strategy` of the environment config.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FeeStrategyConfig {
+    Estimate(EstimateMultiplier),
+    Fixed(FixedFee),
+    Percentile(HistoricalPercentile),
+}
+
+impl Default for FeeStrategyConfig {
+    fn default() -> Self {
+        Self::Estimate(EstimateMultiplier::default())
+    }
+}
+
+#[async_trait]
+impl FeeStrategy for FeeStrategyConfig {
+    fn needs_estimate(&self) -> bool {
+        match self {
+            Self::Estimate(strategy) => strategy.needs_estimate(),
+            Self::Fixed(strategy) => strategy.needs_estimate(),
+            Self::Percentile(strategy) => strategy.needs_estimate(),
+        }
+    }
+
+    async fn max_fee<P>(
+        &self,
+        provider: &P,
+        estimated_fee: u64,
+    ) -> Result<FieldElement, ProviderError<P::Error>>
+    where
+        P: Provider + Sync,
+    {
+        match self {
+            Self::Estimate(strategy) => strategy.max_fee(provider, estimated_fee).await,
+            Self::Fixed(strategy) => strategy.max_fee(provider, estimated_fee).await,
+            Self::Percentile(strategy) => strategy.max_fee(provider, estimated_fee).await,
+        }
+    }
+}
+
+/// Nearest-rank percentile of `values`, `None` if there are none.
+pub fn percentile(values: &mut [u128], percentile: u8) -> Option<u128> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let rank = (usize::from(percentile.clamp(1, 100)) * values.len() + 99) / 100;
+    Some(values[rank - 1])
+}
@@ -0,0 +1,25 @@
+use starknet::core::types::FieldElement;
+
+use super::{percentile, EstimateMultiplier, FeeStrategy, FixedFee};
+
+#[test]
+fn test_percentile() {
+    assert_eq!(percentile(&mut [], 50), None);
+    assert_eq!(percentile(&mut [7], 1), Some(7));
+
+    let mut fees = [40, 10, 30, 20, 50, 60, 70, 80, 90, 100];
+    assert_eq!(percentile(&mut fees, 50), Some(50));
+    assert_eq!(percentile(&mut fees, 75), Some(80));
+    assert_eq!(percentile(&mut fees, 100), Some(100));
+    assert_eq!(percentile(&mut fees, 0), Some(10));
+}
+
+#[test]
+fn test_estimate_strategies() {
+    let estimate = EstimateMultiplier { multiplier: 1.5 };
+    assert_eq!(estimate.apply(1000), FieldElement::from(1500_u64));
+    assert!(estimate.needs_estimate());
+
+    let fixed = FixedFee { max_fee: FieldElement::from(42_u64) };
+    assert!(!fixed.needs_estimate());
+}
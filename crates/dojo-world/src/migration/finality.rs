@@ -0,0 +1,150 @@
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use starknet::core::types::{
+    FieldElement, MaybePendingTransactionReceipt, TransactionReceipt, TransactionStatus,
+};
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::providers::Provider;
+use thiserror::Error;
+
+use super::retry::{RetryPolicy, Retryable};
+
+#[cfg(test)]
+#[path = "finality_test.rs"]
+mod test;
+
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How final a transaction must be before sending the transactions depending on it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Finality {
+    /// Any receipt, pending ones included.
+    #[default]
+    Pending,
+    AcceptedOnL2,
+    AcceptedOnL1,
+}
+
+impl Finality {
+    /// Default finality on the chain: accepted on L1 on mainnet, where redoing a migration built
+    /// on a reverted transaction is costly, pending elsewhere.
+    pub fn default_for_chain(chain_id: FieldElement) -> Self {
+        if cairo_short_string_to_felt("SN_MAIN").ok() == Some(chain_id) {
+            Self::AcceptedOnL1
+        } else {
+            Self::Pending
+        }
+    }
+
+    fn is_reached(&self, status: TransactionStatus) -> bool {
+        match self {
+            Self::Pending => true,
+            Self::AcceptedOnL2 => {
+                matches!(status, TransactionStatus::AcceptedOnL2 | TransactionStatus::AcceptedOnL1)
+            }
+            Self::AcceptedOnL1 => status == TransactionStatus::AcceptedOnL1,
+        }
+    }
+}
+
+impl FromStr for Finality {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "PENDING" => Ok(Self::Pending),
+            "ACCEPTED_ON_L2" => Ok(Self::AcceptedOnL2),
+            "ACCEPTED_ON_L1" => Ok(Self::AcceptedOnL1),
+            _ => Err(anyhow!(
+                "Unknown finality `{s}`, expected `PENDING`, `ACCEPTED_ON_L2` or `ACCEPTED_ON_L1`"
+            )),
+        }
+    }
+}
+
+impl Display for Finality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "PENDING"),
+            Self::AcceptedOnL2 => write!(f, "ACCEPTED_ON_L2"),
+            Self::AcceptedOnL1 => write!(f, "ACCEPTED_ON_L1"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FinalityError {
+    #[error("Transaction {0:#x} was rejected.")]
+    Rejected(FieldElement),
+    #[error("Transaction {0:#x} is unknown to the node after {1:?}, it was likely dropped.")]
+    Dropped(FieldElement, Duration),
+}
+
+/// Polls the provider until the transaction reaches `finality`, failing if it's rejected.
+/// Errors fetching the receipt, usually because it isn't known yet, are retried. While the node
+/// can't be reached, the polls are spaced out following the backoff of `retry`. A transaction
+/// without receipt for `retry.receipt_timeout` is considered dropped.
+pub async fn wait_for_finality<P>(
+    provider: &P,
+    transaction_hash: FieldElement,
+    finality: Finality,
+    retry: &RetryPolicy,
+) -> Result<(), FinalityError>
+where
+    P: Provider + Sync,
+{
+    let mut failures = 0;
+    let mut last_seen = Instant::now();
+    loop {
+        if last_seen.elapsed() >= retry.receipt_timeout {
+            return Err(FinalityError::Dropped(transaction_hash, retry.receipt_timeout));
+        }
+
+        let receipt = provider.get_transaction_receipt(transaction_hash).await;
+        if matches!(&receipt, Err(e) if e.is_retryable()) {
+            failures += 1;
+            tokio::time::sleep(retry.backoff(failures).max(RECEIPT_POLL_INTERVAL)).await;
+            continue;
+        }
+        failures = 0;
+        if receipt.is_ok() {
+            last_seen = Instant::now();
+        }
+
+        match receipt {
+            Ok(MaybePendingTransactionReceipt::Receipt(receipt)) => {
+                let status = receipt_status(&receipt);
+                if status == TransactionStatus::Rejected {
+                    return Err(FinalityError::Rejected(transaction_hash));
+                }
+                if finality.is_reached(status) {
+                    return Ok(());
+                }
+            }
+            Ok(MaybePendingTransactionReceipt::PendingReceipt(_))
+                if finality == Finality::Pending =>
+            {
+                return Ok(());
+            }
+            _ => {}
+        }
+
+        tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+    }
+}
+
+/// Status of the transaction a receipt is for.
+pub fn receipt_status(receipt: &TransactionReceipt) -> TransactionStatus {
+    match receipt {
+        TransactionReceipt::Invoke(r) => r.status,
+        TransactionReceipt::L1Handler(r) => r.status,
+        TransactionReceipt::Declare(r) => r.status,
+        TransactionReceipt::Deploy(r) => r.status,
+        TransactionReceipt::DeployAccount(r) => r.status,
+    }
+}
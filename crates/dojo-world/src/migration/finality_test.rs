@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use dojo_test_utils::rpc::MockJsonRpcTransport;
+use serde_json::json;
+use starknet::core::types::{FieldElement, TransactionStatus};
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcMethod};
+
+use super::{wait_for_finality, Finality, FinalityError};
+use crate::migration::retry::RetryPolicy;
+
+#[test]
+fn test_finality() {
+    let mainnet = cairo_short_string_to_felt("SN_MAIN").unwrap();
+    let goerli = cairo_short_string_to_felt("SN_GOERLI").unwrap();
+    assert_eq!(Finality::default_for_chain(mainnet), Finality::AcceptedOnL1);
+    assert_eq!(Finality::default_for_chain(goerli), Finality::Pending);
+
+    assert!(Finality::AcceptedOnL2.is_reached(TransactionStatus::AcceptedOnL1));
+    assert!(!Finality::AcceptedOnL1.is_reached(TransactionStatus::AcceptedOnL2));
+    assert_eq!("accepted_on_l2".parse::<Finality>().unwrap(), Finality::AcceptedOnL2);
+    assert!("finalized".parse::<Finality>().is_err());
+}
+
+#[tokio::test]
+async fn test_wait_for_dropped_transaction() {
+    let mut mock_transport = MockJsonRpcTransport::new();
+    mock_transport.set_response(
+        JsonRpcMethod::GetTransactionReceipt,
+        json!(["0x1"]),
+        json!({
+            "id": 1,
+            "error": {
+                "code": 25,
+                "message": "Invalid transaction hash"
+            },
+        }),
+    );
+    let rpc = JsonRpcClient::new(mock_transport);
+    let retry = RetryPolicy { receipt_timeout: Duration::from_secs(1), ..RetryPolicy::default() };
+
+    let err =
+        wait_for_finality(&rpc, FieldElement::ONE, Finality::Pending, &retry).await.unwrap_err();
+    assert!(matches!(err, FinalityError::Dropped(hash, _) if hash == FieldElement::ONE));
+}
@@ -1,3 +1,13 @@
+pub mod bundle;
+pub mod cache;
+pub mod deployment;
+pub mod dispatcher;
+pub mod fee;
+pub mod finality;
+pub mod nonce;
 pub mod object;
+pub mod plan;
+pub mod retry;
+pub mod schema;
 pub mod strategy;
 pub mod world;
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use starknet::accounts::ConnectedAccount;
+use starknet::core::types::{FieldElement, MaybePendingTransactionReceipt, TransactionStatus};
+use starknet::providers::{Provider, ProviderError};
+use tokio::sync::Mutex;
+
+use super::finality::receipt_status;
+use super::retry::RetryPolicy;
+
+#[cfg(test)]
+#[path = "nonce_test.rs"]
+mod test;
+
+/// What happened to a transaction sent with a reserved nonce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceUse {
+    /// The node accepted the transaction with this hash, the nonce is consumed unless the node
+    /// rejects it later on.
+    Used(FieldElement),
+    /// The transaction was never sent, e.g. because the class was already declared.
+    Unused,
+    /// The transaction may or may not have reached the node, or was rejected by it, possibly
+    /// because the nonce was wrong.
+    Unknown,
+}
+
+/// Hands out the nonces of an account so that several transactions can be sent without waiting
+/// for the previous ones to be accepted.
+///
+/// The account's nonce is fetched on first use, then incremented locally for every reservation.
+/// When a transaction whose fate is unknown breaks the sequence, the nonce is fetched again from
+/// the pending block on the next reservation, skipping the nonces of the transactions sent
+/// since. The account must not send transactions bypassing the manager while it's in use.
+pub struct NonceManager<'a, A> {
+    account: &'a A,
+    retry: RetryPolicy,
+    state: Mutex<NonceState>,
+}
+
+#[derive(Default)]
+struct NonceState {
+    next: Option<FieldElement>,
+    /// Transactions sent with each nonce, until the account's nonce gets past it.
+    sent: BTreeMap<FieldElement, FieldElement>,
+}
+
+impl<'a, A> NonceManager<'a, A>
+where
+    A: ConnectedAccount + Sync,
+{
+    pub fn new(account: &'a A, retry: RetryPolicy) -> Self {
+        Self { account, retry, state: Mutex::new(NonceState::default()) }
+    }
+
+    pub fn account(&self) -> &'a A {
+        self.account
+    }
+
+    /// Reserves the next nonce of the account, which must be settled with [`Self::settle`] once
+    /// the transaction using it is sent or abandoned.
+    pub async fn reserve(
+        &self,
+    ) -> Result<FieldElement, ProviderError<<A::Provider as Provider>::Error>> {
+        let mut state = self.state.lock().await;
+        let mut nonce = match state.next {
+            Some(nonce) => nonce,
+            None => self.retry.run(|| self.account.get_nonce()).await?,
+        };
+        while state.sent.contains_key(&nonce) {
+            nonce = nonce + FieldElement::ONE;
+        }
+        state.next = Some(nonce + FieldElement::ONE);
+        Ok(nonce)
+    }
+
+    /// Records what happened to the transaction sent with `nonce`. An unused nonce is handed out
+    /// again if it was the last one reserved, otherwise it leaves a gap the later transactions
+    /// can't get past and the nonce is fetched again.
+    pub async fn settle(&self, nonce: FieldElement, nonce_use: NonceUse) {
+        let mut state = self.state.lock().await;
+        match nonce_use {
+            NonceUse::Used(transaction_hash) => {
+                state.sent.insert(nonce, transaction_hash);
+            }
+            NonceUse::Unused if state.next == Some(nonce + FieldElement::ONE) => {
+                state.next = Some(nonce)
+            }
+            NonceUse::Unused | NonceUse::Unknown => state.next = None,
+        }
+    }
+
+    /// Nonces the account can't get past: from its current nonce up to the last one sent, the
+    /// ones never used and the ones whose transaction the node rejected. The transactions sent
+    /// after a gap can't be included until it's filled, by sending a transaction with each of
+    /// these nonces, in order.
+    pub async fn gaps(
+        &self,
+    ) -> Result<Vec<FieldElement>, ProviderError<<A::Provider as Provider>::Error>> {
+        let current = self.retry.run(|| self.account.get_nonce()).await?;
+        let mut state = self.state.lock().await;
+        state.sent = state.sent.split_off(&current);
+        let Some(&last) = state.sent.keys().next_back() else {
+            return Ok(vec![]);
+        };
+
+        let provider = self.account.provider();
+        let mut gaps = vec![];
+        let mut nonce = current;
+        while nonce <= last {
+            let filled = match state.sent.get(&nonce) {
+                // A transaction without a receipt yet may still be included.
+                Some(hash) => !matches!(
+                    provider.get_transaction_receipt(*hash).await,
+                    Ok(MaybePendingTransactionReceipt::Receipt(receipt))
+                        if receipt_status(&receipt) == TransactionStatus::Rejected
+                ),
+                None => false,
+            };
+            if !filled {
+                state.sent.remove(&nonce);
+                gaps.push(nonce);
+            }
+            nonce = nonce + FieldElement::ONE;
+        }
+
+        Ok(gaps)
+    }
+
+    /// Forgets the local nonce, fetching it again on the next reservation.
+    pub async fn resync(&self) {
+        self.state.lock().await.next = None;
+    }
+}
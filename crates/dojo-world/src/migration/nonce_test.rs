@@ -0,0 +1,102 @@
+use dojo_test_utils::sequencer::Sequencer;
+use futures::future::join_all;
+use starknet::accounts::{Account, Call, ConnectedAccount};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::get_selector_from_name;
+
+use super::{NonceManager, NonceUse};
+use crate::config::EnvironmentConfig;
+use crate::migration::retry::RetryPolicy;
+
+async fn send<A>(account: &A, nonce: FieldElement, calls: Vec<Call>) -> FieldElement
+where
+    A: ConnectedAccount + Sync,
+{
+    account
+        .execute(calls)
+        .nonce(nonce)
+        .max_fee(FieldElement::ZERO)
+        .send()
+        .await
+        .unwrap()
+        .transaction_hash
+}
+
+#[tokio::test]
+async fn test_reserve_nonces() {
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+    let account = env_config.migrator().await.unwrap();
+    let start = account.get_nonce().await.unwrap();
+    let nonces = NonceManager::new(&account, RetryPolicy::none());
+
+    let mut reserved = join_all((0..4).map(|_| nonces.reserve())).await;
+    reserved.sort_by_key(|res| *res.as_ref().unwrap());
+    let reserved = reserved.into_iter().map(Result::unwrap).collect::<Vec<_>>();
+    let expected =
+        (0..4_u32).map(|i| start + FieldElement::from(i)).collect::<Vec<FieldElement>>();
+    assert_eq!(reserved, expected);
+
+    // The last nonce is handed out again when it isn't used.
+    nonces.settle(expected[3], NonceUse::Unused).await;
+    assert_eq!(nonces.reserve().await.unwrap(), expected[3]);
+
+    // Leaving a gap fetches the nonce again, none of the transactions having been sent.
+    nonces.settle(expected[1], NonceUse::Unused).await;
+    assert_eq!(nonces.reserve().await.unwrap(), start);
+
+    nonces.settle(start, NonceUse::Unknown).await;
+    assert_eq!(nonces.reserve().await.unwrap(), start);
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test]
+async fn test_fill_gaps() {
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+    let account = env_config.migrator().await.unwrap();
+    let start = account.get_nonce().await.unwrap();
+    let nonces = NonceManager::new(&account, RetryPolicy::none());
+
+    // The middle transaction calls a contract that doesn't exist and is rejected, the last one is
+    // then rejected for its nonce.
+    let failing = Call {
+        to: FieldElement::from(0xdead_u32),
+        selector: get_selector_from_name("missing").unwrap(),
+        calldata: vec![],
+    };
+    for calls in [vec![], vec![failing], vec![]] {
+        let nonce = nonces.reserve().await.unwrap();
+        let transaction_hash = send(&account, nonce, calls).await;
+        nonces.settle(nonce, NonceUse::Used(transaction_hash)).await;
+    }
+
+    let gaps = nonces.gaps().await.unwrap();
+    assert_eq!(gaps, vec![start + FieldElement::ONE, start + FieldElement::TWO]);
+
+    // Sending the transactions again with the nonces of the gaps gets the account past them.
+    for nonce in gaps {
+        let transaction_hash = send(&account, nonce, vec![]).await;
+        nonces.settle(nonce, NonceUse::Used(transaction_hash)).await;
+    }
+    assert!(nonces.gaps().await.unwrap().is_empty());
+
+    let end = start + FieldElement::from(3_u8);
+    assert_eq!(account.get_nonce().await.unwrap(), end);
+    assert_eq!(nonces.reserve().await.unwrap(), end);
+
+    sequencer.stop().unwrap();
+}
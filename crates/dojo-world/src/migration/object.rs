@@ -1,5 +1,7 @@
 use std::fs::File;
+use std::future::Future;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use anyhow::{anyhow, Context, Result};
@@ -18,7 +20,14 @@ use starknet::core::utils::{
 use starknet::providers::Provider;
 use thiserror::Error;
 
+use super::finality::{wait_for_finality, Finality, FinalityError};
+use super::retry::{RetryPolicy, Retryable};
 use super::world::{ClassDiff, ContractDiff};
+use crate::config::FeeConfig;
+
+#[cfg(test)]
+#[path = "object_test.rs"]
+mod test;
 
 pub type DeclareOutput = DeclareTransactionResult;
 
@@ -26,7 +35,8 @@ pub type DeclareOutput = DeclareTransactionResult;
 pub struct DeployOutput {
     pub transaction_hash: FieldElement,
     pub contract_address: FieldElement,
-    pub declare_res: DeclareOutput,
+    /// `None` if the class was already declared on the remote chain.
+    pub declare_res: Option<DeclareOutput>,
 }
 
 #[derive(Debug)]
@@ -43,10 +53,14 @@ pub enum MigrationError<S, P> {
     ContractAlreadyDeployed,
     #[error("World contract address not found.")]
     WorldAddressNotFound,
+    #[error("Invalid contract artifact: {0:#}")]
+    InvalidArtifact(anyhow::Error),
     #[error(transparent)]
     Migrator(#[from] AccountError<S, P>),
     #[error(transparent)]
     CairoShortStringToFelt(#[from] CairoShortStringToFeltError),
+    #[error(transparent)]
+    Finality(#[from] FinalityError),
 }
 
 // TODO: evaluate the contract address when building the migration plan
@@ -58,10 +72,12 @@ pub struct ContractMigration {
     pub contract_address: Option<FieldElement>,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct ClassMigration {
     pub class: ClassDiff,
     pub artifact_path: PathBuf,
+    /// Set once the class has been declared ahead of its registration.
+    pub declared: bool,
 }
 
 #[async_trait]
@@ -70,11 +86,30 @@ pub trait Declarable {
         &self,
         account: &A,
     ) -> Result<DeclareOutput, MigrationError<A::SignError, <A::Provider as Provider>::Error>>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        self.declare_with_nonce(account, None, &FeeConfig::default(), &RetryPolicy::default())
+            .await
+    }
+
+    /// Declares the class, using `nonce` instead of fetching the account's current nonce. The
+    /// declaration is sent again as long as it fails to reach the node, following `retry`, with
+    /// the same nonce so that one which did reach it can't be accepted twice, see
+    /// [`send_pinned`].
+    async fn declare_with_nonce<A>(
+        &self,
+        account: &A,
+        nonce: Option<FieldElement>,
+        fees: &FeeConfig,
+        retry: &RetryPolicy,
+    ) -> Result<DeclareOutput, MigrationError<A::SignError, <A::Provider as Provider>::Error>>
     where
         A: ConnectedAccount + Sync,
     {
         let (flattened_class, casm_class_hash) =
-            prepare_contract_declaration_params(self.artifact_path()).unwrap();
+            prepare_contract_declaration_params(self.artifact_path())
+                .map_err(MigrationError::InvalidArtifact)?;
 
         if account
             .provider()
@@ -85,9 +120,24 @@ pub trait Declarable {
             return Err(MigrationError::ClassAlreadyDeclared);
         }
 
-        account
-            .declare(Arc::new(flattened_class), casm_class_hash)
-            .send()
+        let nonce = pinned_nonce(account, nonce, retry).await.map_err(MigrationError::Migrator)?;
+        let flattened_class = Arc::new(flattened_class);
+        let declaration = || account.declare(flattened_class.clone(), casm_class_hash).nonce(nonce);
+
+        let declaration = retry
+            .run(|| fees.declaration(account.provider(), declaration()))
+            .await
+            .map_err(MigrationError::Migrator)?;
+        retry
+            .run(|| fees.simulate_declaration(&declaration))
+            .await
+            .map_err(MigrationError::Migrator)?;
+
+        let declaration = declaration.prepared().expect("the nonce and max fee are set");
+        let transaction_hash = declaration.transaction_hash();
+        let sent =
+            DeclareTransactionResult { transaction_hash, class_hash: flattened_class.class_hash() };
+        send_pinned(account.provider(), transaction_hash, sent, retry, || declaration.send())
             .await
             .map_err(MigrationError::Migrator)
     }
@@ -98,33 +148,39 @@ pub trait Declarable {
 // TODO: Remove `mut` once we can calculate the contract address before sending the tx
 #[async_trait]
 pub trait Deployable: Declarable + Sync {
+    /// Declares the class if needed and deploys it through the UDC once the declaration reaches
+    /// `finality`. The transactions are sent again as long as they fail to reach the node,
+    /// following `retry`, with the nonce they were first sent with.
     async fn deploy<A>(
         &mut self,
         constructor_calldata: Vec<FieldElement>,
         account: &A,
+        fees: &FeeConfig,
+        finality: Finality,
+        retry: &RetryPolicy,
     ) -> Result<DeployOutput, MigrationError<A::SignError, <A::Provider as Provider>::Error>>
     where
         A: ConnectedAccount + Sync,
     {
-        let declare_res = self.declare(account).await?;
-
-        let calldata = [
-            vec![
-                declare_res.class_hash,                         // class hash
-                FieldElement::ZERO,                             // salt
-                FieldElement::ZERO,                             // unique
-                FieldElement::from(constructor_calldata.len()), // constructor calldata len
-            ],
-            constructor_calldata.clone(),
-        ]
-        .concat();
-
-        let contract_address = get_contract_address(
-            FieldElement::ZERO,
-            declare_res.class_hash,
-            &constructor_calldata,
-            FieldElement::ZERO,
-        );
+        let declare_res = match self.declare_with_nonce(account, None, fees, retry).await {
+            Ok(res) => {
+                wait_for_finality(account.provider(), res.transaction_hash, finality, retry)
+                    .await?;
+                Some(res)
+            }
+            Err(MigrationError::ClassAlreadyDeclared) => None,
+            Err(e) => return Err(e),
+        };
+
+        let class_hash = match &declare_res {
+            Some(res) => res.class_hash,
+            None => class_hash_from_artifact(self.artifact_path())
+                .map_err(MigrationError::InvalidArtifact)?,
+        };
+
+        let salt = self.salt();
+        let contract_address =
+            get_contract_address(salt, class_hash, &constructor_calldata, FieldElement::ZERO);
 
         self.set_contract_address(contract_address);
 
@@ -137,27 +193,123 @@ pub trait Deployable: Declarable + Sync {
             return Err(MigrationError::ContractAlreadyDeployed);
         }
 
-        let InvokeTransactionResult { transaction_hash } = account
-            .execute(vec![Call {
-                calldata,
-                // devnet UDC address
-                to: FieldElement::from_hex_be(
-                    "0x41a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf",
-                )
-                .unwrap(),
-                selector: get_selector_from_name("deployContract").unwrap(),
-            }])
-            .send()
+        let call = deploy_call(class_hash, salt, &constructor_calldata);
+        let nonce = pinned_nonce(account, None, retry).await.map_err(MigrationError::Migrator)?;
+        let execution = retry
+            .run(|| {
+                fees.execution(account.provider(), account.execute(vec![call.clone()]).nonce(nonce))
+            })
+            .await
+            .map_err(MigrationError::Migrator)?;
+        retry
+            .run(|| fees.simulate_execution(&execution))
+            .await
+            .map_err(MigrationError::Migrator)?;
+
+        let execution = execution.prepared().expect("the nonce and max fee are set");
+        let transaction_hash = execution.transaction_hash();
+        let sent = InvokeTransactionResult { transaction_hash };
+        send_pinned(account.provider(), transaction_hash, sent, retry, || execution.send())
             .await
             .map_err(MigrationError::Migrator)?;
 
         Ok(DeployOutput { transaction_hash, contract_address, declare_res })
     }
 
+    fn salt(&self) -> FieldElement;
+
     // TEMP: Remove once we can calculate the contract address before sending the tx
     fn set_contract_address(&mut self, contract_address: FieldElement);
 }
 
+/// Nonce a transaction is sent with, `nonce` or the account's current one. Pinning it before
+/// the first attempt makes sending again a transaction whose response was lost safe: the node
+/// rejects the copy instead of executing it twice, and [`send_pinned`] recognizes the original.
+async fn pinned_nonce<A>(
+    account: &A,
+    nonce: Option<FieldElement>,
+    retry: &RetryPolicy,
+) -> Result<FieldElement, AccountError<A::SignError, <A::Provider as Provider>::Error>>
+where
+    A: ConnectedAccount + Sync,
+{
+    match nonce {
+        Some(nonce) => Ok(nonce),
+        None => retry.run(|| account.get_nonce()).await.map_err(AccountError::Provider),
+    }
+}
+
+/// Sends the transaction hashed `transaction_hash` with `send`, again as long as it fails to reach
+/// the node following `retry`. An attempt whose response was lost may still have reached it, so
+/// before sending the transaction again, and when the node refuses it again because its nonce is
+/// used, the node is asked for its receipt: one means an earlier attempt went through, and `sent`
+/// is returned as that attempt's result.
+async fn send_pinned<P, F, Fut, T, E>(
+    provider: &P,
+    transaction_hash: FieldElement,
+    sent: T,
+    retry: &RetryPolicy,
+    mut send: F,
+) -> Result<T, E>
+where
+    P: Provider + Sync,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    T: Clone,
+    E: Retryable,
+{
+    let attempted = &AtomicBool::new(false);
+    let sent = &sent;
+    let reached_node =
+        move || async move { provider.get_transaction_receipt(transaction_hash).await.is_ok() };
+
+    retry
+        .run(|| {
+            let resend = attempted.swap(true, Ordering::Relaxed);
+            let attempt = send();
+            async move {
+                if resend && reached_node().await {
+                    return Ok(sent.clone());
+                }
+                match attempt.await {
+                    Err(_) if resend && reached_node().await => Ok(sent.clone()),
+                    res => res,
+                }
+            }
+        })
+        .await
+}
+
+/// Address of the Universal Deployer Contract, the same on the devnet and the public networks.
+pub fn udc_address() -> FieldElement {
+    FieldElement::from_hex_be("0x41a78e741e5af2fec34b695679bc6891742439f7afb8484ecd7766661ad02bf")
+        .unwrap()
+}
+
+/// Call deploying an instance of the class through the UDC.
+pub fn deploy_call(
+    class_hash: FieldElement,
+    salt: FieldElement,
+    constructor_calldata: &[FieldElement],
+) -> Call {
+    let calldata = [
+        vec![
+            class_hash,                                     // class hash
+            salt,                                           // salt
+            FieldElement::ZERO,                             // unique
+            FieldElement::from(constructor_calldata.len()), // constructor calldata len
+        ],
+        constructor_calldata.to_vec(),
+    ]
+    .concat();
+
+    Call {
+        calldata,
+        to: udc_address(),
+        selector: get_selector_from_name("deployContract").unwrap(),
+    }
+}
+
 #[async_trait]
 impl Declarable for ClassMigration {
     fn artifact_path(&self) -> &PathBuf {
@@ -174,6 +326,10 @@ impl Declarable for ContractMigration {
 
 #[async_trait]
 impl Deployable for ContractMigration {
+    fn salt(&self) -> FieldElement {
+        self.salt
+    }
+
     fn set_contract_address(&mut self, contract_address: FieldElement) {
         self.contract_address = Some(contract_address);
     }
@@ -183,6 +339,8 @@ impl Deployable for ContractMigration {
 pub struct WorldContract<'a, A> {
     pub address: FieldElement,
     pub account: &'a A,
+    pub fees: FeeConfig,
+    pub retry: RetryPolicy,
 }
 
 impl<'a, A> WorldContract<'a, A>
@@ -190,7 +348,40 @@ where
     A: ConnectedAccount + Sync,
 {
     pub fn new(address: FieldElement, account: &'a A) -> Self {
-        Self { address, account }
+        Self { address, account, fees: FeeConfig::default(), retry: RetryPolicy::default() }
+    }
+
+    /// Sends the transactions with these fees instead of the estimated ones.
+    pub fn with_fees(mut self, fees: FeeConfig) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// Sends the transactions again as long as they fail to reach the node, following `retry`.
+    /// They're sent again with the same nonce, a transaction is never accepted twice.
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sends the calls in a single transaction.
+    pub async fn send_calls(
+        &self,
+        calls: Vec<Call>,
+    ) -> Result<InvokeTransactionResult, AccountError<A::SignError, <A::Provider as Provider>::Error>>
+    {
+        let provider = self.account.provider();
+        let nonce = pinned_nonce(self.account, None, &self.retry).await?;
+        let execution = self
+            .retry
+            .run(|| self.fees.execution(provider, self.account.execute(calls.clone()).nonce(nonce)))
+            .await?;
+        self.retry.run(|| self.fees.simulate_execution(&execution)).await?;
+
+        let execution = execution.prepared().expect("the nonce and max fee are set");
+        let transaction_hash = execution.transaction_hash();
+        let sent = InvokeTransactionResult { transaction_hash };
+        send_pinned(provider, transaction_hash, sent, &self.retry, || execution.send()).await
     }
 
     pub async fn set_executor(
@@ -198,14 +389,32 @@ where
         executor: FieldElement,
     ) -> Result<InvokeTransactionResult, AccountError<A::SignError, <A::Provider as Provider>::Error>>
     {
-        self.account
-            .execute(vec![Call {
-                calldata: vec![executor],
-                to: self.address,
-                selector: get_selector_from_name("set_executor").unwrap(),
-            }])
-            .send()
-            .await
+        self.send_calls(vec![self.set_executor_call(executor)]).await
+    }
+
+    pub fn set_executor_call(&self, executor: FieldElement) -> Call {
+        set_executor_call(self.address, executor)
+    }
+
+    /// Executes `system` through the world, `system` being the short string encoded system name.
+    pub async fn execute(
+        &self,
+        system: FieldElement,
+        calldata: Vec<FieldElement>,
+    ) -> Result<InvokeTransactionResult, AccountError<A::SignError, <A::Provider as Provider>::Error>>
+    {
+        self.send_calls(vec![self.execute_call(system, calldata)]).await
+    }
+
+    pub fn execute_call(&self, system: FieldElement, calldata: Vec<FieldElement>) -> Call {
+        let mut execute_calldata = vec![system, FieldElement::from(calldata.len())];
+        execute_calldata.extend(calldata);
+
+        Call {
+            calldata: execute_calldata,
+            to: self.address,
+            selector: get_selector_from_name("execute").unwrap(),
+        }
     }
 
     pub async fn register_components(
@@ -213,22 +422,11 @@ where
         components: &[FieldElement],
     ) -> Result<InvokeTransactionResult, AccountError<A::SignError, <A::Provider as Provider>::Error>>
     {
-        let calls = components
-            .iter()
-            .map(|c| Call {
-                to: self.address,
-                // function selector: "register_component"
-                selector: FieldElement::from_mont([
-                    11981012454229264524,
-                    8784065169116922201,
-                    15056747385353365869,
-                    456849768949735353,
-                ]),
-                calldata: vec![*c],
-            })
-            .collect::<Vec<_>>();
+        self.send_calls(self.register_components_calls(components)).await
+    }
 
-        self.account.execute(calls).send().await
+    pub fn register_components_calls(&self, components: &[FieldElement]) -> Vec<Call> {
+        register_components_calls(self.address, components)
     }
 
     pub async fn register_systems(
@@ -238,26 +436,60 @@ where
     where
         A: ConnectedAccount + Sync,
     {
-        let calls = systems
-            .iter()
-            .map(|s| Call {
-                to: self.address,
-                // function selector: "register_system"
-                selector: FieldElement::from_mont([
-                    6581716859078500959,
-                    16871126355047595269,
-                    14219012428168968926,
-                    473332093618875024,
-                ]),
-                calldata: vec![*s],
-            })
-            .collect::<Vec<_>>();
+        self.send_calls(self.register_systems_calls(systems)).await
+    }
 
-        self.account.execute(calls).send().await
+    pub fn register_systems_calls(&self, systems: &[FieldElement]) -> Vec<Call> {
+        register_systems_calls(self.address, systems)
     }
 }
 
-fn prepare_contract_declaration_params(
+/// Call of the world at `world` setting its executor, built without an account.
+pub fn set_executor_call(world: FieldElement, executor: FieldElement) -> Call {
+    Call {
+        calldata: vec![executor],
+        to: world,
+        selector: get_selector_from_name("set_executor").unwrap(),
+    }
+}
+
+/// Calls of the world at `world` registering the components, built without an account.
+pub fn register_components_calls(world: FieldElement, components: &[FieldElement]) -> Vec<Call> {
+    components
+        .iter()
+        .map(|c| Call {
+            to: world,
+            // function selector: "register_component"
+            selector: FieldElement::from_mont([
+                11981012454229264524,
+                8784065169116922201,
+                15056747385353365869,
+                456849768949735353,
+            ]),
+            calldata: vec![*c],
+        })
+        .collect()
+}
+
+/// Calls of the world at `world` registering the systems, built without an account.
+pub fn register_systems_calls(world: FieldElement, systems: &[FieldElement]) -> Vec<Call> {
+    systems
+        .iter()
+        .map(|s| Call {
+            to: world,
+            // function selector: "register_system"
+            selector: FieldElement::from_mont([
+                6581716859078500959,
+                16871126355047595269,
+                14219012428168968926,
+                473332093618875024,
+            ]),
+            calldata: vec![*s],
+        })
+        .collect()
+}
+
+pub fn prepare_contract_declaration_params(
     artifact_path: &PathBuf,
 ) -> Result<(FlattenedSierraClass, FieldElement)> {
     let flattened_class = get_flattened_class(artifact_path)
@@ -267,6 +499,11 @@ fn prepare_contract_declaration_params(
     Ok((flattened_class, compiled_class_hash))
 }
 
+/// Class hash of the Sierra class compiled to `artifact_path`.
+pub fn class_hash_from_artifact(artifact_path: &PathBuf) -> Result<FieldElement> {
+    Ok(get_flattened_class(artifact_path)?.class_hash())
+}
+
 fn get_flattened_class(artifact_path: &PathBuf) -> Result<FlattenedSierraClass> {
     let file = File::open(artifact_path)?;
     let contract_artifact: SierraClass = serde_json::from_reader(&file)?;
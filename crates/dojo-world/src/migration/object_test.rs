@@ -0,0 +1,69 @@
+use std::time::Duration;
+
+use dojo_test_utils::sequencer::Sequencer;
+use starknet::accounts::{Account, AccountError, ConnectedAccount};
+use starknet::core::types::{FieldElement, InvokeTransactionResult};
+use starknet::providers::ProviderError;
+
+use super::send_pinned;
+use crate::config::EnvironmentConfig;
+use crate::migration::retry::{BackoffCurve, RetryPolicy};
+
+#[tokio::test]
+async fn test_send_pinned_after_lost_response() {
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+    let account = env_config.migrator().await.unwrap();
+    let start = account.get_nonce().await.unwrap();
+    let retry = RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+        curve: BackoffCurve::Constant,
+        receipt_timeout: Duration::from_secs(10),
+    };
+
+    let execution = account
+        .execute(vec![])
+        .nonce(start)
+        .max_fee(FieldElement::ZERO)
+        .prepared()
+        .expect("the nonce and max fee are set");
+    let transaction_hash = execution.transaction_hash();
+
+    // The first attempt reaches the node but its response is lost, sending the transaction again
+    // would be refused for its nonce.
+    let mut attempts = 0;
+    let res = send_pinned(
+        account.provider(),
+        transaction_hash,
+        InvokeTransactionResult { transaction_hash },
+        &retry,
+        || {
+            attempts += 1;
+            let lost = attempts == 1;
+            let execution = &execution;
+            async move {
+                let res = execution.send().await;
+                if lost {
+                    res?;
+                    return Err(AccountError::Provider(ProviderError::RateLimited));
+                }
+                res
+            }
+        },
+    )
+    .await;
+
+    assert_eq!(res.unwrap().transaction_hash, transaction_hash);
+    assert_eq!(attempts, 2);
+    assert_eq!(account.get_nonce().await.unwrap(), start + FieldElement::ONE);
+
+    sequencer.stop().unwrap();
+}
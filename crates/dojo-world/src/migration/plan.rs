@@ -0,0 +1,274 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_with::serde_as;
+use starknet::accounts::{Account, Call, ConnectedAccount, SingleOwnerAccount};
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::{BlockId, BlockTag, FieldElement};
+use starknet::core::utils::get_contract_address;
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet::providers::Provider;
+use starknet::signers::LocalWallet;
+
+use crate::migration::object::{
+    deploy_call, prepare_contract_declaration_params, register_components_calls,
+    register_systems_calls, set_executor_call, ClassMigration, ContractMigration, Declarable,
+};
+use crate::migration::strategy::MigrationStrategy;
+
+/// A transaction a migration would send. Fees are in wei, `None` when they couldn't be
+/// estimated, usually because the step depends on a class or contract declared or deployed by a
+/// previous step.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlannedStep {
+    Declare {
+        name: String,
+        #[serde_as(as = "UfeHex")]
+        class_hash: FieldElement,
+        /// Class currently used under this name by the remote world.
+        #[serde_as(as = "Option<UfeHex>")]
+        remote_class_hash: Option<FieldElement>,
+        estimated_fee: Option<u64>,
+    },
+    Deploy {
+        name: String,
+        #[serde_as(as = "UfeHex")]
+        class_hash: FieldElement,
+        #[serde_as(as = "UfeHex")]
+        salt: FieldElement,
+        #[serde_as(as = "UfeHex")]
+        address: FieldElement,
+        estimated_fee: Option<u64>,
+    },
+    SetExecutor {
+        #[serde_as(as = "UfeHex")]
+        executor: FieldElement,
+        estimated_fee: Option<u64>,
+    },
+    RegisterSystems {
+        #[serde_as(as = "Vec<UfeHex>")]
+        class_hashes: Vec<FieldElement>,
+        estimated_fee: Option<u64>,
+    },
+    RegisterComponents {
+        #[serde_as(as = "Vec<UfeHex>")]
+        class_hashes: Vec<FieldElement>,
+        estimated_fee: Option<u64>,
+    },
+}
+
+impl PlannedStep {
+    pub fn estimated_fee(&self) -> Option<u64> {
+        match self {
+            Self::Declare { estimated_fee, .. }
+            | Self::Deploy { estimated_fee, .. }
+            | Self::SetExecutor { estimated_fee, .. }
+            | Self::RegisterSystems { estimated_fee, .. }
+            | Self::RegisterComponents { estimated_fee, .. } => *estimated_fee,
+        }
+    }
+}
+
+/// The transactions a migration would send, in order, without sending any of them.
+#[serde_as]
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationPlan {
+    /// Address of the world once migrated.
+    #[serde_as(as = "Option<UfeHex>")]
+    pub world_address: Option<FieldElement>,
+    pub steps: Vec<PlannedStep>,
+    /// Sum of the fees that could be estimated.
+    pub estimated_fee: u64,
+    /// Number of steps whose fee couldn't be estimated.
+    pub unestimated_steps: usize,
+    /// Calls of the steps that aren't declarations, in order.
+    #[serde(skip)]
+    pub calls: Vec<Call>,
+}
+
+impl MigrationStrategy {
+    /// Lists the transactions [`MigrationStrategy::execute`] would send and estimates their fees
+    /// with the `account`, without sending anything.
+    pub async fn plan<A>(&self, account: &A) -> Result<MigrationPlan>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        self.plan_with(Some(account)).await
+    }
+
+    /// Lists the transactions [`MigrationStrategy::execute`] would send without any network
+    /// access: no fee is estimated and every class not known to be declared is planned to be.
+    pub async fn plan_offline(&self) -> Result<MigrationPlan> {
+        self.plan_with(None::<&SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>>)
+            .await
+    }
+
+    async fn plan_with<A>(&self, account: Option<&A>) -> Result<MigrationPlan>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        let mut steps = vec![];
+        let mut calls = vec![];
+
+        let executor_address = match &self.executor {
+            Some(executor) => {
+                let address =
+                    plan_deploy(account, executor, vec![], &mut steps, &mut calls).await?;
+                if self.world.is_none() {
+                    if let Some(world_address) = self.world_address() {
+                        let call = set_executor_call(world_address, address);
+                        calls.push(call.clone());
+                        let estimated_fee = estimate_calls(account, vec![call]).await;
+                        steps.push(PlannedStep::SetExecutor { executor: address, estimated_fee });
+                    }
+                }
+                Some(address)
+            }
+            None => None,
+        };
+
+        let world_address = match &self.world {
+            Some(world) => {
+                // The world is always deployed along with a new executor.
+                let calldata = vec![executor_address.unwrap_or_default()];
+                Some(plan_deploy(account, world, calldata, &mut steps, &mut calls).await?)
+            }
+            None => self.world_address(),
+        };
+
+        plan_declarations(account, &self.systems, &mut steps).await?;
+        let class_hashes = self.systems.iter().map(|s| s.class.local).collect::<Vec<_>>();
+        for class_hashes in self.registration_batches(&class_hashes) {
+            let estimated_fee = match world_address {
+                Some(address) => {
+                    let register_calls = register_systems_calls(address, &class_hashes);
+                    calls.extend(register_calls.iter().cloned());
+                    estimate_calls(account, register_calls).await
+                }
+                None => None,
+            };
+            steps.push(PlannedStep::RegisterSystems { class_hashes, estimated_fee });
+        }
+
+        plan_declarations(account, &self.components, &mut steps).await?;
+        let class_hashes = self.components.iter().map(|c| c.class.local).collect::<Vec<_>>();
+        for class_hashes in self.registration_batches(&class_hashes) {
+            let estimated_fee = match world_address {
+                Some(address) => {
+                    let register_calls = register_components_calls(address, &class_hashes);
+                    calls.extend(register_calls.iter().cloned());
+                    estimate_calls(account, register_calls).await
+                }
+                None => None,
+            };
+            steps.push(PlannedStep::RegisterComponents { class_hashes, estimated_fee });
+        }
+
+        let estimated_fee = steps.iter().filter_map(PlannedStep::estimated_fee).sum();
+        let unestimated_steps = steps.iter().filter(|s| s.estimated_fee().is_none()).count();
+
+        Ok(MigrationPlan { world_address, steps, estimated_fee, unestimated_steps, calls })
+    }
+
+    /// Class hashes registered by each registration transaction, `max_calls_per_tx` at a time.
+    fn registration_batches(&self, class_hashes: &[FieldElement]) -> Vec<Vec<FieldElement>> {
+        let size = self.max_calls_per_tx.unwrap_or(class_hashes.len()).max(1);
+        class_hashes.chunks(size).map(<[_]>::to_vec).collect()
+    }
+}
+
+/// Plans the declaration of the contract's class if needed and its deployment, returning the
+/// address it will be deployed at.
+async fn plan_deploy<A>(
+    account: Option<&A>,
+    contract: &ContractMigration,
+    constructor_calldata: Vec<FieldElement>,
+    steps: &mut Vec<PlannedStep>,
+    calls: &mut Vec<Call>,
+) -> Result<FieldElement>
+where
+    A: ConnectedAccount + Sync,
+{
+    let name = &contract.contract.name;
+    let class_hash = contract.contract.local;
+
+    if !is_declared(account, class_hash).await {
+        steps.push(PlannedStep::Declare {
+            name: name.clone(),
+            class_hash,
+            remote_class_hash: contract.contract.remote,
+            estimated_fee: estimate_declaration(account, contract).await?,
+        });
+    }
+
+    let salt = contract.salt;
+    let address = get_contract_address(salt, class_hash, &constructor_calldata, FieldElement::ZERO);
+    let call = deploy_call(class_hash, salt, &constructor_calldata);
+    calls.push(call.clone());
+    steps.push(PlannedStep::Deploy {
+        name: name.clone(),
+        class_hash,
+        salt,
+        address,
+        estimated_fee: estimate_calls(account, vec![call]).await,
+    });
+
+    Ok(address)
+}
+
+/// Plans the declaration of the classes that aren't declared yet.
+async fn plan_declarations<A>(
+    account: Option<&A>,
+    classes: &[ClassMigration],
+    steps: &mut Vec<PlannedStep>,
+) -> Result<()>
+where
+    A: ConnectedAccount + Sync,
+{
+    for class in classes {
+        if class.declared || is_declared(account, class.class.local).await {
+            continue;
+        }
+
+        steps.push(PlannedStep::Declare {
+            name: class.class.name.clone(),
+            class_hash: class.class.local,
+            remote_class_hash: class.class.remote,
+            estimated_fee: estimate_declaration(account, class).await?,
+        });
+    }
+
+    Ok(())
+}
+
+/// Whether the class is declared on the chain, never known offline.
+async fn is_declared<A>(account: Option<&A>, class_hash: FieldElement) -> bool
+where
+    A: ConnectedAccount + Sync,
+{
+    let Some(account) = account else { return false };
+    account.provider().get_class(BlockId::Tag(BlockTag::Pending), class_hash).await.is_ok()
+}
+
+async fn estimate_declaration<A, D>(account: Option<&A>, class: &D) -> Result<Option<u64>>
+where
+    A: ConnectedAccount + Sync,
+    D: Declarable,
+{
+    let Some(account) = account else { return Ok(None) };
+    let (flattened_class, casm_class_hash) =
+        prepare_contract_declaration_params(class.artifact_path())?;
+
+    let estimate = account.declare(Arc::new(flattened_class), casm_class_hash).estimate_fee().await;
+    Ok(estimate.ok().map(|fee| fee.overall_fee))
+}
+
+async fn estimate_calls<A>(account: Option<&A>, calls: Vec<Call>) -> Option<u64>
+where
+    A: ConnectedAccount + Sync,
+{
+    account?.execute(calls).estimate_fee().await.ok().map(|fee| fee.overall_fee)
+}
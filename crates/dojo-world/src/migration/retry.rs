@@ -0,0 +1,139 @@
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use starknet::accounts::AccountError;
+use starknet::providers::ProviderError;
+
+use super::object::MigrationError;
+
+#[cfg(test)]
+#[path = "retry_test.rs"]
+mod test;
+
+/// How many times an RPC operation is attempted and how long to wait between the attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts of an operation, the first one included. `1` disables retries.
+    pub max_attempts: u32,
+    /// Wait before the first retry, growing after every failed retry following `curve`.
+    pub initial_backoff: Duration,
+    /// Cap on the wait between two attempts.
+    pub max_backoff: Duration,
+    pub curve: BackoffCurve,
+    /// Time after which a transaction the node doesn't know about is considered dropped.
+    pub receipt_timeout: Duration,
+}
+
+/// How the wait between two attempts grows with the number of failed attempts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackoffCurve {
+    /// Doubled after every retry.
+    #[default]
+    Exponential,
+    /// Increased by the initial backoff after every retry.
+    Linear,
+    /// Always the initial backoff.
+    Constant,
+}
+
+impl FromStr for BackoffCurve {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "exponential" => Ok(Self::Exponential),
+            "linear" => Ok(Self::Linear),
+            "constant" => Ok(Self::Constant),
+            _ => Err(anyhow!(
+                "Unknown backoff curve `{s}`, expected `exponential`, `linear` or `constant`"
+            )),
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            curve: BackoffCurve::Exponential,
+            receipt_timeout: Duration::from_secs(300),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Attempts every operation once.
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Wait after the failed attempt number `attempt`, starting at 1.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = match self.curve {
+            BackoffCurve::Exponential => 2_u32.saturating_pow(attempt.saturating_sub(1)),
+            BackoffCurve::Linear => attempt.max(1),
+            BackoffCurve::Constant => 1,
+        };
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    /// Runs `operation` until it succeeds, fails with an error that isn't retryable or runs out
+    /// of attempts, the last error being returned.
+    pub async fn run<F, Fut, T, E>(&self, mut operation: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: Retryable,
+    {
+        let mut attempt = 1;
+        loop {
+            match operation().await {
+                Err(e) if e.is_retryable() && attempt < self.max_attempts => {
+                    tokio::time::sleep(self.backoff(attempt)).await;
+                    attempt += 1;
+                }
+                res => return res,
+            }
+        }
+    }
+}
+
+/// Errors that may not happen again when retrying the operation that failed.
+pub trait Retryable {
+    fn is_retryable(&self) -> bool;
+}
+
+/// Errors reaching the node and rate limiting are transient, errors returned by Starknet aren't:
+/// the same request would get the same answer.
+impl<E> Retryable for ProviderError<E> {
+    fn is_retryable(&self) -> bool {
+        match self {
+            ProviderError::RateLimited | ProviderError::Other(_) => true,
+            ProviderError::StarknetError(_) | ProviderError::ArrayLengthMismatch => false,
+        }
+    }
+}
+
+impl<S, E> Retryable for AccountError<S, E> {
+    fn is_retryable(&self) -> bool {
+        match self {
+            AccountError::Provider(e) => e.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+impl<S, P> Retryable for MigrationError<S, P> {
+    fn is_retryable(&self) -> bool {
+        match self {
+            MigrationError::Migrator(e) => e.is_retryable(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use super::{BackoffCurve, RetryPolicy, Retryable};
+
+#[derive(Debug, PartialEq)]
+enum TestError {
+    Transient,
+    Fatal,
+}
+
+impl Retryable for TestError {
+    fn is_retryable(&self) -> bool {
+        *self == TestError::Transient
+    }
+}
+
+fn policy(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+        curve: BackoffCurve::Constant,
+        receipt_timeout: Duration::ZERO,
+    }
+}
+
+#[test]
+fn test_backoff() {
+    let policy = RetryPolicy {
+        max_attempts: 10,
+        initial_backoff: Duration::from_millis(500),
+        max_backoff: Duration::from_secs(3),
+        curve: BackoffCurve::Exponential,
+        receipt_timeout: Duration::ZERO,
+    };
+    assert_eq!(policy.backoff(1), Duration::from_millis(500));
+    assert_eq!(policy.backoff(2), Duration::from_secs(1));
+    assert_eq!(policy.backoff(3), Duration::from_secs(2));
+    assert_eq!(policy.backoff(4), Duration::from_secs(3));
+    assert_eq!(policy.backoff(100), Duration::from_secs(3));
+
+    let linear = RetryPolicy { curve: BackoffCurve::Linear, ..policy };
+    assert_eq!(linear.backoff(1), Duration::from_millis(500));
+    assert_eq!(linear.backoff(2), Duration::from_secs(1));
+    assert_eq!(linear.backoff(3), Duration::from_millis(1500));
+    assert_eq!(linear.backoff(100), Duration::from_secs(3));
+
+    let constant = RetryPolicy { curve: BackoffCurve::Constant, ..policy };
+    assert_eq!(constant.backoff(1), Duration::from_millis(500));
+    assert_eq!(constant.backoff(100), Duration::from_millis(500));
+
+    assert_eq!("Linear".parse::<BackoffCurve>().unwrap(), BackoffCurve::Linear);
+    assert!("quadratic".parse::<BackoffCurve>().is_err());
+}
+
+#[tokio::test]
+async fn test_retry_transient_errors() {
+    let attempts = Cell::new(0);
+    let res = policy(3)
+        .run(|| async {
+            attempts.set(attempts.get() + 1);
+            if attempts.get() < 3 { Err(TestError::Transient) } else { Ok(attempts.get()) }
+        })
+        .await;
+    assert_eq!(res, Ok(3));
+
+    attempts.set(0);
+    let res: Result<(), _> = policy(3)
+        .run(|| async {
+            attempts.set(attempts.get() + 1);
+            Err(TestError::Transient)
+        })
+        .await;
+    assert_eq!(res, Err(TestError::Transient));
+    assert_eq!(attempts.get(), 3);
+}
+
+#[tokio::test]
+async fn test_no_retry_on_fatal_errors() {
+    let attempts = Cell::new(0);
+    let res: Result<(), _> = policy(3)
+        .run(|| async {
+            attempts.set(attempts.get() + 1);
+            Err(TestError::Fatal)
+        })
+        .await;
+    assert_eq!(res, Err(TestError::Fatal));
+    assert_eq!(attempts.get(), 1);
+}
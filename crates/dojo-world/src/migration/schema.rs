@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+
+use anyhow::{anyhow, bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::FieldElement;
+
+use crate::manifest::Member;
+
+#[cfg(test)]
+#[path = "schema_test.rs"]
+mod test;
+
+/// Transform of the values of a component whose schema changed, from the previous layout to the
+/// new one, declared in Scarb.toml:
+///
+/// ```toml
+/// [tool.dojo.upgrades.Position]
+/// system = "migrate_position"
+/// partitions = ["0x1"]
+/// members = { x = "x", y = "vertical", z = "0" }
+/// ```
+///
+/// The same transform is applied on-chain, through `system`, and by the indexers to their rows.
+#[serde_as]
+#[derive(Clone, Default, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SchemaUpgrade {
+    /// System executed with the partition, the id and the new values of every entity, as
+    /// `(partition: u250, entity_id: u250, values: Span<felt252>)`. Only the indexers transform
+    /// the values when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    /// Partitions holding entities of the component besides the default one, the world can't
+    /// list them.
+    #[serde_as(as = "Vec<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub partitions: Vec<FieldElement>,
+    /// Source of the members of the new schema by name. Members not listed take the value of the
+    /// previous member with the same name, or zero if there is none.
+    #[serde(default)]
+    pub members: BTreeMap<String, MemberSource>,
+}
+
+/// Where the value of a member of the new schema comes from.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "String", into = "String")]
+pub enum MemberSource {
+    /// A member of the previous schema, by name.
+    Member(String),
+    /// A constant, given in decimal or in hex.
+    Value(FieldElement),
+}
+
+impl TryFrom<String> for MemberSource {
+    type Error = anyhow::Error;
+
+    fn try_from(source: String) -> Result<Self> {
+        if source.starts_with("0x") {
+            FieldElement::from_hex_be(&source)
+                .map(Self::Value)
+                .map_err(|_| anyhow!("Invalid value `{source}`"))
+        } else if source.starts_with(|c: char| c.is_ascii_digit()) {
+            FieldElement::from_dec_str(&source)
+                .map(Self::Value)
+                .map_err(|_| anyhow!("Invalid value `{source}`"))
+        } else if !source.is_empty()
+            && source.chars().all(|c| c == '_' || c.is_ascii_alphanumeric())
+        {
+            Ok(Self::Member(source))
+        } else {
+            Err(anyhow!("Invalid member source `{source}`, expected a member name or a value"))
+        }
+    }
+}
+
+impl From<MemberSource> for String {
+    fn from(source: MemberSource) -> Self {
+        source.to_string()
+    }
+}
+
+impl Display for MemberSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Member(name) => write!(f, "{name}"),
+            Self::Value(value) => write!(f, "{value:#x}"),
+        }
+    }
+}
+
+impl SchemaUpgrade {
+    /// Source of the member `name` of the new schema.
+    pub fn source(&self, name: &str) -> MemberSource {
+        self.members.get(name).cloned().unwrap_or_else(|| MemberSource::Member(name.to_string()))
+    }
+
+    /// Fails if a listed member isn't part of the new schema, or is sourced from a member that
+    /// isn't part of the previous one.
+    pub fn validate(&self, previous: &[Member], members: &[Member]) -> Result<()> {
+        for (name, source) in &self.members {
+            if !members.iter().any(|m| m.name == *name) {
+                bail!("`{name}` isn't a member of the new schema");
+            }
+            if let MemberSource::Member(source) = source {
+                if !previous.iter().any(|m| m.name == *source) {
+                    bail!(
+                        "`{source}`, the source of `{name}`, isn't a member of the previous schema"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Values of the members of the new schema in declaration order, from the values of the
+    /// members of the previous schema as read by [`member_values`].
+    pub fn apply(
+        &self,
+        members: &[Member],
+        previous: &HashMap<String, Vec<FieldElement>>,
+    ) -> Vec<FieldElement> {
+        members
+            .iter()
+            .flat_map(|member| {
+                let size = member_size(member);
+                let mut values = match self.source(&member.name) {
+                    MemberSource::Member(name) => previous.get(&name).cloned().unwrap_or_default(),
+                    MemberSource::Value(value) => vec![value],
+                };
+                values.resize(size, FieldElement::ZERO);
+                values
+            })
+            .collect()
+    }
+}
+
+/// Number of felts a member of the given type occupies in storage.
+pub fn member_size(member: &Member) -> usize {
+    match member.ty.as_str() {
+        "u256" => 2,
+        _ => 1,
+    }
+}
+
+/// Values of the members of a component by name, from its raw storage values. Values are laid
+/// out by slot, packed members are extracted from their slot using their offset and width.
+pub fn member_values(
+    members: &[Member],
+    values: &[FieldElement],
+) -> Result<HashMap<String, Vec<FieldElement>>> {
+    let mut by_slot = members.iter().collect::<Vec<_>>();
+    by_slot.sort_by_key(|m| (m.slot, m.offset));
+
+    let mut values = values.iter();
+    let mut decoded = HashMap::new();
+    let mut slot_value = None;
+
+    for member in by_slot {
+        let mut next = || {
+            values.next().copied().with_context(|| format!("Missing value for `{}`", member.name))
+        };
+
+        let value = match (member.packed_bits(), slot_value) {
+            (Some(bits), Some((slot, packed))) if slot == member.slot => {
+                vec![unpack(packed, member.offset, bits)]
+            }
+            (Some(bits), _) => {
+                let packed = next()?;
+                slot_value = Some((member.slot, packed));
+                vec![unpack(packed, member.offset, bits)]
+            }
+            (None, _) => (0..member_size(member)).map(|_| next()).collect::<Result<_>>()?,
+        };
+
+        decoded.insert(member.name.clone(), value);
+    }
+
+    Ok(decoded)
+}
+
+/// Raw storage values of a component, laid out by slot, from the values of its members in
+/// declaration order as given by [`SchemaUpgrade::apply`]. The inverse of [`member_values`].
+pub fn pack_values(members: &[Member], values: &[FieldElement]) -> Result<Vec<FieldElement>> {
+    let mut values = values.iter().copied();
+    let mut slots = BTreeMap::<usize, Vec<FieldElement>>::new();
+
+    for member in members {
+        let size = member_size(member);
+        let member_values = values.by_ref().take(size).collect::<Vec<_>>();
+        if member_values.len() != size {
+            bail!("Missing value for `{}`", member.name);
+        }
+
+        match member.packed_bits() {
+            Some(_) => {
+                let slot = slots.entry(member.slot).or_insert_with(|| vec![FieldElement::ZERO]);
+                slot[0] = slot[0] + member_values[0] * shift(member.offset);
+            }
+            None => {
+                slots.insert(member.slot, member_values);
+            }
+        }
+    }
+
+    Ok(slots.into_values().flatten().collect())
+}
+
+/// Extracts the `bits` wide value stored at `offset` in a packed slot.
+pub fn unpack(packed: FieldElement, offset: u8, bits: u8) -> FieldElement {
+    // Packed members are at most 128 bits wide.
+    let shifted = packed.floor_div(shift(offset)).to_bytes_be();
+    let low = u128::from_be_bytes(shifted[16..].try_into().unwrap());
+    let mask = if bits >= 128 { u128::MAX } else { (1 << bits) - 1 };
+
+    FieldElement::from(low & mask)
+}
+
+/// `2^offset`, the factor of a value stored at `offset` in a packed slot.
+fn shift(offset: u8) -> FieldElement {
+    let mut shift = [0u8; 32];
+    shift[31 - offset as usize / 8] = 1 << (offset % 8);
+    FieldElement::from_bytes_be(&shift).unwrap()
+}
+
+/// Decodes the result of the world's `entities`, `(Span<u250>, Span<Span<felt252>>)`, into the
+/// ids of the entities along with their raw storage values.
+pub fn decode_entities(result: &[FieldElement]) -> Result<Vec<(FieldElement, Vec<FieldElement>)>> {
+    let mut result = result.iter().copied();
+
+    let ids = take_span(&mut result).context("Truncated entity ids")?;
+    let entities_len = take_len(&mut result)?;
+    if entities_len != ids.len() {
+        bail!("{} entity ids for {entities_len} entities", ids.len());
+    }
+
+    let mut entities = vec![];
+    for id in ids {
+        let values = take_span(&mut result)
+            .with_context(|| format!("Truncated values of entity {id:#x}"))?;
+        entities.push((id, values));
+    }
+
+    Ok(entities)
+}
+
+fn take_len(values: &mut impl Iterator<Item = FieldElement>) -> Result<usize> {
+    let len = values.next().ok_or(anyhow!("Missing length"))?;
+    u64::try_from(len).map(|len| len as usize).map_err(|_| anyhow!("Invalid length {len:#x}"))
+}
+
+/// Takes a span serialized as its length followed by its elements.
+fn take_span(values: &mut impl Iterator<Item = FieldElement>) -> Result<Vec<FieldElement>> {
+    let len = take_len(values)?;
+    let span = values.by_ref().take(len).collect::<Vec<_>>();
+    if span.len() != len {
+        bail!("Expected {len} elements, got {}", span.len());
+    }
+    Ok(span)
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use starknet::core::types::FieldElement;
+
+use super::{decode_entities, member_values, pack_values, MemberSource, SchemaUpgrade};
+use crate::manifest::Member;
+
+fn member(name: &str, ty: &str, slot: usize, offset: u8) -> Member {
+    Member { name: name.into(), ty: ty.into(), slot, offset, bits: None, variants: None, doc: None }
+}
+
+#[test]
+fn test_member_source() {
+    let parse = |source: &str| MemberSource::try_from(source.to_string()).unwrap();
+    assert_eq!(parse("health"), MemberSource::Member("health".into()));
+    assert_eq!(parse("10"), MemberSource::Value(FieldElement::from(10_u8)));
+    assert_eq!(parse("0x10"), MemberSource::Value(FieldElement::from(16_u8)));
+    assert!(MemberSource::try_from("a-b".to_string()).is_err());
+
+    let upgrade: SchemaUpgrade =
+        toml::from_str("system = \"migrate\"\nmembers = { z = \"0\", y = \"x\" }").unwrap();
+    assert_eq!(upgrade.system.as_deref(), Some("migrate"));
+    assert_eq!(upgrade.source("y"), MemberSource::Member("x".into()));
+    assert_eq!(upgrade.source("x"), MemberSource::Member("x".into()));
+}
+
+#[test]
+fn test_schema_upgrade() {
+    let previous = vec![member("x", "u32", 0, 0), member("y", "u32", 0, 32)];
+    let members =
+        vec![member("x", "u32", 0, 0), member("z", "u256", 1, 0), member("w", "felt252", 2, 0)];
+
+    // x and y packed in a single slot
+    let packed = FieldElement::from((7_u64 << 32) | 3);
+    let values = member_values(&previous, &[packed]).unwrap();
+    assert_eq!(values["x"], vec![FieldElement::from(3_u8)]);
+    assert_eq!(values["y"], vec![FieldElement::from(7_u8)]);
+
+    let upgrade = SchemaUpgrade {
+        system: None,
+        partitions: vec![],
+        members: [("z".to_string(), MemberSource::Member("y".into()))].into_iter().collect(),
+    };
+    upgrade.validate(&previous, &members).unwrap();
+    assert_eq!(
+        upgrade.apply(&members, &values),
+        vec![
+            FieldElement::from(3_u8),
+            FieldElement::from(7_u8),
+            FieldElement::ZERO,
+            FieldElement::ZERO,
+        ]
+    );
+
+    let unknown = SchemaUpgrade {
+        system: None,
+        partitions: vec![],
+        members: [("w".to_string(), MemberSource::Member("v".into()))].into_iter().collect(),
+    };
+    assert!(unknown.validate(&previous, &members).is_err());
+    assert!(upgrade.validate(&previous, &previous).is_err());
+    assert!(member_values(&members, &[FieldElement::ONE]).is_err());
+
+    let defaults = SchemaUpgrade::default().apply(&members, &HashMap::new());
+    assert_eq!(defaults, vec![FieldElement::ZERO; 4]);
+}
+
+#[test]
+fn test_pack_values() {
+    let members = vec![
+        member("x", "u32", 0, 0),
+        member("owner", "felt252", 1, 0),
+        member("y", "u32", 0, 32),
+        member("supply", "u256", 2, 0),
+    ];
+    let values = [3_u8, 9, 7, 1, 2].map(FieldElement::from);
+
+    let packed = pack_values(&members, &values).unwrap();
+    assert_eq!(
+        packed,
+        vec![
+            FieldElement::from(3_u64 + (7_u64 << 32)),
+            FieldElement::from(9_u8),
+            FieldElement::ONE,
+            FieldElement::TWO,
+        ]
+    );
+
+    let unpacked = member_values(&members, &packed).unwrap();
+    assert_eq!(unpacked["x"], vec![FieldElement::from(3_u8)]);
+    assert_eq!(unpacked["y"], vec![FieldElement::from(7_u8)]);
+    assert_eq!(unpacked["supply"], vec![FieldElement::ONE, FieldElement::TWO]);
+
+    assert!(pack_values(&members, &values[..4]).is_err());
+}
+
+#[test]
+fn test_declared_bits() {
+    // x is declared 4 bits wide, y follows it at offset 4.
+    let members = vec![
+        Member { bits: Some(4), ..member("x", "u8", 0, 0) },
+        member("y", "u8", 0, 4),
+    ];
+
+    let packed = pack_values(&members, &[FieldElement::from(9_u8), FieldElement::THREE]).unwrap();
+    assert_eq!(packed, vec![FieldElement::from(9_u8 + (3 << 4))]);
+
+    let unpacked = member_values(&members, &packed).unwrap();
+    assert_eq!(unpacked["x"], vec![FieldElement::from(9_u8)]);
+    assert_eq!(unpacked["y"], vec![FieldElement::THREE]);
+}
+
+#[test]
+fn test_decode_entities() {
+    let felts = |values: &[u8]| values.iter().map(|v| FieldElement::from(*v)).collect::<Vec<_>>();
+
+    let entities = decode_entities(&felts(&[2, 10, 11, 2, 1, 5, 2, 6, 7])).unwrap();
+    assert_eq!(entities, vec![(felts(&[10])[0], felts(&[5])), (felts(&[11])[0], felts(&[6, 7]))]);
+
+    assert!(decode_entities(&felts(&[2, 10, 11, 1, 1, 5])).is_err());
+    assert!(decode_entities(&felts(&[1, 10, 1, 2, 5])).is_err());
+}
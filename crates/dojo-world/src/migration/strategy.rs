@@ -1,18 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 use anyhow::{anyhow, Context, Result};
 use camino::Utf8PathBuf;
-use starknet::accounts::ConnectedAccount;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::accounts::{AccountError, Call, ConnectedAccount};
+use starknet::core::serde::unsigned_field_element::UfeHex;
 use starknet::core::types::{FieldElement, InvokeTransactionResult};
 use starknet::providers::Provider;
 
-use crate::config::WorldConfig;
+use crate::config::{FeeConfig, WorldConfig};
+use crate::manifest::Manifest;
+use crate::migration::cache::{DeclaredClass, DeclaredClasses};
+use crate::migration::deployment::{DeployedContract, DeploymentManifest, RegisteredClass};
+use crate::migration::dispatcher::Dispatcher;
+use crate::migration::finality::{wait_for_finality, Finality};
+use crate::migration::nonce::{NonceManager, NonceUse};
 use crate::migration::object::{
-    ClassMigration, ContractMigration, Declarable, DeployOutput, Deployable, MigrationError,
-    RegisterOutput, WorldContract,
+    ClassMigration, ContractMigration, Declarable, DeclareOutput, DeployOutput, Deployable,
+    MigrationError, RegisterOutput, WorldContract,
 };
+use crate::migration::retry::RetryPolicy;
 use crate::migration::world::{ClassDiff, ContractDiff, WorldDiff};
 
 pub type MigrationResult<S, P> = Result<MigrationOutput, MigrationError<S, P>>;
@@ -21,6 +33,44 @@ pub type MigrationResult<S, P> = Result<MigrationOutput, MigrationError<S, P>>;
 #[path = "strategy_test.rs"]
 mod test;
 
+/// Controls how much of the local world is migrated relative to the remote one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MigrationKind {
+    /// Declare and register every class, reusing the remote world if there is one.
+    Full,
+    /// Only migrate the classes that differ from the remote world.
+    #[default]
+    Incremental,
+    /// Deploy a new world and executor with a fresh salt and register every class on it.
+    ForceRedeploy,
+}
+
+impl FromStr for MigrationKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "full" => Ok(Self::Full),
+            "incremental" => Ok(Self::Incremental),
+            "force-redeploy" => Ok(Self::ForceRedeploy),
+            _ => Err(anyhow!(
+                "Unknown migration strategy `{s}`, expected one of `full`, `incremental` or \
+                 `force-redeploy`"
+            )),
+        }
+    }
+}
+
+impl Display for MigrationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => write!(f, "full"),
+            Self::Incremental => write!(f, "incremental"),
+            Self::ForceRedeploy => write!(f, "force-redeploy"),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MigrationOutput {
     pub world: Option<DeployOutput>,
@@ -29,6 +79,149 @@ pub struct MigrationOutput {
     pub components: RegisterOutput,
 }
 
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmittedTransaction {
+    pub description: String,
+    #[serde_as(as = "UfeHex")]
+    pub transaction_hash: FieldElement,
+    #[serde_as(as = "Vec<UfeHex>")]
+    #[serde(default)]
+    pub calldata: Vec<FieldElement>,
+    /// Class declared or deployed by the transaction.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub class_hash: Option<FieldElement>,
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub salt: Option<FieldElement>,
+    /// Address of the contract deployed by the transaction.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub contract_address: Option<FieldElement>,
+    /// Account that sent the transaction, when it isn't the migrator.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default)]
+    pub sender: Option<FieldElement>,
+}
+
+/// Transactions submitted so far by a migration, so that an interrupted or failed migration can
+/// report what already went through and be resumed.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct MigrationCheckpoint {
+    pub submitted: Vec<SubmittedTransaction>,
+    /// File the checkpoint is written to after every submitted transaction.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl MigrationCheckpoint {
+    pub fn load_from_path<P>(path: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        serde_json::from_reader(fs::File::open(path)?)
+            .map_err(|e| anyhow!("Failed to load migration checkpoint: {e}"))
+    }
+
+    /// Writes the checkpoint to `path` after every transaction submitted from now on, so that it
+    /// survives the process being killed.
+    pub fn persist_to(&mut self, path: impl Into<PathBuf>) {
+        self.path = Some(path.into());
+    }
+
+    /// The last transaction submitted for the step with this description.
+    pub fn find(&self, description: &str) -> Option<&SubmittedTransaction> {
+        self.submitted.iter().rev().find(|tx| tx.description == description)
+    }
+
+    /// Every transaction submitted for the step with this description, in order. Steps sent in
+    /// several batches have one per batch.
+    pub fn find_all<'a>(
+        &'a self,
+        description: &'a str,
+    ) -> impl Iterator<Item = &'a SubmittedTransaction> + 'a {
+        self.submitted.iter().filter(move |tx| tx.description == description)
+    }
+
+    fn push(&mut self, tx: SubmittedTransaction) {
+        self.submitted.push(tx);
+
+        if let Some(path) = &self.path {
+            if let Err(e) = self.write_to_path(path) {
+                eprintln!("warning: {e}");
+            }
+        }
+    }
+
+    fn record(
+        &mut self,
+        description: impl Into<String>,
+        transaction_hash: FieldElement,
+        calldata: Vec<FieldElement>,
+    ) {
+        self.push(SubmittedTransaction {
+            description: description.into(),
+            transaction_hash,
+            calldata,
+            class_hash: None,
+            salt: None,
+            contract_address: None,
+            sender: None,
+        });
+    }
+
+    fn record_declare(&mut self, name: &str, output: &DeclareOutput) {
+        self.record_declare_by(name, output, None);
+    }
+
+    fn record_declare_by(
+        &mut self,
+        name: &str,
+        output: &DeclareOutput,
+        sender: Option<FieldElement>,
+    ) {
+        self.push(SubmittedTransaction {
+            description: format!("declare {name}"),
+            transaction_hash: output.transaction_hash,
+            calldata: vec![],
+            class_hash: Some(output.class_hash),
+            salt: None,
+            contract_address: None,
+            sender,
+        });
+    }
+
+    fn record_deploy(
+        &mut self,
+        contract: &str,
+        migration: &ContractMigration,
+        output: &DeployOutput,
+        constructor_calldata: Vec<FieldElement>,
+    ) {
+        if let Some(declare_res) = &output.declare_res {
+            self.record_declare(contract, declare_res);
+        }
+        self.push(SubmittedTransaction {
+            description: format!("deploy {contract}"),
+            transaction_hash: output.transaction_hash,
+            calldata: constructor_calldata,
+            class_hash: Some(migration.contract.local),
+            salt: Some(migration.salt),
+            contract_address: Some(output.contract_address),
+            sender: None,
+        });
+    }
+
+    pub fn write_to_path<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(path, content).context("Failed to write migration checkpoint")
+    }
+}
+
 #[derive(Debug)]
 pub struct MigrationStrategy {
     pub world: Option<ContractMigration>,
@@ -36,15 +229,235 @@ pub struct MigrationStrategy {
     pub systems: Vec<ClassMigration>,
     pub components: Vec<ClassMigration>,
     pub world_config: WorldConfig,
+    pub checkpoint: MigrationCheckpoint,
+    /// Fees of the transactions sent by the migration.
+    pub fees: FeeConfig,
+    /// Finality awaited before sending the transactions depending on previous ones.
+    pub finality: Finality,
+    /// Retries of the RPC operations failing to reach the node.
+    pub retry: RetryPolicy,
+    /// Most registrations sent in a single transaction, all of a kind at once when not set.
+    pub max_calls_per_tx: Option<usize>,
 }
 
 impl MigrationStrategy {
-    fn world_address(&self) -> Option<FieldElement> {
+    pub(crate) fn world_address(&self) -> Option<FieldElement> {
         match &self.world {
             Some(c) => c.contract_address,
             None => self.world_config.address,
         }
     }
+
+    /// Resumes a migration from the checkpoint of a previous attempt: the classes it declared and
+    /// the contracts it deployed are skipped, as long as they match the local artifacts.
+    pub fn resume_from(&mut self, previous: MigrationCheckpoint) {
+        for class in self.components.iter_mut().chain(self.systems.iter_mut()) {
+            let declared = previous.find(&format!("declare {}", class.class.name));
+            if declared.and_then(|tx| tx.class_hash) == Some(class.class.local) {
+                class.declared = true;
+            }
+        }
+
+        for (name, contract) in [("executor", &mut self.executor), ("world", &mut self.world)] {
+            let deployed = previous.find(&format!("deploy {name}"));
+            let (Some(contract), Some(deployed)) = (contract, deployed) else {
+                continue;
+            };
+            if deployed.class_hash == Some(contract.contract.local) {
+                contract.salt = deployed.salt.unwrap_or(contract.salt);
+                contract.contract_address = deployed.contract_address;
+            }
+        }
+
+        self.checkpoint.submitted = previous.submitted;
+    }
+
+    /// Marks the components and systems `cache` knows to be declared on the chain, they are
+    /// then neither looked up nor declared again. Returns how many were found.
+    pub fn skip_cached_declarations(
+        &mut self,
+        cache: &DeclaredClasses,
+        chain_id: FieldElement,
+    ) -> usize {
+        let mut cached = 0;
+        for class in self.components.iter_mut().chain(self.systems.iter_mut()) {
+            if !class.declared && cache.get(chain_id, class.class.local).is_some() {
+                class.declared = true;
+                cached += 1;
+            }
+        }
+        cached
+    }
+
+    /// Adds the components and systems to `cache` once the migration completed, all of them
+    /// being declared by then, along with the transactions that declared them.
+    pub fn cache_declarations(&self, cache: &mut DeclaredClasses, chain_id: FieldElement) {
+        for class in self.components.iter().chain(self.systems.iter()) {
+            if cache.get(chain_id, class.class.local).is_some() {
+                continue;
+            }
+
+            let transaction_hash = self
+                .checkpoint
+                .find(&format!("declare {}", class.class.name))
+                .filter(|tx| tx.class_hash == Some(class.class.local))
+                .map(|tx| tx.transaction_hash);
+            cache.insert(chain_id, class.class.local, DeclaredClass { transaction_hash });
+        }
+    }
+
+    /// Describes the world resulting from this migration, once executed. Salts, addresses,
+    /// transactions and the registration order of what wasn't migrated this time are taken from
+    /// `previous`. Block numbers of the new transactions are left to
+    /// [`DeploymentManifest::resolve_block_numbers`].
+    pub fn deployment_manifest(
+        &self,
+        local: &Manifest,
+        previous: Option<&DeploymentManifest>,
+    ) -> DeploymentManifest {
+        let previous = previous.cloned().unwrap_or_default();
+        let submitted =
+            |description: &str| self.checkpoint.find(description).map(|tx| tx.transaction_hash);
+
+        let world = DeployedContract {
+            name: "World".into(),
+            class_hash: local.world,
+            salt: self.world.as_ref().map_or(previous.world.salt, |w| w.salt),
+            address: self.world_address().or(previous.world.address),
+            transaction_hash: match self.world {
+                Some(_) => submitted("deploy world"),
+                None => previous.world.transaction_hash,
+            },
+            block_number: match self.world {
+                Some(_) => None,
+                None => previous.world.block_number,
+            },
+        };
+
+        let executor = DeployedContract {
+            name: "Executor".into(),
+            class_hash: local.executor,
+            salt: self.executor.as_ref().map_or(previous.executor.salt, |e| e.salt),
+            address: self
+                .executor
+                .as_ref()
+                .and_then(|e| e.contract_address)
+                .or(previous.executor.address),
+            transaction_hash: match self.executor {
+                Some(_) => submitted("deploy executor"),
+                None => previous.executor.transaction_hash,
+            },
+            block_number: match self.executor {
+                Some(_) => None,
+                None => previous.executor.block_number,
+            },
+        };
+
+        let mut components = registration_order(
+            &previous.components,
+            local.components.iter().map(|c| (c.name.clone(), c.class_hash)),
+            self.checkpoint.find_all("register components").collect(),
+        );
+        for component in &mut components {
+            if let Some(local) = local.components.iter().find(|c| c.name == component.name) {
+                component.members = local.members.clone();
+            }
+        }
+        let systems = registration_order(
+            &previous.systems,
+            local.systems.iter().map(|s| {
+                (s.name.strip_suffix("System").unwrap_or(&s.name).to_string(), s.class_hash)
+            }),
+            self.checkpoint.find_all("register systems").collect(),
+        );
+
+        DeploymentManifest { world, executor, components, systems, fees: None }
+    }
+
+    /// Deployment manifest of the world once the classes of a registration, prepared by
+    /// [`prepare_for_registration`], are registered. They replace the classes of the same name in
+    /// `previous` or are appended to them, the rest of the deployment is kept as is.
+    pub fn registration_manifest(
+        &self,
+        local: &Manifest,
+        previous: &DeploymentManifest,
+    ) -> DeploymentManifest {
+        let merged = |previous: &[RegisteredClass], registered: &[ClassMigration]| {
+            let mut classes: Vec<_> =
+                previous.iter().map(|c| (c.name.clone(), c.class_hash)).collect();
+            for class in registered {
+                match classes.iter_mut().find(|(name, _)| *name == class.class.name) {
+                    Some((_, class_hash)) => *class_hash = class.class.local,
+                    None => classes.push((class.class.name.clone(), class.class.local)),
+                }
+            }
+            classes
+        };
+
+        let mut components = registration_order(
+            &previous.components,
+            merged(&previous.components, &self.components).into_iter(),
+            self.checkpoint.find_all("register components").collect(),
+        );
+        for component in &mut components {
+            let members = if self.components.iter().any(|c| c.class.name == component.name) {
+                local.components.iter().find(|c| c.name == component.name).map(|c| &c.members)
+            } else {
+                previous.components.iter().find(|c| c.name == component.name).map(|c| &c.members)
+            };
+            component.members = members.cloned().unwrap_or_default();
+        }
+        let systems = registration_order(
+            &previous.systems,
+            merged(&previous.systems, &self.systems).into_iter(),
+            self.checkpoint.find_all("register systems").collect(),
+        );
+
+        DeploymentManifest { components, systems, fees: None, ..previous.clone() }
+    }
+}
+
+/// Keeps the classes already registered in their previous order and appends the new ones. The
+/// classes registered by one of `registrations` point to it, the others keep their previous
+/// transaction.
+fn registration_order(
+    previous: &[RegisteredClass],
+    local: impl Iterator<Item = (String, FieldElement)>,
+    registrations: Vec<&SubmittedTransaction>,
+) -> Vec<RegisteredClass> {
+    let mut local: Vec<_> = local.collect();
+    let mut classes = vec![];
+
+    let registered = |name: String, class_hash: FieldElement| {
+        if let Some(tx) = registrations.iter().rev().find(|tx| tx.calldata.contains(&class_hash)) {
+            return RegisteredClass {
+                name,
+                class_hash,
+                transaction_hash: Some(tx.transaction_hash),
+                block_number: None,
+                members: vec![],
+            };
+        }
+
+        let previous = previous.iter().find(|c| c.name == name && c.class_hash == class_hash);
+        RegisteredClass {
+            name,
+            class_hash,
+            transaction_hash: previous.and_then(|c| c.transaction_hash),
+            block_number: previous.and_then(|c| c.block_number),
+            members: vec![],
+        }
+    };
+
+    for class in previous {
+        if let Some(index) = local.iter().position(|(name, _)| *name == class.name) {
+            let (name, class_hash) = local.remove(index);
+            classes.push(registered(name, class_hash));
+        }
+    }
+    classes.extend(local.into_iter().map(|(name, class_hash)| registered(name, class_hash)));
+
+    classes
 }
 
 impl MigrationStrategy {
@@ -56,42 +469,87 @@ impl MigrationStrategy {
         A: ConnectedAccount + Sync,
     {
         let executor_output = match &mut self.executor {
+            Some(ContractMigration { contract_address: Some(address), .. }) => {
+                println!("- Executor contract already deployed at {address:#x}\n");
+                None
+            }
             Some(executor) => {
-                let res = executor.deploy(vec![], &migrator).await?;
+                let res = executor
+                    .deploy(vec![], &migrator, &self.fees, self.finality, &self.retry)
+                    .await?;
+                self.checkpoint.record_deploy("executor", executor, &res, vec![]);
+                wait_for_finality(
+                    migrator.provider(),
+                    res.transaction_hash,
+                    self.finality,
+                    &self.retry,
+                )
+                .await?;
 
                 println!(
                     r"- Executor contract:
-    Declared at tx: {:#x}
+    Declared at tx: {}
     Deployed at tx: {:#x}
 ",
-                    res.declare_res.transaction_hash, res.contract_address
+                    declared_at(&res.declare_res),
+                    res.contract_address
                 );
 
-                if self.world.is_none() {
-                    let addr = self.world_address().ok_or(MigrationError::WorldAddressNotFound)?;
-                    WorldContract::new(addr, &migrator).set_executor(res.contract_address).await?;
-                }
-
                 Some(res)
             }
             None => None,
         };
 
+        if let Some(executor) = self.executor.as_ref().and_then(|e| e.contract_address) {
+            let done = self
+                .checkpoint
+                .find("set executor")
+                .map_or(false, |tx| tx.calldata == vec![executor]);
+            if self.world.is_none() && !done {
+                let addr = self.world_address().ok_or(MigrationError::WorldAddressNotFound)?;
+                let InvokeTransactionResult { transaction_hash } =
+                    WorldContract::new(addr, &migrator)
+                        .with_fees(self.fees)
+                        .with_retry(self.retry)
+                        .set_executor(executor)
+                        .await?;
+                self.checkpoint.record("set executor", transaction_hash, vec![executor]);
+            }
+        }
+
         let world_output = match &mut self.world {
+            Some(ContractMigration { contract_address: Some(address), .. }) => {
+                println!("- World contract already deployed at {address:#x}\n");
+                None
+            }
             Some(world) => {
+                let constructor_calldata =
+                    vec![self.executor.as_ref().unwrap().contract_address.unwrap()];
                 let res = world
                     .deploy(
-                        vec![self.executor.as_ref().unwrap().contract_address.unwrap()],
+                        constructor_calldata.clone(),
                         &migrator,
+                        &self.fees,
+                        self.finality,
+                        &self.retry,
                     )
                     .await?;
+                self.checkpoint.record_deploy("world", world, &res, constructor_calldata);
+                wait_for_finality(
+                    migrator.provider(),
+                    res.transaction_hash,
+                    self.finality,
+                    &self.retry,
+                )
+                .await?;
 
                 println!(
                     r"- World contract:
-    Declared at tx: {:#x}
+    Declared at tx: {}
     Deployed at tx: {:#x}
 ",
-                    res.declare_res.transaction_hash, res.contract_address
+                    declared_at(&res.declare_res),
+                    res.contract_address
                 );
 
                 Some(res)
@@ -115,60 +573,220 @@ impl MigrationStrategy {
         })
     }
 
-    async fn register_components<A>(
-        &self,
+    /// Declares every component and system up front, spreading the declarations across the
+    /// dispatcher's accounts. The registration then skips the classes declared here.
+    pub async fn declare_classes<A>(
+        &mut self,
+        dispatcher: &Dispatcher<A>,
+    ) -> Result<(), MigrationError<A::SignError, <A::Provider as Provider>::Error>>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        let mut transaction_hashes = vec![];
+        for classes in [&mut self.components, &mut self.systems] {
+            // Classes declared by a previous attempt or known to the cache are skipped.
+            let pending = classes.iter().filter(|c| !c.declared).cloned().collect::<Vec<_>>();
+            let results = dispatcher.declare_all(&pending).await;
+            for (class, (sender, res)) in classes.iter_mut().filter(|c| !c.declared).zip(results) {
+                let name = &class.class.name;
+                match res? {
+                    Some(res) => {
+                        println!("{name} declared at tx: {:#x}", res.transaction_hash);
+                        self.checkpoint.record_declare_by(name, &res, Some(sender));
+                        transaction_hashes.push(res.transaction_hash);
+                    }
+                    None => println!("{name} already declared"),
+                }
+                class.declared = true;
+            }
+        }
+
+        // The registration calls into the classes, they must be declared by then.
+        dispatcher.wait_for_transactions(&transaction_hashes, self.finality).await?;
+
+        Ok(())
+    }
+
+    pub async fn register_components<A>(
+        &mut self,
         migrator: &A,
     ) -> Result<RegisterOutput, MigrationError<A::SignError, <A::Provider as Provider>::Error>>
     where
         A: ConnectedAccount + Sync,
     {
-        let mut declare_output = vec![];
-        for component in &self.components {
-            let res = component.declare(migrator).await?;
+        let components = self.components.clone();
+        let (class_hashes, declare_output) = self.declare_in_order(migrator, &components).await?;
 
-            println!("{} declared at tx: {:#x}", component.class.name, res.transaction_hash);
+        let world_address = self.world_address().ok_or(MigrationError::WorldAddressNotFound)?;
 
-            declare_output.push(res);
-        }
+        let world =
+            WorldContract::new(world_address, migrator).with_fees(self.fees).with_retry(self.retry);
+        let calls = world.register_components_calls(&class_hashes);
+        let transaction_hash =
+            self.register_classes(&world, "register components", class_hashes, calls).await?;
+
+        Ok(RegisterOutput { transaction_hash, declare_output })
+    }
+
+    pub async fn register_systems<A>(
+        &mut self,
+        migrator: &A,
+    ) -> Result<RegisterOutput, MigrationError<A::SignError, <A::Provider as Provider>::Error>>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        let systems = self.systems.clone();
+        let (class_hashes, declare_output) = self.declare_in_order(migrator, &systems).await?;
 
         let world_address = self.world_address().ok_or(MigrationError::WorldAddressNotFound)?;
 
-        let InvokeTransactionResult { transaction_hash } =
-            WorldContract::new(world_address, migrator)
-                .register_components(
-                    &declare_output.iter().map(|o| o.class_hash).collect::<Vec<_>>(),
-                )
-                .await?;
+        let world =
+            WorldContract::new(world_address, migrator).with_fees(self.fees).with_retry(self.retry);
+        let calls = world.register_systems_calls(&class_hashes);
+        let transaction_hash =
+            self.register_classes(&world, "register systems", class_hashes, calls).await?;
 
         Ok(RegisterOutput { transaction_hash, declare_output })
     }
 
-    async fn register_systems<A>(
-        &self,
+    /// Declares the classes not declared ahead of their registration, back to back with the
+    /// nonces of a [`NonceManager`], and waits for the declarations to reach finality. Returns
+    /// the class hashes in the order of `classes`, and the declarations sent.
+    ///
+    /// A declaration the node rejects leaves a gap in the nonces, holding back the declarations
+    /// sent after it. The declarations of the gaps are sent once more, with the same nonces.
+    async fn declare_in_order<A>(
+        &mut self,
         migrator: &A,
-    ) -> Result<RegisterOutput, MigrationError<A::SignError, <A::Provider as Provider>::Error>>
+        classes: &[ClassMigration],
+    ) -> Result<
+        (Vec<FieldElement>, Vec<DeclareOutput>),
+        MigrationError<A::SignError, <A::Provider as Provider>::Error>,
+    >
     where
         A: ConnectedAccount + Sync,
     {
+        let nonces = NonceManager::new(migrator, self.retry);
         let mut declare_output = vec![];
-        for system in &self.systems {
-            let res = system.declare(migrator).await?;
+        let mut class_hashes = vec![];
+        // The class declared with each nonce, and the index of its declaration.
+        let mut sent = HashMap::new();
+        for class in classes {
+            if class.declared {
+                class_hashes.push(class.class.local);
+                continue;
+            }
 
-            println!("{} declared at tx: {:#x}", system.class.name, res.transaction_hash);
+            let nonce = nonces.reserve().await.map_err(AccountError::Provider)?;
+            match class.declare_with_nonce(migrator, Some(nonce), &self.fees, &self.retry).await {
+                Ok(res) => {
+                    println!("{} declared at tx: {:#x}", class.class.name, res.transaction_hash);
+                    nonces.settle(nonce, NonceUse::Used(res.transaction_hash)).await;
+                    self.checkpoint.record_declare(&class.class.name, &res);
+                    class_hashes.push(res.class_hash);
+                    sent.insert(nonce, (class, declare_output.len()));
+                    declare_output.push(res);
+                }
+                Err(MigrationError::ClassAlreadyDeclared) => {
+                    nonces.settle(nonce, NonceUse::Unused).await;
+                    println!("{} already declared", class.class.name);
+                    class_hashes.push(class.class.local);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Declarations are sent in order, the last one reaching finality implies the others did.
+        let Some(last) = declare_output.last().map(|res| res.transaction_hash) else {
+            return Ok((class_hashes, declare_output));
+        };
+        let provider = migrator.provider();
+        let Err(unfinalized) = wait_for_finality(provider, last, self.finality, &self.retry).await
+        else {
+            return Ok((class_hashes, declare_output));
+        };
 
-            declare_output.push(res);
+        let gaps = nonces.gaps().await.map_err(AccountError::Provider)?;
+        if gaps.is_empty() {
+            return Err(unfinalized.into());
+        }
+        for nonce in gaps {
+            let Some(&(class, index)) = sent.get(&nonce) else {
+                return Err(unfinalized.into());
+            };
+            let res =
+                class.declare_with_nonce(migrator, Some(nonce), &self.fees, &self.retry).await?;
+            println!("{} declared again at tx: {:#x}", class.class.name, res.transaction_hash);
+            nonces.settle(nonce, NonceUse::Used(res.transaction_hash)).await;
+            self.checkpoint.record_declare(&class.class.name, &res);
+            declare_output[index] = res;
         }
 
-        let world_address = self.world_address().ok_or(MigrationError::WorldAddressNotFound)?;
+        let last = declare_output.last().expect("a declaration was sent").transaction_hash;
+        wait_for_finality(provider, last, self.finality, &self.retry).await?;
 
-        let InvokeTransactionResult { transaction_hash } =
-            WorldContract::new(world_address, migrator)
-                .register_components(
-                    &declare_output.iter().map(|o| o.class_hash).collect::<Vec<_>>(),
-                )
-                .await?;
+        Ok((class_hashes, declare_output))
+    }
 
-        Ok(RegisterOutput { transaction_hash, declare_output })
+    /// Registers the classes that a previous attempt didn't, `max_calls_per_tx` at a time, and
+    /// returns the last registration. A batch that fails is sent again one class at a time, so
+    /// that a batch too large for the network still goes through.
+    async fn register_classes<A>(
+        &mut self,
+        world: &WorldContract<'_, A>,
+        description: &str,
+        class_hashes: Vec<FieldElement>,
+        calls: Vec<Call>,
+    ) -> Result<FieldElement, AccountError<A::SignError, <A::Provider as Provider>::Error>>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        let registered = self
+            .checkpoint
+            .find_all(description)
+            .flat_map(|tx| tx.calldata.iter().copied())
+            .collect::<HashSet<_>>();
+        let mut last = self.checkpoint.find(description).map(|tx| tx.transaction_hash);
+
+        let pending = class_hashes
+            .into_iter()
+            .zip(calls)
+            .filter(|(class_hash, _)| !registered.contains(class_hash))
+            .collect::<Vec<_>>();
+        let batches = match (pending.is_empty(), last) {
+            (true, Some(last)) => return Ok(last),
+            // Nothing to register, the transaction still marks the step as done.
+            (true, None) => vec![vec![]],
+            (false, _) => {
+                let size = self.max_calls_per_tx.unwrap_or(pending.len()).max(1);
+                pending.chunks(size).map(<[_]>::to_vec).collect()
+            }
+        };
+
+        for batch in batches {
+            let (class_hashes, calls): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+            match world.send_calls(calls.clone()).await {
+                Ok(InvokeTransactionResult { transaction_hash }) => {
+                    self.checkpoint.record(description, transaction_hash, class_hashes);
+                    last = Some(transaction_hash);
+                }
+                Err(e) if calls.len() <= 1 => return Err(e),
+                Err(e) => {
+                    println!(
+                        "Sending {} registrations at once failed, sending them one by one: {e}",
+                        calls.len()
+                    );
+                    for (class_hash, call) in class_hashes.into_iter().zip(calls) {
+                        let InvokeTransactionResult { transaction_hash } =
+                            world.send_calls(vec![call]).await?;
+                        self.checkpoint.record(description, transaction_hash, vec![class_hash]);
+                        last = Some(transaction_hash);
+                    }
+                }
+            }
+        }
+
+        Ok(last.expect("at least one batch is sent"))
     }
 }
 
@@ -178,7 +796,240 @@ pub fn prepare_for_migration(
     target_dir: Utf8PathBuf,
     diff: WorldDiff,
     world_config: WorldConfig,
+    kind: MigrationKind,
 ) -> Result<MigrationStrategy> {
+    let artifact_paths = collect_artifact_paths(target_dir)?;
+
+    // We don't need to care if a contract has already been declared or not, because
+    // the migration strategy will take care of that.
+
+    // If the world contract needs to be migrated, then all contracts need to be migrated
+    // else we need to evaluate which contracts need to be migrated.
+    let force_redeploy = kind == MigrationKind::ForceRedeploy;
+    let mut world = evaluate_contract_to_migrate(&diff.world, &artifact_paths, force_redeploy)?;
+    let mut executor =
+        evaluate_contract_to_migrate(&diff.executor, &artifact_paths, world.is_some())?;
+
+    // Redeploying with the same salt would yield the addresses of the existing contracts.
+    if force_redeploy {
+        for contract in [&mut world, &mut executor].into_iter().flatten() {
+            contract.salt = FieldElement::from(rand::random::<u64>());
+        }
+    }
+
+    let migrate_all = world.is_some() || kind == MigrationKind::Full;
+    let components =
+        evaluate_components_to_migrate(&diff.components, &artifact_paths, migrate_all)?;
+    let systems = evaluate_systems_to_migrate(&diff.systems, &artifact_paths, migrate_all)?;
+
+    Ok(MigrationStrategy {
+        world,
+        executor,
+        systems,
+        components,
+        world_config,
+        checkpoint: MigrationCheckpoint::default(),
+        fees: FeeConfig::default(),
+        finality: Finality::default(),
+        retry: RetryPolicy::default(),
+        max_calls_per_tx: None,
+    })
+}
+
+/// Construct a migration strategy replaying a previous deployment: the world and executor are
+/// deployed with the same salts and the classes are registered in the same order, which yields
+/// the same world addresses on a fresh chain. The local artifacts must match the deployed classes.
+pub fn prepare_from_deployment(
+    target_dir: Utf8PathBuf,
+    local: &Manifest,
+    deployment: &DeploymentManifest,
+    world_config: WorldConfig,
+) -> Result<MigrationStrategy> {
+    let artifact_paths = collect_artifact_paths(target_dir)?;
+
+    let mut mismatches = vec![];
+    let mut check = |name: &str, local: Option<FieldElement>, deployed: FieldElement| match local {
+        Some(local) if local == deployed => {}
+        Some(local) => mismatches.push(format!(
+            "{name}: local class {local:#x} differs from deployed {deployed:#x}"
+        )),
+        None => mismatches.push(format!("{name}: not found in the local manifest")),
+    };
+
+    check("World", Some(local.world), deployment.world.class_hash);
+    check("Executor", Some(local.executor), deployment.executor.class_hash);
+    for component in &deployment.components {
+        let class_hash =
+            local.components.iter().find(|c| c.name == component.name).map(|c| c.class_hash);
+        check(&component.name, class_hash, component.class_hash);
+    }
+    for system in &deployment.systems {
+        let class_hash = local
+            .systems
+            .iter()
+            .find(|s| s.name.strip_suffix("System").unwrap_or(&s.name) == system.name)
+            .map(|s| s.class_hash);
+        check(&system.name, class_hash, system.class_hash);
+    }
+
+    if !mismatches.is_empty() {
+        return Err(anyhow!(
+            "Local artifacts don't match the deployment manifest, rebuild with the same sources \
+             and compiler version:\n    {}",
+            mismatches.join("\n    ")
+        ));
+    }
+
+    let contract_migration = |contract: &DeployedContract| -> Result<ContractMigration> {
+        Ok(ContractMigration {
+            salt: contract.salt,
+            contract: ContractDiff {
+                name: contract.name.clone(),
+                local: contract.class_hash,
+                remote: None,
+                address: None,
+            },
+            artifact_path: find_artifact_path(&contract.name, &artifact_paths)?.clone(),
+            contract_address: None,
+        })
+    };
+
+    let class_migration = |class: &RegisteredClass, suffix: &str| -> Result<ClassMigration> {
+        Ok(ClassMigration {
+            class: ClassDiff { name: class.name.clone(), local: class.class_hash, remote: None },
+            artifact_path: find_artifact_path(&format!("{}{suffix}", class.name), &artifact_paths)?
+                .clone(),
+            declared: false,
+        })
+    };
+
+    Ok(MigrationStrategy {
+        world: Some(contract_migration(&deployment.world)?),
+        executor: Some(contract_migration(&deployment.executor)?),
+        systems: deployment
+            .systems
+            .iter()
+            .map(|s| class_migration(s, "System"))
+            .collect::<Result<_>>()?,
+        components: deployment
+            .components
+            .iter()
+            .map(|c| class_migration(c, "Component"))
+            .collect::<Result<_>>()?,
+        world_config,
+        checkpoint: MigrationCheckpoint::default(),
+        fees: FeeConfig::default(),
+        finality: Finality::default(),
+        retry: RetryPolicy::default(),
+        max_calls_per_tx: None,
+    })
+}
+
+/// Construct a strategy declaring and registering the given components and systems on an
+/// existing world, leaving the world and the executor untouched.
+pub fn prepare_for_registration(
+    target_dir: Utf8PathBuf,
+    local: &Manifest,
+    components: &[String],
+    systems: &[String],
+    world_config: WorldConfig,
+) -> Result<MigrationStrategy> {
+    let artifact_paths = collect_artifact_paths(target_dir)?;
+
+    let components = components
+        .iter()
+        .map(|name| {
+            let component = local
+                .components
+                .iter()
+                .find(|c| c.name == *name)
+                .with_context(|| format!("Component `{name}` not found in the manifest"))?;
+            Ok(ClassMigration {
+                class: ClassDiff {
+                    name: component.name.clone(),
+                    local: component.class_hash,
+                    remote: None,
+                },
+                artifact_path: find_artifact_path(&format!("{name}Component"), &artifact_paths)?
+                    .clone(),
+                declared: false,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    let systems = systems
+        .iter()
+        .map(|name| {
+            let name = name.strip_suffix("System").unwrap_or(name);
+            let system = local
+                .systems
+                .iter()
+                .find(|s| s.name.strip_suffix("System").unwrap_or(&s.name) == name)
+                .with_context(|| format!("System `{name}` not found in the manifest"))?;
+            Ok(ClassMigration {
+                class: ClassDiff { name: name.to_string(), local: system.class_hash, remote: None },
+                artifact_path: find_artifact_path(&format!("{name}System"), &artifact_paths)?
+                    .clone(),
+                declared: false,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(MigrationStrategy {
+        world: None,
+        executor: None,
+        systems,
+        components,
+        world_config,
+        checkpoint: MigrationCheckpoint::default(),
+        fees: FeeConfig::default(),
+        finality: Finality::default(),
+        retry: RetryPolicy::default(),
+        max_calls_per_tx: None,
+    })
+}
+
+/// Construct a strategy upgrading a live world in place: the systems and components whose class
+/// changed are declared and registered again, and a changed executor is deployed and set on the
+/// world. The world itself keeps its address and storage, new classes are left to registration.
+pub fn prepare_for_upgrade(
+    target_dir: Utf8PathBuf,
+    diff: WorldDiff,
+    world_config: WorldConfig,
+) -> Result<MigrationStrategy> {
+    if diff.world.address.is_none() || diff.world.remote.is_none() {
+        return Err(anyhow!("No world deployed at the configured address, migrate it first"));
+    }
+
+    let artifact_paths = collect_artifact_paths(target_dir)?;
+    let changed = |classes: Vec<ClassDiff>| {
+        classes
+            .into_iter()
+            .filter(|c| matches!(c.remote, Some(remote) if remote != c.local))
+            .collect::<Vec<_>>()
+    };
+
+    let executor = evaluate_contract_to_migrate(&diff.executor, &artifact_paths, false)?;
+    let components =
+        evaluate_components_to_migrate(&changed(diff.components), &artifact_paths, false)?;
+    let systems = evaluate_systems_to_migrate(&changed(diff.systems), &artifact_paths, false)?;
+
+    Ok(MigrationStrategy {
+        world: None,
+        executor,
+        systems,
+        components,
+        world_config,
+        checkpoint: MigrationCheckpoint::default(),
+        fees: FeeConfig::default(),
+        finality: Finality::default(),
+        retry: RetryPolicy::default(),
+        max_calls_per_tx: None,
+    })
+}
+
+/// Maps the contract names to their artifact in the target directory.
+pub fn collect_artifact_paths(target_dir: Utf8PathBuf) -> Result<HashMap<String, PathBuf>> {
     let entries = fs::read_dir(target_dir)
         .map_err(|err| anyhow!("Failed reading source directory: {err}"))?;
 
@@ -195,18 +1046,14 @@ pub fn prepare_for_migration(
         artifact_paths.insert(name, entry.path());
     }
 
-    // We don't need to care if a contract has already been declared or not, because
-    // the migration strategy will take care of that.
-
-    // If the world contract needs to be migrated, then all contracts need to be migrated
-    // else we need to evaluate which contracts need to be migrated.
-    let world = evaluate_contract_to_migrate(&diff.world, &artifact_paths, false)?;
-    let executor = evaluate_contract_to_migrate(&diff.executor, &artifact_paths, world.is_some())?;
-    let components =
-        evaluate_components_to_migrate(&diff.components, &artifact_paths, world.is_some())?;
-    let systems = evaluate_systems_to_migrate(&diff.systems, &artifact_paths, world.is_some())?;
+    Ok(artifact_paths)
+}
 
-    Ok(MigrationStrategy { world, executor, systems, components, world_config })
+fn declared_at(declare_res: &Option<DeclareOutput>) -> String {
+    match declare_res {
+        Some(res) => format!("{:#x}", res.transaction_hash),
+        None => "already declared".to_string(),
+    }
 }
 
 fn evaluate_systems_to_migrate(
@@ -222,9 +1069,9 @@ fn evaluate_systems_to_migrate(
             _ => {
                 let path = find_artifact_path(&format!("{}System", s.name), artifact_paths)?;
                 syst_to_migrate.push(ClassMigration {
-                    // declared: false,
                     class: s.clone(),
                     artifact_path: path.clone(),
+                    declared: false,
                 });
             }
         }
@@ -245,8 +1092,11 @@ fn evaluate_components_to_migrate(
             Some(remote) if remote == c.local && !world_contract_will_migrate => continue,
             _ => {
                 let path = find_artifact_path(&format!("{}Component", c.name), artifact_paths)?;
-                comps_to_migrate
-                    .push(ClassMigration { class: c.clone(), artifact_path: path.clone() });
+                comps_to_migrate.push(ClassMigration {
+                    class: c.clone(),
+                    artifact_path: path.clone(),
+                    declared: false,
+                });
             }
         }
     }
@@ -1,8 +1,17 @@
 use camino::Utf8PathBuf;
 use dojo_test_utils::sequencer::Sequencer;
+use starknet::accounts::ConnectedAccount;
+use starknet::core::types::FieldElement;
 
-use crate::config::{EnvironmentConfig, WorldConfig};
-use crate::migration::strategy::prepare_for_migration;
+use crate::config::{AccountConfig, EnvironmentConfig, WorldConfig};
+use crate::manifest::Manifest;
+use crate::migration::deployment::{DeploymentManifest, RegisteredClass};
+use crate::migration::object::MigrationError;
+use crate::migration::plan::PlannedStep;
+use crate::migration::strategy::{
+    prepare_for_migration, prepare_for_registration, prepare_for_upgrade,
+    prepare_from_deployment, MigrationKind, SubmittedTransaction,
+};
 use crate::migration::world::WorldDiff;
 
 #[tokio::test]
@@ -22,8 +31,354 @@ async fn test_migration() {
         .await
         .unwrap();
 
-    let mut migration = prepare_for_migration(target_dir, world, WorldConfig::default()).unwrap();
+    let mut migration =
+        prepare_for_migration(target_dir, world, WorldConfig::default(), MigrationKind::default())
+            .unwrap();
     migration.execute(env_config.migrator().await.unwrap()).await.unwrap();
 
     sequencer.stop().unwrap();
 }
+
+#[tokio::test]
+async fn test_migration_with_invalid_artifact() {
+    let target_dir = Utf8PathBuf::from_path_buf("../../examples/ecs/target/dev".into()).unwrap();
+
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+
+    let mut migration =
+        prepare_for_migration(target_dir, world, WorldConfig::default(), MigrationKind::default())
+            .unwrap();
+    migration.executor.as_mut().unwrap().artifact_path = "missing_executor.json".into();
+    let res = migration.execute(env_config.migrator().await.unwrap()).await;
+    assert!(matches!(res, Err(MigrationError::InvalidArtifact(_))));
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test]
+async fn test_migration_from_deployment() {
+    let target_dir = Utf8PathBuf::from_path_buf("../../examples/ecs/target/dev".into()).unwrap();
+    let local = Manifest::load_from_path(target_dir.join("manifest.json")).unwrap();
+
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+    let mut migration = prepare_for_migration(
+        target_dir.clone(),
+        world,
+        WorldConfig::default(),
+        MigrationKind::ForceRedeploy,
+    )
+    .unwrap();
+    migration.execute(env_config.migrator().await.unwrap()).await.unwrap();
+    let deployment = migration.deployment_manifest(&local, None);
+    assert!(deployment.world.transaction_hash.is_some());
+    assert!(deployment.executor.transaction_hash.is_some());
+    assert!(deployment.systems.iter().all(|s| s.transaction_hash.is_some()));
+    assert!(deployment.components.iter().all(|c| c.transaction_hash.is_some()));
+    // the members are the previous schema of the next upgrade
+    assert!(deployment.components.iter().all(|c| !c.members.is_empty()));
+    sequencer.stop().unwrap();
+
+    // Replaying the deployment on a fresh chain yields the same world.
+    let sequencer = Sequencer::start().await;
+    let env_config = EnvironmentConfig { rpc: Some(sequencer.url()), ..env_config };
+
+    let mut migration =
+        prepare_from_deployment(target_dir, &local, &deployment, WorldConfig::default()).unwrap();
+    migration.execute(env_config.migrator().await.unwrap()).await.unwrap();
+
+    assert_eq!(migration.deployment_manifest(&local, None), deployment);
+
+    sequencer.stop().unwrap();
+}
+
+#[test]
+fn test_registration_manifest() {
+    let target_dir = Utf8PathBuf::from_path_buf("../../examples/ecs/target/dev".into()).unwrap();
+    let local = Manifest::load_from_path(target_dir.join("manifest.json")).unwrap();
+    let class = |name: &str, class_hash: u8| RegisteredClass {
+        name: name.into(),
+        class_hash: class_hash.into(),
+        transaction_hash: Some(FieldElement::ONE),
+        ..RegisteredClass::default()
+    };
+    let previous = DeploymentManifest {
+        components: vec![class("Position", 0x1), class("Moves", 0x2)],
+        systems: vec![class("Spawn", 0x3)],
+        ..DeploymentManifest::default()
+    };
+
+    let mut registration = prepare_for_registration(
+        target_dir,
+        &local,
+        &["Moves".into()],
+        &["MoveSystem".into()],
+        WorldConfig::default(),
+    )
+    .unwrap();
+    let moves = registration.components[0].class.local;
+    let move_system = registration.systems[0].class.local;
+    for (description, class_hash) in
+        [("register components", moves), ("register systems", move_system)]
+    {
+        registration.checkpoint.submitted.push(SubmittedTransaction {
+            description: description.into(),
+            transaction_hash: FieldElement::TWO,
+            calldata: vec![class_hash],
+            class_hash: None,
+            salt: None,
+            contract_address: None,
+            sender: None,
+        });
+    }
+
+    let deployment = registration.registration_manifest(&local, &previous);
+
+    // The upgraded component keeps its place, the new system is appended.
+    let names = |classes: &[RegisteredClass]| {
+        classes.iter().map(|c| (c.name.clone(), c.class_hash)).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        names(&deployment.components),
+        vec![("Position".to_string(), FieldElement::ONE), ("Moves".to_string(), moves)]
+    );
+    assert_eq!(
+        names(&deployment.systems),
+        vec![("Spawn".to_string(), FieldElement::from(0x3_u8)), ("Move".to_string(), move_system)]
+    );
+    assert_eq!(deployment.components[0].transaction_hash, Some(FieldElement::ONE));
+    assert_eq!(deployment.components[1].transaction_hash, Some(FieldElement::TWO));
+    assert_eq!(deployment.systems[1].transaction_hash, Some(FieldElement::TWO));
+    assert!(!deployment.components[1].members.is_empty());
+    assert_eq!(deployment.world, previous.world);
+}
+
+#[tokio::test]
+async fn test_migration_with_dispatcher() {
+    let target_dir = Utf8PathBuf::from_path_buf("../../examples/ecs/target/dev".into()).unwrap();
+
+    let sequencer = Sequencer::start().await;
+    let accounts = sequencer.accounts();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(accounts[0].address),
+        private_key: Some(accounts[0].private_key),
+        accounts: vec![AccountConfig {
+            account_address: accounts[1].address,
+            private_key: accounts[1].private_key,
+        }],
+        ..EnvironmentConfig::default()
+    };
+
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+
+    let mut migration =
+        prepare_for_migration(target_dir, world, WorldConfig::default(), MigrationKind::default())
+            .unwrap();
+    let dispatcher = env_config.dispatcher().await.unwrap();
+    assert_eq!(dispatcher.accounts().len(), 2);
+    migration.declare_classes(&dispatcher).await.unwrap();
+    assert!(migration.components.iter().chain(&migration.systems).all(|c| c.declared));
+
+    // Both accounts sent declarations, and nothing else.
+    let declarations = migration
+        .checkpoint
+        .submitted
+        .iter()
+        .filter(|tx| tx.description.starts_with("declare "))
+        .count();
+    let mut nonces = vec![];
+    for account in dispatcher.accounts() {
+        nonces.push(account.get_nonce().await.unwrap());
+    }
+    assert!(nonces.iter().all(|nonce| *nonce > FieldElement::ZERO), "{nonces:?}");
+    assert_eq!(
+        nonces.iter().fold(FieldElement::ZERO, |total, nonce| total + *nonce),
+        FieldElement::from(declarations)
+    );
+
+    migration.execute(env_config.migrator().await.unwrap()).await.unwrap();
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test]
+async fn test_migration_plan() {
+    let target_dir = Utf8PathBuf::from_path_buf("../../examples/ecs/target/dev".into()).unwrap();
+
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+
+    let mut migration =
+        prepare_for_migration(target_dir, world, WorldConfig::default(), MigrationKind::default())
+            .unwrap();
+    let migrator = env_config.migrator().await.unwrap();
+
+    let plan = migration.plan(&migrator).await.unwrap();
+    assert!(matches!(
+        plan.steps.first(),
+        Some(PlannedStep::Declare { name, .. }) if name == "Executor"
+    ));
+    assert!(matches!(plan.steps.last(), Some(PlannedStep::RegisterComponents { .. })));
+
+    migration.execute(migrator).await.unwrap();
+    assert_eq!(migration.world.unwrap().contract_address, plan.world_address);
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test]
+async fn test_migration_resume() {
+    let target_dir = Utf8PathBuf::from_path_buf("../../examples/ecs/target/dev".into()).unwrap();
+
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+    let mut migration = prepare_for_migration(
+        target_dir.clone(),
+        world,
+        WorldConfig::default(),
+        MigrationKind::default(),
+    )
+    .unwrap();
+    migration.execute(env_config.migrator().await.unwrap()).await.unwrap();
+    let world_address = migration.world.as_ref().unwrap().contract_address;
+
+    // Deploying the same contracts again would fail, resuming skips them.
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+    let mut resumed =
+        prepare_for_migration(target_dir, world, WorldConfig::default(), MigrationKind::default())
+            .unwrap();
+    resumed.resume_from(migration.checkpoint.clone());
+    assert!(resumed.components.iter().chain(&resumed.systems).all(|c| c.declared));
+    assert_eq!(resumed.world.as_ref().unwrap().contract_address, world_address);
+
+    let submitted = resumed.checkpoint.submitted.len();
+    resumed.execute(env_config.migrator().await.unwrap()).await.unwrap();
+    assert_eq!(resumed.checkpoint.submitted.len(), submitted);
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test]
+async fn test_migration_in_batches() {
+    let target_dir = Utf8PathBuf::from_path_buf("../../examples/ecs/target/dev".into()).unwrap();
+    let local = Manifest::load_from_path(target_dir.join("manifest.json")).unwrap();
+
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+    let mut migration =
+        prepare_for_migration(target_dir, world, WorldConfig::default(), MigrationKind::default())
+            .unwrap();
+    migration.max_calls_per_tx = Some(1);
+
+    let migrator = env_config.migrator().await.unwrap();
+    let plan = migration.plan(&migrator).await.unwrap();
+    let registrations =
+        plan.steps.iter().filter(|s| matches!(s, PlannedStep::RegisterComponents { .. })).count();
+    assert_eq!(registrations, migration.components.len());
+
+    migration.execute(migrator).await.unwrap();
+    let registrations = migration.checkpoint.find_all("register components").count();
+    assert_eq!(registrations, migration.components.len());
+
+    let deployment = migration.deployment_manifest(&local, None);
+    assert!(deployment.components.iter().all(|c| c.transaction_hash.is_some()));
+
+    sequencer.stop().unwrap();
+}
+
+#[tokio::test]
+async fn test_upgrade() {
+    let target_dir = Utf8PathBuf::from_path_buf("../../examples/ecs/target/dev".into()).unwrap();
+
+    let sequencer = Sequencer::start().await;
+    let account = sequencer.account();
+    let env_config = EnvironmentConfig {
+        rpc: Some(sequencer.url()),
+        account_address: Some(account.address),
+        private_key: Some(account.private_key),
+        ..EnvironmentConfig::default()
+    };
+
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+    assert!(prepare_for_upgrade(target_dir.clone(), world, WorldConfig::default()).is_err());
+
+    let world = WorldDiff::from_path(target_dir.clone(), &WorldConfig::default(), &env_config)
+        .await
+        .unwrap();
+    let mut migration = prepare_for_migration(
+        target_dir.clone(),
+        world,
+        WorldConfig::default(),
+        MigrationKind::default(),
+    )
+    .unwrap();
+    migration.execute(env_config.migrator().await.unwrap()).await.unwrap();
+
+    // Nothing changed since the migration, there is nothing to upgrade.
+    let world_config = WorldConfig { address: migration.world.unwrap().contract_address };
+    let world = WorldDiff::from_path(target_dir.clone(), &world_config, &env_config).await.unwrap();
+    let upgrade = prepare_for_upgrade(target_dir, world, world_config).unwrap();
+    assert!(upgrade.executor.is_none());
+    assert!(upgrade.systems.is_empty());
+    assert!(upgrade.components.is_empty());
+
+    sequencer.stop().unwrap();
+}
@@ -2,10 +2,49 @@ use std::fmt::Display;
 
 use anyhow::{anyhow, Result};
 use camino::Utf8PathBuf;
+use serde::Serialize;
 use starknet::core::types::FieldElement;
 
 use crate::config::{EnvironmentConfig, WorldConfig};
 use crate::manifest::Manifest;
+use crate::migration::deployment::DeploymentManifest;
+
+#[cfg(test)]
+#[path = "world_test.rs"]
+mod test;
+
+/// How a class of the remote world compares to the local one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClassStatus {
+    /// The remote class hash is the local one.
+    Matches,
+    /// The class isn't registered, the world returns a zero class hash for unknown names.
+    Missing,
+    /// The remote class hash differs from the local one.
+    Stale,
+}
+
+impl ClassStatus {
+    fn of(local: FieldElement, remote: Option<FieldElement>) -> Self {
+        match remote {
+            None => ClassStatus::Missing,
+            Some(remote) if remote == FieldElement::ZERO => ClassStatus::Missing,
+            Some(remote) if remote == local => ClassStatus::Matches,
+            Some(_) => ClassStatus::Stale,
+        }
+    }
+}
+
+impl Display for ClassStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClassStatus::Matches => write!(f, "matches"),
+            ClassStatus::Missing => write!(f, "missing"),
+            ClassStatus::Stale => write!(f, "stale"),
+        }
+    }
+}
 
 /// Represents differences between a local and remote contract.
 #[derive(Debug, Default, Clone)]
@@ -34,6 +73,18 @@ pub struct WorldDiff {
     pub systems: Vec<ClassDiff>,
 }
 
+impl ContractDiff {
+    pub fn status(&self) -> ClassStatus {
+        ClassStatus::of(self.local, self.remote)
+    }
+}
+
+impl ClassDiff {
+    pub fn status(&self) -> ClassStatus {
+        ClassStatus::of(self.local, self.remote)
+    }
+}
+
 impl WorldDiff {
     pub async fn from_path(
         target_dir: Utf8PathBuf,
@@ -52,6 +103,27 @@ impl WorldDiff {
             None
         };
 
+        Ok(Self::new(&local_manifest, remote_manifest, world_config))
+    }
+
+    /// Diffs the local world against the last known `deployment` instead of the remote world,
+    /// without any network access. The remote world may have changed since.
+    pub fn from_deployment(
+        target_dir: Utf8PathBuf,
+        world_config: &WorldConfig,
+        deployment: Option<&DeploymentManifest>,
+    ) -> Result<WorldDiff> {
+        let local_manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+        let remote_manifest = deployment.map(DeploymentManifest::to_manifest);
+
+        Ok(Self::new(&local_manifest, remote_manifest, world_config))
+    }
+
+    fn new(
+        local_manifest: &Manifest,
+        remote_manifest: Option<Manifest>,
+        world_config: &WorldConfig,
+    ) -> WorldDiff {
         let systems = local_manifest
             .systems
             .iter()
@@ -104,7 +176,7 @@ impl WorldDiff {
             remote: remote_manifest.map(|m| m.executor),
         };
 
-        Ok(WorldDiff { world, executor, systems, contracts, components })
+        WorldDiff { world, executor, systems, contracts, components }
     }
 }
 
@@ -0,0 +1,19 @@
+use starknet::core::types::FieldElement;
+
+use super::{ClassDiff, ClassStatus, ContractDiff};
+
+#[test]
+fn test_class_status() {
+    let class = |remote: Option<u64>| ClassDiff {
+        name: "Position".into(),
+        local: FieldElement::ONE,
+        remote: remote.map(FieldElement::from),
+    };
+    assert_eq!(class(Some(1)).status(), ClassStatus::Matches);
+    assert_eq!(class(Some(2)).status(), ClassStatus::Stale);
+    assert_eq!(class(Some(0)).status(), ClassStatus::Missing);
+    assert_eq!(class(None).status(), ClassStatus::Missing);
+
+    let executor = ContractDiff { local: FieldElement::ONE, remote: None, ..Default::default() };
+    assert_eq!(executor.status(), ClassStatus::Missing);
+}
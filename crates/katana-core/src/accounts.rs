@@ -5,9 +5,7 @@ use std::sync::Arc;
 use anyhow::Result;
 use blockifier::abi::abi_utils::get_storage_var_address;
 use blockifier::execution::contract_class::{ContractClass, ContractClassV0};
-use rand::rngs::SmallRng;
-use rand::{RngCore, SeedableRng};
-use starknet::signers::SigningKey;
+use dojo_signers::devnet::dev_accounts;
 use starknet_api::core::{
     calculate_contract_address, ClassHash, ContractAddress, Nonce, PatriciaKey,
 };
@@ -19,7 +17,7 @@ use crate::constants::{
     DEFAULT_ACCOUNT_CONTRACT, DEFAULT_ACCOUNT_CONTRACT_CLASS_HASH, FEE_TOKEN_ADDRESS,
 };
 use crate::state::DictStateReader;
-use crate::util::compute_legacy_class_hash;
+use crate::util::{compute_legacy_class_hash, field_element_to_starkfelt};
 
 #[derive(Debug, Clone)]
 pub struct Account {
@@ -145,38 +143,21 @@ impl PredeployedAccounts {
         class_hash: ClassHash,
         contract_class: ContractClass,
     ) -> Vec<Account> {
-        let mut seed = seed;
-        let mut accounts = vec![];
-
-        for _ in 0..total {
-            let mut rng = SmallRng::from_seed(seed);
-            let mut private_key_bytes = [0u8; 32];
-
-            rng.fill_bytes(&mut private_key_bytes);
-            private_key_bytes[0] %= 0x9;
-            seed = private_key_bytes;
-
-            let private_key =
-                StarkFelt::new(private_key_bytes).expect("should create StarkFelt from bytes");
-
-            accounts.push(Account::new(
-                balance,
-                compute_public_key_from_private_key(private_key),
-                private_key,
-                class_hash,
-                contract_class.clone(),
-            ));
-        }
-
-        accounts
+        dev_accounts(seed, total, class_hash.0.into())
+            .into_iter()
+            .map(|account| {
+                Account::new(
+                    balance,
+                    field_element_to_starkfelt(&account.public_key),
+                    field_element_to_starkfelt(&account.private_key),
+                    class_hash,
+                    contract_class.clone(),
+                )
+            })
+            .collect()
     }
 
     pub fn default_account_class() -> (ClassHash, ContractClass) {
         (ClassHash(*DEFAULT_ACCOUNT_CONTRACT_CLASS_HASH), (*DEFAULT_ACCOUNT_CONTRACT).clone())
     }
 }
-
-// TODO: remove starknet-rs dependency
-fn compute_public_key_from_private_key(private_key: StarkFelt) -> StarkFelt {
-    StarkFelt::from(SigningKey::from_secret_scalar(private_key.into()).verifying_key().scalar())
-}
@@ -434,6 +434,23 @@ impl Sequencer for KatanaSequencer {
         self.starknet.generate_latest_block();
         self.starknet.generate_pending_block();
     }
+
+    fn mint(&mut self, contract_address: ContractAddress, amount: u128) -> SequencerResult<()> {
+        let fee_token_address = self.starknet.block_context.fee_token_address;
+        let balance_key = get_storage_var_address("ERC20_balances", &[*contract_address.0.key()])
+            .map_err(SequencerError::StarknetApi)?;
+
+        let balance = self.starknet.pending_state.get_storage_at(fee_token_address, balance_key)?;
+        let balance = starkfelt_to_u128(balance)?.saturating_add(amount);
+
+        self.starknet.pending_state.set_storage_at(
+            fee_token_address,
+            balance_key,
+            StarkFelt::from(balance),
+        );
+
+        Ok(())
+    }
 }
 
 pub trait Sequencer {
@@ -512,4 +529,7 @@ pub trait Sequencer {
     ) -> SequencerResult<Vec<EmittedEvent>>;
 
     fn state_update(&self, block_id: BlockId) -> SequencerResult<StateUpdate>;
+
+    /// Credits the fee token balance of `contract_address` with `amount`.
+    fn mint(&mut self, contract_address: ContractAddress, amount: u128) -> SequencerResult<()>;
 }
@@ -2,9 +2,13 @@ use jsonrpsee::core::Error;
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::error::CallError;
 use jsonrpsee::types::ErrorObject;
+use starknet::core::types::FieldElement;
 
 #[derive(thiserror::Error, Clone, Copy, Debug)]
-pub enum KatanaApiError {}
+pub enum KatanaApiError {
+    #[error("Failed to mint fee tokens")]
+    FailedToMint = 1,
+}
 
 impl From<KatanaApiError> for Error {
     fn from(err: KatanaApiError) -> Self {
@@ -16,4 +20,7 @@ impl From<KatanaApiError> for Error {
 pub trait KatanaApi {
     #[method(name = "generateBlock")]
     async fn generate_block(&self) -> Result<(), Error>;
+
+    #[method(name = "mint")]
+    async fn mint(&self, address: FieldElement, amount: u128) -> Result<(), Error>;
 }
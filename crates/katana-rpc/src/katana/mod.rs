@@ -2,9 +2,13 @@ use std::sync::Arc;
 
 use jsonrpsee::core::{async_trait, Error};
 use katana_core::sequencer::Sequencer;
+use starknet::core::types::FieldElement;
+use starknet_api::core::{ContractAddress, PatriciaKey};
+use starknet_api::hash::StarkHash;
+use starknet_api::patricia_key;
 use tokio::sync::RwLock;
 
-use self::api::KatanaApiServer;
+use self::api::{KatanaApiError, KatanaApiServer};
 
 pub mod api;
 
@@ -24,4 +28,12 @@ impl<S: Sequencer + Send + Sync + 'static> KatanaApiServer for KatanaRpc<S> {
         self.sequencer.write().await.generate_new_block();
         Ok(())
     }
+
+    async fn mint(&self, address: FieldElement, amount: u128) -> Result<(), Error> {
+        self.sequencer
+            .write()
+            .await
+            .mint(ContractAddress(patricia_key!(address)), amount)
+            .map_err(|_| Error::from(KatanaApiError::FailedToMint))
+    }
 }
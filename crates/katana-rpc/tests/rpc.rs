@@ -4,16 +4,34 @@ use std::str::FromStr;
 use std::sync::Arc;
 
 use anyhow::{Ok, Result};
+use blockifier::abi::abi_utils::get_storage_var_address;
+use blockifier::state::state_api::State;
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::Error;
+use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::rpc_params;
+use jsonrpsee::server::ServerHandle;
+use jsonrpsee::types::error::CallError;
+use katana_core::sequencer::KatanaSequencer;
+use katana_core::starknet::StarknetConfig;
+use katana_rpc::config::RpcConfig;
+use katana_rpc::KatanaNodeRpc;
 use starknet::core::types::contract::legacy::LegacyContractClass;
 use starknet::core::types::contract::SierraClass;
 use starknet::core::types::{
-    BroadcastedDeclareTransaction, BroadcastedDeclareTransactionV1,
+    BlockId, BlockTag, BroadcastedDeclareTransaction, BroadcastedDeclareTransactionV1,
     BroadcastedDeclareTransactionV2, FieldElement, FlattenedSierraClass,
 };
+use starknet::core::utils::get_storage_var_address as storage_var_address;
 use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
 use starknet::providers::Provider;
+use starknet_api::hash::StarkFelt;
+use tokio::sync::RwLock;
 use url::Url;
 
+const FEE_TOKEN_ADDRESS: &str =
+    "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
+
 fn get_flattened_sierra_class(raw_contract_class: &str) -> Result<FlattenedSierraClass> {
     let contract_artifact: SierraClass = serde_json::from_str(raw_contract_class)?;
     Ok(contract_artifact.flatten()?)
@@ -84,3 +102,80 @@ async fn test_send_declare_v1_tx() {
     println!("{res:?}");
     assert!(res.is_ok());
 }
+
+async fn start_katana() -> (Arc<RwLock<KatanaSequencer>>, Url, ServerHandle) {
+    let sequencer = Arc::new(RwLock::new(KatanaSequencer::new(StarknetConfig {
+        total_accounts: 2,
+        allow_zero_max_fee: true,
+        ..StarknetConfig::default()
+    })));
+    sequencer.write().await.start();
+    let (addr, handle) =
+        KatanaNodeRpc::new(sequencer.clone(), RpcConfig { port: 0 }).run().await.unwrap();
+    (sequencer, Url::parse(&format!("http://{addr}")).unwrap(), handle)
+}
+
+async fn predeployed_account(sequencer: &RwLock<KatanaSequencer>, index: usize) -> FieldElement {
+    let address =
+        sequencer.read().await.starknet.predeployed_accounts.accounts[index].account_address;
+    FieldElement::from_byte_slice_be(address.0.key().bytes()).unwrap()
+}
+
+async fn fee_token_balance(url: &Url, address: FieldElement) -> FieldElement {
+    let provider = JsonRpcClient::new(HttpTransport::new(url.clone()));
+    provider
+        .get_storage_at(
+            FieldElement::from_hex_be(FEE_TOKEN_ADDRESS).unwrap(),
+            storage_var_address("ERC20_balances", &[address]).unwrap(),
+            BlockId::Tag(BlockTag::Pending),
+        )
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_mint() {
+    let (sequencer, url, handle) = start_katana().await;
+    let address = predeployed_account(&sequencer, 0).await;
+    let before = fee_token_balance(&url, address).await;
+
+    let client = HttpClientBuilder::default().build(url.as_str()).unwrap();
+    client.request::<(), _>("katana_mint", rpc_params![address, 1000u128]).await.unwrap();
+
+    let after = fee_token_balance(&url, address).await;
+    assert_eq!(after, before + FieldElement::from(1000u32));
+
+    handle.stop().unwrap();
+}
+
+#[tokio::test]
+async fn test_mint_fails_on_invalid_balance() {
+    let (sequencer, url, handle) = start_katana().await;
+    let address = predeployed_account(&sequencer, 1).await;
+
+    // A balance which doesn't fit in a u128 can't be added to.
+    {
+        let mut sequencer = sequencer.write().await;
+        let account = sequencer.starknet.predeployed_accounts.accounts[1].account_address;
+        let fee_token_address = sequencer.starknet.block_context.fee_token_address;
+        let balance_key = get_storage_var_address("ERC20_balances", &[*account.0.key()]).unwrap();
+        sequencer.starknet.pending_state.set_storage_at(
+            fee_token_address,
+            balance_key,
+            StarkFelt::try_from("0x100000000000000000000000000000000").unwrap(),
+        );
+    }
+
+    let client = HttpClientBuilder::default().build(url.as_str()).unwrap();
+    let err =
+        client.request::<(), _>("katana_mint", rpc_params![address, 1000u128]).await.unwrap_err();
+    match err {
+        Error::Call(CallError::Custom(err)) => {
+            assert_eq!(err.code(), 1);
+            assert_eq!(err.message(), "Failed to mint fee tokens");
+        }
+        err => panic!("unexpected error: {err:?}"),
+    }
+
+    handle.stop().unwrap();
+}
@@ -1,6 +1,7 @@
 use std::path::PathBuf;
 
 use clap::{Args, Parser};
+use dojo_signers::devnet::{parse_seed, DEFAULT_SEED};
 use katana_core::constants::DEFAULT_GAS_PRICE;
 use katana_core::starknet::StarknetConfig;
 use katana_rpc::config::RpcConfig;
@@ -82,7 +83,7 @@ impl App {
     pub fn starknet_config(&self) -> StarknetConfig {
         StarknetConfig {
             total_accounts: self.starknet.total_accounts,
-            seed: parse_seed(self.starknet.seed.clone()),
+            seed: self.starknet.seed.as_deref().map_or(DEFAULT_SEED, parse_seed),
             gas_price: self.starknet.environment.gas_price.unwrap_or(DEFAULT_GAS_PRICE),
             blocks_on_demand: self.starknet.blocks_on_demand,
             account_path: self.starknet.account_path.clone(),
@@ -91,18 +92,3 @@ impl App {
         }
     }
 }
-
-fn parse_seed(seed: Option<String>) -> [u8; 32] {
-    seed.map(|seed| {
-        let seed = seed.as_bytes();
-
-        if seed.len() >= 32 {
-            unsafe { *(seed[..32].as_ptr() as *const [u8; 32]) }
-        } else {
-            let mut actual_seed = [0u8; 32];
-            seed.iter().enumerate().for_each(|(i, b)| actual_seed[i] = *b);
-            actual_seed
-        }
-    })
-    .unwrap_or_default()
-}
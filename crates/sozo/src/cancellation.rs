@@ -0,0 +1,43 @@
+use std::fmt::Display;
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(test)]
+#[path = "cancellation_test.rs"]
+mod test;
+
+/// Why a command stopped before completion.
+#[derive(Debug, Clone, Copy)]
+pub enum Interrupted {
+    Timeout(Duration),
+    CtrlC,
+}
+
+impl Display for Interrupted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Timeout(timeout) => write!(f, "timed out after {}s", timeout.as_secs()),
+            Self::CtrlC => write!(f, "interrupted by user"),
+        }
+    }
+}
+
+/// Drives `fut` until it completes, `timeout` elapses or Ctrl-C is received. On interruption the
+/// future is dropped, cancelling any in-flight RPC request.
+pub async fn run_cancellable<F, T>(fut: F, timeout: Option<Duration>) -> Result<T, Interrupted>
+where
+    F: Future<Output = T>,
+{
+    let deadline = async {
+        match timeout {
+            Some(timeout) => tokio::time::sleep(timeout).await,
+            None => std::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        res = fut => Ok(res),
+        _ = deadline => Err(Interrupted::Timeout(timeout.unwrap_or_default())),
+        _ = tokio::signal::ctrl_c() => Err(Interrupted::CtrlC),
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{run_cancellable, Interrupted};
+
+/// Sets the flag when dropped, to observe the cancellation of a future.
+struct DropFlag(Arc<AtomicBool>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn test_completes_without_timeout() {
+    let res = run_cancellable(async { 42 }, None).await;
+    assert_eq!(res.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn test_completes_before_timeout() {
+    let fut = async {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        "done"
+    };
+    let res = run_cancellable(fut, Some(Duration::from_secs(10))).await;
+    assert_eq!(res.unwrap(), "done");
+}
+
+#[tokio::test]
+async fn test_timeout_drops_the_future() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+    let fut = async move {
+        let _flag = flag;
+        std::future::pending::<()>().await;
+    };
+
+    let err = run_cancellable(fut, Some(Duration::from_millis(50))).await.unwrap_err();
+    assert!(matches!(err, Interrupted::Timeout(timeout) if timeout == Duration::from_millis(50)));
+    assert!(dropped.load(Ordering::SeqCst), "the timed out future should be dropped");
+}
+
+#[test]
+fn test_interrupted_display() {
+    assert_eq!(Interrupted::Timeout(Duration::from_secs(30)).to_string(), "timed out after 30s");
+    assert_eq!(Interrupted::CtrlC.to_string(), "interrupted by user");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_ctrl_c_drops_the_future() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+    let fut = async move {
+        let _flag = flag;
+        // Let the Ctrl-C handler be installed by the first poll before signaling.
+        tokio::task::yield_now().await;
+        let status = std::process::Command::new("kill")
+            .args(["-INT", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+        std::future::pending::<()>().await;
+    };
+
+    let err = run_cancellable(fut, Some(Duration::from_secs(10))).await.unwrap_err();
+    assert!(matches!(err, Interrupted::CtrlC));
+    assert!(dropped.load(Ordering::SeqCst), "the interrupted future should be dropped");
+}
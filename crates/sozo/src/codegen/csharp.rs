@@ -0,0 +1,253 @@
+//! C# bindings for Unity: the components as classes with their decoders, builders of the calls
+//! executing the systems and the selectors of the world's events. Felts and integers wider than
+//! 64 bits are `BigInteger`s, which Unity supports from .NET Standard 2.1.
+
+use dojo_world::manifest::{Component, Manifest, System};
+use starknet::core::utils::get_selector_from_name;
+
+use super::{enums, layout, pascal_case, system_name, EnumDef, TypeKind};
+use crate::commands::events::WORLD_EVENTS;
+
+#[cfg(test)]
+#[path = "csharp_test.rs"]
+mod test;
+
+/// Helpers shared by the generated code.
+const RUNTIME: &str = r#"    // A call to a contract, signed and sent by the account of the player.
+    public struct Call
+    {
+        public string To;
+        public string Entrypoint;
+        public string[] Calldata;
+    }
+
+    public static class Felt
+    {
+        public static string ToHex(BigInteger value)
+        {
+            return "0x" + value.ToString("x").TrimStart('0').PadLeft(1, '0');
+        }
+
+        public static BigInteger Parse(string value)
+        {
+            var hex = value.StartsWith("0x") ? value.Substring(2) : value;
+            return BigInteger.Parse("0" + hex, NumberStyles.HexNumber);
+        }
+
+        public static string ShortString(string value)
+        {
+            var hex = new StringBuilder();
+            foreach (var c in value)
+            {
+                hex.Append(((int)c).ToString("x2"));
+            }
+            return "0x" + (hex.Length > 0 ? hex.ToString() : "0");
+        }
+
+        public static string ParseShortString(string value)
+        {
+            var bytes = Parse(value).ToByteArray();
+            var text = new StringBuilder();
+            for (var i = bytes.Length - 1; i >= 0; i--)
+            {
+                if (bytes[i] != 0)
+                {
+                    text.Append((char)bytes[i]);
+                }
+            }
+            return text.ToString();
+        }
+
+        internal static BigInteger Unpack(BigInteger value, int offset, int bits)
+        {
+            return (value >> offset) & ((BigInteger.One << bits) - 1);
+        }
+
+        internal static IEnumerable<string> U256(BigInteger value)
+        {
+            var mask = (BigInteger.One << 128) - 1;
+            return new[] { ToHex(value & mask), ToHex(value >> 128) };
+        }
+
+        internal static Call Execute(string world, string system, List<string> calldata)
+        {
+            var args = new List<string> { ShortString(system), ToHex(calldata.Count) };
+            args.AddRange(calldata);
+            return new Call { To = world, Entrypoint = "execute", Calldata = args.ToArray() };
+        }
+    }
+"#;
+
+/// Returns the source of the bindings and the assembly definition Unity compiles them with, by
+/// file name.
+pub fn generate(manifest: &Manifest, package_name: &str) -> Vec<(String, String)> {
+    let enums = enums(manifest);
+    let namespace = pascal_case(package_name);
+
+    let mut out = String::from("// Generated by sozo from the world's manifest, do not edit.\n\n");
+    out.push_str(
+        "using System;\nusing System.Collections.Generic;\nusing System.Globalization;\nusing \
+         System.Linq;\nusing System.Numerics;\nusing System.Text;\n\n",
+    );
+    out.push_str(&format!("namespace {namespace}\n{{\n"));
+    out.push_str(RUNTIME);
+
+    for def in &enums {
+        out.push('\n');
+        out.push_str(&generate_enum(def));
+    }
+    for component in &manifest.components {
+        out.push('\n');
+        out.push_str(&generate_component(component, &enums));
+    }
+
+    out.push_str("\n    public static class Components\n    {\n");
+    out.push_str("        // Decoders of the components' records, by component name.\n");
+    out.push_str(
+        "        public static readonly Dictionary<string, Func<string[], object>> Decoders =\n",
+    );
+    out.push_str("            new Dictionary<string, Func<string[], object>>\n            {\n");
+    for component in &manifest.components {
+        out.push_str(&format!("                {{ \"{0}\", {0}.Decode }},\n", component.name));
+    }
+    out.push_str("            };\n    }\n");
+
+    out.push_str("\n    public static class Systems\n    {\n");
+    for (i, system) in manifest.systems.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        out.push_str(&generate_system(system, &enums));
+    }
+    out.push_str("    }\n");
+
+    out.push_str("\n    // Selectors of the world's events, the first key of the events.\n");
+    out.push_str("    public static class WorldEvents\n    {\n");
+    for name in WORLD_EVENTS {
+        let selector = get_selector_from_name(name).expect("valid event name");
+        out.push_str(&format!("        public const string {name} = \"{selector:#x}\";\n"));
+    }
+    out.push_str("    }\n}\n");
+
+    let assembly = format!("{{\n  \"name\": \"{namespace}\",\n  \"autoReferenced\": true\n}}\n");
+
+    vec![(format!("{namespace}.cs"), out), (format!("{namespace}.asmdef"), assembly)]
+}
+
+fn generate_enum(def: &EnumDef) -> String {
+    let mut out = format!("    public enum {}\n    {{\n", def.name);
+    for variant in &def.variants {
+        out.push_str(&format!("        {variant},\n"));
+    }
+    out.push_str("    }\n");
+    out
+}
+
+fn generate_component(component: &Component, enums: &[EnumDef]) -> String {
+    let layouts = layout(&component.members, enums);
+
+    let mut out = format!("    [Serializable]\n    public class {}\n    {{\n", component.name);
+    for layout in &layouts {
+        out.push_str(&format!(
+            "        public {} {};\n",
+            field_type(&layout.kind),
+            pascal_case(&layout.member.name)
+        ));
+    }
+
+    out.push_str(&format!(
+        "\n        // Decodes the values of a `{0}` record, as stored by the world.\n        \
+         public static {0} Decode(string[] values)\n        {{\n",
+        component.name
+    ));
+    out.push_str("            var v = values.Select(Felt.Parse).ToArray();\n");
+    out.push_str(&format!("            return new {}\n            {{\n", component.name));
+    for layout in &layouts {
+        let raw = match layout.packed {
+            Some((offset, bits)) => format!("Felt.Unpack(v[{}], {offset}, {bits})", layout.index),
+            None => format!("v[{}]", layout.index),
+        };
+        let value = match (&layout.kind, layout.high_index) {
+            (TypeKind::U256, Some(high)) => format!("{raw} + (v[{high}] << 128)"),
+            (TypeKind::Bool, _) => format!("!{raw}.IsZero"),
+            (TypeKind::Uint(bits), _) if *bits <= 64 => {
+                format!("({}){raw}", field_type(&layout.kind))
+            }
+            (TypeKind::Enum(name), _) => format!("({name})(int){raw}"),
+            _ => raw,
+        };
+        out.push_str(&format!("                {} = {value},\n", pascal_case(&layout.member.name)));
+    }
+    out.push_str("            };\n        }\n    }\n");
+
+    out
+}
+
+fn generate_system(system: &System, enums: &[EnumDef]) -> String {
+    let name = system_name(&system.name);
+    let inputs = system
+        .inputs
+        .iter()
+        .map(|input| (input.name.as_str(), TypeKind::from_type(&input.ty, enums)))
+        .collect::<Vec<_>>();
+
+    let params = inputs
+        .iter()
+        .map(|(input, kind)| format!(", {} {input}", input_type(kind)))
+        .collect::<String>();
+
+    let mut out = format!(
+        "        // Builds the call executing the `{name}` system.\n        public static Call \
+         Execute{name}(string world{params})\n        {{\n            var calldata = new \
+         List<string>();\n"
+    );
+    for (input, kind) in &inputs {
+        out.push_str(&encode(input, kind, 3));
+    }
+    out.push_str(&format!(
+        "            return Felt.Execute(world, \"{name}\", calldata);\n        }}\n"
+    ));
+
+    out
+}
+
+/// Statements appending the serialization of `value` to `calldata`.
+fn encode(value: &str, kind: &TypeKind, depth: usize) -> String {
+    let indent = "    ".repeat(depth);
+    match kind {
+        TypeKind::Bool => format!("{indent}calldata.Add({value} ? \"0x1\" : \"0x0\");\n"),
+        TypeKind::U256 => format!("{indent}calldata.AddRange(Felt.U256({value}));\n"),
+        TypeKind::Enum(_) => format!("{indent}calldata.Add(Felt.ToHex((int){value}));\n"),
+        TypeKind::Array(inner) => {
+            let item = format!("item{depth}");
+            format!(
+                "{indent}calldata.Add(Felt.ToHex({value}.Count));\n{indent}foreach (var {item} in \
+                 {value})\n{indent}{{\n{}{indent}}}\n",
+                encode(&item, inner, depth + 1)
+            )
+        }
+        TypeKind::Felt | TypeKind::Uint(_) => {
+            format!("{indent}calldata.Add(Felt.ToHex({value}));\n")
+        }
+    }
+}
+
+fn input_type(kind: &TypeKind) -> String {
+    match kind {
+        TypeKind::Array(inner) => format!("IList<{}>", input_type(inner)),
+        _ => field_type(kind),
+    }
+}
+
+fn field_type(kind: &TypeKind) -> String {
+    match kind {
+        TypeKind::Bool => "bool".to_string(),
+        TypeKind::Uint(8) => "byte".to_string(),
+        TypeKind::Uint(16) => "ushort".to_string(),
+        TypeKind::Uint(32) => "uint".to_string(),
+        TypeKind::Uint(64) => "ulong".to_string(),
+        TypeKind::Enum(name) => name.clone(),
+        TypeKind::Array(inner) => format!("List<{}>", field_type(inner)),
+        TypeKind::Felt | TypeKind::Uint(_) | TypeKind::U256 => "BigInteger".to_string(),
+    }
+}
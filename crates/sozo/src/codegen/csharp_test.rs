@@ -0,0 +1,62 @@
+use dojo_world::manifest::Manifest;
+use starknet::core::utils::get_selector_from_name;
+
+use super::generate;
+
+fn ecs_bindings() -> (String, String) {
+    let manifest = Manifest::load_from_path("../../examples/ecs/target/dev/manifest.json").unwrap();
+    let mut files = generate(&manifest, "ecs-bindings").into_iter();
+    let (source_name, source) = files.next().unwrap();
+    let (assembly_name, assembly) = files.next().unwrap();
+    assert_eq!(source_name, "EcsBindings.cs");
+    assert_eq!(assembly_name, "EcsBindings.asmdef");
+    assert!(files.next().is_none());
+    (source, assembly)
+}
+
+#[test]
+fn test_generate_components() {
+    let (source, _) = ecs_bindings();
+
+    assert!(source.contains("namespace EcsBindings\n{\n"));
+    assert!(source.contains(
+        "    [Serializable]\n    public class Moves\n    {\n        public byte Remaining;\n"
+    ));
+    assert!(source.contains(
+        "    [Serializable]\n    public class Position\n    {\n        public uint X;\n        \
+         public uint Y;\n"
+    ));
+    assert!(source.contains("        public static Position Decode(string[] values)\n"));
+    assert!(source.contains("                { \"Moves\", Moves.Decode },\n"));
+    assert!(source.contains("                { \"Position\", Position.Decode },\n"));
+}
+
+#[test]
+fn test_generate_systems() {
+    let (source, _) = ecs_bindings();
+
+    assert!(source.contains(
+        "        public static Call ExecuteSpawn(string world)\n        {\n            var \
+         calldata = new List<string>();\n            return Felt.Execute(world, \"Spawn\", \
+         calldata);\n        }\n"
+    ));
+    assert!(source.contains(
+        "        public static Call ExecuteMove(string world, BigInteger direction)\n        \
+         {\n            var calldata = new List<string>();\n            \
+         calldata.Add(Felt.ToHex(direction));\n            return Felt.Execute(world, \"Move\", \
+         calldata);\n        }\n"
+    ));
+}
+
+#[test]
+fn test_generate_events_and_assembly() {
+    let (source, assembly) = ecs_bindings();
+
+    let selector = get_selector_from_name("StoreSetRecord").unwrap();
+    assert!(
+        source.contains(&format!(
+            "        public const string StoreSetRecord = \"{selector:#x}\";\n"
+        ))
+    );
+    assert_eq!(assembly, "{\n  \"name\": \"EcsBindings\",\n  \"autoReferenced\": true\n}\n");
+}
@@ -0,0 +1,160 @@
+//! Generation of typed client bindings from the build manifest, so clients don't hand-maintain
+//! the felt encoding of the world's components and systems.
+
+use dojo_world::manifest::{Manifest, Member};
+
+pub mod csharp;
+pub mod python;
+pub mod typescript;
+
+/// An enum of the world, only known through the component members using it.
+pub struct EnumDef {
+    pub name: String,
+    pub variants: Vec<String>,
+}
+
+/// Returns the enums used by the components, each once.
+pub fn enums(manifest: &Manifest) -> Vec<EnumDef> {
+    let mut enums: Vec<EnumDef> = vec![];
+    for member in manifest.components.iter().flat_map(|c| &c.members) {
+        let Some(variants) = &member.variants else {
+            continue;
+        };
+        let name = short_name(&member.ty);
+        if !enums.iter().any(|e| e.name == name) {
+            enums.push(EnumDef { name: name.to_string(), variants: variants.clone() });
+        }
+    }
+    enums
+}
+
+/// Name of a type without its module path.
+pub fn short_name(ty: &str) -> &str {
+    ty.rsplit("::").next().unwrap_or(ty)
+}
+
+/// Converts a snake case or kebab case name to pascal case, as C# names its types and members.
+pub fn pascal_case(name: &str) -> String {
+    name.split(['_', '-'])
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+        })
+        .collect::<Vec<String>>()
+        .concat()
+}
+
+/// Converts a pascal case or kebab case name to snake case, as Python names its functions and
+/// modules.
+pub fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if c == '-' {
+            out.push('_');
+        } else if c.is_uppercase() {
+            if previous.map_or(false, |p| p.is_lowercase() || p.is_ascii_digit()) {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+        previous = Some(c);
+    }
+    out
+}
+
+/// Strips the `System` suffix the compiler appends to the systems' names, the world registers
+/// them without it.
+pub fn system_name(name: &str) -> &str {
+    name.strip_suffix("System").unwrap_or(name)
+}
+
+/// How a value is serialized to felts, derived from its Cairo type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind {
+    Felt,
+    Bool,
+    Uint(u32),
+    /// Serialized as its low and high 128 bits.
+    U256,
+    /// Serialized as the index of the variant.
+    Enum(String),
+    /// Serialized as its length followed by its items.
+    Array(Box<TypeKind>),
+}
+
+impl TypeKind {
+    pub fn from_type(ty: &str, enums: &[EnumDef]) -> Self {
+        if let Some(inner) = ty
+            .strip_prefix("core::array::Array::<")
+            .or_else(|| ty.strip_prefix("core::array::Span::<"))
+            .and_then(|inner| inner.strip_suffix('>'))
+        {
+            return Self::Array(Box::new(Self::from_type(inner, enums)));
+        }
+
+        match short_name(ty) {
+            "bool" => Self::Bool,
+            "u8" => Self::Uint(8),
+            "u16" => Self::Uint(16),
+            "u32" | "usize" => Self::Uint(32),
+            "u64" => Self::Uint(64),
+            "u128" => Self::Uint(128),
+            "u256" => Self::U256,
+            name if enums.iter().any(|e| e.name == name) => Self::Enum(name.to_string()),
+            _ => Self::Felt,
+        }
+    }
+}
+
+/// Where the value of a member is read from in the values of a record.
+pub struct MemberLayout<'a> {
+    pub member: &'a Member,
+    pub kind: TypeKind,
+    /// Index of the value holding the member, or its low part for `u256`.
+    pub index: usize,
+    /// Offset and width of the member in its slot, when packed with others.
+    pub packed: Option<(u8, u8)>,
+    /// Index of the high part of `u256` members.
+    pub high_index: Option<usize>,
+}
+
+/// Lays the members out as they are stored: by slot, packed members sharing the value of their
+/// slot. Returned in declaration order.
+pub fn layout<'a>(members: &'a [Member], enums: &[EnumDef]) -> Vec<MemberLayout<'a>> {
+    let mut by_slot = members.iter().collect::<Vec<_>>();
+    by_slot.sort_by_key(|m| (m.slot, m.offset));
+
+    let mut next = 0;
+    let mut slot_index = None;
+    let mut layouts = vec![];
+    for member in by_slot {
+        let kind = TypeKind::from_type(&member.ty, enums);
+        let (index, packed) = match (member.packed_bits(), slot_index) {
+            (Some(bits), Some((slot, index))) if slot == member.slot => {
+                (index, Some((member.offset, bits)))
+            }
+            (Some(bits), _) => {
+                slot_index = Some((member.slot, next));
+                next += 1;
+                (next - 1, Some((member.offset, bits)))
+            }
+            (None, _) => {
+                next += 1;
+                (next - 1, None)
+            }
+        };
+        let high_index = (kind == TypeKind::U256).then(|| {
+            next += 1;
+            next - 1
+        });
+
+        layouts.push(MemberLayout { member, kind, index, packed, high_index });
+    }
+
+    layouts.sort_by_key(|l| members.iter().position(|m| std::ptr::eq(m, l.member)));
+    layouts
+}
@@ -0,0 +1,293 @@
+//! Python bindings for bots and analytics: the components as dataclasses with their decoders,
+//! builders of the calls executing the systems, decoders of the world's events and the GraphQL
+//! queries reading the components from torii. Felts are `int`s, and may be given as hex strings.
+
+use dojo_world::manifest::{Component, Manifest, System};
+use starknet::core::utils::get_selector_from_name;
+
+use super::{enums, layout, snake_case, system_name, EnumDef, TypeKind};
+use crate::commands::events::WORLD_EVENTS;
+
+#[cfg(test)]
+#[path = "python_test.rs"]
+mod test;
+
+/// Helpers shared by the generated code.
+const RUNTIME: &str = r#"BigNumberish = Union[int, str]
+
+
+@dataclass
+class Call:
+    """A call to a contract, in the shape of starknet.py's `Call`."""
+
+    to_addr: int
+    selector: int
+    calldata: List[int]
+
+
+def to_int(value: BigNumberish) -> int:
+    return int(value, 0) if isinstance(value, str) else int(value)
+
+
+def short_string(value: str) -> int:
+    return int.from_bytes(value.encode("ascii"), "big")
+
+
+def parse_short_string(value: BigNumberish) -> str:
+    value = to_int(value)
+    return value.to_bytes((value.bit_length() + 7) // 8, "big").decode("ascii")
+
+
+def _unpack(value: int, offset: int, bits: int) -> int:
+    return (value >> offset) & ((1 << bits) - 1)
+
+
+def _u256(value: BigNumberish) -> List[int]:
+    value = to_int(value)
+    return [value & ((1 << 128) - 1), value >> 128]
+
+
+def _execute(world: BigNumberish, system: str, calldata: List[int]) -> Call:
+    return Call(
+        to_addr=to_int(world),
+        selector=EXECUTE_SELECTOR,
+        calldata=[short_string(system), len(calldata), *calldata],
+    )
+"#;
+
+/// Decoder of the world's events, `COMPONENTS` decodes the records.
+const EVENTS: &str = r#"def decode_event(
+    keys: Sequence[BigNumberish], data: Sequence[BigNumberish]
+) -> Optional[Dict[str, Any]]:
+    """Decodes an event emitted by the world, `None` if it isn't one of the world's."""
+    selector = to_int(keys[0])
+    name = next((name for name, s in EVENT_SELECTORS.items() if s == selector), None)
+    data = [to_int(value) for value in data]
+
+    if name is None:
+        return None
+    if name == "WorldSpawned":
+        return {
+            "name": name,
+            "address": data[0],
+            "caller": data[1],
+            "world_name": parse_short_string(data[2]),
+        }
+    if name in ("ComponentRegistered", "SystemRegistered"):
+        return {"name": name, "registered": parse_short_string(data[0]), "class_hash": data[1]}
+
+    component = parse_short_string(data[0])
+    keys_length = data[1]
+    event: Dict[str, Any] = {
+        "name": name,
+        "component": component,
+        "keys": data[2 : 2 + keys_length],
+    }
+    rest = data[2 + keys_length :]
+    if name == "StoreDeleteRecord":
+        return event
+
+    if name == "StoreSetField":
+        event["offset"] = rest[0]
+        rest = rest[1:]
+    event["values"] = rest[1 : 1 + rest[0]]
+    decode = COMPONENTS.get(component)
+    if name == "StoreSetRecord" and decode is not None:
+        event["record"] = decode(event["values"])
+    return event
+"#;
+
+/// Subscription to the entity updates indexed by torii, optionally of a single component.
+const ENTITY_STATE_UPDATES: &str = r#"ENTITY_STATE_UPDATES_SUBSCRIPTION = """
+subscription EntityStateUpdates($componentName: String) {
+  entityStateUpdates(componentName: $componentName) {
+    id
+    entityId
+    componentName
+    data
+  }
+}
+"""
+"#;
+
+/// Returns the module of the bindings and the `pyproject.toml` packaging it, by file name.
+pub fn generate(manifest: &Manifest, package_name: &str) -> Vec<(String, String)> {
+    let enums = enums(manifest);
+    let module = snake_case(package_name);
+
+    let mut out = String::from("# Generated by sozo from the world's manifest, do not edit.\n\n");
+    out.push_str(
+        "from dataclasses import dataclass\nfrom enum import IntEnum\nfrom typing import Any, \
+         Callable, Dict, List, Optional, Sequence, Union\n\n",
+    );
+    let execute = get_selector_from_name("execute").expect("valid selector");
+    out.push_str(&format!("EXECUTE_SELECTOR = {execute:#x}\n\n"));
+    out.push_str(RUNTIME);
+
+    for def in &enums {
+        out.push_str("\n\n");
+        out.push_str(&generate_enum(def));
+    }
+    for component in &manifest.components {
+        out.push_str("\n\n");
+        out.push_str(&generate_component(component, &enums));
+    }
+
+    out.push_str("\n\n# Decoders of the components' records, by component name.\n");
+    out.push_str("COMPONENTS: Dict[str, Callable[[Sequence[BigNumberish]], Any]] = {\n");
+    for component in &manifest.components {
+        out.push_str(&format!("    \"{0}\": {0}.decode,\n", component.name));
+    }
+    out.push_str("}\n");
+
+    for system in &manifest.systems {
+        out.push_str("\n\n");
+        out.push_str(&generate_system(system, &enums));
+    }
+
+    out.push_str("\n\n# Selectors of the world's events, the first key of the events.\n");
+    out.push_str("EVENT_SELECTORS: Dict[str, int] = {\n");
+    for name in WORLD_EVENTS {
+        let selector = get_selector_from_name(name).expect("valid event name");
+        out.push_str(&format!("    \"{name}\": {selector:#x},\n"));
+    }
+    out.push_str("}\n\n\n");
+    out.push_str(EVENTS);
+
+    out.push_str("\n\n# Queries of the components indexed by torii, by component name.\n");
+    for component in &manifest.components {
+        out.push_str(&generate_query(component));
+    }
+    out.push_str("QUERIES: Dict[str, str] = {\n");
+    for component in &manifest.components {
+        let constant = snake_case(&component.name).to_uppercase();
+        out.push_str(&format!("    \"{}\": {constant}_QUERY,\n", component.name));
+    }
+    out.push_str("}\n\n");
+    out.push_str(ENTITY_STATE_UPDATES);
+
+    let project = format!(
+        "[project]\nname = \"{package_name}\"\nversion = \"0.1.0\"\nrequires-python = \
+         \">=3.8\"\n\n[tool.setuptools]\npy-modules = [\"{module}\"]\n"
+    );
+
+    vec![(format!("{module}.py"), out), ("pyproject.toml".to_string(), project)]
+}
+
+fn generate_enum(def: &EnumDef) -> String {
+    let mut out = format!("class {}(IntEnum):\n", def.name);
+    for (i, variant) in def.variants.iter().enumerate() {
+        out.push_str(&format!("    {variant} = {i}\n"));
+    }
+    out
+}
+
+fn generate_component(component: &Component, enums: &[EnumDef]) -> String {
+    let layouts = layout(&component.members, enums);
+
+    let mut out = format!("@dataclass\nclass {}:\n", component.name);
+    for layout in &layouts {
+        out.push_str(&format!("    {}: {}\n", layout.member.name, output_type(&layout.kind)));
+    }
+
+    out.push_str(&format!(
+        "\n    @classmethod\n    def decode(cls, values: Sequence[BigNumberish]) -> \
+         \"{0}\":\n        \"\"\"Decodes the values of a `{0}` record, as stored by the \
+         world.\"\"\"\n",
+        component.name
+    ));
+    out.push_str("        v = [to_int(value) for value in values]\n        return cls(\n");
+    for layout in &layouts {
+        let raw = match layout.packed {
+            Some((offset, bits)) => format!("_unpack(v[{}], {offset}, {bits})", layout.index),
+            None => format!("v[{}]", layout.index),
+        };
+        let value = match (&layout.kind, layout.high_index) {
+            (TypeKind::U256, Some(high)) => format!("{raw} + (v[{high}] << 128)"),
+            (TypeKind::Bool, _) => format!("{raw} != 0"),
+            (TypeKind::Enum(name), _) => format!("{name}({raw})"),
+            _ => raw,
+        };
+        out.push_str(&format!("            {}={value},\n", layout.member.name));
+    }
+    out.push_str("        )\n");
+
+    out
+}
+
+/// The query reading a component by id from torii, selecting all its members.
+fn generate_query(component: &Component) -> String {
+    let constant = snake_case(&component.name).to_uppercase();
+    let members =
+        component.members.iter().map(|member| format!("    {}\n", member.name)).collect::<String>();
+    format!(
+        "{constant}_QUERY = \"\"\"\nquery {0}($id: Int!) {{\n  {1}(id: $id) {{\n{members}  \
+         }}\n}}\n\"\"\"\n\n",
+        component.name,
+        component.name.to_lowercase()
+    )
+}
+
+fn generate_system(system: &System, enums: &[EnumDef]) -> String {
+    let name = system_name(&system.name);
+    let inputs = system
+        .inputs
+        .iter()
+        .map(|input| (input.name.as_str(), TypeKind::from_type(&input.ty, enums)))
+        .collect::<Vec<_>>();
+
+    let params = inputs
+        .iter()
+        .map(|(input, kind)| format!(", {input}: {}", input_type(kind)))
+        .collect::<String>();
+
+    let mut out = format!(
+        "def execute_{}(world: BigNumberish{params}) -> Call:\n    \"\"\"Builds the call \
+         executing the `{name}` system.\"\"\"\n    calldata: List[int] = []\n",
+        snake_case(name)
+    );
+    for (input, kind) in &inputs {
+        out.push_str(&encode(input, kind, 1));
+    }
+    out.push_str(&format!("    return _execute(world, \"{name}\", calldata)\n"));
+
+    out
+}
+
+/// Statements appending the serialization of `value` to `calldata`.
+fn encode(value: &str, kind: &TypeKind, depth: usize) -> String {
+    let indent = "    ".repeat(depth);
+    match kind {
+        TypeKind::Bool => format!("{indent}calldata.append(1 if {value} else 0)\n"),
+        TypeKind::U256 => format!("{indent}calldata.extend(_u256({value}))\n"),
+        TypeKind::Array(inner) => {
+            let item = format!("item{depth}");
+            format!(
+                "{indent}calldata.append(len({value}))\n{indent}for {item} in {value}:\n{}",
+                encode(&item, inner, depth + 1)
+            )
+        }
+        TypeKind::Felt | TypeKind::Uint(_) | TypeKind::Enum(_) => {
+            format!("{indent}calldata.append(to_int({value}))\n")
+        }
+    }
+}
+
+fn input_type(kind: &TypeKind) -> String {
+    match kind {
+        TypeKind::Bool => "bool".to_string(),
+        TypeKind::Uint(bits) if *bits <= 32 => "int".to_string(),
+        TypeKind::Enum(name) => name.clone(),
+        TypeKind::Array(inner) => format!("Sequence[{}]", input_type(inner)),
+        TypeKind::Felt | TypeKind::Uint(_) | TypeKind::U256 => "BigNumberish".to_string(),
+    }
+}
+
+fn output_type(kind: &TypeKind) -> String {
+    match kind {
+        TypeKind::Bool => "bool".to_string(),
+        TypeKind::Enum(name) => name.clone(),
+        TypeKind::Array(inner) => format!("List[{}]", output_type(inner)),
+        TypeKind::Felt | TypeKind::Uint(_) | TypeKind::U256 => "int".to_string(),
+    }
+}
@@ -0,0 +1,60 @@
+use dojo_world::manifest::Manifest;
+use starknet::core::utils::get_selector_from_name;
+
+use super::generate;
+
+fn ecs_bindings() -> (String, String) {
+    let manifest = Manifest::load_from_path("../../examples/ecs/target/dev/manifest.json").unwrap();
+    let mut files = generate(&manifest, "ecs-bindings").into_iter();
+    let (module_name, module) = files.next().unwrap();
+    let (project_name, project) = files.next().unwrap();
+    assert_eq!(module_name, "ecs_bindings.py");
+    assert_eq!(project_name, "pyproject.toml");
+    assert!(files.next().is_none());
+    (module, project)
+}
+
+#[test]
+fn test_generate_components() {
+    let (module, _) = ecs_bindings();
+
+    assert!(module.starts_with("# Generated by sozo from the world's manifest, do not edit.\n"));
+    assert!(module.contains("@dataclass\nclass Moves:\n    remaining: int\n"));
+    assert!(module.contains("@dataclass\nclass Position:\n    x: int\n    y: int\n"));
+    assert!(module.contains("    \"Moves\": Moves.decode,\n"));
+    assert!(module.contains("    \"Position\": Position.decode,\n"));
+}
+
+#[test]
+fn test_generate_systems() {
+    let (module, _) = ecs_bindings();
+
+    let execute = get_selector_from_name("execute").unwrap();
+    assert!(module.contains(&format!("EXECUTE_SELECTOR = {execute:#x}\n")));
+    assert!(module.contains(
+        "def execute_spawn(world: BigNumberish) -> Call:\n    \"\"\"Builds the call executing \
+         the `Spawn` system.\"\"\"\n    calldata: List[int] = []\n    return _execute(world, \
+         \"Spawn\", calldata)\n"
+    ));
+    assert!(module.contains(
+        "def execute_move(world: BigNumberish, direction: BigNumberish) -> Call:\n    \"\"\"Builds \
+         the call executing the `Move` system.\"\"\"\n    calldata: List[int] = []\n    \
+         calldata.append(to_int(direction))\n    return _execute(world, \"Move\", calldata)\n"
+    ));
+}
+
+#[test]
+fn test_generate_queries_and_project() {
+    let (module, project) = ecs_bindings();
+
+    assert!(module.contains(
+        "POSITION_QUERY = \"\"\"\nquery Position($id: Int!) {\n  position(id: $id) {\n    x\n    \
+         y\n  }\n}\n\"\"\"\n"
+    ));
+    assert!(module.contains("    \"Position\": POSITION_QUERY,\n"));
+    assert_eq!(
+        project,
+        "[project]\nname = \"ecs-bindings\"\nversion = \"0.1.0\"\nrequires-python = \
+         \">=3.8\"\n\n[tool.setuptools]\npy-modules = [\"ecs_bindings\"]\n"
+    );
+}
@@ -0,0 +1,272 @@
+//! TypeScript bindings: the components as interfaces with their decoders, builders of the calls
+//! executing the systems and decoders of the world's events. Felts are hex strings on the way in
+//! and `bigint`s on the way out, integers up to 32 bits are numbers.
+
+use dojo_world::manifest::{Component, Manifest, System};
+use starknet::core::utils::get_selector_from_name;
+
+use super::{enums, layout, system_name, EnumDef, TypeKind};
+use crate::commands::events::WORLD_EVENTS;
+
+#[cfg(test)]
+#[path = "typescript_test.rs"]
+mod test;
+
+/// Helpers shared by the generated code.
+const RUNTIME: &str = r#"export type BigNumberish = string | number | bigint;
+
+/// A call to a contract, in the shape expected by starknet.js accounts.
+export interface Call {
+  contractAddress: string;
+  entrypoint: string;
+  calldata: string[];
+}
+
+export function toHex(value: BigNumberish): string {
+  return "0x" + BigInt(value).toString(16);
+}
+
+export function shortString(value: string): string {
+  let hex = "";
+  for (const char of value) {
+    hex += char.charCodeAt(0).toString(16).padStart(2, "0");
+  }
+  return "0x" + (hex || "0");
+}
+
+export function parseShortString(value: BigNumberish): string {
+  let hex = BigInt(value).toString(16);
+  if (hex.length % 2) {
+    hex = "0" + hex;
+  }
+  let out = "";
+  for (let i = 0; i < hex.length; i += 2) {
+    out += String.fromCharCode(parseInt(hex.slice(i, i + 2), 16));
+  }
+  return out;
+}
+
+function unpack(value: bigint, offset: number, bits: number): bigint {
+  return (value >> BigInt(offset)) & ((1n << BigInt(bits)) - 1n);
+}
+
+function u256(value: BigNumberish): string[] {
+  const big = BigInt(value);
+  return [toHex(big & ((1n << 128n) - 1n)), toHex(big >> 128n)];
+}
+
+function execute(world: string, system: string, calldata: string[]): Call {
+  return {
+    contractAddress: world,
+    entrypoint: "execute",
+    calldata: [shortString(system), toHex(calldata.length), ...calldata],
+  };
+}
+"#;
+
+/// Decoders of the world's events, `COMPONENTS` decodes the records.
+const EVENTS: &str = r#"export type WorldEvent =
+  | { name: "WorldSpawned"; address: string; caller: string; worldName: string }
+  | { name: "ComponentRegistered" | "SystemRegistered"; registered: string; classHash: string }
+  | {
+      name: "StoreSetRecord" | "StoreSetField" | "StoreDeleteRecord";
+      component: string;
+      keys: string[];
+      offset?: number;
+      values?: string[];
+      /// The decoded record of `StoreSetRecord` events of known components.
+      record?: unknown;
+    };
+
+/// Decodes an event emitted by the world, `undefined` if it isn't one of the world's.
+export function decodeEvent(event: { keys: string[]; data: string[] }): WorldEvent | undefined {
+  const selector = BigInt(event.keys[0]);
+  const name = (Object.keys(EVENT_SELECTORS) as (keyof typeof EVENT_SELECTORS)[]).find(
+    (name) => BigInt(EVENT_SELECTORS[name]) === selector,
+  );
+  const data = event.data;
+
+  switch (name) {
+    case undefined:
+      return undefined;
+    case "WorldSpawned":
+      return { name, address: data[0], caller: data[1], worldName: parseShortString(data[2]) };
+    case "ComponentRegistered":
+    case "SystemRegistered":
+      return { name, registered: parseShortString(data[0]), classHash: data[1] };
+    default: {
+      const component = parseShortString(data[0]);
+      const keysLength = Number(data[1]);
+      const keys = data.slice(2, 2 + keysLength);
+      let rest = data.slice(2 + keysLength);
+      if (name === "StoreDeleteRecord") {
+        return { name, component, keys };
+      }
+
+      let offset: number | undefined;
+      if (name === "StoreSetField") {
+        offset = Number(rest[0]);
+        rest = rest.slice(1);
+      }
+      const values = rest.slice(1, 1 + Number(rest[0]));
+      const decode = COMPONENTS[component];
+      const record = name === "StoreSetRecord" && decode ? decode(values) : undefined;
+      return { name, component, keys, offset, values, record };
+    }
+  }
+}
+"#;
+
+/// Returns the `index.ts` and `package.json` of the bindings, by file name.
+pub fn generate(manifest: &Manifest, package_name: &str) -> Vec<(String, String)> {
+    let enums = enums(manifest);
+
+    let mut out = String::from("// Generated by sozo from the world's manifest, do not edit.\n\n");
+    out.push_str(RUNTIME);
+
+    for def in &enums {
+        out.push('\n');
+        out.push_str(&generate_enum(def));
+    }
+    for component in &manifest.components {
+        out.push('\n');
+        out.push_str(&generate_component(component, &enums));
+    }
+
+    out.push_str("\n/// Decoders of the components' records, by component name.\n");
+    out.push_str(
+        "export const COMPONENTS: Record<string, (values: BigNumberish[]) => unknown> = {\n",
+    );
+    for component in &manifest.components {
+        out.push_str(&format!("  {0}: decode{0},\n", component.name));
+    }
+    out.push_str("};\n");
+
+    for system in &manifest.systems {
+        out.push('\n');
+        out.push_str(&generate_system(system, &enums));
+    }
+
+    out.push_str("\nexport const EVENT_SELECTORS = {\n");
+    for name in WORLD_EVENTS {
+        let selector = get_selector_from_name(name).expect("valid event name");
+        out.push_str(&format!("  {name}: \"{selector:#x}\",\n"));
+    }
+    out.push_str("} as const;\n\n");
+    out.push_str(EVENTS);
+
+    let package = format!(
+        "{{\n  \"name\": \"{package_name}\",\n  \"version\": \"0.1.0\",\n  \"main\": \
+         \"index.ts\",\n  \"types\": \"index.ts\"\n}}\n"
+    );
+
+    vec![("index.ts".to_string(), out), ("package.json".to_string(), package)]
+}
+
+fn generate_enum(def: &EnumDef) -> String {
+    let mut out = format!("export enum {} {{\n", def.name);
+    for variant in &def.variants {
+        out.push_str(&format!("  {variant},\n"));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn generate_component(component: &Component, enums: &[EnumDef]) -> String {
+    let layouts = layout(&component.members, enums);
+
+    let mut out = format!("export interface {} {{\n", component.name);
+    for layout in &layouts {
+        out.push_str(&format!("  {}: {};\n", layout.member.name, output_type(&layout.kind)));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!(
+        "/// Decodes the values of a `{0}` record, as stored by the world.\nexport function \
+         decode{0}(values: BigNumberish[]): {0} {{\n",
+        component.name
+    ));
+    out.push_str("  const v = values.map((value) => BigInt(value));\n  return {\n");
+    for layout in &layouts {
+        let raw = match layout.packed {
+            Some((offset, bits)) => format!("unpack(v[{}], {offset}, {bits})", layout.index),
+            None => format!("v[{}]", layout.index),
+        };
+        let value = match (&layout.kind, layout.high_index) {
+            (TypeKind::U256, Some(high)) => format!("{raw} + (v[{high}] << 128n)"),
+            (TypeKind::Bool, _) => format!("{raw} !== 0n"),
+            (TypeKind::Uint(bits), _) if *bits <= 32 => format!("Number({raw})"),
+            (TypeKind::Enum(name), _) => format!("Number({raw}) as {name}"),
+            _ => raw,
+        };
+        out.push_str(&format!("    {}: {value},\n", layout.member.name));
+    }
+    out.push_str("  };\n}\n");
+
+    out
+}
+
+fn generate_system(system: &System, enums: &[EnumDef]) -> String {
+    let name = system_name(&system.name);
+    let inputs = system
+        .inputs
+        .iter()
+        .map(|input| (input.name.as_str(), TypeKind::from_type(&input.ty, enums)))
+        .collect::<Vec<_>>();
+
+    let params = inputs
+        .iter()
+        .map(|(input, kind)| format!(", {input}: {}", input_type(kind)))
+        .collect::<String>();
+
+    let mut out = format!(
+        "/// Builds the call executing the `{name}` system.\nexport function execute{name}(world: \
+         string{params}): Call {{\n  const calldata: string[] = [];\n"
+    );
+    for (input, kind) in &inputs {
+        out.push_str(&encode(input, kind, 1));
+    }
+    out.push_str(&format!("  return execute(world, \"{name}\", calldata);\n}}\n"));
+
+    out
+}
+
+/// Statements appending the serialization of `value` to `calldata`.
+fn encode(value: &str, kind: &TypeKind, depth: usize) -> String {
+    let indent = "  ".repeat(depth);
+    match kind {
+        TypeKind::Bool => format!("{indent}calldata.push({value} ? \"0x1\" : \"0x0\");\n"),
+        TypeKind::U256 => format!("{indent}calldata.push(...u256({value}));\n"),
+        TypeKind::Array(inner) => {
+            let item = format!("item{depth}");
+            format!(
+                "{indent}calldata.push(toHex({value}.length));\n{indent}for (const {item} of \
+                 {value}) {{\n{}{indent}}}\n",
+                encode(&item, inner, depth + 1)
+            )
+        }
+        TypeKind::Felt | TypeKind::Uint(_) | TypeKind::Enum(_) => {
+            format!("{indent}calldata.push(toHex({value}));\n")
+        }
+    }
+}
+
+fn input_type(kind: &TypeKind) -> String {
+    match kind {
+        TypeKind::Bool => "boolean".to_string(),
+        TypeKind::Uint(bits) if *bits <= 32 => "number".to_string(),
+        TypeKind::Enum(name) => name.clone(),
+        TypeKind::Array(inner) => format!("{}[]", input_type(inner)),
+        TypeKind::Felt | TypeKind::Uint(_) | TypeKind::U256 => "BigNumberish".to_string(),
+    }
+}
+
+fn output_type(kind: &TypeKind) -> String {
+    match kind {
+        TypeKind::Bool => "boolean".to_string(),
+        TypeKind::Uint(bits) if *bits <= 32 => "number".to_string(),
+        TypeKind::Enum(name) => name.clone(),
+        TypeKind::Array(inner) => format!("{}[]", output_type(inner)),
+        TypeKind::Felt | TypeKind::Uint(_) | TypeKind::U256 => "bigint".to_string(),
+    }
+}
@@ -0,0 +1,57 @@
+use dojo_world::manifest::Manifest;
+use starknet::core::utils::get_selector_from_name;
+
+use super::generate;
+
+fn ecs_bindings() -> (String, String) {
+    let manifest = Manifest::load_from_path("../../examples/ecs/target/dev/manifest.json").unwrap();
+    let mut files = generate(&manifest, "ecs-bindings").into_iter();
+    let (index_name, index) = files.next().unwrap();
+    let (package_name, package) = files.next().unwrap();
+    assert_eq!(index_name, "index.ts");
+    assert_eq!(package_name, "package.json");
+    assert!(files.next().is_none());
+    (index, package)
+}
+
+#[test]
+fn test_generate_components() {
+    let (index, _) = ecs_bindings();
+
+    assert!(index.starts_with("// Generated by sozo from the world's manifest, do not edit.\n"));
+    assert!(index.contains("export interface Moves {\n  remaining: number;\n}\n"));
+    assert!(index.contains("export interface Position {\n  x: number;\n  y: number;\n}\n"));
+    assert!(index.contains("export function decodePosition(values: BigNumberish[]): Position {\n"));
+    assert!(index.contains("  Moves: decodeMoves,\n"));
+    assert!(index.contains("  Position: decodePosition,\n"));
+}
+
+#[test]
+fn test_generate_systems() {
+    let (index, _) = ecs_bindings();
+
+    assert!(index.contains(
+        "export function executeSpawn(world: string): Call {\n  const calldata: string[] = \
+         [];\n  return execute(world, \"Spawn\", calldata);\n}\n"
+    ));
+    // `Direction` isn't used by a component, so it's only known as a felt.
+    assert!(index.contains(
+        "export function executeMove(world: string, direction: BigNumberish): Call {\n  const \
+         calldata: string[] = [];\n  calldata.push(toHex(direction));\n  return execute(world, \
+         \"Move\", calldata);\n}\n"
+    ));
+}
+
+#[test]
+fn test_generate_events_and_package() {
+    let (index, package) = ecs_bindings();
+
+    let selector = get_selector_from_name("StoreSetRecord").unwrap();
+    assert!(index.contains(&format!("  StoreSetRecord: \"{selector:#x}\",\n")));
+    assert!(index.contains("export function decodeEvent("));
+    assert_eq!(
+        package,
+        "{\n  \"name\": \"ecs-bindings\",\n  \"version\": \"0.1.0\",\n  \"main\": \"index.ts\",\n  \
+         \"types\": \"index.ts\"\n}\n"
+    );
+}
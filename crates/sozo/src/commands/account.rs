@@ -0,0 +1,105 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::Utf8PathBuf;
+use clap::{Args, Subcommand};
+use dojo_world::config::EnvironmentConfig;
+use dojo_world::faucet::{Faucet, DEFAULT_FUND_AMOUNT};
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::core::types::FieldElement;
+use url::Url;
+
+use super::build::ProfileSpec;
+use crate::cancellation::run_cancellable;
+
+#[derive(Args)]
+pub struct AccountArgs {
+    #[command(subcommand)]
+    command: AccountCommand,
+}
+
+#[derive(Subcommand)]
+pub enum AccountCommand {
+    #[command(about = "Fund an account with fee tokens through a development network faucet")]
+    Fund(FundArgs),
+}
+
+#[derive(Args)]
+pub struct FundArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, help = "Address to fund, defaults to the configured account address")]
+    address: Option<FieldElement>,
+
+    #[clap(long, default_value_t = DEFAULT_FUND_AMOUNT)]
+    #[clap(help = "Amount of fee tokens to mint, in wei")]
+    amount: u128,
+
+    #[clap(long, help = "URL of a starknet-devnet faucet, mints through katana's RPC if omitted")]
+    faucet_url: Option<Url>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: AccountArgs, timeout: Option<Duration>) -> Result<()> {
+    match args.command {
+        AccountCommand::Fund(args) => fund(args, timeout),
+    }
+}
+
+fn fund(args: FundArgs, timeout: Option<Duration>) -> Result<()> {
+    dotenv().ok();
+
+    let FundArgs { path, address, amount, faucet_url, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let address = match address {
+        Some(address) => address,
+        None => env_config.account_address()?,
+    };
+
+    let faucet = match faucet_url {
+        Some(url) => Faucet::Devnet(url),
+        None => Faucet::Katana(
+            env_config.rpc.clone().ok_or(anyhow!("Missing `rpc_url` in the environment config"))?,
+        ),
+    };
+
+    ws.config()
+        .tokio_handle()
+        .block_on(run_cancellable(faucet.fund(address, amount), timeout))
+        .map_err(|reason| anyhow!("Funding {address:#x} {reason}"))??;
+
+    println!("Funded {address:#x} with {amount} wei");
+
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::Utf8PathBuf;
+use clap::{Args, Subcommand};
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::object::WorldContract;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::accounts::ConnectedAccount;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use super::call::call;
+use crate::cancellation::run_cancellable;
+use crate::fee::FeeArgs;
+use crate::porcelain;
+use crate::receipts::{History, HistoryEntry, WaitArgs};
+
+#[derive(Args)]
+pub struct AuthArgs {
+    #[command(subcommand)]
+    command: AuthCommand,
+
+    #[clap(long, global = true)]
+    #[clap(help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, global = true, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    fee: FeeArgs,
+
+    #[command(flatten)]
+    wait: WaitArgs,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+#[derive(Subcommand)]
+pub enum AuthCommand {
+    #[command(about = "Authorize a system to write to a component")]
+    Writer {
+        #[clap(help = "Name of the component")]
+        component: String,
+
+        #[clap(help = "Name of the system, without the `System` suffix")]
+        system: String,
+
+        #[clap(long, help = "Role granted to the system, defaults to the system name")]
+        role: Option<String>,
+
+        #[clap(long, help = "Revoke the authorization instead of granting it")]
+        revoke: bool,
+    },
+
+    #[command(about = "Grant the world level Admin role, allowing to write to any component")]
+    Owner {
+        #[clap(help = "Account address, or name of a system without the `System` suffix")]
+        target: String,
+
+        #[clap(long, help = "Revoke the role instead of granting it")]
+        revoke: bool,
+    },
+
+    #[command(
+        about = "List which systems of the manifest are authorized to write to which components"
+    )]
+    List,
+}
+
+pub fn run(args: AuthArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let AuthArgs { command, world, path, fee, wait, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+
+    // Authorizations are managed by executing the auth systems registered in the world.
+    let (system, calldata, description) = match command {
+        AuthCommand::Writer { component, system, role, revoke } => {
+            let system = system.strip_suffix("System").unwrap_or(&system).to_string();
+            let target_id = cairo_short_string_to_felt(&system)?;
+            let resource_id = cairo_short_string_to_felt(&component)?;
+
+            if revoke {
+                (
+                    "RevokeScopedAuthRole",
+                    vec![target_id, resource_id],
+                    format!("revoke {system} writer of {component}"),
+                )
+            } else {
+                let role_id = cairo_short_string_to_felt(role.as_deref().unwrap_or(&system))?;
+                (
+                    "RouteAuth",
+                    vec![target_id, role_id, resource_id],
+                    format!("grant {system} writer of {component}"),
+                )
+            }
+        }
+        AuthCommand::Owner { target, revoke } => {
+            let target_id = if target.starts_with("0x") {
+                FieldElement::from_hex_be(&target)?
+            } else {
+                cairo_short_string_to_felt(target.strip_suffix("System").unwrap_or(&target))?
+            };
+
+            if revoke {
+                ("RevokeAuthRole", vec![target_id], format!("revoke {target} owner"))
+            } else {
+                (
+                    "GrantAuthRole",
+                    vec![target_id, cairo_short_string_to_felt("Admin")?],
+                    format!("grant {target} owner"),
+                )
+            }
+        }
+        AuthCommand::List => {
+            let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+            if !target_dir.join("manifest.json").exists() {
+                build::run(BuildArgs {
+                    path: Some(source_dir.clone()),
+                    check: false,
+                    output_abis: None,
+                    offline: false,
+                    profile_spec,
+                })?;
+            }
+            let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+            let provider = env_config.provider()?;
+
+            let list = async {
+                for system in &manifest.systems {
+                    let mut writes = vec![];
+                    for component in &manifest.components {
+                        let calldata = vec![system.class_hash, component.class_hash];
+                        let res = call(&provider, world_address, "is_authorized", calldata).await?;
+                        if res.first().map_or(false, |r| *r != FieldElement::ZERO) {
+                            writes.push(component.name.as_str());
+                        }
+                    }
+
+                    let name = system.name.strip_suffix("System").unwrap_or(&system.name);
+                    if porcelain {
+                        for component in writes {
+                            porcelain::record("writer", [name, component]);
+                        }
+                    } else if writes.is_empty() {
+                        println!("{name}: -");
+                    } else {
+                        println!("{name}: {}", writes.join(", "));
+                    }
+                }
+                Ok::<_, anyhow::Error>(())
+            };
+
+            return ws.config().tokio_handle().block_on(async {
+                run_cancellable(list, timeout).await.map_err(|reason| anyhow!("Call {reason}"))?
+            });
+        }
+    };
+
+    let history = History::new(&source_dir, profile.as_str());
+
+    ws.config().tokio_handle().block_on(async {
+        let account = env_config.migrator().await?;
+        let world = WorldContract::new(world_address, &account)
+            .with_fees(fee.fee_config(&env_config))
+            .with_retry(env_config.retry_policy());
+        let system_name = cairo_short_string_to_felt(system)?;
+
+        if fee.needs_estimate() {
+            let call = world.execute_call(system_name, calldata.clone());
+            if !fee.check(&account, vec![call], porcelain).await? {
+                return Ok(());
+            }
+        }
+
+        let transaction_hash = world
+            .execute(system_name, calldata.clone())
+            .await
+            .map_err(|e| anyhow!("Failed to execute {system}: {e}"))?
+            .transaction_hash;
+        if !porcelain {
+            println!("Transaction hash: {transaction_hash:#x}");
+        }
+
+        let provider = account.provider();
+        wait.receipt(provider, transaction_hash, timeout, porcelain).await?;
+
+        let entry =
+            HistoryEntry::from_receipt(provider, "auth", &description, transaction_hash, calldata)
+                .await;
+        if porcelain {
+            porcelain::transaction(&entry);
+        }
+        history.append(&[entry])
+    })
+}
@@ -0,0 +1,96 @@
+use std::env::{self, current_dir};
+use std::fs;
+
+use anyhow::Result;
+use camino::Utf8PathBuf;
+use clap::{Args, ValueEnum};
+use dojo_world::manifest::Manifest;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::codegen::{csharp, python, typescript};
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Language {
+    Typescript,
+    Csharp,
+    Python,
+}
+
+#[derive(Args)]
+pub struct BindgenArgs {
+    #[clap(value_enum, help = "Language of the bindings")]
+    language: Language,
+
+    #[clap(long, short)]
+    #[clap(help = "Directory to write the bindings to, defaults to `bindings/<language>`")]
+    output: Option<Utf8PathBuf>,
+
+    #[clap(long, help = "Name of the generated package, defaults to the project's")]
+    package_name: Option<String>,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: BindgenArgs) -> Result<()> {
+    let BindgenArgs { language, output, package_name, path, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+    let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+
+    let package_name = match package_name {
+        Some(name) => name,
+        None => ws.current_package()?.id.name.to_string(),
+    };
+    let (files, default_dir) = match language {
+        Language::Typescript => (typescript::generate(&manifest, &package_name), "typescript"),
+        Language::Csharp => (csharp::generate(&manifest, &package_name), "csharp"),
+        Language::Python => (python::generate(&manifest, &package_name), "python"),
+    };
+
+    let output = output.unwrap_or_else(|| source_dir.join("bindings").join(default_dir));
+    fs::create_dir_all(&output)?;
+    for (name, content) in files {
+        fs::write(output.join(name), content)?;
+    }
+    println!("Bindings written to {output}");
+
+    Ok(())
+}
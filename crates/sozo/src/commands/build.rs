@@ -1,35 +1,65 @@
+use std::collections::BTreeMap;
 use std::env::{self, current_dir};
+use std::fs;
+use std::sync::OnceLock;
 
-use anyhow::Result;
-use camino::Utf8PathBuf;
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::{Args, Parser};
-use dojo_lang::compiler::DojoCompiler;
+use dojo_lang::compiler::{DojoCheckCompiler, DojoCompiler};
 use dojo_lang::plugin::CairoPluginRepository;
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::strategy::collect_artifact_paths;
 use scarb::compiler::{CompilerRepository, Profile};
 use scarb::core::Config;
 use scarb::ops;
 use scarb::ui::Verbosity;
+use serde_json::{json, Value};
 use smol_str::SmolStr;
 
+/// File of the combined ABI document written to `--output-abis`, distinct from `World.json` even
+/// on case insensitive file systems.
+const WORLD_ABI_FILE: &str = "world_abi.json";
+
 #[derive(Args, Debug)]
 pub struct BuildArgs {
     #[clap(help = "Source directory")]
     pub path: Option<Utf8PathBuf>,
 
+    #[arg(long)]
+    #[arg(help = "Only expand the Dojo plugins and type check, without writing any artifact.")]
+    pub check: bool,
+
+    #[arg(long, conflicts_with = "check", value_name = "DIR")]
+    #[arg(help = "Also write the ABI of every contract, and a combined world ABI document keyed \
+                  by component and system name, to this directory.")]
+    pub output_abis: Option<Utf8PathBuf>,
+
+    #[arg(long)]
+    #[arg(help = "Build without accessing the network, the dependencies must already be fetched.")]
+    pub offline: bool,
+
     /// Specify the profile to use.
     #[command(flatten)]
     pub profile_spec: ProfileSpec,
 }
 
-/// Profile specifier.
+/// Profile selected by name with the global `--profile` of [`super::App`], set once the command
+/// line is parsed.
+static SELECTED_PROFILE: OnceLock<SmolStr> = OnceLock::new();
+
+/// Selects the profile every command determines its [`ProfileSpec`] with.
+pub fn select_profile(profile: Option<SmolStr>) {
+    if let Some(profile) = profile {
+        let _ = SELECTED_PROFILE.set(profile);
+    }
+}
+
+/// Profile specifier, `--release` and `--dev` taking precedence over the profile selected by
+/// name.
 #[derive(Parser, Clone, Debug)]
-#[group(multiple = false)]
 pub struct ProfileSpec {
-    #[arg(short = 'P', long)]
-    #[arg(help = "Specify profile to use by name.")]
-    pub profile: Option<SmolStr>,
-
-    #[arg(long, hide_short_help = true)]
+    #[arg(long, hide_short_help = true, conflicts_with = "dev")]
     #[arg(help = "Use release profile.")]
     pub release: bool,
 
@@ -43,8 +73,10 @@ impl ProfileSpec {
         Ok(match &self {
             Self { release: true, .. } => Profile::RELEASE,
             Self { dev: true, .. } => Profile::DEV,
-            Self { profile: Some(profile), .. } => Profile::new(profile.clone())?,
-            _ => Profile::default(),
+            _ => match SELECTED_PROFILE.get() {
+                Some(profile) => Profile::new(profile.clone())?,
+                None => Profile::default(),
+            },
         })
     }
 }
@@ -64,7 +96,11 @@ pub fn run(args: BuildArgs) -> anyhow::Result<()> {
     };
 
     let mut compilers = CompilerRepository::std();
-    compilers.add(Box::new(DojoCompiler)).unwrap();
+    if args.check {
+        compilers.add(Box::new(DojoCheckCompiler)).unwrap();
+    } else {
+        compilers.add(Box::new(DojoCompiler)).unwrap();
+    }
 
     let cairo_plugins = CairoPluginRepository::new();
 
@@ -75,10 +111,102 @@ pub fn run(args: BuildArgs) -> anyhow::Result<()> {
         .compilers(compilers)
         .cairo_plugins(cairo_plugins.into())
         .profile(args.profile_spec.determine()?)
+        .offline(args.offline)
         .build()
         .unwrap();
 
     let ws = ops::read_workspace(config.manifest_path(), &config)?;
 
-    ops::compile(&ws)
+    ops::compile(&ws)?;
+
+    if let Some(output_dir) = args.output_abis {
+        let profile = args.profile_spec.determine()?;
+        let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+        let output_dir = if output_dir.is_absolute() {
+            output_dir
+        } else {
+            Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap().join(output_dir)
+        };
+        write_abis(&target_dir, &output_dir)?;
+        println!("ABIs written to {output_dir}");
+    }
+
+    Ok(())
+}
+
+/// Writes the ABI of every artifact of `target_dir` to `<output_dir>/<contract>.json`, and the
+/// world's components and systems along with their ABIs to `<output_dir>/world_abi.json`, so that
+/// frontends don't have to parse the Sierra artifacts:
+///
+/// ```json
+/// {
+///   "world": { "class_hash": "0x..", "abi": [..] },
+///   "executor": { "class_hash": "0x..", "abi": [..] },
+///   "components": { "Position": { "class_hash": "0x..", "members": [..], "abi": [..] } },
+///   "systems": { "Move": { "class_hash": "0x..", "inputs": [..], "outputs": [..], .. } },
+///   "contracts": { .. }
+/// }
+/// ```
+fn write_abis(target_dir: &Utf8Path, output_dir: &Utf8Path) -> Result<()> {
+    let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+    let artifact_paths = collect_artifact_paths(target_dir.to_path_buf())?;
+
+    let mut abis = BTreeMap::new();
+    for (name, path) in &artifact_paths {
+        let artifact = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read the artifact {}", path.display()))?;
+        let mut artifact: Value = serde_json::from_str(&artifact)
+            .with_context(|| format!("Failed to parse the artifact {}", path.display()))?;
+        abis.insert(name.clone(), artifact.get_mut("abi").map(Value::take).unwrap_or(json!([])));
+    }
+    let abi = |name: &str| abis.get(name).cloned().unwrap_or(json!([]));
+
+    let components = manifest
+        .components
+        .iter()
+        .map(|c| {
+            let component = json!({
+                "class_hash": c.class_hash,
+                "members": c.members,
+                "abi": abi(&format!("{}Component", c.name)),
+            });
+            (c.name.clone(), component)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let systems = manifest
+        .systems
+        .iter()
+        .map(|s| {
+            let name = s.name.strip_suffix("System").unwrap_or(&s.name).to_string();
+            let system = json!({
+                "class_hash": s.class_hash,
+                "inputs": s.inputs,
+                "outputs": s.outputs,
+                "dependencies": s.dependencies,
+                "abi": abi(&s.name),
+            });
+            (name, system)
+        })
+        .collect::<BTreeMap<_, _>>();
+    let contracts = manifest
+        .contracts
+        .iter()
+        .map(|c| (c.name.to_string(), json!({ "class_hash": c.class_hash, "abi": abi(&c.name) })))
+        .collect::<BTreeMap<_, _>>();
+    let world = json!({
+        "world": { "class_hash": manifest.world, "abi": abi("World") },
+        "executor": { "class_hash": manifest.executor, "abi": abi("Executor") },
+        "components": components,
+        "systems": systems,
+        "contracts": contracts,
+    });
+
+    fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create the directory {output_dir}"))?;
+    for (name, abi) in &abis {
+        fs::write(output_dir.join(format!("{name}.json")), serde_json::to_string_pretty(abi)?)?;
+    }
+    fs::write(output_dir.join(WORLD_ABI_FILE), serde_json::to_string_pretty(&world)?)?;
+
+    Ok(())
 }
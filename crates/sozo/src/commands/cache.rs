@@ -0,0 +1,42 @@
+use std::fs;
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Args, Subcommand};
+use dojo_world::migration::cache::{DeclaredClasses, CACHE_DIR_ENV};
+
+#[derive(Args)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    command: CacheCommand,
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommand {
+    #[command(about = "Forget the classes known to be declared, they are looked up again by the \
+                       next migrations")]
+    Clear,
+    #[command(about = "Print the path of the cache")]
+    Path,
+}
+
+pub fn run(args: CacheArgs) -> Result<()> {
+    let path = DeclaredClasses::default_path()
+        .ok_or(anyhow!("No home directory to find the cache in, set `{CACHE_DIR_ENV}`"))?;
+
+    match args.command {
+        CacheCommand::Clear => {
+            if !path.exists() {
+                println!("The cache is already empty");
+                return Ok(());
+            }
+
+            let cached = DeclaredClasses::load_from_path(&path).map_or(0, |cache| cache.len());
+            fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            println!("Forgot {cached} declared classes");
+        }
+        CacheCommand::Path => println!("{}", path.display()),
+    }
+
+    Ok(())
+}
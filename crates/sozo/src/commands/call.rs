@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use camino::Utf8PathBuf;
+use clap::{Args, Subcommand};
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::{Manifest, Member, EXECUTOR_ADDRESS_SLOT};
+use dojo_world::migration::schema::{member_size, member_values};
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::core::types::{BlockId, BlockTag, FieldElement, FunctionCall};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::Provider;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::cancellation::run_cancellable;
+use crate::porcelain;
+
+#[derive(Args)]
+pub struct CallArgs {
+    #[command(subcommand)]
+    command: CallCommand,
+
+    #[clap(long, global = true, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, global = true)]
+    #[clap(help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+#[derive(Subcommand)]
+pub enum CallCommand {
+    #[command(about = "Read the value of a component for an entity, decoded using the manifest")]
+    Entity {
+        #[clap(help = "Name of the component")]
+        component: String,
+
+        #[clap(value_delimiter = ',', help = "Comma separated keys of the entity")]
+        keys: Vec<FieldElement>,
+
+        #[clap(long, default_value = "0", help = "Partition of the entity")]
+        partition: FieldElement,
+    },
+
+    #[command(about = "Get the class hash of a registered component")]
+    Component {
+        #[clap(help = "Name of the component")]
+        name: String,
+    },
+
+    #[command(about = "Get the class hash of a registered system")]
+    System {
+        #[clap(help = "Name of the system, without the `System` suffix")]
+        name: String,
+    },
+
+    #[command(about = "Get the address of the world's executor")]
+    Executor,
+
+    #[command(about = "Call any view function of the world")]
+    Raw {
+        #[clap(help = "Name of the view function")]
+        entrypoint: String,
+
+        #[clap(short, long, value_delimiter = ',', help = "Comma separated calldata")]
+        calldata: Vec<FieldElement>,
+    },
+}
+
+pub fn run(args: CallArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let CallArgs { command, path, world, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+    let provider = env_config.provider()?;
+
+    let print_value = |value: FieldElement| {
+        if porcelain {
+            porcelain::record("value", [format!("{value:#x}")]);
+        } else {
+            println!("{value:#x}");
+        }
+    };
+
+    let call_world = async {
+        match command {
+            CallCommand::Entity { component, keys, partition } => {
+                let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+                if !target_dir.join("manifest.json").exists() {
+                    build::run(BuildArgs {
+                        path: Some(source_dir.clone()),
+                        check: false,
+                        output_abis: None,
+                        offline: false,
+                        profile_spec,
+                    })?;
+                }
+
+                let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+                let members = &manifest
+                    .components
+                    .iter()
+                    .find(|c| c.name == component)
+                    .with_context(|| format!("Component `{component}` not found in the manifest"))?
+                    .members;
+
+                let values =
+                    entity_values(&provider, world_address, &component, members, partition, keys)
+                        .await?;
+                if values.is_empty() {
+                    if !porcelain {
+                        println!("No value set for this entity");
+                    }
+                    return Ok(());
+                }
+
+                for (member, value) in decode_members(members, &values)? {
+                    if porcelain {
+                        porcelain::record("member", [member, value]);
+                    } else {
+                        println!("{member}: {value}");
+                    }
+                }
+            }
+            CallCommand::Component { name } => {
+                let calldata = vec![cairo_short_string_to_felt(&name)?];
+                let res = call(&provider, world_address, "component", calldata).await?;
+                print_value(res.first().copied().unwrap_or_default());
+            }
+            CallCommand::System { name } => {
+                let name = name.strip_suffix("System").unwrap_or(&name);
+                let calldata = vec![cairo_short_string_to_felt(name)?];
+                let res = call(&provider, world_address, "system", calldata).await?;
+                print_value(res.first().copied().unwrap_or_default());
+            }
+            CallCommand::Executor => {
+                let executor = provider
+                    .get_storage_at(
+                        world_address,
+                        EXECUTOR_ADDRESS_SLOT,
+                        BlockId::Tag(BlockTag::Pending),
+                    )
+                    .await?;
+                print_value(executor);
+            }
+            CallCommand::Raw { entrypoint, calldata } => {
+                let res = call(&provider, world_address, &entrypoint, calldata).await?;
+                for value in res {
+                    print_value(value);
+                }
+            }
+        }
+
+        Ok::<_, anyhow::Error>(())
+    };
+
+    ws.config().tokio_handle().block_on(async {
+        run_cancellable(call_world, timeout).await.map_err(|reason| anyhow!("Call {reason}"))?
+    })
+}
+
+pub(crate) async fn call<P>(
+    provider: &P,
+    world_address: FieldElement,
+    entrypoint: &str,
+    calldata: Vec<FieldElement>,
+) -> Result<Vec<FieldElement>>
+where
+    P: Provider + Sync,
+    P::Error: 'static,
+{
+    provider
+        .call(
+            FunctionCall {
+                contract_address: world_address,
+                entry_point_selector: get_selector_from_name(entrypoint)?,
+                calldata,
+            },
+            BlockId::Tag(BlockTag::Pending),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to call `{entrypoint}`: {e}"))
+}
+
+/// Reads the raw storage values of a component for an entity, empty if the entity has none.
+pub(crate) async fn entity_values<P>(
+    provider: &P,
+    world_address: FieldElement,
+    component: &str,
+    members: &[Member],
+    partition: FieldElement,
+    keys: Vec<FieldElement>,
+) -> Result<Vec<FieldElement>>
+where
+    P: Provider + Sync,
+    P::Error: 'static,
+{
+    // component, query { address_domain, partition, keys }, offset, length
+    let mut calldata = vec![
+        cairo_short_string_to_felt(component)?,
+        FieldElement::ZERO,
+        partition,
+        FieldElement::from(keys.len()),
+    ];
+    calldata.extend(keys);
+    calldata.extend([FieldElement::ZERO, FieldElement::from(storage_len(members))]);
+
+    let res = call(provider, world_address, "entity", calldata).await?;
+    // the result is a span, prefixed with its length
+    Ok(res.get(1..).unwrap_or_default().to_vec())
+}
+
+/// Number of felts the component occupies in storage, members packed together share a slot.
+fn storage_len(members: &[Member]) -> usize {
+    let mut slots = BTreeMap::new();
+    for member in members {
+        slots.entry(member.slot).or_insert_with(|| member_size(member));
+    }
+    slots.values().sum()
+}
+
+/// Decodes the raw storage values of a component into its members' declared types. Values are
+/// laid out by slot, packed members are extracted from their slot using their offset and width.
+pub(crate) fn decode_members(
+    members: &[Member],
+    values: &[FieldElement],
+) -> Result<Vec<(String, String)>> {
+    let mut values = member_values(members, values)?;
+
+    // Print the members in declaration order.
+    Ok(members
+        .iter()
+        .map(|member| {
+            let values = values.remove(&member.name).unwrap_or_default();
+            let value = values.first().copied().unwrap_or_default();
+            let value = match (member.ty.as_str(), &member.variants) {
+                (_, Some(variants)) => variants
+                    .iter()
+                    .enumerate()
+                    .find(|(i, _)| FieldElement::from(*i) == value)
+                    .map(|(_, variant)| variant.clone())
+                    .unwrap_or(format!("{value:#x}")),
+                ("bool", _) => (value != FieldElement::ZERO).to_string(),
+                ("u8" | "u16" | "u32" | "u64" | "u128" | "usize", _) => value.to_string(),
+                ("u256", _) => format!(
+                    "{{ low: {value}, high: {} }}",
+                    values.get(1).copied().unwrap_or_default()
+                ),
+                _ => format!("{value:#x}"),
+            };
+            (member.name.clone(), value)
+        })
+        .collect())
+}
@@ -0,0 +1,59 @@
+use std::env::current_dir;
+use std::fs;
+
+use anyhow::{Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+
+use super::build::ProfileSpec;
+
+#[derive(Args)]
+pub struct CleanArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, help = "Also remove the deployment records of the profile")]
+    all: bool,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: CleanArgs) -> Result<()> {
+    let CleanArgs { path, all, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profile = profile_spec.determine()?;
+
+    // The build artifacts and cache, the generated manifest and the migration checkpoint.
+    remove_dir(&source_dir.join(format!("target/{}", profile.as_str())))?;
+
+    if all {
+        remove_dir(&source_dir.join(format!("deployments/{}", profile.as_str())))?;
+    }
+
+    Ok(())
+}
+
+fn remove_dir(path: &Utf8Path) -> Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    fs::remove_dir_all(path).with_context(|| format!("Failed to remove {path}"))?;
+    println!("Removed {path}");
+
+    Ok(())
+}
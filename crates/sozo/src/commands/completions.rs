@@ -0,0 +1,87 @@
+use std::env::current_dir;
+use std::{fs, io};
+
+use anyhow::Result;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::builder::PossibleValuesParser;
+use clap::{Args, Command, CommandFactory};
+use clap_complete::{generate, Shell};
+use toml::Value;
+
+use super::App;
+
+#[derive(Args)]
+pub struct CompletionsArgs {
+    #[clap(value_enum, help = "Shell to generate the completion script for")]
+    shell: Shell,
+
+    #[clap(long)]
+    #[clap(help = "Source directory whose profiles are completed, defaults to the current one. \
+                   Regenerate the script after adding profiles")]
+    path: Option<Utf8PathBuf>,
+}
+
+/// Names of the profiles of the project: the built-in ones, those of Scarb.toml and those of the
+/// environment config.
+fn profile_names(manifest_path: &Utf8Path) -> Vec<String> {
+    let mut names = vec!["dev".to_string(), "release".to_string()];
+
+    let Ok(manifest) = fs::read_to_string(manifest_path) else {
+        return names;
+    };
+    let Ok(manifest) = manifest.parse::<Value>() else {
+        return names;
+    };
+
+    let tables = [
+        manifest.get("profile"),
+        manifest.get("tool").and_then(|t| t.get("dojo")).and_then(|d| d.get("env")),
+    ];
+    for table in tables.into_iter().flatten().filter_map(Value::as_table) {
+        for (name, value) in table {
+            if value.is_table() && !names.contains(name) {
+                names.push(name.clone());
+            }
+        }
+    }
+
+    names
+}
+
+/// Completes the values of `--profile` with `profiles`, on the command and its subcommands.
+fn with_profiles(command: Command, profiles: &[String]) -> Command {
+    let command = if command.get_arguments().any(|arg| arg.get_id() == "profile") {
+        command.mut_arg("profile", |arg| {
+            arg.value_parser(PossibleValuesParser::new(profiles.iter().map(String::as_str)))
+        })
+    } else {
+        command
+    };
+
+    let subcommands =
+        command.get_subcommands().map(|s| s.get_name().to_string()).collect::<Vec<_>>();
+    subcommands.iter().fold(command, |command, name| {
+        command.mut_subcommand(name, |subcommand| with_profiles(subcommand, profiles))
+    })
+}
+
+pub fn run(args: CompletionsArgs) -> Result<()> {
+    let source_dir = match args.path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profiles = profile_names(&source_dir.join("Scarb.toml"));
+    let mut command = with_profiles(App::command(), &profiles);
+    generate(args.shell, &mut command, "sozo", &mut io::stdout());
+
+    Ok(())
+}
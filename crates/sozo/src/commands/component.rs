@@ -0,0 +1,446 @@
+use std::collections::HashMap;
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::{Args, Subcommand};
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::{Component, Manifest};
+use dojo_world::migration::deployment::registered_classes_from_remote;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use serde_json::Value;
+use starknet::core::types::{BlockId, BlockTag, FieldElement, FunctionCall};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::Provider;
+use url::Url;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use super::call::{decode_members, entity_values};
+use crate::cancellation::run_cancellable;
+use crate::porcelain;
+
+#[derive(Args)]
+pub struct ComponentArgs {
+    #[command(subcommand)]
+    command: ComponentCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ComponentCommands {
+    #[command(about = "Print the members of a component and their storage layout")]
+    Schema {
+        #[clap(help = "Name of the component")]
+        name: String,
+
+        #[clap(long, help = "Output the schema as JSON")]
+        json: bool,
+
+        #[clap(long)]
+        #[clap(
+            help = "Check the layout against the component registered in the world at this address"
+        )]
+        world: Option<FieldElement>,
+
+        #[clap(long, help = "Source directory")]
+        path: Option<Utf8PathBuf>,
+
+        #[command(flatten)]
+        profile_spec: ProfileSpec,
+    },
+
+    #[command(about = "Read the values of a component for an entity from the world, decoded \
+                       using its schema")]
+    Get(GetArgs),
+
+    #[command(about = "List the components registered in a world, read from the chain")]
+    List(ListArgs),
+}
+
+#[derive(Args)]
+pub struct GetArgs {
+    #[clap(help = "Name of the component")]
+    name: String,
+
+    #[clap(value_delimiter = ',', help = "Comma separated keys of the entity")]
+    keys: Vec<FieldElement>,
+
+    #[clap(long, default_value = "0", help = "Partition of the entity")]
+    partition: FieldElement,
+
+    #[clap(long, help = "Output the values as JSON")]
+    json: bool,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// Arguments of `sozo component list` and `sozo system list`, which only need the chain: the
+/// project is read for its environment and world address if there is one.
+#[derive(Args)]
+pub struct ListArgs {
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "RPC endpoint of the chain, defaults to the `rpc_url` of the environment")]
+    rpc_url: Option<Url>,
+
+    #[clap(long, default_value_t = 0, help = "First block to read the registrations from")]
+    from_block: u64,
+
+    #[clap(long, help = "Output the list as JSON")]
+    json: bool,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: ComponentArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    match args.command {
+        ComponentCommands::Schema { name, json, world, path, profile_spec } => {
+            schema(name, json, porcelain, world, path, profile_spec)
+        }
+        ComponentCommands::Get(args) => get(args, timeout, porcelain),
+        ComponentCommands::List(args) => list("component", args, timeout, porcelain),
+    }
+}
+
+/// Prints the components or systems, per `kind`, registered in the world with their class hash
+/// and registration transaction, in registration order.
+pub(crate) fn list(
+    kind: &str,
+    args: ListArgs,
+    timeout: Option<Duration>,
+    porcelain: bool,
+) -> Result<()> {
+    dotenv().ok();
+
+    let ListArgs { world, rpc_url, from_block, json, path, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let (world_config, env_config) = chain_config(&source_dir, profile_spec, rpc_url)?;
+
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+    let provider = env_config.provider()?;
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let (components, systems) = runtime.block_on(async {
+        let fetch = registered_classes_from_remote(&provider, world_address, from_block);
+        run_cancellable(fetch, timeout)
+            .await
+            .map_err(|reason| anyhow!("Reading the registrations {reason}"))?
+    })?;
+    let classes = if kind == "system" { systems } else { components };
+
+    if porcelain {
+        for class in classes {
+            porcelain::record(
+                kind,
+                [
+                    class.name,
+                    format!("{:#x}", class.class_hash),
+                    porcelain::optional(class.transaction_hash.map(|hash| format!("{hash:#x}"))),
+                    porcelain::optional(class.block_number),
+                ],
+            );
+        }
+    } else if json {
+        println!("{}", serde_json::to_string_pretty(&classes)?);
+    } else if classes.is_empty() {
+        println!("No {kind} registered in the world {world_address:#x}");
+    } else {
+        let width = classes.iter().map(|class| class.name.len()).max().unwrap_or(0).max(4);
+        println!("{:width$}  {:<66}  {:<66}  BLOCK", "NAME", "CLASS HASH", "TRANSACTION");
+        for class in classes {
+            println!(
+                "{:width$}  {:<66}  {:<66}  {}",
+                class.name,
+                format!("{:#066x}", class.class_hash),
+                class.transaction_hash.map_or("-".to_string(), |hash| format!("{hash:#066x}")),
+                class.block_number.map_or("-".to_string(), |block| block.to_string()),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// The world and environment configs of the project in `source_dir`, defaults if there is no
+/// project, with `rpc_url` overriding the RPC endpoint of the environment.
+pub(crate) fn chain_config(
+    source_dir: &Utf8Path,
+    profile_spec: ProfileSpec,
+    rpc_url: Option<Url>,
+) -> Result<(WorldConfig, EnvironmentConfig)> {
+    let manifest_path = source_dir.join("Scarb.toml");
+    let (world_config, mut env_config) = if manifest_path.exists() {
+        let config = Config::builder(manifest_path)
+            .ui_verbosity(Verbosity::Verbose)
+            .log_filter_directive(env::var_os("SCARB_LOG"))
+            .build()
+            .unwrap();
+        let ws = ops::read_workspace(config.manifest_path(), &config)?;
+        let profile = profile_spec.determine()?;
+        (
+            WorldConfig::from_workspace(&ws).unwrap_or_default(),
+            EnvironmentConfig::from_workspace(profile.as_str(), &ws)?,
+        )
+    } else {
+        (WorldConfig::default(), EnvironmentConfig::default())
+    };
+    if rpc_url.is_some() {
+        env_config.rpc = rpc_url;
+    }
+
+    Ok((world_config, env_config))
+}
+
+fn get(args: GetArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let GetArgs { name, keys, partition, json, world, path, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profile = profile_spec.determine()?;
+    let manifest_path = source_dir.join(format!("target/{}/manifest.json", profile.as_str()));
+
+    if !manifest_path.exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+
+    let manifest = Manifest::load_from_path(manifest_path)?;
+    let component = manifest
+        .components
+        .into_iter()
+        .find(|c| c.name == name)
+        .with_context(|| format!("Component `{name}` not found in the manifest"))?;
+
+    let config = Config::builder(source_dir.join("Scarb.toml"))
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+    let provider = env_config.provider()?;
+
+    let values = ws.config().tokio_handle().block_on(async {
+        let fetch =
+            entity_values(&provider, world_address, &name, &component.members, partition, keys);
+        run_cancellable(fetch, timeout).await.map_err(|reason| anyhow!("Call {reason}"))?
+    })?;
+
+    if values.is_empty() {
+        if json {
+            println!("null");
+        } else if !porcelain {
+            println!("No value set for this entity");
+        }
+        return Ok(());
+    }
+
+    let members = decode_members(&component.members, &values)?;
+    if porcelain {
+        for (member, value) in members {
+            porcelain::record("member", [member, value]);
+        }
+    } else if json {
+        println!("{}", serde_json::to_string_pretty(&json_members(&component, members))?);
+    } else {
+        let width = members.iter().map(|(member, _)| member.len()).max().unwrap_or(0);
+        for (member, value) in members {
+            println!("{member:width$}  {value}");
+        }
+    }
+
+    Ok(())
+}
+
+fn schema(
+    name: String,
+    json: bool,
+    porcelain: bool,
+    world: Option<FieldElement>,
+    path: Option<Utf8PathBuf>,
+    profile_spec: ProfileSpec,
+) -> Result<()> {
+    dotenv().ok();
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profile = profile_spec.determine()?;
+    let manifest_path = source_dir.join(format!("target/{}/manifest.json", profile.as_str()));
+
+    if !manifest_path.exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+
+    let manifest = Manifest::load_from_path(manifest_path)?;
+    let component = manifest
+        .components
+        .into_iter()
+        .find(|c| c.name == name)
+        .with_context(|| format!("Component `{name}` not found in the manifest"))?;
+
+    if let Some(world_address) = world {
+        let config = Config::builder(source_dir.join("Scarb.toml"))
+            .ui_verbosity(Verbosity::Verbose)
+            .log_filter_directive(env::var_os("SCARB_LOG"))
+            .build()
+            .unwrap();
+        let ws = ops::read_workspace(config.manifest_path(), &config)?;
+        let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+        let provider = env_config.provider()?;
+
+        let registered = ws.config().tokio_handle().block_on(async {
+            provider
+                .call(
+                    FunctionCall {
+                        contract_address: world_address,
+                        entry_point_selector: get_selector_from_name("component")?,
+                        calldata: vec![cairo_short_string_to_felt(&component.name)?],
+                    },
+                    BlockId::Tag(BlockTag::Pending),
+                )
+                .await
+                .map_err(|e| anyhow!("Failed to query the world: {e}"))
+        })?;
+
+        let class_hash = registered.first().copied().unwrap_or_default();
+        if class_hash == FieldElement::ZERO {
+            bail!("Component `{name}` is not registered in the world");
+        }
+        if class_hash != component.class_hash {
+            eprintln!(
+                "warning: the world has class {class_hash:#x} registered for `{name}` but the \
+                 manifest has {:#x}, the layout may be outdated",
+                component.class_hash
+            );
+        }
+    }
+
+    if porcelain {
+        for member in component.members {
+            porcelain::record(
+                "schema",
+                [
+                    member.name,
+                    member.ty,
+                    member.slot.to_string(),
+                    member.offset.to_string(),
+                    porcelain::optional(member.variants.map(|variants| variants.join(","))),
+                ],
+            );
+        }
+    } else if json {
+        println!("{}", serde_json::to_string_pretty(&component)?);
+    } else {
+        print!("{}", format_schema(&component));
+    }
+
+    Ok(())
+}
+
+/// Decoded members as a JSON object, integers up to 64 bits and booleans as JSON values and the
+/// others as strings.
+fn json_members(component: &Component, members: Vec<(String, String)>) -> Value {
+    let types: HashMap<_, _> = component.members.iter().map(|m| (&m.name, &m.ty)).collect();
+
+    let members = members.into_iter().map(|(name, value)| {
+        let value = match types.get(&name).map(|ty| ty.as_str()) {
+            Some("bool") => value.parse().map(Value::Bool).unwrap_or(Value::String(value)),
+            Some("u8" | "u16" | "u32" | "u64") => {
+                value.parse::<u64>().map(Value::from).unwrap_or(Value::String(value))
+            }
+            _ => Value::String(value),
+        };
+        (name, value)
+    });
+
+    Value::Object(members.collect())
+}
+
+fn format_schema(component: &Component) -> String {
+    let name_width = component.members.iter().map(|m| m.name.len()).max().unwrap_or(0).max(4);
+    let ty_width = component.members.iter().map(|m| m.ty.len()).max().unwrap_or(0).max(4);
+
+    let mut out = format!("{} (class hash {:#x})\n", component.name, component.class_hash);
+    out.push_str(&format!("{:name_width$}  {:ty_width$}  SLOT  OFFSET\n", "NAME", "TYPE"));
+    for member in &component.members {
+        out.push_str(&format!(
+            "{:name_width$}  {:ty_width$}  {:<4}  {}\n",
+            member.name, member.ty, member.slot, member.offset
+        ));
+        if let Some(variants) = &member.variants {
+            out.push_str(&format!("{:name_width$}  variants: {}\n", "", variants.join(", ")));
+        }
+    }
+
+    out
+}
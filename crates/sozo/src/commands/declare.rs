@@ -0,0 +1,179 @@
+use std::env::{self, current_dir};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::EnvironmentConfig;
+use dojo_world::migration::object::{
+    class_hash_from_artifact, prepare_contract_declaration_params, ClassMigration, Declarable,
+    MigrationError,
+};
+use dojo_world::migration::strategy::collect_artifact_paths;
+use dojo_world::migration::world::ClassDiff;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::accounts::ConnectedAccount;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::fee::FeeArgs;
+use crate::porcelain;
+use crate::receipts::{History, HistoryEntry, WaitArgs};
+
+#[derive(Args)]
+pub struct DeclareArgs {
+    #[clap(help = "Name of the contract to declare, such as `PositionComponent`, or path to its \
+                   compiled artifact")]
+    contract: String,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    fee: FeeArgs,
+
+    #[command(flatten)]
+    wait: WaitArgs,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: DeclareArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let DeclareArgs { contract, path, fee, wait, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+    let history = History::new(&source_dir, profile.as_str());
+
+    let (name, artifact_path) = resolve_artifact(contract, &source_dir, &profile_spec)?;
+    let class_hash = class_hash_from_artifact(&artifact_path)?;
+    if !porcelain {
+        println!("Class hash: {class_hash:#x}");
+    }
+
+    let class = ClassMigration {
+        class: ClassDiff { name: name.clone(), local: class_hash, remote: None },
+        artifact_path,
+        declared: false,
+    };
+
+    ws.config().tokio_handle().block_on(async {
+        let account = env_config.migrator().await?;
+
+        if fee.needs_estimate() {
+            let (flattened_class, compiled_class_hash) =
+                prepare_contract_declaration_params(&class.artifact_path)?;
+            let flattened_class = Arc::new(flattened_class);
+            if !fee
+                .check_declaration(&account, flattened_class, compiled_class_hash, porcelain)
+                .await?
+            {
+                return Ok(());
+            }
+        }
+
+        let fees = fee.fee_config(&env_config);
+        let retry = env_config.retry_policy();
+        let output = match class.declare_with_nonce(&account, None, &fees, &retry).await {
+            Ok(output) => output,
+            Err(MigrationError::ClassAlreadyDeclared) => {
+                if porcelain {
+                    porcelain::record("declared", [format!("{class_hash:#x}"), String::new()]);
+                } else {
+                    println!("{name} is already declared");
+                }
+                return Ok(());
+            }
+            Err(e) => return Err(anyhow!("Failed to declare {name}: {e}")),
+        };
+        if !porcelain {
+            println!("Transaction hash: {:#x}", output.transaction_hash);
+        }
+
+        let provider = account.provider();
+        wait.receipt(provider, output.transaction_hash, timeout, porcelain).await?;
+
+        let entry = HistoryEntry::from_receipt(
+            provider,
+            "declare",
+            &format!("declare {name}"),
+            output.transaction_hash,
+            vec![],
+        )
+        .await;
+        if porcelain {
+            porcelain::record(
+                "declared",
+                [format!("{class_hash:#x}"), format!("{:#x}", output.transaction_hash)],
+            );
+        }
+        history.append(&[entry])
+    })?;
+
+    Ok(())
+}
+
+/// Resolves a contract given by name or by path to its compiled artifact, returning its name and
+/// artifact path. Artifacts given by path are used as is, names are looked up in the build
+/// output, which is built first if missing.
+pub(crate) fn resolve_artifact(
+    contract: String,
+    source_dir: &Utf8PathBuf,
+    profile_spec: &ProfileSpec,
+) -> Result<(String, PathBuf)> {
+    let artifact_path = PathBuf::from(&contract);
+    if artifact_path.is_file() {
+        let name = artifact_path
+            .file_stem()
+            .map_or(contract.clone(), |stem| stem.to_string_lossy().to_string());
+        return Ok((name, artifact_path));
+    }
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec: profile_spec.clone(),
+        })?;
+    }
+
+    let mut artifact_paths = collect_artifact_paths(target_dir)?;
+    let artifact_path = artifact_paths.remove(&contract).ok_or_else(|| {
+        let mut names = artifact_paths.into_keys().collect::<Vec<_>>();
+        names.sort();
+        anyhow!("No contract named `{contract}`, expected one of: {}", names.join(", "))
+    })?;
+    Ok((contract, artifact_path))
+}
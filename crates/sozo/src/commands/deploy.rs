@@ -0,0 +1,211 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::EnvironmentConfig;
+use dojo_world::migration::object::{
+    class_hash_from_artifact, deploy_call, ClassMigration, Declarable, MigrationError,
+};
+use dojo_world::migration::world::ClassDiff;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::accounts::{Account, ConnectedAccount};
+use starknet::core::types::{BlockId, BlockTag, FieldElement};
+use starknet::core::utils::get_contract_address;
+use starknet::providers::Provider;
+
+use super::build::ProfileSpec;
+use super::declare::resolve_artifact;
+use crate::fee::FeeArgs;
+use crate::porcelain;
+use crate::receipts::{History, HistoryEntry, WaitArgs};
+
+#[derive(Args)]
+pub struct DeployArgs {
+    #[clap(help = "Class hash of the contract to deploy, or the name or artifact path of a \
+                   contract to declare first if needed")]
+    class: String,
+
+    #[clap(short, long, value_delimiter = ',')]
+    #[clap(help = "Comma separated constructor calldata")]
+    calldata: Vec<FieldElement>,
+
+    #[clap(long, help = "Salt of the contract address, random when not set")]
+    salt: Option<FieldElement>,
+
+    #[clap(long)]
+    #[clap(
+        help = "Only print the address the contract would be deployed at, without sending anything"
+    )]
+    predict: bool,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    fee: FeeArgs,
+
+    #[command(flatten)]
+    wait: WaitArgs,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: DeployArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let DeployArgs { class, calldata, salt, predict, path, fee, wait, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    // Class hashes are deployed as is, contracts given by name or path are declared if needed.
+    let (class_hash, class) = match FieldElement::from_hex_be(&class) {
+        Ok(class_hash) if class.starts_with("0x") => (class_hash, None),
+        _ => {
+            let (name, artifact_path) = resolve_artifact(class, &source_dir, &profile_spec)?;
+            let class_hash = class_hash_from_artifact(&artifact_path)?;
+            let class = ClassMigration {
+                class: ClassDiff { name, local: class_hash, remote: None },
+                artifact_path,
+                declared: false,
+            };
+            (class_hash, Some(class))
+        }
+    };
+
+    // Deployed through the UDC as not unique, so the address doesn't depend on the account.
+    let salt = salt.unwrap_or_else(|| FieldElement::from(rand::random::<u64>()));
+    let contract_address = get_contract_address(salt, class_hash, &calldata, FieldElement::ZERO);
+    if !porcelain {
+        println!("Class hash: {class_hash:#x}");
+        println!("Salt: {salt:#x}");
+        println!("Contract address: {contract_address:#x}");
+    }
+    if predict {
+        if porcelain {
+            porcelain::record(
+                "deployed",
+                [format!("{contract_address:#x}"), format!("{class_hash:#x}"), String::new()],
+            );
+        }
+        return Ok(());
+    }
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+    let history = History::new(&source_dir, profile.as_str());
+
+    ws.config().tokio_handle().block_on(async {
+        let account = env_config.migrator().await?;
+        let provider = account.provider();
+        let fees = fee.fee_config(&env_config);
+        let retry = env_config.retry_policy();
+        let mut entries = vec![];
+
+        if let Some(class) = &class {
+            let name = &class.class.name;
+            match class.declare_with_nonce(&account, None, &fees, &retry).await {
+                Ok(output) => {
+                    if !porcelain {
+                        println!("Declared {name} in {:#x}", output.transaction_hash);
+                    }
+                    wait.receipt(provider, output.transaction_hash, timeout, porcelain).await?;
+                    entries.push(
+                        HistoryEntry::from_receipt(
+                            provider,
+                            "deploy",
+                            &format!("declare {name}"),
+                            output.transaction_hash,
+                            vec![],
+                        )
+                        .await,
+                    );
+                }
+                Err(MigrationError::ClassAlreadyDeclared) => {}
+                Err(e) => return Err(anyhow!("Failed to declare {name}: {e}")),
+            }
+        }
+
+        if provider
+            .get_class_hash_at(BlockId::Tag(BlockTag::Pending), contract_address)
+            .await
+            .is_ok()
+        {
+            return Err(anyhow!(
+                "A contract is already deployed at {contract_address:#x}, use another `--salt`"
+            ));
+        }
+
+        let call = || deploy_call(class_hash, salt, &calldata);
+        if fee.needs_estimate() && !fee.check(&account, vec![call()], porcelain).await? {
+            return history.append(&entries);
+        }
+
+        let execution = retry
+            .run(|| fees.execution(provider, account.execute(vec![call()])))
+            .await
+            .map_err(|e| anyhow!("Failed to estimate the fee of the deployment: {e}"))?;
+        fees.simulate_execution(&execution)
+            .await
+            .map_err(|e| anyhow!("Failed to simulate the deployment: {e}"))?;
+        let execution = &execution;
+        let transaction_hash = retry
+            .run(|| execution.send())
+            .await
+            .map_err(|e| anyhow!("Failed to deploy {class_hash:#x}: {e}"))?
+            .transaction_hash;
+        if !porcelain {
+            println!("Transaction hash: {transaction_hash:#x}");
+        }
+
+        wait.receipt(provider, transaction_hash, timeout, porcelain).await?;
+
+        entries.push(
+            HistoryEntry::from_receipt(
+                provider,
+                "deploy",
+                &format!("deploy {class_hash:#x} at {contract_address:#x}"),
+                transaction_hash,
+                calldata,
+            )
+            .await,
+        );
+        if porcelain {
+            porcelain::record(
+                "deployed",
+                [
+                    format!("{contract_address:#x}"),
+                    format!("{class_hash:#x}"),
+                    format!("{transaction_hash:#x}"),
+                ],
+            );
+        }
+        history.append(&entries)
+    })?;
+
+    Ok(())
+}
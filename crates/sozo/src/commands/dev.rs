@@ -0,0 +1,129 @@
+use std::env::current_dir;
+use std::sync::mpsc::{channel, Receiver};
+use std::time::Duration;
+
+use anyhow::Result;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use dojo_world::migration::strategy::MigrationKind;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use super::migrate::{self, MigrateArgs};
+use crate::fee::FeeArgs;
+
+/// Time to wait for the changes to settle before rebuilding, editors often touch several files
+/// when saving.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Args)]
+pub struct DevArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, help = "Only rebuild on changes, without migrating the world")]
+    no_migrate: bool,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: DevArgs, timeout: Option<Duration>) -> Result<()> {
+    let DevArgs { path, no_migrate, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let (sender, receiver) = channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(source_dir.join("src").as_std_path(), RecursiveMode::Recursive)?;
+    watcher.watch(source_dir.join("Scarb.toml").as_std_path(), RecursiveMode::NonRecursive)?;
+
+    loop {
+        rebuild(&source_dir, &profile_spec, no_migrate, timeout);
+
+        println!("\nWatching {source_dir} for changes...");
+        wait_for_change(&receiver)?;
+    }
+}
+
+/// Builds the project and migrates the world. Only the components and systems that differ from
+/// the deployed world are migrated. Failures are reported without stopping the watch.
+fn rebuild(
+    source_dir: &Utf8Path,
+    profile_spec: &ProfileSpec,
+    no_migrate: bool,
+    timeout: Option<Duration>,
+) {
+    let build_args =
+        BuildArgs {
+            path: Some(source_dir.to_path_buf()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec: profile_spec.clone(),
+        };
+    if let Err(e) = build::run(build_args) {
+        eprintln!("error: build failed: {e:?}");
+        return;
+    }
+
+    if no_migrate {
+        return;
+    }
+
+    let migrate_args = MigrateArgs {
+        path: Some(source_dir.to_path_buf()),
+        dry_run: false,
+        json: false,
+        output_calldata: None,
+        strategy: MigrationKind::Incremental,
+        from_manifest: None,
+        restart: false,
+        yes: false,
+        // The project was built above.
+        skip_build: true,
+        artifacts_dir: None,
+        max_calls_per_tx: None,
+        offline: false,
+        // Rebuilt worlds are migrated on every change, estimating each time would slow it down.
+        fee: FeeArgs { skip_balance_check: true, ..Default::default() },
+        profile_spec: profile_spec.clone(),
+    };
+    if let Err(e) = migrate::run(migrate_args, timeout) {
+        eprintln!("error: migration failed: {e:?}");
+    }
+}
+
+/// Blocks until a source file changes, then until no other change happened for
+/// [`DEBOUNCE_DELAY`].
+fn wait_for_change(receiver: &Receiver<notify::Result<Event>>) -> Result<()> {
+    loop {
+        match receiver.recv()? {
+            Ok(event)
+                if matches!(
+                    event.kind,
+                    EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+                ) =>
+            {
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("warning: failed to watch the sources: {e}"),
+        }
+    }
+
+    while receiver.recv_timeout(DEBOUNCE_DELAY).is_ok() {}
+
+    Ok(())
+}
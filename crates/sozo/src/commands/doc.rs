@@ -0,0 +1,290 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::env::current_dir;
+use std::fs;
+
+use anyhow::{Context, Result};
+use camino::Utf8PathBuf;
+use clap::{Args, ValueEnum};
+use dojo_world::manifest::Manifest;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, ValueEnum)]
+pub enum DocFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    fn extension(&self) -> &str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct DocArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, value_enum, default_value_t = DocFormat::Markdown)]
+    #[clap(help = "Format of the documentation")]
+    format: DocFormat,
+
+    #[clap(short, long, value_name = "FILE")]
+    #[clap(help = "File to write the documentation to, `target/<profile>/doc/world.<format>` by \
+                   default")]
+    output: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// Reference documentation of the world, written in one of the [`DocFormat`]s.
+struct DocWriter {
+    format: DocFormat,
+    out: String,
+}
+
+impl DocWriter {
+    fn heading(&mut self, level: usize, anchor: Option<&str>, text: &str) {
+        let text = self.escape(text);
+        match (self.format, anchor) {
+            (DocFormat::Markdown, Some(anchor)) => {
+                self.out.push_str(&format!("<a id=\"{anchor}\"></a>\n\n"));
+                self.out.push_str(&format!("{} {text}\n\n", "#".repeat(level)));
+            }
+            (DocFormat::Markdown, None) => {
+                self.out.push_str(&format!("{} {text}\n\n", "#".repeat(level)))
+            }
+            (DocFormat::Html, Some(anchor)) => {
+                self.out.push_str(&format!("<h{level} id=\"{anchor}\">{text}</h{level}>\n"))
+            }
+            (DocFormat::Html, None) => self.out.push_str(&format!("<h{level}>{text}</h{level}>\n")),
+        }
+    }
+
+    /// A paragraph of text already escaped, made of links and code for instance.
+    fn paragraph(&mut self, text: &str) {
+        match self.format {
+            DocFormat::Markdown => self.out.push_str(&format!("{text}\n\n")),
+            DocFormat::Html => self.out.push_str(&format!("<p>{text}</p>\n")),
+        }
+    }
+
+    /// Doc comments are markdown already, they are kept as is in HTML as well.
+    fn doc(&mut self, doc: Option<&str>) {
+        let Some(doc) = doc else { return };
+        match self.format {
+            DocFormat::Markdown => self.out.push_str(&format!("{doc}\n\n")),
+            DocFormat::Html => {
+                self.out.push_str(&format!("<pre class=\"doc\">{}</pre>\n", self.escape(doc)))
+            }
+        }
+    }
+
+    /// A table whose cells are already escaped.
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        match self.format {
+            DocFormat::Markdown => {
+                self.out.push_str(&format!("| {} |\n", header.join(" | ")));
+                self.out.push_str(&format!("|{}\n", "---|".repeat(header.len())));
+                for row in rows {
+                    let row = row.iter().map(|cell| cell.replace('\n', " ")).collect::<Vec<_>>();
+                    self.out.push_str(&format!("| {} |\n", row.join(" | ")));
+                }
+                self.out.push('\n');
+            }
+            DocFormat::Html => {
+                self.out.push_str("<table>\n<tr>");
+                for cell in header {
+                    self.out.push_str(&format!("<th>{cell}</th>"));
+                }
+                self.out.push_str("</tr>\n");
+                for row in rows {
+                    self.out.push_str("<tr>");
+                    for cell in row {
+                        self.out.push_str(&format!("<td>{cell}</td>"));
+                    }
+                    self.out.push_str("</tr>\n");
+                }
+                self.out.push_str("</table>\n");
+            }
+        }
+    }
+
+    fn link(&self, text: &str, anchor: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => format!("[{}](#{anchor})", self.escape(text)),
+            DocFormat::Html => format!("<a href=\"#{anchor}\">{}</a>", self.escape(text)),
+        }
+    }
+
+    fn code(&self, text: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => format!("`{}`", text.replace('|', "\\|")),
+            DocFormat::Html => format!("<code>{}</code>", self.escape(text)),
+        }
+    }
+
+    fn escape(&self, text: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => text.to_string(),
+            DocFormat::Html => {
+                text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+            }
+        }
+    }
+
+    fn finish(self, title: &str) -> String {
+        match self.format {
+            DocFormat::Markdown => self.out,
+            DocFormat::Html => format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\
+                 \n</head>\n<body>\n{}</body>\n</html>\n",
+                self.out
+            ),
+        }
+    }
+}
+
+fn component_anchor(name: &str) -> String {
+    format!("component-{}", name.to_lowercase())
+}
+
+fn system_anchor(name: &str) -> String {
+    format!("system-{}", name.to_lowercase())
+}
+
+/// Documents the components with their members and the systems with their inputs, each system
+/// linking to the components it writes and each component to the systems writing it.
+pub fn render(manifest: &Manifest, title: &str, format: DocFormat) -> String {
+    let mut doc = DocWriter { format, out: String::new() };
+    let components = manifest.components.iter().map(|c| c.name.as_str()).collect::<BTreeSet<_>>();
+    let system_name = |name: &str| name.strip_suffix("System").unwrap_or(name).to_string();
+
+    let mut writers = BTreeMap::<&str, Vec<String>>::new();
+    for system in &manifest.systems {
+        for dependency in &system.dependencies {
+            writers.entry(dependency.as_str()).or_default().push(system_name(&system.name));
+        }
+    }
+
+    doc.heading(1, None, title);
+
+    doc.heading(2, None, "Components");
+    let mut sorted_components = manifest.components.iter().collect::<Vec<_>>();
+    sorted_components.sort_by(|a, b| a.name.cmp(&b.name));
+    for component in sorted_components {
+        doc.heading(3, Some(&component_anchor(&component.name)), &component.name);
+        doc.doc(component.doc.as_deref());
+
+        let rows = component
+            .members
+            .iter()
+            .map(|member| {
+                // Members can be other components, stored inline.
+                let ty = if components.contains(member.ty.as_str()) {
+                    doc.link(&member.ty, &component_anchor(&member.ty))
+                } else {
+                    doc.code(&member.ty)
+                };
+                let description = doc.escape(member.doc.as_deref().unwrap_or_default());
+                vec![doc.escape(&member.name), ty, description]
+            })
+            .collect::<Vec<_>>();
+        doc.table(&["Member", "Type", "Description"], &rows);
+
+        if let Some(systems) = writers.get(component.name.as_str()) {
+            let links =
+                systems.iter().map(|s| doc.link(s, &system_anchor(s))).collect::<Vec<_>>();
+            doc.paragraph(&format!("Written by: {}", links.join(", ")));
+        }
+        let class_hash = doc.code(&format!("{:#x}", component.class_hash));
+        doc.paragraph(&format!("Class hash: {class_hash}"));
+    }
+
+    doc.heading(2, None, "Systems");
+    let mut sorted_systems = manifest.systems.iter().collect::<Vec<_>>();
+    sorted_systems.sort_by(|a, b| a.name.cmp(&b.name));
+    for system in sorted_systems {
+        let name = system_name(&system.name);
+        doc.heading(3, Some(&system_anchor(&name)), &name);
+        doc.doc(system.doc.as_deref());
+
+        if system.inputs.is_empty() {
+            doc.paragraph("No inputs.");
+        } else {
+            let rows = system
+                .inputs
+                .iter()
+                .map(|input| vec![doc.escape(&input.name), doc.code(&input.ty)])
+                .collect::<Vec<_>>();
+            doc.table(&["Input", "Type"], &rows);
+        }
+        if !system.outputs.is_empty() {
+            let outputs = system.outputs.iter().map(|o| doc.code(&o.ty)).collect::<Vec<_>>();
+            doc.paragraph(&format!("Returns: {}", outputs.join(", ")));
+        }
+        if !system.dependencies.is_empty() {
+            let links = system
+                .dependencies
+                .iter()
+                .map(|c| doc.link(c, &component_anchor(c)))
+                .collect::<Vec<_>>();
+            doc.paragraph(&format!("Writes: {}", links.join(", ")));
+        }
+        let class_hash = doc.code(&format!("{:#x}", system.class_hash));
+        doc.paragraph(&format!("Class hash: {class_hash}"));
+    }
+
+    doc.finish(title)
+}
+
+pub fn run(args: DocArgs) -> Result<()> {
+    let DocArgs { path, format, output, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    build::run(BuildArgs {
+        path: Some(source_dir.clone()),
+        check: false,
+        output_abis: None,
+        offline: false,
+        profile_spec,
+    })?;
+
+    let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+    let title = source_dir.file_name().unwrap_or("World");
+    let content = render(&manifest, title, format);
+
+    let output = match output {
+        Some(output) if output.is_absolute() => output,
+        Some(output) => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap().join(output),
+        None => target_dir.join(format!("doc/world.{}", format.extension())),
+    };
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create the directory {parent}"))?;
+    }
+    fs::write(&output, content).with_context(|| format!("Failed to write {output}"))?;
+    println!("Documentation written to {output}");
+
+    Ok(())
+}
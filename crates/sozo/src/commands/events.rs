@@ -0,0 +1,296 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use serde::Serialize;
+use serde_json::{json, Value};
+use starknet::core::types::{BlockId, BlockTag, EmittedEvent, EventFilter, FieldElement};
+use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
+use starknet::providers::Provider;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use super::call::decode_members;
+use crate::cancellation::run_cancellable;
+use crate::porcelain;
+
+/// Events emitted by the world and its storage.
+pub(crate) const WORLD_EVENTS: &[&str] = &[
+    "WorldSpawned",
+    "ComponentRegistered",
+    "SystemRegistered",
+    "StoreSetRecord",
+    "StoreSetField",
+    "StoreDeleteRecord",
+];
+
+#[derive(Args)]
+pub struct EventsArgs {
+    #[clap(long, help = "First block to fetch events from, defaults to the genesis block")]
+    from_block: Option<u64>,
+
+    #[clap(long, help = "Last block to fetch events from, defaults to the pending block")]
+    to_block: Option<u64>,
+
+    #[clap(long, value_delimiter = ',')]
+    #[clap(help = "Comma separated names of the events to fetch, defaults to all world events")]
+    events: Vec<String>,
+
+    #[clap(long, default_value_t = 100, help = "Number of events fetched per request")]
+    chunk_size: u64,
+
+    #[clap(long, help = "Output the events as JSON lines")]
+    json: bool,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+#[derive(Serialize)]
+pub(crate) struct DecodedEvent {
+    pub(crate) block_number: u64,
+    #[serde(serialize_with = "serialize_hex")]
+    pub(crate) transaction_hash: FieldElement,
+    pub(crate) name: String,
+    pub(crate) fields: Value,
+}
+
+fn serialize_hex<S: serde::Serializer>(value: &FieldElement, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&format!("{value:#x}"))
+}
+
+pub fn run(args: EventsArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let EventsArgs { from_block, to_block, events, chunk_size, json, world, path, profile_spec } =
+        args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+    let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+    let provider = env_config.provider()?;
+
+    let names = if events.is_empty() {
+        WORLD_EVENTS.iter().map(|name| name.to_string()).collect()
+    } else {
+        events
+    };
+    let selectors =
+        names.iter().map(|name| get_selector_from_name(name)).collect::<Result<Vec<_>, _>>()?;
+
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(from_block.unwrap_or_default())),
+        to_block: Some(to_block.map_or(BlockId::Tag(BlockTag::Pending), BlockId::Number)),
+        address: Some(world_address),
+        keys: Some(vec![selectors]),
+    };
+
+    let fetch = async {
+        let mut continuation_token = None;
+        loop {
+            let page = provider
+                .get_events(filter.clone(), continuation_token, chunk_size)
+                .await
+                .map_err(|e| anyhow!("Failed to fetch events: {e}"))?;
+
+            for event in page.events {
+                let decoded = decode_event(&manifest, &names, event);
+                if porcelain {
+                    print_porcelain(decoded);
+                } else if json {
+                    println!("{}", serde_json::to_string(&decoded)?);
+                } else {
+                    println!(
+                        "{:<8} {:#x} {:<20} {}",
+                        decoded.block_number,
+                        decoded.transaction_hash,
+                        decoded.name,
+                        decoded.fields
+                    );
+                }
+            }
+
+            continuation_token = page.continuation_token;
+            if continuation_token.is_none() {
+                break;
+            }
+        }
+
+        Ok::<_, anyhow::Error>(())
+    };
+
+    ws.config().tokio_handle().block_on(async {
+        run_cancellable(fetch, timeout)
+            .await
+            .map_err(|reason| anyhow!("Fetching events {reason}"))?
+    })
+}
+
+pub(crate) fn print_porcelain(decoded: DecodedEvent) {
+    let mut fields = vec![
+        decoded.block_number.to_string(),
+        format!("{:#x}", decoded.transaction_hash),
+        decoded.name,
+    ];
+    fields.extend(porcelain_fields(&decoded.fields));
+    porcelain::record("event", fields);
+}
+
+/// Decodes an event emitted by the world, falling back to its raw data when it doesn't have the
+/// expected layout.
+pub(crate) fn decode_event(
+    manifest: &Manifest,
+    names: &[String],
+    event: EmittedEvent,
+) -> DecodedEvent {
+    let name = event
+        .keys
+        .first()
+        .and_then(|key| names.iter().find(|name| get_selector_from_name(name).ok() == Some(*key)))
+        .cloned()
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let fields = decode_fields(manifest, &name, &event.data).unwrap_or_else(
+        || json!({ "data": event.data.iter().map(|d| format!("{d:#x}")).collect::<Vec<_>>() }),
+    );
+
+    DecodedEvent {
+        block_number: event.block_number,
+        transaction_hash: event.transaction_hash,
+        name,
+        fields,
+    }
+}
+
+fn decode_fields(manifest: &Manifest, name: &str, data: &[FieldElement]) -> Option<Value> {
+    let short_string = |value: &FieldElement| {
+        parse_cairo_short_string(value).unwrap_or_else(|_| format!("{value:#x}"))
+    };
+    let hex = |value: &FieldElement| format!("{value:#x}");
+
+    match name {
+        "WorldSpawned" => Some(json!({
+            "address": hex(data.first()?),
+            "caller": hex(data.get(1)?),
+            "name": short_string(data.get(2)?),
+        })),
+        "ComponentRegistered" | "SystemRegistered" => Some(json!({
+            "name": short_string(data.first()?),
+            "class_hash": hex(data.get(1)?),
+        })),
+        "StoreSetRecord" | "StoreSetField" | "StoreDeleteRecord" => {
+            let component = short_string(data.first()?);
+            let (keys, rest) = span(&data[1..])?;
+
+            let mut fields = json!({
+                "component": component,
+                "keys": keys.iter().map(hex).collect::<Vec<_>>(),
+            });
+
+            match name {
+                "StoreSetRecord" => {
+                    let (values, _) = span(rest)?;
+                    fields["values"] = decode_values(manifest, &component, values);
+                }
+                "StoreSetField" => {
+                    let offset = rest.first()?;
+                    let (values, _) = span(&rest[1..])?;
+                    fields["offset"] = json!(offset.to_string());
+                    fields["values"] = json!(values.iter().map(hex).collect::<Vec<_>>());
+                }
+                _ => {}
+            }
+
+            Some(fields)
+        }
+        _ => None,
+    }
+}
+
+/// Decodes the values of a whole record using the component's members from the manifest.
+fn decode_values(manifest: &Manifest, component: &str, values: &[FieldElement]) -> Value {
+    let decoded = manifest
+        .components
+        .iter()
+        .find(|c| c.name == component)
+        .and_then(|c| decode_members(&c.members, values).ok());
+
+    match decoded {
+        Some(members) => {
+            Value::Object(members.into_iter().map(|(name, value)| (name, json!(value))).collect())
+        }
+        None => json!(values.iter().map(|v| format!("{v:#x}")).collect::<Vec<_>>()),
+    }
+}
+
+/// Flattens the decoded fields to `key=value` pairs, nested values are kept as compact JSON.
+pub(crate) fn porcelain_fields(fields: &Value) -> Vec<String> {
+    let Value::Object(fields) = fields else {
+        return vec![];
+    };
+
+    fields
+        .iter()
+        .map(|(key, value)| match value {
+            Value::String(value) => format!("{key}={value}"),
+            value => format!("{key}={value}"),
+        })
+        .collect()
+}
+
+/// Splits a length prefixed span off the front of the data.
+fn span(data: &[FieldElement]) -> Option<(&[FieldElement], &[FieldElement])> {
+    let len: usize = data.first()?.to_string().parse().ok()?;
+    let rest = data.get(1..)?;
+    (rest.len() >= len).then(|| rest.split_at(len))
+}
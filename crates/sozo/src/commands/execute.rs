@@ -0,0 +1,220 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::object::WorldContract;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use serde_json::Value;
+use starknet::accounts::ConnectedAccount;
+use starknet::core::types::{FieldElement, MaybePendingTransactionReceipt, TransactionReceipt};
+use starknet::core::utils::cairo_short_string_to_felt;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::fee::FeeArgs;
+use crate::porcelain;
+use crate::receipts::{status_name, History, HistoryEntry, WaitArgs};
+use crate::script::{self, encode_call, Script};
+
+#[derive(Args)]
+pub struct ExecuteArgs {
+    #[clap(required_unless_present = "script")]
+    #[clap(help = "Name of the system to execute, without the `System` suffix")]
+    system: Option<String>,
+
+    #[clap(short, long, value_delimiter = ',')]
+    #[clap(help = "Comma separated calldata of the system, the world address is appended by the \
+                   executor")]
+    calldata: Vec<FieldElement>,
+
+    #[clap(long, value_name = "JSON", conflicts_with = "calldata")]
+    #[clap(help = "Arguments of the system as a JSON object by input name, or a JSON list in \
+                   order, encoded from the inputs of the system in the manifest, e.g. \
+                   '{\"x\": 10, \"name\": \"alice\"}'")]
+    args_json: Option<String>,
+
+    #[clap(long, value_name = "FILE", conflicts_with_all = ["system", "calldata", "args_json"])]
+    #[clap(help = "Execute the calls of a TOML or JSON script in order, their arguments being \
+                   encoded from the inputs of the systems, in a single multicall transaction")]
+    script: Option<Utf8PathBuf>,
+
+    #[clap(long, requires = "script")]
+    #[clap(help = "Send a transaction per call of the script instead of a single multicall, each \
+                   one once the previous has a receipt, or is accepted on L2 with `--wait`")]
+    sequential: bool,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    fee: FeeArgs,
+
+    #[command(flatten)]
+    wait: WaitArgs,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: ExecuteArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let ExecuteArgs {
+        system,
+        calldata,
+        args_json,
+        script,
+        sequential,
+        world,
+        path,
+        fee,
+        wait,
+        profile_spec,
+    } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+
+    // The manifest describing the inputs of the systems, built if needed.
+    let load_manifest = || -> Result<Manifest> {
+        let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+        if !target_dir.join("manifest.json").exists() {
+            build::run(BuildArgs {
+                path: Some(source_dir.clone()),
+                check: false,
+                output_abis: None,
+                offline: false,
+                profile_spec,
+            })?;
+        }
+        Manifest::load_from_path(target_dir.join("manifest.json"))
+    };
+
+    let executions = match (script, args_json) {
+        (Some(script), _) => Script::load_from_path(&script)?.encode(&load_manifest()?)?,
+        (None, Some(args)) => {
+            let system = system.expect("the system is required without a script");
+            let args: Value = serde_json::from_str(&args)
+                .map_err(|e| anyhow!("Invalid `--args-json`: {e}"))?;
+            vec![encode_call(&load_manifest()?, &system, Some(&args))?]
+        }
+        (None, None) => {
+            let system = system.expect("the system is required without a script");
+            vec![(system.strip_suffix("System").unwrap_or(&system).to_string(), calldata)]
+        }
+    };
+
+    let history = History::new(&source_dir, profile.as_str());
+
+    ws.config().tokio_handle().block_on(async {
+        let account = env_config.migrator().await?;
+        let world = WorldContract::new(world_address, &account)
+            .with_fees(fee.fee_config(&env_config))
+            .with_retry(env_config.retry_policy());
+
+        let mut calls = vec![];
+        for (system, calldata) in &executions {
+            calls.push(world.execute_call(cairo_short_string_to_felt(system)?, calldata.clone()));
+        }
+
+        // The calls of a sequential script are estimated together, as they depend on each other.
+        if fee.needs_estimate() && !fee.check(&account, calls.clone(), porcelain).await? {
+            return Ok(());
+        }
+
+        let batches = script::batches(executions.iter().zip(calls).collect(), sequential);
+        let provider = account.provider();
+        let (world, wait, history) = (&world, &wait, &history);
+        script::send_in_order(
+            batches,
+            move |batch| async move {
+                let systems = batch_systems(&batch);
+                let calls = batch.into_iter().map(|(_, call)| call).collect();
+                let transaction_hash = world
+                    .send_calls(calls)
+                    .await
+                    .map_err(|e| anyhow!("Failed to execute {systems}: {e}"))?
+                    .transaction_hash;
+                if !porcelain {
+                    println!("Transaction hash: {transaction_hash:#x}");
+                }
+                Ok(transaction_hash)
+            },
+            move |batch, transaction_hash| async move {
+                let receipt = wait.receipt(provider, transaction_hash, timeout, porcelain).await?;
+
+                if !porcelain {
+                    print_receipt(&receipt);
+                }
+
+                let entry = HistoryEntry::from_receipt(
+                    provider,
+                    "execute",
+                    &format!("execute {}", batch_systems(&batch)),
+                    transaction_hash,
+                    batch.iter().flat_map(|((_, calldata), _)| calldata.clone()).collect(),
+                )
+                .await;
+                if porcelain {
+                    porcelain::transaction(&entry);
+                }
+                history.append(&[entry])
+            },
+        )
+        .await?;
+
+        Ok(())
+    })
+}
+
+/// The systems executed by a batch of calls, separated by commas.
+fn batch_systems<C>(batch: &[(&(String, Vec<FieldElement>), C)]) -> String {
+    batch.iter().map(|((system, _), _)| system.as_str()).collect::<Vec<_>>().join(", ")
+}
+
+fn print_receipt(receipt: &MaybePendingTransactionReceipt) {
+    match receipt {
+        MaybePendingTransactionReceipt::Receipt(TransactionReceipt::Invoke(receipt)) => {
+            println!("Status: {}", status_name(receipt.status));
+            println!("Actual fee: {:#x}", receipt.actual_fee);
+            println!("Events emitted: {}", receipt.events.len());
+        }
+        MaybePendingTransactionReceipt::Receipt(_) => {}
+        MaybePendingTransactionReceipt::PendingReceipt(_) => println!("Status: PENDING"),
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::object::WorldContract;
+use dotenv::dotenv;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::accounts::Account;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::{cairo_short_string_to_felt, parse_cairo_short_string};
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::cancellation::run_cancellable;
+use crate::porcelain;
+
+/// Longest array generated for `Array` and `Span` inputs.
+const MAX_ARRAY_LEN: usize = 4;
+
+#[derive(Args)]
+pub struct FuzzArgs {
+    #[clap(help = "Name of the system to fuzz, without the `System` suffix")]
+    system: String,
+
+    #[clap(long, default_value_t = 100, help = "Number of calls to simulate")]
+    runs: usize,
+
+    #[clap(long, help = "Seed of the generated calldata, to reproduce a previous run")]
+    seed: Option<u64>,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// How an input of a system is serialized, derived from its type in the manifest.
+#[derive(Debug, Clone, PartialEq)]
+enum InputKind {
+    Felt,
+    Bool,
+    Uint(u32),
+    U256,
+    /// An enum with this many variants, serialized as the variant's index.
+    Enum(usize),
+    Array(Box<InputKind>),
+}
+
+impl InputKind {
+    fn from_type(ty: &str, enums: &HashMap<String, usize>) -> Self {
+        if let Some(inner) = ty
+            .strip_prefix("core::array::Array::<")
+            .or_else(|| ty.strip_prefix("core::array::Span::<"))
+            .and_then(|inner| inner.strip_suffix('>'))
+        {
+            return Self::Array(Box::new(Self::from_type(inner, enums)));
+        }
+
+        let name = ty.rsplit("::").next().unwrap_or(ty);
+        match name {
+            "bool" => Self::Bool,
+            "u8" => Self::Uint(8),
+            "u16" => Self::Uint(16),
+            "u32" => Self::Uint(32),
+            "u64" => Self::Uint(64),
+            "u128" => Self::Uint(128),
+            "u256" => Self::U256,
+            _ => match enums.get(name) {
+                Some(variants) if *variants > 0 => Self::Enum(*variants),
+                _ => Self::Felt,
+            },
+        }
+    }
+
+    /// Appends a random value to `calldata`, biased towards the edge cases of the type.
+    fn generate(&self, rng: &mut StdRng, calldata: &mut Vec<FieldElement>) {
+        match self {
+            Self::Felt => calldata.push(match rng.gen_range(0..4) {
+                0 => FieldElement::ZERO,
+                1 => FieldElement::ONE,
+                2 => FieldElement::MAX,
+                _ => FieldElement::from(rng.gen::<u128>()),
+            }),
+            Self::Bool => calldata.push(FieldElement::from(rng.gen_range(0u8..2))),
+            Self::Uint(bits) => calldata.push(FieldElement::from(random_uint(rng, *bits))),
+            Self::U256 => {
+                calldata.push(FieldElement::from(random_uint(rng, 128)));
+                calldata.push(FieldElement::from(random_uint(rng, 128)));
+            }
+            Self::Enum(variants) => calldata.push(FieldElement::from(rng.gen_range(0..*variants))),
+            Self::Array(inner) => {
+                let len = rng.gen_range(0..=MAX_ARRAY_LEN);
+                calldata.push(FieldElement::from(len));
+                for _ in 0..len {
+                    inner.generate(rng, calldata);
+                }
+            }
+        }
+    }
+}
+
+pub(crate) fn random_uint(rng: &mut StdRng, bits: u32) -> u128 {
+    let max = if bits == 128 { u128::MAX } else { (1 << bits) - 1 };
+    match rng.gen_range(0..4) {
+        0 => 0,
+        1 => 1,
+        2 => max,
+        _ => rng.gen_range(0..=max),
+    }
+}
+
+/// Failures sharing a reason, with the calldata of the first one to reproduce it.
+struct FailureGroup {
+    count: usize,
+    calldata: Vec<FieldElement>,
+}
+
+/// Makes the errors of different calls comparable: felts that are short strings, usually the
+/// panic reason, are decoded and the others, like addresses and hashes, are elided.
+fn failure_reason(error: &str) -> String {
+    let mut reason = String::new();
+    let mut rest = error;
+    while let Some(start) = rest.find("0x") {
+        reason.push_str(&rest[..start]);
+        let hex = &rest[start..];
+        let end = hex[2..].find(|c: char| !c.is_ascii_hexdigit()).map_or(hex.len(), |i| i + 2);
+
+        let decoded = FieldElement::from_hex_be(&hex[..end])
+            .ok()
+            .and_then(|felt| parse_cairo_short_string(&felt).ok())
+            .filter(|s| s.len() > 1 && s.chars().all(|c| c.is_ascii_graphic() || c == ' '));
+        match decoded {
+            Some(decoded) => reason.push_str(&format!("'{decoded}'")),
+            None => reason.push_str("0x…"),
+        }
+        rest = &hex[end..];
+    }
+    reason.push_str(rest);
+    reason
+}
+
+pub fn run(args: FuzzArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let FuzzArgs { system, runs, seed, world, path, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+
+    let system = system.strip_suffix("System").unwrap_or(&system).to_string();
+    let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+    let inputs = &manifest
+        .systems
+        .iter()
+        .find(|s| s.name.strip_suffix("System").unwrap_or(&s.name) == system)
+        .with_context(|| format!("System `{system}` not found in the manifest"))?
+        .inputs;
+
+    // Enums are only described by the members of the components.
+    let enums = manifest
+        .components
+        .iter()
+        .flat_map(|c| &c.members)
+        .filter_map(|m| {
+            let variants = m.variants.as_ref()?;
+            Some((m.ty.rsplit("::").next().unwrap_or(&m.ty).to_string(), variants.len()))
+        })
+        .collect::<HashMap<_, _>>();
+    let kinds = inputs.iter().map(|i| InputKind::from_type(&i.ty, &enums)).collect::<Vec<_>>();
+
+    let seed = seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let system_name = cairo_short_string_to_felt(&system)?;
+
+    let mut failures = HashMap::<String, FailureGroup>::new();
+    let fuzz = async {
+        let account = env_config.migrator().await?;
+        let world = WorldContract::new(world_address, &account);
+
+        for _ in 0..runs {
+            let mut calldata = vec![];
+            for kind in &kinds {
+                kind.generate(&mut rng, &mut calldata);
+            }
+
+            // The fee estimation simulates the call without sending it.
+            let call = world.execute_call(system_name, calldata.clone());
+            if let Err(e) = account.execute(vec![call]).estimate_fee().await {
+                let group = failures
+                    .entry(failure_reason(&e.to_string()))
+                    .or_insert(FailureGroup { count: 0, calldata });
+                group.count += 1;
+            }
+        }
+
+        Ok::<_, anyhow::Error>(())
+    };
+
+    ws.config().tokio_handle().block_on(async {
+        run_cancellable(fuzz, timeout).await.map_err(|reason| anyhow!("Fuzzing {reason}"))?
+    })?;
+
+    let mut failures = failures.into_iter().collect::<Vec<_>>();
+    failures.sort_by(|(a, a_group), (b, b_group)| b_group.count.cmp(&a_group.count).then(a.cmp(b)));
+
+    if porcelain {
+        for (reason, group) in &failures {
+            let calldata =
+                group.calldata.iter().map(|c| format!("{c:#x}")).collect::<Vec<_>>().join(",");
+            porcelain::record("failure", [group.count.to_string(), reason.clone(), calldata]);
+        }
+        return Ok(());
+    }
+
+    let failed = failures.iter().map(|(_, group)| group.count).sum::<usize>();
+    println!("{runs} calls to {system} simulated with seed {seed}, {failed} failed");
+    for (reason, group) in &failures {
+        let calldata =
+            group.calldata.iter().map(|c| format!("{c:#x}")).collect::<Vec<_>>().join(",");
+        println!("\n{}x {reason}", group.count);
+        if calldata.is_empty() {
+            println!("    sozo execute {system}");
+        } else {
+            println!("    sozo execute {system} --calldata {calldata}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,143 @@
+use std::collections::BTreeSet;
+use std::env::current_dir;
+
+use anyhow::Result;
+use camino::Utf8PathBuf;
+use clap::{Args, ValueEnum};
+use dojo_world::manifest::Manifest;
+use serde::Serialize;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+
+#[cfg(test)]
+#[path = "graph_test.rs"]
+mod test;
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    Json,
+}
+
+#[derive(Args)]
+pub struct GraphArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, value_enum, default_value_t = GraphFormat::Dot)]
+    #[clap(help = "Output format of the dependency graph")]
+    format: GraphFormat,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// Component dependencies of the world's systems, as collected by the compiler plugin.
+#[derive(Debug, Default, Serialize)]
+pub struct DependencyGraph {
+    pub components: Vec<String>,
+    pub systems: Vec<SystemNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SystemNode {
+    pub name: String,
+    /// Components written by the system. These are the components the system needs to be
+    /// authorized for.
+    pub writes: Vec<String>,
+    /// Components read by the system.
+    pub reads: Vec<String>,
+}
+
+impl DependencyGraph {
+    pub fn from_manifest(manifest: &Manifest) -> Self {
+        let mut components: BTreeSet<String> =
+            manifest.components.iter().map(|c| c.name.clone()).collect();
+
+        let mut systems = manifest
+            .systems
+            .iter()
+            .map(|s| {
+                let writes: BTreeSet<String> = s.dependencies.iter().cloned().collect();
+                let reads: BTreeSet<String> = s.reads.iter().cloned().collect();
+                components.extend(writes.iter().chain(&reads).cloned());
+                SystemNode {
+                    name: s.name.to_string(),
+                    writes: writes.into_iter().collect(),
+                    reads: reads.into_iter().collect(),
+                }
+            })
+            .collect::<Vec<_>>();
+        systems.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Self { components: components.into_iter().collect(), systems }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph world {\n    rankdir=LR;\n");
+
+        for component in &self.components {
+            out.push_str(&format!("    \"{component}\" [shape=box];\n"));
+        }
+
+        for system in &self.systems {
+            out.push_str(&format!("    \"{}\" [shape=ellipse];\n", system.name));
+            for component in &system.writes {
+                out.push_str(&format!(
+                    "    \"{}\" -> \"{component}\" [label=\"writes\"];\n",
+                    system.name
+                ));
+            }
+            for component in &system.reads {
+                out.push_str(&format!(
+                    "    \"{}\" -> \"{component}\" [label=\"reads\", style=dashed];\n",
+                    system.name
+                ));
+            }
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+pub fn run(args: GraphArgs) -> Result<()> {
+    let GraphArgs { path, format, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profile = profile_spec.determine()?;
+    let manifest_path = source_dir.join(format!("target/{}/manifest.json", profile.as_str()));
+
+    if !manifest_path.exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+
+    let manifest = Manifest::load_from_path(manifest_path)?;
+    let graph = DependencyGraph::from_manifest(&manifest);
+
+    match format {
+        GraphFormat::Dot => print!("{}", graph.to_dot()),
+        GraphFormat::Json => println!("{}", serde_json::to_string_pretty(&graph)?),
+    }
+
+    Ok(())
+}
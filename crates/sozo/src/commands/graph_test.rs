@@ -0,0 +1,40 @@
+use dojo_world::manifest::{Component, Manifest, System};
+
+use super::DependencyGraph;
+
+fn manifest() -> Manifest {
+    Manifest {
+        components: vec![Component { name: "Position".into(), ..Default::default() }],
+        systems: vec![
+            System {
+                name: "MoveSystem".into(),
+                dependencies: vec!["Position".into()],
+                reads: vec!["Moves".into(), "Position".into()],
+                ..Default::default()
+            },
+            System {
+                name: "ViewSystem".into(),
+                reads: vec!["Position".into(), "Position".into()],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_system_reads() {
+    let graph = DependencyGraph::from_manifest(&manifest());
+
+    assert_eq!(graph.components, ["Moves", "Position"]);
+    let view = &graph.systems[1];
+    assert_eq!(view.name, "ViewSystem");
+    assert!(view.writes.is_empty());
+    assert_eq!(view.reads, ["Position"]);
+
+    let dot = graph.to_dot();
+    assert!(dot.contains("\"MoveSystem\" -> \"Position\" [label=\"writes\"];"));
+    assert!(dot.contains("\"MoveSystem\" -> \"Moves\" [label=\"reads\", style=dashed];"));
+    assert!(dot.contains("\"ViewSystem\" -> \"Position\" [label=\"reads\", style=dashed];"));
+    assert!(!dot.contains("\"ViewSystem\" -> \"Position\" [label=\"writes\"]"));
+}
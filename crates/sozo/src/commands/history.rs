@@ -0,0 +1,130 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use dojo_world::config::EnvironmentConfig;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+
+use super::build::ProfileSpec;
+use crate::cancellation::run_cancellable;
+use crate::porcelain;
+use crate::receipts::{History, HistoryEntry};
+
+#[derive(Args)]
+pub struct HistoryArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(short, long, help = "Only show the last N transactions")]
+    limit: Option<usize>,
+
+    #[clap(long, help = "Only show transactions sent by this command")]
+    command: Option<String>,
+
+    #[clap(long, help = "Output the transactions as JSON lines")]
+    json: bool,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: HistoryArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let HistoryArgs { path, limit, command, json, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profile = profile_spec.determine()?;
+    let mut entries = History::new(&source_dir, profile.as_str()).entries()?;
+
+    if let Some(command) = command {
+        entries.retain(|entry| entry.command == command);
+    }
+    if let Some(limit) = limit {
+        entries.drain(..entries.len().saturating_sub(limit));
+    }
+    if entries.iter().any(HistoryEntry::is_unresolved) {
+        if let Err(e) = resolve(&source_dir, profile.as_str(), &mut entries, timeout) {
+            eprintln!("warning: failed to look up the status of the transactions: {e}");
+        }
+    }
+
+    for entry in entries {
+        if porcelain {
+            porcelain::record(
+                "history",
+                [
+                    entry.timestamp.to_string(),
+                    entry.command,
+                    format!("{:#x}", entry.transaction_hash),
+                    porcelain::optional(entry.status),
+                    porcelain::optional(entry.actual_fee.map(|fee| format!("{fee:#x}"))),
+                    entry.description,
+                    porcelain::optional(entry.sender.map(|sender| format!("{sender:#x}"))),
+                ],
+            );
+            continue;
+        }
+
+        if json {
+            println!("{}", serde_json::to_string(&entry)?);
+            continue;
+        }
+
+        let fee = entry.actual_fee.map(|fee| format!("{fee:#x}")).unwrap_or("-".to_string());
+        println!(
+            "{} {:<8} {:#x} {:<16} fee: {:<10} {}",
+            entry.timestamp,
+            entry.command,
+            entry.transaction_hash,
+            entry.status.as_deref().unwrap_or("UNKNOWN"),
+            fee,
+            entry.description
+        );
+    }
+
+    Ok(())
+}
+
+/// Looks up the status and fee of the transactions that weren't final when they were recorded,
+/// through the RPC endpoint of the profile's environment.
+fn resolve(
+    source_dir: &Utf8Path,
+    profile: &str,
+    entries: &mut [HistoryEntry],
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let config = Config::builder(source_dir.join("Scarb.toml"))
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()?;
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+    let provider = EnvironmentConfig::from_workspace(profile, &ws)?.provider()?;
+
+    let resolution = async {
+        for entry in entries.iter_mut().filter(|entry| entry.is_unresolved()) {
+            entry.resolve(&provider).await;
+        }
+    };
+    ws.config()
+        .tokio_handle()
+        .block_on(run_cancellable(resolution, timeout))
+        .map_err(|reason| anyhow!("Looking up the transactions {reason}"))
+}
@@ -0,0 +1,115 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::EnvironmentConfig;
+use dojo_world::migration::deployment::DeploymentManifest;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::core::types::FieldElement;
+use url::Url;
+
+use super::build::ProfileSpec;
+use crate::cancellation::run_cancellable;
+
+const DEPLOYMENT_FILE: &str = "manifest.json";
+
+#[derive(Args)]
+pub struct ImportArgs {
+    #[clap(help = "Address of the deployed world to adopt")]
+    world: FieldElement,
+
+    #[clap(long, help = "RPC endpoint of the chain, defaults to the `rpc_url` of the environment")]
+    rpc_url: Option<Url>,
+
+    #[clap(long, default_value_t = 0)]
+    #[clap(help = "First block to read the world's history from, the world's executor is only \
+                   found if it was deployed after it")]
+    from_block: u64,
+
+    #[clap(long, help = "Replace the deployment manifest of the profile if it exists")]
+    force: bool,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// Writes the deployment manifest of a world deployed by someone else, read from the chain, so
+/// that it can be upgraded, authorized and indexed like a world migrated from this project.
+pub fn run(args: ImportArgs, timeout: Option<Duration>) -> Result<()> {
+    dotenv().ok();
+
+    let ImportArgs { world, rpc_url, from_block, force, path, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let deployment_path =
+        source_dir.join(format!("deployments/{}/{DEPLOYMENT_FILE}", profile.as_str()));
+    if deployment_path.exists() && !force {
+        bail!("{deployment_path} already exists, pass `--force` to replace it");
+    }
+
+    let mut env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+    if rpc_url.is_some() {
+        env_config.rpc = rpc_url;
+    }
+    let provider = env_config.provider()?;
+
+    let deployment = ws.config().tokio_handle().block_on(async {
+        run_cancellable(DeploymentManifest::from_remote(&provider, world, from_block), timeout)
+            .await
+            .map_err(|reason| anyhow!("Importing the world {reason}"))?
+    })?;
+
+    println!("World {world:#x}");
+    for (kind, classes) in [("component", &deployment.components), ("system", &deployment.systems)]
+    {
+        for class in classes {
+            println!("  {kind:<10} {:<24} {:#x}", class.name, class.class_hash);
+        }
+    }
+    for contract in [&deployment.world, &deployment.executor] {
+        if contract.transaction_hash.is_none() {
+            eprintln!(
+                "warning: the deployment of the {} wasn't found from block {from_block}, its salt \
+                 is unknown",
+                contract.name.to_lowercase()
+            );
+        }
+    }
+
+    deployment.write_to_path(&deployment_path)?;
+    println!(
+        "\nDeployment manifest written to {deployment_path}. Set `world_address = \"{world:#x}\"` \
+         in Scarb.toml to manage the world from this project."
+    );
+
+    Ok(())
+}
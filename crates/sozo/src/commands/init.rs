@@ -1,22 +1,51 @@
 use std::env::{current_dir, set_current_dir};
 use std::error::Error;
-use std::path::PathBuf;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::{fs, io};
 
 use clap::Args;
+use dojo_signers::devnet::default_dev_accounts;
+use dojo_world::config::{update_toml_table, KeyUpdate};
+
+const KATANA_RPC_URL: &str = "http://localhost:5050/";
+const DEFAULT_TEMPLATE: &str = "dojoengine/dojo-starter";
 
 #[derive(Args, Debug)]
 pub struct InitArgs {
-    #[clap(help = "Target directory")]
+    #[clap(help = "Target directory, the project is set up interactively when omitted along \
+                   with the template")]
     path: Option<PathBuf>,
 
-    #[clap(help = "Parse a full git url or a url path", default_value = "dojoengine/dojo-starter")]
+    #[clap(help = "Parse a full git url or a url path, defaults to `dojoengine/dojo-starter`")]
+    template: Option<String>,
+}
+
+/// Answers to the questions asked by `sozo init` when run without arguments.
+struct Setup {
+    name: String,
     template: String,
+    network: Network,
+    examples: bool,
+}
+
+/// Network the `[tool.dojo.env]` of the new project targets.
+enum Network {
+    /// A local `katana`, with its first predeployed account.
+    Katana,
+    Remote { rpc_url: String, account_address: String, keystore_path: Option<String> },
 }
 
 pub fn run(args: InitArgs) -> Result<(), Box<dyn Error>> {
-    let target_dir = match args.path {
+    let setup = if args.path.is_none() && args.template.is_none() { Some(ask()?) } else { None };
+    let path = setup.as_ref().map(|setup| PathBuf::from(&setup.name)).or(args.path);
+    let template = match &setup {
+        Some(setup) => setup.template.clone(),
+        None => args.template.unwrap_or_else(|| DEFAULT_TEMPLATE.to_string()),
+    };
+
+    let target_dir = match path {
         Some(path) => {
             if path.is_absolute() {
                 path
@@ -32,7 +61,6 @@ pub fn run(args: InitArgs) -> Result<(), Box<dyn Error>> {
 
     println!("Setting up project directory tree...");
 
-    let template = args.template;
     let repo_url = if template.starts_with("https://") {
         template
     } else {
@@ -43,6 +71,19 @@ pub fn run(args: InitArgs) -> Result<(), Box<dyn Error>> {
 
     println!("✅ Project directory tree created successfully!");
 
+    let manifest_path = target_dir.join("Scarb.toml");
+    match &setup {
+        Some(setup) => {
+            apply(setup, &target_dir, &manifest_path)?;
+            println!("✅ Scarb.toml configured for `{}`", setup.name);
+        }
+        None => {
+            if write_katana_env(&manifest_path)? {
+                println!("✅ Environment configured with the first account of `katana`");
+            }
+        }
+    }
+
     // Navigate to the newly cloned repo.
     let initial_dir = current_dir()?;
     set_current_dir(&target_dir)?;
@@ -70,6 +111,144 @@ pub fn run(args: InitArgs) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+/// Asks for the project's name, template, network and whether to keep the template's examples.
+/// Empty answers, or the end of the input, pick the defaults.
+fn ask() -> io::Result<Setup> {
+    println!("Setting up a new Dojo project, press enter to pick the default in brackets.\n");
+
+    let name = loop {
+        let name = prompt("Project name", "dojo_starter")?;
+        if is_package_name(&name) {
+            break name;
+        }
+        println!("Use lowercase letters, digits and underscores, starting with a letter.");
+    };
+
+    let template = prompt("Template, as a git url or a GitHub `owner/repo`", DEFAULT_TEMPLATE)?;
+
+    let network = match prompt("Network, `katana` or the url of an RPC endpoint", "katana")? {
+        network if network == "katana" => Network::Katana,
+        rpc_url => Network::Remote {
+            rpc_url,
+            account_address: prompt("Account address", "")?,
+            keystore_path: Some(prompt("Path of the account's keystore, if any", "")?)
+                .filter(|path| !path.is_empty()),
+        },
+    };
+
+    let examples = loop {
+        match prompt("Keep the example components and systems? (y/n)", "y")?.as_str() {
+            "y" | "yes" => break true,
+            "n" | "no" => break false,
+            _ => {}
+        }
+    };
+
+    Ok(Setup { name, template, network, examples })
+}
+
+fn prompt(question: &str, default: &str) -> io::Result<String> {
+    if default.is_empty() {
+        print!("{question}: ");
+    } else {
+        print!("{question} [{default}]: ");
+    }
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default.to_string() } else { answer.to_string() })
+}
+
+fn is_package_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_lowercase())
+        && chars.all(|c| c == '_' || c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
+/// Names the package, configures the environment of the chosen network and, without examples,
+/// replaces the template's sources by an empty crate.
+fn apply(setup: &Setup, target_dir: &Path, manifest_path: &Path) -> Result<(), Box<dyn Error>> {
+    let content = fs::read_to_string(manifest_path)?;
+    let content =
+        update_toml_table(&content, "package", &[("name", KeyUpdate::Set(setup.name.clone()))])?;
+    fs::write(manifest_path, content)?;
+
+    match &setup.network {
+        Network::Katana => {
+            write_katana_env(manifest_path)?;
+        }
+        Network::Remote { rpc_url, account_address, keystore_path } => {
+            let mut content = fs::read_to_string(manifest_path)?;
+            if !content.lines().any(|line| line.trim() == "[tool.dojo.env]") {
+                if !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                content.push_str("\n[tool.dojo.env]\n");
+            }
+            let keystore = match keystore_path {
+                Some(path) => KeyUpdate::Set(path.clone()),
+                None => KeyUpdate::Remove,
+            };
+            let content = update_toml_table(
+                &content,
+                "tool.dojo.env",
+                &[
+                    ("rpc_url", KeyUpdate::Set(rpc_url.clone())),
+                    ("account_address", KeyUpdate::Set(account_address.clone())),
+                    ("private_key", KeyUpdate::Remove),
+                    ("keystore_path", keystore),
+                ],
+            )?;
+            fs::write(manifest_path, content)?;
+            println!(
+                "The account's key is read from `DOJO_PRIVATE_KEY`, or from the keystore with \
+                 its password in `DOJO_KEYSTORE_PASSWORD`."
+            );
+        }
+    }
+
+    if !setup.examples {
+        let src_dir = target_dir.join("src");
+        if src_dir.exists() {
+            fs::remove_dir_all(&src_dir)?;
+        }
+        fs::create_dir_all(&src_dir)?;
+        fs::write(src_dir.join("lib.cairo"), "")?;
+    }
+
+    Ok(())
+}
+
+/// Configures the environment to use the first account predeployed by `katana` when started
+/// with its default seed, unless the template already configures one.
+fn write_katana_env(manifest_path: &Path) -> Result<bool, Box<dyn Error>> {
+    let Ok(content) = fs::read_to_string(manifest_path) else {
+        return Ok(false);
+    };
+
+    let manifest: toml::Value = toml::from_str(&content)?;
+    let env = manifest.get("tool").and_then(|tool| tool.get("dojo")).and_then(|d| d.get("env"));
+    if env.is_some() {
+        return Ok(false);
+    }
+
+    let account = default_dev_accounts(1)[0];
+    let mut content = content;
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!(
+        "\n[tool.dojo.env]\n# First account predeployed by `katana` with its default seed.\n\
+         rpc_url = \"{KATANA_RPC_URL}\"\naccount_address = \"{:#x}\"\nprivate_key = \"{:#x}\"\n",
+        account.address, account.private_key
+    ));
+    fs::write(manifest_path, content)?;
+
+    Ok(true)
+}
+
 fn clone_repo(url: &str, path: &PathBuf) -> Result<(), Box<dyn Error>> {
     if path.exists() {
         let entries = fs::read_dir(path)?.count();
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+use std::env::current_dir;
+use std::fs;
+
+use anyhow::Result;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use dojo_world::manifest::{Input, Manifest, Member, Output};
+use serde::Serialize;
+use serde_json::Value;
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::FieldElement;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+
+#[derive(Args)]
+pub struct InspectArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, help = "Output the summary as JSON")]
+    json: bool,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// Size of a compiled contract class.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ContractSize {
+    /// Size of the class file written by the build.
+    pub bytes: u64,
+    /// Length of the Sierra program, what the declaration limits apply to.
+    pub sierra_felts: usize,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct ContractSummary {
+    pub name: String,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    pub size: Option<ContractSize>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct ComponentSummary {
+    pub name: String,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    pub members: Vec<Member>,
+    pub size: Option<ContractSize>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct SystemSummary {
+    pub name: String,
+    #[serde_as(as = "UfeHex")]
+    pub class_hash: FieldElement,
+    pub inputs: Vec<Input>,
+    pub outputs: Vec<Output>,
+    pub dependencies: Vec<String>,
+    pub size: Option<ContractSize>,
+}
+
+/// Overview of the built world. Sizes are only known for the classes the build info lists.
+#[derive(Debug, Serialize)]
+pub struct WorldSummary {
+    pub world: ContractSummary,
+    pub executor: ContractSummary,
+    pub components: Vec<ComponentSummary>,
+    pub systems: Vec<SystemSummary>,
+    pub contracts: Vec<ContractSummary>,
+}
+
+impl WorldSummary {
+    /// Summarizes the manifest, reading the sizes of the classes from the build's `target_dir`.
+    pub fn new(manifest: &Manifest, target_dir: &Utf8Path) -> Result<Self> {
+        let mut sizes = HashMap::new();
+        for contract in manifest.build_info.iter().flat_map(|info| &info.contracts) {
+            if let Some(size) = contract_size(target_dir, &contract.name)? {
+                sizes.insert(contract.class_hash, size);
+            }
+        }
+
+        let contract = |name: &str, class_hash: FieldElement| ContractSummary {
+            name: name.to_string(),
+            class_hash,
+            size: sizes.get(&class_hash).copied(),
+        };
+
+        let mut components = manifest
+            .components
+            .iter()
+            .map(|c| ComponentSummary {
+                name: c.name.clone(),
+                class_hash: c.class_hash,
+                members: c.members.clone(),
+                size: sizes.get(&c.class_hash).copied(),
+            })
+            .collect::<Vec<_>>();
+        components.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let mut systems = manifest
+            .systems
+            .iter()
+            .map(|s| SystemSummary {
+                name: s.name.to_string(),
+                class_hash: s.class_hash,
+                inputs: s.inputs.clone(),
+                outputs: s.outputs.clone(),
+                dependencies: s.dependencies.clone(),
+                size: sizes.get(&s.class_hash).copied(),
+            })
+            .collect::<Vec<_>>();
+        systems.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self {
+            world: contract("World", manifest.world),
+            executor: contract("Executor", manifest.executor),
+            components,
+            systems,
+            contracts: manifest.contracts.iter().map(|c| contract(&c.name, c.class_hash)).collect(),
+        })
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str(&format!("World     {}\n", format_contract(&self.world)));
+        out.push_str(&format!("Executor  {}\n", format_contract(&self.executor)));
+
+        out.push_str(&format!("\nComponents ({})\n", self.components.len()));
+        for component in &self.components {
+            out.push_str(&format!(
+                "  {} {:#x}{}\n",
+                component.name,
+                component.class_hash,
+                format_size(component.size)
+            ));
+            for member in &component.members {
+                out.push_str(&format!("    {}: {}\n", member.name, member.ty));
+            }
+        }
+
+        out.push_str(&format!("\nSystems ({})\n", self.systems.len()));
+        for system in &self.systems {
+            out.push_str(&format!(
+                "  {} {:#x}{}\n",
+                system.name,
+                system.class_hash,
+                format_size(system.size)
+            ));
+            let inputs = system
+                .inputs
+                .iter()
+                .map(|i| format!("{}: {}", i.name, i.ty))
+                .collect::<Vec<_>>()
+                .join(", ");
+            let outputs = system.outputs.iter().map(|o| o.ty.as_str()).collect::<Vec<_>>();
+            out.push_str(&format!("    execute({inputs})"));
+            if !outputs.is_empty() {
+                out.push_str(&format!(" -> ({})", outputs.join(", ")));
+            }
+            out.push('\n');
+            if !system.dependencies.is_empty() {
+                out.push_str(&format!("    writes: {}\n", system.dependencies.join(", ")));
+            }
+        }
+
+        if !self.contracts.is_empty() {
+            out.push_str(&format!("\nContracts ({})\n", self.contracts.len()));
+            for contract in &self.contracts {
+                out.push_str(&format!("  {} {}\n", contract.name, format_contract(contract)));
+            }
+        }
+
+        out
+    }
+}
+
+fn format_contract(contract: &ContractSummary) -> String {
+    format!("{:#x}{}", contract.class_hash, format_size(contract.size))
+}
+
+fn format_size(size: Option<ContractSize>) -> String {
+    match size {
+        Some(size) => format!(" ({} bytes, {} felts)", size.bytes, size.sierra_felts),
+        None => String::new(),
+    }
+}
+
+/// Size of the class the build wrote for the contract, `<target>_<contract>.json`.
+fn contract_size(target_dir: &Utf8Path, contract_name: &str) -> Result<Option<ContractSize>> {
+    let suffix = format!("_{contract_name}.json");
+    for entry in target_dir.read_dir_utf8()? {
+        let entry = entry?;
+        if !entry.file_name().ends_with(&suffix) {
+            continue;
+        }
+
+        let class: Value = serde_json::from_slice(&fs::read(entry.path())?)?;
+        let sierra_felts = class["sierra_program"].as_array().map_or(0, Vec::len);
+        return Ok(Some(ContractSize { bytes: entry.metadata()?.len(), sierra_felts }));
+    }
+
+    Ok(None)
+}
+
+pub fn run(args: InspectArgs) -> Result<()> {
+    let InspectArgs { path, json, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    let manifest_path = target_dir.join("manifest.json");
+
+    if !manifest_path.exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+
+    let manifest = Manifest::load_from_path(manifest_path)?;
+    let summary = WorldSummary::new(&manifest, &target_dir)?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary)?);
+    } else {
+        print!("{}", summary.to_text());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,413 @@
+use std::env::{self, current_dir};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::{Args, Subcommand};
+use dojo_world::config::{EnvironmentConfig, KeyUpdate, SignerLocation, WorldConfig};
+use dojo_world::migration::object::{deploy_call, WorldContract};
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::accounts::{Account, Call, ConnectedAccount};
+use serde_json::Value;
+use starknet::core::types::{BlockId, BlockTag, ContractClass, FieldElement, FunctionCall};
+use starknet::core::utils::{
+    cairo_short_string_to_felt, get_contract_address, get_selector_from_name,
+};
+use starknet::providers::Provider;
+use starknet::signers::SigningKey;
+
+use super::build::ProfileSpec;
+use crate::cancellation::run_cancellable;
+use crate::fee::FeeArgs;
+use crate::receipts::{wait_for_acceptance, History, HistoryEntry};
+
+#[cfg(test)]
+#[path = "keystore_test.rs"]
+mod test;
+
+#[derive(Args)]
+pub struct KeystoreArgs {
+    #[command(subcommand)]
+    command: KeystoreCommand,
+}
+
+#[derive(Subcommand)]
+pub enum KeystoreCommand {
+    #[command(about = "Generate a random private key and save it to an encrypted keystore")]
+    New(NewArgs),
+    #[command(about = "Save an existing private key to an encrypted keystore")]
+    Import(ImportArgs),
+    #[command(about = "Decrypt a keystore and print its public key")]
+    Inspect(InspectArgs),
+    #[command(about = "Replace the key of the environment's account by a new keystore, updating \
+                       the account's signer or moving the world's ownership to a new account")]
+    Rotate(RotateArgs),
+}
+
+/// Password of the keystore, prompted for when not given.
+#[derive(Args)]
+pub struct PasswordArgs {
+    #[clap(long, env = "DOJO_KEYSTORE_PASSWORD")]
+    #[clap(help = "Password of the keystore, prompted for if omitted")]
+    password: Option<String>,
+}
+
+#[derive(Args)]
+pub struct NewArgs {
+    #[clap(help = "Path to write the keystore to")]
+    path: PathBuf,
+
+    #[clap(long, help = "Overwrite the keystore if it already exists")]
+    force: bool,
+
+    #[command(flatten)]
+    password: PasswordArgs,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    #[clap(help = "Private key to import")]
+    private_key: FieldElement,
+
+    #[clap(help = "Path to write the keystore to")]
+    path: PathBuf,
+
+    #[clap(long, help = "Overwrite the keystore if it already exists")]
+    force: bool,
+
+    #[command(flatten)]
+    password: PasswordArgs,
+}
+
+#[derive(Args)]
+pub struct InspectArgs {
+    #[clap(help = "Path of the keystore")]
+    path: PathBuf,
+
+    #[clap(long, help = "Also print the private key")]
+    private: bool,
+
+    #[command(flatten)]
+    password: PasswordArgs,
+}
+
+#[derive(Args)]
+pub struct RotateArgs {
+    #[clap(help = "Path to write the new keystore to")]
+    keystore: PathBuf,
+
+    #[clap(long, help = "Overwrite the keystore if it already exists")]
+    force: bool,
+
+    #[clap(long)]
+    #[clap(help = "Deploy a new account controlled by the new key, of the same class as the \
+                   current one, and transfer the world's ownership to it instead of changing \
+                   the current account's signer")]
+    new_account: bool,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, alias = "source", help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    password: PasswordArgs,
+
+    #[command(flatten)]
+    fee: FeeArgs,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: KeystoreArgs, timeout: Option<Duration>) -> Result<()> {
+    match args.command {
+        KeystoreCommand::New(args) => {
+            save(SigningKey::from_random(), args.path, args.force, args.password)
+        }
+        KeystoreCommand::Import(args) => save(
+            SigningKey::from_secret_scalar(args.private_key),
+            args.path,
+            args.force,
+            args.password,
+        ),
+        KeystoreCommand::Inspect(args) => inspect(args),
+        KeystoreCommand::Rotate(args) => rotate(args, timeout),
+    }
+}
+
+fn save(key: SigningKey, path: PathBuf, force: bool, password: PasswordArgs) -> Result<()> {
+    write_keystore(&key, &path, force, password)?;
+    println!(
+        "\nUse it by setting `keystore_path = \"{}\"` in the `[tool.dojo.env]` of Scarb.toml, the \
+         password is read from `keystore_password` or `DOJO_KEYSTORE_PASSWORD`.",
+        path.display()
+    );
+
+    Ok(())
+}
+
+/// Encrypts the key to `path` with a new password, prompted for twice when not given, and returns
+/// the password.
+fn write_keystore(
+    key: &SigningKey,
+    path: &Path,
+    force: bool,
+    password: PasswordArgs,
+) -> Result<String> {
+    if path.exists() && !force {
+        return Err(anyhow!("{} already exists, pass `--force` to overwrite it", path.display()));
+    }
+
+    let password = match password.password {
+        Some(password) => password,
+        None => {
+            let password = rpassword::prompt_password("Enter the keystore password: ")?;
+            if rpassword::prompt_password("Confirm the password: ")? != password {
+                return Err(anyhow!("Passwords don't match"));
+            }
+            password
+        }
+    };
+    if password.is_empty() {
+        return Err(anyhow!("The keystore password can't be empty"));
+    }
+
+    key.save_as_keystore(path, &password)
+        .map_err(|e| anyhow!("Failed to write {}: {e}", path.display()))?;
+
+    println!("Keystore written to {}", path.display());
+    println!("Public key: {:#x}", key.verifying_key().scalar());
+
+    Ok(password)
+}
+
+fn inspect(args: InspectArgs) -> Result<()> {
+    let InspectArgs { path, private, password } = args;
+
+    let password = match password.password {
+        Some(password) => password,
+        None => rpassword::prompt_password("Enter the keystore password: ")?,
+    };
+    let key = SigningKey::from_keystore(&path, &password)
+        .map_err(|e| anyhow!("Failed to decrypt {}: {e}", path.display()))?;
+
+    println!("Public key: {:#x}", key.verifying_key().scalar());
+    if private {
+        println!("Private key: {:#x}", key.secret_scalar());
+    }
+
+    Ok(())
+}
+
+/// Rotates the key of the environment's account. The new keystore is written before anything is
+/// sent, so the key can't be lost, and the environment config is rewritten as soon as the
+/// transaction is accepted on L2.
+fn rotate(args: RotateArgs, timeout: Option<Duration>) -> Result<()> {
+    dotenv().ok();
+
+    let RotateArgs { keystore, force, new_account, world, path, password, fee, profile_spec } =
+        args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+    let signer_location = SignerLocation::from_workspace(profile.as_str(), &ws);
+    let history = History::new(&source_dir, profile.as_str());
+
+    let world_address = world.or(world_config.address);
+    if new_account && world_address.is_none() {
+        return Err(anyhow!(
+            "Missing world address to transfer, pass `--world` or set `world_address`"
+        ));
+    }
+
+    let key = SigningKey::from_random();
+    let public_key = key.verifying_key().scalar();
+    let password = write_keystore(&key, &keystore, force, password)?;
+    let keystore_path = config_path(&keystore, &source_dir);
+
+    let rotation = async {
+        let account = env_config.migrator().await?;
+        let provider = account.provider();
+        let old_address = account.address();
+
+        let pending = BlockId::Tag(BlockTag::Pending);
+        let class = provider
+            .get_class_at(pending, old_address)
+            .await
+            .map_err(|e| anyhow!("Failed to fetch the class of {old_address:#x}: {e}"))?;
+        let (set_public_key, get_public_key) = public_key_selectors(&class)?;
+
+        // The new account is deployed and made owner in the same transaction as the old one
+        // loses the role, so the world is never left without an owner.
+        let (calls, address, description) = if new_account {
+            check_account_constructor(&class)?;
+            let class_hash = provider
+                .get_class_hash_at(pending, old_address)
+                .await
+                .map_err(|e| anyhow!("Failed to fetch the class of {old_address:#x}: {e}"))?;
+            let salt = FieldElement::from(rand::random::<u64>());
+            let calldata = [public_key];
+            let address = get_contract_address(salt, class_hash, &calldata, FieldElement::ZERO);
+
+            let world = WorldContract::new(world_address.unwrap(), &account);
+            let grant = world.execute_call(
+                cairo_short_string_to_felt("GrantAuthRole")?,
+                vec![address, cairo_short_string_to_felt("Admin")?],
+            );
+            let revoke = world
+                .execute_call(cairo_short_string_to_felt("RevokeAuthRole")?, vec![old_address]);
+            let calls = vec![deploy_call(class_hash, salt, &calldata), grant, revoke];
+            (calls, address, format!("transfer ownership to {address:#x}"))
+        } else {
+            let call =
+                Call { to: old_address, selector: set_public_key, calldata: vec![public_key] };
+            (vec![call], old_address, format!("rotate the key of {old_address:#x}"))
+        };
+
+        let fees = fee.fee_config(&env_config);
+        let retry = env_config.retry_policy();
+        let execution = retry
+            .run(|| fees.execution(provider, account.execute(calls.clone())))
+            .await
+            .map_err(|e| anyhow!("Failed to estimate the fee of the rotation: {e}"))?;
+        fees.simulate_execution(&execution)
+            .await
+            .map_err(|e| anyhow!("Failed to simulate the rotation: {e}"))?;
+        // Sent once: sent again, it would be signed with a fresh nonce and could run twice.
+        let transaction_hash = execution
+            .send()
+            .await
+            .map_err(|e| anyhow!("Failed to {description}: {e}"))?
+            .transaction_hash;
+        println!("Transaction hash: {transaction_hash:#x}");
+
+        wait_for_acceptance(provider, transaction_hash, Duration::from_secs(1), false).await?;
+
+        // The old key is useless from now on, the config must follow before anything else can
+        // fail.
+        let mut updates = vec![
+            ("keystore_path", KeyUpdate::Set(keystore_path.clone())),
+            ("keystore_password", KeyUpdate::Replace(password.clone())),
+            ("private_key", KeyUpdate::Remove),
+            ("signer_command", KeyUpdate::Remove),
+        ];
+        if new_account {
+            updates.push(("account_address", KeyUpdate::Set(format!("{address:#x}"))));
+        }
+        signer_location.update(&updates)?;
+        println!("Updated `[{}]` of {}", signer_location.table, signer_location.path);
+
+        let signer = provider
+            .call(
+                FunctionCall {
+                    contract_address: address,
+                    entry_point_selector: get_public_key,
+                    calldata: vec![],
+                },
+                pending,
+            )
+            .await;
+        match signer {
+            Ok(signer) if signer.first() == Some(&public_key) => {}
+            Ok(_) => eprintln!("warning: the signer of {address:#x} isn't the new key"),
+            Err(e) => eprintln!("warning: failed to read the public key of {address:#x}: {e}"),
+        }
+
+        let calldata = calls.into_iter().flat_map(|call| call.calldata).collect();
+        let entry =
+            HistoryEntry::from_receipt(provider, "keys", &description, transaction_hash, calldata)
+                .await;
+        if let Err(e) = history.append(&[entry]) {
+            eprintln!("warning: failed to record the rotation in the history: {e}");
+        }
+
+        Ok::<_, anyhow::Error>(address)
+    };
+
+    let address = ws.config().tokio_handle().block_on(async {
+        run_cancellable(rotation, timeout).await.map_err(|reason| anyhow!("Rotation {reason}"))?
+    })?;
+
+    for var in ["DOJO_PRIVATE_KEY", "DOJO_KEYSTORE_PATH", "DOJO_ACCOUNT_ADDRESS"] {
+        if env::var(var).is_ok() {
+            eprintln!("warning: `{var}` is set and may override the rotated config");
+        }
+    }
+    if new_account {
+        println!("Fund {address:#x} before sending transactions with it.");
+    }
+
+    Ok(())
+}
+
+/// Selectors of the entrypoints setting and reading the public key of an account of `class`,
+/// in snake case for Cairo 1 accounts and in camel case for Cairo 0 ones.
+fn public_key_selectors(class: &ContractClass) -> Result<(FieldElement, FieldElement)> {
+    let (set, get) = match class {
+        ContractClass::Sierra(_) => ("set_public_key", "get_public_key"),
+        ContractClass::Legacy(_) => ("setPublicKey", "getPublicKey"),
+    };
+    Ok((get_selector_from_name(set)?, get_selector_from_name(get)?))
+}
+
+/// Fails unless the constructor of the account class takes the public key alone, the only
+/// calldata `--new-account` deploys it with.
+fn check_account_constructor(class: &ContractClass) -> Result<()> {
+    let abi: Value = match class {
+        ContractClass::Sierra(class) => serde_json::from_str(&class.abi)?,
+        ContractClass::Legacy(class) => serde_json::to_value(&class.abi)?,
+    };
+    let inputs = abi
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|entry| entry["type"] == "constructor" || entry["name"] == "constructor")
+        .and_then(|constructor| constructor["inputs"].as_array())
+        .map_or(0, |inputs| inputs.len());
+    if inputs != 1 {
+        bail!(
+            "The constructor of the account's class takes {inputs} arguments, `--new-account` \
+             only deploys accounts taking their public key"
+        );
+    }
+    Ok(())
+}
+
+/// `keystore` as written in the config, relative to the directory of Scarb.toml when it's inside
+/// it since the paths of the config are resolved against it.
+fn config_path(keystore: &Path, source_dir: &Utf8Path) -> String {
+    let keystore = if keystore.is_absolute() {
+        keystore.to_path_buf()
+    } else {
+        current_dir().map(|dir| dir.join(keystore)).unwrap_or_else(|_| keystore.to_path_buf())
+    };
+    keystore.strip_prefix(source_dir).unwrap_or(&keystore).display().to_string()
+}
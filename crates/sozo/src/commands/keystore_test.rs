@@ -0,0 +1,59 @@
+use std::path::Path;
+
+use camino::Utf8Path;
+use serde_json::json;
+use starknet::core::types::{ContractClass, FlattenedSierraClass};
+use starknet::core::utils::get_selector_from_name;
+
+use super::{check_account_constructor, config_path, public_key_selectors};
+
+fn sierra_class(abi: serde_json::Value) -> ContractClass {
+    ContractClass::Sierra(FlattenedSierraClass {
+        sierra_program: vec![],
+        contract_class_version: "0.1.0".into(),
+        entry_points_by_type: serde_json::from_value(json!({
+            "CONSTRUCTOR": [],
+            "EXTERNAL": [],
+            "L1_HANDLER": [],
+        }))
+        .unwrap(),
+        abi: abi.to_string(),
+    })
+}
+
+#[test]
+fn test_public_key_selectors() {
+    let class = sierra_class(json!([]));
+    let (set, get) = public_key_selectors(&class).unwrap();
+    assert_eq!(set, get_selector_from_name("set_public_key").unwrap());
+    assert_eq!(get, get_selector_from_name("get_public_key").unwrap());
+}
+
+#[test]
+fn test_check_account_constructor() {
+    let account = sierra_class(json!([
+        { "type": "function", "name": "__execute__", "inputs": [] },
+        { "type": "constructor", "name": "constructor", "inputs": [
+            { "name": "public_key", "type": "core::felt252" },
+        ] },
+    ]));
+    assert!(check_account_constructor(&account).is_ok());
+
+    let guarded = sierra_class(json!([
+        { "type": "constructor", "name": "constructor", "inputs": [
+            { "name": "owner", "type": "core::felt252" },
+            { "name": "guardian", "type": "core::felt252" },
+        ] },
+    ]));
+    assert!(check_account_constructor(&guarded).is_err());
+    assert!(check_account_constructor(&sierra_class(json!([]))).is_err());
+}
+
+#[test]
+fn test_config_path() {
+    let source_dir = Utf8Path::new("/game");
+    let inside = config_path(Path::new("/game/keys/deployer.json"), source_dir);
+    assert_eq!(inside, "keys/deployer.json");
+    let outside = config_path(Path::new("/keys/deployer.json"), source_dir);
+    assert_eq!(outside, "/keys/deployer.json");
+}
@@ -0,0 +1,191 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::core::types::{BlockId, EventFilter, FieldElement};
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::Provider;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use super::events::{decode_event, porcelain_fields, print_porcelain, WORLD_EVENTS};
+use crate::cancellation::{run_cancellable, Interrupted};
+
+#[derive(Args)]
+pub struct LogsArgs {
+    #[clap(long, help = "First block to tail events from, defaults to the next block")]
+    from_block: Option<u64>,
+
+    #[clap(long, help = "Last block to tail events from, tails forever otherwise")]
+    to_block: Option<u64>,
+
+    #[clap(long, value_delimiter = ',')]
+    #[clap(help = "Comma separated names of the events to tail, defaults to all world events")]
+    events: Vec<String>,
+
+    #[clap(long, default_value_t = 1000, help = "Interval between polls of the node, in ms")]
+    poll_interval: u64,
+
+    #[clap(long, default_value_t = 100, help = "Number of events fetched per request")]
+    chunk_size: u64,
+
+    #[clap(long, help = "Output the events as JSON lines")]
+    json: bool,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// Prints the events of the world as the blocks including them are produced, until interrupted
+/// or past `--to-block`. The node is polled for new blocks, events of the pending block are
+/// printed once it's closed so that none is printed twice.
+pub fn run(args: LogsArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let LogsArgs {
+        from_block,
+        to_block,
+        events,
+        poll_interval,
+        chunk_size,
+        json,
+        world,
+        path,
+        profile_spec,
+    } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+    let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+    let provider = env_config.provider()?;
+
+    let names = if events.is_empty() {
+        WORLD_EVENTS.iter().map(|name| name.to_string()).collect()
+    } else {
+        events
+    };
+    let selectors =
+        names.iter().map(|name| get_selector_from_name(name)).collect::<Result<Vec<_>, _>>()?;
+
+    let tail = async {
+        let mut next_block = match from_block {
+            Some(block) => block,
+            None => provider.block_number().await.map_err(|e| anyhow!("{e}"))? + 1,
+        };
+        if !porcelain && !json {
+            println!("Tailing the events of {world_address:#x} from block {next_block}");
+        }
+
+        while to_block.map_or(true, |to_block| next_block <= to_block) {
+            let latest = provider
+                .block_number()
+                .await
+                .map_err(|e| anyhow!("Failed to fetch the latest block: {e}"))?;
+            let latest = to_block.map_or(latest, |to_block| latest.min(to_block));
+
+            if latest >= next_block {
+                let filter = EventFilter {
+                    from_block: Some(BlockId::Number(next_block)),
+                    to_block: Some(BlockId::Number(latest)),
+                    address: Some(world_address),
+                    keys: Some(vec![selectors.clone()]),
+                };
+
+                let mut continuation_token = None;
+                loop {
+                    let page = provider
+                        .get_events(filter.clone(), continuation_token, chunk_size)
+                        .await
+                        .map_err(|e| anyhow!("Failed to fetch events: {e}"))?;
+
+                    for event in page.events {
+                        let decoded = decode_event(&manifest, &names, event);
+                        if porcelain {
+                            print_porcelain(decoded);
+                        } else if json {
+                            println!("{}", serde_json::to_string(&decoded)?);
+                        } else {
+                            println!(
+                                "[{}] {} {:#x}\n    {}",
+                                decoded.block_number,
+                                decoded.name,
+                                decoded.transaction_hash,
+                                porcelain_fields(&decoded.fields).join("\n    ")
+                            );
+                        }
+                    }
+
+                    continuation_token = page.continuation_token;
+                    if continuation_token.is_none() {
+                        break;
+                    }
+                }
+
+                next_block = latest + 1;
+            } else {
+                tokio::time::sleep(Duration::from_millis(poll_interval)).await;
+            }
+        }
+
+        Ok::<_, anyhow::Error>(())
+    };
+
+    ws.config().tokio_handle().block_on(async {
+        match run_cancellable(tail, timeout).await {
+            Ok(res) => res,
+            // Ctrl-C is the expected way out when tailing forever.
+            Err(Interrupted::CtrlC) => Ok(()),
+            Err(reason) => Err(anyhow!("Tailing events {reason}")),
+        }
+    })
+}
@@ -1,34 +1,128 @@
 use std::env::{self, current_dir};
+use std::fs;
+use std::time::Duration;
 
-use anyhow::{anyhow, Result};
-use camino::Utf8PathBuf;
+use anyhow::{anyhow, bail, Result};
+use camino::{Utf8Path, Utf8PathBuf};
 use clap::Args;
-use dojo_world::config::{EnvironmentConfig, WorldConfig};
-use dojo_world::migration::strategy::prepare_for_migration;
+use dojo_world::config::{parse_chain_id, EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::bundle::CallBundle;
+use dojo_world::migration::cache::DeclaredClasses;
+use dojo_world::migration::deployment::{DeploymentManifest, MigrationFees};
+use dojo_world::migration::finality::Finality;
+use dojo_world::migration::plan::{MigrationPlan, PlannedStep};
+use dojo_world::migration::strategy::{
+    prepare_for_migration, prepare_from_deployment, MigrationCheckpoint, MigrationKind,
+};
 use dojo_world::migration::world::WorldDiff;
 use dotenv::dotenv;
 use scarb::core::Config;
 use scarb::ops;
 use scarb::ui::Verbosity;
+use starknet::accounts::Account;
+use starknet::core::types::FieldElement;
 
 use super::build::{self, BuildArgs, ProfileSpec};
+use crate::cancellation::run_cancellable;
+use crate::confirm::confirm;
+use crate::fee::FeeArgs;
+use crate::receipts::{History, HistoryEntry};
+
+const CHECKPOINT_FILE: &str = "migration_checkpoint.json";
+const DEPLOYMENT_FILE: &str = "manifest.json";
 
 #[derive(Args)]
 pub struct MigrateArgs {
     #[clap(help = "Source directory")]
-    path: Option<Utf8PathBuf>,
+    pub path: Option<Utf8PathBuf>,
+
+    #[clap(long, visible_alias = "plan")]
+    #[clap(help = "Print the transactions the migration would send and their estimated fees, \
+                   without sending anything")]
+    pub dry_run: bool,
+
+    #[clap(long, requires = "dry_run", help = "Output the migration plan as JSON")]
+    pub json: bool,
+
+    #[clap(long, value_name = "FILE", conflicts_with = "dry_run")]
+    #[clap(help = "Write the calls the migration would make to FILE as a JSON bundle, to execute \
+                   them through a multisig or governance process, without sending anything")]
+    pub output_calldata: Option<Utf8PathBuf>,
+
+    #[clap(long, default_value_t = MigrationKind::Incremental)]
+    #[clap(help = "Migration strategy: `full`, `incremental` or `force-redeploy`")]
+    pub strategy: MigrationKind,
+
+    #[clap(long)]
+    #[clap(help = "Replay the deployment described by this manifest instead of diffing against \
+                   the remote world, reproducing the same world on a new chain")]
+    pub from_manifest: Option<Utf8PathBuf>,
+
+    #[clap(long)]
+    #[clap(help = "Ignore the checkpoint left by a failed migration instead of resuming from it")]
+    pub restart: bool,
+
+    #[clap(short, long, help = "Don't ask for confirmation on the protected chains")]
+    pub yes: bool,
+
+    #[clap(long)]
+    #[clap(help = "Migrate the artifacts already in the target directory instead of building the \
+                   project first")]
+    pub skip_build: bool,
+
+    #[clap(long, value_name = "DIR")]
+    #[clap(help = "Migrate the artifacts of DIR, like the ones built by a CI job, instead of the \
+                   project's target directory. Implies `--skip-build`")]
+    pub artifacts_dir: Option<Utf8PathBuf>,
+
+    #[clap(long, value_name = "CALLS", value_parser = clap::value_parser!(u64).range(1..))]
+    #[clap(help = "Split the registrations into transactions of at most CALLS calls, all the \
+                   components or systems are registered in a single transaction by default")]
+    pub max_calls_per_tx: Option<u64>,
 
-    #[clap(short, long, help = "Perform a dry run and outputs the plan to be executed")]
-    plan: bool,
+    #[clap(long)]
+    #[clap(help = "Build and plan the migration without accessing the network, diffing against \
+                   the last deployment manifest of the profile instead of the remote world. \
+                   Requires `--dry-run` or `--output-calldata`, no fee is estimated")]
+    pub offline: bool,
+
+    #[clap(long, requires = "offline", value_parser = parse_chain_id)]
+    #[clap(help = "Chain the offline migration targets, the classes cached as declared on it \
+                   are left out of the plan")]
+    pub chain_id: Option<FieldElement>,
+
+    #[command(flatten)]
+    pub fee: FeeArgs,
 
     #[command(flatten)]
-    profile_spec: ProfileSpec,
+    pub profile_spec: ProfileSpec,
 }
 
-pub fn run(args: MigrateArgs) -> Result<()> {
+pub fn run(args: MigrateArgs, timeout: Option<Duration>) -> Result<()> {
     dotenv().ok();
 
-    let MigrateArgs { path, profile_spec, .. } = args;
+    let MigrateArgs {
+        path,
+        dry_run,
+        json,
+        output_calldata,
+        strategy,
+        from_manifest,
+        restart,
+        yes,
+        skip_build,
+        artifacts_dir,
+        max_calls_per_tx,
+        offline,
+        chain_id,
+        fee,
+        profile_spec,
+    } = args;
+
+    if offline && !dry_run && output_calldata.is_none() {
+        bail!("`--offline` only plans the migration, pass `--dry-run` or `--output-calldata`");
+    }
 
     let source_dir = match path {
         Some(path) => {
@@ -52,24 +146,340 @@ pub fn run(args: MigrateArgs) -> Result<()> {
     let ws = ops::read_workspace(config.manifest_path(), &config)?;
 
     let profile = profile_spec.determine()?;
-    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    let prebuilt = skip_build || artifacts_dir.is_some();
+    let target_dir = match artifacts_dir {
+        Some(dir) if dir.is_absolute() => dir,
+        Some(dir) => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap().join(dir),
+        None => source_dir.join(format!("target/{}", profile.as_str())),
+    };
 
-    if !target_dir.join("manifest.json").exists() {
-        build::run(BuildArgs { path: Some(source_dir), profile_spec })?;
+    if !prebuilt {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline,
+            profile_spec,
+        })?;
+    } else if !target_dir.join("manifest.json").exists() {
+        bail!("No artifacts found in {target_dir}, build them with `sozo build` first");
     }
 
-    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let mut world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
     let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+    let history = History::new(&source_dir, profile.as_str());
+
+    let local_manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+    let deployment_path =
+        source_dir.join(format!("deployments/{}/{DEPLOYMENT_FILE}", profile.as_str()));
+    let previous_deployment = if from_manifest.is_none() && deployment_path.exists() {
+        Some(DeploymentManifest::load_from_path(&deployment_path)?)
+    } else {
+        None
+    };
+
+    let checkpoint_path = target_dir.join(CHECKPOINT_FILE);
+    let previous_checkpoint = if !restart && checkpoint_path.exists() {
+        Some(MigrationCheckpoint::load_from_path(&checkpoint_path)?)
+    } else {
+        None
+    };
+
+    // Offline, the world of the last deployment is the one to migrate.
+    if offline && world_config.address.is_none() {
+        world_config.address =
+            previous_deployment.as_ref().and_then(|deployment| deployment.world.address);
+    }
 
     ws.config().tokio_handle().block_on(async {
+        let mut migration = match &from_manifest {
+            Some(path) => {
+                let deployment = DeploymentManifest::load_from_path(path)?;
+                prepare_from_deployment(
+                    target_dir.clone(),
+                    &local_manifest,
+                    &deployment,
+                    world_config,
+                )?
+            }
+            None => {
+                let diff = if offline {
+                    WorldDiff::from_deployment(
+                        target_dir.clone(),
+                        &world_config,
+                        previous_deployment.as_ref(),
+                    )?
+                } else {
+                    WorldDiff::from_path(target_dir.clone(), &world_config, &env_config).await?
+                };
+                prepare_for_migration(target_dir.clone(), diff, world_config, strategy)?
+            }
+        };
+
+        if let Some(previous) = previous_checkpoint {
+            println!(
+                "Resuming the migration from {checkpoint_path}, {} transactions were already \
+                 submitted. Pass `--restart` to start over.\n",
+                previous.submitted.len()
+            );
+            migration.resume_from(previous);
+        }
+        let resumed = migration.checkpoint.submitted.len();
+        migration.max_calls_per_tx = max_calls_per_tx.map(|calls| calls as usize);
+
+        if offline {
+            if let Some(chain_id) = chain_id {
+                let cached = migration.skip_cached_declarations(&load_declared_classes(), chain_id);
+                if cached > 0 {
+                    println!("{cached} classes are known to be declared and won't be planned.\n");
+                }
+            }
+            let plan = migration.plan_offline().await?;
+            if let Some(output) = &output_calldata {
+                return write_bundle(&plan, output);
+            }
+            if json {
+                println!("{}", serde_json::to_string_pretty(&plan)?);
+            } else {
+                print_plan(&plan);
+            }
+            return Ok(());
+        }
+
         let migrator = env_config.migrator().await?;
-        let diff = WorldDiff::from_path(target_dir.clone(), &world_config, &env_config).await?;
-        let mut migration = prepare_for_migration(target_dir, diff, world_config)?;
-        migration
-            .execute(migrator)
-            .await
-            .map_err(|e| anyhow!("Problem when tyring to migrate: {e}"))
+        let chain_id = migrator.chain_id();
+        let mut declared_classes = load_declared_classes();
+        let cached = migration.skip_cached_declarations(&declared_classes, chain_id);
+        if cached > 0 {
+            println!(
+                "{cached} classes are known to be declared and won't be looked up, run `sozo \
+                 cache clear` if the chain was reset.\n"
+            );
+        }
+
+        migration.fees = fee.migration_fee_config(&env_config);
+        migration.retry = env_config.retry_policy();
+        migration.finality = env_config.finality(migrator.chain_id());
+
+        if dry_run {
+            let plan = run_cancellable(migration.plan(&migrator), timeout)
+                .await
+                .map_err(|reason| anyhow!("Planning the migration {reason}"))??;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&plan)?);
+            } else {
+                print_plan(&plan);
+            }
+            return Ok(());
+        }
+
+        if let Some(output) = &output_calldata {
+            let plan = run_cancellable(migration.plan(&migrator), timeout)
+                .await
+                .map_err(|reason| anyhow!("Planning the migration {reason}"))??;
+            return write_bundle(&plan, output);
+        }
+
+        let protected = !yes && env_config.is_protected(chain_id);
+        if fee.needs_estimate() || protected {
+            let plan = run_cancellable(migration.plan(&migrator), timeout)
+                .await
+                .map_err(|reason| anyhow!("Planning the migration {reason}"))??;
+            if protected {
+                print_plan(&plan);
+                if !confirm("Migrate the world", chain_id)? {
+                    println!("Migration cancelled.");
+                    return Ok(());
+                }
+            }
+            if fee.needs_estimate() && !fee.check_plan(&migrator, &plan, false).await? {
+                return Ok(());
+            }
+        }
+
+        if migration.finality != Finality::Pending {
+            println!("Waiting for {} between dependent steps.\n", migration.finality);
+        }
+        migration.checkpoint.persist_to(checkpoint_path.clone());
+
+        let sender = migrator.address();
+        let migrate = async {
+            // Spread the declarations across the additional accounts when there are some.
+            if !env_config.accounts.is_empty() {
+                let dispatcher = env_config.dispatcher().await?;
+                println!("- Declaring classes with {} accounts...", dispatcher.accounts().len());
+                migration.declare_classes(&dispatcher).await.map_err(|e| anyhow!("{e}"))?;
+            }
+            migration.execute(migrator).await.map_err(|e| anyhow!("{e}"))
+        };
+        let res = run_cancellable(migrate, timeout).await;
+
+        let provider = env_config.provider()?;
+        let entries = migration.checkpoint.submitted[resumed..]
+            .iter()
+            .map(|tx| {
+                HistoryEntry::submitted(
+                    "migrate",
+                    &tx.description,
+                    tx.transaction_hash,
+                    tx.calldata.clone(),
+                    Some(tx.sender.unwrap_or(sender)),
+                )
+            })
+            .collect::<Vec<_>>();
+        if let Err(e) = history.append(&entries) {
+            eprintln!("warning: failed to record the migration in the history: {e}");
+        }
+
+        let err = match res {
+            Ok(Ok(_)) => {
+                let mut deployment =
+                    migration.deployment_manifest(&local_manifest, previous_deployment.as_ref());
+                if let Err(e) = deployment.resolve_block_numbers(&provider).await {
+                    eprintln!("warning: {e}");
+                }
+                if !migration.checkpoint.submitted.is_empty() {
+                    let fees =
+                        MigrationFees::from_receipts(&provider, &migration.checkpoint.submitted)
+                            .await;
+                    print_fees(&fees);
+                    deployment.fees = Some(fees);
+                }
+                deployment.write_to_path(&deployment_path)?;
+                println!("\nDeployment manifest written to {deployment_path}");
+
+                migration.cache_declarations(&mut declared_classes, chain_id);
+                if let Err(e) = declared_classes.save() {
+                    eprintln!("warning: {e}");
+                }
+
+                // The migration is complete, the next one mustn't skip anything.
+                if checkpoint_path.exists() {
+                    fs::remove_file(&checkpoint_path)?;
+                }
+                return Ok(());
+            }
+            Ok(Err(e)) => anyhow!("Problem when tyring to migrate: {e}"),
+            Err(reason) => anyhow!("Migration {reason}"),
+        };
+
+        // Let the user know what already went through before bailing out.
+        let checkpoint = &migration.checkpoint;
+        if checkpoint.submitted.is_empty() {
+            println!("\nNo transactions were submitted.");
+        } else {
+            println!("\nTransactions submitted before the migration stopped:");
+            for tx in &checkpoint.submitted {
+                println!("    {}: {:#x}", tx.description, tx.transaction_hash);
+            }
+
+            checkpoint.write_to_path(&checkpoint_path)?;
+            println!("Checkpoint written to {checkpoint_path}, migrate again to resume from it.");
+        }
+
+        Err(err)
     })?;
 
     Ok(())
 }
+
+/// Writes the calls of the plan to `output` as a bundle to execute through another process.
+fn write_bundle(plan: &MigrationPlan, output: &Utf8Path) -> Result<()> {
+    let bundle = CallBundle::from_plan(plan);
+    bundle.write_to_path(output)?;
+
+    println!("{} calls written to {output}", bundle.calls.len());
+    if !bundle.declarations.is_empty() {
+        println!(
+            "The classes must be declared, with `sozo declare <name>`, before executing them:"
+        );
+        for declaration in &bundle.declarations {
+            println!("    {}: {:#x}", declaration.name, declaration.class_hash);
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn print_plan(plan: &MigrationPlan) {
+    match plan.world_address {
+        Some(address) => println!("Migration plan of world {address:#x}:"),
+        None => println!("Migration plan:"),
+    }
+    if plan.steps.is_empty() {
+        println!("    Nothing to migrate, the world is up to date.");
+        return;
+    }
+
+    for (i, step) in plan.steps.iter().enumerate() {
+        let description = match step {
+            PlannedStep::Declare { name, class_hash, remote_class_hash, .. } => {
+                let change = match remote_class_hash {
+                    Some(remote) => format!("replaces {remote:#x}"),
+                    None => "new".to_string(),
+                };
+                format!("declare {name} {class_hash:#x} ({change})")
+            }
+            PlannedStep::Deploy { name, address, .. } => format!("deploy {name} at {address:#x}"),
+            PlannedStep::SetExecutor { executor, .. } => format!("set executor to {executor:#x}"),
+            PlannedStep::RegisterSystems { class_hashes, .. } => {
+                format!("register {} systems", class_hashes.len())
+            }
+            PlannedStep::RegisterComponents { class_hashes, .. } => {
+                format!("register {} components", class_hashes.len())
+            }
+        };
+        let fee = step.estimated_fee().map_or("-".to_string(), |fee| fee.to_string());
+        println!("  {:>3}. {description:<90} fee: {fee}", i + 1);
+    }
+
+    println!("\nEstimated fee: {} wei", plan.estimated_fee);
+    if plan.unestimated_steps > 0 {
+        println!(
+            "The fee of {} steps couldn't be estimated, they depend on classes or contracts \
+             declared or deployed by the previous steps.",
+            plan.unestimated_steps
+        );
+    }
+}
+
+/// Prints the actual fee of every transaction of the migration, and the totals per kind of
+/// transaction.
+fn print_fees(fees: &MigrationFees) {
+    println!("\nFees:");
+    for tx in &fees.transactions {
+        let fee = tx.actual_fee.map_or("pending".to_string(), format_eth);
+        println!(
+            "    {:<8} {:<60} {:#066x}  {fee}",
+            tx.kind.as_str(),
+            tx.description,
+            tx.transaction_hash
+        );
+    }
+    println!();
+    for (kind, total) in &fees.totals {
+        println!("    Total {:<8} {}", kind.as_str(), format_eth(*total));
+    }
+    println!("    Total          {}", format_eth(fees.total));
+}
+
+/// A fee in wei, as ETH.
+fn format_eth(wei: FieldElement) -> String {
+    const WEI_PER_ETH: u128 = 1_000_000_000_000_000_000;
+    let wei = u128::try_from(wei).unwrap_or(u128::MAX);
+    let decimals = format!("{:018}", wei % WEI_PER_ETH);
+    let decimals = decimals.trim_end_matches('0');
+    format!("{}.{} ETH", wei / WEI_PER_ETH, if decimals.is_empty() { "0" } else { decimals })
+}
+
+/// The classes known to be declared, an unreadable cache being ignored as everything it holds
+/// can be looked up again.
+fn load_declared_classes() -> DeclaredClasses {
+    let Some(path) = DeclaredClasses::default_path() else {
+        return DeclaredClasses::default();
+    };
+    DeclaredClasses::load_from_path(&path).unwrap_or_else(|e| {
+        eprintln!("warning: {e}, run `sozo cache clear` to reset it");
+        DeclaredClasses::default()
+    })
+}
@@ -1,26 +1,175 @@
 use clap::{Parser, Subcommand};
+use smol_str::SmolStr;
 
+use self::account::AccountArgs;
+use self::auth::AuthArgs;
+use self::bindgen::BindgenArgs;
 use self::build::BuildArgs;
+use self::cache::CacheArgs;
+use self::call::CallArgs;
+use self::clean::CleanArgs;
+use self::completions::CompletionsArgs;
+use self::component::ComponentArgs;
+use self::declare::DeclareArgs;
+use self::deploy::DeployArgs;
+use self::dev::DevArgs;
+use self::doc::DocArgs;
+use self::events::EventsArgs;
+use self::execute::ExecuteArgs;
+use self::fuzz::FuzzArgs;
+use self::graph::GraphArgs;
+use self::history::HistoryArgs;
+use self::import::ImportArgs;
 use self::init::InitArgs;
+use self::inspect::InspectArgs;
+use self::keystore::KeystoreArgs;
+use self::logs::LogsArgs;
 use self::migrate::MigrateArgs;
+use self::register::RegisterArgs;
+use self::run::RunArgs;
+use self::status::StatusArgs;
+use self::system::SystemArgs;
 use self::test::TestArgs;
+use self::upgrade::UpgradeArgs;
+use self::verify::VerifyArgs;
+use self::world::WorldArgs;
 
+pub(crate) mod account;
+pub(crate) mod auth;
+pub(crate) mod bindgen;
 pub(crate) mod build;
+pub(crate) mod cache;
+pub(crate) mod call;
+pub(crate) mod clean;
+pub(crate) mod completions;
+pub(crate) mod component;
+pub(crate) mod declare;
+pub(crate) mod deploy;
+pub(crate) mod dev;
+pub(crate) mod doc;
+pub(crate) mod events;
+pub(crate) mod execute;
+pub(crate) mod fuzz;
+pub(crate) mod graph;
+pub(crate) mod history;
+pub(crate) mod import;
 pub(crate) mod init;
+pub(crate) mod inspect;
+pub(crate) mod keystore;
+pub(crate) mod logs;
 pub(crate) mod migrate;
+pub(crate) mod register;
+pub(crate) mod run;
+pub(crate) mod status;
+pub(crate) mod system;
 pub(crate) mod test;
+pub(crate) mod upgrade;
+pub(crate) mod verify;
+pub(crate) mod world;
 
 #[derive(Subcommand)]
 pub enum Commands {
+    #[command(about = "Manage the accounts used to interact with the world")]
+    Account(AccountArgs),
+    #[command(about = "Manage which systems and accounts are authorized to write to the world")]
+    Auth(AuthArgs),
+    #[command(about = "Generate typed client bindings of the world's components, systems and \
+                       events")]
+    Bindgen(BindgenArgs),
     #[command(about = "Build the world, generating the necessary artifacts for deployment")]
     Build(BuildArgs),
+    #[command(about = "Manage the cache of the classes known to be declared, shared by all \
+                       projects")]
+    Cache(CacheArgs),
+    #[command(about = "Call a view function of the world, without sending a transaction")]
+    Call(CallArgs),
+    #[command(about = "Remove the build artifacts, generated manifests and migration state")]
+    Clean(CleanArgs),
+    #[command(about = "Generate the completion script of a shell")]
+    Completions(CompletionsArgs),
+    #[command(about = "Inspect the world's components and read their values")]
+    #[command(alias = "model")]
+    Component(ComponentArgs),
+    #[command(about = "Declare a single compiled contract, without migrating the world")]
+    Declare(DeclareArgs),
+    #[command(about = "Deploy an instance of a class, such as a token or a mock living alongside \
+                       the world")]
+    Deploy(DeployArgs),
+    #[command(about = "Rebuild and migrate the world whenever the sources change")]
+    Dev(DevArgs),
+    #[command(about = "Generate the reference documentation of the world's components and \
+                       systems, from their doc comments")]
+    Doc(DocArgs),
+    #[command(about = "Fetch and decode the events emitted by the world")]
+    Events(EventsArgs),
+    #[command(about = "Execute a system on the world")]
+    Execute(ExecuteArgs),
+    #[command(about = "Simulate calls of a system with random calldata generated from its \
+                       inputs, and report the failures")]
+    Fuzz(FuzzArgs),
+    #[command(about = "Output the dependency graph between the world's systems and components")]
+    Graph(GraphArgs),
+    #[command(about = "Show the transactions previously sent to the world")]
+    History(HistoryArgs),
+    #[command(about = "Write the deployment manifest of a world deployed by someone else, read \
+                       from the chain, to manage it from this project")]
+    Import(ImportArgs),
     #[command(about = "Initialize a new project")]
     Init(InitArgs),
+    #[command(about = "Summarize the built world: components, systems, class hashes and sizes")]
+    Inspect(InspectArgs),
+    #[command(about = "Create and inspect the encrypted keystores the environment's \
+                       `keystore_path` refers to")]
+    #[command(alias = "keys")]
+    Keystore(KeystoreArgs),
+    #[command(about = "Tail the events emitted by the world, decoded as they are produced")]
+    Logs(LogsArgs),
     #[command(about = "Run a migration, declaring and deploying contracts as necessary to \
                        update the world")]
     Migrate(MigrateArgs),
+    #[command(about = "Declare and register components or systems to an existing world")]
+    Register(RegisterArgs),
+    #[command(about = "Run the steps of a post-migration script, such as initialization calls, \
+                       token mints and auth grants, skipping those already done")]
+    Run(RunArgs),
+    #[command(about = "Compare the components and systems of the deployed world against the \
+                       local build, without sending anything")]
+    Status(StatusArgs),
+    #[command(about = "Inspect the systems registered in the world")]
+    System(SystemArgs),
     #[command(about = "Test the project's smart contracts")]
     Test(TestArgs),
+    #[command(about = "Upgrade the systems, components and executor of a live world whose \
+                       classes changed, keeping its address and storage")]
+    Upgrade(UpgradeArgs),
+    #[command(about = "Verify that the world's classes can be reproduced from source and match \
+                       the deployed world")]
+    Verify(VerifyArgs),
+    #[command(about = "Inspect a deployed world")]
+    World(WorldArgs),
+}
+
+impl Commands {
+    /// Whether the command outputs records with `--porcelain`, see [`crate::porcelain`].
+    pub fn has_porcelain_output(&self) -> bool {
+        matches!(
+            self,
+            Commands::Auth(_)
+                | Commands::Call(_)
+                | Commands::Component(_)
+                | Commands::Declare(_)
+                | Commands::Deploy(_)
+                | Commands::Events(_)
+                | Commands::Execute(_)
+                | Commands::Fuzz(_)
+                | Commands::History(_)
+                | Commands::Logs(_)
+                | Commands::Run(_)
+                | Commands::Status(_)
+                | Commands::System(_)
+                | Commands::World(_)
+        )
+    }
 }
 
 #[derive(Parser)]
@@ -29,4 +178,17 @@ pub enum Commands {
 pub struct App {
     #[command(subcommand)]
     pub command: Commands,
+
+    #[arg(long, global = true)]
+    #[arg(help = "Abort commands interacting with the network after this many seconds")]
+    pub timeout: Option<u64>,
+
+    #[arg(long, global = true)]
+    #[arg(help = "Output stable, tab separated records meant to be parsed by scripts")]
+    pub porcelain: bool,
+
+    #[arg(short = 'P', long, global = true, env = "SOZO_PROFILE")]
+    #[arg(help = "Profile to use by name, selecting the build profile and the `[tool.dojo.env]` \
+                  section of the environment")]
+    pub profile: Option<SmolStr>,
 }
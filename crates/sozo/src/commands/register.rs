@@ -0,0 +1,210 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::{Args, Subcommand};
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::deployment::DeploymentManifest;
+use dojo_world::migration::strategy::{prepare_for_registration, MigrationStrategy};
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::accounts::Account;
+use starknet::core::types::FieldElement;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::cancellation::run_cancellable;
+use crate::fee::FeeArgs;
+use crate::receipts::{History, HistoryEntry};
+
+#[cfg(test)]
+#[path = "register_test.rs"]
+mod test;
+
+const DEPLOYMENT_FILE: &str = "manifest.json";
+
+#[derive(Args)]
+pub struct RegisterArgs {
+    #[command(subcommand)]
+    command: RegisterCommand,
+
+    #[clap(long, global = true)]
+    #[clap(help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, global = true, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    fee: FeeArgs,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+#[derive(Subcommand)]
+pub enum RegisterCommand {
+    #[command(about = "Declare and register components to the world")]
+    Component {
+        #[clap(required = true, help = "Names of the components to register")]
+        names: Vec<String>,
+    },
+
+    #[command(about = "Declare and register systems to the world")]
+    System {
+        #[clap(required = true, help = "Names of the systems to register")]
+        names: Vec<String>,
+    },
+}
+
+pub fn run(args: RegisterArgs, timeout: Option<Duration>) -> Result<()> {
+    dotenv().ok();
+
+    let RegisterArgs { command, world, path, fee, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+
+    let mut world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+    world_config.address = Some(world_address);
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+    let history = History::new(&source_dir, profile.as_str());
+    let deployment_path =
+        source_dir.join(format!("deployments/{}/{DEPLOYMENT_FILE}", profile.as_str()));
+
+    let local_manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+    let (components, systems) = match command {
+        RegisterCommand::Component { names } => (names, vec![]),
+        RegisterCommand::System { names } => (vec![], names),
+    };
+    let mut registration =
+        prepare_for_registration(target_dir, &local_manifest, &components, &systems, world_config)?;
+    registration.fees = fee.migration_fee_config(&env_config);
+    registration.retry = env_config.retry_policy();
+
+    ws.config().tokio_handle().block_on(async {
+        let migrator = env_config.migrator().await?;
+        registration.finality = env_config.finality(migrator.chain_id());
+
+        if fee.needs_estimate() {
+            let plan = run_cancellable(registration.plan(&migrator), timeout)
+                .await
+                .map_err(|reason| anyhow!("Estimating the fees {reason}"))??;
+            if !fee.check_plan(&migrator, &plan, false).await? {
+                return Ok(());
+            }
+        }
+
+        let res = run_cancellable(
+            async {
+                if !registration.components.is_empty() {
+                    let output = registration
+                        .register_components(&migrator)
+                        .await
+                        .map_err(|e| anyhow!("Problem when trying to register: {e}"))?;
+                    println!("Components registered at tx: {:#x}", output.transaction_hash);
+                }
+                if !registration.systems.is_empty() {
+                    let output = registration
+                        .register_systems(&migrator)
+                        .await
+                        .map_err(|e| anyhow!("Problem when trying to register: {e}"))?;
+                    println!("Systems registered at tx: {:#x}", output.transaction_hash);
+                }
+                Ok::<_, anyhow::Error>(())
+            },
+            timeout,
+        )
+        .await;
+
+        let entries = registration
+            .checkpoint
+            .submitted
+            .iter()
+            .map(|tx| {
+                HistoryEntry::submitted(
+                    "register",
+                    &tx.description,
+                    tx.transaction_hash,
+                    tx.calldata.clone(),
+                    Some(tx.sender.unwrap_or(migrator.address())),
+                )
+            })
+            .collect::<Vec<_>>();
+        if let Err(e) = history.append(&entries) {
+            eprintln!("warning: failed to record the registration in the history: {e}");
+        }
+
+        res.map_err(|reason| anyhow!("Registration {reason}"))??;
+
+        let deployment =
+            registered_deployment(&deployment_path, &registration, &local_manifest, world_address)?;
+        if let Some(mut deployment) = deployment {
+            if let Err(e) = deployment.resolve_block_numbers(&env_config.provider()?).await {
+                eprintln!("warning: {e}");
+            }
+            deployment.write_to_path(&deployment_path)?;
+            println!("\nDeployment manifest written to {deployment_path}");
+        }
+
+        Ok(())
+    })
+}
+
+/// Deployment manifest at `path` with the classes of `registration` registered, so that the next
+/// migration diffing against it doesn't register them again. `None` when there's no deployment
+/// of `world` to update.
+fn registered_deployment(
+    path: &Utf8Path,
+    registration: &MigrationStrategy,
+    local: &Manifest,
+    world: FieldElement,
+) -> Result<Option<DeploymentManifest>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let previous = DeploymentManifest::load_from_path(path)?;
+    if previous.world.address != Some(world) {
+        return Ok(None);
+    }
+
+    Ok(Some(registration.registration_manifest(local, &previous)))
+}
@@ -0,0 +1,112 @@
+use assert_fs::TempDir;
+use camino::Utf8PathBuf;
+use dojo_world::config::{FeeConfig, WorldConfig};
+use dojo_world::manifest::{Component, Manifest, Member};
+use dojo_world::migration::deployment::{DeployedContract, DeploymentManifest, RegisteredClass};
+use dojo_world::migration::finality::Finality;
+use dojo_world::migration::object::ClassMigration;
+use dojo_world::migration::retry::RetryPolicy;
+use dojo_world::migration::strategy::{
+    MigrationCheckpoint, MigrationStrategy, SubmittedTransaction,
+};
+use dojo_world::migration::world::ClassDiff;
+use starknet::core::types::FieldElement;
+
+use super::registered_deployment;
+
+const WORLD: FieldElement = FieldElement::ONE;
+
+/// Registration of the `Moves` component with class hash `0x2`, registered by transaction `0x20`.
+fn registration() -> MigrationStrategy {
+    let moves = FieldElement::TWO;
+    let mut checkpoint = MigrationCheckpoint::default();
+    checkpoint.submitted.push(SubmittedTransaction {
+        description: "register components".into(),
+        transaction_hash: FieldElement::from(0x20_u8),
+        calldata: vec![FieldElement::ONE, moves],
+        class_hash: None,
+        salt: None,
+        contract_address: None,
+        sender: None,
+    });
+
+    MigrationStrategy {
+        world: None,
+        executor: None,
+        systems: vec![],
+        components: vec![ClassMigration {
+            class: ClassDiff { name: "Moves".into(), local: moves, remote: None },
+            artifact_path: "MovesComponent.json".into(),
+            declared: true,
+        }],
+        world_config: WorldConfig { address: Some(WORLD) },
+        checkpoint,
+        fees: FeeConfig::default(),
+        finality: Finality::default(),
+        retry: RetryPolicy::default(),
+        max_calls_per_tx: None,
+    }
+}
+
+fn local_manifest() -> Manifest {
+    let member = Member { name: "remaining".into(), ty: "u8".into(), ..Member::default() };
+    Manifest {
+        components: vec![Component {
+            name: "Moves".into(),
+            members: vec![member],
+            class_hash: FieldElement::TWO,
+            ..Component::default()
+        }],
+        ..Manifest::default()
+    }
+}
+
+fn deployment(world: FieldElement) -> DeploymentManifest {
+    DeploymentManifest {
+        world: DeployedContract { address: Some(world), ..DeployedContract::default() },
+        components: vec![RegisteredClass {
+            name: "Position".into(),
+            class_hash: FieldElement::from(0x3_u8),
+            transaction_hash: Some(FieldElement::from(0x10_u8)),
+            ..RegisteredClass::default()
+        }],
+        ..DeploymentManifest::default()
+    }
+}
+
+#[test]
+fn test_registered_deployment() {
+    let dir = TempDir::new().unwrap();
+    let path =
+        Utf8PathBuf::from_path_buf(dir.path().join("deployments/dev/manifest.json")).unwrap();
+
+    // Nothing to update before the world is migrated.
+    assert!(
+        registered_deployment(&path, &registration(), &local_manifest(), WORLD).unwrap().is_none()
+    );
+
+    deployment(WORLD).write_to_path(&path).unwrap();
+    let updated =
+        registered_deployment(&path, &registration(), &local_manifest(), WORLD).unwrap().unwrap();
+
+    assert_eq!(updated.world, deployment(WORLD).world);
+    assert_eq!(updated.components.len(), 2);
+    assert_eq!(updated.components[0], deployment(WORLD).components[0]);
+    let moves = &updated.components[1];
+    assert_eq!(moves.name, "Moves");
+    assert_eq!(moves.class_hash, FieldElement::TWO);
+    assert_eq!(moves.transaction_hash, Some(FieldElement::from(0x20_u8)));
+    assert_eq!(moves.members, local_manifest().components[0].members);
+}
+
+#[test]
+fn test_registered_deployment_of_another_world() {
+    let dir = TempDir::new().unwrap();
+    let path =
+        Utf8PathBuf::from_path_buf(dir.path().join("deployments/dev/manifest.json")).unwrap();
+    deployment(FieldElement::from(0x42_u8)).write_to_path(&path).unwrap();
+
+    assert!(
+        registered_deployment(&path, &registration(), &local_manifest(), WORLD).unwrap().is_none()
+    );
+}
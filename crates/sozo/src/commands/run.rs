@@ -0,0 +1,554 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env::{self, current_dir};
+use std::fs;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::deployment::DeploymentManifest;
+use dojo_world::migration::finality::wait_for_finality;
+use dojo_world::migration::object::{deploy_call, WorldContract};
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_with::serde_as;
+use starknet::accounts::{Account, Call, ConnectedAccount};
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::{
+    BlockId, BlockTag, FieldElement, MaybePendingTransactionReceipt, StarknetError,
+    TransactionStatus,
+};
+use starknet::core::utils::{
+    cairo_short_string_to_felt, get_contract_address, get_selector_from_name, starknet_keccak,
+};
+use starknet::providers::{Provider, ProviderError};
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::cancellation::run_cancellable;
+use crate::fee::FeeArgs;
+use crate::porcelain;
+use crate::receipts::{receipt_status, History, HistoryEntry};
+use crate::script::{encode_call, load_from_path, parse_felt};
+
+#[cfg(test)]
+#[path = "run_test.rs"]
+mod test;
+
+const DEPLOYMENT_FILE: &str = "manifest.json";
+
+#[derive(Args)]
+pub struct RunArgs {
+    #[clap(help = "Path of the TOML or JSON script")]
+    script: Utf8PathBuf,
+
+    #[clap(long, help = "Only print the steps left to run, without sending anything")]
+    dry_run: bool,
+
+    #[clap(long)]
+    #[clap(help = "Run every step again, ignoring those recorded as done by a previous run")]
+    restart: bool,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    fee: FeeArgs,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// Steps run in order after a migration, each one in its own transaction:
+///
+/// ```toml
+/// [[steps]]
+/// id = "gold"
+/// deploy = { class = "ERC20", calldata = ["Gold", "GLD", 18, "$caller"] }
+///
+/// [[steps]]
+/// id = "init"
+/// execute = { system = "init", args = { token = "$gold" } }
+///
+/// [[steps]]
+/// id = "mint"
+/// invoke = { contract = "$gold", entrypoint = "mint", calldata = ["$caller", 1000, 0] }
+///
+/// [[steps]]
+/// id = "spawn-writer"
+/// grant_writer = { system = "spawn", component = "Position" }
+/// ```
+///
+/// Strings starting with `$` refer to the world (`$world`), the executor (`$executor`), the
+/// account running the script (`$caller`) or the contract deployed by an earlier step (`$<id>`).
+#[derive(Debug, Deserialize)]
+struct RunScript {
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Step {
+    /// Identifies the step in the state of the script, it's run again once renamed.
+    id: String,
+    #[serde(flatten)]
+    action: Action,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Action {
+    /// Executes a system, its arguments being encoded from its inputs like `execute --script`.
+    Execute {
+        system: String,
+        #[serde(default)]
+        args: Option<Value>,
+    },
+    /// Calls an entrypoint of any contract.
+    Invoke {
+        contract: String,
+        entrypoint: String,
+        #[serde(default)]
+        calldata: Vec<Value>,
+    },
+    /// Deploys a declared class through the UDC, given by class hash or contract name. The salt
+    /// defaults to one derived from the step id.
+    Deploy {
+        class: String,
+        #[serde(default)]
+        salt: Option<Value>,
+        #[serde(default)]
+        calldata: Vec<Value>,
+    },
+    /// Authorizes a system to write to a component.
+    GrantWriter {
+        system: String,
+        component: String,
+        #[serde(default)]
+        role: Option<String>,
+    },
+    /// Grants the world level Admin role to an account or a system.
+    GrantOwner(String),
+}
+
+impl RunScript {
+    /// Reads a JSON script if the file has the `.json` extension, a TOML one otherwise.
+    fn load_from_path(path: &Utf8Path) -> Result<Self> {
+        let script: Self = load_from_path(path)?;
+        script.check_ids().with_context(|| format!("Invalid script {path}"))?;
+        Ok(script)
+    }
+
+    /// Fails if several steps share an id, they would share their state.
+    fn check_ids(&self) -> Result<()> {
+        let mut ids = HashSet::new();
+        if let Some(step) = self.steps.iter().find(|step| !ids.insert(step.id.as_str())) {
+            bail!("the id `{}` is used by several steps", step.id);
+        }
+        Ok(())
+    }
+}
+
+/// Steps of a script already run on a world, so that running it again only runs the new ones.
+#[serde_as]
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RunState {
+    #[serde_as(as = "Option<UfeHex>")]
+    world: Option<FieldElement>,
+    steps: BTreeMap<String, StepState>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+struct StepState {
+    /// Not set for deployments found already done, by someone else for instance.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    transaction_hash: Option<FieldElement>,
+    /// Address of the contract deployed by the step.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address: Option<FieldElement>,
+    /// Sent but not final yet when the state was written. The receipt of the transaction is
+    /// checked by the next run, which only runs the step again if it never made it.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pending: bool,
+}
+
+impl RunState {
+    /// Path of the state of the script at `script_path`, keyed by its full path so that scripts
+    /// with the same name in different directories don't share their state.
+    fn path(deployment_dir: &Utf8Path, script_path: &Utf8Path) -> Utf8PathBuf {
+        let key = format!("{:x}", starknet_keccak(script_path.as_str().as_bytes()));
+        let key = &key[key.len().saturating_sub(16)..];
+        let name = script_path.file_stem().unwrap_or("script");
+        deployment_dir.join(format!("run/{name}-{key}.json"))
+    }
+
+    /// Loads the state of the script at `script_path`, falling back to the one written before
+    /// states were keyed by the script's full path.
+    fn load(deployment_dir: &Utf8Path, script_path: &Utf8Path) -> Result<Self> {
+        let path = Self::path(deployment_dir, script_path);
+        let legacy_path = deployment_dir
+            .join(format!("run/{}.json", script_path.file_stem().unwrap_or("script")));
+        match [path, legacy_path].into_iter().find(|path| path.exists()) {
+            Some(path) => Self::load_from_path(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn load_from_path(path: &Utf8Path) -> Result<Self> {
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {path}"))
+    }
+
+    fn write_to_path(&self, path: &Utf8Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create the directory {parent}"))?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {path}"))
+    }
+}
+
+/// Replaces the `$` references of the strings of `value` by the addresses they refer to.
+fn resolve(value: &Value, references: &HashMap<String, FieldElement>) -> Result<Value> {
+    Ok(match value {
+        Value::String(s) => match s.strip_prefix('$') {
+            Some(name) => {
+                let address = references.get(name).with_context(|| {
+                    format!(
+                        "unknown reference `{s}`, expected `$world`, `$executor`, `$caller` or \
+                         the id of an earlier deploy step"
+                    )
+                })?;
+                Value::String(format!("{address:#x}"))
+            }
+            None => value.clone(),
+        },
+        Value::Array(values) => {
+            Value::Array(values.iter().map(|v| resolve(v, references)).collect::<Result<_>>()?)
+        }
+        Value::Object(values) => Value::Object(
+            values
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), resolve(v, references)?)))
+                .collect::<Result<_>>()?,
+        ),
+        _ => value.clone(),
+    })
+}
+
+fn resolve_felts(
+    values: &[Value],
+    references: &HashMap<String, FieldElement>,
+) -> Result<Vec<FieldElement>> {
+    values.iter().map(|value| parse_felt(&resolve(value, references)?)).collect()
+}
+
+/// The call of a step with its description, and the address of the contract it deploys.
+fn prepare(
+    step: &Step,
+    manifest: &Manifest,
+    world: &WorldContract<'_, impl ConnectedAccount + Sync>,
+    references: &HashMap<String, FieldElement>,
+) -> Result<(Call, String, Option<FieldElement>)> {
+    let short_string = |s: &str| cairo_short_string_to_felt(s).map_err(|e| anyhow!("{s}: {e}"));
+
+    Ok(match &step.action {
+        Action::Execute { system, args } => {
+            let args = args.as_ref().map(|args| resolve(args, references)).transpose()?;
+            let (system, calldata) = encode_call(manifest, system, args.as_ref())?;
+            let call = world.execute_call(short_string(&system)?, calldata);
+            (call, format!("execute {system}"), None)
+        }
+        Action::Invoke { contract, entrypoint, calldata } => {
+            let contract = parse_felt(&resolve(&Value::String(contract.clone()), references)?)?;
+            let call = Call {
+                to: contract,
+                selector: get_selector_from_name(entrypoint)?,
+                calldata: resolve_felts(calldata, references)?,
+            };
+            (call, format!("invoke {entrypoint} of {contract:#x}"), None)
+        }
+        Action::Deploy { class, salt, calldata } => {
+            let class_hash = match manifest
+                .contracts
+                .iter()
+                .find(|c| c.name == class.as_str() || c.name.ends_with(&format!("::{class}")))
+            {
+                Some(contract) => contract.class_hash,
+                None if class.starts_with("0x") => FieldElement::from_hex_be(class)?,
+                None => bail!("contract `{class}` not found in the manifest"),
+            };
+            let salt = match salt {
+                Some(salt) => parse_felt(&resolve(salt, references)?)?,
+                None => starknet_keccak(step.id.as_bytes()),
+            };
+            let calldata = resolve_felts(calldata, references)?;
+
+            // Deployed as not unique, so the address doesn't depend on the account.
+            let address = get_contract_address(salt, class_hash, &calldata, FieldElement::ZERO);
+            (
+                deploy_call(class_hash, salt, &calldata),
+                format!("deploy {class} at {address:#x}"),
+                Some(address),
+            )
+        }
+        Action::GrantWriter { system, component, role } => {
+            let system = system.strip_suffix("System").unwrap_or(system);
+            let role = role.as_deref().unwrap_or(system);
+            let calldata =
+                vec![short_string(system)?, short_string(role)?, short_string(component)?];
+            (
+                world.execute_call(short_string("RouteAuth")?, calldata),
+                format!("grant {system} writer of {component}"),
+                None,
+            )
+        }
+        Action::GrantOwner(target) => {
+            let target = match resolve(&Value::String(target.clone()), references)? {
+                Value::String(s) => s.strip_suffix("System").unwrap_or(&s).to_string(),
+                _ => unreachable!("strings are resolved to strings"),
+            };
+            let target_id = parse_felt(&Value::String(target.clone()))?;
+            let calldata = vec![target_id, short_string("Admin")?];
+            (
+                world.execute_call(short_string("GrantAuthRole")?, calldata),
+                format!("grant {target} owner"),
+                None,
+            )
+        }
+    })
+}
+
+pub fn run(args: RunArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let RunArgs { script, dry_run, restart, world, path, fee, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+    let script_path = if script.is_absolute() {
+        script
+    } else {
+        Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap().join(script)
+    };
+    let run_script = RunScript::load_from_path(&script_path)?;
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+    let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+
+    let deployment_dir = source_dir.join(format!("deployments/{}", profile.as_str()));
+    let deployment_path = deployment_dir.join(DEPLOYMENT_FILE);
+    let deployment = if deployment_path.exists() {
+        Some(DeploymentManifest::load_from_path(&deployment_path)?)
+    } else {
+        None
+    };
+
+    let world_address = world
+        .or(world_config.address)
+        .or(deployment.as_ref().and_then(|d| d.world.address))
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+
+    let state_path = RunState::path(&deployment_dir, &script_path);
+    let mut state = if restart {
+        RunState::default()
+    } else {
+        RunState::load(&deployment_dir, &script_path)?
+    };
+    if state.world.map_or(false, |world| world != world_address) {
+        println!("The world changed since the last run, running every step again.");
+        state = RunState::default();
+    }
+    state.world = Some(world_address);
+
+    let history = History::new(&source_dir, profile.as_str());
+
+    ws.config().tokio_handle().block_on(async {
+        let account = env_config.migrator().await?;
+        let provider = account.provider();
+        let retry = env_config.retry_policy();
+        let finality = env_config.finality(account.chain_id());
+        let world = WorldContract::new(world_address, &account)
+            .with_fees(fee.fee_config(&env_config))
+            .with_retry(retry);
+
+        let mut references = HashMap::from([
+            ("world".to_string(), world_address),
+            ("caller".to_string(), account.address()),
+        ]);
+        if let Some(executor) = deployment.as_ref().and_then(|d| d.executor.address) {
+            references.insert("executor".to_string(), executor);
+        }
+
+        for step in &run_script.steps {
+            let pending = state.steps.get(&step.id).filter(|done| done.pending);
+            if let Some(transaction_hash) = pending.and_then(|done| done.transaction_hash) {
+                if dry_run {
+                    // The steps after it may still refer to the contract it deploys.
+                    if let Some(address) = pending.and_then(|done| done.address) {
+                        references.insert(step.id.clone(), address);
+                    }
+                    println!("{}: sent in {transaction_hash:#x}, not final yet", step.id);
+                    continue;
+                }
+                if reached_node(provider, transaction_hash).await? {
+                    let finalized = wait_for_finality(provider, transaction_hash, finality, &retry);
+                    run_cancellable(finalized, timeout)
+                        .await
+                        .map_err(|reason| anyhow!("Waiting for step `{}` {reason}", step.id))??;
+                    if let Some(done) = state.steps.get_mut(&step.id) {
+                        done.pending = false;
+                    }
+                    state.write_to_path(&state_path)?;
+                } else {
+                    if !porcelain {
+                        println!("{}: {transaction_hash:#x} never made it, running again", step.id);
+                    }
+                    state.steps.remove(&step.id);
+                }
+            }
+
+            if let Some(done) = state.steps.get(&step.id) {
+                if let Some(address) = done.address {
+                    references.insert(step.id.clone(), address);
+                }
+                if !porcelain {
+                    println!("{}: already done", step.id);
+                }
+                continue;
+            }
+
+            let (call, description, address) = prepare(step, &manifest, &world, &references)
+                .with_context(|| format!("Invalid step `{}`", step.id))?;
+            if let Some(address) = address {
+                references.insert(step.id.clone(), address);
+            }
+            if dry_run {
+                println!("{}: {description}", step.id);
+                continue;
+            }
+
+            // A contract already at the address was deployed by an earlier, interrupted run.
+            if let Some(address) = address {
+                let deployed = provider.get_class_hash_at(BlockId::Tag(BlockTag::Pending), address);
+                if deployed.await.is_ok() {
+                    if !porcelain {
+                        println!("{}: already deployed at {address:#x}", step.id);
+                    }
+                    let step_state = StepState {
+                        transaction_hash: None,
+                        address: Some(address),
+                        pending: false,
+                    };
+                    state.steps.insert(step.id.clone(), step_state);
+                    state.write_to_path(&state_path)?;
+                    continue;
+                }
+            }
+
+            // Steps are estimated one at a time, as they depend on the previous ones.
+            if fee.needs_estimate() && !fee.check(&account, vec![call.clone()], porcelain).await? {
+                return Ok(());
+            }
+
+            let transaction_hash = world
+                .send_calls(vec![call.clone()])
+                .await
+                .map_err(|e| anyhow!("Failed to run step `{}`: {e}", step.id))?
+                .transaction_hash;
+            if !porcelain {
+                println!("{}: {description} in {transaction_hash:#x}", step.id);
+            }
+
+            // Recorded as soon as it's sent, so that an interrupted run doesn't send it twice.
+            let step_state =
+                StepState { transaction_hash: Some(transaction_hash), address, pending: true };
+            state.steps.insert(step.id.clone(), step_state);
+            state.write_to_path(&state_path)?;
+
+            let finalized = wait_for_finality(provider, transaction_hash, finality, &retry);
+            run_cancellable(finalized, timeout)
+                .await
+                .map_err(|reason| anyhow!("Waiting for step `{}` {reason}", step.id))??;
+
+            let entry = HistoryEntry::from_receipt(
+                provider,
+                "run",
+                &format!("{}: {description}", step.id),
+                transaction_hash,
+                call.calldata,
+            )
+            .await;
+            if porcelain {
+                porcelain::transaction(&entry);
+            }
+            history.append(&[entry])?;
+
+            if let Some(done) = state.steps.get_mut(&step.id) {
+                done.pending = false;
+            }
+            state.write_to_path(&state_path)?;
+        }
+
+        Ok(())
+    })
+}
+
+/// Whether a transaction sent by an interrupted run reached the node. Rejected transactions and
+/// those the node doesn't know didn't, their step is run again.
+async fn reached_node<P>(provider: &P, transaction_hash: FieldElement) -> Result<bool>
+where
+    P: Provider + Sync,
+{
+    match provider.get_transaction_receipt(transaction_hash).await {
+        Ok(MaybePendingTransactionReceipt::Receipt(receipt)) => {
+            Ok(receipt_status(&receipt) != TransactionStatus::Rejected)
+        }
+        Ok(MaybePendingTransactionReceipt::PendingReceipt(_)) => Ok(true),
+        Err(ProviderError::StarknetError(StarknetError::TransactionHashNotFound)) => Ok(false),
+        Err(e) => Err(anyhow!("Failed to fetch the receipt of {transaction_hash:#x}: {e}")),
+    }
+}
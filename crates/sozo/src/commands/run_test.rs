@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use camino::Utf8Path;
+use dojo_world::manifest::{Contract, Input, Manifest, System};
+use dojo_world::migration::object::{udc_address, WorldContract};
+use serde_json::json;
+use starknet::accounts::SingleOwnerAccount;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::{cairo_short_string_to_felt, get_contract_address, starknet_keccak};
+use starknet::providers::jsonrpc::{HttpTransport, JsonRpcClient};
+use starknet::signers::{LocalWallet, SigningKey};
+use url::Url;
+
+use super::{prepare, resolve, RunScript, RunState, Step};
+
+type TestAccount = SingleOwnerAccount<JsonRpcClient<HttpTransport>, LocalWallet>;
+
+fn account() -> TestAccount {
+    let url = Url::parse("http://localhost:5050").unwrap();
+    let provider = JsonRpcClient::new(HttpTransport::new(url));
+    let signer = LocalWallet::from(SigningKey::from_secret_scalar(FieldElement::ONE));
+    SingleOwnerAccount::new(provider, signer, FieldElement::TWO, FieldElement::ZERO)
+}
+
+fn manifest() -> Manifest {
+    Manifest {
+        systems: vec![System {
+            name: "InitSystem".into(),
+            inputs: vec![Input { name: "token".into(), ty: "felt252".into() }],
+            ..Default::default()
+        }],
+        contracts: vec![Contract {
+            name: "dojo_erc::erc20::ERC20".into(),
+            class_hash: 0x20_u8.into(),
+        }],
+        ..Default::default()
+    }
+}
+
+fn step(toml: &str) -> Step {
+    toml::from_str(toml).unwrap()
+}
+
+fn short_string(s: &str) -> FieldElement {
+    cairo_short_string_to_felt(s).unwrap()
+}
+
+#[test]
+fn test_resolve() {
+    let references = HashMap::from([("gold".to_string(), FieldElement::from(0xabc_u64))]);
+
+    let value = json!({ "token": "$gold", "amounts": [1, "$gold"], "name": "Gold" });
+    assert_eq!(
+        resolve(&value, &references).unwrap(),
+        json!({ "token": "0xabc", "amounts": [1, "0xabc"], "name": "Gold" })
+    );
+
+    let error = resolve(&json!(["$silver"]), &references).unwrap_err();
+    assert!(error.to_string().contains("unknown reference `$silver`"));
+}
+
+#[test]
+fn test_prepare() {
+    let account = account();
+    let world = WorldContract::new(FieldElement::from(0x77_u8), &account);
+    let manifest = manifest();
+    let references = HashMap::from([("gold".to_string(), FieldElement::from(0xabc_u64))]);
+
+    let deploy = step(r#"
+        id = "gold"
+        deploy = { class = "ERC20", calldata = ["Gold", 18] }
+    "#);
+    let (call, _, address) = prepare(&deploy, &manifest, &world, &references).unwrap();
+    let calldata = [short_string("Gold"), FieldElement::from(18_u8)];
+    let salt = starknet_keccak(b"gold");
+    assert_eq!(call.to, udc_address());
+    assert_eq!(
+        address,
+        Some(get_contract_address(salt, 0x20_u8.into(), &calldata, FieldElement::ZERO))
+    );
+
+    let execute = step(r#"
+        id = "init"
+        execute = { system = "init", args = { token = "$gold" } }
+    "#);
+    let (call, description, address) = prepare(&execute, &manifest, &world, &references).unwrap();
+    assert_eq!(call.to, world.address);
+    assert_eq!(call.calldata, [short_string("Init"), FieldElement::ONE, 0xabc_u64.into()]);
+    assert_eq!(description, "execute Init");
+    assert_eq!(address, None);
+
+    let invoke = step(r#"
+        id = "mint"
+        invoke = { contract = "$gold", entrypoint = "mint", calldata = [1000, 0] }
+    "#);
+    let (call, _, _) = prepare(&invoke, &manifest, &world, &references).unwrap();
+    assert_eq!(call.to, FieldElement::from(0xabc_u64));
+    assert_eq!(call.calldata, [FieldElement::from(1000_u64), FieldElement::ZERO]);
+
+    let grant = step(r#"
+        id = "writer"
+        grant_writer = { system = "spawnSystem", component = "Position" }
+    "#);
+    let (call, _, _) = prepare(&grant, &manifest, &world, &references).unwrap();
+    assert_eq!(
+        call.calldata,
+        [
+            short_string("RouteAuth"),
+            FieldElement::THREE,
+            short_string("spawn"),
+            short_string("spawn"),
+            short_string("Position"),
+        ]
+    );
+
+    // References to later steps aren't known yet.
+    let unknown = step(r#"
+        id = "mint-silver"
+        invoke = { contract = "$silver", entrypoint = "mint" }
+    "#);
+    assert!(prepare(&unknown, &manifest, &world, &references).is_err());
+}
+
+#[test]
+fn test_duplicate_ids() {
+    let script: RunScript = toml::from_str(
+        r#"
+        [[steps]]
+        id = "init"
+        execute = { system = "init" }
+
+        [[steps]]
+        id = "init"
+        grant_owner = "spawn"
+        "#,
+    )
+    .unwrap();
+
+    let error = script.check_ids().unwrap_err();
+    assert_eq!(error.to_string(), "the id `init` is used by several steps");
+}
+
+#[test]
+fn test_state_path() {
+    let deployment_dir = Utf8Path::new("/game/deployments/dev");
+    let first = RunState::path(deployment_dir, Utf8Path::new("/game/scripts/setup.toml"));
+    let second = RunState::path(deployment_dir, Utf8Path::new("/game/other/setup.toml"));
+
+    assert_ne!(first, second);
+    assert!(first.starts_with("/game/deployments/dev/run"));
+    assert!(first.file_name().unwrap().starts_with("setup-"));
+}
@@ -0,0 +1,209 @@
+use std::env::{self, current_dir};
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::migration::world::{ClassStatus, WorldDiff};
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use serde::Serialize;
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::FieldElement;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::cancellation::run_cancellable;
+use crate::porcelain;
+
+#[derive(Args)]
+pub struct StatusArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "Output the status as JSON")]
+    json: bool,
+
+    #[clap(long, help = "Exit with an error if any class is missing or stale")]
+    check: bool,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+/// How a class of the deployed world compares to the local build.
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct ClassReport {
+    pub kind: &'static str,
+    pub name: String,
+    pub status: ClassStatus,
+    #[serde_as(as = "UfeHex")]
+    pub local: FieldElement,
+    #[serde_as(as = "Option<UfeHex>")]
+    pub remote: Option<FieldElement>,
+}
+
+#[serde_as]
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    #[serde_as(as = "UfeHex")]
+    pub world_address: FieldElement,
+    pub classes: Vec<ClassReport>,
+}
+
+impl StatusReport {
+    pub fn new(world_address: FieldElement, diff: &WorldDiff) -> Self {
+        let mut classes = vec![];
+        for (kind, contract) in [("world", &diff.world), ("executor", &diff.executor)] {
+            classes.push(ClassReport {
+                kind,
+                name: contract.name.clone(),
+                status: contract.status(),
+                local: contract.local,
+                remote: contract.remote,
+            });
+        }
+        for (kind, classes_diff) in [("component", &diff.components), ("system", &diff.systems)] {
+            let mut reports = classes_diff
+                .iter()
+                .map(|class| ClassReport {
+                    kind,
+                    name: class.name.clone(),
+                    status: class.status(),
+                    local: class.local,
+                    remote: class.remote.filter(|remote| *remote != FieldElement::ZERO),
+                })
+                .collect::<Vec<_>>();
+            reports.sort_by(|a, b| a.name.cmp(&b.name));
+            classes.extend(reports);
+        }
+
+        Self { world_address, classes }
+    }
+
+    /// Number of classes that aren't up to date with the local build.
+    pub fn drifted(&self) -> usize {
+        self.classes.iter().filter(|class| class.status != ClassStatus::Matches).count()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!("World {:#x}\n", self.world_address);
+
+        let mut kind = "";
+        for class in &self.classes {
+            if class.kind != kind {
+                kind = class.kind;
+                match kind {
+                    "component" => out.push_str("\nComponents\n"),
+                    "system" => out.push_str("\nSystems\n"),
+                    _ => {}
+                }
+            }
+
+            out.push_str(&format!("  {:<8} {} {:#x}", class.status, class.name, class.local));
+            if let (ClassStatus::Stale, Some(remote)) = (class.status, class.remote) {
+                out.push_str(&format!(", deployed {remote:#x}"));
+            }
+            out.push('\n');
+        }
+
+        match self.drifted() {
+            0 => out.push_str("\nThe world is up to date with the local build.\n"),
+            drifted => out.push_str(&format!(
+                "\n{drifted} of {} classes differ from the local build, run `sozo migrate` to \
+                 update the world.\n",
+                self.classes.len()
+            )),
+        }
+
+        out
+    }
+}
+
+pub fn run(args: StatusArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let StatusArgs { path, world, json, check, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let config = Config::builder(source_dir.join("Scarb.toml"))
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+
+    let mut world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    world_config.address = world.or(world_config.address);
+    let world_address = world_config
+        .address
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+
+    let diff = ws
+        .config()
+        .tokio_handle()
+        .block_on(run_cancellable(
+            WorldDiff::from_path(target_dir, &world_config, &env_config),
+            timeout,
+        ))
+        .map_err(|reason| anyhow!("Reading the world {reason}"))??;
+    let report = StatusReport::new(world_address, &diff);
+
+    if porcelain {
+        for class in &report.classes {
+            porcelain::record(
+                "status",
+                [
+                    class.kind.to_string(),
+                    class.name.clone(),
+                    class.status.to_string(),
+                    format!("{:#x}", class.local),
+                    porcelain::optional(class.remote.map(|remote| format!("{remote:#x}"))),
+                ],
+            );
+        }
+    } else if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        print!("{}", report.to_text());
+    }
+
+    if check && report.drifted() > 0 {
+        bail!("{} classes differ from the local build.", report.drifted());
+    }
+
+    Ok(())
+}
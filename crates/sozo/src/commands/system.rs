@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+use super::component::{self, ListArgs};
+
+#[derive(Args)]
+pub struct SystemArgs {
+    #[command(subcommand)]
+    command: SystemCommands,
+}
+
+#[derive(Subcommand)]
+pub enum SystemCommands {
+    #[command(about = "List the systems registered in a world, read from the chain")]
+    List(ListArgs),
+}
+
+pub fn run(args: SystemArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    match args.command {
+        SystemCommands::List(args) => component::list("system", args, timeout, porcelain),
+    }
+}
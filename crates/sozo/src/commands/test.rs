@@ -1,10 +1,14 @@
 //! Compiles and runs tests for a Dojo project.
 
 use std::env::{self, current_dir};
+use std::time::Duration;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use cairo_lang_compiler::db::RootDatabase;
 use cairo_lang_compiler::diagnostics::DiagnosticsReporter;
+use cairo_lang_defs::ids::{FreeFunctionId, FunctionWithBodyId, TopLevelLanguageElementId};
+use cairo_lang_filesystem::ids::CrateId;
+use cairo_lang_semantic::db::SemanticGroup;
 use cairo_lang_test_runner::TestRunner;
 use camino::Utf8PathBuf;
 use clap::Args;
@@ -15,32 +19,87 @@ use scarb::core::{Config, Workspace};
 use scarb::ops;
 use scarb::ui::Verbosity;
 
+use super::build::{self, BuildArgs, ProfileSpec};
+use crate::cancellation::run_cancellable;
+use crate::integration::{self, INTEGRATION_TESTS_DIR};
+use crate::property::{self, PropertyTest};
+use crate::snapshot::{self, SnapshotTest, SNAPSHOTS_DIR};
+
+#[cfg(test)]
+#[path = "test_test.rs"]
+mod test;
+
 #[derive(Args)]
 pub struct TestArgs {
-    /// The path to compile and run its tests.
-    path: Utf8PathBuf,
+    /// The path to compile and run its tests, the current directory by default.
+    path: Option<Utf8PathBuf>,
     /// The filter for the tests, running only tests containing the filter string.
-    #[arg(short, long, default_value_t = String::default())]
-    filter: String,
+    #[arg(short, long)]
+    filter: Option<String>,
+    /// Match the filter against the whole test path instead.
+    #[arg(long, requires = "filter")]
+    exact: bool,
+    /// List the tests matching the filter without running them.
+    #[arg(long)]
+    list: bool,
     /// Should we run ignored tests as well.
     #[arg(long, default_value_t = false)]
     include_ignored: bool,
     /// Should we run only the ignored tests.
     #[arg(long, default_value_t = false)]
     ignored: bool,
+    /// Number of random inputs each `#[fuzz]` property test is run with.
+    #[arg(long, default_value_t = 256)]
+    fuzz_runs: usize,
+    /// Seed of the inputs of the property tests, to reproduce a previous run.
+    #[arg(long)]
+    fuzz_seed: Option<u64>,
+    /// Write the snapshots of the `#[snapshot]` tests from their current values instead of
+    /// comparing them.
+    #[arg(long)]
+    update_snapshots: bool,
+    /// Run the integration tests in `tests/integration` instead, against the world migrated to
+    /// an in-process Katana.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "exact", "include_ignored", "ignored", "fuzz_runs", "fuzz_seed", "update_snapshots"
+        ]
+    )]
+    integration: bool,
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
 }
 
-pub fn run(args: TestArgs) -> anyhow::Result<()> {
-    let source_dir = if args.path.is_absolute() {
-        args.path
-    } else {
-        let mut current_path = current_dir().unwrap();
-        current_path.push(args.path);
-        Utf8PathBuf::from_path_buf(current_path).unwrap()
+pub fn run(args: TestArgs, timeout: Option<Duration>) -> anyhow::Result<()> {
+    let source_dir = match args.path {
+        Some(path) if path.is_absolute() => path,
+        Some(path) => {
+            let mut current_path = current_dir().unwrap();
+            current_path.push(path);
+            Utf8PathBuf::from_path_buf(current_path).unwrap()
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
     };
 
+    if args.integration {
+        let filter = args.filter.unwrap_or_default();
+        return run_integration(source_dir, &filter, args.list, args.profile_spec, timeout);
+    }
+
     let mut compilers = CompilerRepository::std();
-    compilers.add(Box::new(DojoTestCompiler)).unwrap();
+    compilers.add(Box::new(DojoTestCompiler {
+        filter: args.filter.unwrap_or_default(),
+        exact: args.exact,
+        list: args.list,
+        include_ignored: args.include_ignored,
+        ignored: args.ignored,
+        fuzz_runs: args.fuzz_runs,
+        fuzz_seed: args.fuzz_seed.unwrap_or_else(rand::random),
+        snapshots_dir: source_dir.join(SNAPSHOTS_DIR),
+        update_snapshots: args.update_snapshots,
+    }))
+    .unwrap();
 
     let cairo_plugins = CairoPluginRepository::new();
 
@@ -61,7 +120,71 @@ pub fn run(args: TestArgs) -> anyhow::Result<()> {
     ops::compile(&ws)
 }
 
-pub struct DojoTestCompiler;
+/// Builds the world and runs the integration tests matching the filter against it.
+fn run_integration(
+    source_dir: Utf8PathBuf,
+    filter: &str,
+    list: bool,
+    profile_spec: ProfileSpec,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let scenarios = integration::collect_scenarios(&source_dir, filter)?;
+    if list {
+        for scenario in &scenarios {
+            println!("{}: integration test", scenario.file_stem().unwrap_or(scenario.as_str()));
+        }
+        println!("\n{} tests", scenarios.len());
+        return Ok(());
+    }
+    if scenarios.is_empty() {
+        println!("running 0 integration tests, add them to {INTEGRATION_TESTS_DIR}");
+        return Ok(());
+    }
+
+    let profile = profile_spec.determine()?;
+    build::run(BuildArgs {
+        path: Some(source_dir.clone()),
+        check: false,
+        output_abis: None,
+        offline: false,
+        profile_spec,
+    })?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime
+        .block_on(run_cancellable(integration::run(target_dir, &scenarios), timeout))
+        .map_err(|reason| anyhow!("Integration tests {reason}"))?
+}
+
+pub struct DojoTestCompiler {
+    pub filter: String,
+    pub exact: bool,
+    pub list: bool,
+    pub include_ignored: bool,
+    pub ignored: bool,
+    pub fuzz_runs: usize,
+    pub fuzz_seed: u64,
+    pub snapshots_dir: Utf8PathBuf,
+    pub update_snapshots: bool,
+}
+
+impl DojoTestCompiler {
+    fn matches(&self, test: &str) -> bool {
+        if self.exact { test == self.filter } else { test.contains(&self.filter) }
+    }
+
+    /// Runs the snapshot and property tests, after the unit tests.
+    fn run_generated(
+        &self,
+        db: &RootDatabase,
+        snapshot_tests: &[SnapshotTest],
+        property_tests: &[PropertyTest],
+    ) -> Result<()> {
+        snapshot::run(db, snapshot_tests, &self.snapshots_dir, self.update_snapshots)?;
+        property::run(db, property_tests, self.fuzz_runs, self.fuzz_seed)
+    }
+}
 
 impl Compiler for DojoTestCompiler {
     fn target_kind(&self) -> &str {
@@ -80,18 +203,97 @@ impl Compiler for DojoTestCompiler {
             bail!("failed to compile");
         }
 
+        let tests = collect_functions(&*db, &main_crate_ids, "test")
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect::<Vec<_>>();
+        // Snapshot and property tests aren't ignored, they only run along with the others.
+        let (snapshot_tests, property_tests) = if self.ignored {
+            (vec![], vec![])
+        } else {
+            let matching = |attribute| {
+                collect_functions(&*db, &main_crate_ids, attribute)
+                    .into_iter()
+                    .filter(|(_, path)| self.matches(path))
+                    .collect::<Vec<_>>()
+            };
+            (snapshot::collect(db, matching("snapshot"))?, property::collect(db, matching("fuzz"))?)
+        };
+
+        if self.list {
+            let tests = tests.iter().filter(|test| self.matches(test)).collect::<Vec<_>>();
+            for test in &tests {
+                println!("{test}: test");
+            }
+            for test in &snapshot_tests {
+                println!("{}: snapshot test", test.path);
+            }
+            for test in &property_tests {
+                println!("{}: property test", test.path);
+            }
+            println!("\n{} tests", tests.len() + snapshot_tests.len() + property_tests.len());
+            return Ok(());
+        }
+
+        // The runner only filters by substring, an exact filter can only be passed on to it when
+        // no other test path contains the test's.
+        if self.exact {
+            if !tests.contains(&self.filter) {
+                if snapshot_tests.is_empty() && property_tests.is_empty() {
+                    println!("running 0 tests");
+                }
+                return self.run_generated(db, &snapshot_tests, &property_tests);
+            }
+            let others = tests
+                .iter()
+                .filter(|test| **test != self.filter && test.contains(&self.filter))
+                .collect::<Vec<_>>();
+            if !others.is_empty() {
+                bail!(
+                    "`--exact {}` can't exclude the tests containing it: {}",
+                    self.filter,
+                    others.iter().map(|t| t.as_str()).collect::<Vec<_>>().join(", ")
+                );
+            }
+        }
+
         let runner = TestRunner {
             db: db.snapshot(),
             main_crate_ids,
-            // TODO: Pass these in
-            filter: "".to_string(),
-            include_ignored: false,
-            ignored: false,
+            filter: self.filter.clone(),
+            include_ignored: self.include_ignored,
+            ignored: self.ignored,
             starknet: true,
         };
 
         runner.run()?;
 
-        Ok(())
+        self.run_generated(db, &snapshot_tests, &property_tests)
     }
 }
+
+/// Free functions annotated with `#[<attribute>]` in the main crates, along with their paths.
+fn collect_functions(
+    db: &dyn SemanticGroup,
+    main_crate_ids: &[CrateId],
+    attribute: &str,
+) -> Vec<(FreeFunctionId, String)> {
+    let mut functions = vec![];
+    for crate_id in main_crate_ids {
+        for module_id in db.crate_modules(*crate_id).iter() {
+            let Ok(module_functions) = db.module_free_functions_ids(*module_id) else {
+                continue;
+            };
+            for function_id in module_functions.iter() {
+                let attributes =
+                    db.function_with_body_attributes(FunctionWithBodyId::Free(*function_id));
+                if attributes.map_or(false, |attrs| attrs.iter().any(|attr| attr.id == attribute))
+                {
+                    functions.push((*function_id, function_id.full_path(db.upcast())));
+                }
+            }
+        }
+    }
+
+    functions
+}
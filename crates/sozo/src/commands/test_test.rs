@@ -0,0 +1,34 @@
+use cairo_lang_filesystem::db::FilesGroup;
+use cairo_lang_filesystem::ids::CrateLongId;
+use dojo_test_utils::compiler::{build_test_config, build_test_db};
+use scarb::ops;
+
+use super::collect_functions;
+
+#[test]
+fn test_collect_functions() {
+    let config = build_test_config("../../examples/ecs/Scarb.toml").unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config).unwrap();
+    let db = build_test_db(&ws).unwrap();
+    let crate_ids = vec![db.intern_crate(CrateLongId("dojo_examples".into()))];
+
+    let paths = |attribute| {
+        let mut paths = collect_functions(&db, &crate_ids, attribute)
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    };
+
+    assert_eq!(
+        paths("test"),
+        vec![
+            "dojo_examples::components::test_position_is_equal",
+            "dojo_examples::components::test_position_is_zero",
+            "dojo_examples::systems::tests::test_move",
+        ]
+    );
+    assert_eq!(paths("fuzz"), vec!["dojo_examples::components::test_position_equals_itself"]);
+    assert!(paths("snapshot").is_empty());
+}
@@ -0,0 +1,587 @@
+use std::collections::HashMap;
+use std::env::{self, current_dir};
+use std::fs;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::{Manifest, Member};
+use dojo_world::migration::deployment::DeploymentManifest;
+use dojo_world::migration::finality::wait_for_finality;
+use dojo_world::migration::object::WorldContract;
+use dojo_world::migration::schema::{decode_entities, member_values, SchemaUpgrade};
+use dojo_world::migration::strategy::{prepare_for_upgrade, MigrationStrategy};
+use dojo_world::migration::world::WorldDiff;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::accounts::{Account, ConnectedAccount};
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::providers::Provider;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+use super::call::{call, entity_values};
+use super::migrate::print_plan;
+use crate::cancellation::run_cancellable;
+use crate::confirm::confirm;
+use crate::fee::FeeArgs;
+use crate::receipts::{History, HistoryEntry};
+
+const DEPLOYMENT_FILE: &str = "manifest.json";
+
+/// Entities left to migrate by the last upgrade, removed once they all are.
+const TRANSFORMS_FILE: &str = "upgrade.json";
+
+/// Entities migrated per transaction by the upgrade transforms, unless `max_calls_per_tx` is set.
+const TRANSFORM_CALLS_PER_TX: usize = 50;
+
+/// A component upgraded with a transform run on-chain through a system.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+struct Transform {
+    component: String,
+    /// Class of the component registered by the upgrade.
+    #[serde_as(as = "UfeHex")]
+    class_hash: FieldElement,
+    system: String,
+    upgrade: SchemaUpgrade,
+    /// Members of the component before the upgrade.
+    previous: Vec<Member>,
+    members: Vec<Member>,
+    /// Partitions, ids and raw values of the entities, read with the previous schema once the
+    /// upgrade is final. `None` until then.
+    #[serde_as(as = "Option<Vec<(UfeHex, UfeHex, Vec<UfeHex>)>>")]
+    entities: Option<Vec<(FieldElement, FieldElement, Vec<FieldElement>)>>,
+    /// Number of entities already sent to the system.
+    migrated: usize,
+}
+
+/// Transforms of an upgrade, written before the upgrade is sent and updated as the entities are
+/// read and migrated, so that a run interrupted midway is resumed by the next one.
+#[serde_as]
+#[derive(Debug, Serialize, Deserialize)]
+struct PendingTransforms {
+    #[serde_as(as = "UfeHex")]
+    world: FieldElement,
+    transforms: Vec<Transform>,
+}
+
+impl PendingTransforms {
+    fn load_from_path(path: &Utf8Path) -> Result<Self> {
+        let content = fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {path}"))
+    }
+
+    fn write_to_path(&self, path: &Utf8Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create the directory {parent}"))?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {path}"))
+    }
+}
+
+#[derive(Args)]
+pub struct UpgradeArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long)]
+    #[clap(help = "Print the transactions the upgrade would send and their estimated fees, \
+                   without sending anything")]
+    dry_run: bool,
+
+    #[clap(short, long, help = "Don't ask for confirmation on the protected chains")]
+    yes: bool,
+
+    #[command(flatten)]
+    fee: FeeArgs,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: UpgradeArgs, timeout: Option<Duration>) -> Result<()> {
+    dotenv().ok();
+
+    let UpgradeArgs { path, world, dry_run, yes, fee, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let manifest_path = source_dir.join("Scarb.toml");
+    let config = Config::builder(manifest_path)
+        .ui_verbosity(Verbosity::Verbose)
+        .log_filter_directive(env::var_os("SCARB_LOG"))
+        .build()
+        .unwrap();
+    let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+    let profile = profile_spec.determine()?;
+    let target_dir = source_dir.join(format!("target/{}", profile.as_str()));
+
+    if !target_dir.join("manifest.json").exists() {
+        build::run(BuildArgs {
+            path: Some(source_dir.clone()),
+            check: false,
+            output_abis: None,
+            offline: false,
+            profile_spec,
+        })?;
+    }
+
+    let mut world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+    world_config.address = Some(
+        world
+            .or(world_config.address)
+            .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?,
+    );
+    let env_config = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?;
+    let history = History::new(&source_dir, profile.as_str());
+
+    let local_manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+    let deployment_path =
+        source_dir.join(format!("deployments/{}/{DEPLOYMENT_FILE}", profile.as_str()));
+    let previous_deployment = if deployment_path.exists() {
+        Some(DeploymentManifest::load_from_path(&deployment_path)?)
+    } else {
+        None
+    };
+
+    ws.config().tokio_handle().block_on(async {
+        let migrator = env_config.migrator().await?;
+        let diff = WorldDiff::from_path(target_dir.clone(), &world_config, &env_config).await?;
+
+        if matches!(diff.world.remote, Some(remote) if remote != diff.world.local) {
+            eprintln!(
+                "warning: the world class changed, it can't be upgraded in place. Migrate with \
+                 `--strategy force-redeploy` to deploy a new world instead."
+            );
+        }
+        let new_classes = diff
+            .components
+            .iter()
+            .chain(&diff.systems)
+            .filter(|c| c.remote.is_none())
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        if !new_classes.is_empty() {
+            println!(
+                "Not registered yet, use `sozo register` to add them: {}\n",
+                new_classes.join(", ")
+            );
+        }
+
+        let provider = env_config.provider()?;
+        let world_address = world_config.address.expect("world address is set");
+        let transforms_path =
+            source_dir.join(format!("deployments/{}/{TRANSFORMS_FILE}", profile.as_str()));
+        let transforms = transforms(&local_manifest, &diff, previous_deployment.as_ref())?;
+        // classes of the components registered on the world, as returned by its `component`
+        let registered = diff
+            .components
+            .iter()
+            .filter_map(|c| Some((c.name.clone(), c.remote?)))
+            .collect::<HashMap<_, _>>();
+
+        // Only what the upgrade registers is recorded, the rest of the world is left as is.
+        let mut upgraded_manifest = local_manifest.clone();
+        upgraded_manifest.world = diff.world.remote.unwrap_or(local_manifest.world);
+        upgraded_manifest.components.retain(|c| !new_classes.contains(&c.name.as_str()));
+        upgraded_manifest
+            .systems
+            .retain(|s| !new_classes.contains(&s.name.strip_suffix("System").unwrap_or(&s.name)));
+
+        let mut upgrade = prepare_for_upgrade(target_dir.clone(), diff, world_config)?;
+        let chain_id = migrator.chain_id();
+        upgrade.fees = fee.migration_fee_config(&env_config);
+        upgrade.retry = env_config.retry_policy();
+        upgrade.finality = env_config.finality(chain_id);
+
+        if transforms_path.exists() {
+            let mut pending = PendingTransforms::load_from_path(&transforms_path)?;
+            if pending.world != world_address {
+                bail!(
+                    "{transforms_path} holds the entities left to migrate on the world {:#x}, \
+                     remove it to upgrade this one",
+                    pending.world
+                );
+            }
+            // The upgrade was interrupted before its entities were read. The components it
+            // didn't register are upgraded again below, along with their transforms.
+            pending.transforms.retain(|transform| {
+                transform.entities.is_some()
+                    || registered.get(&transform.component) == Some(&transform.class_hash)
+            });
+
+            if dry_run {
+                for transform in &pending.transforms {
+                    match &transform.entities {
+                        Some(entities) => println!(
+                            "{} entities of {} are left to migrate through `{}`",
+                            entities.len() - transform.migrated,
+                            transform.component,
+                            transform.system
+                        ),
+                        None => println!(
+                            "The entities of {} are left to migrate through `{}`",
+                            transform.component, transform.system
+                        ),
+                    }
+                }
+            } else if pending.transforms.is_empty() {
+                fs::remove_file(&transforms_path)
+                    .with_context(|| format!("Failed to remove {transforms_path}"))?;
+            } else {
+                println!("Resuming the migration of the entities of the previous upgrade");
+                snapshot_pending(&provider, &mut pending, &transforms_path).await?;
+                migrate_entities(&upgrade, &migrator, pending, &transforms_path, &history, timeout)
+                    .await?;
+            }
+        }
+
+        if upgrade.executor.is_none() && upgrade.components.is_empty() && upgrade.systems.is_empty()
+        {
+            println!("Nothing to upgrade, the world is up to date.");
+            return Ok(());
+        }
+
+        let protected = !yes && env_config.is_protected(chain_id);
+        if dry_run || fee.needs_estimate() || protected {
+            let plan = run_cancellable(upgrade.plan(&migrator), timeout)
+                .await
+                .map_err(|reason| anyhow!("Planning the upgrade {reason}"))??;
+            if dry_run {
+                print_plan(&plan);
+                for transform in &transforms {
+                    println!(
+                        "The entities of {} are then migrated through `{}`",
+                        transform.component, transform.system
+                    );
+                }
+                return Ok(());
+            }
+            if protected {
+                print_plan(&plan);
+                if !confirm("Upgrade the world", chain_id)? {
+                    println!("Upgrade cancelled.");
+                    return Ok(());
+                }
+            }
+            if fee.needs_estimate() && !fee.check_plan(&migrator, &plan, false).await? {
+                return Ok(());
+            }
+        }
+
+        // Recorded first, so that the entities are still migrated if the run is interrupted
+        // once the upgrade is sent.
+        let mut pending = PendingTransforms { world: world_address, transforms };
+        if !pending.transforms.is_empty() {
+            pending.write_to_path(&transforms_path)?;
+        }
+
+        let sender = migrator.address();
+        let res = run_cancellable(
+            async { upgrade.execute(migrator).await.map_err(|e| anyhow!("{e}")) },
+            timeout,
+        )
+        .await;
+
+        let entries = upgrade
+            .checkpoint
+            .submitted
+            .iter()
+            .map(|tx| {
+                HistoryEntry::submitted(
+                    "upgrade",
+                    &tx.description,
+                    tx.transaction_hash,
+                    tx.calldata.clone(),
+                    Some(tx.sender.unwrap_or(sender)),
+                )
+            })
+            .collect::<Vec<_>>();
+        if let Err(e) = history.append(&entries) {
+            eprintln!("warning: failed to record the upgrade in the history: {e}");
+        }
+
+        match res {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => return Err(anyhow!("Problem when trying to upgrade: {e}")),
+            Err(reason) => return Err(anyhow!("Upgrade {reason}")),
+        }
+
+        let mut deployment =
+            upgrade.deployment_manifest(&upgraded_manifest, previous_deployment.as_ref());
+        if let Err(e) = deployment.resolve_block_numbers(&provider).await {
+            eprintln!("warning: {e}");
+        }
+        deployment.write_to_path(&deployment_path)?;
+        println!("\nDeployment manifest written to {deployment_path}");
+
+        if pending.transforms.is_empty() {
+            return Ok(());
+        }
+
+        snapshot_pending(&provider, &mut pending, &transforms_path).await?;
+        let migrator = env_config.migrator().await?;
+        migrate_entities(&upgrade, &migrator, pending, &transforms_path, &history, timeout).await
+    })
+}
+
+/// The components whose class changes and whose upgrade transform runs through a system. The
+/// previous schema is the one the deployment manifest records for the class registered on the
+/// world.
+fn transforms(
+    manifest: &Manifest,
+    diff: &WorldDiff,
+    deployment: Option<&DeploymentManifest>,
+) -> Result<Vec<Transform>> {
+    let mut transforms = vec![];
+    for class in &diff.components {
+        let Some(remote) = class.remote.filter(|remote| *remote != class.local) else {
+            continue;
+        };
+        let Some(component) = manifest.components.iter().find(|c| c.name == class.name) else {
+            continue;
+        };
+        let Some((upgrade, system)) =
+            component.upgrade.as_ref().and_then(|u| Some((u, u.system.clone()?)))
+        else {
+            continue;
+        };
+
+        let registered = diff.systems.iter().any(|s| {
+            s.remote.is_some() && s.name.strip_suffix("System").unwrap_or(&s.name) == system
+        });
+        if !registered {
+            bail!(
+                "`{system}`, which migrates the entities of {}, must be registered before the \
+                 upgrade, use `sozo register system {system}`",
+                class.name
+            );
+        }
+
+        let previous = deployment
+            .and_then(|d| d.components.iter().find(|c| c.name == class.name))
+            .filter(|c| c.class_hash == remote && !c.members.is_empty())
+            .map(|c| c.members.clone())
+            .ok_or_else(|| {
+                anyhow!(
+                    "The schema of {}, registered on the world as {remote:#x}, isn't recorded in \
+                     the deployment manifest, it's needed to migrate its entities",
+                    class.name
+                )
+            })?;
+        upgrade
+            .validate(&previous, &component.members)
+            .with_context(|| format!("Invalid upgrade of {}", class.name))?;
+
+        transforms.push(Transform {
+            component: component.name.clone(),
+            class_hash: class.local,
+            system,
+            upgrade: upgrade.clone(),
+            previous,
+            members: component.members.clone(),
+            entities: None,
+            migrated: 0,
+        });
+    }
+    Ok(transforms)
+}
+
+/// Reads the entities of the transforms that aren't read yet, then records them. The entities
+/// are read once the new schema is registered, so that the writes made until then are migrated
+/// too. Their raw values are still laid out as before.
+async fn snapshot_pending<P>(
+    provider: &P,
+    pending: &mut PendingTransforms,
+    path: &Utf8Path,
+) -> Result<()>
+where
+    P: Provider + Sync,
+    P::Error: 'static,
+{
+    for transform in pending.transforms.iter_mut().filter(|t| t.entities.is_none()) {
+        let entities = snapshot(provider, pending.world, transform)
+            .await
+            .with_context(|| format!("Failed to read the entities of {}", transform.component))?;
+        transform.entities = Some(entities);
+    }
+    pending.write_to_path(path)
+}
+
+/// Reads the partitions, ids and raw values of the entities of a component, in the default
+/// partition and in the ones listed by its upgrade.
+async fn snapshot<P>(
+    provider: &P,
+    world_address: FieldElement,
+    transform: &Transform,
+) -> Result<Vec<(FieldElement, FieldElement, Vec<FieldElement>)>>
+where
+    P: Provider + Sync,
+    P::Error: 'static,
+{
+    let name = &transform.component;
+    let component = cairo_short_string_to_felt(name)?;
+    let mut entities = vec![];
+
+    // the ids are an array, prefixed with its length
+    let ids =
+        call(provider, world_address, "entity_ids", vec![component, FieldElement::ZERO]).await?;
+    for id in ids.into_iter().skip(1) {
+        // an entity with a single key and no partition is stored under its key
+        let values = entity_values(
+            provider,
+            world_address,
+            name,
+            &transform.previous,
+            FieldElement::ZERO,
+            vec![id],
+        )
+        .await?;
+        entities.push((FieldElement::ZERO, id, values));
+    }
+
+    // The ids of the other partitions are hashes of their keys, the entities can only be read
+    // whole, with as many values as the new schema takes.
+    for &partition in &transform.upgrade.partitions {
+        let res = call(provider, world_address, "entities", vec![component, partition]).await?;
+        for (id, values) in decode_entities(&res)? {
+            member_values(&transform.previous, &values).with_context(|| {
+                format!(
+                    "The entity {id:#x} of the partition {partition:#x} holds fewer values than \
+                     the previous schema of {name} takes"
+                )
+            })?;
+            entities.push((partition, id, values));
+        }
+    }
+    Ok(entities)
+}
+
+/// Migrates the entities left, recording them in the history, and removes the pending
+/// transforms once they're all migrated.
+async fn migrate_entities<A>(
+    upgrade: &MigrationStrategy,
+    migrator: &A,
+    mut pending: PendingTransforms,
+    path: &Utf8Path,
+    history: &History,
+    timeout: Option<Duration>,
+) -> Result<()>
+where
+    A: ConnectedAccount + Sync,
+{
+    let mut entries = vec![];
+    let res = run_cancellable(
+        run_transforms(upgrade, migrator, &mut pending, path, &mut entries),
+        timeout,
+    )
+    .await;
+    if let Err(e) = history.append(&entries) {
+        eprintln!("warning: failed to record the entity migrations in the history: {e}");
+    }
+
+    match res {
+        Ok(Ok(())) => {
+            fs::remove_file(path).with_context(|| format!("Failed to remove {path}"))?;
+            Ok(())
+        }
+        Ok(Err(e)) => Err(e.context(format!(
+            "The entities left are migrated by the next `sozo upgrade`, as recorded in {path}"
+        ))),
+        Err(reason) => Err(anyhow!(
+            "Migrating the entities {reason}, the next `sozo upgrade` migrates the ones left"
+        )),
+    }
+}
+
+/// Sends the entities of every transformed component, with their new values, to its system.
+/// The progress is written to `path` after every transaction.
+async fn run_transforms<A>(
+    upgrade: &MigrationStrategy,
+    migrator: &A,
+    pending: &mut PendingTransforms,
+    path: &Utf8Path,
+    entries: &mut Vec<HistoryEntry>,
+) -> Result<()>
+where
+    A: ConnectedAccount + Sync,
+{
+    let world = WorldContract::new(pending.world, migrator)
+        .with_fees(upgrade.fees)
+        .with_retry(upgrade.retry);
+    let batch_size = upgrade.max_calls_per_tx.unwrap_or(TRANSFORM_CALLS_PER_TX).max(1);
+
+    for index in 0..pending.transforms.len() {
+        let Transform { component, system, upgrade: schema_upgrade, previous, members, .. } =
+            &pending.transforms[index];
+        let system_name = cairo_short_string_to_felt(system)?;
+
+        let mut calls = vec![];
+        let entities = pending.transforms[index].entities.as_deref().unwrap_or_default();
+        for (partition, id, values) in entities {
+            let values = member_values(previous, values)
+                .with_context(|| format!("Failed to decode {component} of entity {id:#x}"))?;
+            let values = schema_upgrade.apply(members, &values);
+
+            let mut calldata = vec![*partition, *id, FieldElement::from(values.len())];
+            calldata.extend(values);
+            calls.push(world.execute_call(system_name, calldata));
+        }
+        let (component, system) = (component.clone(), system.clone());
+
+        let migrated = pending.transforms[index].migrated;
+        for batch in calls[migrated.min(calls.len())..].chunks(batch_size) {
+            let transaction_hash = world
+                .send_calls(batch.to_vec())
+                .await
+                .map_err(|e| {
+                    anyhow!(
+                        "Failed to migrate the entities of {component} after {} of {}: {e}",
+                        pending.transforms[index].migrated,
+                        calls.len()
+                    )
+                })?
+                .transaction_hash;
+            let provider = migrator.provider();
+            wait_for_finality(provider, transaction_hash, upgrade.finality, &upgrade.retry).await?;
+            entries.push(HistoryEntry::submitted(
+                "upgrade",
+                &format!("migrate {component} entities"),
+                transaction_hash,
+                vec![],
+                Some(migrator.address()),
+            ));
+
+            pending.transforms[index].migrated += batch.len();
+            pending.write_to_path(path)?;
+        }
+        println!("{} entities of {component} migrated through `{system}`", calls.len());
+    }
+
+    Ok(())
+}
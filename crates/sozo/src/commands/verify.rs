@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+use std::env::{self, current_dir};
+
+use anyhow::{anyhow, bail, Result};
+use camino::Utf8PathBuf;
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, WorldConfig};
+use dojo_world::manifest::{BuildInfo, Manifest};
+use dojo_world::migration::deployment::DeploymentManifest;
+use dotenv::dotenv;
+use scarb::core::Config;
+use scarb::ops;
+use scarb::ui::Verbosity;
+use starknet::core::types::FieldElement;
+
+use super::build::{self, BuildArgs, ProfileSpec};
+
+const DEPLOYMENT_FILE: &str = "manifest.json";
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    #[clap(help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[clap(long)]
+    #[clap(help = "Rebuild the project and compare the class hashes against the last deployment \
+                   of the profile, or against `--manifest`")]
+    reproduce: bool,
+
+    #[clap(long, requires = "reproduce")]
+    #[clap(help = "Build manifest to verify against instead of the last deployment")]
+    manifest: Option<Utf8PathBuf>,
+
+    #[clap(long)]
+    #[clap(help = "Rebuild the project and compare the class hashes against the deployed world")]
+    onchain: bool,
+
+    #[clap(long, requires = "onchain")]
+    #[clap(help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: VerifyArgs) -> Result<()> {
+    dotenv().ok();
+
+    let VerifyArgs { path, reproduce, manifest, onchain, world, profile_spec } = args;
+
+    if !reproduce && !onchain {
+        bail!(
+            "Nothing to verify, use `--reproduce` to rebuild and compare against a manifest or \
+             `--onchain` to compare against the deployed world"
+        );
+    }
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let profile = profile_spec.determine()?;
+    let target_manifest = source_dir.join(format!("target/{}/manifest.json", profile.as_str()));
+
+    // The target manifest is the output of the rebuild, what it's compared to comes from elsewhere.
+    let expected = match (reproduce, &manifest) {
+        (false, _) => None,
+        (true, Some(path)) => {
+            let expected = Manifest::load_from_path(path)?;
+            Some(Expected::Build(expected.build_info.ok_or_else(|| {
+                anyhow!("Manifest has no build info, was it generated by `sozo build`?")
+            })?))
+        }
+        (true, None) => {
+            let deployment_path =
+                source_dir.join(format!("deployments/{}/{DEPLOYMENT_FILE}", profile.as_str()));
+            if !deployment_path.exists() {
+                bail!(
+                    "No deployment found at {deployment_path}, migrate the world first or pass \
+                     `--manifest`"
+                );
+            }
+            Some(Expected::Deployment(DeploymentManifest::load_from_path(&deployment_path)?))
+        }
+    };
+
+    build::run(BuildArgs {
+        path: Some(source_dir.clone()),
+        check: false,
+        output_abis: None,
+        offline: false,
+        profile_spec,
+    })?;
+
+    let actual = Manifest::load_from_path(&target_manifest)?;
+    let mut verified = true;
+
+    if let Some(expected) = expected {
+        let reproduced = match expected {
+            Expected::Build(expected_info) => {
+                let actual_info = actual.build_info.clone().unwrap_or_default();
+                verify_build_info(&expected_info, &actual_info)
+            }
+            Expected::Deployment(deployment) => verify_deployment(&actual, &deployment),
+        };
+        if reproduced {
+            println!("Build reproduced, all class hashes match.");
+        } else {
+            println!("Build could not be reproduced.");
+            verified = false;
+        }
+    }
+
+    if onchain {
+        let config = Config::builder(source_dir.join("Scarb.toml"))
+            .ui_verbosity(Verbosity::Verbose)
+            .log_filter_directive(env::var_os("SCARB_LOG"))
+            .build()
+            .unwrap();
+        let ws = ops::read_workspace(config.manifest_path(), &config)?;
+
+        let world_config = WorldConfig::from_workspace(&ws).unwrap_or_default();
+        let world_address = world
+            .or(world_config.address)
+            .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+        let provider = EnvironmentConfig::from_workspace(profile.as_str(), &ws)?.provider()?;
+
+        let remote = ws.config().tokio_handle().block_on(Manifest::from_remote(
+            provider,
+            world_address,
+            Some(actual.clone()),
+        ))?;
+
+        if verify_remote(&actual, &remote) {
+            println!("World {world_address:#x} matches the local build.");
+        } else {
+            println!("World {world_address:#x} has drifted from the local build.");
+            verified = false;
+        }
+    }
+
+    if !verified {
+        bail!("Verification failed.");
+    }
+
+    Ok(())
+}
+
+/// What a rebuild is compared to.
+enum Expected {
+    /// Fingerprints of a build manifest.
+    Build(BuildInfo),
+    /// Classes of the last deployment of the profile.
+    Deployment(DeploymentManifest),
+}
+
+/// Prints the class hash of every contract of the deployment against the rebuilt one, and
+/// returns whether they all match.
+fn verify_deployment(local: &Manifest, deployment: &DeploymentManifest) -> bool {
+    let mut classes = vec![
+        ("World".to_string(), deployment.world.class_hash, Some(local.world)),
+        ("Executor".to_string(), deployment.executor.class_hash, Some(local.executor)),
+    ];
+    classes.extend(deployment.components.iter().map(|component| {
+        let rebuilt = local.components.iter().find(|c| c.name == component.name);
+        (component.name.clone(), component.class_hash, rebuilt.map(|c| c.class_hash))
+    }));
+    classes.extend(deployment.systems.iter().map(|system| {
+        let rebuilt = local
+            .systems
+            .iter()
+            .find(|s| s.name.strip_suffix("System").unwrap_or(&s.name) == system.name);
+        (system.name.clone(), system.class_hash, rebuilt.map(|s| s.class_hash))
+    }));
+
+    let mut matching = true;
+    for (name, deployed, rebuilt) in classes {
+        match rebuilt {
+            None => {
+                matching = false;
+                println!("{name}: missing from the build");
+            }
+            Some(rebuilt) if rebuilt != deployed => {
+                matching = false;
+                println!("{name}: class hash mismatch, deployed {deployed:#x} got {rebuilt:#x}");
+            }
+            Some(_) => println!("{name}: {deployed:#x} ok"),
+        }
+    }
+
+    matching
+}
+
+/// Prints the class hash of every component and system registered in the world against the
+/// locally built one, and returns whether they all match. A zero class hash means the class
+/// isn't registered.
+fn verify_remote(local: &Manifest, remote: &Manifest) -> bool {
+    let mut classes = vec![
+        ("World".to_string(), local.world, remote.world),
+        ("Executor".to_string(), local.executor, remote.executor),
+    ];
+    classes.extend(local.components.iter().zip(&remote.components).map(|(local, remote)| {
+        (local.name.clone(), local.class_hash, remote.class_hash)
+    }));
+    classes.extend(
+        local
+            .systems
+            .iter()
+            .zip(&remote.systems)
+            .map(|(local, remote)| (local.name.to_string(), local.class_hash, remote.class_hash)),
+    );
+
+    let mut matching = true;
+    for (name, local, remote) in classes {
+        if remote == FieldElement::ZERO {
+            matching = false;
+            println!("{name}: not registered in the world");
+        } else if remote != local {
+            matching = false;
+            println!("{name}: class hash drift, local {local:#x} on-chain {remote:#x}");
+        } else {
+            println!("{name}: {local:#x} ok");
+        }
+    }
+
+    matching
+}
+
+/// Prints the differences between the two builds and returns whether they produced the same
+/// classes.
+fn verify_build_info(expected: &BuildInfo, actual: &BuildInfo) -> bool {
+    if expected.compiler_version != actual.compiler_version {
+        println!(
+            "warning: compiler version differs, expected {} got {}",
+            expected.compiler_version, actual.compiler_version
+        );
+    }
+    if expected.plugin_version != actual.plugin_version {
+        println!(
+            "warning: plugin version differs, expected {} got {}",
+            expected.plugin_version, actual.plugin_version
+        );
+    }
+
+    let actual_contracts: HashMap<_, _> = actual.contracts.iter().map(|c| (&c.name, c)).collect();
+
+    let mut reproduced = true;
+    for contract in &expected.contracts {
+        match actual_contracts.get(&contract.name) {
+            None => {
+                reproduced = false;
+                println!("{}: missing from the build", contract.name);
+            }
+            Some(rebuilt) if rebuilt.class_hash != contract.class_hash => {
+                reproduced = false;
+                println!(
+                    "{}: class hash mismatch, expected {:#x} got {:#x}{}",
+                    contract.name,
+                    contract.class_hash,
+                    rebuilt.class_hash,
+                    if rebuilt.source_hash != contract.source_hash {
+                        " (source changed)"
+                    } else {
+                        ""
+                    }
+                );
+            }
+            Some(_) => println!("{}: {:#x} ok", contract.name, contract.class_hash),
+        }
+    }
+
+    reproduced
+}
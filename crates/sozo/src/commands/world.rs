@@ -0,0 +1,173 @@
+use std::env::current_dir;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use camino::Utf8PathBuf;
+use clap::{Args, Subcommand};
+use dojo_world::manifest::EXECUTOR_ADDRESS_SLOT;
+use dojo_world::migration::deployment::{registered_classes_from_remote, world_spawn};
+use dotenv::dotenv;
+use serde_json::json;
+use starknet::core::types::{BlockId, BlockTag, FieldElement};
+use starknet::core::utils::get_storage_var_address;
+use starknet::providers::Provider;
+use url::Url;
+
+use super::build::ProfileSpec;
+use super::component::chain_config;
+use crate::cancellation::run_cancellable;
+use crate::porcelain;
+
+#[derive(Args)]
+pub struct WorldArgs {
+    #[command(subcommand)]
+    command: WorldCommands,
+}
+
+#[derive(Subcommand)]
+pub enum WorldCommands {
+    #[command(about = "Print the addresses, creator and registrations of a world, read from the \
+                       chain")]
+    Info(InfoArgs),
+}
+
+#[derive(Args)]
+pub struct InfoArgs {
+    #[clap(long, help = "Address of the world, defaults to the `world_address` in Scarb.toml")]
+    world: Option<FieldElement>,
+
+    #[clap(long, help = "RPC endpoint of the chain, defaults to the `rpc_url` of the environment")]
+    rpc_url: Option<Url>,
+
+    #[clap(long, default_value_t = 0)]
+    #[clap(help = "First block to read the world's history from, its creator is only found if it \
+                   was deployed after it")]
+    from_block: u64,
+
+    #[clap(long, help = "Output the info as JSON")]
+    json: bool,
+
+    #[clap(long, help = "Source directory")]
+    path: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    profile_spec: ProfileSpec,
+}
+
+pub fn run(args: WorldArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    match args.command {
+        WorldCommands::Info(args) => info(args, timeout, porcelain),
+    }
+}
+
+/// Prints what the chain knows about a world. The world doesn't store an owner nor a metadata
+/// URI, its creator is the account that spawned it.
+fn info(args: InfoArgs, timeout: Option<Duration>, porcelain: bool) -> Result<()> {
+    dotenv().ok();
+
+    let InfoArgs { world, rpc_url, from_block, json, path, profile_spec } = args;
+
+    let source_dir = match path {
+        Some(path) => {
+            if path.is_absolute() {
+                path
+            } else {
+                let mut current_path = current_dir().unwrap();
+                current_path.push(path);
+                Utf8PathBuf::from_path_buf(current_path).unwrap()
+            }
+        }
+        None => Utf8PathBuf::from_path_buf(current_dir().unwrap()).unwrap(),
+    };
+
+    let (world_config, env_config) = chain_config(&source_dir, profile_spec, rpc_url)?;
+    let world_address = world
+        .or(world_config.address)
+        .ok_or(anyhow!("Missing world address, pass `--world` or set `world_address`"))?;
+    let provider = env_config.provider()?;
+
+    let runtime = tokio::runtime::Runtime::new()?;
+    let (class_hash, executor, initialized, spawn, (components, systems)) =
+        runtime.block_on(async {
+            let fetch = async {
+                let pending = BlockId::Tag(BlockTag::Pending);
+                let class_hash = provider
+                    .get_class_hash_at(pending, world_address)
+                    .await
+                    .map_err(|e| anyhow!("Failed to fetch the class of the world: {e}"))?;
+                let executor = provider
+                    .get_storage_at(world_address, EXECUTOR_ADDRESS_SLOT, pending)
+                    .await
+                    .map_err(|e| anyhow!("Failed to read the executor of the world: {e}"))?;
+                let initialized = provider
+                    .get_storage_at(
+                        world_address,
+                        get_storage_var_address("initialized", &[])?,
+                        pending,
+                    )
+                    .await
+                    .map_err(|e| anyhow!("Failed to read the world: {e}"))?;
+                let spawn = world_spawn(&provider, world_address, from_block).await?;
+                let registered =
+                    registered_classes_from_remote(&provider, world_address, from_block).await?;
+
+                Ok::<_, anyhow::Error>((
+                    class_hash,
+                    executor,
+                    initialized != FieldElement::ZERO,
+                    spawn,
+                    registered,
+                ))
+            };
+            run_cancellable(fetch, timeout)
+                .await
+                .map_err(|reason| anyhow!("Reading the world {reason}"))?
+        })?;
+
+    if porcelain {
+        porcelain::record(
+            "world",
+            [
+                format!("{world_address:#x}"),
+                porcelain::optional(spawn.as_ref().map(|spawn| &spawn.name)),
+                format!("{class_hash:#x}"),
+                format!("{executor:#x}"),
+                porcelain::optional(spawn.as_ref().map(|spawn| format!("{:#x}", spawn.creator))),
+                initialized.to_string(),
+                components.len().to_string(),
+                systems.len().to_string(),
+            ],
+        );
+    } else if json {
+        let info = json!({
+            "address": format!("{world_address:#x}"),
+            "class_hash": format!("{class_hash:#x}"),
+            "executor": format!("{executor:#x}"),
+            "initialized": initialized,
+            "spawn": spawn,
+            "components": components.len(),
+            "systems": systems.len(),
+        });
+        println!("{}", serde_json::to_string_pretty(&info)?);
+    } else {
+        println!("World        {world_address:#x}");
+        println!("Class hash   {class_hash:#x}");
+        println!("Executor     {executor:#x}");
+        match &spawn {
+            Some(spawn) => {
+                println!("Name         {}", spawn.name);
+                println!("Creator      {:#x}", spawn.creator);
+                println!(
+                    "Spawned in   {:#x} (block {})",
+                    spawn.transaction_hash, spawn.block_number
+                );
+            }
+            None => println!("Creator      unknown, not spawned after block {from_block}"),
+        }
+        println!("Initialized  {initialized}");
+        println!("Components   {}", components.len());
+        println!("Systems      {}", systems.len());
+    }
+
+    Ok(())
+}
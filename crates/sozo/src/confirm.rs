@@ -0,0 +1,35 @@
+//! Confirmation of the changes sent to the protected chains, those listed in the environment's
+//! `protected_chains`, mainnet by default.
+
+use std::io::{self, BufRead, Write};
+
+use anyhow::{bail, Result};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::parse_cairo_short_string;
+
+#[cfg(test)]
+#[path = "confirm_test.rs"]
+mod test;
+
+/// Asks whether to go on with `action` on the chain, returning the answer. Fails when the input
+/// is closed, as in scripts which are expected to pass `--yes`.
+pub fn confirm(action: &str, chain_id: FieldElement) -> Result<bool> {
+    ask(action, chain_id, &mut io::stdin().lock(), &mut io::stdout())
+}
+
+fn ask<R: BufRead, W: Write>(
+    action: &str,
+    chain_id: FieldElement,
+    input: &mut R,
+    output: &mut W,
+) -> Result<bool> {
+    let chain = parse_cairo_short_string(&chain_id).unwrap_or_else(|_| format!("{chain_id:#x}"));
+    write!(output, "\n{action} on {chain}, a protected chain? [y/N] ")?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    if input.read_line(&mut answer)? == 0 {
+        bail!("No confirmation given for a protected chain, pass `--yes` to skip it");
+    }
+    Ok(matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes"))
+}
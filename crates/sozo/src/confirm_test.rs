@@ -0,0 +1,49 @@
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+use super::ask;
+
+fn answer(input: &str) -> anyhow::Result<bool> {
+    let mainnet = cairo_short_string_to_felt("SN_MAIN").unwrap();
+    ask("Migrate the world", mainnet, &mut input.as_bytes(), &mut Vec::new())
+}
+
+#[test]
+fn test_prompt() {
+    let mainnet = cairo_short_string_to_felt("SN_MAIN").unwrap();
+    let mut output = Vec::new();
+    ask("Migrate the world", mainnet, &mut "y\n".as_bytes(), &mut output).unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "\nMigrate the world on SN_MAIN, a protected chain? [y/N] "
+    );
+
+    // Chain ids which aren't short strings are shown as hex.
+    let mut output = Vec::new();
+    let chain_id = FieldElement::from_hex_be(&format!("0x1{}", "0".repeat(62))).unwrap();
+    ask("Upgrade the world", chain_id, &mut "y\n".as_bytes(), &mut output).unwrap();
+    assert!(String::from_utf8(output).unwrap().contains(&format!("on {chain_id:#x},")));
+}
+
+#[test]
+fn test_accepted_answers() {
+    for input in ["y\n", "yes\n", "Y\n", "YES\n", "  yes  \n", "y"] {
+        assert!(answer(input).unwrap(), "{input:?} should confirm");
+    }
+}
+
+#[test]
+fn test_declined_by_default() {
+    for input in ["\n", "n\n", "no\n", "yep\n", "sure\n"] {
+        assert!(!answer(input).unwrap(), "{input:?} should decline");
+    }
+}
+
+#[test]
+fn test_closed_input_fails() {
+    let err = answer("").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "No confirmation given for a protected chain, pass `--yes` to skip it"
+    );
+}
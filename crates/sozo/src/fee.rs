@@ -0,0 +1,211 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use clap::Args;
+use dojo_world::config::{EnvironmentConfig, FeeConfig};
+use dojo_world::migration::plan::MigrationPlan;
+use starknet::accounts::{Account, Call, ConnectedAccount};
+use starknet::core::types::{BlockId, BlockTag, FieldElement, FlattenedSierraClass, FunctionCall};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::Provider;
+
+use crate::porcelain;
+
+/// Address of the token fees are paid in, the same on every network.
+const FEE_TOKEN_ADDRESS: FieldElement = FieldElement::from_mont([
+    4380532846569209554,
+    17839402928228694863,
+    17240401758547432026,
+    418961398025637529,
+]);
+
+/// Fee controls of the commands sending transactions.
+#[derive(Args, Debug, Default)]
+pub struct FeeArgs {
+    #[clap(long, value_name = "WEI")]
+    #[clap(help = "Max fee to spend, nothing is sent if the estimated fee exceeds it. The max fee \
+                   of each transaction is otherwise decided by the `fee_strategy` of the \
+                   environment")]
+    pub max_fee: Option<FieldElement>,
+
+    #[clap(long, help = "Print the estimated fee without sending anything")]
+    pub estimate_only: bool,
+
+    #[clap(long, help = "Send without checking that the account can pay the estimated fee")]
+    pub skip_balance_check: bool,
+
+    #[clap(long)]
+    #[clap(help = "Simulate each transaction right before sending it, stopping at the first one \
+                   that fails with its revert reason")]
+    pub simulate: bool,
+}
+
+impl FeeArgs {
+    /// Fees of a command sending a single transaction, whose max fee is `--max-fee` when set.
+    pub fn fee_config(&self, env_config: &EnvironmentConfig) -> FeeConfig {
+        FeeConfig { simulate: self.simulate, ..env_config.fee_config(self.max_fee) }
+    }
+
+    /// Fees of the transactions of a migration, each one's max fee derived from its estimate
+    /// since `--max-fee` bounds their sum.
+    pub fn migration_fee_config(&self, env_config: &EnvironmentConfig) -> FeeConfig {
+        FeeConfig { simulate: self.simulate, ..env_config.fee_config(None) }
+    }
+
+    /// Whether the fees must be estimated before sending anything.
+    pub fn needs_estimate(&self) -> bool {
+        self.estimate_only || self.max_fee.is_some() || !self.skip_balance_check
+    }
+
+    /// Estimates the fee of sending `calls`, failing if it exceeds `--max-fee` or the balance of
+    /// the account. Returns `false` once the estimate is printed if nothing should be sent.
+    pub async fn check<A>(&self, account: &A, calls: Vec<Call>, porcelain: bool) -> Result<bool>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        let estimate = account
+            .execute(calls)
+            .estimate_fee()
+            .await
+            .map_err(|e| anyhow!("Failed to estimate the fee: {e}"))?;
+        self.check_estimate(account, estimate.overall_fee, porcelain).await
+    }
+
+    /// Same as [`FeeArgs::check`], for the declaration of a class.
+    pub async fn check_declaration<A>(
+        &self,
+        account: &A,
+        class: Arc<FlattenedSierraClass>,
+        compiled_class_hash: FieldElement,
+        porcelain: bool,
+    ) -> Result<bool>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        let estimate = account
+            .declare(class, compiled_class_hash)
+            .estimate_fee()
+            .await
+            .map_err(|e| anyhow!("Failed to estimate the fee: {e}"))?;
+        self.check_estimate(account, estimate.overall_fee, porcelain).await
+    }
+
+    async fn check_estimate<A>(&self, account: &A, fee: u64, porcelain: bool) -> Result<bool>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        if self.estimate_only {
+            if porcelain {
+                porcelain::record("fee", [fee]);
+            } else {
+                println!("Estimated fee: {fee} wei");
+            }
+            return Ok(false);
+        }
+
+        if let Some(max_fee) = self.max_fee {
+            if FieldElement::from(fee) > max_fee {
+                bail!("Estimated fee of {fee} wei exceeds the max fee of {max_fee}");
+            }
+        }
+
+        if !self.skip_balance_check {
+            check_balance(account, fee).await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Same as [`FeeArgs::check`], for the transactions of a migration. Steps whose fee couldn't
+    /// be estimated aren't bounded by `--max-fee` nor accounted for in the balance check.
+    pub async fn check_plan<A>(
+        &self,
+        account: &A,
+        plan: &MigrationPlan,
+        porcelain: bool,
+    ) -> Result<bool>
+    where
+        A: ConnectedAccount + Sync,
+    {
+        if self.estimate_only {
+            if porcelain {
+                porcelain::record("fee", [plan.estimated_fee]);
+            } else {
+                println!("Estimated fee: {} wei", plan.estimated_fee);
+            }
+            return Ok(false);
+        }
+
+        if let Some(max_fee) = self.max_fee {
+            if FieldElement::from(plan.estimated_fee) > max_fee {
+                bail!(
+                    "Estimated fee of {} wei exceeds the max fee of {max_fee}",
+                    plan.estimated_fee
+                );
+            }
+            if plan.unestimated_steps > 0 {
+                eprintln!(
+                    "warning: the fee of {} transactions couldn't be estimated, they aren't \
+                     bounded by --max-fee",
+                    plan.unestimated_steps
+                );
+            }
+        }
+
+        if !self.skip_balance_check {
+            check_balance(account, plan.estimated_fee).await?;
+        }
+
+        Ok(true)
+    }
+}
+
+/// Fails early, with the shortfall, if the account can't pay `fee`.
+async fn check_balance<A>(account: &A, fee: u64) -> Result<()>
+where
+    A: ConnectedAccount + Sync,
+{
+    let balance = account
+        .provider()
+        .call(
+            FunctionCall {
+                contract_address: FEE_TOKEN_ADDRESS,
+                entry_point_selector: get_selector_from_name("balanceOf")?,
+                calldata: vec![account.address()],
+            },
+            BlockId::Tag(BlockTag::Pending),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to fetch the balance of the account: {e}"))?;
+
+    // The balance is a u256, any high part covers the fee.
+    let (Some(low), Some(high)) = (balance.first(), balance.get(1)) else {
+        bail!("Unexpected balance of the account: {balance:?}");
+    };
+    if *high != FieldElement::ZERO || *low >= FieldElement::from(fee) {
+        return Ok(());
+    }
+
+    let low = u128::try_from(*low).unwrap_or_default();
+    let shortfall = u128::from(fee) - low;
+    let hint = faucet_hint(account.chain_id()).map(|hint| format!("\n{hint}")).unwrap_or_default();
+    bail!(
+        "Account {:#x} holds {low} wei but the estimated fee is {fee} wei, {shortfall} wei \
+         short.{hint}",
+        account.address()
+    );
+}
+
+/// Where to get funds on the known development networks.
+fn faucet_hint(chain_id: FieldElement) -> Option<&'static str> {
+    let is = |name: &str| cairo_short_string_to_felt(name).ok() == Some(chain_id);
+    if is("SN_GOERLI") {
+        Some("Fund it with the faucet at https://faucet.goerli.starknet.io")
+    } else if is("SN_GOERLI2") {
+        Some("Fund it by bridging ETH from Goerli to Starknet Goerli 2")
+    } else if is("KATANA") {
+        Some("Use one of the prefunded accounts Katana lists at startup")
+    } else {
+        None
+    }
+}
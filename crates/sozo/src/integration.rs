@@ -0,0 +1,282 @@
+//! Integration tests run by `sozo test --integration`. Each test is a scenario in
+//! `tests/integration/<name>.toml` executing systems on the world migrated to an in-process
+//! Katana and checking the resulting component values:
+//!
+//! ```toml
+//! [[steps]]
+//! execute = "spawn"
+//!
+//! [[steps]]
+//! execute = "move"
+//! calldata = ["1"]
+//!
+//! [[steps]]
+//! component = "Position"
+//! keys = ["$caller"]
+//! expect = { x = "1", y = "0" }
+//!
+//! [[steps]]
+//! execute = "move"
+//! calldata = ["0"]
+//!
+//! [[steps]]
+//! execute = "move"
+//! calldata = ["0"]
+//! fails = true
+//! ```
+//!
+//! The scenarios run in alphabetical order against the same world, each one sees the state left
+//! by the previous ones.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use dojo_signers::devnet::default_dev_accounts;
+use dojo_world::config::{EnvironmentConfig, FeeConfig, WorldConfig};
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::finality::{wait_for_finality, Finality};
+use dojo_world::migration::object::WorldContract;
+use dojo_world::migration::strategy::{prepare_for_migration, MigrationKind};
+use dojo_world::migration::world::WorldDiff;
+use katana_core::sequencer::KatanaSequencer;
+use katana_core::starknet::StarknetConfig;
+use katana_rpc::config::RpcConfig;
+use katana_rpc::KatanaNodeRpc;
+use serde::Deserialize;
+use starknet::accounts::{Account, ConnectedAccount};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::providers::Provider;
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::commands::call::{decode_members, entity_values};
+
+/// Directory of the scenarios, relative to the project's root.
+pub const INTEGRATION_TESTS_DIR: &str = "tests/integration";
+
+/// Placeholder for the address of the account sending the transactions, in calldata and keys.
+const CALLER: &str = "$caller";
+
+#[derive(Debug, Deserialize)]
+struct Scenario {
+    #[serde(default)]
+    steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Step {
+    /// Executes a system through the world, failing the test if the transaction fails unless
+    /// `fails` is set, in which case it must.
+    Execute {
+        execute: String,
+        #[serde(default)]
+        calldata: Vec<String>,
+        #[serde(default)]
+        fails: bool,
+    },
+    /// Checks the values of some members of a component for an entity.
+    Expect {
+        component: String,
+        #[serde(default)]
+        keys: Vec<String>,
+        expect: BTreeMap<String, String>,
+    },
+}
+
+/// Paths of the scenarios of the project whose name contains `filter`, sorted by name.
+pub fn collect_scenarios(source_dir: &Utf8Path, filter: &str) -> Result<Vec<Utf8PathBuf>> {
+    let dir = source_dir.join(INTEGRATION_TESTS_DIR);
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut scenarios = vec![];
+    for entry in dir.read_dir_utf8()? {
+        let path = entry?.into_path();
+        if path.extension() == Some("toml")
+            && path.file_stem().map_or(false, |name| name.contains(filter))
+        {
+            scenarios.push(path);
+        }
+    }
+    scenarios.sort();
+
+    Ok(scenarios)
+}
+
+/// Migrates the world built to `target_dir` to a Katana started for the run, then runs the
+/// scenarios against it. Fails if any scenario does.
+pub async fn run(target_dir: Utf8PathBuf, scenarios: &[Utf8PathBuf]) -> Result<()> {
+    let sequencer = Arc::new(RwLock::new(KatanaSequencer::new(StarknetConfig {
+        total_accounts: 1,
+        allow_zero_max_fee: true,
+        ..StarknetConfig::default()
+    })));
+    sequencer.write().await.start();
+    let (addr, handle) = KatanaNodeRpc::new(sequencer, RpcConfig { port: 0 })
+        .run()
+        .await
+        .map_err(|e| anyhow!("Failed to start Katana: {e}"))?;
+
+    let res = run_on(&format!("http://{addr}").parse()?, target_dir, scenarios).await;
+    let _ = handle.stop();
+    res
+}
+
+async fn run_on(url: &Url, target_dir: Utf8PathBuf, scenarios: &[Utf8PathBuf]) -> Result<()> {
+    let dev_account = default_dev_accounts(1)[0];
+    let env_config = EnvironmentConfig {
+        rpc: Some(url.clone()),
+        private_key: Some(dev_account.private_key),
+        account_address: Some(dev_account.address),
+        ..EnvironmentConfig::default()
+    };
+    let world_config = WorldConfig::default();
+
+    println!("Migrating the world to Katana at {url}...");
+    let manifest = Manifest::load_from_path(target_dir.join("manifest.json"))?;
+    let diff = WorldDiff::from_path(target_dir.clone(), &world_config, &env_config).await?;
+    let mut migration = prepare_for_migration(target_dir, diff, world_config, MigrationKind::Full)?;
+    let output = migration
+        .execute(env_config.migrator().await?)
+        .await
+        .map_err(|e| anyhow!("Failed to migrate the world: {e}"))?;
+    let world_address = output
+        .world
+        .map(|world| world.contract_address)
+        .ok_or(anyhow!("The world wasn't deployed by the migration"))?;
+
+    let account = env_config.migrator().await?;
+    // Failing executions must not be sent, the sequencer would reject them.
+    let world = WorldContract::new(world_address, &account)
+        .with_fees(FeeConfig { simulate: true, ..FeeConfig::default() });
+
+    println!("\nrunning {} integration tests", scenarios.len());
+    let mut failed = vec![];
+    for path in scenarios {
+        let name = path.file_stem().unwrap_or(path.as_str());
+        match run_scenario(path, &world, &manifest).await {
+            Ok(()) => println!("test {name} ... ok"),
+            Err(e) => {
+                println!("test {name} ... FAILED\n    {e:#}");
+                failed.push(name);
+            }
+        }
+    }
+
+    println!(
+        "\ntest result: {}. {} passed; {} failed\n",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        scenarios.len() - failed.len(),
+        failed.len()
+    );
+    if !failed.is_empty() {
+        bail!("integration tests failed: {}", failed.join(", "));
+    }
+
+    Ok(())
+}
+
+async fn run_scenario<A>(
+    path: &Utf8Path,
+    world: &WorldContract<'_, A>,
+    manifest: &Manifest,
+) -> Result<()>
+where
+    A: ConnectedAccount + Sync,
+    <A::Provider as Provider>::Error: 'static,
+{
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+    let scenario: Scenario =
+        toml::from_str(&content).with_context(|| format!("Failed to parse {path}"))?;
+    let caller = world.account.address();
+    let provider = world.account.provider();
+
+    for (index, step) in scenario.steps.into_iter().enumerate() {
+        let step_number = index + 1;
+        match step {
+            Step::Execute { execute, calldata, fails } => {
+                let system = execute.strip_suffix("System").unwrap_or(&execute);
+                let calldata = parse_felts(&calldata, caller)?;
+                let res = world.execute(cairo_short_string_to_felt(system)?, calldata).await;
+                match (res, fails) {
+                    (Ok(res), false) => {
+                        let hash = res.transaction_hash;
+                        wait_for_finality(provider, hash, Finality::Pending, &world.retry).await?;
+                    }
+                    (Err(e), false) => bail!("step {step_number}: executing {execute} failed: {e}"),
+                    (Ok(res), true) => bail!(
+                        "step {step_number}: executing {execute} succeeded in {:#x}, expected it \
+                         to fail",
+                        res.transaction_hash
+                    ),
+                    (Err(_), true) => {}
+                }
+            }
+            Step::Expect { component, keys, expect } => {
+                let members = &manifest
+                    .components
+                    .iter()
+                    .find(|c| c.name == component)
+                    .with_context(|| format!("Component `{component}` not found in the manifest"))?
+                    .members;
+                let keys = parse_felts(&keys, caller)?;
+                let values = entity_values(
+                    provider,
+                    world.address,
+                    &component,
+                    members,
+                    FieldElement::ZERO,
+                    keys,
+                )
+                .await?;
+                let values = decode_members(members, &values)?;
+
+                for (member, expected) in &expect {
+                    let actual = values
+                        .iter()
+                        .find(|(name, _)| name == member)
+                        .map(|(_, value)| value.as_str())
+                        .with_context(|| format!("`{component}` has no member `{member}`"))?;
+                    if !same_value(actual, expected, caller) {
+                        bail!(
+                            "step {step_number}: expected {component}.{member} to be {expected}, \
+                             found {actual}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses felts written in hex or decimal, [`CALLER`] standing for the caller's address.
+fn parse_felts(values: &[String], caller: FieldElement) -> Result<Vec<FieldElement>> {
+    values.iter().map(|value| parse_felt(value, caller)).collect()
+}
+
+fn parse_felt(value: &str, caller: FieldElement) -> Result<FieldElement> {
+    if value == CALLER {
+        return Ok(caller);
+    }
+    let felt = match value.strip_prefix("0x") {
+        Some(_) => FieldElement::from_hex_be(value).ok(),
+        None => FieldElement::from_dec_str(value).ok(),
+    };
+    felt.ok_or(anyhow!("Invalid felt `{value}`, expected hex, decimal or `{CALLER}`"))
+}
+
+/// Decoded values are compared as felts when both are numbers, so that `0xa` matches `10`.
+fn same_value(actual: &str, expected: &str, caller: FieldElement) -> bool {
+    match (parse_felt(actual, caller), parse_felt(expected, caller)) {
+        (Ok(actual), Ok(expected)) => actual == expected,
+        _ => actual == expected,
+    }
+}
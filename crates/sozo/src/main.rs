@@ -1,20 +1,68 @@
 use std::process::exit;
+use std::time::Duration;
 
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 use env_logger::Env;
 use log::error;
 
+mod cancellation;
+mod codegen;
 mod commands;
+mod confirm;
+mod fee;
+mod integration;
+mod porcelain;
+mod property;
+mod receipts;
+mod script;
+mod snapshot;
 
-use self::commands::{build, init, migrate, test, App, Commands};
+use self::commands::{
+    account, auth, bindgen, build, cache, call, clean, completions, component, declare, deploy,
+    dev, doc, events, execute, fuzz, graph, history, import, init, inspect, keystore, logs, migrate,
+    register, run, status, system, test, upgrade, verify, world, App, Commands,
+};
 
 fn main() {
     env_logger::Builder::from_env(Env::default().default_filter_or("sozo=info")).init();
 
     let cli = App::parse();
+    build::select_profile(cli.profile);
+    let timeout = cli.timeout.map(Duration::from_secs);
+    let porcelain = cli.porcelain;
+    if porcelain {
+        if !cli.command.has_porcelain_output() {
+            App::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "`--porcelain` isn't supported by this command, it has no records to output",
+                )
+                .exit();
+        }
+        porcelain::header();
+    }
 
     let res = match cli.command {
+        Commands::Account(args) => account::run(args, timeout),
+        Commands::Auth(args) => auth::run(args, timeout, porcelain),
+        Commands::Bindgen(args) => bindgen::run(args),
         Commands::Build(args) => build::run(args),
+        Commands::Cache(args) => cache::run(args),
+        Commands::Call(args) => call::run(args, timeout, porcelain),
+        Commands::Clean(args) => clean::run(args),
+        Commands::Completions(args) => completions::run(args),
+        Commands::Component(args) => component::run(args, timeout, porcelain),
+        Commands::Declare(args) => declare::run(args, timeout, porcelain),
+        Commands::Deploy(args) => deploy::run(args, timeout, porcelain),
+        Commands::Dev(args) => dev::run(args, timeout),
+        Commands::Doc(args) => doc::run(args),
+        Commands::Events(args) => events::run(args, timeout, porcelain),
+        Commands::Execute(args) => execute::run(args, timeout, porcelain),
+        Commands::Fuzz(args) => fuzz::run(args, timeout, porcelain),
+        Commands::Graph(args) => graph::run(args),
+        Commands::History(args) => history::run(args, timeout, porcelain),
+        Commands::Import(args) => import::run(args, timeout),
         Commands::Init(args) => {
             match init::run(args) {
                 Ok(_) => (),
@@ -22,8 +70,18 @@ fn main() {
             };
             Ok(())
         }
-        Commands::Migrate(args) => migrate::run(args),
-        Commands::Test(args) => test::run(args),
+        Commands::Inspect(args) => inspect::run(args),
+        Commands::Keystore(args) => keystore::run(args, timeout),
+        Commands::Logs(args) => logs::run(args, timeout, porcelain),
+        Commands::Migrate(args) => migrate::run(args, timeout),
+        Commands::Register(args) => register::run(args, timeout),
+        Commands::Run(args) => run::run(args, timeout, porcelain),
+        Commands::Status(args) => status::run(args, timeout, porcelain),
+        Commands::System(args) => system::run(args, timeout, porcelain),
+        Commands::Test(args) => test::run(args, timeout),
+        Commands::Upgrade(args) => upgrade::run(args, timeout),
+        Commands::Verify(args) => verify::run(args),
+        Commands::World(args) => world::run(args, timeout, porcelain),
     };
 
     if let Err(err) = res {
@@ -0,0 +1,99 @@
+//! Output meant to be parsed by scripts, enabled with `--porcelain`.
+//!
+//! The output starts with a `sozo-porcelain <version>` header, followed by one record per line.
+//! Commands without records, such as `migrate` or `upgrade`, refuse `--porcelain`.
+//! A record is its kind followed by its fields, separated by tabs. Field elements are lowercase
+//! `0x` prefixed hex, numbers are decimal and absent values are `-`, regardless of the locale.
+//! Tabs, newlines and backslashes in fields are escaped as `\t`, `\n` and `\\`.
+//!
+//! The layout of the records is only changed along with [`VERSION`], fields may however be
+//! appended to the end of a record. Records of version 1:
+//!
+//! - `tx <transaction hash> <status> <actual fee>`, sent by `execute`, `auth`.
+//! - `declared <class hash> <transaction hash>`, the class declared by `declare`, the hash is
+//!   absent if it was already declared.
+//! - `deployed <contract address> <class hash> <transaction hash>`, the contract deployed by
+//!   `deploy`, the hash is absent with `--predict`.
+//! - `history <timestamp> <command> <transaction hash> <status> <actual fee> <description>
+//!   <sender>`, the status and fee being looked up if they weren't known when it was sent.
+//! - `event <block number> <transaction hash> <name> [<key>=<value>...]`
+//! - `member <name> <value>`, an entity's member read by `call entity` or `component get`.
+//! - `value <value>`, a value returned by the other `call` subcommands.
+//! - `schema <name> <type> <slot> <offset> <variants>`, variants are comma separated.
+//! - `writer <system> <component>`, a system allowed to write to a component by `auth list`.
+//! - `failure <count> <reason> <calldata>`, failures of `fuzz` grouped by reason, calldata is
+//!   that of the first failure, comma separated.
+//! - `status <kind> <name> <status> <local class hash> <remote class hash>`, a class of the world
+//!   compared by `status`, the kind being `world`, `executor`, `component` or `system` and the
+//!   status `matches`, `missing` or `stale`.
+//! - `fee <estimated fee>`, the fee of a transaction not sent because of `--estimate-only`.
+
+use std::fmt::Display;
+
+use crate::receipts::HistoryEntry;
+
+#[cfg(test)]
+#[path = "porcelain_test.rs"]
+mod test;
+
+/// Version of the record layouts, bumped on any incompatible change.
+pub const VERSION: u32 = 1;
+
+/// Prints the version header, once before any record.
+pub fn header() {
+    println!("{}", header_line());
+}
+
+fn header_line() -> String {
+    format!("sozo-porcelain\t{VERSION}")
+}
+
+/// Prints a record of the given kind.
+pub fn record<I, F>(kind: &str, fields: I)
+where
+    I: IntoIterator<Item = F>,
+    F: Display,
+{
+    println!("{}", record_line(kind, fields));
+}
+
+fn record_line<I, F>(kind: &str, fields: I) -> String
+where
+    I: IntoIterator<Item = F>,
+    F: Display,
+{
+    let mut line = kind.to_string();
+    for field in fields {
+        line.push('\t');
+        line.push_str(&escape(&field.to_string()));
+    }
+    line
+}
+
+/// Formats an optional value, `-` when absent.
+pub fn optional<T: Display>(value: Option<T>) -> String {
+    value.map_or("-".to_string(), |value| value.to_string())
+}
+
+fn escape(field: &str) -> String {
+    if field.is_empty() {
+        return "-".to_string();
+    }
+    field.replace('\\', "\\\\").replace('\t', "\\t").replace('\n', "\\n")
+}
+
+/// Prints the `tx` record of a transaction sent by the command.
+pub fn transaction(entry: &HistoryEntry) {
+    println!("{}", transaction_line(entry));
+}
+
+fn transaction_line(entry: &HistoryEntry) -> String {
+    record_line(
+        "tx",
+        [
+            format!("{:#x}", entry.transaction_hash),
+            optional(entry.status.as_deref()),
+            optional(entry.actual_fee.map(|fee| format!("{fee:#x}"))),
+        ],
+    )
+}
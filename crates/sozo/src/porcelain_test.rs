@@ -0,0 +1,45 @@
+use starknet::core::types::FieldElement;
+
+use super::{header_line, optional, record_line, transaction_line, VERSION};
+use crate::receipts::HistoryEntry;
+
+#[test]
+fn test_header() {
+    assert_eq!(VERSION, 1, "the record layouts are documented for version 1");
+    assert_eq!(header_line(), "sozo-porcelain\t1");
+}
+
+#[test]
+fn test_record_fields() {
+    assert_eq!(record_line("value", ["0x1"]), "value\t0x1");
+    assert_eq!(record_line("member", ["x", "10"]), "member\tx\t10");
+    assert_eq!(record_line::<_, &str>("fee", []), "fee");
+    assert_eq!(record_line("failure", [3, 42]), "failure\t3\t42");
+}
+
+#[test]
+fn test_record_escaping() {
+    assert_eq!(record_line("value", ["a\tb"]), "value\ta\\tb");
+    assert_eq!(record_line("value", ["a\nb"]), "value\ta\\nb");
+    assert_eq!(record_line("value", ["a\\b"]), "value\ta\\\\b");
+    assert_eq!(record_line("value", ["\\t"]), "value\t\\\\t");
+    assert_eq!(record_line("value", [""]), "value\t-");
+}
+
+#[test]
+fn test_optional() {
+    assert_eq!(optional(Some("ACCEPTED_ON_L2")), "ACCEPTED_ON_L2");
+    assert_eq!(optional(Some(0)), "0");
+    assert_eq!(optional::<&str>(None), "-");
+}
+
+#[test]
+fn test_transaction() {
+    let mut entry =
+        HistoryEntry::submitted("execute", "spawn", FieldElement::from(0xabcu32), vec![], None);
+    assert_eq!(transaction_line(&entry), "tx\t0xabc\t-\t-");
+
+    entry.status = Some("ACCEPTED_ON_L2".to_string());
+    entry.actual_fee = Some(FieldElement::from(0x1fu32));
+    assert_eq!(transaction_line(&entry), "tx\t0xabc\tACCEPTED_ON_L2\t0x1f");
+}
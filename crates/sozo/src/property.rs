@@ -0,0 +1,339 @@
+//! Property tests run by `sozo test`. A property test is a free function annotated with
+//! `#[fuzz]` whose parameters are generated randomly, it fails when it panics:
+//!
+//! ```cairo
+//! #[fuzz]
+//! #[available_gas(2000000)]
+//! fn test_move_stays_in_bounds(x: u32, y: u32, direction: u8) {
+//!     let (next_x, next_y) = next_position(x, y, direction % 4);
+//!     assert(next_x <= MAX_X & next_y <= MAX_Y, 'out of bounds');
+//! }
+//! ```
+//!
+//! The parameters are `felt252`, `bool` and unsigned integers. A failing case is shrunk towards
+//! zero, one input at a time, and the smallest one still failing is reported. The functions run
+//! without a Starknet state, they can't deploy the world.
+
+use anyhow::{bail, Context, Result};
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_defs::ids::{FreeFunctionId, FunctionWithBodyId};
+use cairo_lang_lowering::ids::ConcreteFunctionWithBodyId;
+use cairo_lang_runner::{RunResultValue, SierraCasmRunner, StarknetState};
+use cairo_lang_semantic::db::SemanticGroup;
+use cairo_lang_sierra::program::Function;
+use cairo_lang_sierra_generator::db::SierraGenGroup;
+use cairo_lang_sierra_generator::replace_ids::replace_sierra_ids_in_program;
+use cairo_lang_sierra_to_casm::metadata::MetadataComputationConfig;
+use cairo_lang_syntax::attribute::structured::AttributeArgVariant;
+use cairo_lang_syntax::node::TypedSyntaxNode;
+use num_bigint::{BigInt, Sign};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::parse_cairo_short_string;
+
+use crate::commands::fuzz::random_uint;
+
+#[cfg(test)]
+#[path = "property_test.rs"]
+mod test;
+
+/// Most runs spent shrinking a failing case.
+const MAX_SHRINK_RUNS: usize = 1000;
+
+/// How a parameter of a property test is generated.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Param {
+    Felt,
+    Bool,
+    Uint(u32),
+    U256,
+}
+
+impl Param {
+    fn from_type(ty: &str) -> Option<Self> {
+        Some(match ty.rsplit("::").next().unwrap_or(ty) {
+            "felt252" => Self::Felt,
+            "bool" => Self::Bool,
+            "u8" => Self::Uint(8),
+            "u16" => Self::Uint(16),
+            "u32" => Self::Uint(32),
+            "u64" => Self::Uint(64),
+            "u128" => Self::Uint(128),
+            "u256" => Self::U256,
+            _ => return None,
+        })
+    }
+
+    /// Appends a random value to `args`, biased towards the edge cases of the type.
+    fn generate(&self, rng: &mut StdRng, args: &mut Vec<FieldElement>) {
+        match self {
+            Self::Felt => args.push(match rng.gen_range(0..5) {
+                0 => FieldElement::ZERO,
+                1 => FieldElement::ONE,
+                2 => FieldElement::MAX,
+                3 => FieldElement::from(rng.gen_range(0u8..=255)),
+                _ => FieldElement::from(rng.gen::<u128>()),
+            }),
+            Self::Bool => args.push(FieldElement::from(rng.gen_range(0u8..2))),
+            Self::Uint(bits) => args.push(FieldElement::from(random_uint(rng, *bits))),
+            Self::U256 => {
+                args.push(FieldElement::from(random_uint(rng, 128)));
+                args.push(FieldElement::from(random_uint(rng, 128)));
+            }
+        }
+    }
+}
+
+/// A function annotated with `#[fuzz]`.
+pub struct PropertyTest {
+    pub path: String,
+    function_id: FreeFunctionId,
+    params: Vec<(String, Param)>,
+    available_gas: Option<usize>,
+}
+
+/// The property tests of the main crates, failing on parameters that can't be generated.
+pub fn collect(
+    db: &RootDatabase,
+    functions: Vec<(FreeFunctionId, String)>,
+) -> Result<Vec<PropertyTest>> {
+    let mut tests = vec![];
+    for (function_id, path) in functions {
+        let signature = db
+            .free_function_signature(function_id)
+            .ok()
+            .with_context(|| format!("Failed to read the signature of {path}"))?;
+
+        let mut params = vec![];
+        for param in &signature.params {
+            let ty = param.ty.format(db);
+            let Some(kind) = Param::from_type(&ty) else {
+                bail!(
+                    "{path}: `{}: {ty}` can't be generated, property tests take `felt252`, \
+                     `bool` and unsigned integers",
+                    param.name
+                );
+            };
+            params.push((param.name.to_string(), kind));
+        }
+
+        let available_gas = available_gas(db, function_id, &path)?;
+        tests.push(PropertyTest { path, function_id, params, available_gas });
+    }
+
+    Ok(tests)
+}
+
+/// The gas set by `#[available_gas(..)]` on the function, if any.
+pub(crate) fn available_gas(
+    db: &RootDatabase,
+    function_id: FreeFunctionId,
+    path: &str,
+) -> Result<Option<usize>> {
+    let attributes = db
+        .function_with_body_attributes(FunctionWithBodyId::Free(function_id))
+        .ok()
+        .unwrap_or_default();
+    let Some(attr) = attributes.iter().find(|attr| attr.id == "available_gas") else {
+        return Ok(None);
+    };
+
+    let arg = match attr.args.first().map(|arg| &arg.variant) {
+        Some(AttributeArgVariant::Unnamed { value, .. }) => {
+            value.as_syntax_node().get_text_without_trivia(db)
+        }
+        _ => String::new(),
+    };
+    let gas = arg
+        .parse()
+        .with_context(|| format!("{path}: invalid `available_gas` `{arg}`, expected a number"))?;
+    Ok(Some(gas))
+}
+
+/// Compiles the functions, given with their paths, into a runner running them without contracts.
+pub(crate) fn build_runner<'a>(
+    db: &RootDatabase,
+    functions: impl IntoIterator<Item = (FreeFunctionId, &'a str)>,
+) -> Result<SierraCasmRunner> {
+    let function_ids = functions
+        .into_iter()
+        .map(|(function_id, path)| {
+            ConcreteFunctionWithBodyId::from_no_generics_free(db, function_id)
+                .with_context(|| format!("{path}: the function can't be generic"))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let program = db
+        .get_sierra_program_for_functions(function_ids)
+        .ok()
+        .context("Compilation failed without any diagnostics")?;
+    let program = replace_sierra_ids_in_program(db, &program);
+    Ok(SierraCasmRunner::new(
+        program,
+        Some(MetadataComputationConfig::default()),
+        Default::default(),
+    )?)
+}
+
+/// Runs every test `runs` times with inputs generated from `seed`, failing if any of them does.
+pub fn run(db: &RootDatabase, tests: &[PropertyTest], runs: usize, seed: u64) -> Result<()> {
+    if tests.is_empty() {
+        return Ok(());
+    }
+
+    let runner = build_runner(db, tests.iter().map(|test| (test.function_id, test.path.as_str())))?;
+
+    println!("running {} property tests with seed {seed}", tests.len());
+    let mut failed = vec![];
+    for test in tests {
+        let function = runner.find_function(&test.path)?;
+        // Every test gets the same inputs whatever the filter, so that a seed reproduces it alone.
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let mut failure = None;
+        for run in 1..=runs {
+            let args = generate_args(&test.params, &mut rng);
+            if let Some(panic) = run_case(&runner, function, test, &args)? {
+                failure = Some((run, args, panic));
+                break;
+            }
+        }
+
+        let Some((run, args, panic)) = failure else {
+            println!("test {} ... ok ({runs} runs)", test.path);
+            continue;
+        };
+        let (args, panic) = shrink(args, panic, |args| run_case(&runner, function, test, args))?;
+        println!("test {} ... fail (run {run})", test.path);
+        println!("    counterexample: {}", format_inputs(test, &args));
+        println!("    panicked with: {}", format_panic(&panic));
+        failed.push(test.path.as_str());
+    }
+
+    println!(
+        "\nproperty test result: {}. {} passed; {} failed",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        tests.len() - failed.len(),
+        failed.len()
+    );
+    if !failed.is_empty() {
+        bail!("{} property tests failed, reproduce them with `--fuzz-seed {seed}`", failed.len());
+    }
+
+    Ok(())
+}
+
+/// The inputs of a run, the values of the parameters one after the other.
+fn generate_args(params: &[(String, Param)], rng: &mut StdRng) -> Vec<FieldElement> {
+    let mut args = vec![];
+    for (_, param) in params {
+        param.generate(rng, &mut args);
+    }
+    args
+}
+
+/// Runs the test with `args`, returning its panic data if it fails.
+fn run_case(
+    runner: &SierraCasmRunner,
+    function: &Function,
+    test: &PropertyTest,
+    args: &[FieldElement],
+) -> Result<Option<Vec<FieldElement>>> {
+    let args = args
+        .iter()
+        .map(|arg| BigInt::from_bytes_be(Sign::Plus, &arg.to_bytes_be()))
+        .collect::<Vec<_>>();
+    let result =
+        runner.run_function(function, &args, test.available_gas, StarknetState::default())?;
+
+    match result.value {
+        RunResultValue::Success(_) => Ok(None),
+        RunResultValue::Panic(data) => Ok(Some(
+            data.iter()
+                .map(|felt| FieldElement::from_dec_str(&felt.to_biguint().to_string()))
+                .collect::<Result<_, _>>()?,
+        )),
+    }
+}
+
+/// Moves every input of a failing case towards zero as long as the test, run by `run_case`, keeps
+/// failing.
+fn shrink<F>(
+    mut args: Vec<FieldElement>,
+    mut panic: Vec<FieldElement>,
+    mut run_case: F,
+) -> Result<(Vec<FieldElement>, Vec<FieldElement>)>
+where
+    F: FnMut(&[FieldElement]) -> Result<Option<Vec<FieldElement>>>,
+{
+    let mut shrink_runs = 0;
+    loop {
+        let mut shrunk = false;
+        for index in 0..args.len() {
+            let value = args[index];
+            let mut candidates = vec![FieldElement::ZERO, value.floor_div(FieldElement::TWO)];
+            if value != FieldElement::ZERO {
+                candidates.push(value - FieldElement::ONE);
+            }
+            candidates.dedup();
+
+            for candidate in candidates.into_iter().filter(|c| *c != value) {
+                if shrink_runs == MAX_SHRINK_RUNS {
+                    return Ok((args, panic));
+                }
+                shrink_runs += 1;
+
+                let mut smaller = args.clone();
+                smaller[index] = candidate;
+                if let Some(smaller_panic) = run_case(&smaller)? {
+                    args = smaller;
+                    panic = smaller_panic;
+                    shrunk = true;
+                    break;
+                }
+            }
+        }
+        if !shrunk {
+            return Ok((args, panic));
+        }
+    }
+}
+
+fn format_inputs(test: &PropertyTest, args: &[FieldElement]) -> String {
+    let mut args = args.iter();
+    let params = test
+        .params
+        .iter()
+        .map(|(name, param)| {
+            let value = match param {
+                Param::U256 => {
+                    let low = args.next().copied().unwrap_or_default();
+                    let high = args.next().copied().unwrap_or_default();
+                    format!("{{ low: {low}, high: {high} }}")
+                }
+                _ => args.next().copied().unwrap_or_default().to_string(),
+            };
+            format!("{name}: {value}")
+        })
+        .collect::<Vec<_>>();
+    format!("({})", params.join(", "))
+}
+
+/// Panic data with its short strings, usually the reason, decoded.
+pub(crate) fn format_panic(panic: &[FieldElement]) -> String {
+    let data = panic
+        .iter()
+        .map(|felt| match readable_short_string(felt) {
+            Some(s) => format!("'{s}'"),
+            None => format!("{felt:#x}"),
+        })
+        .collect::<Vec<_>>();
+    format!("({})", data.join(", "))
+}
+
+/// The felt as a short string, if it's made of printable characters.
+pub(crate) fn readable_short_string(felt: &FieldElement) -> Option<String> {
+    parse_cairo_short_string(felt)
+        .ok()
+        .filter(|s| !s.is_empty() && s.chars().all(|c| c.is_ascii_graphic() || c == ' '))
+}
@@ -0,0 +1,45 @@
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use starknet::core::types::FieldElement;
+
+use super::{generate_args, shrink, Param};
+
+fn params() -> Vec<(String, Param)> {
+    vec![
+        ("x".to_string(), Param::Uint(32)),
+        ("alive".to_string(), Param::Bool),
+        ("amount".to_string(), Param::U256),
+        ("name".to_string(), Param::Felt),
+    ]
+}
+
+#[test]
+fn test_seeded_runs_are_deterministic() {
+    let runs = |seed| {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..100).map(|_| generate_args(&params(), &mut rng)).collect::<Vec<_>>()
+    };
+
+    let first = runs(42);
+    assert_eq!(first, runs(42));
+    assert_ne!(first, runs(43));
+
+    for args in &first {
+        // The `u256` takes two felts.
+        assert_eq!(args.len(), 5);
+        assert!(args[0] <= FieldElement::from(u32::MAX));
+        assert!(args[1] <= FieldElement::ONE);
+    }
+}
+
+#[test]
+fn test_shrink_to_minimal_counterexample() {
+    let limit = FieldElement::from(10_u8);
+    // Fails when `x` reaches the limit, whatever `y`.
+    let run_case = |args: &[FieldElement]| Ok((args[0] >= limit).then(|| vec![args[0]]));
+
+    let args = vec![FieldElement::from(1000_u32), FieldElement::from(777_u32)];
+    let (args, panic) = shrink(args, vec![FieldElement::from(1000_u32)], run_case).unwrap();
+    assert_eq!(args, [limit, FieldElement::ZERO]);
+    assert_eq!(panic, [limit]);
+}
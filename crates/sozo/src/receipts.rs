@@ -0,0 +1,299 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{anyhow, bail, Context, Result};
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use dojo_world::migration::retry::RetryPolicy;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use starknet::core::serde::unsigned_field_element::UfeHex;
+use starknet::core::types::{
+    FieldElement, MaybePendingTransactionReceipt, TransactionReceipt, TransactionStatus,
+};
+use starknet::providers::Provider;
+
+use crate::cancellation::run_cancellable;
+
+#[cfg(test)]
+#[path = "receipts_test.rs"]
+mod test;
+
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A transaction sent by sozo.
+#[serde_as]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// Seconds since the unix epoch.
+    pub timestamp: u64,
+    pub command: String,
+    pub description: String,
+    #[serde_as(as = "UfeHex")]
+    pub transaction_hash: FieldElement,
+    #[serde_as(as = "Vec<UfeHex>")]
+    pub calldata: Vec<FieldElement>,
+    pub status: Option<String>,
+    #[serde_as(as = "Option<UfeHex>")]
+    pub actual_fee: Option<FieldElement>,
+    /// Account that sent the transaction.
+    #[serde_as(as = "Option<UfeHex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<FieldElement>,
+}
+
+impl HistoryEntry {
+    /// Creates an entry, looking up the transaction's receipt for its status and fee.
+    pub async fn from_receipt<P>(
+        provider: &P,
+        command: &str,
+        description: &str,
+        transaction_hash: FieldElement,
+        calldata: Vec<FieldElement>,
+    ) -> Self
+    where
+        P: Provider + Sync,
+    {
+        let mut entry = Self::submitted(command, description, transaction_hash, calldata, None);
+        entry.resolve(provider).await;
+        entry
+    }
+
+    /// Creates an entry for a transaction just sent, whose status and fee are left to be
+    /// resolved when the history is shown.
+    pub fn submitted(
+        command: &str,
+        description: &str,
+        transaction_hash: FieldElement,
+        calldata: Vec<FieldElement>,
+        sender: Option<FieldElement>,
+    ) -> Self {
+        let timestamp =
+            SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default();
+
+        Self {
+            timestamp,
+            command: command.to_string(),
+            description: description.to_string(),
+            transaction_hash,
+            calldata,
+            status: None,
+            actual_fee: None,
+            sender,
+        }
+    }
+
+    /// Whether the transaction may still change status: its receipt wasn't found or it's pending.
+    pub fn is_unresolved(&self) -> bool {
+        matches!(self.status.as_deref(), None | Some("PENDING"))
+    }
+
+    /// Looks up the transaction's receipt for its status and fee, keeping them unknown if it
+    /// can't be found.
+    pub async fn resolve<P>(&mut self, provider: &P)
+    where
+        P: Provider + Sync,
+    {
+        let receipt = provider.get_transaction_receipt(self.transaction_hash).await;
+        let (status, actual_fee) = match receipt {
+            Ok(MaybePendingTransactionReceipt::Receipt(receipt)) => {
+                let actual_fee = match &receipt {
+                    TransactionReceipt::Invoke(r) => r.actual_fee,
+                    TransactionReceipt::L1Handler(r) => r.actual_fee,
+                    TransactionReceipt::Declare(r) => r.actual_fee,
+                    TransactionReceipt::Deploy(r) => r.actual_fee,
+                    TransactionReceipt::DeployAccount(r) => r.actual_fee,
+                };
+                (Some(status_name(receipt_status(&receipt)).to_string()), Some(actual_fee))
+            }
+            Ok(MaybePendingTransactionReceipt::PendingReceipt(_)) => {
+                (Some("PENDING".to_string()), None)
+            }
+            Err(_) => (None, None),
+        };
+        self.status = status;
+        self.actual_fee = actual_fee;
+    }
+}
+
+/// Polls the provider until the transaction's receipt is available. Use with
+/// [`crate::cancellation::run_cancellable`] to bound the wait.
+pub async fn wait_for_receipt<P>(
+    provider: &P,
+    transaction_hash: FieldElement,
+) -> Result<MaybePendingTransactionReceipt>
+where
+    P: Provider + Sync,
+{
+    let start = Instant::now();
+    loop {
+        if let Ok(receipt) = provider.get_transaction_receipt(transaction_hash).await {
+            return Ok(receipt);
+        }
+        check_not_dropped(transaction_hash, start)?;
+        tokio::time::sleep(RECEIPT_POLL_INTERVAL).await;
+    }
+}
+
+/// Fails if the transaction is still unknown to the node `receipt_timeout` after `last_seen`.
+fn check_not_dropped(transaction_hash: FieldElement, last_seen: Instant) -> Result<()> {
+    let timeout = RetryPolicy::default().receipt_timeout;
+    if last_seen.elapsed() >= timeout {
+        bail!("Transaction {transaction_hash:#x} still unknown after {timeout:?}, likely dropped");
+    }
+    Ok(())
+}
+
+/// Receipt waiting of the commands sending transactions.
+#[derive(Args, Debug)]
+pub struct WaitArgs {
+    #[clap(long)]
+    #[clap(help = "Wait until the transactions are accepted on L2, printing their status changes")]
+    pub wait: bool,
+
+    #[clap(long, value_name = "MS", default_value = "1000", requires = "wait")]
+    #[clap(help = "Interval between two polls of the receipt when waiting")]
+    pub poll_interval: u64,
+
+    #[clap(long, value_name = "SECONDS", requires = "wait")]
+    #[clap(help = "Give up waiting after this long, defaults to `--timeout`")]
+    pub wait_timeout: Option<u64>,
+}
+
+impl WaitArgs {
+    /// Waits for the receipt of the transaction, until it's accepted on L2 with `--wait`. Fails
+    /// if the wait times out or, with `--wait`, if the transaction is rejected.
+    pub async fn receipt<P>(
+        &self,
+        provider: &P,
+        transaction_hash: FieldElement,
+        timeout: Option<Duration>,
+        porcelain: bool,
+    ) -> Result<MaybePendingTransactionReceipt>
+    where
+        P: Provider + Sync,
+    {
+        if !self.wait {
+            return run_cancellable(wait_for_receipt(provider, transaction_hash), timeout)
+                .await
+                .map_err(|reason| anyhow!("Waiting for the receipt {reason}"))?;
+        }
+
+        let interval = Duration::from_millis(self.poll_interval);
+        let timeout = self.wait_timeout.map(Duration::from_secs).or(timeout);
+        let acceptance = wait_for_acceptance(provider, transaction_hash, interval, porcelain);
+        run_cancellable(acceptance, timeout)
+            .await
+            .map_err(|reason| anyhow!("Waiting for {transaction_hash:#x} to be accepted {reason}"))?
+    }
+}
+
+/// Polls the receipt of the transaction every `interval` until it's accepted on L2, printing its
+/// status whenever it changes unless `quiet`. Fails if the transaction is rejected or stays unknown
+/// to the node, likely dropped.
+pub async fn wait_for_acceptance<P>(
+    provider: &P,
+    transaction_hash: FieldElement,
+    interval: Duration,
+    quiet: bool,
+) -> Result<MaybePendingTransactionReceipt>
+where
+    P: Provider + Sync,
+{
+    let mut last_status = None;
+    let mut last_seen = Instant::now();
+    loop {
+        let receipt = provider.get_transaction_receipt(transaction_hash).await.ok();
+        if receipt.is_some() {
+            last_seen = Instant::now();
+        }
+        let status = match &receipt {
+            Some(MaybePendingTransactionReceipt::Receipt(receipt)) => Some(receipt_status(receipt)),
+            Some(MaybePendingTransactionReceipt::PendingReceipt(_)) => {
+                Some(TransactionStatus::Pending)
+            }
+            None => None,
+        };
+
+        if let Some(changed) = status.filter(|status| Some(*status) != last_status) {
+            if !quiet {
+                println!("Status: {}", status_name(changed));
+            }
+            last_status = status;
+        }
+
+        match (receipt, status) {
+            (_, Some(TransactionStatus::Rejected)) => {
+                bail!("Transaction {transaction_hash:#x} was rejected")
+            }
+            (
+                Some(receipt),
+                Some(TransactionStatus::AcceptedOnL2 | TransactionStatus::AcceptedOnL1),
+            ) => return Ok(receipt),
+            (None, _) => check_not_dropped(transaction_hash, last_seen)?,
+            _ => {}
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+pub fn receipt_status(receipt: &TransactionReceipt) -> TransactionStatus {
+    match receipt {
+        TransactionReceipt::Invoke(r) => r.status,
+        TransactionReceipt::L1Handler(r) => r.status,
+        TransactionReceipt::Declare(r) => r.status,
+        TransactionReceipt::Deploy(r) => r.status,
+        TransactionReceipt::DeployAccount(r) => r.status,
+    }
+}
+
+pub fn status_name(status: TransactionStatus) -> &'static str {
+    match status {
+        TransactionStatus::Pending => "PENDING",
+        TransactionStatus::AcceptedOnL2 => "ACCEPTED_ON_L2",
+        TransactionStatus::AcceptedOnL1 => "ACCEPTED_ON_L1",
+        TransactionStatus::Rejected => "REJECTED",
+    }
+}
+
+/// Append-only log of the transactions sent by sozo for a profile, stored as JSON lines.
+pub struct History {
+    path: Utf8PathBuf,
+}
+
+impl History {
+    pub fn new(source_dir: &Utf8Path, profile: &str) -> Self {
+        Self { path: source_dir.join(".sozo").join("history").join(format!("{profile}.jsonl")) }
+    }
+
+    pub fn append(&self, entries: &[HistoryEntry]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open history file {}", self.path))?;
+
+        for entry in entries {
+            writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+
+        fs::read_to_string(&self.path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).context("Malformed history entry"))
+            .collect()
+    }
+}
@@ -0,0 +1,160 @@
+use assert_fs::TempDir;
+use camino::Utf8PathBuf;
+use dojo_test_utils::rpc::MockJsonRpcTransport;
+use serde_json::json;
+use starknet::core::types::{FieldElement, TransactionStatus};
+use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcMethod};
+
+use super::{status_name, History, HistoryEntry};
+
+fn provider_with_receipt(response: serde_json::Value) -> JsonRpcClient<MockJsonRpcTransport> {
+    let mut mock_transport = MockJsonRpcTransport::new();
+    mock_transport.set_response(JsonRpcMethod::GetTransactionReceipt, json!(["0x1"]), response);
+    JsonRpcClient::new(mock_transport)
+}
+
+#[test]
+fn test_status_name() {
+    assert_eq!(status_name(TransactionStatus::Pending), "PENDING");
+    assert_eq!(status_name(TransactionStatus::AcceptedOnL2), "ACCEPTED_ON_L2");
+    assert_eq!(status_name(TransactionStatus::AcceptedOnL1), "ACCEPTED_ON_L1");
+    assert_eq!(status_name(TransactionStatus::Rejected), "REJECTED");
+}
+
+#[test]
+fn test_submitted_is_unresolved() {
+    let mut entry = HistoryEntry::submitted("execute", "spawn", FieldElement::ONE, vec![], None);
+    assert!(entry.is_unresolved());
+
+    entry.status = Some("PENDING".to_string());
+    assert!(entry.is_unresolved());
+
+    entry.status = Some("ACCEPTED_ON_L2".to_string());
+    assert!(!entry.is_unresolved());
+}
+
+#[test]
+fn test_entry_serialization() {
+    let mut entry = HistoryEntry::submitted(
+        "execute",
+        "spawn",
+        FieldElement::from(0xabcu32),
+        vec![FieldElement::ONE, FieldElement::from(16u32)],
+        None,
+    );
+    entry.timestamp = 1;
+
+    let value = serde_json::to_value(&entry).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "timestamp": 1,
+            "command": "execute",
+            "description": "spawn",
+            "transaction_hash": "0xabc",
+            "calldata": ["0x1", "0x10"],
+            "status": null,
+            "actual_fee": null,
+        })
+    );
+
+    entry.sender = Some(FieldElement::TWO);
+    entry.actual_fee = Some(FieldElement::from(0x20u32));
+    let line = serde_json::to_string(&entry).unwrap();
+    assert!(line.contains(r#""sender":"0x2""#));
+    assert!(line.contains(r#""actual_fee":"0x20""#));
+
+    let decoded: HistoryEntry = serde_json::from_str(&line).unwrap();
+    assert_eq!(decoded.sender, Some(FieldElement::TWO));
+    assert_eq!(decoded.actual_fee, Some(FieldElement::from(0x20u32)));
+    assert_eq!(decoded.calldata, entry.calldata);
+}
+
+#[test]
+fn test_history_append_and_entries() {
+    let temp = TempDir::new().unwrap();
+    let source_dir = Utf8PathBuf::from_path_buf(temp.path().to_path_buf()).unwrap();
+    let history = History::new(&source_dir, "dev");
+    assert!(history.entries().unwrap().is_empty());
+
+    let first = HistoryEntry::submitted("declare", "world", FieldElement::ONE, vec![], None);
+    let second = HistoryEntry::submitted("execute", "spawn", FieldElement::TWO, vec![], None);
+    history.append(&[first]).unwrap();
+    history.append(&[second]).unwrap();
+
+    let path = source_dir.join(".sozo/history/dev.jsonl");
+    assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+    let entries = history.entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].command, "declare");
+    assert_eq!(entries[1].transaction_hash, FieldElement::TWO);
+
+    // Other profiles have their own history.
+    assert!(History::new(&source_dir, "prod").entries().unwrap().is_empty());
+
+    std::fs::write(&path, "\n{not json}\n").unwrap();
+    let err = history.entries().unwrap_err();
+    assert_eq!(err.to_string(), "Malformed history entry");
+}
+
+#[tokio::test]
+async fn test_resolve_accepted() {
+    let provider = provider_with_receipt(json!({
+        "id": 1,
+        "result": {
+            "type": "INVOKE",
+            "transaction_hash": "0x1",
+            "actual_fee": "0x20",
+            "status": "ACCEPTED_ON_L2",
+            "block_hash": "0x2",
+            "block_number": 3,
+            "messages_sent": [],
+            "events": []
+        }
+    }));
+
+    let mut entry = HistoryEntry::submitted("execute", "spawn", FieldElement::ONE, vec![], None);
+    entry.resolve(&provider).await;
+    assert_eq!(entry.status.as_deref(), Some("ACCEPTED_ON_L2"));
+    assert_eq!(entry.actual_fee, Some(FieldElement::from(0x20u32)));
+    assert!(!entry.is_unresolved());
+}
+
+#[tokio::test]
+async fn test_resolve_pending() {
+    let provider = provider_with_receipt(json!({
+        "id": 1,
+        "result": {
+            "type": "INVOKE",
+            "transaction_hash": "0x1",
+            "actual_fee": "0x20",
+            "messages_sent": [],
+            "events": []
+        }
+    }));
+
+    let mut entry = HistoryEntry::submitted("execute", "spawn", FieldElement::ONE, vec![], None);
+    entry.resolve(&provider).await;
+    assert_eq!(entry.status.as_deref(), Some("PENDING"));
+    assert_eq!(entry.actual_fee, None);
+    assert!(entry.is_unresolved());
+}
+
+#[tokio::test]
+async fn test_resolve_unknown_transaction() {
+    let provider = provider_with_receipt(json!({
+        "id": 1,
+        "error": {
+            "code": 25,
+            "message": "Invalid transaction hash"
+        },
+    }));
+
+    let mut entry = HistoryEntry::submitted("execute", "spawn", FieldElement::ONE, vec![], None);
+    entry.status = Some("PENDING".to_string());
+    entry.actual_fee = Some(FieldElement::ONE);
+    entry.resolve(&provider).await;
+    assert_eq!(entry.status, None);
+    assert_eq!(entry.actual_fee, None);
+}
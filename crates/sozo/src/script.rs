@@ -0,0 +1,263 @@
+//! Scripts of system calls run by `sozo execute --script`, written in TOML or JSON. The arguments
+//! of a call are given by input name or in order, and encoded following the system's inputs in
+//! the manifest:
+//!
+//! ```toml
+//! [[calls]]
+//! system = "spawn"
+//!
+//! [[calls]]
+//! system = "move"
+//! args = { direction = "Left" }
+//!
+//! [[calls]]
+//! system = "trade"
+//! args = ["0x1234", 100, [1, 2], true]
+//! ```
+//!
+//! Numbers are written as integers, or as decimal or hex strings when they don't fit in 64 bits.
+//! Other strings are short strings, enums are given by variant name and arrays as lists.
+
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+
+use anyhow::{anyhow, bail, Context, Result};
+use camino::Utf8Path;
+use dojo_world::manifest::{Input, Manifest};
+use num_bigint::BigUint;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+#[cfg(test)]
+#[path = "script_test.rs"]
+mod test;
+
+#[derive(Debug, Deserialize)]
+pub struct Script {
+    pub calls: Vec<ScriptCall>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScriptCall {
+    /// Name of the system, with or without the `System` suffix, in any case.
+    pub system: String,
+    /// The arguments by input name, or in order.
+    #[serde(default)]
+    pub args: Option<Value>,
+}
+
+impl Script {
+    /// Reads a JSON script if the file has the `.json` extension, a TOML one otherwise.
+    pub fn load_from_path(path: &Utf8Path) -> Result<Self> {
+        load_from_path(path)
+    }
+
+    /// The name, without the `System` suffix, and the calldata of every call, in order.
+    pub fn encode(&self, manifest: &Manifest) -> Result<Vec<(String, Vec<FieldElement>)>> {
+        self.calls
+            .iter()
+            .enumerate()
+            .map(|(index, call)| {
+                encode_call(manifest, &call.system, call.args.as_ref())
+                    .map_err(|e| anyhow!("call {}: {e:#}", index + 1))
+            })
+            .collect()
+    }
+}
+
+/// Groups the calls of a script in the transactions sending them: a transaction per call when
+/// `sequential`, a single multicall otherwise.
+pub fn batches<T>(calls: Vec<T>, sequential: bool) -> Vec<Vec<T>> {
+    if sequential { calls.into_iter().map(|call| vec![call]).collect() } else { vec![calls] }
+}
+
+/// Sends the batches in order with `send`, which returns the hash of the transaction, and waits for
+/// each transaction with `wait` before sending the next batch, as its calls may depend on it.
+pub async fn send_in_order<B, S, SF, W, WF>(batches: Vec<B>, mut send: S, mut wait: W) -> Result<()>
+where
+    B: Clone,
+    S: FnMut(B) -> SF,
+    SF: Future<Output = Result<FieldElement>>,
+    W: FnMut(B, FieldElement) -> WF,
+    WF: Future<Output = Result<()>>,
+{
+    for batch in batches {
+        let transaction_hash = send(batch.clone()).await?;
+        wait(batch, transaction_hash).await?;
+    }
+    Ok(())
+}
+
+/// Reads a JSON file if it has the `.json` extension, a TOML one otherwise.
+pub fn load_from_path<T: DeserializeOwned>(path: &Utf8Path) -> Result<T> {
+    let content = fs::read_to_string(path).with_context(|| format!("Failed to read {path}"))?;
+    if path.extension() == Some("json") {
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {path}"))
+    } else {
+        toml::from_str(&content).with_context(|| format!("Failed to parse {path}"))
+    }
+}
+
+/// The name of `system` as registered by the world, without its `System` suffix, and its calldata
+/// encoded from `args`. The system is found regardless of the case of its name.
+pub fn encode_call(
+    manifest: &Manifest,
+    system: &str,
+    args: Option<&Value>,
+) -> Result<(String, Vec<FieldElement>)> {
+    // Enums are only described by the members of the components.
+    let enums = manifest
+        .components
+        .iter()
+        .flat_map(|c| &c.members)
+        .filter_map(|m| {
+            let variants = m.variants.clone()?;
+            Some((m.ty.rsplit("::").next().unwrap_or(&m.ty).to_string(), variants))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let name = system.strip_suffix("System").unwrap_or(system);
+    let system = manifest
+        .systems
+        .iter()
+        .find(|s| s.name.strip_suffix("System").unwrap_or(&s.name).eq_ignore_ascii_case(name))
+        .with_context(|| format!("system `{name}` not found in the manifest"))?;
+    let name = system.name.strip_suffix("System").unwrap_or(&system.name);
+    let calldata = encode_args(args, &system.inputs, &enums)
+        .with_context(|| format!("invalid arguments of {name}"))?;
+
+    Ok((name.to_string(), calldata))
+}
+
+fn encode_args(
+    args: Option<&Value>,
+    inputs: &[Input],
+    enums: &HashMap<String, Vec<String>>,
+) -> Result<Vec<FieldElement>> {
+    let values = match args {
+        None => vec![],
+        Some(Value::Array(values)) => values.iter().collect(),
+        Some(Value::Object(values)) => {
+            if let Some(name) = values.keys().find(|name| !inputs.iter().any(|i| i.name == **name))
+            {
+                bail!("`{name}` isn't an input");
+            }
+            inputs.iter().filter_map(|input| values.get(&input.name)).collect()
+        }
+        Some(_) => bail!("expected a list of arguments, or a table of arguments by input name"),
+    };
+    if values.len() != inputs.len() {
+        let names = inputs.iter().map(|i| i.name.as_str()).collect::<Vec<_>>();
+        bail!("expected {} arguments ({}), got {}", inputs.len(), names.join(", "), values.len());
+    }
+
+    let mut calldata = vec![];
+    for (value, input) in values.into_iter().zip(inputs) {
+        encode_value(value, &input.ty, enums, &mut calldata)
+            .with_context(|| format!("`{}`", input.name))?;
+    }
+    Ok(calldata)
+}
+
+/// Appends the serialization of `value` as a `ty` to `calldata`.
+fn encode_value(
+    value: &Value,
+    ty: &str,
+    enums: &HashMap<String, Vec<String>>,
+    calldata: &mut Vec<FieldElement>,
+) -> Result<()> {
+    if let Some(inner) = ty
+        .strip_prefix("core::array::Array::<")
+        .or_else(|| ty.strip_prefix("core::array::Span::<"))
+        .and_then(|inner| inner.strip_suffix('>'))
+    {
+        let Value::Array(values) = value else { bail!("expected a list for {ty}") };
+        calldata.push(FieldElement::from(values.len()));
+        for value in values {
+            encode_value(value, inner, enums, calldata)?;
+        }
+        return Ok(());
+    }
+
+    let name = ty.rsplit("::").next().unwrap_or(ty);
+    let bits = match name {
+        "bool" => {
+            let Value::Bool(value) = value else { bail!("expected `true` or `false`") };
+            calldata.push(if *value { FieldElement::ONE } else { FieldElement::ZERO });
+            return Ok(());
+        }
+        "u256" => {
+            let value = parse_number(value)?;
+            if value.bits() > 256 {
+                bail!("{value} doesn't fit in a u256");
+            }
+            let mask = (BigUint::from(1_u8) << 128_u32) - 1_u8;
+            calldata.push(to_felt(&(&value & &mask))?);
+            calldata.push(to_felt(&(value >> 128_u32))?);
+            return Ok(());
+        }
+        "u8" => 8,
+        "u16" => 16,
+        "u32" => 32,
+        "u64" => 64,
+        "u128" => 128,
+        _ => match (enums.get(name), value) {
+            (Some(variants), Value::String(variant)) => {
+                let index = variants.iter().position(|v| v == variant).with_context(|| {
+                    format!("`{variant}` isn't a variant of {name}: {}", variants.join(", "))
+                })?;
+                calldata.push(FieldElement::from(index));
+                return Ok(());
+            }
+            // Felts, addresses and class hashes, or enums given by index.
+            _ => {
+                calldata.push(parse_felt(value)?);
+                return Ok(());
+            }
+        },
+    };
+
+    let value = parse_number(value)?;
+    if value.bits() > bits {
+        bail!("{value} doesn't fit in a {name}");
+    }
+    calldata.push(to_felt(&value)?);
+    Ok(())
+}
+
+/// A felt given as a number, a decimal or hex string, or a short string.
+pub fn parse_felt(value: &Value) -> Result<FieldElement> {
+    match value {
+        Value::String(s) if !is_number(s) => Ok(cairo_short_string_to_felt(s)?),
+        Value::Bool(b) => Ok(if *b { FieldElement::ONE } else { FieldElement::ZERO }),
+        _ => to_felt(&parse_number(value)?),
+    }
+}
+
+fn is_number(s: &str) -> bool {
+    match s.strip_prefix("0x") {
+        Some(hex) => !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => !s.is_empty() && s.chars().all(|c| c.is_ascii_digit()),
+    }
+}
+
+/// An integer, or a decimal or hex string.
+fn parse_number(value: &Value) -> Result<BigUint> {
+    let number = match value {
+        Value::Number(n) => n.as_u64().map(BigUint::from),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => BigUint::parse_bytes(hex.as_bytes(), 16),
+            None => BigUint::parse_bytes(s.as_bytes(), 10),
+        },
+        _ => None,
+    };
+    number.ok_or(anyhow!("expected a positive integer, or a decimal or hex string, got {value}"))
+}
+
+fn to_felt(value: &BigUint) -> Result<FieldElement> {
+    FieldElement::from_dec_str(&value.to_string()).map_err(|_| anyhow!("{value} isn't a felt"))
+}
@@ -0,0 +1,233 @@
+use std::cell::RefCell;
+use std::fs;
+use std::time::Duration;
+
+use anyhow::bail;
+use assert_fs::TempDir;
+use camino::Utf8PathBuf;
+use dojo_world::manifest::{Component, Input, Manifest, Member, System};
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+use super::{Script, encode_call};
+
+fn input(name: &str, ty: &str) -> Input {
+    Input { name: name.into(), ty: ty.into() }
+}
+
+fn manifest() -> Manifest {
+    Manifest {
+        components: vec![Component {
+            name: "Facing".into(),
+            members: vec![Member {
+                name: "direction".into(),
+                ty: "dojo_examples::Direction".into(),
+                variants: Some(vec!["Left".into(), "Right".into()]),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        systems: vec![
+            System { name: "SpawnSystem".into(), ..Default::default() },
+            System {
+                name: "MoveSystem".into(),
+                inputs: vec![input("direction", "dojo_examples::Direction")],
+                ..Default::default()
+            },
+            System {
+                name: "TradeSystem".into(),
+                inputs: vec![
+                    input("to", "core::starknet::contract_address::ContractAddress"),
+                    input("amount", "core::integer::u256"),
+                    input("items", "core::array::Array::<core::integer::u8>"),
+                    input("gift", "core::bool"),
+                ],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+fn write_script(dir: &TempDir, name: &str, content: &str) -> Utf8PathBuf {
+    let path = Utf8PathBuf::from_path_buf(dir.path().join(name)).unwrap();
+    fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn test_load_script() {
+    let dir = TempDir::new().unwrap();
+    let toml = write_script(
+        &dir,
+        "calls.toml",
+        r#"
+        [[calls]]
+        system = "spawn"
+
+        [[calls]]
+        system = "move"
+        args = { direction = "Left" }
+
+        [[calls]]
+        system = "trade"
+        args = ["0x1234", 100, [1, 2], true]
+        "#,
+    );
+    let json = write_script(
+        &dir,
+        "calls.json",
+        r#"{
+            "calls": [
+                { "system": "spawn" },
+                { "system": "move", "args": { "direction": "Left" } },
+                { "system": "trade", "args": ["0x1234", 100, [1, 2], true] }
+            ]
+        }"#,
+    );
+
+    // Both formats describe the same calls.
+    let from_toml = Script::load_from_path(&toml).unwrap().encode(&manifest()).unwrap();
+    let from_json = Script::load_from_path(&json).unwrap().encode(&manifest()).unwrap();
+    assert_eq!(from_toml, from_json);
+
+    let calldata = |values: &[u64]| -> Vec<FieldElement> {
+        values.iter().map(|v| FieldElement::from(*v)).collect()
+    };
+    assert_eq!(
+        from_toml,
+        [
+            ("Spawn".to_string(), vec![]),
+            ("Move".to_string(), calldata(&[0])),
+            ("Trade".to_string(), calldata(&[0x1234, 100, 0, 2, 1, 2, 1])),
+        ]
+    );
+
+    let invalid = write_script(&dir, "invalid.toml", "[[calls]]\nargs = [1]\n");
+    let error = Script::load_from_path(&invalid).unwrap_err();
+    assert_eq!(error.to_string(), format!("Failed to parse {invalid}"));
+}
+
+#[test]
+fn test_encode_call() {
+    let manifest = manifest();
+
+    // The case and `System` suffix of the name don't matter, the arguments are in any order.
+    let args = json!({
+        "gift": false,
+        "items": [],
+        "amount": "0x100000000000000000000000000000002",
+        "to": "Bob"
+    });
+    let (name, calldata) = encode_call(&manifest, "tradeSystem", Some(&args)).unwrap();
+    assert_eq!(name, "Trade");
+    assert_eq!(
+        calldata,
+        [
+            cairo_short_string_to_felt("Bob").unwrap(),
+            FieldElement::TWO,
+            FieldElement::ONE,
+            FieldElement::ZERO,
+            FieldElement::ZERO,
+        ]
+    );
+
+    let error = |system: &str, args| {
+        format!("{:#}", encode_call(&manifest, system, Some(&args)).unwrap_err())
+    };
+    assert_eq!(error("attack", json!([])), "system `attack` not found in the manifest");
+    assert_eq!(
+        error("move", json!({ "direction": "Up" })),
+        "invalid arguments of Move: `direction`: `Up` isn't a variant of Direction: Left, Right"
+    );
+    assert_eq!(
+        error("move", json!({ "speed": 2 })),
+        "invalid arguments of Move: `speed` isn't an input"
+    );
+    assert_eq!(
+        error("trade", json!(["0x1234", 100])),
+        "invalid arguments of Trade: expected 4 arguments (to, amount, items, gift), got 2"
+    );
+    assert_eq!(
+        error("trade", json!(["0x1234", 100, [256], true])),
+        "invalid arguments of Trade: `items`: 256 doesn't fit in a u8"
+    );
+}
+
+#[test]
+fn test_encode_script_errors() {
+    let script: Script = toml::from_str(
+        r#"
+        [[calls]]
+        system = "spawn"
+
+        [[calls]]
+        system = "move"
+        args = ["Down"]
+        "#,
+    )
+    .unwrap();
+
+    // The failing call is reported by its position in the script.
+    let error = script.encode(&manifest()).unwrap_err();
+    assert!(error.to_string().starts_with("call 2: invalid arguments of Move"));
+}
+
+#[test]
+fn test_batches() {
+    assert_eq!(batches(vec!["spawn", "move"], true), [vec!["spawn"], vec!["move"]]);
+    assert_eq!(batches(vec!["spawn", "move"], false), [vec!["spawn", "move"]]);
+}
+
+/// Sends the batches, recording when each one is sent and when its transaction is accepted. The
+/// transaction of `rejected` fails to be accepted.
+async fn send_recorded(batches: Vec<Vec<&str>>, rejected: Option<&str>) -> (Vec<String>, bool) {
+    let events = RefCell::new(vec![]);
+    let events_ref = &events;
+    let res = send_in_order(
+        batches,
+        |batch| async move {
+            events_ref.borrow_mut().push(format!("send {}", batch.join(",")));
+            Ok(FieldElement::from(batch.len()))
+        },
+        |batch, _| async move {
+            // The transaction takes some time to be accepted.
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            if rejected.map_or(false, |rejected| batch.contains(&rejected)) {
+                bail!("rejected");
+            }
+            events_ref.borrow_mut().push(format!("accepted {}", batch.join(",")));
+            Ok(())
+        },
+    )
+    .await;
+    (events.into_inner(), res.is_ok())
+}
+
+#[tokio::test]
+async fn test_sequential_calls_wait_for_the_previous() {
+    let (events, sent) = send_recorded(batches(vec!["spawn", "move", "trade"], true), None).await;
+    assert!(sent);
+    assert_eq!(
+        events,
+        [
+            "send spawn",
+            "accepted spawn",
+            "send move",
+            "accepted move",
+            "send trade",
+            "accepted trade"
+        ]
+    );
+
+    // The calls after a rejected transaction aren't sent.
+    let (events, sent) =
+        send_recorded(batches(vec!["spawn", "move", "trade"], true), Some("move")).await;
+    assert!(!sent);
+    assert_eq!(events, ["send spawn", "accepted spawn", "send move"]);
+
+    let (events, sent) = send_recorded(batches(vec!["spawn", "move", "trade"], false), None).await;
+    assert!(sent);
+    assert_eq!(events, ["send spawn,move,trade", "accepted spawn,move,trade"]);
+}
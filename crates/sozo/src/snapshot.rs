@@ -0,0 +1,212 @@
+//! Snapshot tests run by `sozo test`. A snapshot test is a free function annotated with
+//! `#[snapshot]` whose returned value is serialized and compared against the snapshot committed
+//! in `tests/snapshots`, instead of asserting on every felt by hand:
+//!
+//! ```cairo
+//! #[snapshot]
+//! #[available_gas(2000000)]
+//! fn snapshot_spawned_player() -> (Position, Moves) {
+//!     spawn_player(0x1337)
+//! }
+//! ```
+//!
+//! `sozo test --update-snapshots` writes the snapshots of the tests, once the new values are
+//! reviewed. Like the property tests, the functions run without a Starknet state and can't deploy
+//! the world.
+
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+use cairo_lang_compiler::db::RootDatabase;
+use cairo_lang_defs::ids::FreeFunctionId;
+use cairo_lang_runner::{RunResultValue, StarknetState};
+use cairo_lang_semantic::db::SemanticGroup;
+use camino::{Utf8Path, Utf8PathBuf};
+use starknet::core::types::FieldElement;
+
+use crate::property::{available_gas, build_runner, format_panic, readable_short_string};
+
+#[cfg(test)]
+#[path = "snapshot_test.rs"]
+mod test;
+
+/// Directory of the snapshots, relative to the project.
+pub const SNAPSHOTS_DIR: &str = "tests/snapshots";
+
+/// A function annotated with `#[snapshot]`.
+pub struct SnapshotTest {
+    pub path: String,
+    function_id: FreeFunctionId,
+    return_type: String,
+    available_gas: Option<usize>,
+}
+
+impl SnapshotTest {
+    /// The file of the snapshot in `snapshots_dir`, named after the path of the test.
+    fn snapshot_path(&self, snapshots_dir: &Utf8Path) -> Utf8PathBuf {
+        snapshots_dir.join(format!("{}.snap", self.path.replace("::", "__")))
+    }
+}
+
+/// The snapshot tests of the main crates, failing on those taking parameters.
+pub fn collect(
+    db: &RootDatabase,
+    functions: Vec<(FreeFunctionId, String)>,
+) -> Result<Vec<SnapshotTest>> {
+    let mut tests = vec![];
+    for (function_id, path) in functions {
+        let signature = db
+            .free_function_signature(function_id)
+            .ok()
+            .with_context(|| format!("Failed to read the signature of {path}"))?;
+        if !signature.params.is_empty() {
+            bail!("{path}: snapshot tests can't take parameters");
+        }
+
+        let return_type = signature.return_type.format(db);
+        let available_gas = available_gas(db, function_id, &path)?;
+        tests.push(SnapshotTest { path, function_id, return_type, available_gas });
+    }
+
+    Ok(tests)
+}
+
+/// Runs every test and compares its value to its snapshot in `snapshots_dir`, or writes the
+/// snapshot instead with `update`.
+pub fn run(
+    db: &RootDatabase,
+    tests: &[SnapshotTest],
+    snapshots_dir: &Utf8Path,
+    update: bool,
+) -> Result<()> {
+    if tests.is_empty() {
+        return Ok(());
+    }
+
+    let runner = build_runner(db, tests.iter().map(|test| (test.function_id, test.path.as_str())))?;
+
+    println!("running {} snapshot tests", tests.len());
+    let mut failed = vec![];
+    let mut updated = 0;
+    for test in tests {
+        let function = runner.find_function(&test.path)?;
+        let result =
+            runner.run_function(function, &[], test.available_gas, StarknetState::default())?;
+        let values = match result.value {
+            RunResultValue::Success(values) => values,
+            RunResultValue::Panic(data) => {
+                let data = data
+                    .iter()
+                    .map(|felt| FieldElement::from_dec_str(&felt.to_biguint().to_string()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                println!("test {} ... fail", test.path);
+                println!("    panicked with: {}", format_panic(&data));
+                failed.push(test.path.as_str());
+                continue;
+            }
+        };
+        let values = values
+            .iter()
+            .map(|felt| FieldElement::from_dec_str(&felt.to_biguint().to_string()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let actual = render(&test.path, &test.return_type, &values);
+
+        let snapshot_path = test.snapshot_path(snapshots_dir);
+        match compare(&snapshot_path, &actual, update)? {
+            Comparison::Matches => println!("test {} ... ok", test.path),
+            Comparison::Updated => {
+                println!("test {} ... updated", test.path);
+                updated += 1;
+            }
+            Comparison::Missing => {
+                println!("test {} ... fail", test.path);
+                println!("    no snapshot at {snapshot_path}");
+                failed.push(test.path.as_str());
+            }
+            Comparison::Differs(expected) => {
+                println!("test {} ... fail", test.path);
+                print_diff(&expected, &actual);
+                failed.push(test.path.as_str());
+            }
+        }
+    }
+
+    println!(
+        "\nsnapshot test result: {}. {} passed; {} failed; {updated} updated",
+        if failed.is_empty() { "ok" } else { "FAILED" },
+        tests.len() - failed.len() - updated,
+        failed.len()
+    );
+    if !failed.is_empty() {
+        bail!(
+            "{} snapshot tests failed, review the new values and record them with \
+             `--update-snapshots`",
+            failed.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// How the value returned by a test compares to its snapshot.
+#[derive(Debug, PartialEq)]
+enum Comparison {
+    Matches,
+    /// The snapshot was written with the new value.
+    Updated,
+    Missing,
+    /// The snapshot holds another value, its content.
+    Differs(String),
+}
+
+/// Compares the rendered value of a test to its snapshot, writing the value to the snapshot
+/// instead of failing with `update`.
+fn compare(snapshot_path: &Utf8Path, actual: &str, update: bool) -> Result<Comparison> {
+    let expected = fs::read_to_string(snapshot_path).ok();
+    if expected.as_deref() == Some(actual) {
+        return Ok(Comparison::Matches);
+    }
+
+    if update {
+        if let Some(dir) = snapshot_path.parent() {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create the directory {dir}"))?;
+        }
+        fs::write(snapshot_path, actual)
+            .with_context(|| format!("Failed to write {snapshot_path}"))?;
+        return Ok(Comparison::Updated);
+    }
+
+    Ok(expected.map_or(Comparison::Missing, Comparison::Differs))
+}
+
+/// The returned felts one per line, with their short strings decoded in comments.
+fn render(path: &str, return_type: &str, values: &[FieldElement]) -> String {
+    let mut snapshot = format!("// {path}\n// returns: {return_type}\n");
+    for value in values {
+        match readable_short_string(value) {
+            Some(s) => snapshot.push_str(&format!("{value:#x} // '{s}'\n")),
+            None => snapshot.push_str(&format!("{value:#x}\n")),
+        }
+    }
+    snapshot
+}
+
+/// Prints the lines of the snapshot that changed.
+fn print_diff(expected: &str, actual: &str) {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+    for line in 0..expected.len().max(actual.len()) {
+        match (expected.get(line), actual.get(line)) {
+            (Some(e), Some(a)) if e == a => {}
+            (e, a) => {
+                println!("    line {}:", line + 1);
+                if let Some(e) = e {
+                    println!("    - {e}");
+                }
+                if let Some(a) = a {
+                    println!("    + {a}");
+                }
+            }
+        }
+    }
+}
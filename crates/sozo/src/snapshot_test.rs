@@ -0,0 +1,67 @@
+use std::fs;
+
+use assert_fs::TempDir;
+use camino::Utf8PathBuf;
+use starknet::core::types::FieldElement;
+
+use super::{compare, render, Comparison};
+
+fn snapshot() -> String {
+    let values = [FieldElement::from(0x1337_u32), FieldElement::from_hex_be("0x50696e67").unwrap()];
+    render("dojo_examples::snapshot_player", "(core::felt252, core::felt252)", &values)
+}
+
+#[test]
+fn test_render() {
+    assert_eq!(
+        snapshot(),
+        "// dojo_examples::snapshot_player\n// returns: (core::felt252, core::felt252)\n0x1337\n\
+         0x50696e67 // 'Ping'\n"
+    );
+}
+
+#[test]
+fn test_compare_new_snapshot() {
+    let dir = TempDir::new().unwrap();
+    let snapshots_dir = Utf8PathBuf::from_path_buf(dir.path().join("tests/snapshots")).unwrap();
+    let snapshot_path = snapshots_dir.join("dojo_examples__snapshot_player.snap");
+
+    assert_eq!(compare(&snapshot_path, &snapshot(), false).unwrap(), Comparison::Missing);
+    assert!(!snapshot_path.exists());
+}
+
+#[test]
+fn test_compare_mismatch() {
+    let dir = TempDir::new().unwrap();
+    let snapshot_path =
+        Utf8PathBuf::from_path_buf(dir.path().join("dojo_examples__snapshot_player.snap")).unwrap();
+    let outdated = snapshot().replace("0x1337", "0x1338");
+    fs::write(&snapshot_path, &outdated).unwrap();
+
+    assert_eq!(
+        compare(&snapshot_path, &snapshot(), false).unwrap(),
+        Comparison::Differs(outdated.clone())
+    );
+    // A mismatching snapshot is left untouched without `--update-snapshots`.
+    assert_eq!(fs::read_to_string(&snapshot_path).unwrap(), outdated);
+
+    fs::write(&snapshot_path, snapshot()).unwrap();
+    assert_eq!(compare(&snapshot_path, &snapshot(), false).unwrap(), Comparison::Matches);
+}
+
+#[test]
+fn test_compare_update_snapshots() {
+    let dir = TempDir::new().unwrap();
+    let snapshots_dir = Utf8PathBuf::from_path_buf(dir.path().join("tests/snapshots")).unwrap();
+    let snapshot_path = snapshots_dir.join("dojo_examples__snapshot_player.snap");
+
+    // The missing snapshot is written, along with its directory.
+    assert_eq!(compare(&snapshot_path, &snapshot(), true).unwrap(), Comparison::Updated);
+    assert_eq!(fs::read_to_string(&snapshot_path).unwrap(), snapshot());
+    assert_eq!(compare(&snapshot_path, &snapshot(), true).unwrap(), Comparison::Matches);
+
+    // A mismatching snapshot is overwritten with the new value.
+    let updated = snapshot().replace("0x1337", "0x1338");
+    assert_eq!(compare(&snapshot_path, &updated, true).unwrap(), Comparison::Updated);
+    assert_eq!(fs::read_to_string(&snapshot_path).unwrap(), updated);
+}
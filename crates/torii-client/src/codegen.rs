@@ -0,0 +1,78 @@
+//! Generates the Rust types of a world's components from its manifest, typically from a build
+//! script:
+//!
+//! ```ignore
+//! let manifest = Manifest::load_from_path("target/dev/manifest.json")?;
+//! let out = PathBuf::from(env::var("OUT_DIR")?).join("components.rs");
+//! fs::write(out, torii_client::codegen::generate(&manifest, None))?;
+//! ```
+
+use dojo_world::manifest::{Component, Manifest, Member};
+
+#[cfg(test)]
+#[path = "codegen_test.rs"]
+mod test;
+
+/// Returns the source of a struct implementing [`crate::Component`] for every component of the
+/// manifest, and of an enum for every enum member. `namespace` is the one torii serves the world
+/// under, if any.
+pub fn generate(manifest: &Manifest, namespace: Option<&str>) -> String {
+    let mut out = String::from("// Generated by torii-client from the world's manifest.\n");
+
+    let mut enums = vec![];
+    for component in &manifest.components {
+        out.push('\n');
+        out.push_str(&generate_component(component, namespace));
+
+        for member in &component.members {
+            if let Some(variants) = &member.variants {
+                if !enums.contains(&member.ty) {
+                    enums.push(member.ty.clone());
+                    out.push('\n');
+                    out.push_str(&generate_enum(&member.ty, variants));
+                }
+            }
+        }
+    }
+
+    out
+}
+
+fn generate_component(component: &Component, namespace: Option<&str>) -> String {
+    let query = format!("{}{}", namespace.unwrap_or_default(), component.name).to_lowercase();
+    let fields = component.members.iter().map(|m| m.name.as_str()).collect::<Vec<_>>().join(" ");
+
+    let mut out = "#[derive(Debug, Clone, PartialEq, serde::Deserialize)]\n".to_string();
+    out.push_str(&format!("pub struct {} {{\n", component.name));
+    for member in &component.members {
+        out.push_str(&format!("    pub {}: {},\n", member.name, rust_type(member)));
+    }
+    out.push_str("}\n\n");
+
+    out.push_str(&format!("impl torii_client::Component for {} {{\n", component.name));
+    out.push_str(&format!("    const QUERY: &'static str = \"{query}\";\n"));
+    out.push_str(&format!("    const FIELDS: &'static str = \"{fields}\";\n"));
+    out.push_str("}\n");
+
+    out
+}
+
+fn generate_enum(name: &str, variants: &[String]) -> String {
+    let mut out = "#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Deserialize)]\n".to_string();
+    out.push_str(&format!("pub enum {name} {{\n"));
+    for variant in variants {
+        out.push_str(&format!("    {variant},\n"));
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Torii serves integers up to 64 bits as numbers and the larger ones as strings.
+fn rust_type(member: &Member) -> String {
+    match member.ty.as_str() {
+        ty @ ("u8" | "u16" | "u32" | "u64") => ty.to_string(),
+        "bool" => "bool".to_string(),
+        ty if member.variants.is_some() => ty.to_string(),
+        _ => "String".to_string(),
+    }
+}
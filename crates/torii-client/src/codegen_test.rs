@@ -0,0 +1,43 @@
+use dojo_world::manifest::{Component, Manifest, Member};
+
+use super::generate;
+
+fn member(name: &str, ty: &str, variants: Option<Vec<String>>) -> Member {
+    Member { name: name.into(), ty: ty.into(), slot: 0, offset: 0, bits: None, variants, doc: None }
+}
+
+#[test]
+fn test_generate_components() {
+    let direction = Some(vec!["Left".to_string(), "Right".to_string()]);
+    let manifest = Manifest {
+        components: vec![
+            Component {
+                name: "Position".into(),
+                members: vec![member("x", "u32", None), member("y", "u32", None)],
+                ..Default::default()
+            },
+            Component {
+                name: "Moves".into(),
+                members: vec![
+                    member("remaining", "u8", None),
+                    member("last", "Direction", direction.clone()),
+                    member("owner", "felt252", None),
+                ],
+                ..Default::default()
+            },
+            Component {
+                name: "Facing".into(),
+                members: vec![member("direction", "Direction", direction)],
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+
+    let source = generate(&manifest, Some("Game"));
+    assert!(source.contains("pub struct Position {\n    pub x: u32,\n    pub y: u32,\n}"));
+    assert!(source.contains("const QUERY: &'static str = \"gameposition\";"));
+    assert!(source.contains("const FIELDS: &'static str = \"remaining last owner\";"));
+    assert!(source.contains("    pub last: Direction,\n    pub owner: String,\n"));
+    assert_eq!(source.matches("pub enum Direction {\n    Left,\n    Right,\n}").count(), 1);
+}
@@ -0,0 +1,225 @@
+//! Typed client for the torii GraphQL endpoint, usable from native and wasm targets.
+//!
+//! Components are fetched through types implementing [`Component`], which can be generated from
+//! a world's manifest with the `codegen` feature instead of written by hand.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use starknet::core::types::FieldElement;
+use thiserror::Error;
+use url::Url;
+
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "subscriptions")]
+pub mod subscription;
+
+#[derive(Debug, Error)]
+pub enum ClientError {
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error("GraphQL query failed: {}", .0.join(", "))]
+    GraphQL(Vec<String>),
+    #[error("Failed to decode the response: {0}")]
+    Decode(#[from] serde_json::Error),
+    #[cfg(feature = "subscriptions")]
+    #[error(transparent)]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    #[cfg(feature = "subscriptions")]
+    #[error("Subscription failed: {0}")]
+    Subscription(String),
+}
+
+/// A component as served by torii, fetched with [`Client::component`].
+pub trait Component: DeserializeOwned {
+    /// Name of the component's query, the component's name in lowercase, prefixed if torii serves
+    /// it under a namespace.
+    const QUERY: &'static str;
+    /// Space separated members selected by the query.
+    const FIELDS: &'static str;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Entity {
+    pub id: String,
+    pub name: String,
+    pub partition_id: String,
+    /// Comma separated keys of the entity.
+    pub keys: String,
+    pub transaction_hash: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityStateUpdate {
+    /// Cursor of the update, to resume subscriptions from.
+    pub id: String,
+    pub entity_id: String,
+    pub component_id: String,
+    pub component_name: String,
+    pub system_call_id: i64,
+    pub transaction_hash: String,
+    /// Comma separated values of the component.
+    pub data: String,
+    /// Whether the component was removed from the entity, the game object should be despawned.
+    #[serde(default)]
+    pub deleted: bool,
+    pub created_at: String,
+}
+
+impl EntityStateUpdate {
+    /// Values of the component, `None` if they aren't all valid felts.
+    pub fn values(&self) -> Option<Vec<FieldElement>> {
+        if self.data.is_empty() {
+            return Some(vec![]);
+        }
+        self.data.split(',').map(|value| FieldElement::from_hex_be(value.trim()).ok()).collect()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemMetrics {
+    pub system: String,
+    pub calls: i64,
+    pub failures: i64,
+    pub failure_rate: f64,
+    pub total_fee: String,
+    pub average_fee: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    pub system: String,
+    /// Component the system can write, `*` for admins.
+    pub component: String,
+    pub role: String,
+    pub transaction_hash: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    data: Option<Value>,
+    #[serde(default)]
+    errors: Vec<GraphQLError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphQLError {
+    message: String,
+}
+
+/// Client of a torii server, cheap to clone.
+#[derive(Debug, Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    /// Root of the server, the endpoints are relative to it.
+    url: Url,
+}
+
+impl Client {
+    /// Creates a client of the server at `url`, e.g. `http://localhost:8080`.
+    pub fn new(url: Url) -> Self {
+        Self::with_http_client(reqwest::Client::new(), url)
+    }
+
+    pub fn with_http_client(http: reqwest::Client, url: Url) -> Self {
+        Self { http, url }
+    }
+
+    /// Runs a query and returns its `data`.
+    pub async fn query(&self, query: &str, variables: Value) -> Result<Value, ClientError> {
+        let response: Response = self
+            .http
+            .post(self.url.join("query").expect("query is a valid path"))
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+
+        if !response.errors.is_empty() {
+            return Err(ClientError::GraphQL(
+                response.errors.into_iter().map(|e| e.message).collect(),
+            ));
+        }
+
+        Ok(response.data.unwrap_or_default())
+    }
+
+    /// Runs a query selecting a single field and decodes it.
+    async fn query_field<T: DeserializeOwned>(
+        &self,
+        field: &str,
+        query: &str,
+        variables: Value,
+    ) -> Result<T, ClientError> {
+        let mut data = self.query(query, variables).await?;
+        Ok(serde_json::from_value(data[field].take())?)
+    }
+
+    /// Returns the component stored under `id`.
+    pub async fn component<C: Component>(&self, id: i64) -> Result<C, ClientError> {
+        let query = format!("query($id: Int!) {{ {}(id: $id) {{ {} }} }}", C::QUERY, C::FIELDS);
+        self.query_field(C::QUERY, &query, json!({ "id": id })).await
+    }
+
+    pub async fn entity(&self, id: &str) -> Result<Entity, ClientError> {
+        self.query_field(
+            "entity",
+            "query($id: ID!) { entity(id: $id) { id name partitionId keys transactionHash \
+             createdAt } }",
+            json!({ "id": id }),
+        )
+        .await
+    }
+
+    /// Returns the component updates made by a transaction, in the order they were indexed.
+    pub async fn entity_state_updates_by_transaction(
+        &self,
+        transaction_hash: FieldElement,
+    ) -> Result<Vec<EntityStateUpdate>, ClientError> {
+        self.query_field(
+            "entityStateUpdatesByTransaction",
+            "query($hash: FieldElement!) { entityStateUpdatesByTransaction(transactionHash: \
+             $hash) { id entityId componentId componentName systemCallId transactionHash data \
+             createdAt } }",
+            json!({ "hash": format!("{transaction_hash:#x}") }),
+        )
+        .await
+    }
+
+    /// Returns the metrics of the most called systems first.
+    pub async fn system_metrics(&self, limit: i64) -> Result<Vec<SystemMetrics>, ClientError> {
+        self.query_field(
+            "systemMetrics",
+            "query($limit: Int) { systemMetrics(limit: $limit) { system calls failures \
+             failureRate totalFee averageFee updatedAt } }",
+            json!({ "limit": limit }),
+        )
+        .await
+    }
+
+    /// Returns the components systems are allowed to write, optionally filtered by system and by
+    /// component.
+    pub async fn authorizations(
+        &self,
+        system: Option<&str>,
+        component: Option<&str>,
+    ) -> Result<Vec<Authorization>, ClientError> {
+        self.query_field(
+            "authorizations",
+            "query($system: String, $component: String) { authorizations(system: $system, \
+             component: $component) { system component role transactionHash updatedAt } }",
+            json!({ "system": system, "component": component }),
+        )
+        .await
+    }
+}
@@ -0,0 +1,141 @@
+//! Subscriptions to the torii websocket endpoint, following the `graphql-transport-ws` protocol.
+
+use futures_util::{SinkExt, StreamExt};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{channel, Receiver};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+use crate::{Client, ClientError, EntityStateUpdate, GraphQLError};
+
+const PROTOCOL: &str = "graphql-transport-ws";
+/// Items received but not yet consumed before the connection is throttled.
+const SUBSCRIPTION_BUFFER: usize = 100;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// Items pushed by the server for a subscription. It ends when the connection is lost or the
+/// server completes it, and is closed once dropped.
+pub struct Subscription<T> {
+    receiver: Receiver<Result<T, ClientError>>,
+}
+
+impl<T> Subscription<T> {
+    pub async fn next(&mut self) -> Option<Result<T, ClientError>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Client {
+    /// Subscribes to the component updates indexed after the update `cursor`, or to the new ones
+    /// without a cursor. After a disconnection, subscribing again from the `id` of the last update
+    /// received doesn't miss any.
+    pub async fn subscribe_entity_state_updates(
+        &self,
+        cursor: Option<&str>,
+    ) -> Result<Subscription<EntityStateUpdate>, ClientError> {
+        self.subscribe(
+            "entityStateUpdates",
+            "subscription($cursor: ID) { entityStateUpdates(cursor: $cursor) { id entityId \
+             componentId componentName systemCallId transactionHash data deleted createdAt } }",
+            json!({ "cursor": cursor }),
+        )
+        .await
+    }
+
+    /// Runs a subscription selecting a single field and decodes its items.
+    async fn subscribe<T: DeserializeOwned + Send + 'static>(
+        &self,
+        field: &'static str,
+        query: &str,
+        variables: Value,
+    ) -> Result<Subscription<T>, ClientError> {
+        let mut url = self.url.join("ws").expect("ws is a valid path");
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        url.set_scheme(scheme).expect("websocket schemes are valid");
+
+        let mut request = url.as_str().into_client_request()?;
+        request.headers_mut().insert("Sec-WebSocket-Protocol", HeaderValue::from_static(PROTOCOL));
+        let (mut socket, _) = connect_async(request).await?;
+
+        send(&mut socket, json!({ "type": "connection_init" })).await?;
+        loop {
+            match receive(&mut socket).await? {
+                Some(message) if message["type"] == "connection_ack" => break,
+                Some(_) => continue,
+                None => {
+                    return Err(ClientError::Subscription("connection closed on init".into()));
+                }
+            }
+        }
+
+        let payload = json!({ "query": query, "variables": variables });
+        send(&mut socket, json!({ "id": "1", "type": "subscribe", "payload": payload })).await?;
+
+        let (sender, receiver) = channel(SUBSCRIPTION_BUFFER);
+        tokio::spawn(async move {
+            loop {
+                let mut message = match receive(&mut socket).await {
+                    Ok(Some(message)) => message,
+                    Ok(None) => return,
+                    Err(e) => {
+                        let _ = sender.send(Err(e)).await;
+                        return;
+                    }
+                };
+
+                let item = match message["type"].as_str() {
+                    Some("next") => decode_item(message["payload"].take(), field),
+                    Some("error") => Err(graphql_error(message["payload"].take())),
+                    Some("ping") => {
+                        if send(&mut socket, json!({ "type": "pong" })).await.is_err() {
+                            return;
+                        }
+                        continue;
+                    }
+                    Some("complete") => return,
+                    _ => continue,
+                };
+
+                let failed = item.is_err();
+                if sender.send(item).await.is_err() || failed {
+                    return;
+                }
+            }
+        });
+
+        Ok(Subscription { receiver })
+    }
+}
+
+async fn send(socket: &mut Socket, message: Value) -> Result<(), ClientError> {
+    Ok(socket.send(Message::Text(message.to_string())).await?)
+}
+
+/// Receives the next protocol message, `None` once the connection is closed.
+async fn receive(socket: &mut Socket) -> Result<Option<Value>, ClientError> {
+    while let Some(message) = socket.next().await {
+        match message? {
+            Message::Text(text) => return Ok(Some(serde_json::from_str(&text)?)),
+            Message::Close(_) => return Ok(None),
+            _ => continue,
+        }
+    }
+    Ok(None)
+}
+
+fn decode_item<T: DeserializeOwned>(mut payload: Value, field: &str) -> Result<T, ClientError> {
+    if payload.get("errors").map_or(false, |errors| !errors.is_null()) {
+        return Err(graphql_error(payload["errors"].take()));
+    }
+    Ok(serde_json::from_value(payload["data"][field].take())?)
+}
+
+fn graphql_error(errors: Value) -> ClientError {
+    let errors: Vec<GraphQLError> = serde_json::from_value(errors).unwrap_or_default();
+    ClientError::GraphQL(errors.into_iter().map(|e| e.message).collect())
+}
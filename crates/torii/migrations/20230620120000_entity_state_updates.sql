@@ -0,0 +1,14 @@
+CREATE TABLE entity_state_updates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    entity_id TEXT NOT NULL,
+    component_id TEXT NOT NULL,
+    system_call_id INTEGER NOT NULL,
+    data TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (entity_id) REFERENCES entities(id),
+    FOREIGN KEY (component_id) REFERENCES components(id),
+    FOREIGN KEY (system_call_id) REFERENCES system_calls(id)
+);
+
+CREATE INDEX idx_entity_state_updates_system_call_id ON entity_state_updates (system_call_id);
+CREATE INDEX idx_system_calls_transaction_hash ON system_calls (transaction_hash);
@@ -0,0 +1,3 @@
+-- Members removed from a component by an upgrade. Their storage columns are kept so they remain
+-- queryable, as deprecated fields, until clients have moved to the new schema.
+ALTER TABLE components ADD COLUMN deprecated_definition TEXT NOT NULL DEFAULT '[]';
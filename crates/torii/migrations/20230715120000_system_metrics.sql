@@ -0,0 +1,9 @@
+CREATE TABLE system_metrics (
+    system TEXT NOT NULL PRIMARY KEY,
+    calls INTEGER NOT NULL DEFAULT 0,
+    failures INTEGER NOT NULL DEFAULT 0,
+    total_fee TEXT NOT NULL DEFAULT '0x0',
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_system_metrics_calls ON system_metrics (calls);
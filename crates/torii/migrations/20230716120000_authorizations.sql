@@ -0,0 +1,47 @@
+-- Role of a target, `resource` is empty for world-wide roles.
+CREATE TABLE auth_roles (
+    target TEXT NOT NULL,
+    resource TEXT NOT NULL DEFAULT '',
+    role TEXT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (target, resource)
+);
+
+-- Resources a role is allowed to write.
+CREATE TABLE auth_resources (
+    role TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    transaction_hash TEXT NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (role, resource)
+);
+
+CREATE TABLE auth_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    action TEXT NOT NULL,
+    target TEXT NOT NULL DEFAULT '',
+    role TEXT NOT NULL DEFAULT '',
+    resource TEXT NOT NULL DEFAULT '',
+    transaction_hash TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX idx_auth_log_target ON auth_log (target);
+
+-- Components each system is allowed to write, admins being allowed to write all of them.
+CREATE VIEW authorizations AS
+SELECT
+    auth_roles.target AS system,
+    auth_roles.resource AS component,
+    auth_roles.role AS role,
+    auth_roles.transaction_hash AS transaction_hash,
+    MAX(auth_roles.updated_at, auth_resources.updated_at) AS updated_at
+FROM auth_roles
+JOIN auth_resources
+    ON auth_resources.role = auth_roles.role AND auth_resources.resource = auth_roles.resource
+WHERE auth_roles.resource != ''
+UNION ALL
+SELECT target, '*', role, transaction_hash, updated_at
+FROM auth_roles
+WHERE resource = '' AND role = 'Admin';
@@ -0,0 +1,6 @@
+-- Entities whose components were all removed are archived instead of deleted, so clients can
+-- still look them up.
+ALTER TABLE entities ADD COLUMN archived_at DATETIME;
+
+-- Updates removing a component from an entity, their data is empty.
+ALTER TABLE entity_state_updates ADD COLUMN deleted BOOLEAN NOT NULL DEFAULT FALSE;
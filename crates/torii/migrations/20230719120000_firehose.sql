@@ -0,0 +1,13 @@
+-- Decoded state updates retained until every firehose sink has published them.
+CREATE TABLE firehose_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    payload TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Id of the last update each sink published.
+CREATE TABLE firehose_cursors (
+    sink TEXT PRIMARY KEY,
+    cursor INTEGER NOT NULL DEFAULT 0,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,8 @@
+-- Named queries over the latest values of a component, each served as its own GraphQL field.
+CREATE TABLE saved_queries (
+    name TEXT PRIMARY KEY,
+    component_name TEXT NOT NULL,
+    -- JSON of the filters, ordering and limit
+    definition TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
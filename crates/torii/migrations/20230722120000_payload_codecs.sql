@@ -0,0 +1,6 @@
+-- Codec of the payloads compressed during maintenance, NULL for the ones stored as is. Payloads
+-- used to be tagged with a `deflate:` prefix instead.
+ALTER TABLE entity_state_updates ADD COLUMN codec TEXT;
+
+UPDATE entity_state_updates SET codec = 'deflate', data = SUBSTR(data, 9)
+WHERE data LIKE 'deflate:%';
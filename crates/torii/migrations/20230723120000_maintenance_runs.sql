@@ -0,0 +1,15 @@
+-- Metrics of the database maintenance runs, durations in milliseconds.
+CREATE TABLE maintenance_runs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    checkpoint_ms INTEGER NOT NULL,
+    compression_ms INTEGER NOT NULL,
+    compressed_rows INTEGER NOT NULL,
+    vacuum_ms INTEGER NOT NULL,
+    -- Whether the database was rebuilt by a full VACUUM, switching it to incremental vacuuming
+    full_vacuum BOOLEAN NOT NULL,
+    reclaimed_pages INTEGER NOT NULL,
+    -- Free pages left once vacuumed
+    free_pages INTEGER NOT NULL,
+    analyze_ms INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
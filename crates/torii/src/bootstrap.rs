@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use async_graphql::dynamic::TypeRef;
+use dojo_world::manifest::{Component, Manifest, Member};
+use dojo_world::migration::schema::{member_size, member_values, pack_values};
+use sqlx::{Executor, Pool, Row, Sqlite, Transaction};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+use crate::graphql::types::ScalarType;
+use crate::storage::sql::{component_table, row_values};
+
+/// Registers the components and systems of a manifest so the complete GraphQL schema is available
+/// at startup, instead of only after their registration events have been indexed. Already known
+/// systems are left untouched, known components are upgraded if their members changed.
+pub async fn bootstrap_from_manifest(pool: &Pool<Sqlite>, manifest: &Manifest) -> Result<()> {
+    let mut tx = pool.begin().await?;
+
+    for component in &manifest.components {
+        let storage_definition = serde_json::to_string(&component.members)?;
+        let class_hash = format!("{:#x}", component.class_hash);
+
+        let known: Option<(String, String)> = sqlx::query_as(
+            "SELECT storage_definition, deprecated_definition FROM components WHERE id = $1",
+        )
+        .bind(&component.name)
+        .fetch_optional(&mut tx)
+        .await?;
+
+        if let Some((previous, deprecated)) = known {
+            if previous != storage_definition {
+                upgrade_component(&mut tx, component, &previous, &deprecated).await?;
+            }
+            continue;
+        }
+
+        tx.execute(
+            sqlx::query(
+                "INSERT OR IGNORE INTO components (id, name, address, class_hash, \
+                 transaction_hash, storage_definition) VALUES ($1, $2, $3, $4, $5, $6)",
+            )
+            .bind(&component.name)
+            .bind(&component.name)
+            .bind("0x0")
+            .bind(class_hash)
+            .bind("0x0")
+            .bind(storage_definition),
+        )
+        .await?;
+
+        tx.execute(sqlx::query(&create_storage_table(component))).await?;
+    }
+
+    for system in &manifest.systems {
+        let class_hash = format!("{:#x}", system.class_hash);
+
+        tx.execute(
+            sqlx::query(
+                "INSERT OR IGNORE INTO systems (id, name, address, class_hash, transaction_hash) \
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(system.name.as_str())
+            .bind(system.name.as_str())
+            .bind("0x0")
+            .bind(class_hash)
+            .bind("0x0"),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Applies a component upgrade. The columns of removed members are kept and the members are moved
+/// to the deprecated definition, so they are still served as deprecated fields. New members get a
+/// column defaulting to zero. The raw values of the entities are moved to the new layout, through
+/// the component's upgrade transform if it has one.
+async fn upgrade_component(
+    tx: &mut Transaction<'_, Sqlite>,
+    component: &Component,
+    previous: &str,
+    deprecated: &str,
+) -> Result<()> {
+    let previous: Vec<Member> = serde_json::from_str(previous)?;
+    let mut deprecated: Vec<Member> = serde_json::from_str(deprecated)?;
+    let is_current = |name: &str| component.members.iter().any(|m| m.name == name);
+
+    let table = format!("storage_{}", component.name.to_lowercase());
+    for member in &component.members {
+        let has_column =
+            previous.iter().chain(deprecated.iter()).any(|column| column.name == member.name);
+        if !has_column {
+            let default = if column_type(member) == "INTEGER" { "0" } else { "'0x0'" };
+            tx.execute(sqlx::query(&format!(
+                "ALTER TABLE {table} ADD COLUMN {} {} NOT NULL DEFAULT {default}",
+                member.name,
+                column_type(member)
+            )))
+            .await?;
+        }
+    }
+
+    if let Some(upgrade) = &component.upgrade {
+        let columns = previous.iter().chain(deprecated.iter()).cloned().collect::<Vec<_>>();
+        upgrade
+            .validate(&columns, &component.members)
+            .with_context(|| format!("Invalid upgrade of `{}`", component.name))?;
+    }
+    transform_entities(tx, component, &previous)
+        .await
+        .with_context(|| format!("Failed to upgrade the entities of `{}`", component.name))?;
+
+    // members added back are current again
+    deprecated.retain(|member| !is_current(&member.name));
+    deprecated.extend(previous.into_iter().filter(|member| !is_current(&member.name)));
+
+    tx.execute(
+        sqlx::query(
+            "UPDATE components SET class_hash = $1, storage_definition = $2, \
+             deprecated_definition = $3 WHERE id = $4",
+        )
+        .bind(format!("{:#x}", component.class_hash))
+        .bind(serde_json::to_string(&component.members)?)
+        .bind(serde_json::to_string(&deprecated)?)
+        .bind(&component.name),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Moves the raw values of the entities of a component, as written by
+/// [`crate::storage::sql::SqlStorage`], from the previous layout to the new one. Values are
+/// written back as hex strings, sqlite integers being too narrow for felts.
+async fn transform_entities(
+    tx: &mut Transaction<'_, Sqlite>,
+    component: &Component,
+    previous: &[Member],
+) -> Result<()> {
+    let name = cairo_short_string_to_felt(&component.name)?;
+    let table = component_table(name);
+
+    let (columns,): (i64,) =
+        sqlx::query_as("SELECT COUNT(*) FROM pragma_table_info($1) WHERE name GLOB 'column[0-9]*'")
+            .bind(name.to_string())
+            .fetch_one(&mut *tx)
+            .await?;
+    // the table is only created once the component is indexed
+    if columns == 0 {
+        return Ok(());
+    }
+
+    let upgrade = component.upgrade.clone().unwrap_or_default();
+    let new_len = {
+        let zeros = vec![FieldElement::ZERO; component.members.iter().map(member_size).sum()];
+        pack_values(&component.members, &zeros)?.len()
+    };
+    for column in columns as usize + 1..=new_len {
+        tx.execute(sqlx::query(&format!("ALTER TABLE {table} ADD COLUMN column{column} TEXT")))
+            .await?;
+    }
+
+    let rows = sqlx::query(&format!("SELECT rowid, * FROM {table}")).fetch_all(&mut *tx).await?;
+    for row in rows {
+        let raw = row_values(&row)?;
+
+        let values = member_values(previous, &raw)?;
+        let values = pack_values(&component.members, &upgrade.apply(&component.members, &values))?;
+
+        let assignments =
+            (1..=values.len()).map(|i| format!("column{i} = ${i}")).collect::<Vec<_>>();
+        // the row is written back uncompressed
+        let update = format!(
+            "UPDATE {table} SET {}, payload = NULL, codec = NULL WHERE rowid = ${}",
+            assignments.join(", "),
+            values.len() + 1
+        );
+        let mut query = sqlx::query(&update);
+        for value in &values {
+            query = query.bind(format!("{value:#x}"));
+        }
+        tx.execute(query.bind(row.try_get::<i64, _>("rowid")?)).await?;
+    }
+
+    Ok(())
+}
+
+fn create_storage_table(component: &Component) -> String {
+    let columns = component
+        .members
+        .iter()
+        .map(|member| format!("{} {} NOT NULL, ", member.name, column_type(member)))
+        .collect::<String>();
+
+    format!(
+        "CREATE TABLE IF NOT EXISTS storage_{} (id INTEGER PRIMARY KEY AUTOINCREMENT, \
+         {columns}version TEXT NOT NULL, entity_id TEXT NOT NULL, component_id TEXT NOT NULL, \
+         created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP, FOREIGN KEY (entity_id) \
+         REFERENCES entities(id), FOREIGN KEY (component_id) REFERENCES components(id));",
+        component.name.to_lowercase()
+    )
+}
+
+// Mirrors how storage values are read back, sqlite integers are only 64 bits wide.
+pub(crate) fn column_type(member: &Member) -> &'static str {
+    if member.variants.is_some() {
+        return "INTEGER";
+    }
+
+    match member.ty.as_str() {
+        ScalarType::U8 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64 | TypeRef::BOOLEAN => {
+            "INTEGER"
+        }
+        _ => "TEXT",
+    }
+}
@@ -0,0 +1,169 @@
+use anyhow::Result;
+use dojo_world::manifest::Member;
+use dojo_world::migration::schema::member_values;
+use sqlx::{Pool, Row, Sqlite};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+use starknet::providers::jsonrpc::models::{BlockId, BlockTag};
+use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcTransport};
+use starknet_crypto::{pedersen_hash, poseidon_hash_many};
+
+use crate::storage::sql::{component_table, parse_value, row_values};
+
+/// Salt of the world's key-value store addresses, see `KeyValueStore::address`.
+const STORAGE_SALT: u64 = 0x420;
+/// Storage addresses are reduced below 2**251 - 256.
+const ADDRESS_BOUND: &str = "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00";
+
+/// A member whose indexed value differs from the world's storage.
+#[derive(Debug)]
+pub struct Divergence {
+    pub component: String,
+    pub entity_id: String,
+    pub member: String,
+    pub indexed: FieldElement,
+    pub on_chain: FieldElement,
+}
+
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Number of entities sampled across all components.
+    pub entities: usize,
+    /// Number of member values compared.
+    pub values: usize,
+    /// Number of member values that couldn't be compared.
+    pub skipped: usize,
+    pub divergences: Vec<Divergence>,
+}
+
+/// Compares the latest indexed values of up to `sample_size` random entities per component with
+/// the values read from the world's storage.
+pub async fn check_consistency<T>(
+    pool: &Pool<Sqlite>,
+    provider: &JsonRpcClient<T>,
+    world: FieldElement,
+    sample_size: u32,
+) -> Result<CheckReport>
+where
+    T: JsonRpcTransport + Sync + Send,
+{
+    let mut report = CheckReport::default();
+
+    let components: Vec<(String, String)> =
+        sqlx::query_as("SELECT name, storage_definition FROM components").fetch_all(pool).await?;
+
+    for (name, definition) in components {
+        let members: Vec<Member> = serde_json::from_str(&definition)?;
+        let component = cairo_short_string_to_felt(&name)?;
+
+        for (key, partition, indexed) in sample_rows(pool, component, sample_size).await? {
+            let base = storage_base(table_id(component, partition), key);
+            report.entities += 1;
+
+            // raw values are laid out by slot, read as many from the world's storage
+            let mut raw = Vec::with_capacity(indexed.len());
+            for offset in 0..indexed.len() {
+                let address = base + FieldElement::from(offset);
+                raw.push(
+                    provider
+                        .get_storage_at(world, address, &BlockId::Tag(BlockTag::Pending))
+                        .await?,
+                );
+            }
+
+            // rows written before the component was upgraded don't match its members
+            let (indexed, on_chain) =
+                match (member_values(&members, &indexed), member_values(&members, &raw)) {
+                    (Ok(indexed), Ok(on_chain)) => (indexed, on_chain),
+                    _ => {
+                        report.skipped += members.len();
+                        continue;
+                    }
+                };
+
+            for member in &members {
+                report.values += 1;
+                let pairs = indexed[&member.name].iter().zip(&on_chain[&member.name]);
+                for (indexed, on_chain) in pairs {
+                    if indexed != on_chain {
+                        report.divergences.push(Divergence {
+                            component: name.clone(),
+                            entity_id: format!("{key:#x}"),
+                            member: member.name.clone(),
+                            indexed: *indexed,
+                            on_chain: *on_chain,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Key, partition and raw values of up to `sample_size` random entities of the component's
+/// table, as written by [`SqlStorage`](crate::storage::sql::SqlStorage).
+async fn sample_rows(
+    pool: &Pool<Sqlite>,
+    component: FieldElement,
+    sample_size: u32,
+) -> Result<Vec<(FieldElement, FieldElement, Vec<FieldElement>)>> {
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = $1)",
+    )
+    .bind(component.to_string())
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Ok(vec![]);
+    }
+
+    let rows = sqlx::query(&format!(
+        "SELECT CAST(id AS TEXT) AS entity_key, CAST(partition AS TEXT) AS entity_partition, * \
+         FROM {} WHERE archived_at IS NULL ORDER BY RANDOM() LIMIT $1",
+        component_table(component)
+    ))
+    .bind(sample_size)
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            let key = parse_value(&row.try_get::<String, _>("entity_key")?)?;
+            let partition = parse_value(&row.try_get::<String, _>("entity_partition")?)?;
+            Ok((key, partition, row_values(row)?))
+        })
+        .collect()
+}
+
+/// Table of a component in a partition, mirrors `Query::table`.
+pub fn table_id(component: FieldElement, partition: FieldElement) -> FieldElement {
+    if partition == FieldElement::ZERO {
+        return component;
+    }
+    poseidon_hash_many(&[component, partition])
+}
+
+/// Key of an entity in its table, mirrors `Query::hash`.
+pub fn entity_key(partition: FieldElement, keys: &[FieldElement]) -> FieldElement {
+    if keys.len() == 1 && partition == FieldElement::ZERO {
+        return keys[0];
+    }
+
+    let mut serialized = vec![partition, FieldElement::from(keys.len())];
+    serialized.extend(keys);
+    poseidon_hash_many(&serialized)
+}
+
+/// Base storage address of a record, mirrors `KeyValueStore::address`.
+pub fn storage_base(table: FieldElement, key: FieldElement) -> FieldElement {
+    let hash = pedersen_hash(&pedersen_hash(&FieldElement::from(STORAGE_SALT), &table), &key);
+
+    let bound = FieldElement::from_hex_be(ADDRESS_BOUND).unwrap();
+    if hash >= bound {
+        hash - bound
+    } else {
+        hash
+    }
+}
@@ -1,23 +1,50 @@
-use clap::Parser;
-use graphql::server::start_graphql;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use dojo_world::manifest::Manifest;
+use dojo_world::migration::deployment::DeploymentManifest;
+use graphql::federation::{load_worlds, FederatedWorld, Federation};
+use graphql::server::{start_graphql, Relay};
 use num::{BigUint, Num};
-use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::sqlite::{SqliteAutoVacuum, SqliteConnectOptions, SqlitePoolOptions};
+use starknet::core::types::FieldElement;
 use starknet::providers::jsonrpc::HttpTransport;
 use starknet::providers::JsonRpcClient;
 use storage::sql::SqlStorage;
 use tokio_util::sync::CancellationToken;
-use tracing::error;
+use tracing::{error, warn};
 use tracing_subscriber::fmt;
 use url::Url;
 
+use crate::bootstrap::bootstrap_from_manifest;
+use crate::check::check_consistency;
+use crate::compression::Compression;
+use crate::firehose::{load_sinks, start_firehose, FirehoseStorage};
 use crate::indexer::start_indexer;
+use crate::maintenance::{start_maintenance, MaintenanceConfig};
+use crate::processors::custom::{
+    load_processors, CustomProcessors, ProcessingStorage, SqlProcessor,
+};
+use crate::settings::{reload_on_sighup, Settings};
+use crate::webhooks::{start_webhooks, WebhookStorage, Webhooks};
 
+mod bootstrap;
+mod check;
+mod compression;
 mod engine;
+mod explorer;
+mod firehose;
 mod graphql;
 mod indexer;
+mod maintenance;
 mod processors;
+mod settings;
 mod storage;
 mod tests;
+mod webhooks;
 
 /// Dojo World Indexer
 #[derive(Parser, Debug)]
@@ -32,6 +59,83 @@ struct Args {
     /// Database url
     #[arg(short, long, default_value = "sqlite::memory:")]
     database_url: String,
+    /// Path to a world manifest to register components and systems from at startup
+    #[arg(short, long)]
+    manifest: Option<PathBuf>,
+    /// Path to the deployment manifest written by `sozo migrate`, the world to index is read
+    /// from it
+    #[arg(long, conflicts_with = "world")]
+    deployment: Option<PathBuf>,
+    /// Prefix for the GraphQL type and query names generated from the world's components
+    #[arg(long)]
+    graphql_namespace: Option<String>,
+    /// Harden the GraphQL server for untrusted clients: no playground, strict query limits,
+    /// rate limiting and response caching. Requests are rate limited per IP address of the
+    /// connection, so behind a reverse proxy all the clients share the proxy's limit
+    #[arg(long)]
+    read_only: bool,
+    /// Accept signed transactions through the `execute` GraphQL mutation and forward them to the
+    /// rpc endpoint, so clients don't need their own connection to the node
+    #[arg(long, conflicts_with = "read_only")]
+    relay: bool,
+    /// Path to a JSON file listing the webhooks to notify of indexed state changes, reloaded
+    /// along with the settings
+    #[arg(long)]
+    webhooks: Option<PathBuf>,
+    /// Path to a JSON file of the settings tunable at runtime: rate limits, CORS origins and
+    /// page size caps. Reloaded on SIGHUP or through the admin endpoint
+    #[arg(long)]
+    config: Option<PathBuf>,
+    /// Token required by the `/admin/reload` endpoint, which is disabled when not set
+    #[arg(long, env = "TORII_ADMIN_TOKEN", conflicts_with = "read_only")]
+    admin_token: Option<String>,
+    /// Private key signing the GraphQL responses, along with the block they were served at, so
+    /// that clients can hold the operator accountable for them
+    #[arg(long, env = "TORII_SIGNING_KEY")]
+    signing_key: Option<String>,
+    /// Path to a JSON file listing the sinks (webhook, Kafka or NATS) publishing every indexed
+    /// state update
+    #[arg(long)]
+    sinks: Option<PathBuf>,
+    /// Path to a JSON file listing the databases of other worlds to federate with this one, the
+    /// queries spanning them are served at `/federation`
+    #[arg(long, conflicts_with = "read_only")]
+    federate: Option<PathBuf>,
+    /// Path to a JSON file listing SQL processors maintaining derived tables while indexing
+    #[arg(long)]
+    processors: Option<PathBuf>,
+    /// Number of events requested per page when fetching the world's events
+    #[arg(long, default_value = "1024")]
+    events_chunk_size: u64,
+    /// Number of blocks whose events are fetched at once, narrowed automatically when the node
+    /// caps its responses
+    #[arg(long, default_value = "100")]
+    block_range: u64,
+    /// Interval in seconds between database maintenance runs, 0 to disable
+    #[arg(long, default_value = "3600")]
+    maintenance_interval: u64,
+    /// Maximum number of free pages reclaimed by each maintenance run
+    #[arg(long, default_value = "1000")]
+    vacuum_pages: u32,
+    /// Codec compressing the large payloads of the component updates and component tables during
+    /// maintenance, they are decompressed transparently when queried
+    #[arg(long, value_enum, default_value_t = Compression::None)]
+    compression: Compression,
+    /// Size in bytes from which the payloads are compressed
+    #[arg(long, default_value = "1024")]
+    compression_threshold: usize,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare a sample of the indexed component values against the world's storage, then exit
+    Check {
+        /// Number of entities sampled per component
+        #[arg(long, default_value = "20")]
+        sample_size: u32,
+    },
 }
 
 #[tokio::main]
@@ -55,19 +159,141 @@ async fn main() -> anyhow::Result<()> {
         }
     })?;
 
-    let world = BigUint::from_str_radix(&args.world[2..], 16).unwrap_or_else(|error| {
+    let world_address = match &args.deployment {
+        Some(path) => {
+            let deployment = DeploymentManifest::load_from_path(path)?;
+            let address = deployment.world.address.ok_or_else(|| {
+                anyhow::anyhow!("No world address in deployment manifest {}", path.display())
+            })?;
+            format!("{address:#x}")
+        }
+        None => args.world.clone(),
+    };
+
+    let world = BigUint::from_str_radix(&world_address[2..], 16).unwrap_or_else(|error| {
         panic!("Failed parsing world address: {error:?}");
     });
 
     let database_url = &args.database_url;
     #[cfg(feature = "sqlite")]
-    let pool = SqlitePoolOptions::new().max_connections(5).connect(database_url).await?;
+    let pool = {
+        // Lets the maintenance reclaim free pages a few at a time instead of rebuilding the
+        // database, existing databases are switched over by its first run.
+        let options = SqliteConnectOptions::from_str(database_url)?
+            .auto_vacuum(SqliteAutoVacuum::Incremental);
+        SqlitePoolOptions::new().max_connections(5).connect_with(options).await?
+    };
+    sqlx::migrate!("./migrations").run(&pool).await?;
     let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(&args.rpc).unwrap()));
 
-    let storage = SqlStorage::new(pool.clone())?;
-    let indexer = start_indexer(cts.clone(), world, &storage, &provider);
+    if let Some(manifest_path) = &args.manifest {
+        let manifest = Manifest::load_from_path(manifest_path)?;
+        bootstrap_from_manifest(&pool, &manifest).await?;
+    }
+
+    if let Some(Command::Check { sample_size }) = args.command {
+        let world = FieldElement::from_hex_be(&world_address)?;
+        let report = check_consistency(&pool, &provider, world, sample_size).await?;
+
+        for divergence in &report.divergences {
+            println!(
+                "{} {} {}: indexed {:#x}, on-chain {:#x}",
+                divergence.component,
+                divergence.entity_id,
+                divergence.member,
+                divergence.indexed,
+                divergence.on_chain
+            );
+        }
+        println!(
+            "Checked {} values of {} entities ({} skipped), {} divergent",
+            report.values,
+            report.entities,
+            report.skipped,
+            report.divergences.len()
+        );
+
+        if !report.divergences.is_empty() {
+            anyhow::bail!("Indexed state diverges from the world's storage");
+        }
+        return Ok(());
+    }
 
-    let graphql = start_graphql(&pool);
+    let settings = Arc::new(Settings::load(args.config.clone(), args.webhooks.clone())?);
+    tokio::spawn(reload_on_sighup(cts.clone(), settings.clone()));
+
+    let (webhooks, notifications) = Webhooks::new();
+    tokio::spawn(start_webhooks(cts.clone(), settings.webhooks(), notifications));
+
+    let sinks = match &args.sinks {
+        Some(path) => load_sinks(path)?,
+        None => vec![],
+    };
+    let firehose_log = (!sinks.is_empty()).then(|| pool.clone());
+    tokio::spawn(start_firehose(cts.clone(), pool.clone(), sinks));
+
+    let mut processors = CustomProcessors::new(pool.clone());
+    if let Some(path) = &args.processors {
+        for config in load_processors(path)? {
+            processors.register(Arc::new(SqlProcessor::new(&pool, config).await?));
+        }
+    }
+
+    let sql_storage = SqlStorage::new(pool.clone())?;
+    sql_storage.migrate_component_tables().await?;
+
+    let storage = ProcessingStorage::new(
+        WebhookStorage::new(
+            FirehoseStorage::new(sql_storage, firehose_log),
+            webhooks,
+        ),
+        processors.clone(),
+    );
+    let indexer = start_indexer(
+        cts.clone(),
+        world,
+        &storage,
+        &provider,
+        processors,
+        args.events_chunk_size,
+        args.block_range,
+    );
+
+    let relay = if args.relay {
+        Some(Relay {
+            provider: Arc::new(JsonRpcClient::new(HttpTransport::new(Url::parse(&args.rpc)?))),
+            world_address: FieldElement::from_hex_be(&world_address)?,
+        })
+    } else {
+        None
+    };
+    let signing_key = args.signing_key.as_deref().map(FieldElement::from_hex_be).transpose()?;
+    let federation = match &args.federate {
+        Some(path) => {
+            let local = FederatedWorld { id: world_address.clone(), pool: pool.clone() };
+            Some(Federation::connect(local, &load_worlds(path)?).await?)
+        }
+        None => None,
+    };
+    let graphql = start_graphql(
+        &pool,
+        args.graphql_namespace.as_deref(),
+        args.read_only,
+        relay,
+        settings,
+        args.admin_token.clone(),
+        signing_key,
+        federation,
+    );
+
+    if args.maintenance_interval > 0 {
+        let config = MaintenanceConfig::new(Duration::from_secs(args.maintenance_interval))
+            .with_vacuum_pages(args.vacuum_pages)
+            .with_compression(args.compression, args.compression_threshold);
+        tokio::spawn(start_maintenance(cts.clone(), pool.clone(), config));
+    } else if args.compression != Compression::None {
+        warn!("Payloads are only compressed during maintenance, which is disabled");
+    }
 
     tokio::select! {
         res = indexer => {
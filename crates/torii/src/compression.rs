@@ -0,0 +1,186 @@
+use std::io::{Read, Write};
+
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use clap::ValueEnum;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use sqlx::{Pool, Row, Sqlite};
+
+use crate::storage::sql::{component_tables, join_values, row_values};
+
+/// Number of rows compressed per transaction.
+const COMPRESSION_BATCH_SIZE: i64 = 500;
+
+/// Codec compressing the large payloads of the component updates and of the component tables.
+/// The codec of a value is stored next to it, in a `codec` column, so rows written with different
+/// codecs, or uncompressed, can be read side by side.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Compression {
+    #[default]
+    None,
+    Deflate,
+}
+
+impl Compression {
+    /// Value of the `codec` column of the values compressed with this codec, `NULL` when they
+    /// aren't compressed.
+    pub fn codec(self) -> Option<&'static str> {
+        match self {
+            Compression::None => None,
+            Compression::Deflate => Some("deflate"),
+        }
+    }
+
+    /// Codec of a value, from its `codec` column.
+    pub fn from_codec(codec: Option<&str>) -> Result<Self> {
+        match codec {
+            None => Ok(Compression::None),
+            Some("deflate") => Ok(Compression::Deflate),
+            Some(codec) => Err(anyhow!("Unknown codec `{codec}`")),
+        }
+    }
+}
+
+/// Compresses `data` with `compression`, as the base64 encoding of the compressed bytes.
+pub fn compress(data: &str, compression: Compression) -> Result<String> {
+    match compression {
+        Compression::None => Ok(data.to_string()),
+        Compression::Deflate => {
+            let mut encoder = DeflateEncoder::new(vec![], flate2::Compression::default());
+            encoder.write_all(data.as_bytes())?;
+            Ok(STANDARD.encode(encoder.finish()?))
+        }
+    }
+}
+
+/// Restores a value compressed by [`compress`] with `compression`.
+pub fn decompress(data: &str, compression: Compression) -> Result<String> {
+    match compression {
+        Compression::None => Ok(data.to_string()),
+        Compression::Deflate => {
+            let bytes =
+                STANDARD.decode(data).map_err(|e| anyhow!("Invalid compressed value: {e}"))?;
+            let mut decompressed = String::new();
+            DeflateDecoder::new(bytes.as_slice()).read_to_string(&mut decompressed)?;
+            Ok(decompressed)
+        }
+    }
+}
+
+/// Compresses the payloads of the component updates and the rows of the component tables of at
+/// least `min_size` bytes that aren't compressed yet, returning the number of rows compressed.
+pub async fn compress_payloads(
+    pool: &Pool<Sqlite>,
+    compression: Compression,
+    min_size: usize,
+) -> Result<u64> {
+    let Some(codec) = compression.codec() else {
+        return Ok(0);
+    };
+
+    let mut compressed = compress_updates(pool, compression, codec, min_size).await?;
+    for table in component_tables(pool).await? {
+        compressed += compress_component_rows(pool, &table, compression, codec, min_size).await?;
+    }
+    Ok(compressed)
+}
+
+async fn compress_updates(
+    pool: &Pool<Sqlite>,
+    compression: Compression,
+    codec: &str,
+    min_size: usize,
+) -> Result<u64> {
+    let mut compressed = 0;
+    let mut cursor = 0;
+    loop {
+        let mut tx = pool.begin().await?;
+        let rows: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT id, data FROM entity_state_updates WHERE id > $1 AND LENGTH(data) >= $2 AND \
+             codec IS NULL ORDER BY id LIMIT $3",
+        )
+        .bind(cursor)
+        .bind(min_size as i64)
+        .bind(COMPRESSION_BATCH_SIZE)
+        .fetch_all(&mut tx)
+        .await?;
+
+        let Some((last, _)) = rows.last() else {
+            return Ok(compressed);
+        };
+        cursor = *last;
+
+        for (id, data) in &rows {
+            let value = compress(data, compression)?;
+            // Incompressible payloads are left as is, they would only grow.
+            if value.len() >= data.len() {
+                continue;
+            }
+
+            sqlx::query("UPDATE entity_state_updates SET data = $1, codec = $2 WHERE id = $3")
+                .bind(value)
+                .bind(codec)
+                .bind(id)
+                .execute(&mut tx)
+                .await?;
+            compressed += 1;
+        }
+        tx.commit().await?;
+    }
+}
+
+/// Compresses the raw values of the rows of a component table into their `payload`, emptying
+/// their value columns.
+async fn compress_component_rows(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    compression: Compression,
+    codec: &str,
+    min_size: usize,
+) -> Result<u64> {
+    let mut compressed = 0;
+    let mut cursor = 0;
+    loop {
+        let mut tx = pool.begin().await?;
+        let rows = sqlx::query(&format!(
+            "SELECT rowid, * FROM \"{table}\" WHERE rowid > $1 AND codec IS NULL ORDER BY rowid \
+             LIMIT $2"
+        ))
+        .bind(cursor)
+        .bind(COMPRESSION_BATCH_SIZE)
+        .fetch_all(&mut tx)
+        .await?;
+
+        let Some(last) = rows.last() else {
+            return Ok(compressed);
+        };
+        cursor = last.try_get::<i64, _>("rowid")?;
+
+        for row in &rows {
+            let values = row_values(row)?;
+            let data = join_values(&values);
+            if data.len() < min_size {
+                continue;
+            }
+            let value = compress(&data, compression)?;
+            if value.len() >= data.len() {
+                continue;
+            }
+
+            let columns =
+                (1..=values.len()).map(|i| format!(", column{i} = NULL")).collect::<String>();
+            sqlx::query(&format!(
+                "UPDATE \"{table}\" SET payload = $1, codec = $2{columns} WHERE rowid = $3"
+            ))
+            .bind(value)
+            .bind(codec)
+            .bind(row.try_get::<i64, _>("rowid")?)
+            .execute(&mut tx)
+            .await?;
+            compressed += 1;
+        }
+        tx.commit().await?;
+    }
+}
@@ -3,33 +3,63 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use starknet::core::types::{
-    BlockId, BlockWithTxs, Event, InvokeTransaction, MaybePendingBlockWithTxs,
-    MaybePendingTransactionReceipt, StarknetError, Transaction, TransactionReceipt,
+    BlockId, BlockWithTxs, EmittedEvent, Event, EventFilter, FieldElement, InvokeTransaction,
+    MaybePendingBlockWithTxs, MaybePendingTransactionReceipt, StarknetError, Transaction,
+    TransactionReceipt,
 };
 use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcTransport};
 use starknet::providers::{Provider, ProviderError};
 use tokio::time::sleep;
-use tracing::error;
+use tracing::{error, warn};
 
-use crate::processors::{BlockProcessor, EventProcessor, TransactionProcessor};
+use crate::processors::custom::CustomProcessors;
+use crate::processors::{executed_systems, BlockProcessor, EventProcessor, TransactionProcessor};
 use crate::storage::Storage;
 
 pub struct Processors<S: Storage, T: JsonRpcTransport + Sync + Send> {
     block: Vec<Arc<dyn BlockProcessor<S, T>>>,
     transaction: Vec<Arc<dyn TransactionProcessor<S, T>>>,
     event: Vec<Arc<dyn EventProcessor<S, T>>>,
+    custom: Option<CustomProcessors>,
+}
+
+impl<S: Storage, T: JsonRpcTransport + Sync + Send> Processors<S, T> {
+    pub fn new(
+        block: Vec<Arc<dyn BlockProcessor<S, T>>>,
+        transaction: Vec<Arc<dyn TransactionProcessor<S, T>>>,
+        event: Vec<Arc<dyn EventProcessor<S, T>>>,
+    ) -> Self {
+        Self { block, transaction, event, custom: None }
+    }
+
+    /// Runs the `custom` processors on every event, after the built-in ones.
+    pub fn with_custom(mut self, custom: CustomProcessors) -> Self {
+        self.custom = Some(custom);
+        self
+    }
 }
 
 impl<S: Storage, T: JsonRpcTransport + Sync + Send> Default for Processors<S, T> {
     fn default() -> Self {
-        Self { block: vec![], transaction: vec![], event: vec![] }
+        Self { block: vec![], transaction: vec![], event: vec![], custom: None }
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct EngineConfig {
+    /// Contract whose events are indexed.
+    pub world_address: FieldElement,
+    /// Events requested per `getEvents` page.
+    pub events_chunk_size: u64,
+    /// Blocks whose events are requested at once, narrowed when the node caps its responses.
+    pub block_range: u64,
+}
+
 pub struct Engine<'a, S: Storage, T: JsonRpcTransport + Sync + Send> {
     storage: &'a S,
     provider: &'a JsonRpcClient<T>,
     processors: Processors<S, T>,
+    config: EngineConfig,
 }
 
 impl<'a, S: Storage, T: JsonRpcTransport + Sync + Send> Engine<'a, S, T> {
@@ -37,8 +67,9 @@ impl<'a, S: Storage, T: JsonRpcTransport + Sync + Send> Engine<'a, S, T> {
         storage: &'a S,
         provider: &'a JsonRpcClient<T>,
         processors: Processors<S, T>,
+        config: EngineConfig,
     ) -> Self {
-        Self { storage, provider, processors }
+        Self { storage, provider, processors, config }
     }
 
     pub async fn start(&self) -> Result<(), Box<dyn Error>> {
@@ -47,76 +78,230 @@ impl<'a, S: Storage, T: JsonRpcTransport + Sync + Send> Engine<'a, S, T> {
         loop {
             sleep(Duration::from_secs(1)).await;
 
-            let block_with_txs =
-                match self.provider.get_block_with_txs(BlockId::Number(current_block_number)).await
-                {
-                    Ok(block_with_txs) => block_with_txs,
-                    Err(e) => {
-                        if let ProviderError::StarknetError(StarknetError::BlockNotFound) = e {
-                            continue;
-                        }
-
-                        error!("getting  block: {}", e);
-                        continue;
-                    }
-                };
+            let latest_block_number = match self.provider.block_number().await {
+                Ok(block_number) => block_number,
+                Err(e) => {
+                    error!("getting block number: {}", e);
+                    continue;
+                }
+            };
+            if current_block_number > latest_block_number {
+                continue;
+            }
 
-            let block_with_txs = match block_with_txs {
-                MaybePendingBlockWithTxs::Block(block_with_txs) => block_with_txs,
-                _ => continue,
+            let to_block_number = latest_block_number
+                .min(current_block_number + self.config.block_range.max(1) - 1);
+            let events = match fetch_events(
+                self.provider,
+                self.config.world_address,
+                current_block_number,
+                to_block_number,
+                self.config.events_chunk_size,
+            )
+            .await
+            {
+                Ok(events) => events,
+                Err(e) => {
+                    error!("getting events: {}", e);
+                    continue;
+                }
             };
 
-            process_block(self.storage, self.provider, &self.processors.block, &block_with_txs)
-                .await?;
-
-            for transaction in block_with_txs.transactions {
-                let invoke_transaction = match &transaction {
-                    Transaction::Invoke(invoke_transaction) => invoke_transaction,
-                    _ => continue,
-                };
-
-                let invoke_transaction = match invoke_transaction {
-                    InvokeTransaction::V1(invoke_transaction) => invoke_transaction,
-                    _ => continue,
-                };
-
-                let receipt = match self
-                    .provider
-                    .get_transaction_receipt(invoke_transaction.transaction_hash)
-                    .await
-                {
-                    Ok(receipt) => receipt,
-                    _ => continue,
-                };
-
-                let receipt = match receipt {
-                    MaybePendingTransactionReceipt::Receipt(receipt) => receipt,
-                    _ => continue,
-                };
-
-                process_transaction(
-                    self.storage,
-                    self.provider,
-                    &self.processors.transaction,
-                    &receipt.clone(),
-                )
-                .await?;
-
-                if let TransactionReceipt::Invoke(invoke_receipt) = receipt.clone() {
-                    for event in &invoke_receipt.events {
-                        process_event(
-                            self.storage,
-                            self.provider,
-                            &self.processors.event,
-                            &receipt,
-                            event,
-                        )
-                        .await?;
+            while current_block_number <= to_block_number {
+                let block_events = events
+                    .iter()
+                    .filter(|event| event.block_number == current_block_number)
+                    .collect::<Vec<_>>();
+                if !self.process_block_number(current_block_number, &block_events).await? {
+                    break;
+                }
+
+                current_block_number += 1;
+            }
+        }
+    }
+
+    /// Runs the processors on a block and commits its writes with the next head, returning
+    /// `false` if the block isn't available yet.
+    pub(crate) async fn process_block_number(
+        &self,
+        block_number: u64,
+        events: &[&EmittedEvent],
+    ) -> Result<bool, Box<dyn Error>> {
+        let block_with_txs =
+            match self.provider.get_block_with_txs(BlockId::Number(block_number)).await {
+                Ok(block_with_txs) => block_with_txs,
+                Err(e) => {
+                    if let ProviderError::StarknetError(StarknetError::BlockNotFound) = e {
+                        return Ok(false);
                     }
+
+                    error!("getting  block: {}", e);
+                    return Ok(false);
                 }
+            };
+
+        let block_with_txs = match block_with_txs {
+            MaybePendingBlockWithTxs::Block(block_with_txs) => block_with_txs,
+            _ => return Ok(false),
+        };
+
+        self.storage.begin_block().await?;
+        process_block(self.storage, self.provider, &self.processors.block, &block_with_txs).await?;
+
+        for transaction in &block_with_txs.transactions {
+            let invoke_transaction = match transaction {
+                Transaction::Invoke(invoke_transaction) => invoke_transaction,
+                _ => continue,
+            };
+
+            let invoke_transaction = match invoke_transaction {
+                InvokeTransaction::V1(invoke_transaction) => invoke_transaction,
+                _ => continue,
+            };
+
+            let receipt = match self
+                .provider
+                .get_transaction_receipt(invoke_transaction.transaction_hash)
+                .await
+            {
+                Ok(receipt) => receipt,
+                _ => continue,
+            };
+
+            let receipt = match receipt {
+                MaybePendingTransactionReceipt::Receipt(receipt) => receipt,
+                _ => continue,
+            };
+
+            process_transaction(
+                self.storage,
+                self.provider,
+                &self.processors.transaction,
+                transaction,
+                &receipt,
+            )
+            .await?;
+        }
+
+        let mut transaction_hash = None;
+        for emitted in events {
+            // The updates written by the events of a transaction are attributed to its call.
+            if transaction_hash != Some(emitted.transaction_hash) {
+                transaction_hash = Some(emitted.transaction_hash);
+                self.begin_system_call(&block_with_txs, emitted.transaction_hash).await?;
+            }
+
+            let event = Event {
+                from_address: emitted.from_address,
+                keys: emitted.keys.clone(),
+                data: emitted.data.clone(),
+            };
+            process_event(self.storage, self.provider, &self.processors.event, &event).await?;
+
+            if let Some(custom) = &self.processors.custom {
+                custom.process_event(&event).await;
+            }
+        }
+
+        self.storage.set_head(block_number + 1).await?;
+        Ok(true)
+    }
+
+    /// Starts the call of the system executed by a transaction of the block. The world's events
+    /// don't tell which call of a multicall emitted them, they are attributed to the first system
+    /// executed.
+    async fn begin_system_call(
+        &self,
+        block: &BlockWithTxs,
+        transaction_hash: FieldElement,
+    ) -> Result<(), Box<dyn Error>> {
+        let calldata = block.transactions.iter().find_map(|transaction| match transaction {
+            Transaction::Invoke(InvokeTransaction::V1(transaction))
+                if transaction.transaction_hash == transaction_hash =>
+            {
+                Some(transaction.calldata.as_slice())
+            }
+            _ => None,
+        });
+        let system = calldata.and_then(|calldata| {
+            executed_systems(self.config.world_address, calldata).into_iter().next()
+        });
+
+        match system {
+            Some((name, data)) => {
+                self.storage.begin_system_call(transaction_hash, Some(&name), data).await?
+            }
+            None => self.storage.begin_system_call(transaction_hash, None, &[]).await?,
+        }
+        Ok(())
+    }
+}
+
+/// Fetches the events emitted by `address` between the blocks `from` and `to` included,
+/// following the continuation tokens. When the node refuses a request, the page size is halved
+/// if it's too big, otherwise the block range is split in halves until the node serves them.
+pub async fn fetch_events<P>(
+    provider: &P,
+    address: FieldElement,
+    from: u64,
+    to: u64,
+    chunk_size: u64,
+) -> Result<Vec<EmittedEvent>, Box<dyn Error>>
+where
+    P: Provider + Sync,
+{
+    let mut events = vec![];
+    let mut chunk_size = chunk_size.max(1);
+    let mut ranges = vec![(from, to)];
+
+    while let Some((from, to)) = ranges.pop() {
+        match fetch_range(provider, address, from, to, chunk_size).await {
+            Ok(range_events) => events.extend(range_events),
+            Err(ProviderError::StarknetError(StarknetError::PageSizeTooBig)) if chunk_size > 1 => {
+                chunk_size /= 2;
+                ranges.push((from, to));
+            }
+            Err(e) if from < to => {
+                warn!("getting events of blocks {from} to {to}, splitting the range: {e}");
+                // The lower half is popped first to keep the events in order.
+                let middle = from + (to - from) / 2;
+                ranges.push((middle + 1, to));
+                ranges.push((from, middle));
             }
+            Err(e) => return Err(e.to_string().into()),
+        }
+    }
+
+    Ok(events)
+}
+
+async fn fetch_range<P>(
+    provider: &P,
+    address: FieldElement,
+    from: u64,
+    to: u64,
+    chunk_size: u64,
+) -> Result<Vec<EmittedEvent>, ProviderError<P::Error>>
+where
+    P: Provider + Sync,
+{
+    let filter = EventFilter {
+        from_block: Some(BlockId::Number(from)),
+        to_block: Some(BlockId::Number(to)),
+        address: Some(address),
+        keys: None,
+    };
+
+    let mut events = vec![];
+    let mut continuation_token = None;
+    loop {
+        let page = provider.get_events(filter.clone(), continuation_token, chunk_size).await?;
+        events.extend(page.events);
 
-            current_block_number += 1;
+        continuation_token = page.continuation_token;
+        if continuation_token.is_none() {
+            return Ok(events);
         }
     }
 }
@@ -137,10 +322,11 @@ async fn process_transaction<S: Storage, T: starknet::providers::jsonrpc::JsonRp
     storage: &S,
     provider: &JsonRpcClient<T>,
     processors: &[Arc<dyn TransactionProcessor<S, T>>],
+    transaction: &Transaction,
     receipt: &TransactionReceipt,
 ) -> Result<(), Box<dyn Error>> {
     for processor in processors {
-        processor.process(storage, provider, receipt).await?;
+        processor.process(storage, provider, transaction, receipt).await?;
     }
 
     Ok(())
@@ -150,7 +336,6 @@ async fn process_event<S: Storage, T: starknet::providers::jsonrpc::JsonRpcTrans
     storage: &S,
     provider: &JsonRpcClient<T>,
     processors: &[Arc<dyn EventProcessor<S, T>>],
-    _receipt: &TransactionReceipt,
     event: &Event,
 ) -> Result<(), Box<dyn Error>> {
     for processor in processors {
@@ -0,0 +1,216 @@
+use poem::error::{InternalServerError, NotFoundError};
+use poem::web::{Data, Html, Path};
+use poem::{get, handler, EndpointExt, Route};
+use sqlx::{Pool, Sqlite};
+
+use crate::compression::{decompress, Compression};
+
+/// Number of rows shown in each table of the overview.
+const EXPLORER_PAGE_SIZE: i64 = 50;
+
+const STYLE: &str = "body { font-family: monospace; margin: 2em; } table { border-collapse: \
+                     collapse; margin-bottom: 2em; } td, th { border: 1px solid #ccc; padding: \
+                     4px 8px; text-align: left; } th { background: #eee; }";
+
+/// Routes of the built-in explorer, to be nested under `/explorer`.
+pub fn explorer_routes(pool: Pool<Sqlite>) -> impl poem::Endpoint {
+    Route::new().at("/", get(index)).at("/entities/:id", get(entity)).data(pool)
+}
+
+#[handler]
+async fn index(pool: Data<&Pool<Sqlite>>) -> poem::Result<Html<String>> {
+    overview_html(&pool).await.map(Html).map_err(InternalServerError)
+}
+
+#[handler]
+async fn entity(pool: Data<&Pool<Sqlite>>, Path(id): Path<String>) -> poem::Result<Html<String>> {
+    match entity_html(&pool, &id).await.map_err(InternalServerError)? {
+        Some(html) => Ok(Html(html)),
+        None => Err(NotFoundError.into()),
+    }
+}
+
+pub async fn overview_html(pool: &Pool<Sqlite>) -> sqlx::Result<String> {
+    let components: Vec<(String, String, String)> = sqlx::query_as(
+        "SELECT id, name, class_hash FROM components ORDER BY created_at DESC LIMIT $1",
+    )
+    .bind(EXPLORER_PAGE_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let entities: Vec<(String, String, String, Option<String>, String)> = sqlx::query_as(
+        "SELECT id, name, partition_id, keys, created_at FROM entities WHERE archived_at IS NULL \
+         ORDER BY created_at DESC LIMIT $1",
+    )
+    .bind(EXPLORER_PAGE_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let transactions: Vec<(String, String, String, String)> = sqlx::query_as(
+        "SELECT transaction_hash, system_id, data, created_at FROM system_calls ORDER BY \
+         created_at DESC LIMIT $1",
+    )
+    .bind(EXPLORER_PAGE_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let events: Vec<(String, String, String, String)> = sqlx::query_as(
+        "SELECT id, keys, data, created_at FROM events ORDER BY created_at DESC LIMIT $1",
+    )
+    .bind(EXPLORER_PAGE_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let maintenance_runs: Vec<(String, bool, i64, i64, i64, i64)> = sqlx::query_as(
+        "SELECT created_at, full_vacuum, reclaimed_pages, free_pages, compressed_rows, \
+         checkpoint_ms + compression_ms + vacuum_ms + analyze_ms FROM maintenance_runs ORDER BY \
+         id DESC LIMIT $1",
+    )
+    .bind(EXPLORER_PAGE_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let mut body = String::new();
+
+    body.push_str(&table(
+        "Components",
+        &["Id", "Name", "Class hash"],
+        components
+            .into_iter()
+            .map(|(id, name, class_hash)| vec![escape(&id), escape(&name), escape(&class_hash)]),
+    ));
+
+    body.push_str(&table(
+        "Entities",
+        &["Id", "Name", "Partition", "Keys", "Created at"],
+        entities.into_iter().map(|(id, name, partition, keys, created_at)| {
+            vec![
+                format!("<a href=\"/explorer/entities/{0}\">{0}</a>", escape(&id)),
+                escape(&name),
+                escape(&partition),
+                escape(&keys.unwrap_or_default()),
+                escape(&created_at),
+            ]
+        }),
+    ));
+
+    body.push_str(&table(
+        "Recent transactions",
+        &["Transaction hash", "System", "Calldata", "Created at"],
+        transactions.into_iter().map(|(hash, system, data, created_at)| {
+            vec![escape(&hash), escape(&system), escape(&data), escape(&created_at)]
+        }),
+    ));
+
+    body.push_str(&table(
+        "Recent events",
+        &["Id", "Keys", "Data", "Created at"],
+        events.into_iter().map(|(id, keys, data, created_at)| {
+            vec![escape(&id), escape(&keys), escape(&data), escape(&created_at)]
+        }),
+    ));
+
+    body.push_str(&table(
+        "Maintenance runs",
+        &["Ran at", "Full vacuum", "Reclaimed pages", "Free pages", "Compressed rows", "Duration"],
+        maintenance_runs.into_iter().map(
+            |(created_at, full_vacuum, reclaimed, free, compressed, duration_ms)| {
+                vec![
+                    escape(&created_at),
+                    full_vacuum.to_string(),
+                    reclaimed.to_string(),
+                    free.to_string(),
+                    compressed.to_string(),
+                    format!("{duration_ms}ms"),
+                ]
+            },
+        ),
+    ));
+
+    Ok(page("World explorer", &body))
+}
+
+pub async fn entity_html(pool: &Pool<Sqlite>, id: &str) -> sqlx::Result<Option<String>> {
+    let entity: Option<(String, String, Option<String>, String, String)> = sqlx::query_as(
+        "SELECT name, partition_id, keys, transaction_hash, created_at FROM entities WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+
+    let Some((name, partition, keys, transaction_hash, created_at)) = entity else {
+        return Ok(None);
+    };
+
+    let updates: Vec<(String, String, Option<String>, String)> = sqlx::query_as(
+        "SELECT component_id, data, codec, created_at FROM entity_state_updates WHERE entity_id = \
+         $1 ORDER BY created_at DESC LIMIT $2",
+    )
+    .bind(id)
+    .bind(EXPLORER_PAGE_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    let mut body = table(
+        "Entity",
+        &["Name", "Partition", "Keys", "Transaction hash", "Created at"],
+        [vec![
+            escape(&name),
+            escape(&partition),
+            escape(&keys.unwrap_or_default()),
+            escape(&transaction_hash),
+            escape(&created_at),
+        ]]
+        .into_iter(),
+    );
+
+    body.push_str(&table(
+        "State updates",
+        &["Component", "Data", "Created at"],
+        updates.into_iter().map(|(component, data, codec, created_at)| {
+            let data = Compression::from_codec(codec.as_deref())
+                .and_then(|compression| decompress(&data, compression))
+                .unwrap_or(data);
+            vec![escape(&component), escape(&data), escape(&created_at)]
+        }),
+    ));
+    body.push_str("<a href=\"/explorer\">Back</a>");
+
+    Ok(Some(page(&format!("Entity {id}"), &body)))
+}
+
+fn page(title: &str, body: &str) -> String {
+    let title = escape(title);
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title>\
+         <style>{STYLE}</style></head><body><h1>{title}</h1>{body}</body></html>"
+    )
+}
+
+/// Renders a table, the cells are expected to be escaped already.
+fn table(title: &str, headers: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut html = format!("<h2>{}</h2><table><tr>", escape(title));
+    for header in headers {
+        html.push_str(&format!("<th>{header}</th>"));
+    }
+    html.push_str("</tr>");
+
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            html.push_str(&format!("<td>{cell}</td>"));
+        }
+        html.push_str("</tr>");
+    }
+
+    html.push_str("</table>");
+    html
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
@@ -0,0 +1,355 @@
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::FieldElement;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+use url::Url;
+
+use crate::storage::{AuthorizationChange, Storage};
+use crate::webhooks::{component_name, sign, Notification, StateUpdate, SIGNATURE_HEADER};
+
+/// Delay between two polls of the log once a sink has published every update.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Delay before the first retry of a failed batch, doubled after every failed attempt.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Longest delay between two attempts, batches are retried until they are published.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+fn default_batch_size() -> u32 {
+    100
+}
+
+/// Where a sink publishes the updates.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkKind {
+    /// Posts every batch as a JSON array, signed like the webhooks' payloads.
+    Webhook { url: Url, secret: Option<String> },
+    /// Produces the updates to `topic` through a Kafka REST proxy, keyed by entity.
+    Kafka { rest_proxy: Url, topic: String },
+    /// Publishes every update as a message on `subject`.
+    Nats { url: String, subject: String },
+}
+
+/// A destination of the firehose. Updates are delivered at least once and in order, each
+/// carrying its `id` in the log so consumers can drop the ones delivered twice.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SinkConfig {
+    /// Identifies the cursor of the sink, renaming a sink publishes the retained updates again.
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: SinkKind,
+    /// Maximum number of updates published at once.
+    #[serde(default = "default_batch_size")]
+    pub batch_size: u32,
+}
+
+/// Loads the sinks from a JSON file containing an array of [`SinkConfig`].
+pub fn load_sinks(path: impl AsRef<Path>) -> Result<Vec<SinkConfig>> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+/// Appends an update to the log the sinks publish from.
+pub async fn append(pool: &Pool<Sqlite>, notification: &Notification) -> Result<()> {
+    sqlx::query("INSERT INTO firehose_log (payload) VALUES ($1)")
+        .bind(serde_json::to_string(notification)?)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Tracks a cursor for each sink, starting at the oldest retained update for new sinks. The
+/// cursors of the sinks no longer configured are dropped so they don't retain updates forever.
+pub async fn register_sinks(pool: &Pool<Sqlite>, sinks: &[SinkConfig]) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    let names = sinks.iter().map(|s| s.name.clone()).collect::<Vec<_>>();
+    let registered: Vec<(String,)> =
+        sqlx::query_as("SELECT sink FROM firehose_cursors").fetch_all(&mut tx).await?;
+    for (sink,) in registered.into_iter().filter(|(sink,)| !names.contains(sink)) {
+        sqlx::query("DELETE FROM firehose_cursors WHERE sink = $1")
+            .bind(sink)
+            .execute(&mut tx)
+            .await?;
+    }
+    for name in names {
+        sqlx::query("INSERT OR IGNORE INTO firehose_cursors (sink) VALUES ($1)")
+            .bind(name)
+            .execute(&mut tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Returns up to `limit` updates the sink hasn't published yet, in order, each with its `id`.
+pub async fn read_batch(pool: &Pool<Sqlite>, sink: &str, limit: u32) -> Result<Vec<(i64, Value)>> {
+    let rows: Vec<(i64, String)> = sqlx::query_as(
+        "SELECT id, payload FROM firehose_log WHERE id > (SELECT cursor FROM firehose_cursors \
+         WHERE sink = $1) ORDER BY id LIMIT $2",
+    )
+    .bind(sink)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter()
+        .map(|(id, payload)| {
+            let mut update: Value = serde_json::from_str(&payload)?;
+            update["id"] = json!(id);
+            Ok((id, update))
+        })
+        .collect()
+}
+
+/// Records that the sink published every update up to `id`, and drops the updates every sink
+/// has published.
+pub async fn advance_cursor(pool: &Pool<Sqlite>, sink: &str, id: i64) -> Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "UPDATE firehose_cursors SET cursor = $1, updated_at = CURRENT_TIMESTAMP WHERE sink = $2",
+    )
+    .bind(id)
+    .bind(sink)
+    .execute(&mut tx)
+    .await?;
+    sqlx::query("DELETE FROM firehose_log WHERE id <= (SELECT MIN(cursor) FROM firehose_cursors)")
+        .execute(&mut tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Publishes the logged updates to every sink until the token is cancelled. Each sink runs in
+/// its own task, so an unreachable sink only holds back its own cursor.
+pub async fn start_firehose(
+    ct: CancellationToken,
+    pool: Pool<Sqlite>,
+    sinks: Vec<SinkConfig>,
+) -> Result<()> {
+    if sinks.is_empty() {
+        return Ok(());
+    }
+    info!("starting firehose for {} sinks", sinks.len());
+
+    register_sinks(&pool, &sinks).await?;
+    let tasks = sinks
+        .into_iter()
+        .map(|sink| tokio::spawn(run_sink(ct.clone(), pool.clone(), sink)))
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await??;
+    }
+    Ok(())
+}
+
+async fn run_sink(ct: CancellationToken, pool: Pool<Sqlite>, sink: SinkConfig) -> Result<()> {
+    let mut publisher = Publisher { http: reqwest::Client::new(), nats: None };
+
+    loop {
+        let batch = read_batch(&pool, &sink.name, sink.batch_size).await?;
+        let Some(&(last, _)) = batch.last() else {
+            tokio::select! {
+                _ = ct.cancelled() => return Ok(()),
+                _ = tokio::time::sleep(POLL_INTERVAL) => {}
+            }
+            continue;
+        };
+
+        let updates = batch.into_iter().map(|(_, update)| update).collect::<Vec<_>>();
+        let mut delay = INITIAL_RETRY_DELAY;
+        while let Err(e) = publisher.publish(&sink.kind, &updates).await {
+            warn!("firehose sink {} failed, retrying in {delay:?}: {e}", sink.name);
+            tokio::select! {
+                _ = ct.cancelled() => return Ok(()),
+                _ = tokio::time::sleep(delay) => {}
+            }
+            delay = (delay * 2).min(MAX_RETRY_DELAY);
+        }
+
+        advance_cursor(&pool, &sink.name, last).await?;
+    }
+}
+
+/// Connections of a sink, reused across batches.
+struct Publisher {
+    http: reqwest::Client,
+    nats: Option<async_nats::Client>,
+}
+
+impl Publisher {
+    async fn publish(&mut self, kind: &SinkKind, updates: &[Value]) -> Result<()> {
+        match kind {
+            SinkKind::Webhook { url, secret } => {
+                let body = serde_json::to_vec(updates)?;
+                let mut request = self
+                    .http
+                    .post(url.clone())
+                    .header(reqwest::header::CONTENT_TYPE, "application/json")
+                    .body(body.clone());
+                if let Some(secret) = secret {
+                    request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+                }
+                request.send().await?.error_for_status()?;
+            }
+            SinkKind::Kafka { rest_proxy, topic } => {
+                let records = updates
+                    .iter()
+                    .map(|update| json!({ "key": update["key"], "value": update }))
+                    .collect::<Vec<_>>();
+                self.http
+                    .post(rest_proxy.join(&format!("topics/{topic}"))?)
+                    .header(reqwest::header::CONTENT_TYPE, "application/vnd.kafka.json.v2+json")
+                    .json(&json!({ "records": records }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            SinkKind::Nats { url, subject } => {
+                let client = match &self.nats {
+                    Some(client) => client.clone(),
+                    None => {
+                        let client = async_nats::connect(url.as_str())
+                            .await
+                            .map_err(|e| anyhow!("Failed to connect to {url}: {e}"))?;
+                        self.nats = Some(client.clone());
+                        client
+                    }
+                };
+                for update in updates {
+                    client
+                        .publish(subject.clone(), serde_json::to_vec(update)?.into())
+                        .await
+                        .map_err(|e| anyhow!("Failed to publish to {subject}: {e}"))?;
+                }
+                client.flush().await.map_err(|e| anyhow!("Failed to flush to {subject}: {e}"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Storage logging the entity updates written through it for the firehose sinks.
+pub struct FirehoseStorage<S: Storage> {
+    inner: S,
+    /// `None` when no sink is configured, the updates are then not retained.
+    log: Option<Pool<Sqlite>>,
+}
+
+impl<S: Storage> FirehoseStorage<S> {
+    pub fn new(inner: S, log: Option<Pool<Sqlite>>) -> Self {
+        Self { inner, log }
+    }
+
+    async fn log(&self, update: StateUpdate) -> Result<()> {
+        match &self.log {
+            Some(pool) => {
+                let notification =
+                    Notification { timestamp: Utc::now().timestamp(), system: None, update };
+                append(pool, &notification).await
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl<S: Storage + Send + Sync> Storage for FirehoseStorage<S> {
+    async fn head(&self) -> Result<u64> {
+        self.inner.head().await
+    }
+
+    async fn begin_block(&self) -> Result<()> {
+        self.inner.begin_block().await
+    }
+
+    async fn set_head(&self, head: u64) -> Result<()> {
+        self.inner.set_head(head).await
+    }
+
+    async fn begin_system_call(
+        &self,
+        transaction_hash: FieldElement,
+        system: Option<&str>,
+        calldata: &[FieldElement],
+    ) -> Result<()> {
+        self.inner.begin_system_call(transaction_hash, system, calldata).await
+    }
+
+    async fn create_component(&self, name: FieldElement, columns: Vec<FieldElement>) -> Result<()> {
+        self.inner.create_component(name, columns).await
+    }
+
+    async fn set_entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+        values: Vec<FieldElement>,
+    ) -> Result<()> {
+        self.inner.set_entity(component, partition, key, values.clone()).await?;
+        self.log(StateUpdate::EntitySet {
+            component: component_name(component),
+            partition,
+            key,
+            values,
+        })
+        .await
+    }
+
+    async fn delete_entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+    ) -> Result<()> {
+        self.inner.delete_entity(component, partition, key).await?;
+        self.log(StateUpdate::EntityDeleted {
+            component: component_name(component),
+            partition,
+            key,
+        })
+        .await
+    }
+
+    async fn entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+    ) -> Result<Vec<FieldElement>> {
+        self.inner.entity(component, partition, key).await
+    }
+
+    async fn entities(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+    ) -> Result<Vec<Vec<FieldElement>>> {
+        self.inner.entities(component, partition).await
+    }
+
+    async fn record_system_call(
+        &self,
+        system: &str,
+        fee: FieldElement,
+        failed: bool,
+    ) -> Result<()> {
+        self.inner.record_system_call(system, fee, failed).await
+    }
+
+    async fn apply_authorization(
+        &self,
+        change: &AuthorizationChange,
+        transaction_hash: FieldElement,
+    ) -> Result<()> {
+        self.inner.apply_authorization(change, transaction_hash).await
+    }
+}
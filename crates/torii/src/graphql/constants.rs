@@ -1 +1,13 @@
 pub const _DEFAULT_LIMIT: usize = 10;
+// Cap on the `limit` of the paginated queries, unless configured otherwise
+pub const DEFAULT_MAX_PAGE_SIZE: i64 = 100;
+
+// Subscriptions poll the database for the updates indexed since the last one sent
+pub const SUBSCRIPTION_POLL_INTERVAL_MS: u64 = 500;
+pub const SUBSCRIPTION_BATCH_SIZE: i64 = 100;
+
+// Limits applied in read-only mode
+pub const READ_ONLY_MAX_DEPTH: usize = 8;
+pub const READ_ONLY_MAX_COMPLEXITY: usize = 250;
+pub const READ_ONLY_CACHE_TTL_SECS: u64 = 5;
+pub const READ_ONLY_REQUESTS_PER_MINUTE: u32 = 120;
@@ -0,0 +1,41 @@
+use std::sync::Arc;
+
+use poem::http::header::{
+    ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+    ORIGIN, VARY,
+};
+use poem::http::{HeaderValue, Method, StatusCode};
+use poem::{Endpoint, IntoResponse, Request, Response};
+use tokio::sync::watch;
+
+use crate::settings::RuntimeSettings;
+
+/// Allows the origins of the latest settings to query `endpoint` from a browser, answering their
+/// preflight requests. The settings are read on every request so a reload applies to the next.
+pub async fn cors<E: Endpoint>(
+    endpoint: Arc<E>,
+    req: Request,
+    settings: watch::Receiver<RuntimeSettings>,
+) -> poem::Result<Response> {
+    let origin = req
+        .headers()
+        .get(ORIGIN)
+        .and_then(|origin| origin.to_str().ok())
+        .filter(|origin| settings.borrow().allows_origin(origin))
+        .and_then(|origin| HeaderValue::from_str(origin).ok());
+
+    let mut response = match &origin {
+        Some(_) if req.method() == Method::OPTIONS => Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .header(ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, OPTIONS")
+            .header(ACCESS_CONTROL_ALLOW_HEADERS, "content-type, authorization")
+            .finish(),
+        _ => endpoint.call(req).await?.into_response(),
+    };
+
+    if let Some(origin) = origin {
+        response.headers_mut().insert(ACCESS_CONTROL_ALLOW_ORIGIN, origin);
+        response.headers_mut().insert(VARY, HeaderValue::from_static("Origin"));
+    }
+    Ok(response)
+}
@@ -0,0 +1,376 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Result};
+use async_graphql::dynamic::{
+    Field, FieldFuture, FieldValue, InputValue, Object, ResolverContext, Scalar, Schema,
+    Subscription, SubscriptionField, SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::{Name, Value};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::{Pool, Sqlite};
+use tokio::sync::mpsc::{channel, Sender};
+use tokio::sync::watch;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::constants::{
+    DEFAULT_MAX_PAGE_SIZE, SUBSCRIPTION_BATCH_SIZE, SUBSCRIPTION_POLL_INTERVAL_MS,
+};
+use super::object::entity::{self, Entity, EntityObject};
+use super::object::entity_state_update::{
+    self, entity_state_updates_after, latest_component_updates, latest_entity_state_update_id,
+    EntityStateUpdateObject, UpdateFilter,
+};
+use super::object::{ObjectTrait, TypeMapping, ValueMapping};
+use super::types::ScalarType;
+use crate::settings::RuntimeSettings;
+
+const WORLD_ID: &str = "worldId";
+
+/// A world indexed in its own database, by this indexer or another one.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WorldConfig {
+    /// Discriminates the world in the federated schema, such as its address or its region.
+    pub id: String,
+    pub database_url: String,
+}
+
+pub fn load_worlds(path: impl AsRef<Path>) -> Result<Vec<WorldConfig>> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+pub struct FederatedWorld {
+    pub id: String,
+    pub pool: Pool<Sqlite>,
+}
+
+/// The worlds answering the federated queries, e.g. the shards of a deployment split by region.
+pub struct Federation {
+    worlds: Vec<FederatedWorld>,
+}
+
+impl Federation {
+    pub fn new(worlds: Vec<FederatedWorld>) -> Result<Self> {
+        let mut ids = HashSet::new();
+        if let Some(world) = worlds.iter().find(|world| !ids.insert(world.id.as_str())) {
+            bail!("World `{}` is federated more than once", world.id);
+        }
+        Ok(Self { worlds })
+    }
+
+    /// Connects to the databases of the other worlds, which are migrated by their own indexers.
+    pub async fn connect(local: FederatedWorld, configs: &[WorldConfig]) -> Result<Self> {
+        let mut worlds = vec![local];
+        for config in configs {
+            let pool =
+                SqlitePoolOptions::new().max_connections(5).connect(&config.database_url).await?;
+            worlds.push(FederatedWorld { id: config.id.clone(), pool });
+        }
+        Self::new(worlds)
+    }
+
+    fn world(&self, id: &str) -> Result<&FederatedWorld> {
+        self.worlds
+            .iter()
+            .find(|world| world.id == id)
+            .ok_or_else(|| anyhow!("Unknown world `{id}`"))
+    }
+
+    /// The worlds the client restricted the query to, all of them by default.
+    fn selected(&self, ctx: &ResolverContext<'_>) -> async_graphql::Result<Vec<&FederatedWorld>> {
+        let Some(ids) = ctx.args.get("worldIds") else {
+            return Ok(self.worlds.iter().collect());
+        };
+        let mut worlds = Vec::new();
+        for id in ids.list()?.iter() {
+            worlds.push(self.world(id.string()?)?);
+        }
+        Ok(worlds)
+    }
+}
+
+/// Builds the schema answering queries spanning the federated worlds. The components of the
+/// worlds don't share a schema, so entities are served with the raw data of their components,
+/// every type being discriminated by a `worldId`.
+pub fn build_federated_schema(
+    federation: Federation,
+    settings: Option<watch::Receiver<RuntimeSettings>>,
+) -> Result<Schema> {
+    let query_root = Object::new("Query")
+        .field(worlds_field())
+        .field(entity_field())
+        .field(entities_field());
+    let subscription_root = Subscription::new("Subscription").field(updates_subscription());
+
+    let entity = FederatedObject::new("FederatedEntity", &EntityObject::new());
+    let update =
+        FederatedObject::new("FederatedEntityStateUpdate", &EntityStateUpdateObject::new());
+    let world = FederatedObject {
+        type_name: "World",
+        field_type_mapping: IndexMap::from([
+            (Name::new(WORLD_ID), TypeRef::ID.to_string()),
+            (Name::new("head"), TypeRef::INT.to_string()),
+        ]),
+    };
+
+    let mut schema_builder = Schema::build("Query", None, Some("Subscription"))
+        .register(world.object())
+        .register(entity.object().field(components_field()))
+        .register(update.object());
+    for scalar_type in ScalarType::types().iter() {
+        schema_builder = schema_builder.register(Scalar::new(*scalar_type));
+    }
+    if let Some(settings) = settings {
+        schema_builder = schema_builder.data(settings);
+    }
+
+    Ok(schema_builder
+        .register(query_root)
+        .register(subscription_root)
+        .data(Arc::new(federation))
+        .finish()?)
+}
+
+/// Object of the federated schema, with the fields of its single world counterpart.
+struct FederatedObject {
+    type_name: &'static str,
+    field_type_mapping: TypeMapping,
+}
+
+impl FederatedObject {
+    fn new(type_name: &'static str, object: &dyn ObjectTrait) -> Self {
+        let mut field_type_mapping =
+            IndexMap::from([(Name::new(WORLD_ID), TypeRef::ID.to_string())]);
+        field_type_mapping.extend(object.field_type_mapping().clone());
+        Self { type_name, field_type_mapping }
+    }
+}
+
+impl ObjectTrait for FederatedObject {
+    fn name(&self) -> &str {
+        self.type_name
+    }
+
+    fn type_name(&self) -> &str {
+        self.type_name
+    }
+
+    fn field_type_mapping(&self) -> &TypeMapping {
+        &self.field_type_mapping
+    }
+
+    fn resolvers(&self) -> Vec<Field> {
+        vec![]
+    }
+}
+
+fn with_world(world_id: &str, values: ValueMapping) -> ValueMapping {
+    let mut mapping = IndexMap::from([(Name::new(WORLD_ID), Value::from(world_id))]);
+    mapping.extend(values);
+    mapping
+}
+
+fn values_list(values: Vec<ValueMapping>) -> Option<FieldValue<'static>> {
+    Some(FieldValue::list(values.into_iter().map(FieldValue::owned_any)))
+}
+
+fn world_ids_argument() -> InputValue {
+    InputValue::new("worldIds", TypeRef::named_nn_list(TypeRef::ID))
+}
+
+fn worlds_field() -> Field {
+    Field::new("worlds", TypeRef::named_nn_list_nn("World"), |ctx| {
+        FieldFuture::new(async move {
+            let federation = ctx.data::<Arc<Federation>>()?;
+            let mut worlds = Vec::new();
+            for world in &federation.worlds {
+                let head: Option<(i64,)> = sqlx::query_as("SELECT head FROM indexer WHERE id = 1")
+                    .fetch_optional(&world.pool)
+                    .await?;
+                worlds.push(IndexMap::from([
+                    (Name::new(WORLD_ID), Value::from(world.id.as_str())),
+                    (Name::new("head"), Value::from(head.map_or(0, |(head,)| head))),
+                ]));
+            }
+            Ok(values_list(worlds))
+        })
+    })
+}
+
+/// The entity with the given id in every world it exists in.
+fn entity_field() -> Field {
+    Field::new("entity", TypeRef::named_nn_list_nn("FederatedEntity"), |ctx| {
+        FieldFuture::new(async move {
+            let federation = ctx.data::<Arc<Federation>>()?;
+            let id = ctx.args.try_get("id")?.string()?;
+            let mut entities = Vec::new();
+            for world in federation.selected(&ctx)? {
+                let entity: Option<Entity> =
+                    sqlx::query_as("SELECT * FROM entities WHERE id = $1 AND archived_at IS NULL")
+                        .bind(id)
+                        .fetch_optional(&world.pool)
+                        .await?;
+                if let Some(entity) = entity {
+                    entities.push(with_world(&world.id, entity::value_mapping(entity)));
+                }
+            }
+            Ok(values_list(entities))
+        })
+    })
+    .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::ID)))
+    .argument(world_ids_argument())
+}
+
+/// The entities with the given keys, up to `limit` per world.
+fn entities_field() -> Field {
+    Field::new("entities", TypeRef::named_nn_list_nn("FederatedEntity"), |ctx| {
+        FieldFuture::new(async move {
+            let federation = ctx.data::<Arc<Federation>>()?;
+            let keys = ctx.args.try_get("keys")?.string()?;
+            let max_page_size = ctx
+                .data_opt::<watch::Receiver<RuntimeSettings>>()
+                .map(|settings| settings.borrow().max_page_size)
+                .unwrap_or(DEFAULT_MAX_PAGE_SIZE);
+            let limit = match ctx.args.get("limit") {
+                Some(limit) => limit.i64()?.min(max_page_size),
+                None => max_page_size,
+            };
+
+            let mut entities = Vec::new();
+            for world in federation.selected(&ctx)? {
+                let world_entities: Vec<Entity> = sqlx::query_as(
+                    "SELECT * FROM entities WHERE keys = $1 AND archived_at IS NULL
+                     ORDER BY created_at LIMIT $2",
+                )
+                .bind(keys)
+                .bind(limit)
+                .fetch_all(&world.pool)
+                .await?;
+                entities.extend(
+                    world_entities
+                        .into_iter()
+                        .map(|entity| with_world(&world.id, entity::value_mapping(entity))),
+                );
+            }
+            Ok(values_list(entities))
+        })
+    })
+    .argument(InputValue::new("keys", TypeRef::named_nn(TypeRef::STRING)))
+    .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
+    .argument(world_ids_argument())
+}
+
+/// The latest update of every component of the entity, in the entity's world.
+fn components_field() -> Field {
+    Field::new("components", TypeRef::named_nn_list_nn("FederatedEntityStateUpdate"), |ctx| {
+        FieldFuture::new(async move {
+            let federation = ctx.data::<Arc<Federation>>()?;
+            let entity = ctx.parent_value.try_downcast_ref::<ValueMapping>()?;
+            let (Some(Value::String(world_id)), Some(Value::String(entity_id))) =
+                (entity.get(WORLD_ID), entity.get("id"))
+            else {
+                return Err("entity without a world or an id".into());
+            };
+
+            let world = federation.world(world_id)?;
+            let updates = latest_component_updates(&world.pool, entity_id).await?;
+            Ok(values_list(
+                updates
+                    .into_iter()
+                    .map(|update| with_world(world_id, entity_state_update::value_mapping(update)))
+                    .collect(),
+            ))
+        })
+    })
+}
+
+/// Streams the component updates of the worlds as they are indexed. Updates are sent in the
+/// order they were indexed within a world, but the worlds' streams are interleaved. Clients
+/// resume after a reconnection by passing the id of the last update they received from each
+/// world as `<worldId>:<id>` cursors.
+fn updates_subscription() -> SubscriptionField {
+    SubscriptionField::new(
+        "entityStateUpdates",
+        TypeRef::named_nn("FederatedEntityStateUpdate"),
+        |ctx| {
+            SubscriptionFieldFuture::new(async move {
+                let federation = ctx.data::<Arc<Federation>>()?.clone();
+                let mut cursors = HashMap::new();
+                if let Some(list) = ctx.args.get("cursors") {
+                    for cursor in list.list()?.iter() {
+                        let cursor = cursor.string()?;
+                        let (world_id, id) = cursor
+                            .split_once(':')
+                            .ok_or_else(|| format!("Invalid cursor `{cursor}`"))?;
+                        cursors.insert(federation.world(world_id)?.id.clone(), id.parse::<i64>()?);
+                    }
+                }
+
+                let component_name =
+                    ctx.args.get("componentName").map(|name| name.string()).transpose()?;
+                let entity_id = ctx.args.get("entityId").map(|id| id.string()).transpose()?;
+
+                let (sender, receiver) = channel(SUBSCRIPTION_BATCH_SIZE as usize);
+                for world in federation.selected(&ctx)? {
+                    let cursor = match cursors.get(&world.id) {
+                        Some(cursor) => *cursor,
+                        None => latest_entity_state_update_id(&world.pool).await?,
+                    };
+                    let filter = UpdateFilter {
+                        component_name: component_name.map(str::to_string),
+                        entity_id: entity_id.map(str::to_string),
+                    };
+                    let (world_id, pool) = (world.id.clone(), world.pool.clone());
+                    let sender = sender.clone();
+                    tokio::spawn(stream_world_updates(world_id, pool, cursor, filter, sender));
+                }
+
+                Ok(ReceiverStream::new(receiver))
+            })
+        },
+    )
+    .argument(InputValue::new("cursors", TypeRef::named_nn_list(TypeRef::STRING)))
+    .argument(InputValue::new("componentName", TypeRef::named(TypeRef::STRING)))
+    .argument(InputValue::new("entityId", TypeRef::named(TypeRef::ID)))
+    .argument(world_ids_argument())
+}
+
+/// Polls a world for the updates indexed after `cursor`, until the client is gone.
+async fn stream_world_updates(
+    world_id: String,
+    pool: Pool<Sqlite>,
+    mut cursor: i64,
+    filter: UpdateFilter,
+    sender: Sender<async_graphql::Result<FieldValue<'static>>>,
+) {
+    loop {
+        let updates = match entity_state_updates_after(&pool, cursor, &filter).await {
+            Ok(updates) => updates,
+            Err(e) => {
+                let _ = sender.send(Err(format!("World `{world_id}`: {e}").into())).await;
+                return;
+            }
+        };
+
+        if updates.is_empty() {
+            if sender.is_closed() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(SUBSCRIPTION_POLL_INTERVAL_MS)).await;
+            continue;
+        }
+
+        for update in updates {
+            cursor = update.id;
+            let values = with_world(&world_id, entity_state_update::value_mapping(update));
+            if sender.send(Ok(FieldValue::owned_any(values))).await.is_err() {
+                return;
+            }
+        }
+    }
+}
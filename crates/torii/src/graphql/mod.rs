@@ -1,6 +1,11 @@
-mod constants;
-mod object;
+pub mod constants;
+mod cors;
+pub mod federation;
+pub mod object;
+mod read_only;
+pub mod saved_query;
 pub mod schema;
 pub mod server;
-mod types;
+pub mod signing;
+pub mod types;
 mod utils;
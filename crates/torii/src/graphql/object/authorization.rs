@@ -0,0 +1,189 @@
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef};
+use async_graphql::{Name, Value};
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use sqlx::pool::PoolConnection;
+use sqlx::{FromRow, Pool, Result, Sqlite};
+
+use super::{ObjectTrait, TypeMapping, ValueMapping};
+use crate::graphql::types::ScalarType;
+use crate::graphql::utils::limit_arg;
+
+const DEFAULT_LIMIT: i64 = 10;
+
+#[derive(FromRow, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization {
+    pub system: String,
+    pub component: String,
+    pub role: String,
+    pub transaction_hash: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(FromRow, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorizationLog {
+    pub id: i64,
+    pub action: String,
+    pub target: String,
+    pub role: String,
+    pub resource: String,
+    pub transaction_hash: String,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct AuthorizationObject {
+    pub field_type_mapping: TypeMapping,
+}
+
+impl AuthorizationObject {
+    pub fn new() -> Self {
+        Self {
+            field_type_mapping: IndexMap::from([
+                (Name::new("system"), TypeRef::STRING.to_string()),
+                (Name::new("component"), TypeRef::STRING.to_string()),
+                (Name::new("role"), TypeRef::STRING.to_string()),
+                (Name::new("transactionHash"), ScalarType::FELT.to_string()),
+                (Name::new("updatedAt"), ScalarType::DATE_TIME.to_string()),
+            ]),
+        }
+    }
+}
+
+impl ObjectTrait for AuthorizationObject {
+    fn name(&self) -> &str {
+        "authorizations"
+    }
+
+    fn type_name(&self) -> &str {
+        "Authorization"
+    }
+
+    fn field_type_mapping(&self) -> &TypeMapping {
+        &self.field_type_mapping
+    }
+
+    fn resolvers(&self) -> Vec<Field> {
+        vec![Field::new(self.name(), TypeRef::named_nn_list_nn(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                let system = ctx.args.get("system").map(|s| s.string()).transpose()?;
+                let component = ctx.args.get("component").map(|c| c.string()).transpose()?;
+                let authorizations = authorizations(&mut conn, system, component).await?;
+                Ok(Some(FieldValue::list(authorizations.into_iter().map(FieldValue::owned_any))))
+            })
+        })
+        .argument(InputValue::new("system", TypeRef::named(TypeRef::STRING)))
+        .argument(InputValue::new("component", TypeRef::named(TypeRef::STRING)))]
+    }
+}
+
+/// Returns the components systems are allowed to write, optionally filtered by system and by
+/// component. Admins are allowed to write every component, which is reported as `*`.
+pub async fn authorizations(
+    conn: &mut PoolConnection<Sqlite>,
+    system: Option<&str>,
+    component: Option<&str>,
+) -> Result<Vec<ValueMapping>> {
+    let authorizations: Vec<Authorization> = sqlx::query_as(
+        "SELECT * FROM authorizations WHERE ($1 IS NULL OR system = $1) AND ($2 IS NULL OR \
+         component = $2 OR component = '*') ORDER BY system, component",
+    )
+    .bind(system)
+    .bind(component)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(authorizations
+        .into_iter()
+        .map(|authorization| {
+            IndexMap::from([
+                (Name::new("system"), Value::from(authorization.system)),
+                (Name::new("component"), Value::from(authorization.component)),
+                (Name::new("role"), Value::from(authorization.role)),
+                (Name::new("transactionHash"), Value::from(authorization.transaction_hash)),
+                (Name::new("updatedAt"), date_time(authorization.updated_at)),
+            ])
+        })
+        .collect())
+}
+
+pub struct AuthorizationLogObject {
+    pub field_type_mapping: TypeMapping,
+}
+
+impl AuthorizationLogObject {
+    pub fn new() -> Self {
+        Self {
+            field_type_mapping: IndexMap::from([
+                (Name::new("id"), TypeRef::ID.to_string()),
+                (Name::new("action"), TypeRef::STRING.to_string()),
+                (Name::new("target"), TypeRef::STRING.to_string()),
+                (Name::new("role"), TypeRef::STRING.to_string()),
+                (Name::new("resource"), TypeRef::STRING.to_string()),
+                (Name::new("transactionHash"), ScalarType::FELT.to_string()),
+                (Name::new("createdAt"), ScalarType::DATE_TIME.to_string()),
+            ]),
+        }
+    }
+}
+
+impl ObjectTrait for AuthorizationLogObject {
+    fn name(&self) -> &str {
+        "authorizationLog"
+    }
+
+    fn type_name(&self) -> &str {
+        "AuthorizationLog"
+    }
+
+    fn field_type_mapping(&self) -> &TypeMapping {
+        &self.field_type_mapping
+    }
+
+    fn resolvers(&self) -> Vec<Field> {
+        vec![Field::new(self.name(), TypeRef::named_nn_list_nn(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                let limit = limit_arg(&ctx, DEFAULT_LIMIT)?;
+                let log = authorization_log(&mut conn, limit).await?;
+                Ok(Some(FieldValue::list(log.into_iter().map(FieldValue::owned_any))))
+            })
+        })
+        .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))]
+    }
+}
+
+/// Returns the latest grants and revocations first. Columns an action doesn't have, like the
+/// role of a revocation, are empty.
+pub async fn authorization_log(
+    conn: &mut PoolConnection<Sqlite>,
+    limit: i64,
+) -> Result<Vec<ValueMapping>> {
+    let log: Vec<AuthorizationLog> =
+        sqlx::query_as("SELECT * FROM auth_log ORDER BY id DESC LIMIT $1")
+            .bind(limit)
+            .fetch_all(conn)
+            .await?;
+
+    Ok(log
+        .into_iter()
+        .map(|entry| {
+            IndexMap::from([
+                (Name::new("id"), Value::from(entry.id.to_string())),
+                (Name::new("action"), Value::from(entry.action)),
+                (Name::new("target"), Value::from(entry.target)),
+                (Name::new("role"), Value::from(entry.role)),
+                (Name::new("resource"), Value::from(entry.resource)),
+                (Name::new("transactionHash"), Value::from(entry.transaction_hash)),
+                (Name::new("createdAt"), date_time(entry.created_at)),
+            ])
+        })
+        .collect())
+}
+
+fn date_time(date_time: DateTime<Utc>) -> Value {
+    Value::from(date_time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+}
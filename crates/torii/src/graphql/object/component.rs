@@ -6,11 +6,13 @@ use serde::Deserialize;
 use sqlx::pool::PoolConnection;
 use sqlx::{FromRow, Pool, Result, Sqlite};
 
-use super::storage::{storage_by_column, type_mapping_from_definition, ColumnName};
+use super::storage::{
+    enum_mapping_from_definition, storage_by_column, type_mapping_from_definition, ColumnName,
+};
 use super::{ObjectTrait, TypeMapping, ValueMapping};
 use crate::graphql::types::ScalarType;
 use crate::graphql::utils::extract_value::extract;
-use crate::graphql::utils::{format_name, remove_quotes};
+use crate::graphql::utils::remove_quotes;
 
 #[derive(FromRow, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -21,18 +23,20 @@ pub struct Component {
     pub class_hash: String,
     pub transaction_hash: String,
     pub storage_definition: String,
+    pub deprecated_definition: String,
     pub created_at: DateTime<Utc>,
 }
 
 pub struct ComponentObject {
     pub field_type_mapping: TypeMapping,
-    pub storage_names: Vec<String>,
+    // Component names mapped to their storage type names
+    pub storage_types: IndexMap<String, String>,
 }
 
 impl ComponentObject {
-    // Storage names are passed in on new because
+    // Storage types are passed in on new because
     // it builds the related fields dynamically
-    pub fn new(storage_names: Vec<String>) -> Self {
+    pub fn new(storage_types: IndexMap<String, String>) -> Self {
         Self {
             field_type_mapping: IndexMap::from([
                 (Name::new("id"), TypeRef::ID.to_string()),
@@ -41,9 +45,10 @@ impl ComponentObject {
                 (Name::new("classHash"), ScalarType::FELT.to_string()),
                 (Name::new("transactionHash"), ScalarType::FELT.to_string()),
                 (Name::new("storageDefinition"), TypeRef::STRING.to_string()),
+                (Name::new("deprecatedDefinition"), TypeRef::STRING.to_string()),
                 (Name::new("createdAt"), ScalarType::DATE_TIME.to_string()),
             ]),
-            storage_names,
+            storage_types,
         }
     }
 }
@@ -62,29 +67,40 @@ impl ObjectTrait for ComponentObject {
     }
 
     fn unions(&self) -> Option<Vec<Union>> {
-        Some(vec![self.storage_names.iter().fold(Union::new("Storage"), |union, storage| {
-            let (_, type_name) = format_name(storage);
-            union.possible_type(type_name)
-        })])
+        Some(vec![self
+            .storage_types
+            .values()
+            .fold(Union::new("Storage"), |union, type_name| union.possible_type(type_name))])
     }
 
     fn nested_fields(&self) -> Option<Vec<Field>> {
-        Some(vec![Field::new("storage", TypeRef::named("Storage"), |ctx| {
+        let storage_types = self.storage_types.clone();
+        Some(vec![Field::new("storage", TypeRef::named("Storage"), move |ctx| {
+            let storage_types = storage_types.clone();
             FieldFuture::new(async move {
                 let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                 let component_values = ctx.parent_value.try_downcast_ref::<ValueMapping>()?;
 
                 let id = extract::<String>(component_values, "id")?;
                 let defintion = extract::<String>(component_values, "storageDefinition")?;
-                let type_name = extract::<String>(component_values, "name")?;
+                let deprecated = extract::<String>(component_values, "deprecatedDefinition")?;
+                let name = extract::<String>(component_values, "name")?;
+                let type_name = storage_types.get(&name).cloned().unwrap_or_else(|| name.clone());
 
                 let field_type_mapping = type_mapping_from_definition(&defintion)?;
+                let deprecated_mapping = type_mapping_from_definition(&deprecated)?;
+                let mut enum_mapping = enum_mapping_from_definition(&defintion)?;
+                for (name, variants) in enum_mapping_from_definition(&deprecated)? {
+                    enum_mapping.entry(name).or_insert(variants);
+                }
                 let storage_values = storage_by_column(
                     &mut conn,
                     ColumnName::ComponentId,
                     &id,
-                    &type_name,
+                    &name,
                     &field_type_mapping,
+                    &deprecated_mapping,
+                    &enum_mapping,
                 )
                 .await?;
 
@@ -131,6 +147,7 @@ fn value_mapping(component: Component) -> ValueMapping {
         (Name::new("classHash"), Value::from(component.class_hash)),
         (Name::new("transactionHash"), Value::from(component.transaction_hash)),
         (Name::new("storageDefinition"), Value::from(component.storage_definition)),
+        (Name::new("deprecatedDefinition"), Value::from(component.deprecated_definition)),
         (
             Name::new("createdAt"),
             Value::from(component.created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
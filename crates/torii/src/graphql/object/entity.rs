@@ -19,6 +19,7 @@ pub struct Entity {
     pub keys: Option<String>,
     pub transaction_hash: String,
     pub created_at: DateTime<Utc>,
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 pub struct EntityObject {
@@ -35,6 +36,7 @@ impl EntityObject {
                 (Name::new("keys"), TypeRef::STRING.to_string()),
                 (Name::new("transactionHash"), ScalarType::FELT.to_string()),
                 (Name::new("createdAt"), ScalarType::DATE_TIME.to_string()),
+                (Name::new("archived"), TypeRef::BOOLEAN.to_string()),
             ]),
         }
     }
@@ -59,23 +61,37 @@ impl ObjectTrait for EntityObject {
                 FieldFuture::new(async move {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
                     let id = remove_quotes(ctx.args.try_get("id")?.string()?);
-                    let entity_values = entity_by_id(&mut conn, &id).await?;
+                    let include_archived = match ctx.args.get("includeArchived") {
+                        Some(include_archived) => include_archived.boolean()?,
+                        None => false,
+                    };
+                    let entity_values = entity_by_id(&mut conn, &id, include_archived).await?;
                     Ok(Some(FieldValue::owned_any(entity_values)))
                 })
             })
-            .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::ID))),
+            .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::ID)))
+            .argument(InputValue::new("includeArchived", TypeRef::named(TypeRef::BOOLEAN))),
         ]
     }
 }
 
-async fn entity_by_id(conn: &mut PoolConnection<Sqlite>, id: &str) -> Result<ValueMapping> {
+/// Returns the entity with id `id`, archived entities are only found if `include_archived`.
+async fn entity_by_id(
+    conn: &mut PoolConnection<Sqlite>,
+    id: &str,
+    include_archived: bool,
+) -> Result<ValueMapping> {
     let entity: Entity =
-        sqlx::query_as("SELECT * FROM entities WHERE id = $1").bind(id).fetch_one(conn).await?;
+        sqlx::query_as("SELECT * FROM entities WHERE id = $1 AND ($2 OR archived_at IS NULL)")
+            .bind(id)
+            .bind(include_archived)
+            .fetch_one(conn)
+            .await?;
 
     Ok(value_mapping(entity))
 }
 
-fn value_mapping(entity: Entity) -> ValueMapping {
+pub fn value_mapping(entity: Entity) -> ValueMapping {
     IndexMap::from([
         (Name::new("id"), Value::from(entity.id)),
         (Name::new("name"), Value::from(entity.name)),
@@ -86,5 +102,6 @@ fn value_mapping(entity: Entity) -> ValueMapping {
             Name::new("createdAt"),
             Value::from(entity.created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
         ),
+        (Name::new("archived"), Value::from(entity.archived_at.is_some())),
     ])
 }
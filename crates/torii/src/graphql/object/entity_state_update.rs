@@ -0,0 +1,282 @@
+use std::time::Duration;
+
+use async_graphql::dynamic::{
+    Field, FieldFuture, FieldValue, InputValue, SubscriptionField, SubscriptionFieldFuture,
+    TypeRef,
+};
+use async_graphql::{Name, Value};
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use sqlx::pool::PoolConnection;
+use sqlx::{FromRow, Pool, Result, Sqlite};
+use tokio::sync::mpsc::channel;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::{ObjectTrait, TypeMapping, ValueMapping};
+use crate::compression::{decompress, Compression};
+use crate::graphql::constants::{SUBSCRIPTION_BATCH_SIZE, SUBSCRIPTION_POLL_INTERVAL_MS};
+use crate::graphql::types::ScalarType;
+use crate::graphql::utils::remove_quotes;
+
+#[derive(FromRow, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EntityStateUpdate {
+    pub id: i64,
+    pub entity_id: String,
+    pub component_id: String,
+    pub component_name: String,
+    pub system_call_id: i64,
+    pub transaction_hash: String,
+    pub data: String,
+    /// Whether the component was removed from the entity.
+    pub deleted: bool,
+    /// Codec `data` is compressed with, if it was compressed during maintenance.
+    #[serde(default)]
+    pub codec: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+pub struct EntityStateUpdateObject {
+    pub field_type_mapping: TypeMapping,
+}
+
+impl EntityStateUpdateObject {
+    pub fn new() -> Self {
+        Self {
+            field_type_mapping: IndexMap::from([
+                (Name::new("id"), TypeRef::ID.to_string()),
+                (Name::new("entityId"), TypeRef::ID.to_string()),
+                (Name::new("componentId"), TypeRef::ID.to_string()),
+                (Name::new("componentName"), TypeRef::STRING.to_string()),
+                (Name::new("systemCallId"), TypeRef::INT.to_string()),
+                (Name::new("transactionHash"), ScalarType::FELT.to_string()),
+                (Name::new("data"), TypeRef::STRING.to_string()),
+                (Name::new("deleted"), TypeRef::BOOLEAN.to_string()),
+                (Name::new("createdAt"), ScalarType::DATE_TIME.to_string()),
+            ]),
+        }
+    }
+}
+
+impl ObjectTrait for EntityStateUpdateObject {
+    fn name(&self) -> &str {
+        "entityStateUpdate"
+    }
+
+    fn type_name(&self) -> &str {
+        "EntityStateUpdate"
+    }
+
+    fn field_type_mapping(&self) -> &TypeMapping {
+        &self.field_type_mapping
+    }
+
+    fn resolvers(&self) -> Vec<Field> {
+        vec![Field::new(
+            "entityStateUpdatesByTransaction",
+            TypeRef::named_nn_list_nn(self.type_name()),
+            |ctx| {
+                FieldFuture::new(async move {
+                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let hash = remove_quotes(ctx.args.try_get("transactionHash")?.string()?);
+                    let updates = entity_state_updates_by_transaction(&mut conn, &hash).await?;
+                    Ok(Some(FieldValue::list(updates.into_iter().map(FieldValue::owned_any))))
+                })
+            },
+        )
+        .argument(InputValue::new("transactionHash", TypeRef::named_nn(ScalarType::FELT)))]
+    }
+
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        Some(vec![SubscriptionField::new(
+            "entityStateUpdates",
+            TypeRef::named_nn(self.type_name()),
+            |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let pool = ctx.data::<Pool<Sqlite>>()?.clone();
+                    let cursor = match ctx.args.get("cursor") {
+                        Some(cursor) => cursor.string()?.parse::<i64>()?,
+                        None => latest_entity_state_update_id(&pool).await?,
+                    };
+                    let filter = UpdateFilter {
+                        component_name: ctx
+                            .args
+                            .get("componentName")
+                            .map(|name| name.string().map(str::to_string))
+                            .transpose()?,
+                        entity_id: ctx
+                            .args
+                            .get("entityId")
+                            .map(|id| id.string().map(str::to_string))
+                            .transpose()?,
+                    };
+                    Ok(entity_state_update_stream(pool, cursor, filter))
+                })
+            },
+        )
+        .argument(InputValue::new("cursor", TypeRef::named(TypeRef::ID)))
+        .argument(InputValue::new("componentName", TypeRef::named(TypeRef::STRING)))
+        .argument(InputValue::new("entityId", TypeRef::named(TypeRef::ID)))])
+    }
+}
+
+/// Restricts the updates streamed to a component and/or an entity.
+#[derive(Default)]
+pub struct UpdateFilter {
+    pub component_name: Option<String>,
+    pub entity_id: Option<String>,
+}
+
+/// Streams the updates indexed after the update with id `cursor`, replaying the stored ones
+/// before polling for new ones. Clients resume after a reconnection by passing the id of the last
+/// update they received, without missing any.
+pub fn entity_state_update_stream(
+    pool: Pool<Sqlite>,
+    mut cursor: i64,
+    filter: UpdateFilter,
+) -> ReceiverStream<async_graphql::Result<FieldValue<'static>>> {
+    let (sender, receiver) = channel(SUBSCRIPTION_BATCH_SIZE as usize);
+
+    tokio::spawn(async move {
+        loop {
+            let updates = match entity_state_updates_after(&pool, cursor, &filter).await {
+                Ok(updates) => updates,
+                Err(e) => {
+                    let _ = sender.send(Err(e.into())).await;
+                    return;
+                }
+            };
+
+            // Stop polling once the client is gone.
+            if updates.is_empty() {
+                if sender.is_closed() {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(SUBSCRIPTION_POLL_INTERVAL_MS)).await;
+                continue;
+            }
+
+            for update in updates {
+                cursor = update.id;
+                if sender.send(Ok(FieldValue::owned_any(value_mapping(update)))).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    ReceiverStream::new(receiver)
+}
+
+/// Returns the id of the latest update, live subscriptions start after it.
+pub async fn latest_entity_state_update_id(pool: &Pool<Sqlite>) -> Result<i64> {
+    let (id,): (Option<i64>,) = sqlx::query_as("SELECT MAX(id) FROM entity_state_updates")
+        .fetch_one(pool)
+        .await?;
+    Ok(id.unwrap_or_default())
+}
+
+/// Returns the next batch of updates after the update with id `cursor`, in the order they were
+/// indexed.
+pub async fn entity_state_updates_after(
+    pool: &Pool<Sqlite>,
+    cursor: i64,
+    filter: &UpdateFilter,
+) -> Result<Vec<EntityStateUpdate>> {
+    let updates = sqlx::query_as(
+        "SELECT entity_state_updates.*,
+            COALESCE(components.name, entity_state_updates.component_id) AS component_name,
+            system_calls.transaction_hash
+         FROM entity_state_updates
+         JOIN system_calls ON entity_state_updates.system_call_id = system_calls.id
+         LEFT JOIN components ON entity_state_updates.component_id = components.id
+         WHERE entity_state_updates.id > $1
+            AND ($2 IS NULL OR COALESCE(components.name, entity_state_updates.component_id) = $2)
+            AND ($3 IS NULL OR entity_state_updates.entity_id = $3)
+         ORDER BY entity_state_updates.id
+         LIMIT $4",
+    )
+    .bind(cursor)
+    .bind(&filter.component_name)
+    .bind(&filter.entity_id)
+    .bind(SUBSCRIPTION_BATCH_SIZE)
+    .fetch_all(pool)
+    .await?;
+
+    decompressed(updates)
+}
+
+/// Returns the component updates produced by the system calls of a transaction, in the order
+/// they were indexed.
+pub async fn entity_state_updates_by_transaction(
+    conn: &mut PoolConnection<Sqlite>,
+    transaction_hash: &str,
+) -> Result<Vec<ValueMapping>> {
+    let updates: Vec<EntityStateUpdate> = sqlx::query_as(
+        "SELECT entity_state_updates.*,
+            COALESCE(components.name, entity_state_updates.component_id) AS component_name,
+            system_calls.transaction_hash
+         FROM entity_state_updates
+         JOIN system_calls ON entity_state_updates.system_call_id = system_calls.id
+         LEFT JOIN components ON entity_state_updates.component_id = components.id
+         WHERE system_calls.transaction_hash = $1
+         ORDER BY entity_state_updates.id",
+    )
+    .bind(transaction_hash)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(decompressed(updates)?.into_iter().map(value_mapping).collect())
+}
+
+/// Returns the latest update of every component the entity still has, ordered by component name.
+pub async fn latest_component_updates(
+    pool: &Pool<Sqlite>,
+    entity_id: &str,
+) -> Result<Vec<EntityStateUpdate>> {
+    let updates = sqlx::query_as(
+        "SELECT entity_state_updates.*,
+            COALESCE(components.name, entity_state_updates.component_id) AS component_name,
+            system_calls.transaction_hash
+         FROM entity_state_updates
+         JOIN system_calls ON entity_state_updates.system_call_id = system_calls.id
+         LEFT JOIN components ON entity_state_updates.component_id = components.id
+         WHERE entity_state_updates.id IN (
+            SELECT MAX(id) FROM entity_state_updates WHERE entity_id = $1 GROUP BY component_id
+         ) AND NOT entity_state_updates.deleted
+         ORDER BY component_name",
+    )
+    .bind(entity_id)
+    .fetch_all(pool)
+    .await?;
+
+    decompressed(updates)
+}
+
+/// Restores the payloads compressed during maintenance.
+fn decompressed(mut updates: Vec<EntityStateUpdate>) -> Result<Vec<EntityStateUpdate>> {
+    for update in &mut updates {
+        update.data = Compression::from_codec(update.codec.as_deref())
+            .and_then(|compression| decompress(&update.data, compression))
+            .map_err(|e| sqlx::Error::Decode(e.into()))?;
+    }
+    Ok(updates)
+}
+
+pub fn value_mapping(update: EntityStateUpdate) -> ValueMapping {
+    IndexMap::from([
+        (Name::new("id"), Value::from(update.id.to_string())),
+        (Name::new("entityId"), Value::from(update.entity_id)),
+        (Name::new("componentId"), Value::from(update.component_id)),
+        (Name::new("componentName"), Value::from(update.component_name)),
+        (Name::new("systemCallId"), Value::from(update.system_call_id)),
+        (Name::new("transactionHash"), Value::from(update.transaction_hash)),
+        (Name::new("data"), Value::from(update.data)),
+        (Name::new("deleted"), Value::from(update.deleted)),
+        (
+            Name::new("createdAt"),
+            Value::from(update.created_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        ),
+    ])
+}
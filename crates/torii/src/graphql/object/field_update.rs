@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_graphql::dynamic::{
+    Field, FieldValue, InputValue, SubscriptionField, SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::{Name, Value};
+use dojo_world::manifest::Member;
+use indexmap::IndexMap;
+use sqlx::{Pool, Result, Sqlite};
+use tokio::sync::mpsc::channel;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::{create_field, ObjectTrait, TypeMapping, ValueMapping};
+use crate::graphql::constants::{SUBSCRIPTION_BATCH_SIZE, SUBSCRIPTION_POLL_INTERVAL_MS};
+
+/// A member of a component's storage to watch, of one entity or of all of them.
+pub struct FieldSubscription {
+    pub component_name: String,
+    pub field: String,
+    pub entity_id: Option<String>,
+}
+
+/// A change of a member's value, as stored in the component's storage table.
+struct FieldChange {
+    id: i64,
+    entity_id: String,
+    value: Option<String>,
+}
+
+pub struct FieldUpdateObject {
+    pub field_type_mapping: TypeMapping,
+}
+
+impl FieldUpdateObject {
+    pub fn new() -> Self {
+        Self {
+            field_type_mapping: IndexMap::from([
+                (Name::new("id"), TypeRef::ID.to_string()),
+                (Name::new("entityId"), TypeRef::ID.to_string()),
+                (Name::new("componentName"), TypeRef::STRING.to_string()),
+                (Name::new("field"), TypeRef::STRING.to_string()),
+            ]),
+        }
+    }
+}
+
+impl ObjectTrait for FieldUpdateObject {
+    fn name(&self) -> &str {
+        "fieldUpdate"
+    }
+
+    fn type_name(&self) -> &str {
+        "FieldUpdate"
+    }
+
+    fn field_type_mapping(&self) -> &TypeMapping {
+        &self.field_type_mapping
+    }
+
+    // Values are rendered as text whatever the member's type, the previous one is null for the
+    // first write to an entity
+    fn nested_fields(&self) -> Option<Vec<Field>> {
+        Some(vec![
+            create_field("value", TypeRef::named(TypeRef::STRING)),
+            create_field("previousValue", TypeRef::named(TypeRef::STRING)),
+        ])
+    }
+
+    fn resolvers(&self) -> Vec<Field> {
+        vec![]
+    }
+
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        Some(vec![SubscriptionField::new(
+            "componentFieldUpdates",
+            TypeRef::named_nn(self.type_name()),
+            |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let pool = ctx.data::<Pool<Sqlite>>()?.clone();
+                    let subscription = FieldSubscription {
+                        component_name: ctx.args.try_get("componentName")?.string()?.to_string(),
+                        field: ctx.args.try_get("field")?.string()?.to_string(),
+                        entity_id: ctx
+                            .args
+                            .get("entityId")
+                            .map(|id| id.string().map(str::to_string))
+                            .transpose()?,
+                    };
+                    validate_field(&pool, &subscription).await?;
+
+                    let cursor = match ctx.args.get("cursor") {
+                        Some(cursor) => cursor.string()?.parse::<i64>()?,
+                        None => latest_storage_id(&pool, &subscription.component_name).await?,
+                    };
+                    Ok(field_update_stream(pool, cursor, subscription))
+                })
+            },
+        )
+        .argument(InputValue::new("componentName", TypeRef::named_nn(TypeRef::STRING)))
+        .argument(InputValue::new("field", TypeRef::named_nn(TypeRef::STRING)))
+        .argument(InputValue::new("entityId", TypeRef::named(TypeRef::ID)))
+        .argument(InputValue::new("cursor", TypeRef::named(TypeRef::ID)))])
+    }
+}
+
+/// Streams the changes of a member written after the storage row with id `cursor`. Writes
+/// leaving the member's value unchanged are skipped, so clients watching a single member of a
+/// frequently updated component only receive what they display.
+pub fn field_update_stream(
+    pool: Pool<Sqlite>,
+    mut cursor: i64,
+    subscription: FieldSubscription,
+) -> ReceiverStream<async_graphql::Result<FieldValue<'static>>> {
+    let (sender, receiver) = channel(SUBSCRIPTION_BATCH_SIZE as usize);
+
+    tokio::spawn(async move {
+        // Last value of every entity seen, looked up in the storage on first sight.
+        let mut previous_values: HashMap<String, Option<String>> = HashMap::new();
+
+        loop {
+            let changes = match field_changes_after(&pool, cursor, &subscription).await {
+                Ok(changes) => changes,
+                Err(e) => {
+                    let _ = sender.send(Err(e.into())).await;
+                    return;
+                }
+            };
+
+            // Stop polling once the client is gone.
+            if changes.is_empty() {
+                if sender.is_closed() {
+                    return;
+                }
+                tokio::time::sleep(Duration::from_millis(SUBSCRIPTION_POLL_INTERVAL_MS)).await;
+                continue;
+            }
+
+            for change in changes {
+                cursor = change.id;
+
+                let previous = match previous_values.get(&change.entity_id) {
+                    Some(previous) => previous.clone(),
+                    None => match value_before(&pool, &subscription, &change).await {
+                        Ok(previous) => previous,
+                        Err(e) => {
+                            let _ = sender.send(Err(e.into())).await;
+                            return;
+                        }
+                    },
+                };
+                previous_values.insert(change.entity_id.clone(), change.value.clone());
+                if previous == change.value {
+                    continue;
+                }
+
+                let update = value_mapping(&subscription, change, previous);
+                if sender.send(Ok(FieldValue::owned_any(update))).await.is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    ReceiverStream::new(receiver)
+}
+
+/// Checks that the member is one of the component's, it's then safe to select as a column.
+async fn validate_field(pool: &Pool<Sqlite>, subscription: &FieldSubscription) -> Result<()> {
+    let definition: Option<(String,)> =
+        sqlx::query_as("SELECT storage_definition FROM components WHERE name = $1")
+            .bind(&subscription.component_name)
+            .fetch_optional(pool)
+            .await?;
+    let (definition,) = definition.ok_or_else(|| {
+        sqlx::Error::Protocol(format!("unknown component {}", subscription.component_name))
+    })?;
+
+    let members: Vec<Member> =
+        serde_json::from_str(&definition).map_err(|e| sqlx::Error::Decode(e.into()))?;
+    if !members.iter().any(|member| member.name == subscription.field) {
+        return Err(sqlx::Error::Protocol(format!(
+            "{} has no member {}",
+            subscription.component_name, subscription.field
+        )));
+    }
+    Ok(())
+}
+
+fn storage_table(component_name: &str) -> String {
+    format!("storage_{}", component_name.to_lowercase())
+}
+
+/// Returns the id of the latest write to the component, live subscriptions start after it.
+async fn latest_storage_id(pool: &Pool<Sqlite>, component_name: &str) -> Result<i64> {
+    let query = format!("SELECT MAX(id) FROM {}", storage_table(component_name));
+    let (id,): (Option<i64>,) = sqlx::query_as(&query).fetch_one(pool).await?;
+    Ok(id.unwrap_or_default())
+}
+
+/// Returns the next batch of writes to the member after the row with id `cursor`.
+async fn field_changes_after(
+    pool: &Pool<Sqlite>,
+    cursor: i64,
+    subscription: &FieldSubscription,
+) -> Result<Vec<FieldChange>> {
+    // Members are stored as integers or text depending on their type, read them all as text.
+    let query = format!(
+        "SELECT id, entity_id, CAST({} AS TEXT) FROM {} WHERE id > $1 AND ($2 IS NULL OR \
+         entity_id = $2) ORDER BY id LIMIT $3",
+        subscription.field,
+        storage_table(&subscription.component_name)
+    );
+    let rows: Vec<(i64, String, Option<String>)> = sqlx::query_as(&query)
+        .bind(cursor)
+        .bind(&subscription.entity_id)
+        .bind(SUBSCRIPTION_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, entity_id, value)| FieldChange { id, entity_id, value })
+        .collect())
+}
+
+/// Value of the member written to the entity before `change`, `None` if it's the first write.
+async fn value_before(
+    pool: &Pool<Sqlite>,
+    subscription: &FieldSubscription,
+    change: &FieldChange,
+) -> Result<Option<String>> {
+    let query = format!(
+        "SELECT CAST({} AS TEXT) FROM {} WHERE entity_id = $1 AND id < $2 ORDER BY id DESC LIMIT 1",
+        subscription.field,
+        storage_table(&subscription.component_name)
+    );
+    let value: Option<(Option<String>,)> =
+        sqlx::query_as(&query).bind(&change.entity_id).bind(change.id).fetch_optional(pool).await?;
+    Ok(value.and_then(|(value,)| value))
+}
+
+fn value_mapping(
+    subscription: &FieldSubscription,
+    change: FieldChange,
+    previous: Option<String>,
+) -> ValueMapping {
+    IndexMap::from([
+        (Name::new("id"), Value::from(change.id.to_string())),
+        (Name::new("entityId"), Value::from(change.entity_id)),
+        (Name::new("componentName"), Value::from(subscription.component_name.clone())),
+        (Name::new("field"), Value::from(subscription.field.clone())),
+        (Name::new("value"), change.value.map_or(Value::Null, Value::from)),
+        (Name::new("previousValue"), previous.map_or(Value::Null, Value::from)),
+    ])
+}
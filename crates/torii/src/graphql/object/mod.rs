@@ -1,29 +1,56 @@
+pub mod authorization;
 pub mod component;
 pub mod entity;
+pub mod entity_state_update;
 pub mod event;
+pub mod field_update;
 pub mod storage;
 pub mod system;
 pub mod system_call;
+pub mod system_metrics;
+pub mod transaction;
 
-use async_graphql::dynamic::{Field, FieldFuture, Object, TypeRef, Union};
+use async_graphql::dynamic::{
+    Enum, Field, FieldFuture, InputObject, Object, SubscriptionField, TypeRef, Union,
+};
 use async_graphql::{Name, Value};
 use indexmap::IndexMap;
 
 // Type aliases for GraphQL fields
 pub type TypeMapping = IndexMap<Name, String>;
 pub type ValueMapping = IndexMap<Name, Value>;
+// Enum type names mapped to their variant names, ordered by discriminant
+pub type EnumMapping = IndexMap<String, Vec<String>>;
+
+const DEPRECATION_REASON: &str = "Removed from the component by an upgrade";
 
 pub trait ObjectTrait {
     fn name(&self) -> &str;
     fn type_name(&self) -> &str;
     fn field_type_mapping(&self) -> &TypeMapping;
     fn resolvers(&self) -> Vec<Field>;
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        None
+    }
+    fn mutations(&self) -> Option<Vec<Field>> {
+        None
+    }
     fn nested_fields(&self) -> Option<Vec<Field>> {
         None
     }
     fn unions(&self) -> Option<Vec<Union>> {
         None
     }
+    fn enums(&self) -> Option<Vec<Enum>> {
+        None
+    }
+    fn input_objects(&self) -> Option<Vec<InputObject>> {
+        None
+    }
+    // Fields kept for backward compatibility, they are nullable and marked as deprecated
+    fn deprecated_field_type_mapping(&self) -> Option<&TypeMapping> {
+        None
+    }
 
     // Create a new GraphQL object
     fn object(&self) -> Object {
@@ -31,10 +58,19 @@ pub trait ObjectTrait {
 
         // Add fields (ie id, createdAt, etc)
         for (field_name, field_type) in self.field_type_mapping() {
-            let field = create_field(field_name, field_type);
+            let field = create_field(field_name, TypeRef::named_nn(field_type));
             object = object.field(field);
         }
 
+        // Add deprecated fields (ie members removed by a component upgrade)
+        if let Some(deprecated_fields) = self.deprecated_field_type_mapping() {
+            for (field_name, field_type) in deprecated_fields {
+                let field = create_field(field_name, TypeRef::named(field_type))
+                    .deprecation(Some(DEPRECATION_REASON));
+                object = object.field(field);
+            }
+        }
+
         // Add related fields (ie event, system)
         if let Some(nested_fields) = self.nested_fields() {
             for field in nested_fields {
@@ -46,10 +82,10 @@ pub trait ObjectTrait {
     }
 }
 
-fn create_field(name: &str, field_type: &str) -> Field {
+fn create_field(name: &str, field_type: TypeRef) -> Field {
     let outer_name = name.to_owned();
 
-    Field::new(name, TypeRef::named_nn(field_type), move |ctx| {
+    Field::new(name, field_type, move |ctx| {
         let inner_name = outer_name.to_owned();
 
         FieldFuture::new(async move {
@@ -1,24 +1,108 @@
-use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef};
+use std::collections::HashMap;
+
+use async_graphql::dynamic::{
+    Enum, Field, FieldFuture, FieldValue, InputObject, InputValue, ObjectAccessor, TypeRef,
+};
 use async_graphql::{Name, Value};
 use dojo_world::manifest::Member;
 use sqlx::pool::PoolConnection;
 use sqlx::sqlite::SqliteRow;
 use sqlx::{Error, Pool, Result, Row, Sqlite};
+use starknet::core::types::FieldElement;
+use tokio::sync::watch;
 
-use super::{ObjectTrait, TypeMapping, ValueMapping};
+use super::{EnumMapping, ObjectTrait, TypeMapping, ValueMapping};
 use crate::graphql::types::ScalarType;
+use crate::settings::RuntimeSettings;
 
 const BOOLEAN_TRUE: i64 = 1;
 
 pub struct StorageObject {
     pub name: String,
     pub type_name: String,
+    // Name of the component backing this storage, which can differ from the
+    // (possibly namespaced) GraphQL names
+    pub component_name: String,
     pub field_type_mapping: TypeMapping,
+    // Members removed by an upgrade, still resolvable from their columns
+    pub deprecated_field_type_mapping: TypeMapping,
+    pub enum_mapping: EnumMapping,
 }
 
 impl StorageObject {
-    pub fn new(name: String, type_name: String, field_type_mapping: TypeMapping) -> Self {
-        Self { name, type_name, field_type_mapping }
+    pub fn new(
+        name: String,
+        type_name: String,
+        component_name: String,
+        field_type_mapping: TypeMapping,
+        deprecated_field_type_mapping: TypeMapping,
+        enum_mapping: EnumMapping,
+    ) -> Self {
+        Self {
+            name,
+            type_name,
+            component_name,
+            field_type_mapping,
+            deprecated_field_type_mapping,
+            enum_mapping,
+        }
+    }
+
+    // Name of the input object filtering the storages by their members
+    fn where_type_name(&self) -> String {
+        format!("{}WhereInput", self.type_name)
+    }
+
+    // Lists the storages whose members equal the values of the `where` argument, enum members
+    // being given the name of a variant
+    fn filter_resolver(&self) -> Field {
+        let name = self.component_name.clone();
+        let type_mapping = self.field_type_mapping.clone();
+        let deprecated_mapping = self.deprecated_field_type_mapping.clone();
+        let enum_mapping = self.enum_mapping.clone();
+        let field_name = format!("{}Components", self.name);
+        let type_ref = TypeRef::named_nn_list_nn(self.type_name());
+
+        Field::new(field_name, type_ref, move |ctx| {
+            let inner_name = name.clone();
+            let inner_type_mapping = type_mapping.clone();
+            let inner_deprecated_mapping = deprecated_mapping.clone();
+            let inner_enum_mapping = enum_mapping.clone();
+
+            FieldFuture::new(async move {
+                let conditions = match ctx.args.get("where") {
+                    Some(filter) => member_conditions(
+                        &filter.object()?,
+                        &inner_type_mapping,
+                        &inner_enum_mapping,
+                    )?,
+                    None => vec![],
+                };
+                let limit = ctx.args.get("limit").map(|limit| limit.i64()).transpose()?;
+                let max_page_size = ctx
+                    .data_opt::<watch::Receiver<RuntimeSettings>>()
+                    .map(|settings| settings.borrow().max_page_size);
+                let limit = match (limit, max_page_size) {
+                    (Some(limit), Some(max)) => Some(limit.min(max)),
+                    (limit, max) => limit.or(max),
+                };
+
+                let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                let storages = storages_by_members(
+                    &mut conn,
+                    &inner_name,
+                    &conditions,
+                    limit,
+                    &inner_type_mapping,
+                    &inner_deprecated_mapping,
+                    &inner_enum_mapping,
+                )
+                .await?;
+                Ok(Some(FieldValue::list(storages.into_iter().map(FieldValue::owned_any))))
+            })
+        })
+        .argument(InputValue::new("where", TypeRef::named(self.where_type_name())))
+        .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))
     }
 }
 
@@ -35,13 +119,42 @@ impl ObjectTrait for StorageObject {
         &self.field_type_mapping
     }
 
+    fn deprecated_field_type_mapping(&self) -> Option<&TypeMapping> {
+        Some(&self.deprecated_field_type_mapping)
+    }
+
+    fn enums(&self) -> Option<Vec<Enum>> {
+        Some(
+            self.enum_mapping
+                .iter()
+                .map(|(name, variants)| {
+                    variants.iter().fold(Enum::new(name), |e, variant| e.item(variant.as_str()))
+                })
+                .collect(),
+        )
+    }
+
+    fn input_objects(&self) -> Option<Vec<InputObject>> {
+        let input_object = self.field_type_mapping.iter().fold(
+            InputObject::new(self.where_type_name()),
+            |input_object, (field_name, field_type)| {
+                input_object.field(InputValue::new(field_name.as_str(), TypeRef::named(field_type)))
+            },
+        );
+        Some(vec![input_object])
+    }
+
     fn resolvers(&self) -> Vec<Field> {
-        let name = self.name.clone();
+        let name = self.component_name.clone();
         let type_mapping = self.field_type_mapping.clone();
+        let deprecated_mapping = self.deprecated_field_type_mapping.clone();
+        let enum_mapping = self.enum_mapping.clone();
         vec![
             Field::new(self.name(), TypeRef::named_nn(self.type_name()), move |ctx| {
                 let inner_name = name.clone();
                 let inner_type_mapping = type_mapping.clone();
+                let inner_deprecated_mapping = deprecated_mapping.clone();
+                let inner_enum_mapping = enum_mapping.clone();
 
                 FieldFuture::new(async move {
                     let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
@@ -52,16 +165,94 @@ impl ObjectTrait for StorageObject {
                         id.as_str(),
                         &inner_name,
                         &inner_type_mapping,
+                        &inner_deprecated_mapping,
+                        &inner_enum_mapping,
                     )
                     .await?;
                     Ok(Some(FieldValue::owned_any(storage_values)))
                 })
             })
             .argument(InputValue::new("id", TypeRef::named_nn(TypeRef::INT))),
+            self.filter_resolver(),
         ]
     }
 }
 
+/// Value a storage column is compared to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColumnValue {
+    Integer(i64),
+    Text(String),
+}
+
+/// Column values the members given in `filter` are compared to. Enum members are given the name
+/// of a variant, mapped to the discriminant they're stored as.
+pub fn member_conditions(
+    filter: &ObjectAccessor<'_>,
+    fields: &TypeMapping,
+    enums: &EnumMapping,
+) -> async_graphql::Result<Vec<(String, ColumnValue)>> {
+    let mut conditions = vec![];
+    for (field_name, field_type) in fields {
+        let Some(value) = filter.get(field_name).filter(|value| !value.is_null()) else {
+            continue;
+        };
+        let value = match field_type.as_str() {
+            ty if enums.contains_key(ty) => {
+                let variant = value.enum_name()?;
+                let discriminant =
+                    enums[ty].iter().position(|name| name == variant).ok_or_else(|| {
+                        async_graphql::Error::new(format!("`{variant}` isn't a variant of `{ty}`"))
+                    })?;
+                ColumnValue::Integer(discriminant as i64)
+            }
+            ScalarType::U8 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64 => {
+                ColumnValue::Integer(value.i64()?)
+            }
+            // sqlite stores booleans as 0 or 1
+            TypeRef::BOOLEAN => ColumnValue::Integer(i64::from(value.boolean()?)),
+            _ => ColumnValue::Text(value.string()?.to_string()),
+        };
+        conditions.push((field_name.to_string(), value));
+    }
+    Ok(conditions)
+}
+
+/// Storages of the component whose columns equal the values of `conditions`, by id. `limit`
+/// caps the number of storages returned.
+pub async fn storages_by_members(
+    conn: &mut PoolConnection<Sqlite>,
+    name: &str,
+    conditions: &[(String, ColumnValue)],
+    limit: Option<i64>,
+    fields: &TypeMapping,
+    deprecated_fields: &TypeMapping,
+    enums: &EnumMapping,
+) -> Result<Vec<ValueMapping>> {
+    let mut query = format!("SELECT * FROM storage_{}", name);
+    if !conditions.is_empty() {
+        let clauses: Vec<String> =
+            conditions.iter().map(|(column, _)| format!("{column} = ?")).collect();
+        query.push_str(&format!(" WHERE {}", clauses.join(" AND ")));
+    }
+    // a negative limit means no limit in sqlite
+    query.push_str(" ORDER BY id LIMIT ?");
+
+    let mut query = sqlx::query(&query);
+    for (_, value) in conditions {
+        query = match value {
+            ColumnValue::Integer(value) => query.bind(*value),
+            ColumnValue::Text(value) => query.bind(value.clone()),
+        };
+    }
+    let storages = query.bind(limit.unwrap_or(-1)).fetch_all(conn).await?;
+
+    storages
+        .iter()
+        .map(|row| value_mapping_from_row(row, fields, deprecated_fields, enums))
+        .collect()
+}
+
 #[allow(dead_code)]
 pub enum ColumnName {
     Id,
@@ -85,42 +276,133 @@ pub async fn storage_by_column(
     id: &str,
     name: &str,
     fields: &TypeMapping,
+    deprecated_fields: &TypeMapping,
+    enums: &EnumMapping,
 ) -> Result<ValueMapping> {
     let query = format!("SELECT * FROM storage_{} WHERE {} = ?", name, column_name.as_str());
     let storage = sqlx::query(&query).bind(id).fetch_one(conn).await?;
-    let result = value_mapping_from_row(&storage, fields)?;
+    let result = value_mapping_from_row(&storage, fields, deprecated_fields, enums)?;
     Ok(result)
 }
 
-fn value_mapping_from_row(row: &SqliteRow, fields: &TypeMapping) -> Result<ValueMapping> {
+pub fn value_mapping_from_row(
+    row: &SqliteRow,
+    fields: &TypeMapping,
+    deprecated_fields: &TypeMapping,
+    enums: &EnumMapping,
+) -> Result<ValueMapping> {
     let mut value_mapping = ValueMapping::new();
 
+    for (field_name, field_type) in fields {
+        let value = field_value(row, field_name, field_type, enums)?;
+        value_mapping.insert(Name::new(field_name), value);
+    }
+
+    // Rows written after an upgrade may not have a value for the removed members
+    for (field_name, field_type) in deprecated_fields {
+        let value = field_value(row, field_name, field_type, enums).unwrap_or(Value::Null);
+        value_mapping.insert(Name::new(field_name), value);
+    }
+
+    Ok(value_mapping)
+}
+
+fn field_value(
+    row: &SqliteRow,
+    field_name: &str,
+    field_type: &str,
+    enums: &EnumMapping,
+) -> Result<Value> {
     // Cairo's data types are stored as either int or str in sqlite db,
     // int's max size is 64bit so we retrieve all types above u64 as str
-    for (field_name, field_type) in fields {
-        let value = match field_type.as_str() {
-            ScalarType::U8 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64 => {
-                let result = row.try_get::<i64, &str>(field_name.as_str());
-                Value::from(result?)
-            }
-            ScalarType::U128 | ScalarType::U250 | ScalarType::U256 | ScalarType::FELT => {
-                let result = row.try_get::<String, &str>(field_name.as_str());
-                Value::from(result?)
-            }
-            TypeRef::BOOLEAN => {
-                // sqlite stores booleans as 0 or 1
-                let result = row.try_get::<i64, &str>(field_name.as_str());
-                Value::from(matches!(result?, BOOLEAN_TRUE))
-            }
-            _ => return Err(Error::TypeNotFound { type_name: field_type.clone() }),
-        };
+    let value = match field_type {
+        ScalarType::U8 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64 => {
+            let result = row.try_get::<i64, &str>(field_name);
+            Value::from(result?)
+        }
+        ScalarType::U128 | ScalarType::U250 | ScalarType::U256 | ScalarType::FELT => {
+            let result = row.try_get::<String, &str>(field_name);
+            Value::from(result?)
+        }
+        TypeRef::BOOLEAN => {
+            // sqlite stores booleans as 0 or 1
+            let result = row.try_get::<i64, &str>(field_name);
+            Value::from(matches!(result?, BOOLEAN_TRUE))
+        }
+        ty if enums.contains_key(ty) => {
+            // enums are stored as their variant's discriminant
+            let discriminant = row.try_get::<i64, &str>(field_name)?;
+            let variant = usize::try_from(discriminant)
+                .ok()
+                .and_then(|index| enums[ty].get(index))
+                .ok_or_else(|| {
+                    Error::Decode(format!("invalid discriminant {discriminant} for {ty}").into())
+                })?;
+            Value::Enum(Name::new(variant))
+        }
+        _ => return Err(Error::TypeNotFound { type_name: field_type.to_string() }),
+    };
+
+    Ok(value)
+}
+
+/// Values of the members of an entity decoded from the raw storage values of a component table,
+/// see [`dojo_world::migration::schema::member_values`]. Removed members have no raw value left
+/// and resolve to null.
+pub fn value_mapping_from_values(
+    values: &HashMap<String, Vec<FieldElement>>,
+    fields: &TypeMapping,
+    deprecated_fields: &TypeMapping,
+    enums: &EnumMapping,
+) -> Result<ValueMapping> {
+    let mut value_mapping = ValueMapping::new();
 
+    for (field_name, field_type) in fields {
+        let member_values = values.get(field_name.as_str()).map(Vec::as_slice).unwrap_or_default();
+        let value = member_value(member_values, field_type, enums)?;
         value_mapping.insert(Name::new(field_name), value);
     }
+    for field_name in deprecated_fields.keys() {
+        value_mapping.insert(Name::new(field_name), Value::Null);
+    }
 
     Ok(value_mapping)
 }
 
+fn member_value(values: &[FieldElement], field_type: &str, enums: &EnumMapping) -> Result<Value> {
+    let value = values.first().copied().unwrap_or(FieldElement::ZERO);
+    let invalid = || Error::Decode(format!("invalid {field_type} {value:#x}").into());
+
+    let value = match field_type {
+        ScalarType::U8 | ScalarType::U16 | ScalarType::U32 | ScalarType::U64 => {
+            Value::from(u64::try_from(value).map_err(|_| invalid())?)
+        }
+        // serialized as its low and high 128 bits
+        ScalarType::U256 => match values.get(1).copied().unwrap_or(FieldElement::ZERO) {
+            high if high == FieldElement::ZERO => Value::from(format!("{value:#x}")),
+            high => {
+                let low = u128::try_from(value).map_err(|_| invalid())?;
+                Value::from(format!("{high:#x}{low:032x}"))
+            }
+        },
+        ScalarType::U128 | ScalarType::U250 | ScalarType::FELT => {
+            Value::from(format!("{value:#x}"))
+        }
+        TypeRef::BOOLEAN => Value::from(value == FieldElement::ONE),
+        ty if enums.contains_key(ty) => {
+            // enums are stored as their variant's discriminant
+            let variant = u64::try_from(value)
+                .ok()
+                .and_then(|index| enums[ty].get(index as usize))
+                .ok_or_else(invalid)?;
+            Value::Enum(Name::new(variant))
+        }
+        _ => return Err(Error::TypeNotFound { type_name: field_type.to_string() }),
+    };
+
+    Ok(value)
+}
+
 pub fn type_mapping_from_definition(storage_def: &str) -> Result<TypeMapping> {
     let members: Vec<Member> =
         serde_json::from_str(storage_def).map_err(|e| Error::Decode(e.into()))?;
@@ -132,3 +414,16 @@ pub fn type_mapping_from_definition(storage_def: &str) -> Result<TypeMapping> {
         });
     Ok(field_type_mapping)
 }
+
+pub fn enum_mapping_from_definition(storage_def: &str) -> Result<EnumMapping> {
+    let members: Vec<Member> =
+        serde_json::from_str(storage_def).map_err(|e| Error::Decode(e.into()))?;
+    let enum_mapping: EnumMapping =
+        members.into_iter().fold(EnumMapping::new(), |mut mapping, member| {
+            if let Some(variants) = member.variants {
+                mapping.insert(member.ty, variants);
+            }
+            mapping
+        });
+    Ok(enum_mapping)
+}
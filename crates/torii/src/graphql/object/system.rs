@@ -7,6 +7,7 @@ use sqlx::pool::PoolConnection;
 use sqlx::{FromRow, Pool, Result, Sqlite};
 
 use super::system_call::system_calls_by_system_id;
+use super::system_metrics::system_metrics_by_name;
 use super::{ObjectTrait, TypeMapping, ValueMapping};
 use crate::graphql::types::ScalarType;
 use crate::graphql::utils::extract_value::extract;
@@ -70,17 +71,31 @@ impl ObjectTrait for SystemObject {
     }
 
     fn nested_fields(&self) -> Option<Vec<Field>> {
-        Some(vec![Field::new("systemCalls", TypeRef::named_nn_list_nn("SystemCall"), |ctx| {
-            FieldFuture::new(async move {
-                let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
-                let system_values = ctx.parent_value.try_downcast_ref::<ValueMapping>()?;
+        Some(vec![
+            Field::new("systemCalls", TypeRef::named_nn_list_nn("SystemCall"), |ctx| {
+                FieldFuture::new(async move {
+                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let system_values = ctx.parent_value.try_downcast_ref::<ValueMapping>()?;
 
-                let id = extract::<String>(system_values, "id")?;
-                let system_calls = system_calls_by_system_id(&mut conn, &id).await?;
+                    let id = extract::<String>(system_values, "id")?;
+                    let system_calls = system_calls_by_system_id(&mut conn, &id).await?;
 
-                Ok(Some(FieldValue::list(system_calls.into_iter().map(FieldValue::owned_any))))
-            })
-        })])
+                    Ok(Some(FieldValue::list(system_calls.into_iter().map(FieldValue::owned_any))))
+                })
+            }),
+            Field::new("metrics", TypeRef::named("SystemMetrics"), |ctx| {
+                FieldFuture::new(async move {
+                    let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                    let system_values = ctx.parent_value.try_downcast_ref::<ValueMapping>()?;
+
+                    // Systems are executed through the world by their name without the suffix.
+                    let name = extract::<String>(system_values, "name")?;
+                    let name = name.strip_suffix("System").unwrap_or(&name);
+                    let metrics = system_metrics_by_name(&mut conn, name).await?;
+                    Ok(metrics.map(FieldValue::owned_any))
+                })
+            }),
+        ])
     }
 }
 
@@ -0,0 +1,122 @@
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, InputValue, TypeRef};
+use async_graphql::{Name, Value};
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::Deserialize;
+use sqlx::pool::PoolConnection;
+use sqlx::{FromRow, Pool, Result, Sqlite};
+use starknet::core::types::FieldElement;
+
+use super::{ObjectTrait, TypeMapping, ValueMapping};
+use crate::graphql::types::ScalarType;
+use crate::graphql::utils::limit_arg;
+
+const DEFAULT_LIMIT: i64 = 10;
+
+#[derive(FromRow, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SystemMetrics {
+    pub system: String,
+    pub calls: i64,
+    pub failures: i64,
+    pub total_fee: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+pub struct SystemMetricsObject {
+    pub field_type_mapping: TypeMapping,
+}
+
+impl SystemMetricsObject {
+    pub fn new() -> Self {
+        Self {
+            field_type_mapping: IndexMap::from([
+                (Name::new("system"), TypeRef::STRING.to_string()),
+                (Name::new("calls"), TypeRef::INT.to_string()),
+                (Name::new("failures"), TypeRef::INT.to_string()),
+                (Name::new("failureRate"), TypeRef::FLOAT.to_string()),
+                (Name::new("totalFee"), ScalarType::FELT.to_string()),
+                (Name::new("averageFee"), ScalarType::FELT.to_string()),
+                (Name::new("updatedAt"), ScalarType::DATE_TIME.to_string()),
+            ]),
+        }
+    }
+}
+
+impl ObjectTrait for SystemMetricsObject {
+    fn name(&self) -> &str {
+        "systemMetrics"
+    }
+
+    fn type_name(&self) -> &str {
+        "SystemMetrics"
+    }
+
+    fn field_type_mapping(&self) -> &TypeMapping {
+        &self.field_type_mapping
+    }
+
+    fn resolvers(&self) -> Vec<Field> {
+        vec![Field::new(self.name(), TypeRef::named_nn_list_nn(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let mut conn = ctx.data::<Pool<Sqlite>>()?.acquire().await?;
+                let limit = limit_arg(&ctx, DEFAULT_LIMIT)?;
+                let metrics = hottest_systems(&mut conn, limit).await?;
+                Ok(Some(FieldValue::list(metrics.into_iter().map(FieldValue::owned_any))))
+            })
+        })
+        .argument(InputValue::new("limit", TypeRef::named(TypeRef::INT)))]
+    }
+}
+
+/// Returns the metrics of the most called systems first.
+pub async fn hottest_systems(
+    conn: &mut PoolConnection<Sqlite>,
+    limit: i64,
+) -> Result<Vec<ValueMapping>> {
+    let metrics: Vec<SystemMetrics> =
+        sqlx::query_as("SELECT * FROM system_metrics ORDER BY calls DESC, system LIMIT $1")
+            .bind(limit)
+            .fetch_all(conn)
+            .await?;
+
+    Ok(metrics.into_iter().map(value_mapping).collect())
+}
+
+/// Returns the metrics of a system, `system` being its name without the `System` suffix.
+pub async fn system_metrics_by_name(
+    conn: &mut PoolConnection<Sqlite>,
+    system: &str,
+) -> Result<Option<ValueMapping>> {
+    let metrics: Option<SystemMetrics> =
+        sqlx::query_as("SELECT * FROM system_metrics WHERE system = $1")
+            .bind(system)
+            .fetch_optional(conn)
+            .await?;
+
+    Ok(metrics.map(value_mapping))
+}
+
+fn value_mapping(metrics: SystemMetrics) -> ValueMapping {
+    let total_fee = FieldElement::from_hex_be(&metrics.total_fee).unwrap_or_default();
+    let average_fee = if metrics.calls > 0 {
+        total_fee.floor_div(FieldElement::from(metrics.calls as u64))
+    } else {
+        FieldElement::ZERO
+    };
+    let failure_rate =
+        if metrics.calls > 0 { metrics.failures as f64 / metrics.calls as f64 } else { 0.0 };
+
+    IndexMap::from([
+        (Name::new("system"), Value::from(metrics.system)),
+        (Name::new("calls"), Value::from(metrics.calls)),
+        (Name::new("failures"), Value::from(metrics.failures)),
+        (Name::new("failureRate"), Value::from(failure_rate)),
+        (Name::new("totalFee"), Value::from(format!("{total_fee:#x}"))),
+        (Name::new("averageFee"), Value::from(format!("{average_fee:#x}"))),
+        (
+            Name::new("updatedAt"),
+            Value::from(metrics.updated_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)),
+        ),
+    ])
+}
@@ -0,0 +1,214 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_graphql::dynamic::{
+    Field, FieldFuture, FieldValue, InputValue, ResolverContext, SubscriptionField,
+    SubscriptionFieldFuture, TypeRef,
+};
+use async_graphql::{Name, Value};
+use indexmap::IndexMap;
+use starknet::core::types::{
+    BroadcastedInvokeTransaction, BroadcastedInvokeTransactionV1, FieldElement,
+    MaybePendingTransactionReceipt, TransactionReceipt,
+};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::jsonrpc::HttpTransport;
+use starknet::providers::{JsonRpcClient, Provider};
+use tokio::sync::mpsc::channel;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::{ObjectTrait, TypeMapping, ValueMapping};
+use crate::graphql::constants::SUBSCRIPTION_POLL_INTERVAL_MS;
+use crate::graphql::types::ScalarType;
+use crate::graphql::utils::remove_quotes;
+
+/// Status of a transaction sent through torii before the node knows of it.
+const RECEIVED: &str = "RECEIVED";
+const PENDING: &str = "PENDING";
+
+/// Node the signed transactions are forwarded to, and the world they execute systems of.
+#[derive(Clone)]
+pub struct Relay {
+    pub provider: Arc<JsonRpcClient<HttpTransport>>,
+    pub world_address: FieldElement,
+}
+
+pub struct TransactionObject {
+    pub field_type_mapping: TypeMapping,
+}
+
+impl TransactionObject {
+    pub fn new() -> Self {
+        Self {
+            field_type_mapping: IndexMap::from([
+                (Name::new("transactionHash"), ScalarType::FELT.to_string()),
+                (Name::new("status"), TypeRef::STRING.to_string()),
+            ]),
+        }
+    }
+}
+
+impl ObjectTrait for TransactionObject {
+    fn name(&self) -> &str {
+        "transaction"
+    }
+
+    fn type_name(&self) -> &str {
+        "Transaction"
+    }
+
+    fn field_type_mapping(&self) -> &TypeMapping {
+        &self.field_type_mapping
+    }
+
+    fn resolvers(&self) -> Vec<Field> {
+        vec![]
+    }
+
+    fn mutations(&self) -> Option<Vec<Field>> {
+        Some(vec![Field::new("execute", TypeRef::named_nn(self.type_name()), |ctx| {
+            FieldFuture::new(async move {
+                let relay = ctx.data::<Relay>()?;
+
+                let mut calldata = felts_arg(&ctx, "calldata")?;
+                if let Some(system) = ctx.args.get("system") {
+                    let system = cairo_short_string_to_felt(system.string()?)?;
+                    calldata = system_call_calldata(relay.world_address, system, calldata);
+                }
+                let transaction = BroadcastedInvokeTransactionV1 {
+                    sender_address: felt_arg(&ctx, "senderAddress")?,
+                    calldata,
+                    max_fee: felt_arg(&ctx, "maxFee")?,
+                    signature: felts_arg(&ctx, "signature")?,
+                    nonce: felt_arg(&ctx, "nonce")?,
+                };
+
+                let result = relay
+                    .provider
+                    .add_invoke_transaction(&BroadcastedInvokeTransaction::V1(transaction))
+                    .await?;
+                Ok(Some(FieldValue::owned_any(value_mapping(result.transaction_hash, RECEIVED))))
+            })
+        })
+        .argument(InputValue::new("senderAddress", TypeRef::named_nn(ScalarType::FELT)))
+        .argument(InputValue::new("calldata", TypeRef::named_nn_list_nn(ScalarType::FELT)))
+        .argument(InputValue::new("system", TypeRef::named(TypeRef::STRING)))
+        .argument(InputValue::new("maxFee", TypeRef::named_nn(ScalarType::FELT)))
+        .argument(InputValue::new("nonce", TypeRef::named_nn(ScalarType::FELT)))
+        .argument(InputValue::new("signature", TypeRef::named_nn_list_nn(ScalarType::FELT)))])
+    }
+
+    fn subscriptions(&self) -> Option<Vec<SubscriptionField>> {
+        Some(vec![SubscriptionField::new(
+            "transactionStatus",
+            TypeRef::named_nn(self.type_name()),
+            |ctx| {
+                SubscriptionFieldFuture::new(async move {
+                    let relay = ctx.data::<Relay>()?.clone();
+                    let transaction_hash = felt_arg(&ctx, "transactionHash")?;
+                    Ok(transaction_status_stream(relay, transaction_hash))
+                })
+            },
+        )
+        .argument(InputValue::new("transactionHash", TypeRef::named_nn(ScalarType::FELT)))])
+    }
+}
+
+/// Calldata of an account executing `system` through the world, in the multicall encoding of
+/// the Cairo 0 accounts: the calls, then their concatenated calldata.
+pub fn system_call_calldata(
+    world_address: FieldElement,
+    system: FieldElement,
+    calldata: Vec<FieldElement>,
+) -> Vec<FieldElement> {
+    let mut execute_calldata = vec![system, FieldElement::from(calldata.len())];
+    execute_calldata.extend(calldata);
+
+    let mut account_calldata = vec![
+        FieldElement::ONE,
+        world_address,
+        get_selector_from_name("execute").expect("valid selector"),
+        FieldElement::ZERO,
+        FieldElement::from(execute_calldata.len()),
+        FieldElement::from(execute_calldata.len()),
+    ];
+    account_calldata.extend(execute_calldata);
+    account_calldata
+}
+
+/// Streams the status of the transaction every time it changes, until it's accepted on L1 or
+/// rejected.
+fn transaction_status_stream(
+    relay: Relay,
+    transaction_hash: FieldElement,
+) -> ReceiverStream<async_graphql::Result<FieldValue<'static>>> {
+    let (sender, receiver) = channel(1);
+
+    tokio::spawn(async move {
+        let mut last = None;
+        loop {
+            let status = transaction_status(&relay.provider, transaction_hash).await;
+            if last.as_ref() != Some(&status) {
+                let value = value_mapping(transaction_hash, &status);
+                if sender.send(Ok(FieldValue::owned_any(value))).await.is_err() {
+                    return;
+                }
+            }
+
+            if is_final(&status) || sender.is_closed() {
+                return;
+            }
+            last = Some(status);
+            tokio::time::sleep(Duration::from_millis(SUBSCRIPTION_POLL_INTERVAL_MS)).await;
+        }
+    });
+
+    ReceiverStream::new(receiver)
+}
+
+async fn transaction_status(
+    provider: &JsonRpcClient<HttpTransport>,
+    transaction_hash: FieldElement,
+) -> String {
+    let status = match provider.get_transaction_receipt(transaction_hash).await {
+        Ok(MaybePendingTransactionReceipt::Receipt(receipt)) => match receipt {
+            TransactionReceipt::Invoke(r) => r.status,
+            TransactionReceipt::L1Handler(r) => r.status,
+            TransactionReceipt::Declare(r) => r.status,
+            TransactionReceipt::Deploy(r) => r.status,
+            TransactionReceipt::DeployAccount(r) => r.status,
+        },
+        Ok(MaybePendingTransactionReceipt::PendingReceipt(_)) => return PENDING.to_string(),
+        // Not known by the node yet.
+        Err(_) => return RECEIVED.to_string(),
+    };
+
+    serde_json::to_value(status)
+        .ok()
+        .and_then(|status| status.as_str().map(str::to_string))
+        .unwrap_or_else(|| PENDING.to_string())
+}
+
+fn is_final(status: &str) -> bool {
+    matches!(status, "ACCEPTED_ON_L1" | "REJECTED")
+}
+
+fn felt_arg(ctx: &ResolverContext<'_>, name: &str) -> async_graphql::Result<FieldElement> {
+    Ok(FieldElement::from_hex_be(&remove_quotes(ctx.args.try_get(name)?.string()?))?)
+}
+
+fn felts_arg(ctx: &ResolverContext<'_>, name: &str) -> async_graphql::Result<Vec<FieldElement>> {
+    ctx.args
+        .try_get(name)?
+        .list()?
+        .iter()
+        .map(|felt| Ok(FieldElement::from_hex_be(&remove_quotes(felt.string()?))?))
+        .collect()
+}
+
+fn value_mapping(transaction_hash: FieldElement, status: &str) -> ValueMapping {
+    IndexMap::from([
+        (Name::new("transactionHash"), Value::from(format!("{transaction_hash:#x}"))),
+        (Name::new("status"), Value::from(status)),
+    ])
+}
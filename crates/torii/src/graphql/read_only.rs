@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_graphql::dynamic::Schema;
+use async_graphql_poem::GraphQLRequest;
+use poem::http::StatusCode;
+use poem::web::{Data, RemoteAddr};
+use poem::{handler, Response};
+
+use tokio::sync::watch;
+
+use crate::settings::RuntimeSettings;
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Shared state of the read-only endpoint: cached responses and per client request counts, limited
+/// by the latest settings.
+pub struct ReadOnlyState {
+    settings: watch::Receiver<RuntimeSettings>,
+    // request key -> (cached at, serialized response)
+    cache: Mutex<HashMap<String, (Instant, String)>>,
+    // client address -> (window start, requests in window)
+    requests: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl ReadOnlyState {
+    pub fn new(settings: watch::Receiver<RuntimeSettings>) -> Self {
+        Self { settings, cache: Mutex::default(), requests: Mutex::default() }
+    }
+
+    fn cache_ttl_secs(&self) -> u64 {
+        self.settings.borrow().cache_ttl_secs
+    }
+
+    fn allow(&self, client: String) -> bool {
+        let limit = self.settings.borrow().requests_per_minute;
+        let now = Instant::now();
+        let mut requests = self.requests.lock().unwrap();
+        requests.retain(|_, (start, _)| now.duration_since(*start) < RATE_LIMIT_WINDOW);
+
+        let (_, count) = requests.entry(client).or_insert((now, 0));
+        *count += 1;
+        *count <= limit
+    }
+
+    fn cached(&self, key: &str) -> Option<String> {
+        let ttl = Duration::from_secs(self.cache_ttl_secs());
+        let mut cache = self.cache.lock().unwrap();
+        cache.retain(|_, (cached_at, _)| cached_at.elapsed() < ttl);
+        cache.get(key).map(|(_, response)| response.clone())
+    }
+
+    fn cache(&self, key: String, response: String) {
+        self.cache.lock().unwrap().insert(key, (Instant::now(), response));
+    }
+}
+
+/// GraphQL endpoint for untrusted clients, rate limited per client address and serving identical
+/// queries from a short lived cache. The schema is the latest one built.
+///
+/// The client address is the IP of the connection, forwarding headers such as `X-Forwarded-For`
+/// aren't trusted since any client could set them.
+#[handler]
+pub async fn read_only_graphql(
+    schema: Data<&watch::Receiver<Schema>>,
+    state: Data<&Arc<ReadOnlyState>>,
+    remote_addr: &RemoteAddr,
+    req: GraphQLRequest,
+) -> Response {
+    let client = remote_addr
+        .0
+        .as_socket_addr()
+        .map_or_else(|| remote_addr.0.to_string(), |addr| addr.ip().to_string());
+    if !state.allow(client) {
+        return Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .body("Too many requests, try again later");
+    }
+
+    let request = req.0;
+    let key = format!(
+        "{}|{}|{}",
+        request.operation_name.as_deref().unwrap_or_default(),
+        request.query,
+        serde_json::to_string(&request.variables).unwrap_or_default()
+    );
+
+    let body = match state.cached(&key) {
+        Some(body) => body,
+        None => {
+            let schema = schema.borrow().clone();
+            let response = schema.execute(request).await;
+            let is_ok = response.is_ok();
+            let body = serde_json::to_string(&response).unwrap_or_default();
+            // errors may be transient, only successful responses are cached
+            if is_ok {
+                state.cache(key, body.clone());
+            }
+            body
+        }
+    };
+
+    Response::builder()
+        .content_type("application/json")
+        .header("Cache-Control", format!("public, max-age={}", state.cache_ttl_secs()))
+        .body(body)
+}
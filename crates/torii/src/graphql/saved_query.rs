@@ -0,0 +1,340 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail, Result};
+use async_graphql::dynamic::{Field, FieldFuture, FieldValue, TypeRef};
+use dojo_world::manifest::Member;
+use dojo_world::migration::schema::member_values;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::cairo_short_string_to_felt;
+use tokio::sync::watch;
+
+use super::object::storage::{value_mapping_from_values, StorageObject};
+use super::object::{EnumMapping, TypeMapping, ValueMapping};
+use crate::bootstrap::column_type;
+use crate::settings::RuntimeSettings;
+use crate::storage::sql::{component_rows, parse_value};
+
+/// A named query over the latest values of a component's entities, served as its own field of the
+/// `Query` root so that operators can tune and cache the few queries their game relies on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    /// Name of the field, such as `leaderboardTop100`.
+    pub name: String,
+    pub component: String,
+    #[serde(default)]
+    pub filters: Vec<Filter>,
+    /// Member the entities are sorted by, by latest write otherwise.
+    pub order_by: Option<String>,
+    #[serde(default)]
+    pub descending: bool,
+    /// Number of entities returned, capped by the `max_page_size` setting.
+    pub limit: Option<i64>,
+    /// Seconds the results are served from memory before querying the database again.
+    #[serde(default)]
+    pub cache_ttl_secs: u64,
+}
+
+/// Keeps the entities whose `member` compares to `value`. Enum members are compared to the name
+/// of a variant, booleans to `true` or `false`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Filter {
+    pub member: String,
+    pub op: FilterOp,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterOp {
+    Eq,
+    Neq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+}
+
+impl FilterOp {
+    fn sql(&self) -> &'static str {
+        match self {
+            FilterOp::Eq => "=",
+            FilterOp::Neq => "!=",
+            FilterOp::Gt => ">",
+            FilterOp::Gte => ">=",
+            FilterOp::Lt => "<",
+            FilterOp::Lte => "<=",
+        }
+    }
+}
+
+/// A filter checked against the members of its component, with its value parsed.
+#[derive(Debug, Clone)]
+struct Condition {
+    member: String,
+    op: FilterOp,
+    /// Raw storage values the member is compared to.
+    value: Vec<FieldElement>,
+}
+
+impl Condition {
+    fn matches(&self, values: &HashMap<String, Vec<FieldElement>>) -> bool {
+        let Some(member) = values.get(&self.member) else {
+            return false;
+        };
+        let ordering = compare(member, &self.value);
+        match self.op {
+            FilterOp::Eq => ordering == Ordering::Equal,
+            FilterOp::Neq => ordering != Ordering::Equal,
+            FilterOp::Gt => ordering == Ordering::Greater,
+            FilterOp::Gte => ordering != Ordering::Less,
+            FilterOp::Lt => ordering == Ordering::Less,
+            FilterOp::Lte => ordering != Ordering::Greater,
+        }
+    }
+}
+
+/// Orders raw storage values numerically. Only members stored in a single felt are ordered, the
+/// others are only checked for equality.
+fn compare(a: &[FieldElement], b: &[FieldElement]) -> Ordering {
+    let bytes =
+        |values: &[FieldElement]| values.iter().map(|v| v.to_bytes_be()).collect::<Vec<_>>();
+    bytes(a).cmp(&bytes(b))
+}
+
+impl SavedQuery {
+    /// Checks the query against the members of its component, returning its filters with their
+    /// values parsed. Only members stored as integers can be ordered, felts are only compared
+    /// for equality.
+    fn conditions(&self, members: &[Member]) -> Result<Vec<Condition>> {
+        if !is_graphql_name(&self.name) {
+            bail!("`{}` isn't a valid GraphQL field name", self.name);
+        }
+
+        let member = |name: &str| {
+            members
+                .iter()
+                .find(|member| member.name == name)
+                .ok_or_else(|| anyhow!("`{}` has no member `{name}`", self.component))
+        };
+
+        if let Some(order_by) = &self.order_by {
+            if column_type(member(order_by)?) != "INTEGER" {
+                bail!("`{order_by}` can't be ordered, only integer members can");
+            }
+        }
+
+        self.filters
+            .iter()
+            .map(|filter| {
+                let member = member(&filter.member)?;
+                let is_ordering = !matches!(filter.op, FilterOp::Eq | FilterOp::Neq);
+                let invalid = || anyhow!("`{}` isn't a valid `{}`", filter.value, member.ty);
+                let value = match (&member.variants, column_type(member)) {
+                    (Some(variants), _) => {
+                        let index = variants.iter().position(|variant| *variant == filter.value);
+                        index.map(FieldElement::from).ok_or_else(|| {
+                            anyhow!("`{}` isn't a variant of `{}`", filter.value, member.ty)
+                        })?
+                    }
+                    (None, "INTEGER") => match filter.value.as_str() {
+                        "true" => FieldElement::ONE,
+                        "false" => FieldElement::ZERO,
+                        value => {
+                            value.parse::<u64>().map(FieldElement::from).map_err(|_| invalid())?
+                        }
+                    },
+                    (None, _) if is_ordering => {
+                        bail!("`{}` can only be compared with `eq` or `neq`", member.name)
+                    }
+                    (None, _) => parse_value(&filter.value).map_err(|_| invalid())?,
+                };
+
+                // a u256 is stored as its low and high 128 bits
+                let value = if member.ty == "u256" {
+                    let bytes = value.to_bytes_be();
+                    let half = |bytes: &[u8]| {
+                        FieldElement::from(u128::from_be_bytes(bytes.try_into().unwrap()))
+                    };
+                    vec![half(&bytes[16..]), half(&bytes[..16])]
+                } else {
+                    vec![value]
+                };
+                Ok(Condition { member: filter.member.clone(), op: filter.op, value })
+            })
+            .collect()
+    }
+}
+
+fn is_graphql_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c == '_' || c.is_ascii_alphabetic())
+        && chars.all(|c| c == '_' || c.is_ascii_alphanumeric())
+}
+
+async fn component_members(pool: &Pool<Sqlite>, component: &str) -> Result<Vec<Member>> {
+    let definition: Option<(String,)> =
+        sqlx::query_as("SELECT storage_definition FROM components WHERE name = $1")
+            .bind(component)
+            .fetch_optional(pool)
+            .await?;
+    let (definition,) = definition.ok_or_else(|| anyhow!("Unknown component `{component}`"))?;
+    Ok(serde_json::from_str(&definition)?)
+}
+
+pub async fn saved_queries(pool: &Pool<Sqlite>) -> Result<Vec<SavedQuery>> {
+    let definitions: Vec<(String,)> =
+        sqlx::query_as("SELECT definition FROM saved_queries ORDER BY name")
+            .fetch_all(pool)
+            .await?;
+    definitions
+        .into_iter()
+        .map(|(definition,)| Ok(serde_json::from_str(&definition)?))
+        .collect()
+}
+
+/// Saves the query, replacing the one with the same name.
+pub async fn save_query(pool: &Pool<Sqlite>, query: &SavedQuery) -> Result<()> {
+    let members = component_members(pool, &query.component).await?;
+    query.conditions(&members)?;
+
+    sqlx::query(
+        "INSERT OR REPLACE INTO saved_queries (name, component_name, definition) VALUES ($1, $2, \
+         $3)",
+    )
+    .bind(&query.name)
+    .bind(&query.component)
+    .bind(serde_json::to_string(query)?)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Deletes the query, returning whether it existed.
+pub async fn delete_query(pool: &Pool<Sqlite>, name: &str) -> Result<bool> {
+    let deleted = sqlx::query("DELETE FROM saved_queries WHERE name = $1")
+        .bind(name)
+        .execute(pool)
+        .await?
+        .rows_affected()
+        > 0;
+
+    Ok(deleted)
+}
+
+/// Compiled saved query, with its cached results.
+struct Plan {
+    /// Component whose table is queried.
+    component: FieldElement,
+    members: Vec<Member>,
+    conditions: Vec<Condition>,
+    order_by: Option<String>,
+    descending: bool,
+    limit: Option<i64>,
+    ttl: Duration,
+    fields: TypeMapping,
+    deprecated_fields: TypeMapping,
+    enums: EnumMapping,
+    cache: Mutex<Option<(Instant, Vec<ValueMapping>)>>,
+}
+
+impl Plan {
+    fn cached(&self) -> Option<Vec<ValueMapping>> {
+        let cache = self.cache.lock().unwrap();
+        let (cached_at, values) = cache.as_ref()?;
+        (cached_at.elapsed() < self.ttl).then(|| values.clone())
+    }
+
+    /// Reads the entities from the component's table, members being packed in their raw storage
+    /// values the filters and the ordering are applied once they're decoded.
+    async fn fetch(
+        &self,
+        pool: &Pool<Sqlite>,
+        max_page_size: Option<i64>,
+    ) -> Result<Vec<ValueMapping>> {
+        let limit = match (self.limit, max_page_size) {
+            (Some(limit), Some(max)) => limit.min(max),
+            (limit, max) => limit.or(max).unwrap_or(i64::MAX),
+        };
+
+        let mut entities = vec![];
+        for row in component_rows(pool, self.component).await? {
+            let values = member_values(&self.members, &row.values)?;
+            if self.conditions.iter().all(|condition| condition.matches(&values)) {
+                entities.push(values);
+            }
+        }
+        // the sort is stable, entities with the same value stay by latest write
+        match &self.order_by {
+            Some(member) if self.descending => {
+                entities.sort_by(|a, b| compare(&b[member], &a[member]))
+            }
+            Some(member) => entities.sort_by(|a, b| compare(&a[member], &b[member])),
+            None if self.descending => entities.reverse(),
+            None => {}
+        }
+
+        let values = entities
+            .iter()
+            .take(usize::try_from(limit).unwrap_or_default())
+            .map(|values| {
+                value_mapping_from_values(
+                    values,
+                    &self.fields,
+                    &self.deprecated_fields,
+                    &self.enums,
+                )
+            })
+            .collect::<sqlx::Result<Vec<_>>>()?;
+
+        if !self.ttl.is_zero() {
+            *self.cache.lock().unwrap() = Some((Instant::now(), values.clone()));
+        }
+        Ok(values)
+    }
+}
+
+/// Field of the `Query` root serving the saved query, a list of the component's storage type.
+pub fn saved_query_field(
+    query: &SavedQuery,
+    members: &[Member],
+    storage: &StorageObject,
+) -> Result<Field> {
+    let plan = Arc::new(Plan {
+        component: cairo_short_string_to_felt(&query.component)?,
+        members: members.to_vec(),
+        conditions: query.conditions(members)?,
+        order_by: query.order_by.clone(),
+        descending: query.descending,
+        limit: query.limit,
+        ttl: Duration::from_secs(query.cache_ttl_secs),
+        fields: storage.field_type_mapping.clone(),
+        deprecated_fields: storage.deprecated_field_type_mapping.clone(),
+        enums: storage.enum_mapping.clone(),
+        cache: Mutex::default(),
+    });
+
+    let type_ref = TypeRef::named_nn_list_nn(&storage.type_name);
+    Ok(Field::new(&query.name, type_ref, move |ctx| {
+        let plan = plan.clone();
+        FieldFuture::new(async move {
+            let values = match plan.cached() {
+                Some(values) => values,
+                None => {
+                    let pool = ctx.data::<Pool<Sqlite>>()?;
+                    let max_page_size = ctx
+                        .data_opt::<watch::Receiver<RuntimeSettings>>()
+                        .map(|settings| settings.borrow().max_page_size);
+                    plan.fetch(pool, max_page_size).await?
+                }
+            };
+            Ok(Some(FieldValue::list(values.into_iter().map(FieldValue::owned_any))))
+        })
+    })
+    .description(format!("Saved query over the latest `{}` of the entities", query.component)))
+}
@@ -1,29 +1,67 @@
+use std::collections::HashSet;
+
 use anyhow::Result;
-use async_graphql::dynamic::{Object, Scalar, Schema};
+use async_graphql::dynamic::{Field, Object, Scalar, Schema, SchemaBuilder, Subscription, TypeRef};
+use dojo_world::manifest::Member;
+use indexmap::IndexMap;
 use sqlx::SqlitePool;
+use tracing::warn;
 
+use super::object::authorization::{AuthorizationLogObject, AuthorizationObject};
 use super::object::component::{Component, ComponentObject};
 use super::object::entity::EntityObject;
+use super::object::entity_state_update::EntityStateUpdateObject;
 use super::object::event::EventObject;
-use super::object::storage::{type_mapping_from_definition, StorageObject};
+use super::object::field_update::FieldUpdateObject;
+use super::object::storage::{
+    enum_mapping_from_definition, type_mapping_from_definition, StorageObject,
+};
 use super::object::system::SystemObject;
 use super::object::system_call::SystemCallObject;
+use super::object::system_metrics::SystemMetricsObject;
+use super::object::transaction::{Relay, TransactionObject};
 use super::object::ObjectTrait;
+use super::saved_query::{saved_queries, saved_query_field};
 use super::types::ScalarType;
 use super::utils::format_name;
 
-pub async fn build_schema(pool: &SqlitePool) -> Result<Schema> {
-    let mut schema_builder = Schema::build("Query", None, None);
+/// Prefix applied to component types whose names clash with the built-in schema types, when no
+/// namespace is configured.
+pub const DEFAULT_TYPE_PREFIX: &str = "World";
+
+const BUILTIN_SCALARS: [&str; 5] =
+    [TypeRef::STRING, TypeRef::INT, TypeRef::FLOAT, TypeRef::BOOLEAN, TypeRef::ID];
+
+/// Builds the GraphQL schema, `namespace` is prepended to every component's type and query name
+/// so multiple worlds can be served side by side without collisions.
+pub async fn build_schema(pool: &SqlitePool, namespace: Option<&str>) -> Result<Schema> {
+    schema_builder(pool, namespace, None).await?.finish().map_err(|e| e.into())
+}
+
+/// Same as [`build_schema`] but leaves the schema open for further configuration, such as query
+/// limits. With a `relay`, the `execute` mutation forwards signed transactions to its node.
+pub async fn schema_builder(
+    pool: &SqlitePool,
+    namespace: Option<&str>,
+    relay: Option<Relay>,
+) -> Result<SchemaBuilder> {
+    let mutation = relay.as_ref().map(|_| "Mutation");
+    let mut schema_builder = Schema::build("Query", mutation, Some("Subscription"));
 
     // static objects + dynamic objects (component and storage objects)
     let mut objects = static_objects();
-    objects.extend(dynamic_objects(pool).await?);
+    if relay.is_some() {
+        objects.push(Box::new(TransactionObject::new()));
+    }
+    let (dynamic_objects, saved_query_fields) = dynamic_objects(pool, &objects, namespace).await?;
+    objects.extend(dynamic_objects);
 
     // collect field resolvers
     let mut fields = Vec::new();
     for object in &objects {
         fields.extend(object.resolvers());
     }
+    fields.extend(saved_query_fields);
 
     // add field resolvers to query root
     let mut query_root = Object::new("Query");
@@ -31,12 +69,32 @@ pub async fn build_schema(pool: &SqlitePool) -> Result<Schema> {
         query_root = query_root.field(field);
     }
 
+    // add subscription fields to subscription root
+    let mut subscription_root = Subscription::new("Subscription");
+    for object in &objects {
+        for field in object.subscriptions().unwrap_or_default() {
+            subscription_root = subscription_root.field(field);
+        }
+    }
+
+    // add mutation fields to mutation root
+    if let Some(relay) = relay {
+        let mut mutation_root = Object::new("Mutation");
+        for object in &objects {
+            for field in object.mutations().unwrap_or_default() {
+                mutation_root = mutation_root.field(field);
+            }
+        }
+        schema_builder = schema_builder.register(mutation_root).data(relay);
+    }
+
     // register custom scalars
     for scalar_type in ScalarType::types().iter() {
         schema_builder = schema_builder.register(Scalar::new(*scalar_type));
     }
 
-    // register gql objects and union
+    // register gql objects, unions, input objects and enums
+    let mut enums = IndexMap::new();
     for object in &objects {
         schema_builder = schema_builder.register(object.object());
         if let Some(unions) = object.unions() {
@@ -44,9 +102,23 @@ pub async fn build_schema(pool: &SqlitePool) -> Result<Schema> {
                 schema_builder = schema_builder.register(union);
             }
         }
+        if let Some(input_objects) = object.input_objects() {
+            for input_object in input_objects {
+                schema_builder = schema_builder.register(input_object);
+            }
+        }
+        // components can share enum types, they are only registered once
+        if let Some(object_enums) = object.enums() {
+            for e in object_enums {
+                enums.entry(e.type_name().to_string()).or_insert(e);
+            }
+        }
+    }
+    for (_, e) in enums {
+        schema_builder = schema_builder.register(e);
     }
 
-    schema_builder.register(query_root).data(pool.clone()).finish().map_err(|e| e.into())
+    Ok(schema_builder.register(query_root).register(subscription_root).data(pool.clone()))
 }
 
 // predefined base objects
@@ -56,32 +128,142 @@ fn static_objects() -> Vec<Box<dyn ObjectTrait>> {
         Box::new(SystemObject::new()),
         Box::new(EventObject::new()),
         Box::new(SystemCallObject::new()),
+        Box::new(EntityStateUpdateObject::new()),
+        Box::new(FieldUpdateObject::new()),
+        Box::new(SystemMetricsObject::new()),
+        Box::new(AuthorizationObject::new()),
+        Box::new(AuthorizationLogObject::new()),
     ]
 }
 
-async fn dynamic_objects(pool: &SqlitePool) -> Result<Vec<Box<dyn ObjectTrait>>> {
+/// Component and storage objects, along with the fields of the saved queries over the storages.
+async fn dynamic_objects(
+    pool: &SqlitePool,
+    static_objects: &[Box<dyn ObjectTrait>],
+    namespace: Option<&str>,
+) -> Result<(Vec<Box<dyn ObjectTrait>>, Vec<Field>)> {
     let mut conn = pool.acquire().await?;
     let mut objects = Vec::new();
 
+    // names already taken by the static schema, the component object and the storage union
+    let mut reserved = reserved_names(static_objects);
+
     // storage objects
     let components: Vec<Component> =
         sqlx::query_as("SELECT * FROM components").fetch_all(&mut conn).await?;
+    let mut storage_types = IndexMap::new();
+    let mut storages = IndexMap::new();
     for component in components {
-        let storage_object = process_component(component)?;
-        objects.push(storage_object);
+        let (name, type_name) = namespaced_name(&component.name, namespace, &mut reserved);
+        storage_types.insert(component.name.clone(), type_name.clone());
+
+        let members: Vec<Member> = serde_json::from_str(&component.storage_definition)?;
+        let component_name = component.name.clone();
+        storages.insert(component_name, (members, process_component(component, name, type_name)?));
     }
 
-    let storage_names: Vec<String> = objects.iter().map(|obj| obj.name().to_string()).collect();
+    // saved queries, skipped rather than failing the schema when they no longer apply
+    let mut saved_query_fields = Vec::new();
+    for query in saved_queries(pool).await? {
+        let Some((members, storage)) = storages.get(&query.component) else {
+            warn!("Saved query `{}` skipped, `{}` isn't a component", query.name, query.component);
+            continue;
+        };
+        if !reserved.insert(query.name.clone()) {
+            warn!("Saved query `{}` skipped, the name is already taken", query.name);
+            continue;
+        }
+        match saved_query_field(&query, members, storage) {
+            Ok(field) => saved_query_fields.push(field),
+            Err(e) => warn!("Saved query `{}` skipped: {e:#}", query.name),
+        }
+    }
+
+    for (_, (_, storage)) in storages {
+        objects.push(Box::new(storage) as Box<dyn ObjectTrait>);
+    }
 
     // component object
-    let component = ComponentObject::new(storage_names);
+    let component = ComponentObject::new(storage_types);
     objects.push(Box::new(component));
 
-    Ok(objects)
+    Ok((objects, saved_query_fields))
 }
 
-fn process_component(component: Component) -> Result<Box<dyn ObjectTrait>> {
+fn process_component(
+    component: Component,
+    name: String,
+    type_name: String,
+) -> Result<StorageObject> {
     let field_type_mapping = type_mapping_from_definition(&component.storage_definition)?;
-    let (name, type_name) = format_name(component.name.as_str());
-    Ok(Box::new(StorageObject::new(name, type_name, field_type_mapping)))
+    let deprecated_mapping = type_mapping_from_definition(&component.deprecated_definition)?;
+
+    // removed members can still reference enums the current definition doesn't use anymore
+    let mut enum_mapping = enum_mapping_from_definition(&component.storage_definition)?;
+    for (name, variants) in enum_mapping_from_definition(&component.deprecated_definition)? {
+        enum_mapping.entry(name).or_insert(variants);
+    }
+
+    Ok(StorageObject::new(
+        name,
+        type_name,
+        component.name,
+        field_type_mapping,
+        deprecated_mapping,
+        enum_mapping,
+    ))
+}
+
+fn reserved_names(static_objects: &[Box<dyn ObjectTrait>]) -> HashSet<String> {
+    let mut reserved: HashSet<String> = ["Query", "Mutation", "Component", "component", "Storage"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    reserved.extend(ScalarType::types().iter().map(|s| s.to_string()));
+    reserved.extend(BUILTIN_SCALARS.iter().map(|s| s.to_string()));
+    for object in static_objects {
+        reserved.insert(object.name().to_string());
+        reserved.insert(object.type_name().to_string());
+    }
+    reserved
+}
+
+/// Returns the query and type names of a component, prefixed with the namespace if any. Without
+/// a namespace, components clashing with an existing name fall back to the default prefix, and a
+/// numeric suffix is added as a last resort so schema construction never fails on naming.
+fn namespaced_name(
+    component: &str,
+    namespace: Option<&str>,
+    reserved: &mut HashSet<String>,
+) -> (String, String) {
+    let prefixed = |prefix: &str| {
+        let (prefix_name, prefix_type_name) = format_name(prefix);
+        let (name, type_name) = format_name(component);
+        (format!("{prefix_name}{name}"), format!("{prefix_type_name}{type_name}"))
+    };
+
+    let (mut name, mut type_name) = match namespace {
+        Some(prefix) => prefixed(prefix),
+        None => format_name(component),
+    };
+
+    if namespace.is_none() && is_reserved(reserved, &name, &type_name) {
+        (name, type_name) = prefixed(DEFAULT_TYPE_PREFIX);
+    }
+
+    let (base_name, base_type_name) = (name.clone(), type_name.clone());
+    let mut suffix = 1;
+    while is_reserved(reserved, &name, &type_name) {
+        name = format!("{base_name}{suffix}");
+        type_name = format!("{base_type_name}{suffix}");
+        suffix += 1;
+    }
+
+    reserved.insert(name.clone());
+    reserved.insert(type_name.clone());
+    (name, type_name)
+}
+
+fn is_reserved(reserved: &HashSet<String>, name: &str, type_name: &str) -> bool {
+    reserved.contains(name) || reserved.contains(type_name)
 }
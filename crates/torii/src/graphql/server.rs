@@ -1,29 +1,264 @@
+use std::sync::Arc;
+
+use async_graphql::dynamic::Schema;
 use async_graphql::http::{playground_source, GraphQLPlaygroundConfig, GraphiQLSource};
-use async_graphql_poem::GraphQL;
+use async_graphql_poem::{GraphQLRequest, GraphQLResponse, GraphQLSubscription};
+use poem::http::header::AUTHORIZATION;
+use poem::http::StatusCode;
 use poem::listener::TcpListener;
-use poem::web::Html;
-use poem::{get, handler, IntoResponse, Route, Server};
+use poem::web::{Data, Html, Json, Path};
+use poem::{delete, get, handler, post, EndpointExt, IntoResponse, Request, Response, Route, Server};
 use sqlx::{Pool, Sqlite};
+use starknet::core::types::FieldElement;
+use tokio::sync::watch;
 
-use super::schema::build_schema;
+use super::constants::{READ_ONLY_MAX_COMPLEXITY, READ_ONLY_MAX_DEPTH};
+use super::cors::cors;
+use super::federation::{build_federated_schema, Federation};
+pub use super::object::transaction::Relay;
+use super::read_only::{read_only_graphql, ReadOnlyState};
+use super::saved_query::{delete_query, save_query, saved_queries, SavedQuery};
+use super::schema::schema_builder;
+use super::signing::{sign_responses, ResponseSigner};
+use crate::explorer::explorer_routes;
+use crate::settings::Settings;
 
 #[handler]
 async fn graphiql() -> impl IntoResponse {
-    Html(GraphiQLSource::build().endpoint("/query").finish())
+    Html(GraphiQLSource::build().endpoint("/query").subscription_endpoint("/ws").finish())
 }
 
 #[handler]
 async fn graphql_playground() -> impl IntoResponse {
-    Html(playground_source(GraphQLPlaygroundConfig::new("/playground")))
+    let config = GraphQLPlaygroundConfig::new("/playground").subscription_endpoint("/ws");
+    Html(playground_source(config))
+}
+
+/// Executes the query against the latest schema built.
+#[handler]
+async fn graphql(schema: Data<&watch::Receiver<Schema>>, req: GraphQLRequest) -> GraphQLResponse {
+    let schema = schema.borrow().clone();
+    schema.execute(req.0).await.into()
+}
+
+#[handler]
+async fn federated_graphql(schema: Data<&Schema>, req: GraphQLRequest) -> GraphQLResponse {
+    schema.execute(req.0).await.into()
+}
+
+/// Builds the schema of the query endpoints, again whenever the saved queries change.
+#[derive(Clone)]
+struct SchemaFactory {
+    pool: Pool<Sqlite>,
+    namespace: Option<String>,
+    read_only: bool,
+    relay: Option<Relay>,
+    settings: Arc<Settings>,
+}
+
+impl SchemaFactory {
+    async fn build(&self) -> anyhow::Result<Schema> {
+        let builder = schema_builder(&self.pool, self.namespace.as_deref(), self.relay.clone())
+            .await?
+            .data(self.settings.runtime());
+        let builder = if self.read_only {
+            builder.limit_depth(READ_ONLY_MAX_DEPTH).limit_complexity(READ_ONLY_MAX_COMPLEXITY)
+        } else {
+            builder
+        };
+        Ok(builder.finish()?)
+    }
+}
+
+/// Token the admin endpoints require as `Authorization: Bearer <token>`.
+#[derive(Clone)]
+struct AdminToken(String);
+
+impl AdminToken {
+    fn authorizes(&self, req: &Request) -> bool {
+        let expected = format!("Bearer {}", self.0);
+        let authorization = req.headers().get(AUTHORIZATION).and_then(|value| value.to_str().ok());
+        authorization == Some(expected.as_str())
+    }
+}
+
+/// Reloads the settings, answering with the error if they're invalid and were kept.
+#[handler]
+async fn reload_settings(
+    req: &Request,
+    token: Data<&AdminToken>,
+    settings: Data<&Arc<Settings>>,
+) -> Response {
+    if !token.authorizes(req) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match settings.reload() {
+        Ok(()) => "Settings reloaded".into_response(),
+        Err(e) => Response::builder().status(StatusCode::BAD_REQUEST).body(format!("{e:#}")),
+    }
+}
+
+#[handler]
+async fn list_saved_queries(
+    req: &Request,
+    token: Data<&AdminToken>,
+    pool: Data<&Pool<Sqlite>>,
+) -> Response {
+    if !token.authorizes(req) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match saved_queries(&pool).await {
+        Ok(queries) => Json(queries).into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+/// Saves the query, replacing the one with the same name, and serves it from the next query on.
+#[handler]
+async fn put_saved_query(
+    req: &Request,
+    token: Data<&AdminToken>,
+    factory: Data<&SchemaFactory>,
+    schema: Data<&Arc<watch::Sender<Schema>>>,
+    query: Json<SavedQuery>,
+) -> Response {
+    if !token.authorizes(req) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    if let Err(e) = save_query(&factory.pool, &query).await {
+        return error_response(StatusCode::BAD_REQUEST, e);
+    }
+    rebuild_schema(&factory, &schema, format!("Query `{}` saved", query.name)).await
+}
+
+#[handler]
+async fn delete_saved_query(
+    req: &Request,
+    token: Data<&AdminToken>,
+    factory: Data<&SchemaFactory>,
+    schema: Data<&Arc<watch::Sender<Schema>>>,
+    Path(name): Path<String>,
+) -> Response {
+    if !token.authorizes(req) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+
+    match delete_query(&factory.pool, &name).await {
+        Ok(true) => rebuild_schema(&factory, &schema, format!("Query `{name}` deleted")).await,
+        Ok(false) => StatusCode::NOT_FOUND.into_response(),
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
 }
 
-pub async fn start_graphql(pool: &Pool<Sqlite>) -> anyhow::Result<()> {
-    let schema = build_schema(pool).await?;
+async fn rebuild_schema(
+    factory: &SchemaFactory,
+    schema: &watch::Sender<Schema>,
+    message: String,
+) -> Response {
+    match factory.build().await {
+        Ok(rebuilt) => {
+            schema.send_replace(rebuilt);
+            message.into_response()
+        }
+        Err(e) => error_response(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+fn error_response(status: StatusCode, e: anyhow::Error) -> Response {
+    Response::builder().status(status).body(format!("{e:#}"))
+}
+
+pub async fn start_graphql(
+    pool: &Pool<Sqlite>,
+    namespace: Option<&str>,
+    read_only: bool,
+    relay: Option<Relay>,
+    settings: Arc<Settings>,
+    admin_token: Option<String>,
+    signing_key: Option<FieldElement>,
+    federation: Option<Federation>,
+) -> anyhow::Result<()> {
+    let factory = SchemaFactory {
+        pool: pool.clone(),
+        namespace: namespace.map(str::to_string),
+        read_only,
+        relay,
+        settings: settings.clone(),
+    };
+    let schema = factory.build().await?;
+    let (schema_sender, schema_receiver) = watch::channel(schema.clone());
+
+    let app = if read_only {
+        read_only_routes(schema_receiver, &settings)
+    } else {
+        // Subscriptions are served by the initial schema, saved queries aren't subscriptions.
+        Route::new()
+            .at("/query", get(graphiql).post(graphql).data(schema_receiver.clone()))
+            .at("/playground", get(graphql_playground).post(graphql).data(schema_receiver))
+            .at("/ws", get(GraphQLSubscription::new(schema)))
+            .nest("/explorer", explorer_routes(pool.clone()))
+    };
 
-    let app = Route::new()
-        .at("/query", get(graphiql).post(GraphQL::new(schema.clone())))
-        .at("/playground", get(graphql_playground).post(GraphQL::new(schema.clone())));
+    // Queries spanning the federated worlds are served next to the ones of the indexed world.
+    let app = match federation {
+        Some(federation) => {
+            let schema = build_federated_schema(federation, Some(settings.runtime()))?;
+            app.at("/federation", post(federated_graphql).data(schema.clone()))
+                .at("/federation/ws", get(GraphQLSubscription::new(schema)))
+        }
+        None => app,
+    };
+
+    // Without a token, or when serving read-only, the settings are only reloaded on SIGHUP and
+    // the saved queries can't be changed.
+    let app = match admin_token.filter(|_| !read_only) {
+        Some(token) => {
+            let token = AdminToken(token);
+            let schema_sender = Arc::new(schema_sender);
+            app.at(
+                "/admin/reload",
+                post(reload_settings).data(token.clone()).data(settings.clone()),
+            )
+            .at(
+                "/admin/queries",
+                get(list_saved_queries)
+                    .put(put_saved_query)
+                    .data(token.clone())
+                    .data(pool.clone())
+                    .data(factory.clone())
+                    .data(schema_sender.clone()),
+            )
+            .at(
+                "/admin/queries/:name",
+                delete(delete_saved_query).data(token).data(factory).data(schema_sender),
+            )
+        }
+        None => app,
+    };
+
+    // Signed before the CORS headers are added, they aren't covered by the signature.
+    let app = match signing_key {
+        Some(key) => {
+            let signer = Arc::new(ResponseSigner::new(key, pool.clone()));
+            app.around(move |endpoint, req| sign_responses(endpoint, req, signer.clone())).boxed()
+        }
+        None => app.boxed(),
+    };
+
+    let runtime = settings.runtime();
+    let app = app.around(move |endpoint, req| cors(endpoint, req, runtime.clone()));
     Server::new(TcpListener::bind("127.0.0.1:8080")).run(app).await?;
 
     Ok(())
 }
+
+/// Hardened server for untrusted clients: no playground, strict query limits, per client rate
+/// limiting and cached responses. The limits are set by the [`SchemaFactory`].
+fn read_only_routes(schema: watch::Receiver<Schema>, settings: &Settings) -> Route {
+    let state = Arc::new(ReadOnlyState::new(settings.runtime()));
+    Route::new()
+        .at("/query", get(read_only_graphql).post(read_only_graphql).data(schema).data(state))
+}
@@ -0,0 +1,120 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use poem::http::{HeaderValue, Method, StatusCode};
+use poem::{Body, Endpoint, IntoResponse, Request, Response};
+use sqlx::{Pool, Sqlite};
+use starknet::core::types::FieldElement;
+use starknet::core::utils::starknet_keccak;
+use starknet_crypto::{get_public_key, pedersen_hash, rfc6979_generate_k, sign};
+
+/// Signature of the response, as `<r>,<s>`.
+pub const SIGNATURE_HEADER: &str = "x-torii-signature";
+/// Public key of the operator signing the responses.
+pub const PUBLIC_KEY_HEADER: &str = "x-torii-public-key";
+/// Route whose responses are signed.
+pub const SIGNED_PATH: &str = "/query";
+/// Block the indexer had processed once the response was computed, covered by the signature.
+pub const BLOCK_HEADER: &str = "x-torii-block";
+
+/// Signs the responses with the operator's key, so that a client can hold the operator
+/// accountable for the values it was served at a given block: a signed response contradicting
+/// the chain's state at that block proves the indexer lied.
+///
+/// Storage proofs would let the clients check the values themselves, but `getStorageProof` isn't
+/// part of the RPC version the indexer targets.
+pub struct ResponseSigner {
+    private_key: FieldElement,
+    public_key: FieldElement,
+    pool: Pool<Sqlite>,
+}
+
+impl ResponseSigner {
+    pub fn new(private_key: FieldElement, pool: Pool<Sqlite>) -> Self {
+        Self { private_key, public_key: get_public_key(&private_key), pool }
+    }
+
+    pub fn public_key(&self) -> FieldElement {
+        self.public_key
+    }
+
+    /// Hash signed for a response:
+    /// `pedersen(pedersen(starknet_keccak(request), starknet_keccak(body)), block)`, the request
+    /// being the query and variables it answers, so that it can't be passed off as the answer
+    /// to another one.
+    pub fn message_hash(request: &[u8], body: &[u8], block: u64) -> FieldElement {
+        let exchange = pedersen_hash(&starknet_keccak(request), &starknet_keccak(body));
+        pedersen_hash(&exchange, &FieldElement::from(block))
+    }
+
+    /// Signs `body` as the answer to `request` served at `block`, returning `(r, s)`.
+    pub fn sign(
+        &self,
+        request: &[u8],
+        body: &[u8],
+        block: u64,
+    ) -> Result<(FieldElement, FieldElement)> {
+        let hash = Self::message_hash(request, body, block);
+        let k = rfc6979_generate_k(&hash, &self.private_key, None);
+        let signature = sign(&self.private_key, &hash, &k)
+            .map_err(|e| anyhow!("Failed to sign the response: {e}"))?;
+        Ok((signature.r, signature.s))
+    }
+
+    /// Block the indexer has processed up to.
+    pub async fn head(&self) -> Result<u64> {
+        let (head,): (i64,) =
+            sqlx::query_as("SELECT head FROM indexer WHERE id = 1").fetch_one(&self.pool).await?;
+        Ok(head.try_into()?)
+    }
+}
+
+/// Signs the successful responses of `endpoint` to the queries of [`SIGNED_PATH`], the other
+/// routes are passed through. Subscriptions aren't signed, their messages don't go through the
+/// middleware.
+///
+/// The head is read once the response is computed: the indexer commits the writes of a block
+/// along with its head, so the body never reflects a later state than the signed block.
+pub async fn sign_responses<E: Endpoint>(
+    endpoint: Arc<E>,
+    mut req: Request,
+    signer: Arc<ResponseSigner>,
+) -> poem::Result<Response> {
+    if req.uri().path() != SIGNED_PATH {
+        return Ok(endpoint.call(req).await?.into_response());
+    }
+
+    // The query and variables are in the body of a POST, in the query string of a GET.
+    let request = match req.uri().query() {
+        Some(query) if req.method() == Method::GET => query.as_bytes().to_vec(),
+        _ => {
+            let request = req.take_body().into_bytes().await?;
+            req.set_body(Body::from(request.clone()));
+            request.to_vec()
+        }
+    };
+
+    let mut response = endpoint.call(req).await?.into_response();
+    if !response.status().is_success() {
+        return Ok(response);
+    }
+
+    let body = response.take_body().into_bytes().await?;
+    let block = signer.head().await.map_err(internal_error)?;
+    let (r, s) = signer.sign(&request, &body, block).map_err(internal_error)?;
+    response.set_body(Body::from(body));
+
+    let headers = response.headers_mut();
+    for (name, value) in [
+        (SIGNATURE_HEADER, format!("{r:#x},{s:#x}")),
+        (PUBLIC_KEY_HEADER, format!("{:#x}", signer.public_key())),
+        (BLOCK_HEADER, block.to_string()),
+    ] {
+        headers.insert(name, HeaderValue::from_str(&value).expect("valid header value"));
+    }
+    Ok(response)
+}
+
+fn internal_error(e: anyhow::Error) -> poem::Error {
+    poem::Error::from_string(format!("{e:#}"), StatusCode::INTERNAL_SERVER_ERROR)
+}
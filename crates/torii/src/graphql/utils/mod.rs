@@ -1,6 +1,24 @@
+use async_graphql::dynamic::ResolverContext;
+use tokio::sync::watch;
+
+use crate::settings::RuntimeSettings;
+
 pub mod extract_value;
 pub mod value_accessor;
 
+/// The `limit` argument of a paginated query, `default` if absent, capped by the configured
+/// `max_page_size`.
+pub fn limit_arg(ctx: &ResolverContext<'_>, default: i64) -> async_graphql::Result<i64> {
+    let limit = match ctx.args.get("limit") {
+        Some(limit) => limit.i64()?,
+        None => default,
+    };
+    Ok(match ctx.data_opt::<watch::Receiver<RuntimeSettings>>() {
+        Some(settings) => limit.min(settings.borrow().max_page_size),
+        None => limit,
+    })
+}
+
 pub fn remove_quotes(s: &str) -> String {
     s.replace(&['\"', '\''][..], "")
 }
@@ -1,25 +1,50 @@
 use std::error::Error;
+use std::sync::Arc;
 
 use num::BigUint;
+use starknet::core::types::FieldElement;
 use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcTransport};
 use tokio_util::sync::CancellationToken;
 use tracing::info;
 
-use crate::engine::{Engine, Processors};
+use crate::engine::{Engine, EngineConfig, Processors};
+use crate::processors::authorization::AuthorizationProcessor;
+use crate::processors::custom::CustomProcessors;
 // use crate::processors::component_register::ComponentRegistrationProcessor;
 // use crate::processors::component_state_update::ComponentStateUpdateProcessor;
+use crate::processors::store_delete_record::StoreDeleteRecordProcessor;
+use crate::processors::store_set_record::StoreSetRecordProcessor;
+use crate::processors::system_metrics::SystemMetricsProcessor;
 // use crate::processors::system_register::SystemRegistrationProcessor;
 use crate::storage::Storage;
 
-pub async fn start_indexer<S: Storage, T: JsonRpcTransport + Sync + Send>(
+pub async fn start_indexer<S: Storage + Sync, T: JsonRpcTransport + Sync + Send>(
     _ct: CancellationToken,
-    _world: BigUint,
+    world: BigUint,
     storage: &S,
     provider: &JsonRpcClient<T>,
+    custom: CustomProcessors,
+    events_chunk_size: u64,
+    block_range: u64,
 ) -> Result<(), Box<dyn Error>> {
     info!("starting indexer");
 
-    let engine = Engine::new(storage, provider, Processors::default());
+    let world = FieldElement::from_byte_slice_be(&world.to_bytes_be())?;
+    let processors = Processors::new(
+        vec![],
+        vec![
+            Arc::new(SystemMetricsProcessor::new(world)),
+            Arc::new(AuthorizationProcessor::new(world)),
+        ],
+        vec![
+            Arc::new(StoreSetRecordProcessor::new(world)),
+            Arc::new(StoreDeleteRecordProcessor::new(world)),
+        ],
+    )
+    .with_custom(custom);
+
+    let config = EngineConfig { world_address: world, events_chunk_size, block_range };
+    let engine = Engine::new(storage, provider, processors, config);
     engine.start().await?;
 
     Ok(())
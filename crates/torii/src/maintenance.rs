@@ -0,0 +1,184 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use tokio::time::{interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+use crate::compression::{compress_payloads, Compression};
+
+/// Number of free pages reclaimed by each incremental vacuum pass.
+const DEFAULT_VACUUM_PAGES: u32 = 1000;
+
+/// Value of `PRAGMA auto_vacuum` for incremental vacuuming.
+const AUTO_VACUUM_INCREMENTAL: i64 = 2;
+
+/// Size in bytes from which the payloads are compressed.
+const DEFAULT_COMPRESSION_THRESHOLD: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub struct MaintenanceConfig {
+    /// Time between two maintenance runs.
+    pub interval: Duration,
+    /// Maximum number of free pages to reclaim per run.
+    pub vacuum_pages: u32,
+    /// Codec compressing the payloads of the component updates and the component rows written
+    /// since the last run.
+    pub compression: Compression,
+    /// Size in bytes from which the payloads are compressed.
+    pub compression_threshold: usize,
+}
+
+impl MaintenanceConfig {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            vacuum_pages: DEFAULT_VACUUM_PAGES,
+            compression: Compression::None,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+        }
+    }
+
+    pub fn with_vacuum_pages(mut self, pages: u32) -> Self {
+        self.vacuum_pages = pages;
+        self
+    }
+
+    pub fn with_compression(mut self, compression: Compression, threshold: usize) -> Self {
+        self.compression = compression;
+        self.compression_threshold = threshold;
+        self
+    }
+}
+
+/// Durations of the individual steps of the last maintenance run, along with running totals.
+/// Every successful run is also recorded in the `maintenance_runs` table.
+#[derive(Debug, Default, Clone)]
+pub struct MaintenanceMetrics {
+    pub runs: u64,
+    pub failures: u64,
+    pub checkpoint: Duration,
+    pub compression: Duration,
+    pub compressed_rows: u64,
+    pub vacuum: Duration,
+    /// Whether the last run rebuilt the database with a full `VACUUM`.
+    pub full_vacuum: bool,
+    pub reclaimed_pages: u64,
+    /// Free pages left after the last run.
+    pub free_pages: u64,
+    pub analyze: Duration,
+}
+
+/// Periodically checkpoints the WAL, compresses the large payloads, reclaims free pages and
+/// refreshes the query planner statistics until the token is cancelled.
+///
+/// Free pages are reclaimed incrementally, `vacuum_pages` at a time. A database not created with
+/// `PRAGMA auto_vacuum = INCREMENTAL` is rebuilt once with a full `VACUUM` instead, which
+/// switches it to incremental vacuuming.
+pub async fn start_maintenance(
+    ct: CancellationToken,
+    pool: Pool<Sqlite>,
+    config: MaintenanceConfig,
+) -> Result<()> {
+    info!("starting database maintenance every {}s", config.interval.as_secs());
+
+    let mut metrics = MaintenanceMetrics::default();
+    let mut ticker = interval(config.interval);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    // The first tick completes immediately, skip it so we don't run on startup.
+    ticker.tick().await;
+
+    loop {
+        tokio::select! {
+            _ = ct.cancelled() => return Ok(()),
+            _ = ticker.tick() => {}
+        }
+
+        metrics.runs += 1;
+        if let Err(e) = run_maintenance(&pool, &config, &mut metrics).await {
+            metrics.failures += 1;
+            error!("database maintenance failed: {e}");
+            continue;
+        }
+
+        info!(
+            runs = metrics.runs,
+            failures = metrics.failures,
+            checkpoint_ms = metrics.checkpoint.as_millis() as u64,
+            compressed_rows = metrics.compressed_rows,
+            compression_ms = metrics.compression.as_millis() as u64,
+            vacuum_ms = metrics.vacuum.as_millis() as u64,
+            full_vacuum = metrics.full_vacuum,
+            reclaimed_pages = metrics.reclaimed_pages,
+            free_pages = metrics.free_pages,
+            analyze_ms = metrics.analyze.as_millis() as u64,
+            "database maintenance completed"
+        );
+    }
+}
+
+pub async fn run_maintenance(
+    pool: &Pool<Sqlite>,
+    config: &MaintenanceConfig,
+    metrics: &mut MaintenanceMetrics,
+) -> Result<()> {
+    let mut conn = pool.acquire().await?;
+
+    let start = Instant::now();
+    sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(&mut conn).await?;
+    metrics.checkpoint = start.elapsed();
+
+    // Before vacuuming, so the pages freed by the compression are reclaimed.
+    let start = Instant::now();
+    let compressed_rows =
+        compress_payloads(pool, config.compression, config.compression_threshold).await?;
+    metrics.compressed_rows += compressed_rows;
+    metrics.compression = start.elapsed();
+
+    let start = Instant::now();
+    let free_pages = freelist_count(&mut conn).await?;
+    let auto_vacuum: i64 = sqlx::query_scalar("PRAGMA auto_vacuum").fetch_one(&mut conn).await?;
+    metrics.full_vacuum = auto_vacuum != AUTO_VACUUM_INCREMENTAL;
+    // Databases created without incremental vacuuming are rebuilt once, switching them to it.
+    if metrics.full_vacuum {
+        sqlx::query("PRAGMA auto_vacuum = INCREMENTAL").execute(&mut conn).await?;
+        sqlx::query("VACUUM").execute(&mut conn).await?;
+    } else {
+        sqlx::query(&format!("PRAGMA incremental_vacuum({})", config.vacuum_pages))
+            .execute(&mut conn)
+            .await?;
+    }
+    metrics.free_pages = freelist_count(&mut conn).await?;
+    let reclaimed_pages = free_pages.saturating_sub(metrics.free_pages);
+    metrics.reclaimed_pages += reclaimed_pages;
+    metrics.vacuum = start.elapsed();
+
+    let start = Instant::now();
+    sqlx::query("ANALYZE").execute(&mut conn).await?;
+    metrics.analyze = start.elapsed();
+
+    sqlx::query(
+        "INSERT INTO maintenance_runs (checkpoint_ms, compression_ms, compressed_rows, vacuum_ms, \
+         full_vacuum, reclaimed_pages, free_pages, analyze_ms) VALUES ($1, $2, $3, $4, $5, $6, \
+         $7, $8)",
+    )
+    .bind(metrics.checkpoint.as_millis() as i64)
+    .bind(metrics.compression.as_millis() as i64)
+    .bind(compressed_rows as i64)
+    .bind(metrics.vacuum.as_millis() as i64)
+    .bind(metrics.full_vacuum)
+    .bind(reclaimed_pages as i64)
+    .bind(metrics.free_pages as i64)
+    .bind(metrics.analyze.as_millis() as i64)
+    .execute(&mut conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn freelist_count(conn: &mut SqliteConnection) -> Result<u64> {
+    let count: i64 = sqlx::query_scalar("PRAGMA freelist_count").fetch_one(conn).await?;
+    Ok(count as u64)
+}
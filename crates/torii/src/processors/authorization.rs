@@ -0,0 +1,146 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use starknet::core::types::{
+    FieldElement, InvokeTransaction, Transaction, TransactionReceipt, TransactionStatus,
+};
+use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
+use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcTransport};
+
+use super::{multicall_calls, TransactionProcessor};
+use crate::storage::{AuthorizationChange, Storage};
+
+/// Tracks the world's authorizations from the auth systems executed through the world and the
+/// routes it's initialized with. The world doesn't emit events for them, so only the calls made
+/// directly by accounts are seen, not those made by other contracts.
+pub struct AuthorizationProcessor {
+    world: FieldElement,
+    execute_selector: FieldElement,
+    initialize_selector: FieldElement,
+}
+
+impl AuthorizationProcessor {
+    pub fn new(world: FieldElement) -> Self {
+        Self {
+            world,
+            execute_selector: get_selector_from_name("execute").unwrap(),
+            initialize_selector: get_selector_from_name("initialize").unwrap(),
+        }
+    }
+
+    /// Authorization changes made by the calls of an account's multicall, in order.
+    pub fn authorization_changes(&self, calldata: &[FieldElement]) -> Vec<AuthorizationChange> {
+        let mut changes = vec![];
+
+        for (to, selector, data) in multicall_calls(calldata) {
+            if to != self.world {
+                continue;
+            }
+
+            if selector == self.initialize_selector {
+                // routes_len, (target_id, role_id, resource_id)*
+                for route in data.get(1..).unwrap_or_default().chunks_exact(3) {
+                    changes.extend(route_changes(&route[0], &route[1], &route[2]));
+                }
+            } else if selector == self.execute_selector {
+                // system, calldata_len, calldata*
+                let (Some(system), Some(args)) = (data.first(), data.get(2..)) else {
+                    continue;
+                };
+                changes.extend(system_changes(&short_string(system), args));
+            }
+        }
+
+        changes
+    }
+}
+
+fn short_string(value: &FieldElement) -> String {
+    parse_cairo_short_string(value).unwrap_or_else(|_| format!("{value:#x}"))
+}
+
+/// A route grants a role scoped to a resource and allows the role to write the resource.
+fn route_changes(
+    target: &FieldElement,
+    role: &FieldElement,
+    resource: &FieldElement,
+) -> [AuthorizationChange; 2] {
+    [
+        AuthorizationChange::GrantRole {
+            target: short_string(target),
+            role: short_string(role),
+            resource: Some(short_string(resource)),
+        },
+        AuthorizationChange::GrantResource {
+            role: short_string(role),
+            resource: short_string(resource),
+        },
+    ]
+}
+
+/// Changes made by the auth systems of `dojo_core`, other systems don't make any.
+fn system_changes(system: &str, args: &[FieldElement]) -> Vec<AuthorizationChange> {
+    match (system, args) {
+        ("RouteAuth", [target, role, resource, ..]) => route_changes(target, role, resource).into(),
+        ("GrantAuthRole", [target, role, ..]) => vec![AuthorizationChange::GrantRole {
+            target: short_string(target),
+            role: short_string(role),
+            resource: None,
+        }],
+        ("GrantScopedAuthRole", [target, role, resource, ..]) => {
+            vec![AuthorizationChange::GrantRole {
+                target: short_string(target),
+                role: short_string(role),
+                resource: Some(short_string(resource)),
+            }]
+        }
+        ("GrantResource", [role, resource, ..]) => vec![AuthorizationChange::GrantResource {
+            role: short_string(role),
+            resource: short_string(resource),
+        }],
+        ("RevokeAuthRole", [target, ..]) => {
+            vec![AuthorizationChange::RevokeRole { target: short_string(target), resource: None }]
+        }
+        ("RevokeScopedAuthRole", [target, resource, ..]) => {
+            vec![AuthorizationChange::RevokeRole {
+                target: short_string(target),
+                resource: Some(short_string(resource)),
+            }]
+        }
+        _ => vec![],
+    }
+}
+
+#[async_trait]
+impl<S, T> TransactionProcessor<S, T> for AuthorizationProcessor
+where
+    S: Storage + Sync,
+    T: JsonRpcTransport + Sync + Send,
+{
+    fn get_transaction_hash(&self) -> String {
+        String::new()
+    }
+
+    async fn process(
+        &self,
+        storage: &S,
+        _provider: &JsonRpcClient<T>,
+        transaction: &Transaction,
+        transaction_receipt: &TransactionReceipt,
+    ) -> Result<(), Error> {
+        let Transaction::Invoke(InvokeTransaction::V1(transaction)) = transaction else {
+            return Ok(());
+        };
+        let TransactionReceipt::Invoke(receipt) = transaction_receipt else {
+            return Ok(());
+        };
+        if receipt.status == TransactionStatus::Rejected {
+            return Ok(());
+        }
+
+        for change in self.authorization_changes(&transaction.calldata) {
+            storage.apply_authorization(&change, transaction.transaction_hash).await?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,280 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use sqlx::{Executor, Pool, Sqlite};
+use starknet::core::types::{Event, FieldElement};
+use starknet::core::utils::get_selector_from_name;
+use tracing::error;
+
+use crate::storage::{AuthorizationChange, Storage};
+use crate::webhooks::{component_name, StateUpdate};
+
+/// Maintains state derived from the world's, e.g. a leaderboard or aggregates, within the
+/// indexing pass. Invoked with every indexed event and every entity update written to the
+/// storage.
+#[async_trait]
+pub trait Processor: Send + Sync {
+    fn name(&self) -> &str;
+
+    async fn process_event(&self, _pool: &Pool<Sqlite>, _event: &Event) -> Result<()> {
+        Ok(())
+    }
+
+    async fn process_state_update(
+        &self,
+        _pool: &Pool<Sqlite>,
+        _update: &StateUpdate,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// The processors registered on top of the built-in ones, cheap to clone. A failing processor
+/// is logged and doesn't hold back the indexing.
+#[derive(Clone)]
+pub struct CustomProcessors {
+    pool: Pool<Sqlite>,
+    processors: Vec<Arc<dyn Processor>>,
+}
+
+impl CustomProcessors {
+    pub fn new(pool: Pool<Sqlite>) -> Self {
+        Self { pool, processors: vec![] }
+    }
+
+    pub fn register(&mut self, processor: Arc<dyn Processor>) {
+        self.processors.push(processor);
+    }
+
+    pub async fn process_event(&self, event: &Event) {
+        for processor in &self.processors {
+            if let Err(e) = processor.process_event(&self.pool, event).await {
+                error!("processor {} failed on event: {e:#}", processor.name());
+            }
+        }
+    }
+
+    pub async fn process_state_update(&self, update: &StateUpdate) {
+        for processor in &self.processors {
+            if let Err(e) = processor.process_state_update(&self.pool, update).await {
+                error!("processor {} failed on state update: {e:#}", processor.name());
+            }
+        }
+    }
+}
+
+/// A processor made of SQL statements, loaded from the config instead of compiled in. Felts are
+/// bound as hex strings.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SqlProcessorConfig {
+    pub name: String,
+    /// Statements run once at startup, typically creating the derived tables.
+    #[serde(default)]
+    pub setup: Option<String>,
+    /// Component whose updates run `on_set` and `on_delete`.
+    #[serde(default)]
+    pub component: Option<String>,
+    /// Run when an entity of `component` is set, with the entity's key as `$1`, its partition as
+    /// `$2` and its values from `$3` on.
+    #[serde(default)]
+    pub on_set: Option<String>,
+    /// Run when an entity of `component` is deleted, with the entity's key as `$1` and its
+    /// partition as `$2`.
+    #[serde(default)]
+    pub on_delete: Option<String>,
+    /// Name of the event running `on_event`.
+    #[serde(default)]
+    pub event: Option<String>,
+    /// Run for every `event`, with the emitting contract as `$1` and the event's data from `$2`
+    /// on.
+    #[serde(default)]
+    pub on_event: Option<String>,
+}
+
+/// Loads the processors from a JSON file containing an array of [`SqlProcessorConfig`].
+pub fn load_processors(path: impl AsRef<Path>) -> Result<Vec<SqlProcessorConfig>> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+pub struct SqlProcessor {
+    config: SqlProcessorConfig,
+    event_selector: Option<FieldElement>,
+}
+
+impl SqlProcessor {
+    /// Creates the processor and runs its setup statements.
+    pub async fn new(pool: &Pool<Sqlite>, config: SqlProcessorConfig) -> Result<Self> {
+        let event_selector = match &config.event {
+            Some(event) => Some(get_selector_from_name(event)?),
+            None => None,
+        };
+
+        if let Some(setup) = &config.setup {
+            pool.execute(setup.as_str())
+                .await
+                .with_context(|| format!("Failed to set up processor {}", config.name))?;
+        }
+
+        Ok(Self { config, event_selector })
+    }
+}
+
+async fn execute_with(pool: &Pool<Sqlite>, sql: &str, felts: &[FieldElement]) -> Result<()> {
+    let mut query = sqlx::query(sql);
+    for felt in felts {
+        query = query.bind(format!("{felt:#x}"));
+    }
+    query.execute(pool).await?;
+    Ok(())
+}
+
+#[async_trait]
+impl Processor for SqlProcessor {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    async fn process_event(&self, pool: &Pool<Sqlite>, event: &Event) -> Result<()> {
+        let (Some(selector), Some(sql)) = (self.event_selector, &self.config.on_event) else {
+            return Ok(());
+        };
+        if event.keys.first() != Some(&selector) {
+            return Ok(());
+        }
+
+        let felts = [vec![event.from_address], event.data.clone()].concat();
+        execute_with(pool, sql, &felts).await
+    }
+
+    async fn process_state_update(&self, pool: &Pool<Sqlite>, update: &StateUpdate) -> Result<()> {
+        let (component, sql, felts) = match update {
+            StateUpdate::EntitySet { component, partition, key, values } => {
+                (component, &self.config.on_set, [vec![*key, *partition], values.clone()].concat())
+            }
+            StateUpdate::EntityDeleted { component, partition, key } => {
+                (component, &self.config.on_delete, vec![*key, *partition])
+            }
+        };
+
+        match sql {
+            Some(sql) if self.config.component.as_ref() == Some(component) => {
+                execute_with(pool, sql, &felts).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Storage running the custom processors on the entity updates written through it.
+pub struct ProcessingStorage<S: Storage> {
+    inner: S,
+    processors: CustomProcessors,
+}
+
+impl<S: Storage> ProcessingStorage<S> {
+    pub fn new(inner: S, processors: CustomProcessors) -> Self {
+        Self { inner, processors }
+    }
+}
+
+#[async_trait]
+impl<S: Storage + Send + Sync> Storage for ProcessingStorage<S> {
+    async fn head(&self) -> Result<u64> {
+        self.inner.head().await
+    }
+
+    async fn begin_block(&self) -> Result<()> {
+        self.inner.begin_block().await
+    }
+
+    async fn set_head(&self, head: u64) -> Result<()> {
+        self.inner.set_head(head).await
+    }
+
+    async fn begin_system_call(
+        &self,
+        transaction_hash: FieldElement,
+        system: Option<&str>,
+        calldata: &[FieldElement],
+    ) -> Result<()> {
+        self.inner.begin_system_call(transaction_hash, system, calldata).await
+    }
+
+    async fn create_component(&self, name: FieldElement, columns: Vec<FieldElement>) -> Result<()> {
+        self.inner.create_component(name, columns).await
+    }
+
+    async fn set_entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+        values: Vec<FieldElement>,
+    ) -> Result<()> {
+        self.inner.set_entity(component, partition, key, values.clone()).await?;
+        self.processors
+            .process_state_update(&StateUpdate::EntitySet {
+                component: component_name(component),
+                partition,
+                key,
+                values,
+            })
+            .await;
+        Ok(())
+    }
+
+    async fn delete_entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+    ) -> Result<()> {
+        self.inner.delete_entity(component, partition, key).await?;
+        self.processors
+            .process_state_update(&StateUpdate::EntityDeleted {
+                component: component_name(component),
+                partition,
+                key,
+            })
+            .await;
+        Ok(())
+    }
+
+    async fn entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+    ) -> Result<Vec<FieldElement>> {
+        self.inner.entity(component, partition, key).await
+    }
+
+    async fn entities(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+    ) -> Result<Vec<Vec<FieldElement>>> {
+        self.inner.entities(component, partition).await
+    }
+
+    async fn record_system_call(
+        &self,
+        system: &str,
+        fee: FieldElement,
+        failed: bool,
+    ) -> Result<()> {
+        self.inner.record_system_call(system, fee, failed).await
+    }
+
+    async fn apply_authorization(
+        &self,
+        change: &AuthorizationChange,
+        transaction_hash: FieldElement,
+    ) -> Result<()> {
+        self.inner.apply_authorization(change, transaction_hash).await
+    }
+}
@@ -1,13 +1,19 @@
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use starknet::core::types::{BlockWithTxs, Event, TransactionReceipt};
+use starknet::core::types::{BlockWithTxs, Event, FieldElement, Transaction, TransactionReceipt};
+use starknet::core::utils::{get_selector_from_name, parse_cairo_short_string};
 use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcTransport};
 
 use crate::storage::Storage;
 
+pub mod authorization;
 // pub mod component_register;
 // pub mod component_state_update;
+pub mod custom;
+pub mod store_delete_record;
+pub mod store_set_record;
 // pub mod system_register;
+pub mod system_metrics;
 
 #[async_trait]
 pub trait EventProcessor<S: Storage, T: JsonRpcTransport> {
@@ -38,6 +44,56 @@ pub trait TransactionProcessor<S: Storage, T: JsonRpcTransport> {
         &self,
         storage: &S,
         provider: &JsonRpcClient<T>,
+        transaction: &Transaction,
         transaction_receipt: &TransactionReceipt,
     ) -> Result<(), Error>;
 }
+
+/// A call of an account's multicall: the contract called, the selector and the call's data.
+pub type MulticallCall<'a> = (FieldElement, FieldElement, &'a [FieldElement]);
+
+/// Calls of an account's multicall, encoded as
+/// `call_array_len, (to, selector, data_offset, data_len)*, calldata_len, calldata*`. Calls whose
+/// data is out of bounds are skipped.
+pub fn multicall_calls(calldata: &[FieldElement]) -> Vec<MulticallCall<'_>> {
+    let to_usize = |value: &FieldElement| value.to_string().parse::<usize>().ok();
+
+    let Some(calls_len) = calldata.first().and_then(to_usize) else {
+        return vec![];
+    };
+    let Some(calls_end) = calls_len.checked_mul(4).and_then(|len| len.checked_add(1)) else {
+        return vec![];
+    };
+    let (Some(calls), Some(data)) = (calldata.get(1..calls_end), calldata.get(calls_end + 1..))
+    else {
+        return vec![];
+    };
+
+    calls
+        .chunks(4)
+        .filter_map(|call| {
+            let offset = to_usize(&call[2])?;
+            let end = offset.checked_add(to_usize(&call[3])?)?;
+            Some((call[0], call[1], data.get(offset..end)?))
+        })
+        .collect()
+}
+
+/// Systems executed through `world` by the calls of an account's multicall, with the data of
+/// their `execute` calls.
+pub fn executed_systems(
+    world: FieldElement,
+    calldata: &[FieldElement],
+) -> Vec<(String, &[FieldElement])> {
+    let execute = get_selector_from_name("execute").unwrap();
+
+    multicall_calls(calldata)
+        .into_iter()
+        .filter(|(to, selector, _)| *to == world && *selector == execute)
+        .filter_map(|(_, _, data)| {
+            let name = data.first()?;
+            let name = parse_cairo_short_string(name).unwrap_or_else(|_| format!("{name:#x}"));
+            Some((name, data))
+        })
+        .collect()
+}
@@ -0,0 +1,58 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use starknet::core::types::{Event, FieldElement};
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcTransport};
+
+use super::EventProcessor;
+use crate::check::entity_key;
+use crate::storage::Storage;
+
+/// Archives the entities deleted from the world, announced by `StoreDeleteRecord(table_id,
+/// keys)`. The world deletes them from the component's table regardless of the partition.
+pub struct StoreDeleteRecordProcessor {
+    world: FieldElement,
+    selector: FieldElement,
+}
+
+impl StoreDeleteRecordProcessor {
+    pub fn new(world: FieldElement) -> Self {
+        Self { world, selector: get_selector_from_name("StoreDeleteRecord").unwrap() }
+    }
+
+    /// Component and key of the entity deleted by `event`, if it's a deletion of the world.
+    pub fn deleted_entity(&self, event: &Event) -> Option<(FieldElement, FieldElement)> {
+        if event.from_address != self.world || event.keys.first() != Some(&self.selector) {
+            return None;
+        }
+
+        // table_id, keys_len, keys*
+        let (table, keys_len) = (event.data.first()?, event.data.get(1)?);
+        let keys_len = keys_len.to_string().parse::<usize>().ok()?;
+        let keys = event.data.get(2..keys_len.checked_add(2)?)?;
+        Some((*table, entity_key(FieldElement::ZERO, keys)))
+    }
+}
+
+#[async_trait]
+impl<S, T> EventProcessor<S, T> for StoreDeleteRecordProcessor
+where
+    S: Storage + Sync,
+    T: JsonRpcTransport + Sync + Send,
+{
+    fn event_key(&self) -> String {
+        "StoreDeleteRecord".to_string()
+    }
+
+    async fn process(
+        &self,
+        storage: &S,
+        _provider: &JsonRpcClient<T>,
+        event: &Event,
+    ) -> Result<(), Error> {
+        if let Some((component, key)) = self.deleted_entity(event) {
+            storage.delete_entity(component, FieldElement::ZERO, key).await?;
+        }
+        Ok(())
+    }
+}
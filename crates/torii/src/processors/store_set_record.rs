@@ -0,0 +1,64 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use starknet::core::types::{Event, FieldElement};
+use starknet::core::utils::get_selector_from_name;
+use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcTransport};
+
+use super::EventProcessor;
+use crate::check::entity_key;
+use crate::storage::Storage;
+
+/// Writes the entities set in the world, announced by `StoreSetRecord(table_id, keys, value)`.
+/// Like deletions, records are written to the component's table regardless of the partition.
+pub struct StoreSetRecordProcessor {
+    world: FieldElement,
+    selector: FieldElement,
+}
+
+impl StoreSetRecordProcessor {
+    pub fn new(world: FieldElement) -> Self {
+        Self { world, selector: get_selector_from_name("StoreSetRecord").unwrap() }
+    }
+
+    /// Component, key and values of the entity set by `event`, if it's a record of the world.
+    pub fn written_entity(
+        &self,
+        event: &Event,
+    ) -> Option<(FieldElement, FieldElement, Vec<FieldElement>)> {
+        if event.from_address != self.world || event.keys.first() != Some(&self.selector) {
+            return None;
+        }
+
+        // table_id, keys_len, keys*, value_len, value*
+        let to_usize = |value: &FieldElement| value.to_string().parse::<usize>().ok();
+        let table = event.data.first()?;
+        let keys_end = to_usize(event.data.get(1)?)?.checked_add(2)?;
+        let keys = event.data.get(2..keys_end)?;
+        let values_end = to_usize(event.data.get(keys_end)?)?.checked_add(keys_end + 1)?;
+        let values = event.data.get(keys_end + 1..values_end)?;
+        Some((*table, entity_key(FieldElement::ZERO, keys), values.to_vec()))
+    }
+}
+
+#[async_trait]
+impl<S, T> EventProcessor<S, T> for StoreSetRecordProcessor
+where
+    S: Storage + Sync,
+    T: JsonRpcTransport + Sync + Send,
+{
+    fn event_key(&self) -> String {
+        "StoreSetRecord".to_string()
+    }
+
+    async fn process(
+        &self,
+        storage: &S,
+        _provider: &JsonRpcClient<T>,
+        event: &Event,
+    ) -> Result<(), Error> {
+        if let Some((component, key, values)) = self.written_entity(event) {
+            storage.set_entity(component, FieldElement::ZERO, key, values).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+use anyhow::{Error, Result};
+use async_trait::async_trait;
+use starknet::core::types::{
+    FieldElement, InvokeTransaction, Transaction, TransactionReceipt, TransactionStatus,
+};
+use starknet::providers::jsonrpc::{JsonRpcClient, JsonRpcTransport};
+
+use super::{executed_systems, TransactionProcessor};
+use crate::storage::Storage;
+
+/// Aggregates the calls, failures and fees of the systems executed through the world.
+pub struct SystemMetricsProcessor {
+    world: FieldElement,
+}
+
+impl SystemMetricsProcessor {
+    pub fn new(world: FieldElement) -> Self {
+        Self { world }
+    }
+
+    /// Names of the systems executed by the calls of an account's multicall.
+    pub fn executed_systems(&self, calldata: &[FieldElement]) -> Vec<String> {
+        executed_systems(self.world, calldata).into_iter().map(|(name, _)| name).collect()
+    }
+}
+
+#[async_trait]
+impl<S, T> TransactionProcessor<S, T> for SystemMetricsProcessor
+where
+    S: Storage + Sync,
+    T: JsonRpcTransport + Sync + Send,
+{
+    fn get_transaction_hash(&self) -> String {
+        String::new()
+    }
+
+    async fn process(
+        &self,
+        storage: &S,
+        _provider: &JsonRpcClient<T>,
+        transaction: &Transaction,
+        transaction_receipt: &TransactionReceipt,
+    ) -> Result<(), Error> {
+        let Transaction::Invoke(InvokeTransaction::V1(transaction)) = transaction else {
+            return Ok(());
+        };
+        let TransactionReceipt::Invoke(receipt) = transaction_receipt else {
+            return Ok(());
+        };
+
+        let systems = self.executed_systems(&transaction.calldata);
+        if systems.is_empty() {
+            return Ok(());
+        }
+
+        // The fee of a multicall executing several systems is split evenly between them.
+        let fee = receipt.actual_fee.floor_div(FieldElement::from(systems.len()));
+        let failed = receipt.status == TransactionStatus::Rejected;
+        for system in systems {
+            storage.record_system_call(&system, fee, failed).await?;
+        }
+
+        Ok(())
+    }
+}
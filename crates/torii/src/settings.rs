@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info};
+
+use crate::graphql::constants::{
+    DEFAULT_MAX_PAGE_SIZE, READ_ONLY_CACHE_TTL_SECS, READ_ONLY_REQUESTS_PER_MINUTE,
+};
+use crate::webhooks::{load_webhooks, WebhookConfig};
+
+/// Settings of the GraphQL server that can be tuned on a live endpoint, without restarting the
+/// indexer.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RuntimeSettings {
+    /// Requests allowed per client and per minute in read-only mode.
+    pub requests_per_minute: u32,
+    /// Seconds the responses are cached for in read-only mode.
+    pub cache_ttl_secs: u64,
+    /// Origins allowed to query the server from a browser, `*` allowing any. No CORS headers are
+    /// sent when empty.
+    pub cors_origins: Vec<String>,
+    /// Cap on the `limit` of the paginated queries.
+    pub max_page_size: i64,
+}
+
+impl Default for RuntimeSettings {
+    fn default() -> Self {
+        Self {
+            requests_per_minute: READ_ONLY_REQUESTS_PER_MINUTE,
+            cache_ttl_secs: READ_ONLY_CACHE_TTL_SECS,
+            cors_origins: vec![],
+            max_page_size: DEFAULT_MAX_PAGE_SIZE,
+        }
+    }
+}
+
+impl RuntimeSettings {
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        self.cors_origins.iter().any(|allowed| allowed == "*" || allowed == origin)
+    }
+}
+
+/// The runtime settings and the webhooks, read from their files at startup and whenever they
+/// are reloaded. Consumers hold a [`watch::Receiver`] and read the latest value on every use.
+pub struct Settings {
+    config_path: Option<PathBuf>,
+    webhooks_path: Option<PathBuf>,
+    runtime: watch::Sender<RuntimeSettings>,
+    webhooks: watch::Sender<Vec<WebhookConfig>>,
+}
+
+impl Settings {
+    pub fn load(config_path: Option<PathBuf>, webhooks_path: Option<PathBuf>) -> Result<Self> {
+        let (runtime, _) = watch::channel(RuntimeSettings::default());
+        let (webhooks, _) = watch::channel(vec![]);
+        let settings = Self { config_path, webhooks_path, runtime, webhooks };
+        settings.reload()?;
+        Ok(settings)
+    }
+
+    pub fn runtime(&self) -> watch::Receiver<RuntimeSettings> {
+        self.runtime.subscribe()
+    }
+
+    pub fn webhooks(&self) -> watch::Receiver<Vec<WebhookConfig>> {
+        self.webhooks.subscribe()
+    }
+
+    /// Reads the files again. Nothing is applied if either of them is invalid, so a typo doesn't
+    /// take down a live endpoint.
+    pub fn reload(&self) -> Result<()> {
+        let runtime = match &self.config_path {
+            Some(path) => {
+                let file = std::fs::File::open(path)
+                    .with_context(|| format!("Failed to open {}", path.display()))?;
+                serde_json::from_reader(file)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?
+            }
+            None => RuntimeSettings::default(),
+        };
+        let webhooks = match &self.webhooks_path {
+            Some(path) => load_webhooks(path)
+                .with_context(|| format!("Failed to load webhooks from {}", path.display()))?,
+            None => vec![],
+        };
+
+        info!("loaded settings, {} webhooks", webhooks.len());
+        self.runtime.send_replace(runtime);
+        self.webhooks.send_replace(webhooks);
+        Ok(())
+    }
+}
+
+/// Reloads the settings on every SIGHUP until the token is cancelled.
+#[cfg(unix)]
+pub async fn reload_on_sighup(ct: CancellationToken, settings: Arc<Settings>) -> Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangups = signal(SignalKind::hangup())?;
+    loop {
+        tokio::select! {
+            _ = ct.cancelled() => return Ok(()),
+            _ = hangups.recv() => {
+                if let Err(e) = settings.reload() {
+                    error!("keeping the previous settings: {e:?}");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub async fn reload_on_sighup(_ct: CancellationToken, _settings: Arc<Settings>) -> Result<()> {
+    Ok(())
+}
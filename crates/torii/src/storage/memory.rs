@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use anyhow::Result;
@@ -6,7 +7,7 @@ use async_trait::async_trait;
 use starknet::core::types::FieldElement;
 use tokio::sync::RwLock;
 
-use super::Storage;
+use super::{AuthorizationChange, Storage};
 
 type Partition = FieldElement;
 type Component = FieldElement;
@@ -14,20 +15,38 @@ type Key = FieldElement;
 type Entities = HashMap<Partition, HashMap<Key, Vec<FieldElement>>>;
 type Components = HashMap<Component, Entities>;
 
+/// Calls, failures and total fee of a system.
+type SystemMetrics = (u64, u64, FieldElement);
+
 #[derive(Default)]
 pub struct MemoryStorage {
-    head: u64,
+    head: AtomicU64,
     data: Arc<RwLock<Components>>,
+    metrics: Arc<RwLock<HashMap<String, SystemMetrics>>>,
+    authorizations: Arc<RwLock<Vec<(AuthorizationChange, FieldElement)>>>,
 }
 
 #[async_trait]
 impl Storage for MemoryStorage {
     async fn head(&self) -> Result<u64> {
-        Ok(self.head)
+        Ok(self.head.load(Ordering::Relaxed))
+    }
+
+    async fn begin_block(&self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn set_head(&self, head: u64) -> Result<()> {
+        self.head.store(head, Ordering::Relaxed);
+        Ok(())
     }
 
-    async fn set_head(&mut self, head: u64) -> Result<()> {
-        self.head = head;
+    async fn begin_system_call(
+        &self,
+        _transaction_hash: FieldElement,
+        _system: Option<&str>,
+        _calldata: &[FieldElement],
+    ) -> Result<()> {
         Ok(())
     }
 
@@ -104,4 +123,28 @@ impl Storage for MemoryStorage {
         }
         Ok(result)
     }
+
+    async fn record_system_call(
+        &self,
+        system: &str,
+        fee: FieldElement,
+        failed: bool,
+    ) -> Result<()> {
+        let mut metrics = self.metrics.write().await;
+        let (calls, failures, total_fee) =
+            metrics.entry(system.to_string()).or_insert((0, 0, FieldElement::ZERO));
+        *calls += 1;
+        *failures += failed as u64;
+        *total_fee = *total_fee + fee;
+        Ok(())
+    }
+
+    async fn apply_authorization(
+        &self,
+        change: &AuthorizationChange,
+        transaction_hash: FieldElement,
+    ) -> Result<()> {
+        self.authorizations.write().await.push((change.clone(), transaction_hash));
+        Ok(())
+    }
 }
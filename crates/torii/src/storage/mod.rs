@@ -5,10 +5,34 @@ use starknet::core::types::FieldElement;
 pub mod memory;
 pub mod sql;
 
+/// Change of the world's authorizations, made through its auth systems. Targets are systems or
+/// accounts, resources are components.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthorizationChange {
+    /// Grants `role` to `target`, world-wide or scoped to `resource`.
+    GrantRole { target: String, role: String, resource: Option<String> },
+    /// Revokes the role of `target`, world-wide or scoped to `resource`.
+    RevokeRole { target: String, resource: Option<String> },
+    /// Allows the holders of `role` on `resource` to write it.
+    GrantResource { role: String, resource: String },
+}
+
 #[async_trait]
 pub trait Storage {
     async fn head(&self) -> Result<u64>;
-    async fn set_head(&mut self, head: u64) -> Result<()>;
+    /// Starts buffering the writes of a block, until it's committed by [`Storage::set_head`].
+    async fn begin_block(&self) -> Result<()>;
+    /// Advances the head to `head`, committing the writes of the current block along with it.
+    async fn set_head(&self, head: u64) -> Result<()>;
+    /// Starts a call of `system` made by `transaction_hash` with `calldata`, the entity updates
+    /// written until the next call being attributed to it. `system` is `None` when the
+    /// transaction doesn't execute a system through the world.
+    async fn begin_system_call(
+        &self,
+        transaction_hash: FieldElement,
+        system: Option<&str>,
+        calldata: &[FieldElement],
+    ) -> Result<()>;
     async fn create_component(&self, name: FieldElement, columns: Vec<FieldElement>) -> Result<()>;
     async fn set_entity(
         &self,
@@ -34,4 +58,17 @@ pub trait Storage {
         component: FieldElement,
         partition: FieldElement,
     ) -> Result<Vec<Vec<FieldElement>>>;
+    /// Accounts a call of `system` through the world in its metrics.
+    async fn record_system_call(
+        &self,
+        system: &str,
+        fee: FieldElement,
+        failed: bool,
+    ) -> Result<()>;
+    /// Applies a change of the world's authorizations made by `transaction_hash`.
+    async fn apply_authorization(
+        &self,
+        change: &AuthorizationChange,
+        transaction_hash: FieldElement,
+    ) -> Result<()>;
 }
@@ -1,18 +1,282 @@
-use anyhow::Result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use sqlx::pool::PoolConnection;
-use sqlx::{Pool, Sqlite};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Pool, Row, Sqlite, SqliteConnection, Transaction};
 use starknet::core::types::FieldElement;
 
-use super::Storage;
+use super::{AuthorizationChange, Storage};
+use crate::compression::{decompress, Compression};
+use crate::webhooks::component_name;
+
+/// A mutation of the indexed state, buffered until its block is committed.
+#[derive(Clone)]
+enum Write {
+    /// A statement without parameters, the tables of the components being named after them.
+    Statement(String),
+    SystemCall {
+        system: String,
+        fee: FieldElement,
+        failed: bool,
+    },
+    Authorization {
+        change: AuthorizationChange,
+        transaction_hash: FieldElement,
+    },
+    /// A call of a system, the state updates written after it being linked to it.
+    BeginSystemCall {
+        transaction_hash: FieldElement,
+        system: Option<String>,
+        calldata: Vec<FieldElement>,
+    },
+    /// An update of an entity's component, `values` is `None` when the component is deleted.
+    StateUpdate {
+        component: FieldElement,
+        key: FieldElement,
+        values: Option<Vec<FieldElement>>,
+    },
+}
+
+impl Write {
+    async fn apply(&self, conn: &mut SqliteConnection) -> Result<()> {
+        match self {
+            Write::Statement(query) => {
+                sqlx::query(query).execute(conn).await?;
+                Ok(())
+            }
+            Write::SystemCall { system, fee, failed } => {
+                write_system_call(conn, system, *fee, *failed).await
+            }
+            Write::Authorization { change, transaction_hash } => {
+                write_authorization(conn, change, *transaction_hash).await
+            }
+            Write::BeginSystemCall { transaction_hash, system, calldata } => {
+                sqlx::query(
+                    "INSERT INTO system_calls (system_id, transaction_hash, data) VALUES ($1, $2, \
+                     $3)",
+                )
+                .bind(system.as_deref().unwrap_or_default())
+                .bind(format!("{transaction_hash:#x}"))
+                .bind(join_values(calldata))
+                .execute(conn)
+                .await?;
+                Ok(())
+            }
+            Write::StateUpdate { component, key, values } => {
+                // the call is the latest one, writes being applied in order
+                sqlx::query(
+                    "INSERT INTO entity_state_updates (entity_id, component_id, system_call_id, \
+                     data, deleted) VALUES ($1, $2, (SELECT MAX(id) FROM system_calls), $3, $4)",
+                )
+                .bind(format!("{key:#x}"))
+                .bind(component_name(*component))
+                .bind(values.as_deref().map(join_values).unwrap_or_default())
+                .bind(values.is_none())
+                .execute(conn)
+                .await?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Name of the table holding the raw storage values of a component, one `column{n}` per value.
+/// Rows compressed during maintenance hold their values in `payload` instead, encoded with their
+/// `codec`.
+pub fn component_table(component: FieldElement) -> String {
+    // tables are named after the felt of the component, which isn't a valid bare identifier
+    format!("\"{component}\"")
+}
+
+/// Columns added to the component tables since their creation, with their types.
+const ADDED_COLUMNS: [(&str, &str); 3] =
+    [("archived_at", "DATETIME"), ("payload", "TEXT"), ("codec", "TEXT")];
+
+/// An entity of a component table, with its raw storage values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentRow {
+    pub key: String,
+    pub partition: String,
+    pub values: Vec<FieldElement>,
+}
+
+/// The entities of a component that aren't archived, by order of their latest write. Empty if
+/// the component's table isn't created yet.
+pub async fn component_rows(
+    pool: &Pool<Sqlite>,
+    component: FieldElement,
+) -> Result<Vec<ComponentRow>> {
+    let (exists,): (bool,) = sqlx::query_as(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = $1)",
+    )
+    .bind(component.to_string())
+    .fetch_one(pool)
+    .await?;
+    if !exists {
+        return Ok(vec![]);
+    }
+
+    // rows are replaced on every write, which moves them to the end of the table
+    let rows = sqlx::query(&format!(
+        "SELECT CAST(id AS TEXT) AS entity_key, CAST(partition AS TEXT) AS entity_partition, * \
+         FROM {} WHERE archived_at IS NULL ORDER BY rowid",
+        component_table(component)
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    rows.iter()
+        .map(|row| {
+            Ok(ComponentRow {
+                key: row.try_get("entity_key")?,
+                partition: row.try_get("entity_partition")?,
+                values: row_values(row)?,
+            })
+        })
+        .collect()
+}
+
+/// Raw storage values of a row of a component table, decompressed if the row was compressed.
+pub fn row_values(row: &SqliteRow) -> Result<Vec<FieldElement>> {
+    let mut values = vec![];
+    for column in 1.. {
+        let column = format!("column{column}");
+        let value: Option<String> = match row.try_get(column.as_str()) {
+            Ok(value) => value,
+            Err(sqlx::Error::ColumnNotFound(_)) => break,
+            Err(e) => return Err(e.into()),
+        };
+        // columns added by an upgrade are empty until the entity is written again
+        values.push(match value {
+            Some(value) => parse_value(&value)?,
+            None => FieldElement::ZERO,
+        });
+    }
+
+    let codec: Option<String> = match row.try_get("codec") {
+        Ok(codec) => codec,
+        Err(sqlx::Error::ColumnNotFound(_)) => None,
+        Err(e) => return Err(e.into()),
+    };
+    if codec.is_some() {
+        let payload: String = row.try_get("payload")?;
+        let payload = decompress(&payload, Compression::from_codec(codec.as_deref())?)?;
+        // the value columns were emptied by the compression
+        for (value, compressed) in values.iter_mut().zip(payload.split(',')) {
+            *value = parse_value(compressed)?;
+        }
+    }
+    Ok(values)
+}
+
+/// Names of the component tables, the tables with a `partition` column.
+pub async fn component_tables(pool: &Pool<Sqlite>) -> Result<Vec<String>> {
+    let tables: Vec<(String,)> = sqlx::query_as(
+        "SELECT m.name FROM sqlite_master AS m WHERE m.type = 'table' AND EXISTS (SELECT 1 FROM \
+         pragma_table_info(m.name) AS c WHERE c.name = 'partition')",
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(tables.into_iter().map(|(table,)| table).collect())
+}
+
+/// Values of an update or a call, as comma separated hex strings.
+pub(crate) fn join_values(values: &[FieldElement]) -> String {
+    values.iter().map(|value| format!("{value:#x}")).collect::<Vec<_>>().join(",")
+}
+
+/// Parses a raw value of a component table, written either in decimal or as a hex string.
+pub fn parse_value(value: &str) -> Result<FieldElement> {
+    let parsed = match value.strip_prefix("0x") {
+        Some(_) => FieldElement::from_hex_be(value),
+        None => FieldElement::from_dec_str(value),
+    };
+    parsed.with_context(|| format!("Invalid value `{value}`"))
+}
 
 pub struct SqlStorage {
     pool: Pool<Sqlite>,
+    /// Writes of the block being indexed, committed with the head. `None` outside of a block,
+    /// where writes are applied right away.
+    block: Mutex<Option<Vec<Write>>>,
+    /// Whether a system call was started, entity updates are only recorded as part of one.
+    in_system_call: AtomicBool,
 }
 
 impl SqlStorage {
     pub fn new(pool: Pool<Sqlite>) -> Result<Self> {
-        Ok(Self { pool })
+        Ok(Self { pool, block: Mutex::new(None), in_system_call: AtomicBool::new(false) })
+    }
+
+    /// Adds `archived_at`, `payload` and `codec` to the component tables created before entities
+    /// were archived and compressed. Their names are only known at runtime, so the SQL migrations
+    /// can't alter them.
+    pub async fn migrate_component_tables(&self) -> Result<()> {
+        for table in component_tables(&self.pool).await? {
+            for (column, ty) in ADDED_COLUMNS {
+                let (exists,): (bool,) = sqlx::query_as(
+                    "SELECT EXISTS (SELECT 1 FROM pragma_table_info($1) WHERE name = $2)",
+                )
+                .bind(&table)
+                .bind(column)
+                .fetch_one(&self.pool)
+                .await?;
+                if !exists {
+                    sqlx::query(&format!("ALTER TABLE \"{table}\" ADD COLUMN {column} {ty}"))
+                        .execute(&self.pool)
+                        .await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Buffers `write` if a block is being indexed, otherwise hands it back.
+    fn buffer(&self, write: Write) -> Option<Write> {
+        match self.block.lock().expect("block lock poisoned").as_mut() {
+            Some(writes) => {
+                writes.push(write);
+                None
+            }
+            None => Some(write),
+        }
+    }
+
+    /// Transaction to read the indexed state from, which sees the writes of the current block:
+    /// they're applied to it, and rolled back along with it once it's dropped.
+    async fn reader(&self) -> Result<Transaction<'_, Sqlite>> {
+        let writes = self.block.lock().expect("block lock poisoned").clone().unwrap_or_default();
+
+        let mut tx = self.pool.begin().await?;
+        for write in &writes {
+            write.apply(&mut tx).await?;
+        }
+        Ok(tx)
+    }
+
+    /// Records an update of the entity's component as part of the current system call, if any.
+    async fn record_state_update(
+        &self,
+        component: FieldElement,
+        key: FieldElement,
+        values: Option<Vec<FieldElement>>,
+    ) -> Result<()> {
+        if !self.in_system_call.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.write(Write::StateUpdate { component, key, values }).await
+    }
+
+    async fn write(&self, write: Write) -> Result<()> {
+        if let Some(write) = self.buffer(write) {
+            let mut tx = self.pool.begin().await?;
+            write.apply(&mut tx).await?;
+            tx.commit().await?;
+        }
+        Ok(())
     }
 }
 
@@ -26,26 +290,56 @@ impl Storage for SqlStorage {
         Ok(indexer.0.try_into().expect("doesnt fit in u64"))
     }
 
-    async fn set_head(&mut self, head: u64) -> Result<()> {
-        let mut conn: PoolConnection<Sqlite> = self.pool.acquire().await?;
-        sqlx::query(&format!("INSERT INTO indexer (head) WHERE id = {head}"))
-            .execute(&mut conn)
+    async fn begin_block(&self) -> Result<()> {
+        *self.block.lock().expect("block lock poisoned") = Some(vec![]);
+        self.in_system_call.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn set_head(&self, head: u64) -> Result<()> {
+        let writes = self.block.lock().expect("block lock poisoned").take().unwrap_or_default();
+
+        // A single transaction keeps the block and the head consistent on a crash, and syncs
+        // the database once per block rather than once per write.
+        let mut tx = self.pool.begin().await?;
+        for write in &writes {
+            write.apply(&mut tx).await?;
+        }
+        sqlx::query("UPDATE indexer SET head = $1 WHERE id = 1")
+            .bind(i64::try_from(head)?)
+            .execute(&mut tx)
             .await?;
+        tx.commit().await?;
+        self.in_system_call.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn begin_system_call(
+        &self,
+        transaction_hash: FieldElement,
+        system: Option<&str>,
+        calldata: &[FieldElement],
+    ) -> Result<()> {
+        self.write(Write::BeginSystemCall {
+            transaction_hash,
+            system: system.map(str::to_string),
+            calldata: calldata.to_vec(),
+        })
+        .await?;
+        self.in_system_call.store(true, Ordering::Relaxed);
         Ok(())
     }
 
     async fn create_component(&self, name: FieldElement, columns: Vec<FieldElement>) -> Result<()> {
         let mut query = format!(
             "CREATE TABLE IF NOT EXISTS {} (id SERIAL PRIMARY KEY, partition TEXT NOT NULL, ",
-            name
+            component_table(name)
         );
         for column in columns {
             query.push_str(&format!("{} TEXT, ", column));
         }
-        query.push_str(");");
-        let mut conn: PoolConnection<Sqlite> = self.pool.acquire().await?;
-        sqlx::query(&query).execute(&mut conn).await?;
-        Ok(())
+        query.push_str("archived_at DATETIME, payload TEXT, codec TEXT);");
+        self.write(Write::Statement(query)).await
     }
 
     async fn set_entity(
@@ -55,7 +349,19 @@ impl Storage for SqlStorage {
         key: FieldElement,
         values: Vec<FieldElement>,
     ) -> Result<()> {
-        let mut query = format!("INSERT INTO {} (id, partition", component);
+        // The table is created on the first write of the component, with a column per value.
+        let columns = (1..=values.len()).map(|i| format!("column{i} TEXT, ")).collect::<String>();
+        let create = format!(
+            "CREATE TABLE IF NOT EXISTS {} (id SERIAL PRIMARY KEY, partition TEXT NOT NULL, \
+             {columns}archived_at DATETIME, payload TEXT, codec TEXT)",
+            component_table(component)
+        );
+        self.write(Write::Statement(create)).await?;
+
+        // Replacing the row clears `archived_at` when the entity is spawned again, and its
+        // compressed payload.
+        let mut query =
+            format!("INSERT OR REPLACE INTO {} (id, partition", component_table(component));
         for i in 0..values.len() {
             query.push_str(&format!(", column{}", i + 1));
         }
@@ -64,9 +370,9 @@ impl Storage for SqlStorage {
             query.push_str(&format!(", {value}"));
         }
         query.push_str(");");
-        let mut conn = self.pool.acquire().await?;
-        sqlx::query(&query).execute(&mut conn).await?;
-        Ok(())
+        self.write(Write::Statement(query)).await?;
+
+        self.record_state_update(component, key, Some(values)).await
     }
 
     async fn delete_entity(
@@ -75,10 +381,15 @@ impl Storage for SqlStorage {
         partition: FieldElement,
         key: FieldElement,
     ) -> Result<()> {
-        let query = format!("DELETE FROM {component} WHERE id = {key} AND partition = {partition}");
-        let mut conn: PoolConnection<Sqlite> = self.pool.acquire().await?;
-        sqlx::query(&query).execute(&mut conn).await?;
-        Ok(())
+        // Rows are archived rather than deleted, the entity stays known with its last values.
+        let query = format!(
+            "UPDATE {} SET archived_at = CURRENT_TIMESTAMP WHERE id = {key} AND partition = \
+             {partition} AND archived_at IS NULL",
+            component_table(component)
+        );
+        self.write(Write::Statement(query)).await?;
+
+        self.record_state_update(component, key, None).await
     }
 
     async fn entity(
@@ -87,11 +398,14 @@ impl Storage for SqlStorage {
         partition: FieldElement,
         key: FieldElement,
     ) -> Result<Vec<FieldElement>> {
-        let query =
-            format!("SELECT * FROM {component} WHERE id = {key} AND partition = {partition}");
-        let mut conn: PoolConnection<Sqlite> = self.pool.acquire().await?;
-        let row: (i32, String, String) = sqlx::query_as(&query).fetch_one(&mut conn).await?;
-        Ok(serde_json::from_str(&row.2).unwrap())
+        let query = format!(
+            "SELECT * FROM {} WHERE id = {key} AND partition = {partition} AND archived_at IS \
+             NULL",
+            component_table(component)
+        );
+        let mut tx = self.reader().await?;
+        let row = sqlx::query(&query).fetch_one(&mut tx).await?;
+        row_values(&row)
     }
 
     async fn entities(
@@ -99,10 +413,124 @@ impl Storage for SqlStorage {
         component: FieldElement,
         partition: FieldElement,
     ) -> Result<Vec<Vec<FieldElement>>> {
-        let query = format!("SELECT * FROM {component} WHERE partition = {partition}");
-        let mut conn: PoolConnection<Sqlite> = self.pool.acquire().await?;
-        let mut rows =
-            sqlx::query_as::<_, (i32, String, String)>(&query).fetch_all(&mut conn).await?;
-        Ok(rows.drain(..).map(|row| serde_json::from_str(&row.2).unwrap()).collect())
+        let query = format!(
+            "SELECT * FROM {} WHERE partition = {partition} AND archived_at IS NULL",
+            component_table(component)
+        );
+        let mut tx = self.reader().await?;
+        let rows = sqlx::query(&query).fetch_all(&mut tx).await?;
+        rows.iter().map(row_values).collect()
     }
+
+    async fn record_system_call(
+        &self,
+        system: &str,
+        fee: FieldElement,
+        failed: bool,
+    ) -> Result<()> {
+        self.write(Write::SystemCall { system: system.to_string(), fee, failed }).await
+    }
+
+    async fn apply_authorization(
+        &self,
+        change: &AuthorizationChange,
+        transaction_hash: FieldElement,
+    ) -> Result<()> {
+        self.write(Write::Authorization { change: change.clone(), transaction_hash }).await
+    }
+}
+
+async fn write_system_call(
+    conn: &mut SqliteConnection,
+    system: &str,
+    fee: FieldElement,
+    failed: bool,
+) -> Result<()> {
+    // Fees are summed as field elements, they would overflow an integer column.
+    let total_fee: Option<(String,)> =
+        sqlx::query_as("SELECT total_fee FROM system_metrics WHERE system = $1")
+            .bind(system)
+            .fetch_optional(&mut *conn)
+            .await?;
+    let total_fee = match total_fee {
+        Some((total_fee,)) => FieldElement::from_hex_be(&total_fee)? + fee,
+        None => fee,
+    };
+
+    sqlx::query(
+        "INSERT INTO system_metrics (system, calls, failures, total_fee) VALUES ($1, 1, $2, $3) \
+         ON CONFLICT(system) DO UPDATE SET calls = calls + 1, failures = failures + \
+         excluded.failures, total_fee = excluded.total_fee, updated_at = CURRENT_TIMESTAMP",
+    )
+    .bind(system)
+    .bind(failed as i64)
+    .bind(format!("{total_fee:#x}"))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+async fn write_authorization(
+    conn: &mut SqliteConnection,
+    change: &AuthorizationChange,
+    transaction_hash: FieldElement,
+) -> Result<()> {
+    let transaction_hash = format!("{transaction_hash:#x}");
+
+    // World-wide roles are stored with an empty resource to be part of the primary key, the
+    // log uses empty strings for the columns an action doesn't have as well.
+    let (action, target, role, resource) = match change {
+        AuthorizationChange::GrantRole { target, role, resource } => {
+            let resource = resource.as_deref().unwrap_or_default();
+            sqlx::query(
+                "INSERT INTO auth_roles (target, resource, role, transaction_hash) VALUES ($1, \
+                 $2, $3, $4) ON CONFLICT(target, resource) DO UPDATE SET role = excluded.role, \
+                 transaction_hash = excluded.transaction_hash, updated_at = CURRENT_TIMESTAMP",
+            )
+            .bind(target)
+            .bind(resource)
+            .bind(role)
+            .bind(&transaction_hash)
+            .execute(&mut *conn)
+            .await?;
+            ("grant_role", target.as_str(), role.as_str(), resource)
+        }
+        AuthorizationChange::RevokeRole { target, resource } => {
+            let resource = resource.as_deref().unwrap_or_default();
+            sqlx::query("DELETE FROM auth_roles WHERE target = $1 AND resource = $2")
+                .bind(target)
+                .bind(resource)
+                .execute(&mut *conn)
+                .await?;
+            ("revoke_role", target.as_str(), "", resource)
+        }
+        AuthorizationChange::GrantResource { role, resource } => {
+            sqlx::query(
+                "INSERT INTO auth_resources (role, resource, transaction_hash) VALUES ($1, $2, \
+                 $3) ON CONFLICT(role, resource) DO UPDATE SET transaction_hash = \
+                 excluded.transaction_hash, updated_at = CURRENT_TIMESTAMP",
+            )
+            .bind(role)
+            .bind(resource)
+            .bind(&transaction_hash)
+            .execute(&mut *conn)
+            .await?;
+            ("grant_resource", "", role.as_str(), resource.as_str())
+        }
+    };
+
+    sqlx::query(
+        "INSERT INTO auth_log (action, target, role, resource, transaction_hash) VALUES ($1, $2, \
+         $3, $4, $5)",
+    )
+    .bind(action)
+    .bind(target)
+    .bind(role)
+    .bind(resource)
+    .bind(&transaction_hash)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
 }
@@ -0,0 +1,117 @@
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use sqlx::SqlitePool;
+    use starknet::core::types::{Event, FieldElement};
+    use starknet::core::utils::get_selector_from_name;
+
+    use crate::graphql::schema::build_schema;
+    use crate::processors::store_delete_record::StoreDeleteRecordProcessor;
+    use crate::storage::sql::SqlStorage;
+    use crate::tests::common::run_graphql_query;
+
+    #[derive(Deserialize)]
+    pub struct Entity {
+        pub id: String,
+        pub archived: bool,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EntityStateUpdate {
+        pub entity_id: String,
+        pub data: String,
+        pub deleted: bool,
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "archived_entities"))]
+    async fn test_archived_entity(pool: SqlitePool) {
+        let _ = pool.acquire().await;
+
+        let query = "{ entity(id: \"entity_1\") { id archived } }";
+        let value = run_graphql_query(&pool, query).await;
+        let entity: Entity = serde_json::from_value(value["entity"].clone()).unwrap();
+        assert!(!entity.archived);
+
+        // Archived entities are only found when asked for.
+        let schema = build_schema(&pool, None).await.unwrap();
+        let res = schema.execute("{ entity(id: \"entity_4\") { id archived } }").await;
+        assert!(!res.errors.is_empty());
+
+        let query = "{ entity(id: \"entity_4\", includeArchived: true) { id archived } }";
+        let value = run_graphql_query(&pool, query).await;
+        let entity: Entity = serde_json::from_value(value["entity"].clone()).unwrap();
+        assert_eq!(entity.id, "entity_4");
+        assert!(entity.archived);
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures(
+            "entities",
+            "components",
+            "systems",
+            "system_calls",
+            "entity_state_updates",
+            "archived_entities",
+            "entity_deletions"
+        )
+    )]
+    async fn test_deletion_updates(pool: SqlitePool) {
+        let _ = pool.acquire().await;
+
+        let query = "{ entityStateUpdatesByTransaction(transactionHash: \"0x1\") { entityId data \
+                     deleted } }";
+        let value = run_graphql_query(&pool, query).await;
+
+        let updates = value.get("entityStateUpdatesByTransaction").ok_or("no updates").unwrap();
+        let updates: Vec<EntityStateUpdate> = serde_json::from_value(updates.clone()).unwrap();
+        assert_eq!(updates.len(), 2);
+        assert!(!updates[0].deleted);
+        assert_eq!(updates[1].entity_id, "entity_4");
+        assert_eq!(updates[1].data, "");
+        assert!(updates[1].deleted);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_migrate_component_tables(pool: SqlitePool) {
+        sqlx::query("CREATE TABLE Position (id SERIAL PRIMARY KEY, partition TEXT NOT NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+        storage.migrate_component_tables().await.unwrap();
+        // Migrating twice leaves the tables as they are.
+        storage.migrate_component_tables().await.unwrap();
+
+        let columns: Vec<(String,)> =
+            sqlx::query_as("SELECT name FROM pragma_table_info('Position')")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        let columns: Vec<_> = columns.into_iter().map(|(name,)| name).collect();
+        assert_eq!(columns, ["id", "partition", "archived_at", "payload", "codec"]);
+    }
+
+    #[test]
+    fn test_deleted_entity() {
+        let world = FieldElement::from(0x77_u8);
+        let processor = StoreDeleteRecordProcessor::new(world);
+        let selector = get_selector_from_name("StoreDeleteRecord").unwrap();
+        let position = FieldElement::from(0x50_u8);
+        let key = FieldElement::from(3_u8);
+
+        let event = |from_address, keys, data| Event { from_address, keys, data };
+        let deleted = event(world, vec![selector], vec![position, FieldElement::ONE, key]);
+        assert_eq!(processor.deleted_entity(&deleted), Some((position, key)));
+
+        // Only the deletions of the world are followed, and malformed ones are ignored.
+        let other = event(FieldElement::ONE, vec![selector], deleted.data.clone());
+        assert_eq!(processor.deleted_entity(&other), None);
+        let set = event(world, vec![FieldElement::ONE], deleted.data.clone());
+        assert_eq!(processor.deleted_entity(&set), None);
+        let truncated = event(world, vec![selector], vec![position, FieldElement::TWO, key]);
+        assert_eq!(processor.deleted_entity(&truncated), None);
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+
+    use crate::processors::authorization::AuthorizationProcessor;
+    use crate::storage::sql::SqlStorage;
+    use crate::storage::{AuthorizationChange, Storage};
+    use crate::tests::common::run_graphql_query;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "camelCase")]
+    pub struct Authorization {
+        pub system: String,
+        pub component: String,
+        pub role: String,
+    }
+
+    fn authorization(system: &str, component: &str, role: &str) -> Authorization {
+        Authorization { system: system.into(), component: component.into(), role: role.into() }
+    }
+
+    async fn authorizations(pool: &SqlitePool, arguments: &str) -> Vec<Authorization> {
+        let query = format!("{{ authorizations{arguments} {{ system component role }} }}");
+        let value = run_graphql_query(pool, &query).await;
+        let authorizations = value.get("authorizations").ok_or("no authorizations").unwrap();
+        serde_json::from_value(authorizations.clone()).unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_authorizations(pool: SqlitePool) {
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+        let route = |target: &str, role: &str, resource: &str| {
+            [
+                AuthorizationChange::GrantRole {
+                    target: target.into(),
+                    role: role.into(),
+                    resource: Some(resource.into()),
+                },
+                AuthorizationChange::GrantResource { role: role.into(), resource: resource.into() },
+            ]
+        };
+        let changes = [
+            route("Spawn", "Writer", "Position"),
+            route("Spawn", "Writer", "Moves"),
+            route("Move", "Mover", "Position"),
+        ]
+        .concat();
+        for change in &changes {
+            storage.apply_authorization(change, FieldElement::ONE).await.unwrap();
+        }
+        let admin = AuthorizationChange::GrantRole {
+            target: "Deployer".into(),
+            role: "Admin".into(),
+            resource: None,
+        };
+        storage.apply_authorization(&admin, FieldElement::TWO).await.unwrap();
+
+        assert_eq!(authorizations(&pool, "").await.len(), 4);
+        assert_eq!(
+            authorizations(&pool, "(component: \"Position\")").await,
+            vec![
+                authorization("Deployer", "*", "Admin"),
+                authorization("Move", "Position", "Mover"),
+                authorization("Spawn", "Position", "Writer"),
+            ]
+        );
+
+        let revoke = AuthorizationChange::RevokeRole {
+            target: "Spawn".into(),
+            resource: Some("Moves".into()),
+        };
+        storage.apply_authorization(&revoke, FieldElement::THREE).await.unwrap();
+        assert_eq!(
+            authorizations(&pool, "(system: \"Spawn\")").await,
+            vec![authorization("Spawn", "Position", "Writer")]
+        );
+
+        let query =
+            "{ authorizationLog(limit: 2) { action target role resource transactionHash } }";
+        let value = run_graphql_query(&pool, query).await;
+        let log = value.get("authorizationLog").ok_or("no log").unwrap().as_array().unwrap();
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0]["action"], "revoke_role");
+        assert_eq!(log[0]["resource"], "Moves");
+        assert_eq!(log[0]["transactionHash"], "0x3");
+        assert_eq!(log[1]["action"], "grant_role");
+        assert_eq!(log[1]["role"], "Admin");
+    }
+
+    #[test]
+    fn test_authorization_changes() {
+        let world = FieldElement::from(0x420_u64);
+        let execute = get_selector_from_name("execute").unwrap();
+        let felt = |name| cairo_short_string_to_felt(name).unwrap();
+
+        // execute GrantAuthRole(Deployer, Admin), execute Spawn, execute RevokeAuthRole(Deployer)
+        let calldata = [
+            vec![FieldElement::THREE],
+            vec![world, execute, FieldElement::ZERO, FieldElement::from(4_u8)],
+            vec![world, execute, FieldElement::from(4_u8), FieldElement::from(3_u8)],
+            vec![world, execute, FieldElement::from(7_u8), FieldElement::THREE],
+            vec![FieldElement::from(10_u8)],
+            vec![felt("GrantAuthRole"), FieldElement::TWO, felt("Deployer"), felt("Admin")],
+            vec![felt("Spawn"), FieldElement::ONE, FieldElement::ONE],
+            vec![felt("RevokeAuthRole"), FieldElement::ONE, felt("Deployer")],
+        ]
+        .concat();
+
+        let processor = AuthorizationProcessor::new(world);
+        assert_eq!(
+            processor.authorization_changes(&calldata),
+            vec![
+                AuthorizationChange::GrantRole {
+                    target: "Deployer".into(),
+                    role: "Admin".into(),
+                    resource: None,
+                },
+                AuthorizationChange::RevokeRole { target: "Deployer".into(), resource: None },
+            ]
+        );
+    }
+}
@@ -0,0 +1,209 @@
+#[cfg(test)]
+mod tests {
+    use dojo_world::manifest::{Component, Manifest, Member, System};
+    use dojo_world::migration::schema::{MemberSource, SchemaUpgrade};
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+
+    use crate::bootstrap::bootstrap_from_manifest;
+    use crate::storage::sql::component_table;
+    use crate::tests::common::{run_graphql_query, write_component_entities};
+
+    fn manifest() -> Manifest {
+        Manifest {
+            components: vec![Component {
+                name: "Position".into(),
+                members: vec![
+                    Member {
+                        name: "x".into(),
+                        ty: "u32".into(),
+                        slot: 0,
+                        offset: 0,
+                        bits: None,
+                        variants: None,
+                        doc: None,
+                    },
+                    Member {
+                        name: "y".into(),
+                        ty: "u32".into(),
+                        slot: 1,
+                        offset: 0,
+                        bits: None,
+                        variants: None,
+                        doc: None,
+                    },
+                ],
+                class_hash: FieldElement::from(1_u8),
+                package: None,
+                upgrade: None,
+                doc: None,
+            }],
+            systems: vec![System {
+                name: "MoveSystem".into(),
+                class_hash: FieldElement::from(2_u8),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_bootstrap_from_manifest(pool: SqlitePool) {
+        let manifest = manifest();
+        bootstrap_from_manifest(&pool, &manifest).await.unwrap();
+        // bootstrapping is idempotent
+        bootstrap_from_manifest(&pool, &manifest).await.unwrap();
+
+        let query = r#"
+                {
+                    component(id: "Position") {
+                        name
+                        classHash
+                    }
+                    __type(name: "Position") {
+                        fields {
+                            name
+                        }
+                    }
+                }
+            "#;
+        let value = run_graphql_query(&pool, query).await;
+
+        assert_eq!(value["component"]["name"], "Position");
+        assert_eq!(value["component"]["classHash"], "0x1");
+        assert_eq!(value["__type"]["fields"].as_array().unwrap().len(), 2);
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities"))]
+    async fn test_bootstrap_component_upgrade(pool: SqlitePool) {
+        let mut manifest = manifest();
+        bootstrap_from_manifest(&pool, &manifest).await.unwrap();
+
+        sqlx::query(
+            "INSERT INTO storage_position (x, y, version, entity_id, component_id) VALUES (1, 2, \
+             '0.0.0', 'entity_1', 'Position')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // `y` is replaced by `z`
+        let component = &mut manifest.components[0];
+        component.class_hash = FieldElement::from(3_u8);
+        component.members[1] = Member {
+            name: "z".into(),
+            ty: "u32".into(),
+            slot: 1,
+            offset: 0,
+            bits: None,
+            variants: None,
+            doc: None,
+        };
+        bootstrap_from_manifest(&pool, &manifest).await.unwrap();
+
+        let query = r#"
+                {
+                    position(id: 1) {
+                        x
+                        y
+                        z
+                    }
+                    component(id: "Position") {
+                        classHash
+                    }
+                    __type(name: "Position") {
+                        fields(includeDeprecated: true) {
+                            name
+                            isDeprecated
+                        }
+                    }
+                }
+            "#;
+        let value = run_graphql_query(&pool, query).await;
+
+        assert_eq!(value["position"]["x"], 1);
+        assert_eq!(value["position"]["y"], 2);
+        assert_eq!(value["position"]["z"], 0);
+        assert_eq!(value["component"]["classHash"], "0x3");
+
+        let fields = value["__type"]["fields"].as_array().unwrap();
+        let deprecated = fields
+            .iter()
+            .filter(|f| f["isDeprecated"] == true)
+            .map(|f| f["name"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(fields.len(), 3);
+        assert_eq!(deprecated, ["y"]);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_bootstrap_component_upgrade_transform(pool: SqlitePool) {
+        let mut manifest = manifest();
+        bootstrap_from_manifest(&pool, &manifest).await.unwrap();
+
+        let position = write_component_entities(&pool, "Position", 2, &[(1, vec![1, 2])]).await;
+        let table = component_table(position);
+
+        // `y` is renamed to `z`, `x` is reset to 5
+        let component = &mut manifest.components[0];
+        component.class_hash = FieldElement::from(3_u8);
+        component.members[1] = Member {
+            name: "z".into(),
+            ty: "u32".into(),
+            slot: 1,
+            offset: 0,
+            bits: None,
+            variants: None,
+            doc: None,
+        };
+        component.upgrade = Some(SchemaUpgrade {
+            system: None,
+            partitions: vec![],
+            members: [
+                ("x".to_string(), MemberSource::Value(FieldElement::from(5_u8))),
+                ("z".to_string(), MemberSource::Member("y".into())),
+            ]
+            .into_iter()
+            .collect(),
+        });
+        bootstrap_from_manifest(&pool, &manifest).await.unwrap();
+
+        let row: (String, String) =
+            sqlx::query_as(&format!("SELECT column1, column2 FROM {table}"))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(row, ("0x5".to_string(), "0x2".to_string()));
+
+        // `x` and `z` are packed into a single slot
+        let component = &mut manifest.components[0];
+        component.class_hash = FieldElement::from(4_u8);
+        component.members[1].slot = 0;
+        component.members[1].offset = 32;
+        component.upgrade = None;
+        bootstrap_from_manifest(&pool, &manifest).await.unwrap();
+
+        let row: (String,) =
+            sqlx::query_as(&format!("SELECT column1 FROM {table}")).fetch_one(&pool).await.unwrap();
+        assert_eq!(row.0, format!("{:#x}", 5_u64 + (2_u64 << 32)));
+
+        // an upgrade sourced from an unknown member is rejected
+        let component = &mut manifest.components[0];
+        component.class_hash = FieldElement::from(5_u8);
+        component.members[0] = Member {
+            name: "w".into(),
+            ty: "u32".into(),
+            slot: 0,
+            offset: 0,
+            bits: None,
+            variants: None,
+            doc: None,
+        };
+        component.upgrade = Some(SchemaUpgrade {
+            system: None,
+            partitions: vec![],
+            members: [("w".to_string(), MemberSource::Member("v".into()))].into_iter().collect(),
+        });
+        assert!(bootstrap_from_manifest(&pool, &manifest).await.is_err());
+    }
+}
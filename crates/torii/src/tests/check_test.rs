@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use async_trait::async_trait;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::json;
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+    use starknet::providers::jsonrpc::{
+        JsonRpcClient, JsonRpcMethod, JsonRpcResponse, JsonRpcTransport,
+    };
+
+    use crate::check::{check_consistency, entity_key, storage_base, table_id};
+    use crate::tests::common::write_component_entities;
+
+    #[test]
+    fn test_unpartitioned_single_key() {
+        let component = FieldElement::from(0x47616d65_u64);
+        let key = FieldElement::from(69_u8);
+
+        assert_eq!(table_id(component, FieldElement::ZERO), component);
+        assert_eq!(entity_key(FieldElement::ZERO, &[key]), key);
+        assert_ne!(table_id(component, FieldElement::ONE), component);
+        assert_ne!(entity_key(FieldElement::ONE, &[key]), key);
+    }
+
+    #[test]
+    fn test_storage_base_is_a_valid_address() {
+        let bound = FieldElement::from_hex_be(
+            "0x7ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff00",
+        )
+        .unwrap();
+
+        for key in 0..32_u8 {
+            assert!(storage_base(FieldElement::ONE, FieldElement::from(key)) < bound);
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("stats"))]
+    async fn test_check_indexed_values(pool: SqlitePool) {
+        let stats = write_component_entities(&pool, "Stats", 2, &[(1, vec![10, 20])]).await;
+
+        // the world's storage holds a different mana
+        let base = storage_base(stats, FieldElement::ONE);
+        let storage = HashMap::from([
+            (base, FieldElement::from(10_u8)),
+            (base + FieldElement::ONE, FieldElement::from(21_u8)),
+        ]);
+        let provider = JsonRpcClient::new(StorageTransport(storage));
+
+        let report = check_consistency(&pool, &provider, FieldElement::ONE, 10).await.unwrap();
+
+        assert_eq!(report.entities, 1);
+        assert_eq!(report.values, 2);
+        assert_eq!(report.skipped, 0);
+        assert_eq!(report.divergences.len(), 1);
+        let divergence = &report.divergences[0];
+        assert_eq!(divergence.component, "Stats");
+        assert_eq!(divergence.entity_id, "0x1");
+        assert_eq!(divergence.member, "mana");
+        assert_eq!(divergence.indexed, FieldElement::from(20_u8));
+        assert_eq!(divergence.on_chain, FieldElement::from(21_u8));
+    }
+
+    /// Node serving the storage of the world, zero at the addresses it doesn't hold.
+    struct StorageTransport(HashMap<FieldElement, FieldElement>);
+
+    #[async_trait]
+    impl JsonRpcTransport for StorageTransport {
+        type Error = std::io::Error;
+
+        async fn send_request<P, R>(
+            &self,
+            method: JsonRpcMethod,
+            params: P,
+        ) -> Result<JsonRpcResponse<R>, Self::Error>
+        where
+            P: Serialize + Send,
+            R: DeserializeOwned,
+        {
+            assert!(matches!(method, JsonRpcMethod::GetStorageAt));
+            let params = serde_json::to_value(params).unwrap();
+            let key = params.get("key").or_else(|| params.get(1)).unwrap().as_str().unwrap();
+            let value = self
+                .0
+                .get(&FieldElement::from_hex_be(key).unwrap())
+                .copied()
+                .unwrap_or_default();
+
+            let response = json!({ "id": 1, "result": format!("{value:#x}") });
+            Ok(serde_json::from_value(response).unwrap())
+        }
+    }
+}
@@ -1,13 +1,173 @@
-use serde_json::Value;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
 use sqlx::SqlitePool;
+use starknet::core::types::{EmittedEvent, FieldElement};
+use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+use starknet::providers::jsonrpc::{
+    JsonRpcClient, JsonRpcMethod, JsonRpcResponse, JsonRpcTransport,
+};
 
+use crate::engine::{Engine, EngineConfig, Processors};
 use crate::graphql::schema::build_schema;
+use crate::processors::store_delete_record::StoreDeleteRecordProcessor;
+use crate::processors::store_set_record::StoreSetRecordProcessor;
+use crate::storage::sql::{component_table, SqlStorage};
+use crate::storage::Storage;
 
 #[allow(dead_code)]
 pub async fn run_graphql_query(pool: &SqlitePool, query: &str) -> Value {
-    let schema = build_schema(pool).await.unwrap();
+    let schema = build_schema(pool, None).await.unwrap();
     let res = schema.execute(query).await;
 
     assert!(res.errors.is_empty(), "GraphQL query returned errors: {:?}", res.errors);
     serde_json::to_value(res.data).expect("Failed to serialize GraphQL response")
 }
+
+#[allow(dead_code)]
+pub async fn run_namespaced_graphql_query(
+    pool: &SqlitePool,
+    namespace: &str,
+    query: &str,
+) -> Value {
+    let schema = build_schema(pool, Some(namespace)).await.unwrap();
+    let res = schema.execute(query).await;
+
+    assert!(res.errors.is_empty(), "GraphQL query returned errors: {:?}", res.errors);
+    serde_json::to_value(res.data).expect("Failed to serialize GraphQL response")
+}
+
+/// Creates the table of a component with `len` raw values and writes the entities to it, as
+/// `(key, values)`, the way the indexer does.
+#[allow(dead_code)]
+pub async fn write_component_entities(
+    pool: &SqlitePool,
+    component: &str,
+    len: usize,
+    entities: &[(u64, Vec<u64>)],
+) -> FieldElement {
+    let component = cairo_short_string_to_felt(component).unwrap();
+    let columns = (1..=len).map(|i| format!("column{i} TEXT, ")).collect::<String>();
+    sqlx::query(&format!(
+        "CREATE TABLE {} (id SERIAL PRIMARY KEY, partition TEXT NOT NULL, {columns}archived_at \
+         DATETIME, payload TEXT, codec TEXT)",
+        component_table(component)
+    ))
+    .execute(pool)
+    .await
+    .unwrap();
+
+    let storage = SqlStorage::new(pool.clone()).unwrap();
+    for (key, values) in entities {
+        let values = values.iter().map(|v| FieldElement::from(*v)).collect();
+        storage
+            .set_entity(component, FieldElement::ZERO, FieldElement::from(*key), values)
+            .await
+            .unwrap();
+    }
+    component
+}
+
+pub const WORLD: u64 = 0x420;
+pub const TRANSACTION_HASH: u64 = 0x10;
+
+/// Node serving a block with a single transaction executing `Spawn` through the world, without
+/// receipts.
+pub struct BlockTransport;
+
+#[async_trait]
+impl JsonRpcTransport for BlockTransport {
+    type Error = std::io::Error;
+
+    async fn send_request<P, R>(
+        &self,
+        method: JsonRpcMethod,
+        _params: P,
+    ) -> Result<JsonRpcResponse<R>, Self::Error>
+    where
+        P: Serialize + Send,
+        R: DeserializeOwned,
+    {
+        let response = match method {
+            JsonRpcMethod::GetBlockWithTxs => {
+                let spawn = cairo_short_string_to_felt("Spawn").unwrap();
+                let execute = get_selector_from_name("execute").unwrap();
+                // execute(name, calldata) called through the account's multicall
+                let calldata = [
+                    FieldElement::ONE,
+                    FieldElement::from(WORLD),
+                    execute,
+                    FieldElement::ZERO,
+                    FieldElement::TWO,
+                    FieldElement::TWO,
+                    spawn,
+                    FieldElement::ZERO,
+                ];
+                let calldata = calldata.iter().map(|v| format!("{v:#x}")).collect::<Vec<_>>();
+                let block = json!({
+                    "status": "ACCEPTED_ON_L2",
+                    "block_hash": "0x1",
+                    "parent_hash": "0x0",
+                    "block_number": 1,
+                    "new_root": "0x0",
+                    "timestamp": 0,
+                    "sequencer_address": "0x0",
+                    "transactions": [{
+                        "type": "INVOKE",
+                        "version": "0x1",
+                        "transaction_hash": format!("{TRANSACTION_HASH:#x}"),
+                        "max_fee": "0x0",
+                        "signature": [],
+                        "nonce": "0x0",
+                        "sender_address": "0x5",
+                        "calldata": calldata,
+                    }],
+                });
+                json!({ "id": 1, "result": block })
+            }
+            _ => {
+                let error = json!({ "code": 25, "message": "Invalid transaction hash" });
+                json!({ "id": 1, "error": error })
+            }
+        };
+
+        Ok(serde_json::from_value(response).unwrap())
+    }
+}
+
+/// Event emitted by the world in the transaction of [`BlockTransport`].
+#[allow(dead_code)]
+pub fn world_event(name: &str, data: Vec<FieldElement>) -> EmittedEvent {
+    EmittedEvent {
+        from_address: FieldElement::from(WORLD),
+        keys: vec![get_selector_from_name(name).unwrap()],
+        data,
+        block_hash: FieldElement::ONE,
+        block_number: 1,
+        transaction_hash: FieldElement::from(TRANSACTION_HASH),
+    }
+}
+
+/// Indexes `events` as the events of block `block_number`, through the engine and the processors
+/// of the world's records.
+#[allow(dead_code)]
+pub async fn index_block(storage: &SqlStorage, block_number: u64, events: &[EmittedEvent]) {
+    let world = FieldElement::from(WORLD);
+    let provider = JsonRpcClient::new(BlockTransport);
+    let processors = Processors::new(
+        vec![],
+        vec![],
+        vec![
+            Arc::new(StoreSetRecordProcessor::new(world)),
+            Arc::new(StoreDeleteRecordProcessor::new(world)),
+        ],
+    );
+    let config = EngineConfig { world_address: world, events_chunk_size: 10, block_range: 1 };
+    let engine = Engine::new(storage, &provider, processors, config);
+
+    let events = events.iter().collect::<Vec<_>>();
+    assert!(engine.process_block_number(block_number, &events).await.unwrap());
+}
@@ -3,7 +3,7 @@ mod tests {
     use serde::Deserialize;
     use sqlx::SqlitePool;
 
-    use crate::tests::common::run_graphql_query;
+    use crate::tests::common::{run_graphql_query, run_namespaced_graphql_query};
 
     #[derive(Deserialize)]
     struct Game {
@@ -100,4 +100,85 @@ mod tests {
         assert_eq!(component_game.name, component_game.storage.__typename);
         assert_eq!(component_stats.name, component_stats.storage.__typename);
     }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "components"))]
+    async fn test_namespaced_storage(pool: SqlitePool) {
+        let _ = pool.acquire().await;
+
+        let query = r#"
+                {
+                    worldgame(id: 1) {
+                        __typename
+                        name
+                        is_finished
+                    }
+                    component(id: "component_2") {
+                        storage {
+                            __typename
+                        }
+                    }
+                }
+            "#;
+        let value = run_namespaced_graphql_query(&pool, "World", query).await;
+
+        let game = value.get("worldgame").ok_or("no game found").unwrap();
+        let game: Game = serde_json::from_value(game.clone()).unwrap();
+        let storage = &value["component"]["storage"];
+
+        assert_eq!(game.__typename, "WorldGame");
+        assert_eq!(game.name, "0x594F4C4F");
+        assert_eq!(storage["__typename"], "WorldStats");
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "enums"))]
+    async fn test_storage_enum(pool: SqlitePool) {
+        let _ = pool.acquire().await;
+
+        let query = r#"
+                {
+                    moves(id: 1) {
+                        remaining
+                        last_direction
+                    }
+                    __type(name: "Direction") {
+                        enumValues {
+                            name
+                        }
+                    }
+                }
+            "#;
+        let value = run_graphql_query(&pool, query).await;
+
+        assert_eq!(value["moves"]["remaining"], 10);
+        assert_eq!(value["moves"]["last_direction"], "Up");
+        assert_eq!(value["__type"]["enumValues"].as_array().unwrap().len(), 4);
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "enums"))]
+    async fn test_storage_enum_filter(pool: SqlitePool) {
+        let _ = pool.acquire().await;
+
+        let query = r#"
+                {
+                    up: movesComponents(where: { last_direction: Up }) {
+                        remaining
+                        last_direction
+                    }
+                    down: movesComponents(where: { last_direction: Down }) {
+                        remaining
+                    }
+                    all: movesComponents {
+                        remaining
+                    }
+                }
+            "#;
+        let value = run_graphql_query(&pool, query).await;
+
+        let up = value["up"].as_array().unwrap();
+        assert_eq!(up.len(), 1);
+        assert_eq!(up[0]["remaining"], 10);
+        assert_eq!(up[0]["last_direction"], "Up");
+        assert!(value["down"].as_array().unwrap().is_empty());
+        assert_eq!(value["all"].as_array().unwrap().len(), 2);
+    }
 }
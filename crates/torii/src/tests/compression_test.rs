@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+
+    use crate::compression::{compress, compress_payloads, decompress, Compression};
+    use crate::storage::sql::{component_rows, SqlStorage};
+    use crate::storage::Storage;
+    use crate::tests::common::{run_graphql_query, write_component_entities};
+
+    #[derive(Deserialize)]
+    pub struct EntityStateUpdate {
+        pub data: String,
+    }
+
+    #[test]
+    fn test_compression_roundtrip() {
+        let data = vec!["0x2a"; 100].join(",");
+
+        let compressed = compress(&data, Compression::Deflate).unwrap();
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompress(&compressed, Compression::Deflate).unwrap(), data);
+
+        assert_eq!(compress(&data, Compression::None).unwrap(), data);
+        assert_eq!(decompress(&data, Compression::None).unwrap(), data);
+        // the codec isn't guessed from the value
+        assert_eq!(decompress("deflate:0x1", Compression::None).unwrap(), "deflate:0x1");
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures("entities", "components", "systems", "system_calls", "entity_state_updates")
+    )]
+    async fn test_compress_payloads(pool: SqlitePool) {
+        let data = vec!["0x2a"; 100].join(",");
+        sqlx::query(
+            "INSERT INTO entity_state_updates (id, entity_id, component_id, system_call_id, data) \
+             VALUES (4, 'entity_3', 'component_1', 4, $1)",
+        )
+        .bind(&data)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // Only the payloads over the threshold are compressed.
+        assert_eq!(compress_payloads(&pool, Compression::None, 64).await.unwrap(), 0);
+        assert_eq!(compress_payloads(&pool, Compression::Deflate, 64).await.unwrap(), 1);
+        assert_eq!(compress_payloads(&pool, Compression::Deflate, 64).await.unwrap(), 0);
+
+        let (codec,): (Option<String>,) =
+            sqlx::query_as("SELECT codec FROM entity_state_updates WHERE id = 4")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(codec.as_deref(), Some("deflate"));
+
+        let query = "{ entityStateUpdatesByTransaction(transactionHash: \"0x1\") { data } }";
+        let value = run_graphql_query(&pool, query).await;
+
+        let updates = value.get("entityStateUpdatesByTransaction").ok_or("no updates").unwrap();
+        let updates: Vec<EntityStateUpdate> = serde_json::from_value(updates.clone()).unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].data, "0x2b,0x45");
+        assert_eq!(updates[1].data, data);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_compress_component_rows(pool: SqlitePool) {
+        let values = (0..100).map(|i| i % 4).collect::<Vec<u64>>();
+        let entities = [(1, values.clone()), (2, vec![0; 100])];
+        let component = write_component_entities(&pool, "Map", 100, &entities).await;
+        let rows = component_rows(&pool, component).await.unwrap();
+
+        assert_eq!(compress_payloads(&pool, Compression::Deflate, 64).await.unwrap(), 2);
+        let (compressed,): (i64,) = sqlx::query_as(&format!(
+            "SELECT COUNT(*) FROM \"{component}\" WHERE codec = 'deflate' AND column1 IS NULL"
+        ))
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(compressed, 2);
+
+        // read back transparently
+        assert_eq!(component_rows(&pool, component).await.unwrap(), rows);
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+        let expected = values.iter().map(|v| FieldElement::from(*v)).collect::<Vec<_>>();
+        let entity = storage.entity(component, FieldElement::ZERO, FieldElement::ONE);
+        assert_eq!(entity.await.unwrap(), expected);
+        assert_eq!(storage.entities(component, FieldElement::ZERO).await.unwrap().len(), 2);
+
+        // writing the entity again stores it uncompressed
+        storage
+            .set_entity(component, FieldElement::ZERO, FieldElement::ONE, expected)
+            .await
+            .unwrap();
+        let (codec,): (Option<String>,) =
+            sqlx::query_as(&format!("SELECT codec FROM \"{component}\" WHERE id = 1"))
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(codec, None);
+        assert_eq!(component_rows(&pool, component).await.unwrap()[1], rows[0]);
+    }
+}
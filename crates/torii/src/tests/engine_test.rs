@@ -0,0 +1,125 @@
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::{json, Value};
+    use starknet::core::types::FieldElement;
+    use starknet::providers::jsonrpc::{
+        JsonRpcClient, JsonRpcMethod, JsonRpcResponse, JsonRpcTransport,
+    };
+
+    use crate::engine::fetch_events;
+
+    /// Largest page size the node serves.
+    const MAX_CHUNK_SIZE: u64 = 100;
+
+    /// Node serving `getEvents` but refusing requests spanning more than `max_events` events,
+    /// like public RPC providers capping their response sizes.
+    struct CappedTransport {
+        /// Block number of every event, in order.
+        blocks: Vec<u64>,
+        max_events: usize,
+    }
+
+    fn find<'a>(value: &'a Value, key: &str) -> Option<&'a Value> {
+        match value {
+            Value::Object(map) => {
+                map.get(key).or_else(|| map.values().find_map(|value| find(value, key)))
+            }
+            Value::Array(values) => values.iter().find_map(|value| find(value, key)),
+            _ => None,
+        }
+    }
+
+    #[async_trait]
+    impl JsonRpcTransport for CappedTransport {
+        type Error = std::io::Error;
+
+        async fn send_request<P, R>(
+            &self,
+            method: JsonRpcMethod,
+            params: P,
+        ) -> Result<JsonRpcResponse<R>, Self::Error>
+        where
+            P: Serialize + Send,
+            R: DeserializeOwned,
+        {
+            assert!(matches!(method, JsonRpcMethod::GetEvents));
+            let params = serde_json::to_value(params).unwrap();
+            let block = |key| find(&params, key).and_then(|b| b["block_number"].as_u64()).unwrap();
+            let (from, to) = (block("from_block"), block("to_block"));
+            let chunk_size = find(&params, "chunk_size").and_then(Value::as_u64).unwrap();
+            let offset = find(&params, "continuation_token")
+                .and_then(Value::as_str)
+                .map_or(0, |token| token.parse::<usize>().unwrap());
+
+            let events = self
+                .blocks
+                .iter()
+                .enumerate()
+                .filter(|(_, block)| (from..=to).contains(*block))
+                .map(|(i, block)| {
+                    json!({
+                        "from_address": "0x420",
+                        "keys": ["0x1"],
+                        "data": [format!("{i:#x}")],
+                        "block_hash": format!("{block:#x}"),
+                        "block_number": block,
+                        "transaction_hash": format!("{i:#x}"),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            let response = if chunk_size > MAX_CHUNK_SIZE {
+                let error = json!({ "code": 31, "message": "Requested page size is too big" });
+                json!({ "id": 1, "error": error })
+            } else if events.len() > self.max_events {
+                let error = json!({ "code": -32000, "message": "Response is too big" });
+                json!({ "id": 1, "error": error })
+            } else {
+                let end = (offset + chunk_size as usize).min(events.len());
+                let continuation_token = (end < events.len()).then(|| end.to_string());
+                let page = json!({
+                    "events": events[offset..end],
+                    "continuation_token": continuation_token,
+                });
+                json!({ "id": 1, "result": page })
+            };
+
+            Ok(serde_json::from_value(response).unwrap())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_events_splits_capped_ranges() {
+        let blocks = vec![0, 1, 1, 2, 3, 3, 3, 5, 6, 8, 9, 9];
+        let provider =
+            JsonRpcClient::new(CappedTransport { blocks: blocks.clone(), max_events: 3 });
+
+        let events =
+            fetch_events(&provider, FieldElement::from(0x420_u64), 0, 9, 1000).await.unwrap();
+
+        assert_eq!(events.iter().map(|e| e.block_number).collect::<Vec<_>>(), blocks);
+        let data = events.iter().map(|e| e.data[0]).collect::<Vec<_>>();
+        assert_eq!(data, (0..blocks.len()).map(FieldElement::from).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_events_follows_continuation_tokens() {
+        let blocks = (0..250).map(|i| i / 10).collect::<Vec<_>>();
+        let provider = JsonRpcClient::new(CappedTransport { blocks, max_events: 1000 });
+
+        let events =
+            fetch_events(&provider, FieldElement::from(0x420_u64), 0, 24, 40).await.unwrap();
+        assert_eq!(events.len(), 250);
+        assert_eq!(events.last().unwrap().block_number, 24);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_events_fails_on_capped_block() {
+        let provider = JsonRpcClient::new(CappedTransport { blocks: vec![4, 4, 4], max_events: 2 });
+
+        assert!(fetch_events(&provider, FieldElement::from(0x420_u64), 0, 9, 10).await.is_err());
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::cairo_short_string_to_felt;
+
+    use crate::storage::sql::SqlStorage;
+    use crate::tests::common::{index_block, run_graphql_query, world_event};
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EntityStateUpdate {
+        pub entity_id: String,
+        pub component_name: String,
+        pub data: String,
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures("entities", "components", "systems", "system_calls", "entity_state_updates")
+    )]
+    async fn test_entity_state_updates_by_transaction(pool: SqlitePool) {
+        let _ = pool.acquire().await;
+
+        let query = "{ entityStateUpdatesByTransaction(transactionHash: \"0x0\") { entityId \
+                     componentName data } }";
+        let value = run_graphql_query(&pool, query).await;
+
+        let updates = value.get("entityStateUpdatesByTransaction").ok_or("no updates").unwrap();
+        let updates: Vec<EntityStateUpdate> = serde_json::from_value(updates.clone()).unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].entity_id, "entity_1");
+        assert_eq!(updates[0].component_name, "Game");
+        assert_eq!(updates[1].component_name, "Stats");
+        assert_eq!(updates[1].data, "0x2a,0x45");
+
+        let query = "{ entityStateUpdatesByTransaction(transactionHash: \"0x1\") { entityId \
+                     componentName data } }";
+        let value = run_graphql_query(&pool, query).await;
+
+        let updates = value.get("entityStateUpdatesByTransaction").ok_or("no updates").unwrap();
+        let updates: Vec<EntityStateUpdate> = serde_json::from_value(updates.clone()).unwrap();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].data, "0x2b,0x45");
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_indexed_entity_state_updates(pool: SqlitePool) {
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+
+        // table_id, keys_len, keys*, value_len, value*
+        let position = cairo_short_string_to_felt("Position").unwrap();
+        let record = |data: &[u64]| {
+            [vec![position], data.iter().map(|v| FieldElement::from(*v)).collect()].concat()
+        };
+        let events = [
+            world_event("StoreSetRecord", record(&[1, 1, 2, 2, 3])),
+            world_event("StoreSetRecord", record(&[1, 2, 2, 4, 5])),
+            world_event("StoreDeleteRecord", record(&[1, 2])),
+        ];
+        index_block(&storage, 1, &events).await;
+
+        let (system,): (String,) =
+            sqlx::query_as("SELECT system_id FROM system_calls").fetch_one(&pool).await.unwrap();
+        assert_eq!(system, "Spawn");
+
+        let query = "{ entityStateUpdatesByTransaction(transactionHash: \"0x10\") { entityId \
+                     componentName data } }";
+        let value = run_graphql_query(&pool, query).await;
+
+        let updates = value.get("entityStateUpdatesByTransaction").ok_or("no updates").unwrap();
+        let updates: Vec<EntityStateUpdate> = serde_json::from_value(updates.clone()).unwrap();
+        let updates = updates
+            .iter()
+            .map(|u| (u.entity_id.as_str(), u.component_name.as_str(), u.data.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            updates,
+            [
+                ("0x1", "Position", "0x2,0x3"),
+                ("0x2", "Position", "0x4,0x5"),
+                ("0x2", "Position", "")
+            ]
+        );
+    }
+}
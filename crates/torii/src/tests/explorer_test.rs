@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+
+    use crate::explorer::{entity_html, overview_html};
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "systems", "system_calls"))]
+    async fn test_explorer_overview(pool: SqlitePool) {
+        let html = overview_html(&pool).await.unwrap();
+
+        assert!(html.contains("<a href=\"/explorer/entities/entity_1\">entity_1</a>"));
+        assert!(html.contains("system_1"));
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures("entities", "components", "systems", "system_calls", "entity_state_updates")
+    )]
+    async fn test_explorer_entity(pool: SqlitePool) {
+        let html = entity_html(&pool, "entity_2").await.unwrap().unwrap();
+        assert!(html.contains("0x2a,0x45"));
+        assert!(html.contains("0x2b,0x45"));
+
+        assert!(entity_html(&pool, "entity_4").await.unwrap().is_none());
+    }
+}
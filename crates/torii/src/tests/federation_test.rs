@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use async_graphql::Response;
+    use serde_json::Value;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use sqlx::SqlitePool;
+    use tokio_stream::{Stream, StreamExt};
+
+    use crate::graphql::federation::{build_federated_schema, FederatedWorld, Federation};
+
+    /// Second shard, indexing `entity_1` with a `Stats` component.
+    async fn other_world() -> SqlitePool {
+        // a single connection, each one opens its own in-memory database
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await;
+        let pool = pool.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        for fixture in [
+            include_str!("fixtures/entities.sql"),
+            include_str!("fixtures/components.sql"),
+            include_str!("fixtures/systems.sql"),
+            include_str!("fixtures/system_calls.sql"),
+        ] {
+            sqlx::query(fixture).execute(&pool).await.unwrap();
+        }
+        insert_update(&pool, "entity_1", "component_2").await;
+        pool
+    }
+
+    async fn insert_update(pool: &SqlitePool, entity_id: &str, component_id: &str) {
+        sqlx::query(
+            "INSERT INTO entity_state_updates (entity_id, component_id, system_call_id, data) \
+             VALUES ($1, $2, 1, '0x1')",
+        )
+        .bind(entity_id)
+        .bind(component_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    fn federation(eu: SqlitePool, us: SqlitePool) -> Federation {
+        Federation::new(vec![
+            FederatedWorld { id: "eu".to_string(), pool: eu },
+            FederatedWorld { id: "us".to_string(), pool: us },
+        ])
+        .unwrap()
+    }
+
+    async fn next_update(stream: &mut (impl Stream<Item = Response> + Unpin)) -> Value {
+        let res = stream.next().await.expect("subscription ended");
+        assert!(res.errors.is_empty(), "GraphQL subscription returned errors: {:?}", res.errors);
+        serde_json::to_value(res.data).unwrap()["entityStateUpdates"].clone()
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures("entities", "components", "systems", "system_calls", "entity_state_updates")
+    )]
+    async fn test_entity_across_worlds(pool: SqlitePool) {
+        let schema = build_federated_schema(federation(pool, other_world().await), None).unwrap();
+        let res = schema
+            .execute(
+                "{ entity(id: \"entity_1\") { worldId id components { worldId componentName \
+                 data } } }",
+            )
+            .await;
+        assert!(res.errors.is_empty(), "GraphQL query returned errors: {:?}", res.errors);
+        let data = serde_json::to_value(res.data).unwrap();
+
+        let entities = data["entity"].as_array().unwrap();
+        assert_eq!(entities.len(), 2);
+        assert_eq!(entities[0]["worldId"], "eu");
+        assert_eq!(entities[0]["components"][0]["componentName"], "Game");
+        assert_eq!(entities[1]["worldId"], "us");
+        assert_eq!(entities[1]["components"][0]["worldId"], "us");
+        assert_eq!(entities[1]["components"][0]["componentName"], "Stats");
+
+        let res = schema
+            .execute("{ entity(id: \"entity_1\", worldIds: [\"us\"]) { worldId } }")
+            .await;
+        let data = serde_json::to_value(res.data).unwrap();
+        assert_eq!(data["entity"].as_array().unwrap().len(), 1);
+
+        let res = schema.execute("{ entity(id: \"entity_1\", worldIds: [\"asia\"]) { id } }").await;
+        assert_eq!(res.errors[0].message, "Unknown world `asia`");
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_duplicate_world(pool: SqlitePool) {
+        let worlds = vec![
+            FederatedWorld { id: "eu".to_string(), pool: pool.clone() },
+            FederatedWorld { id: "eu".to_string(), pool },
+        ];
+        assert!(Federation::new(worlds).is_err());
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures("entities", "components", "systems", "system_calls", "entity_state_updates")
+    )]
+    async fn test_subscription_across_worlds(pool: SqlitePool) {
+        let us = other_world().await;
+        let schema = build_federated_schema(federation(pool.clone(), us.clone()), None).unwrap();
+        let mut stream = Box::pin(schema.execute_stream(
+            "subscription { entityStateUpdates(cursors: [\"eu:2\", \"us:0\"]) { worldId id \
+             entityId } }",
+        ));
+
+        let mut updates = vec![];
+        for _ in 0..2 {
+            let update = next_update(&mut stream).await;
+            updates.push((update["worldId"].clone(), update["id"].clone()));
+        }
+        updates.sort_by_key(|(world_id, _)| world_id.to_string());
+        assert_eq!(updates, vec![("eu".into(), "3".into()), ("us".into(), "1".into())]);
+
+        // then the live ones
+        insert_update(&us, "entity_2", "component_2").await;
+        let update = next_update(&mut stream).await;
+        assert_eq!(update["worldId"], "us");
+        assert_eq!(update["entityId"], "entity_2");
+    }
+}
@@ -0,0 +1,83 @@
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::cairo_short_string_to_felt;
+
+    use crate::firehose::{
+        advance_cursor, read_batch, register_sinks, FirehoseStorage, SinkConfig, SinkKind,
+    };
+    use crate::storage::memory::MemoryStorage;
+    use crate::storage::Storage;
+
+    fn sink(name: &str) -> SinkConfig {
+        SinkConfig {
+            name: name.into(),
+            kind: SinkKind::Webhook {
+                url: "http://localhost:8000/sink".parse().unwrap(),
+                secret: None,
+            },
+            batch_size: 2,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_firehose_cursors(pool: SqlitePool) {
+        register_sinks(&pool, &[sink("analytics"), sink("archive")]).await.unwrap();
+
+        let storage = FirehoseStorage::new(MemoryStorage::default(), Some(pool.clone()));
+        let position = cairo_short_string_to_felt("Position").unwrap();
+        for key in 1_u8..=3 {
+            storage
+                .set_entity(position, FieldElement::ZERO, key.into(), vec![7_u8.into()])
+                .await
+                .unwrap();
+        }
+        storage.delete_entity(position, FieldElement::ZERO, FieldElement::ONE).await.unwrap();
+
+        // Batches are read in order from the cursor of the sink, until it advances.
+        let batch = read_batch(&pool, "analytics", 2).await.unwrap();
+        assert_eq!(batch.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(batch[0].1["id"], 1);
+        assert_eq!(batch[0].1["type"], "entity_set");
+        assert_eq!(batch[0].1["component"], "Position");
+        assert_eq!(read_batch(&pool, "analytics", 2).await.unwrap().len(), 2);
+
+        advance_cursor(&pool, "analytics", 2).await.unwrap();
+        let batch = read_batch(&pool, "analytics", 2).await.unwrap();
+        assert_eq!(batch.iter().map(|(id, _)| *id).collect::<Vec<_>>(), vec![3, 4]);
+        assert_eq!(batch[1].1["type"], "entity_deleted");
+
+        // Updates are retained until every sink published them.
+        let (retained,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM firehose_log").fetch_one(&pool).await.unwrap();
+        assert_eq!(retained, 4);
+        advance_cursor(&pool, "archive", 3).await.unwrap();
+        let (retained,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM firehose_log").fetch_one(&pool).await.unwrap();
+        assert_eq!(retained, 2);
+
+        // Dropping a sink from the configuration forgets its cursor.
+        register_sinks(&pool, &[sink("archive")]).await.unwrap();
+        assert_eq!(read_batch(&pool, "archive", 10).await.unwrap().len(), 1);
+        assert!(read_batch(&pool, "analytics", 10).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_firehose_disabled(pool: SqlitePool) {
+        let storage = FirehoseStorage::new(MemoryStorage::default(), None);
+        storage
+            .set_entity(
+                FieldElement::ONE,
+                FieldElement::ZERO,
+                FieldElement::ONE,
+                vec![FieldElement::ONE],
+            )
+            .await
+            .unwrap();
+
+        let (retained,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM firehose_log").fetch_one(&pool).await.unwrap();
+        assert_eq!(retained, 0);
+    }
+}
@@ -0,0 +1 @@
+INSERT INTO entities (id, name, partition_id, keys, transaction_hash, archived_at) VALUES ( 'entity_4', 'Entity4', '420', '70', '0x1', '2023-07-18 12:00:00' );
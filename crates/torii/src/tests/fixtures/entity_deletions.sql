@@ -0,0 +1,2 @@
+INSERT INTO entity_state_updates (id, entity_id, component_id, system_call_id, data, deleted)
+VALUES (4, 'entity_4', 'component_1', 4, '', TRUE);
@@ -0,0 +1,8 @@
+INSERT INTO system_calls (id, system_id, transaction_hash, data)
+VALUES (4, 'system_3', '0x1', "0x1,0x2,0x3");
+INSERT INTO entity_state_updates (id, entity_id, component_id, system_call_id, data)
+VALUES (1, 'entity_1', 'component_1', 1, '0x594F4C4F,0x0');
+INSERT INTO entity_state_updates (id, entity_id, component_id, system_call_id, data)
+VALUES (2, 'entity_2', 'component_2', 2, '0x2a,0x45');
+INSERT INTO entity_state_updates (id, entity_id, component_id, system_call_id, data)
+VALUES (3, 'entity_2', 'component_2', 4, '0x2b,0x45');
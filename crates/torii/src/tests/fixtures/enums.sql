@@ -0,0 +1,19 @@
+INSERT INTO components (id, name, address, class_hash, transaction_hash, storage_definition)
+VALUES ('component_3', 'Moves', '0x0', '0x0', '0x0', 
+    '[{"name":"remaining","type":"u8","slot":0,"offset":0},{"name":"last_direction","type":"Direction","slot":1,"offset":0,"variants":["Left","Right","Up","Down"]}]');
+
+CREATE TABLE storage_moves (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    remaining INTEGER NOT NULL,
+    last_direction INTEGER NOT NULL,
+    version TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    component_id TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (entity_id) REFERENCES entities(id),
+    FOREIGN KEY (component_id) REFERENCES components(id)
+);
+
+INSERT INTO storage_moves (id, remaining, last_direction, version, entity_id, component_id, created_at)
+VALUES (1, 10, 2, '0.0.0', 'entity_1', 'component_3', '2023-05-19T21:06:12Z'),
+    (2, 7, 0, '0.0.0', 'entity_1', 'component_3', '2023-05-19T21:08:12Z');
@@ -0,0 +1,15 @@
+INSERT INTO components (id, name, address, class_hash, transaction_hash, storage_definition)
+VALUES ('component_2', 'Stats', '0x0', '0x0', '0x0', 
+    '[{"name":"health","type":"u8","slot":0,"offset":0},{"name":"mana","type":"u8","slot":1,"offset":0}]');
+
+CREATE TABLE storage_stats (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    health INTEGER NOT NULL,
+    mana INTEGER NOT NULL,
+    version TEXT NOT NULL,
+    entity_id TEXT NOT NULL,
+    component_id TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (entity_id) REFERENCES entities(id),
+    FOREIGN KEY (component_id) REFERENCES components(id)
+);
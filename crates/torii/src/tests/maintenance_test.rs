@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use sqlx::SqlitePool;
+
+    use crate::maintenance::{run_maintenance, MaintenanceConfig, MaintenanceMetrics};
+
+    async fn freelist_count(pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar("PRAGMA freelist_count").fetch_one(pool).await.unwrap()
+    }
+
+    /// Fills a table with a few hundred pages, then empties it.
+    async fn free_pages(pool: &SqlitePool) {
+        sqlx::query("CREATE TABLE IF NOT EXISTS filler (data BLOB)").execute(pool).await.unwrap();
+        sqlx::query(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 500) INSERT \
+             INTO filler SELECT randomblob(4096) FROM n",
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query("DELETE FROM filler").execute(pool).await.unwrap();
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_vacuum_shrinks_freelist(pool: SqlitePool) {
+        let config = MaintenanceConfig::new(Duration::from_secs(3600)).with_vacuum_pages(100);
+        let mut metrics = MaintenanceMetrics::default();
+
+        // The database isn't vacuumed incrementally yet, the first run rebuilds it.
+        free_pages(&pool).await;
+        let before = freelist_count(&pool).await;
+        assert!(before > 100);
+        run_maintenance(&pool, &config, &mut metrics).await.unwrap();
+        assert!(metrics.full_vacuum);
+        assert_eq!(metrics.free_pages, 0);
+        assert_eq!(metrics.reclaimed_pages, before as u64);
+
+        // Then at most `vacuum_pages` are reclaimed per run.
+        free_pages(&pool).await;
+        let before = freelist_count(&pool).await;
+        assert!(before > 100);
+        run_maintenance(&pool, &config, &mut metrics).await.unwrap();
+        assert!(!metrics.full_vacuum);
+        assert_eq!(metrics.free_pages, (before - 100) as u64);
+        assert!(freelist_count(&pool).await <= before - 100);
+
+        let runs: Vec<(bool, i64)> =
+            sqlx::query_as("SELECT full_vacuum, reclaimed_pages FROM maintenance_runs ORDER BY id")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[1], (false, 100));
+    }
+}
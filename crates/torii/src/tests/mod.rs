@@ -1,4 +1,24 @@
 mod common;
+mod archival_test;
+mod authorizations_test;
+mod bootstrap_test;
+mod check_test;
 mod components_test;
+mod compression_test;
+mod engine_test;
 mod entities_test;
+mod entity_state_updates_test;
 mod events_test;
+mod explorer_test;
+mod federation_test;
+mod firehose_test;
+mod maintenance_test;
+mod processors_test;
+mod saved_queries_test;
+mod settings_test;
+mod signing_test;
+mod storage_test;
+mod subscriptions_test;
+mod system_metrics_test;
+mod transactions_test;
+mod webhooks_test;
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sqlx::SqlitePool;
+    use starknet::core::types::{Event, FieldElement};
+    use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+
+    use crate::processors::custom::{
+        CustomProcessors, ProcessingStorage, SqlProcessor, SqlProcessorConfig,
+    };
+    use crate::storage::memory::MemoryStorage;
+    use crate::storage::Storage;
+
+    fn leaderboard() -> SqlProcessorConfig {
+        SqlProcessorConfig {
+            name: "leaderboard".into(),
+            setup: Some(
+                "CREATE TABLE leaderboard (player TEXT PRIMARY KEY, score TEXT NOT NULL); CREATE \
+                 TABLE rewards (player TEXT NOT NULL, amount TEXT NOT NULL);"
+                    .into(),
+            ),
+            component: Some("Score".into()),
+            on_set: Some(
+                "INSERT INTO leaderboard (player, score) VALUES ($1, $3) ON CONFLICT(player) DO \
+                 UPDATE SET score = excluded.score"
+                    .into(),
+            ),
+            on_delete: Some("DELETE FROM leaderboard WHERE player = $1".into()),
+            event: Some("Rewarded".into()),
+            on_event: Some("INSERT INTO rewards (player, amount) VALUES ($2, $3)".into()),
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_sql_processor_state_updates(pool: SqlitePool) {
+        let mut processors = CustomProcessors::new(pool.clone());
+        processors.register(Arc::new(SqlProcessor::new(&pool, leaderboard()).await.unwrap()));
+        let storage = ProcessingStorage::new(MemoryStorage::default(), processors);
+
+        let score = cairo_short_string_to_felt("Score").unwrap();
+        let position = cairo_short_string_to_felt("Position").unwrap();
+        let (alice, bob) = (FieldElement::ONE, FieldElement::TWO);
+        storage.set_entity(score, FieldElement::ZERO, alice, vec![10_u8.into()]).await.unwrap();
+        storage.set_entity(score, FieldElement::ZERO, bob, vec![5_u8.into()]).await.unwrap();
+        storage.set_entity(score, FieldElement::ZERO, alice, vec![12_u8.into()]).await.unwrap();
+        storage.set_entity(position, FieldElement::ZERO, bob, vec![7_u8.into()]).await.unwrap();
+
+        let rows: Vec<(String, String)> =
+            sqlx::query_as("SELECT player, score FROM leaderboard ORDER BY player")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(rows, vec![("0x1".into(), "0xc".into()), ("0x2".into(), "0x5".into())]);
+
+        storage.delete_entity(score, FieldElement::ZERO, bob).await.unwrap();
+        let players: Vec<(String,)> =
+            sqlx::query_as("SELECT player FROM leaderboard").fetch_all(&pool).await.unwrap();
+        assert_eq!(players, vec![("0x1".into(),)]);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_sql_processor_events(pool: SqlitePool) {
+        let mut processors = CustomProcessors::new(pool.clone());
+        processors.register(Arc::new(SqlProcessor::new(&pool, leaderboard()).await.unwrap()));
+
+        let event = |name: &str| Event {
+            from_address: FieldElement::from(0x420_u64),
+            keys: vec![get_selector_from_name(name).unwrap()],
+            data: vec![FieldElement::ONE, FieldElement::from(100_u8)],
+        };
+        processors.process_event(&event("Rewarded")).await;
+        processors.process_event(&event("Moved")).await;
+
+        let rewards: Vec<(String, String)> =
+            sqlx::query_as("SELECT player, amount FROM rewards").fetch_all(&pool).await.unwrap();
+        assert_eq!(rewards, vec![("0x1".into(), "0x64".into())]);
+    }
+}
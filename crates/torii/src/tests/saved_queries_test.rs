@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use sqlx::SqlitePool;
+
+    use crate::graphql::saved_query::{
+        delete_query, save_query, saved_queries, Filter, FilterOp, SavedQuery,
+    };
+    use crate::tests::common::{run_graphql_query, write_component_entities};
+
+    fn strongest() -> SavedQuery {
+        SavedQuery {
+            name: "strongest".into(),
+            component: "Stats".into(),
+            filters: vec![Filter {
+                member: "health".into(),
+                op: FilterOp::Gte,
+                value: "20".into(),
+            }],
+            order_by: Some("health".into()),
+            descending: true,
+            limit: Some(10),
+            cache_ttl_secs: 0,
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "stats"))]
+    async fn test_saved_query(pool: SqlitePool) {
+        // entity 2 was written twice, only its latest values count
+        let entities = [(2, vec![40, 1]), (3, vec![10, 2]), (1, vec![30, 3]), (2, vec![50, 1])];
+        write_component_entities(&pool, "Stats", 2, &entities).await;
+
+        save_query(&pool, &strongest()).await.unwrap();
+        assert_eq!(saved_queries(&pool).await.unwrap(), vec![strongest()]);
+
+        let value = run_graphql_query(&pool, "{ strongest { health mana } }").await;
+        assert_eq!(
+            value,
+            json!({ "strongest": [{ "health": 50, "mana": 1 }, { "health": 30, "mana": 3 }] })
+        );
+
+        // without an order, entities are sorted by latest write
+        let mana = SavedQuery {
+            name: "manaThree".into(),
+            filters: vec![Filter { member: "mana".into(), op: FilterOp::Lte, value: "3".into() }],
+            order_by: None,
+            ..strongest()
+        };
+        save_query(&pool, &mana).await.unwrap();
+        let value = run_graphql_query(&pool, "{ manaThree { health } }").await;
+        assert_eq!(
+            value,
+            json!({ "manaThree": [{ "health": 10 }, { "health": 30 }, { "health": 50 }] })
+        );
+
+        assert!(delete_query(&pool, "strongest").await.unwrap());
+        assert!(delete_query(&pool, "manaThree").await.unwrap());
+        assert!(saved_queries(&pool).await.unwrap().is_empty());
+        assert!(!delete_query(&pool, "strongest").await.unwrap());
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "components"))]
+    async fn test_invalid_saved_query(pool: SqlitePool) {
+        let unknown_member = SavedQuery { order_by: Some("speed".into()), ..strongest() };
+        assert!(save_query(&pool, &unknown_member).await.is_err());
+
+        // felts are stored as hex strings, they can't be ordered
+        let felt_order = SavedQuery {
+            component: "Game".into(),
+            filters: vec![],
+            order_by: Some("name".into()),
+            ..strongest()
+        };
+        assert!(save_query(&pool, &felt_order).await.is_err());
+
+        let invalid_name = SavedQuery { name: "top-10".into(), ..strongest() };
+        assert!(save_query(&pool, &invalid_name).await.is_err());
+
+        let unknown_component = SavedQuery { component: "Speed".into(), ..strongest() };
+        assert!(save_query(&pool, &unknown_component).await.is_err());
+
+        assert!(saved_queries(&pool).await.unwrap().is_empty());
+    }
+}
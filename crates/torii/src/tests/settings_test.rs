@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use crate::settings::{RuntimeSettings, Settings};
+
+    fn settings_file(name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("torii-{}-{name}", std::process::id()));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_reload_settings() {
+        let config = settings_file("config.json", r#"{ "requests_per_minute": 10 }"#);
+        let webhooks = settings_file("webhooks.json", "[]");
+        let settings = Settings::load(Some(config.clone()), Some(webhooks.clone())).unwrap();
+
+        let runtime = settings.runtime();
+        assert_eq!(runtime.borrow().requests_per_minute, 10);
+        assert_eq!(runtime.borrow().max_page_size, RuntimeSettings::default().max_page_size);
+        assert!(settings.webhooks().borrow().is_empty());
+
+        // Reloading applies to the receivers already handed out.
+        fs::write(&config, r#"{ "requests_per_minute": 20, "cors_origins": ["https://a.gg"] }"#)
+            .unwrap();
+        fs::write(&webhooks, r#"[{ "url": "http://localhost:8000/hook" }]"#).unwrap();
+        settings.reload().unwrap();
+        assert_eq!(runtime.borrow().requests_per_minute, 20);
+        assert!(runtime.borrow().allows_origin("https://a.gg"));
+        assert!(!runtime.borrow().allows_origin("https://b.gg"));
+        assert_eq!(settings.webhooks().borrow().len(), 1);
+
+        // Invalid settings are rejected as a whole, the previous ones are kept.
+        fs::write(&config, r#"{ "requests_per_minut": 30 }"#).unwrap();
+        fs::write(&webhooks, "[]").unwrap();
+        assert!(settings.reload().is_err());
+        assert_eq!(runtime.borrow().requests_per_minute, 20);
+        assert_eq!(settings.webhooks().borrow().len(), 1);
+
+        fs::remove_file(config).unwrap();
+        fs::remove_file(webhooks).unwrap();
+    }
+
+    #[test]
+    fn test_any_origin() {
+        let settings = RuntimeSettings { cors_origins: vec!["*".into()], ..Default::default() };
+        assert!(settings.allows_origin("https://a.gg"));
+        assert!(!RuntimeSettings::default().allows_origin("https://a.gg"));
+    }
+}
@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+    use starknet_crypto::verify;
+
+    use crate::graphql::signing::ResponseSigner;
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_sign_response(pool: SqlitePool) {
+        let signer = ResponseSigner::new(FieldElement::from(0x1234_u32), pool);
+        assert_eq!(signer.head().await.unwrap(), 0);
+
+        let request = br#"{"query":"{ entities { total_count } }"}"#;
+        let body = br#"{"data":{"entities":{"total_count":1}}}"#;
+        let (r, s) = signer.sign(request, body, 42).unwrap();
+        let hash = ResponseSigner::message_hash(request, body, 42);
+        assert!(verify(&signer.public_key(), &hash, &r, &s).unwrap());
+
+        // The signature covers the block the response was served at.
+        let other_block = ResponseSigner::message_hash(request, body, 43);
+        assert!(!verify(&signer.public_key(), &other_block, &r, &s).unwrap());
+
+        // And the request it answers.
+        let other_request = br#"{"query":"{ components { total_count } }"}"#;
+        let other_request = ResponseSigner::message_hash(other_request, body, 42);
+        assert!(!verify(&signer.public_key(), &other_request, &r, &s).unwrap());
+    }
+}
@@ -0,0 +1,68 @@
+#[cfg(test)]
+mod tests {
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+
+    use crate::storage::sql::SqlStorage;
+    use crate::storage::Storage;
+
+    async fn metrics(pool: &SqlitePool) -> Vec<(String, i64, String)> {
+        sqlx::query_as("SELECT system, calls, total_fee FROM system_metrics ORDER BY system")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_block_writes(pool: SqlitePool) {
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+        assert_eq!(storage.head().await.unwrap(), 0);
+
+        // The writes of a block are only visible once committed with the head.
+        storage.begin_block().await.unwrap();
+        storage.record_system_call("Move", FieldElement::from(30_u64), false).await.unwrap();
+        storage.record_system_call("Move", FieldElement::from(20_u64), true).await.unwrap();
+        assert!(metrics(&pool).await.is_empty());
+
+        storage.set_head(5).await.unwrap();
+        assert_eq!(storage.head().await.unwrap(), 5);
+        assert_eq!(metrics(&pool).await, vec![("Move".to_string(), 2, "0x32".to_string())]);
+
+        // A block interrupted before its commit leaves neither its writes nor the head.
+        storage.begin_block().await.unwrap();
+        storage.record_system_call("Spawn", FieldElement::from(10_u64), false).await.unwrap();
+        drop(storage);
+
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+        assert_eq!(storage.head().await.unwrap(), 5);
+        assert_eq!(metrics(&pool).await.len(), 1);
+
+        // Outside of a block, writes are applied right away.
+        storage.record_system_call("Spawn", FieldElement::from(10_u64), false).await.unwrap();
+        assert_eq!(metrics(&pool).await.len(), 2);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_in_block_reads(pool: SqlitePool) {
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+        let component = FieldElement::from(0x47616d65_u64);
+        let (partition, key) = (FieldElement::ZERO, FieldElement::ONE);
+
+        // Reads see the writes of the block before they're committed.
+        storage.begin_block().await.unwrap();
+        storage.set_entity(component, partition, key, vec![FieldElement::TWO]).await.unwrap();
+        assert_eq!(storage.entity(component, partition, key).await.unwrap(), [FieldElement::TWO]);
+        assert_eq!(storage.entities(component, partition).await.unwrap(), [[FieldElement::TWO]]);
+
+        let (tables,): (i64,) =
+            sqlx::query_as("SELECT COUNT(*) FROM sqlite_master WHERE name = $1")
+                .bind(component.to_string())
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(tables, 0);
+
+        storage.set_head(1).await.unwrap();
+        assert_eq!(storage.entity(component, partition, key).await.unwrap(), [FieldElement::TWO]);
+    }
+}
@@ -0,0 +1,179 @@
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_graphql::Response;
+    use serde::Deserialize;
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::cairo_short_string_to_felt;
+    use tokio_stream::{Stream, StreamExt};
+
+    use crate::graphql::schema::build_schema;
+    use crate::storage::sql::SqlStorage;
+    use crate::tests::common::{index_block, world_event};
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct EntityStateUpdate {
+        pub id: String,
+        pub entity_id: String,
+        pub component_name: String,
+    }
+
+    async fn next_update(stream: &mut (impl Stream<Item = Response> + Unpin)) -> EntityStateUpdate {
+        let res = stream.next().await.expect("subscription ended");
+        assert!(res.errors.is_empty(), "GraphQL subscription returned errors: {:?}", res.errors);
+        let value = serde_json::to_value(res.data).unwrap();
+        serde_json::from_value(value["entityStateUpdates"].clone()).unwrap()
+    }
+
+    async fn insert_update(pool: &SqlitePool, entity_id: &str, component_id: &str) {
+        sqlx::query(
+            "INSERT INTO entity_state_updates (entity_id, component_id, system_call_id, data) \
+             VALUES ($1, $2, 1, '0x1')",
+        )
+        .bind(entity_id)
+        .bind(component_id)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures("entities", "components", "systems", "system_calls", "entity_state_updates")
+    )]
+    async fn test_subscription_replays_missed_updates(pool: SqlitePool) {
+        let schema = build_schema(&pool, None).await.unwrap();
+        let mut stream = Box::pin(schema.execute_stream(
+            "subscription { entityStateUpdates(cursor: \"1\") { id entityId componentName } }",
+        ));
+
+        // updates indexed after the cursor while disconnected
+        assert_eq!(next_update(&mut stream).await.id, "2");
+        assert_eq!(next_update(&mut stream).await.id, "3");
+
+        // then the live ones
+        insert_update(&pool, "entity_1", "component_1").await;
+        let update = next_update(&mut stream).await;
+        assert_eq!(update.id, "4");
+        assert_eq!(update.entity_id, "entity_1");
+        assert_eq!(update.component_name, "Game");
+    }
+
+    #[sqlx::test(
+        migrations = "./migrations",
+        fixtures("entities", "components", "systems", "system_calls", "entity_state_updates")
+    )]
+    async fn test_subscription_without_cursor_streams_live_updates(pool: SqlitePool) {
+        let schema = build_schema(&pool, None).await.unwrap();
+        let mut stream = Box::pin(schema.execute_stream(
+            "subscription { entityStateUpdates(componentName: \"Stats\") { id entityId \
+             componentName } }",
+        ));
+
+        // indexed once the subscription has started
+        let indexer = pool.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            insert_update(&indexer, "entity_1", "component_1").await;
+            insert_update(&indexer, "entity_2", "component_2").await;
+        });
+
+        let update = next_update(&mut stream).await;
+        assert_eq!(update.id, "5");
+        assert_eq!(update.component_name, "Stats");
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_subscription_replays_indexed_updates(pool: SqlitePool) {
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+        let position = cairo_short_string_to_felt("Position").unwrap();
+        let record = |data: &[u64]| {
+            [vec![position], data.iter().map(|v| FieldElement::from(*v)).collect()].concat()
+        };
+
+        // indexed while the client is disconnected, after it received the first update
+        let events = [
+            world_event("StoreSetRecord", record(&[1, 1, 2, 2, 3])),
+            world_event("StoreSetRecord", record(&[1, 2, 2, 4, 5])),
+        ];
+        index_block(&storage, 1, &events).await;
+
+        let schema = build_schema(&pool, None).await.unwrap();
+        let mut stream = Box::pin(schema.execute_stream(
+            "subscription { entityStateUpdates(cursor: \"1\") { id entityId componentName } }",
+        ));
+
+        let update = next_update(&mut stream).await;
+        assert_eq!(update.id, "2");
+        assert_eq!(update.entity_id, "0x2");
+
+        // then the live ones
+        index_block(&storage, 2, &[world_event("StoreDeleteRecord", record(&[1, 1]))]).await;
+        let update = next_update(&mut stream).await;
+        assert_eq!(update.id, "3");
+        assert_eq!(update.entity_id, "0x1");
+        assert_eq!(update.component_name, "Position");
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct FieldUpdate {
+        pub id: String,
+        pub value: Option<String>,
+        pub previous_value: Option<String>,
+    }
+
+    async fn next_field_update(stream: &mut (impl Stream<Item = Response> + Unpin)) -> FieldUpdate {
+        let res = stream.next().await.expect("subscription ended");
+        assert!(res.errors.is_empty(), "GraphQL subscription returned errors: {:?}", res.errors);
+        let value = serde_json::to_value(res.data).unwrap();
+        serde_json::from_value(value["componentFieldUpdates"].clone()).unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "components"))]
+    async fn test_field_subscription_skips_unchanged_values(pool: SqlitePool) {
+        let schema = build_schema(&pool, None).await.unwrap();
+        let mut stream = Box::pin(schema.execute_stream(
+            "subscription { componentFieldUpdates(componentName: \"Stats\", field: \"health\", \
+             entityId: \"entity_2\", cursor: \"0\") { id value previousValue } }",
+        ));
+
+        for (health, mana) in [(42, 70), (40, 70)] {
+            sqlx::query(
+                "INSERT INTO storage_stats (health, mana, version, entity_id, component_id) \
+                 VALUES ($1, $2, '0.0.0', 'entity_2', 'component_2')",
+            )
+            .bind(health)
+            .bind(mana)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let update = next_field_update(&mut stream).await;
+        assert_eq!(update.id, "1");
+        assert_eq!(update.value.as_deref(), Some("42"));
+        assert_eq!(update.previous_value, None);
+
+        // the write of the mana alone isn't streamed
+        let update = next_field_update(&mut stream).await;
+        assert_eq!(update.id, "3");
+        assert_eq!(update.value.as_deref(), Some("40"));
+        assert_eq!(update.previous_value.as_deref(), Some("42"));
+    }
+
+    #[sqlx::test(migrations = "./migrations", fixtures("entities", "components"))]
+    async fn test_field_subscription_rejects_unknown_members(pool: SqlitePool) {
+        let schema = build_schema(&pool, None).await.unwrap();
+        let mut stream = Box::pin(schema.execute_stream(
+            "subscription { componentFieldUpdates(componentName: \"Stats\", field: \"id\") { id \
+             } }",
+        ));
+
+        let res = stream.next().await.expect("subscription ended");
+        assert!(!res.errors.is_empty());
+    }
+}
@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::{cairo_short_string_to_felt, get_selector_from_name};
+
+    use crate::processors::system_metrics::SystemMetricsProcessor;
+    use crate::storage::sql::SqlStorage;
+    use crate::storage::Storage;
+    use crate::tests::common::run_graphql_query;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SystemMetrics {
+        pub system: String,
+        pub calls: i64,
+        pub failures: i64,
+        pub failure_rate: f64,
+        pub total_fee: String,
+        pub average_fee: String,
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_system_metrics(pool: SqlitePool) {
+        let storage = SqlStorage::new(pool.clone()).unwrap();
+        storage.record_system_call("Spawn", FieldElement::from(100_u64), false).await.unwrap();
+        storage.record_system_call("Move", FieldElement::from(30_u64), false).await.unwrap();
+        storage.record_system_call("Move", FieldElement::from(20_u64), true).await.unwrap();
+
+        let query = "{ systemMetrics { system calls failures failureRate totalFee averageFee } }";
+        let value = run_graphql_query(&pool, query).await;
+
+        let metrics = value.get("systemMetrics").ok_or("no metrics").unwrap();
+        let metrics: Vec<SystemMetrics> = serde_json::from_value(metrics.clone()).unwrap();
+        assert_eq!(metrics.len(), 2);
+        assert_eq!(metrics[0].system, "Move");
+        assert_eq!(metrics[0].calls, 2);
+        assert_eq!(metrics[0].failures, 1);
+        assert_eq!(metrics[0].failure_rate, 0.5);
+        assert_eq!(metrics[0].total_fee, "0x32");
+        assert_eq!(metrics[0].average_fee, "0x19");
+        assert_eq!(metrics[1].system, "Spawn");
+        assert_eq!(metrics[1].failures, 0);
+    }
+
+    #[test]
+    fn test_executed_systems() {
+        let world = FieldElement::from(0x420_u64);
+        let execute = get_selector_from_name("execute").unwrap();
+        let other = get_selector_from_name("transfer").unwrap();
+        let spawn = cairo_short_string_to_felt("Spawn").unwrap();
+        let move_ = cairo_short_string_to_felt("Move").unwrap();
+
+        // execute Spawn, transfer on another contract, execute Move
+        let calldata = [
+            vec![FieldElement::from(3_u8)],
+            vec![world, execute, FieldElement::ZERO, FieldElement::from(2_u8)],
+            vec![FieldElement::ONE, other, FieldElement::from(2_u8), FieldElement::ONE],
+            vec![world, execute, FieldElement::from(3_u8), FieldElement::from(3_u8)],
+            vec![FieldElement::from(6_u8), spawn, FieldElement::ZERO, FieldElement::ONE],
+            vec![move_, FieldElement::ONE, FieldElement::ONE],
+        ]
+        .concat();
+
+        let processor = SystemMetricsProcessor::new(world);
+        assert_eq!(processor.executed_systems(&calldata), vec!["Spawn", "Move"]);
+        assert!(processor.executed_systems(&[FieldElement::from(5_u8)]).is_empty());
+    }
+}
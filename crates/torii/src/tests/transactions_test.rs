@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use sqlx::SqlitePool;
+    use starknet::core::types::FieldElement;
+    use starknet::core::utils::get_selector_from_name;
+    use starknet::providers::jsonrpc::HttpTransport;
+    use starknet::providers::JsonRpcClient;
+
+    use crate::graphql::object::transaction::{system_call_calldata, Relay};
+    use crate::graphql::schema::{build_schema, schema_builder};
+
+    const MUTATIONS_QUERY: &str = "{ __schema { mutationType { fields { name } } } }";
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn test_execute_mutation(pool: SqlitePool) {
+        // Only exposed when relaying to a node.
+        let schema = build_schema(&pool, None).await.unwrap();
+        let value = serde_json::to_value(schema.execute(MUTATIONS_QUERY).await.data).unwrap();
+        assert!(value["__schema"]["mutationType"].is_null());
+
+        let relay = Relay {
+            provider: Arc::new(JsonRpcClient::new(HttpTransport::new(
+                "http://localhost:5050".parse().unwrap(),
+            ))),
+            world_address: FieldElement::from(0x420_u64),
+        };
+        let schema = schema_builder(&pool, None, Some(relay)).await.unwrap().finish().unwrap();
+        let value = serde_json::to_value(schema.execute(MUTATIONS_QUERY).await.data).unwrap();
+        assert_eq!(value["__schema"]["mutationType"]["fields"][0]["name"], "execute");
+    }
+
+    #[test]
+    fn test_system_call_calldata() {
+        let world = FieldElement::from(0x420_u64);
+        let system = FieldElement::from(0x5_u8);
+        let calldata = system_call_calldata(world, system, vec![FieldElement::ONE]);
+
+        assert_eq!(
+            calldata,
+            vec![
+                FieldElement::ONE,
+                world,
+                get_selector_from_name("execute").unwrap(),
+                FieldElement::ZERO,
+                FieldElement::from(3_u8),
+                FieldElement::from(3_u8),
+                system,
+                FieldElement::ONE,
+                FieldElement::ONE,
+            ]
+        );
+    }
+}
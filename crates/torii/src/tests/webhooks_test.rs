@@ -0,0 +1,97 @@
+#[cfg(test)]
+mod tests {
+    use starknet::core::types::FieldElement;
+
+    use crate::storage::memory::MemoryStorage;
+    use crate::storage::Storage;
+    use crate::webhooks::{
+        sign, Notification, StateUpdate, WebhookConfig, WebhookStorage, Webhooks,
+    };
+
+    fn notification(component: &str, key: u8, system: Option<&str>) -> Notification {
+        Notification {
+            timestamp: 0,
+            system: system.map(String::from),
+            update: StateUpdate::EntitySet {
+                component: component.into(),
+                partition: FieldElement::ZERO,
+                key: FieldElement::from(key),
+                values: vec![FieldElement::ONE],
+            },
+        }
+    }
+
+    fn webhook(components: &[&str], systems: &[&str], keys: &[u8]) -> WebhookConfig {
+        WebhookConfig {
+            url: "http://localhost:8000/hook".parse().unwrap(),
+            components: components.iter().map(|c| c.to_string()).collect(),
+            systems: systems.iter().map(|s| s.to_string()).collect(),
+            keys: keys.iter().map(|k| FieldElement::from(*k)).collect(),
+            secret: None,
+        }
+    }
+
+    #[test]
+    fn test_webhook_filters() {
+        assert!(webhook(&[], &[], &[]).matches(&notification("Position", 1, None)));
+
+        let by_component = webhook(&["Position"], &[], &[]);
+        assert!(by_component.matches(&notification("Position", 1, None)));
+        assert!(!by_component.matches(&notification("Moves", 1, None)));
+
+        let by_key = webhook(&["Position"], &[], &[2]);
+        assert!(by_key.matches(&notification("Position", 2, None)));
+        assert!(!by_key.matches(&notification("Position", 1, None)));
+
+        let by_system = webhook(&[], &["Move"], &[]);
+        assert!(by_system.matches(&notification("Position", 1, Some("Move"))));
+        assert!(!by_system.matches(&notification("Position", 1, Some("Spawn"))));
+        assert!(!by_system.matches(&notification("Position", 1, None)));
+    }
+
+    #[test]
+    fn test_webhook_payload() {
+        let payload = serde_json::to_value(notification("Position", 1, None)).unwrap();
+        assert_eq!(payload["type"], "entity_set");
+        assert_eq!(payload["component"], "Position");
+        assert_eq!(payload["timestamp"], 0);
+    }
+
+    #[test]
+    fn test_webhook_signature() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_storage_notifies_committed_updates() {
+        let (webhooks, mut receiver) = Webhooks::new();
+        let storage = WebhookStorage::new(MemoryStorage::default(), webhooks);
+        let component = FieldElement::from(0x506f736974696f6e_u64);
+
+        storage.begin_block().await.unwrap();
+        storage.begin_system_call(FieldElement::ONE, Some("Move"), &[]).await.unwrap();
+        storage
+            .set_entity(component, FieldElement::ZERO, FieldElement::TWO, vec![FieldElement::ONE])
+            .await
+            .unwrap();
+        storage.begin_system_call(FieldElement::TWO, None, &[]).await.unwrap();
+        storage.delete_entity(component, FieldElement::ZERO, FieldElement::TWO).await.unwrap();
+        assert!(receiver.try_recv().is_err());
+
+        storage.set_head(1).await.unwrap();
+        let set = receiver.try_recv().unwrap();
+        assert_eq!(set.system.as_deref(), Some("Move"));
+        let StateUpdate::EntitySet { component, .. } = set.update else {
+            panic!("expected the entity to be set first");
+        };
+        assert_eq!(component, "Position");
+        let deleted = receiver.try_recv().unwrap();
+        assert_eq!(deleted.system, None);
+        assert!(matches!(deleted.update, StateUpdate::EntityDeleted { .. }));
+        assert!(receiver.try_recv().is_err());
+    }
+}
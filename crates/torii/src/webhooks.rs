@@ -0,0 +1,316 @@
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use starknet::core::types::FieldElement;
+use starknet::core::utils::parse_cairo_short_string;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+use url::Url;
+
+use crate::storage::{AuthorizationChange, Storage};
+
+/// Number of delivery attempts before a notification is dropped.
+const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled after every failed attempt.
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// Header carrying the hex encoded HMAC-SHA256 of the request body.
+pub const SIGNATURE_HEADER: &str = "X-Torii-Signature";
+
+/// An endpoint to notify of indexed state changes. Empty filters match every update.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookConfig {
+    pub url: Url,
+    /// Names of the components to notify updates of.
+    #[serde(default)]
+    pub components: Vec<String>,
+    /// Names of the systems to notify updates of. Only updates whose originating system is known
+    /// can match.
+    #[serde(default)]
+    pub systems: Vec<String>,
+    /// Entity keys to notify updates of.
+    #[serde(default)]
+    pub keys: Vec<FieldElement>,
+    /// Secret used to sign the payloads, sent in the `X-Torii-Signature` header.
+    pub secret: Option<String>,
+}
+
+impl WebhookConfig {
+    pub fn matches(&self, notification: &Notification) -> bool {
+        let (component, key) = match &notification.update {
+            StateUpdate::EntitySet { component, key, .. }
+            | StateUpdate::EntityDeleted { component, key, .. } => (component, key),
+        };
+
+        (self.components.is_empty() || self.components.contains(component))
+            && (self.keys.is_empty() || self.keys.contains(key))
+            && (self.systems.is_empty()
+                || notification.system.as_ref().map_or(false, |s| self.systems.contains(s)))
+    }
+}
+
+/// Loads the webhooks from a JSON file containing an array of [`WebhookConfig`].
+pub fn load_webhooks(path: impl AsRef<Path>) -> Result<Vec<WebhookConfig>> {
+    let file = std::fs::File::open(path)?;
+    Ok(serde_json::from_reader(file)?)
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateUpdate {
+    EntitySet {
+        component: String,
+        partition: FieldElement,
+        key: FieldElement,
+        values: Vec<FieldElement>,
+    },
+    EntityDeleted {
+        component: String,
+        partition: FieldElement,
+        key: FieldElement,
+    },
+}
+
+/// Payload posted to the webhooks.
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub timestamp: i64,
+    pub system: Option<String>,
+    #[serde(flatten)]
+    pub update: StateUpdate,
+}
+
+/// Handle to queue notifications for delivery, cheap to clone.
+#[derive(Debug, Clone)]
+pub struct Webhooks {
+    sender: UnboundedSender<Notification>,
+}
+
+impl Webhooks {
+    pub fn new() -> (Self, UnboundedReceiver<Notification>) {
+        let (sender, receiver) = unbounded_channel();
+        (Self { sender }, receiver)
+    }
+
+    pub fn notify(&self, system: Option<String>, update: StateUpdate) {
+        let notification = Notification { timestamp: Utc::now().timestamp(), system, update };
+        if self.sender.send(notification).is_err() {
+            warn!("webhook dispatcher stopped, dropping notification");
+        }
+    }
+}
+
+/// Delivers the queued notifications to the matching webhooks until the token is cancelled.
+/// Every delivery runs in its own task so a slow endpoint doesn't hold back the others. The
+/// webhooks are read for every notification, so reloading them applies to the next one.
+pub async fn start_webhooks(
+    ct: CancellationToken,
+    webhooks: watch::Receiver<Vec<WebhookConfig>>,
+    mut receiver: UnboundedReceiver<Notification>,
+) -> Result<()> {
+    info!("starting webhook dispatcher for {} endpoints", webhooks.borrow().len());
+
+    let client = reqwest::Client::new();
+
+    loop {
+        let notification = tokio::select! {
+            _ = ct.cancelled() => return Ok(()),
+            notification = receiver.recv() => match notification {
+                Some(notification) => notification,
+                None => return Ok(()),
+            },
+        };
+
+        let body = serde_json::to_vec(&notification)?;
+        let matching = webhooks
+            .borrow()
+            .iter()
+            .filter(|w| w.matches(&notification))
+            .cloned()
+            .collect::<Vec<_>>();
+        for webhook in matching {
+            tokio::spawn(deliver(client.clone(), webhook, body.clone()));
+        }
+    }
+}
+
+async fn deliver(client: reqwest::Client, webhook: WebhookConfig, body: Vec<u8>) {
+    let mut delay = INITIAL_RETRY_DELAY;
+
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        let mut request = client
+            .post(webhook.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.clone());
+        if let Some(secret) = &webhook.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+        }
+
+        match request.send().await.and_then(|res| res.error_for_status()) {
+            Ok(_) => return,
+            Err(e) if attempt < MAX_DELIVERY_ATTEMPTS => {
+                warn!("webhook {} failed (attempt {attempt}), retrying: {e}", webhook.url);
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            Err(e) => error!("webhook {} failed, giving up: {e}", webhook.url),
+        }
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of the body, prefixed with the algorithm.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(body);
+
+    let digest = mac.finalize().into_bytes();
+    format!("sha256={}", digest.iter().map(|b| format!("{b:02x}")).collect::<String>())
+}
+
+/// Storage notifying the webhooks of the entity updates written through it, attributed to the
+/// system of the call that wrote them. The updates of a block are only notified once it's
+/// committed.
+pub struct WebhookStorage<S: Storage> {
+    inner: S,
+    webhooks: Webhooks,
+    /// System of the current call, `None` outside of a call or when it's unknown.
+    system: Mutex<Option<String>>,
+    /// Updates of the current block, `None` outside of a block.
+    block: Mutex<Option<Vec<(Option<String>, StateUpdate)>>>,
+}
+
+impl<S: Storage> WebhookStorage<S> {
+    pub fn new(inner: S, webhooks: Webhooks) -> Self {
+        Self { inner, webhooks, system: Mutex::new(None), block: Mutex::new(None) }
+    }
+
+    /// Queues the update until the block is committed, or notifies it right away outside of a
+    /// block.
+    fn notify(&self, update: StateUpdate) {
+        let system = self.system.lock().expect("system lock poisoned").clone();
+        match self.block.lock().expect("block lock poisoned").as_mut() {
+            Some(updates) => updates.push((system, update)),
+            None => self.webhooks.notify(system, update),
+        }
+    }
+}
+
+pub(crate) fn component_name(component: FieldElement) -> String {
+    parse_cairo_short_string(&component).unwrap_or_else(|_| format!("{component:#x}"))
+}
+
+#[async_trait]
+impl<S: Storage + Send + Sync> Storage for WebhookStorage<S> {
+    async fn head(&self) -> Result<u64> {
+        self.inner.head().await
+    }
+
+    async fn begin_block(&self) -> Result<()> {
+        self.inner.begin_block().await?;
+        *self.block.lock().expect("block lock poisoned") = Some(vec![]);
+        *self.system.lock().expect("system lock poisoned") = None;
+        Ok(())
+    }
+
+    async fn set_head(&self, head: u64) -> Result<()> {
+        // the updates of a block that fails to commit are dropped, it's indexed again
+        let updates = self.block.lock().expect("block lock poisoned").take().unwrap_or_default();
+        *self.system.lock().expect("system lock poisoned") = None;
+
+        self.inner.set_head(head).await?;
+        for (system, update) in updates {
+            self.webhooks.notify(system, update);
+        }
+        Ok(())
+    }
+
+    async fn begin_system_call(
+        &self,
+        transaction_hash: FieldElement,
+        system: Option<&str>,
+        calldata: &[FieldElement],
+    ) -> Result<()> {
+        self.inner.begin_system_call(transaction_hash, system, calldata).await?;
+        *self.system.lock().expect("system lock poisoned") = system.map(String::from);
+        Ok(())
+    }
+
+    async fn create_component(&self, name: FieldElement, columns: Vec<FieldElement>) -> Result<()> {
+        self.inner.create_component(name, columns).await
+    }
+
+    async fn set_entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+        values: Vec<FieldElement>,
+    ) -> Result<()> {
+        self.inner.set_entity(component, partition, key, values.clone()).await?;
+        self.notify(StateUpdate::EntitySet {
+            component: component_name(component),
+            partition,
+            key,
+            values,
+        });
+        Ok(())
+    }
+
+    async fn delete_entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+    ) -> Result<()> {
+        self.inner.delete_entity(component, partition, key).await?;
+        self.notify(StateUpdate::EntityDeleted {
+            component: component_name(component),
+            partition,
+            key,
+        });
+        Ok(())
+    }
+
+    async fn entity(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+        key: FieldElement,
+    ) -> Result<Vec<FieldElement>> {
+        self.inner.entity(component, partition, key).await
+    }
+
+    async fn entities(
+        &self,
+        component: FieldElement,
+        partition: FieldElement,
+    ) -> Result<Vec<Vec<FieldElement>>> {
+        self.inner.entities(component, partition).await
+    }
+
+    async fn record_system_call(
+        &self,
+        system: &str,
+        fee: FieldElement,
+        failed: bool,
+    ) -> Result<()> {
+        self.inner.record_system_call(system, fee, failed).await
+    }
+
+    async fn apply_authorization(
+        &self,
+        change: &AuthorizationChange,
+        transaction_hash: FieldElement,
+    ) -> Result<()> {
+        self.inner.apply_authorization(change, transaction_hash).await
+    }
+}
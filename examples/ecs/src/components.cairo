@@ -46,3 +46,9 @@ fn test_position_is_equal() {
         'not equal'
     );
 }
+
+#[fuzz]
+#[available_gas(100000)]
+fn test_position_equals_itself(x: u32, y: u32) {
+    assert(PositionTrait::is_equal(Position { x, y }, Position { x, y }), 'not equal');
+}